@@ -0,0 +1,96 @@
+//! Generates deterministic JSON test vectors for ECDSA and ECDSA adaptor signatures.
+//!
+//! Every key and message is derived from a seed by hashing so the output is the same every time.
+//!
+//! ```text
+//! cargo run -p ecdsa_fun --features adaptor,serde --example vectors [seed] > vectors.json
+//! ```
+//!
+//! The Schnorr vectors are generated by the `vectors` example in `schnorr_fun`.
+use ecdsa_fun::{
+    adaptor::{Adaptor, HashTranscript},
+    fun::{hash::HashAdd, hex, Scalar},
+    nonce::Deterministic,
+};
+use rand_chacha::ChaCha20Rng;
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+/// How many vectors to generate for each scheme.
+const COUNT: u32 = 3;
+
+/// Derives the `i`th secret scalar labelled `label` from `seed`.
+fn scalar(seed: &str, label: &str, i: u32) -> Scalar {
+    Scalar::from_hash(
+        Sha256::default()
+            .add(seed.as_bytes())
+            .add(label)
+            .add(i.to_be_bytes()),
+    )
+}
+
+/// Derives the `i`th 32-byte message hash labelled `label` from `seed`.
+fn message(seed: &str, label: &str, i: u32) -> [u8; 32] {
+    scalar(seed, label, i).to_bytes()
+}
+
+fn main() {
+    let seed = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "secp256kfun-test-vectors".into());
+    let adaptor =
+        Adaptor::<HashTranscript<Sha256, ChaCha20Rng>, _>::new(Deterministic::<Sha256>::default());
+    let ecdsa = &adaptor.ecdsa;
+
+    let ecdsa_vectors = (0..COUNT)
+        .map(|i| {
+            let secret_key = scalar(&seed, "ecdsa/secret-key", i);
+            let verification_key = ecdsa.verification_key_for(&secret_key);
+            let message_hash = message(&seed, "ecdsa/message", i);
+            let signature = ecdsa.sign(&secret_key, &message_hash);
+            assert!(ecdsa.verify(&verification_key, &message_hash, &signature));
+            json!({
+                "secret_key": secret_key.to_string(),
+                "verification_key": verification_key.to_string(),
+                "message_hash": hex::encode(&message_hash),
+                "signature": signature.to_string(),
+            })
+        })
+        .collect::<Value>();
+
+    let adaptor_vectors = (0..COUNT)
+        .map(|i| {
+            let secret_key = scalar(&seed, "ecdsa-adaptor/secret-key", i);
+            let verification_key = ecdsa.verification_key_for(&secret_key);
+            let decryption_key = scalar(&seed, "ecdsa-adaptor/decryption-key", i);
+            let encryption_key = adaptor.encryption_key_for(&decryption_key);
+            let message_hash = message(&seed, "ecdsa-adaptor/message", i);
+            let encrypted_signature =
+                adaptor.encrypted_sign(&secret_key, &encryption_key, &message_hash);
+            assert!(adaptor.verify_encrypted_signature(
+                &verification_key,
+                &encryption_key,
+                &message_hash,
+                &encrypted_signature
+            ));
+            let signature = adaptor.decrypt_signature(&decryption_key, encrypted_signature.clone());
+            assert!(ecdsa.verify(&verification_key, &message_hash, &signature));
+            json!({
+                "secret_key": secret_key.to_string(),
+                "verification_key": verification_key.to_string(),
+                "decryption_key": decryption_key.to_string(),
+                "encryption_key": encryption_key.to_string(),
+                "message_hash": hex::encode(&message_hash),
+                "encrypted_signature": encrypted_signature.to_string(),
+                "signature": signature.to_string(),
+            })
+        })
+        .collect::<Value>();
+
+    let vectors = json!({
+        "seed": seed,
+        "ecdsa": ecdsa_vectors,
+        "ecdsa_adaptor": adaptor_vectors,
+    });
+    println!("{}", serde_json::to_string_pretty(&vectors).unwrap());
+}
@@ -5,10 +5,14 @@
 #[cfg(all(feature = "alloc", not(feature = "std")))]
 #[macro_use]
 extern crate alloc;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+pub(crate) use alloc::vec::Vec;
 
 #[cfg(feature = "std")]
 #[macro_use]
 extern crate std;
+#[cfg(feature = "std")]
+pub(crate) use std::vec::Vec;
 
 #[cfg(feature = "libsecp_compat")]
 mod libsecp_compat;
@@ -28,6 +32,9 @@ mod signature;
 pub use signature::Signature;
 #[cfg(feature = "adaptor")]
 pub mod adaptor;
+#[cfg(feature = "jwt")]
+pub mod jwt;
+pub mod recovery;
 
 /// An instance of the ECDSA signature scheme.
 #[derive(Default, Clone, Debug)]
@@ -95,6 +102,35 @@ impl<NG> ECDSA<NG> {
     }
 }
 
+#[cfg(feature = "alloc")]
+impl<H: Default> ECDSA<nonce::Rfc6979<H>> {
+    /// Creates an ECDSA instance that generates nonces exactly as [RFC6979] does.
+    ///
+    /// Unlike [`new`](ECDSA::new) this doesn't tag the nonce generator so with `H` set to SHA-256
+    /// it produces the same signatures as other RFC6979 implementations.
+    ///
+    /// # Example
+    /// ```
+    /// use ecdsa_fun::{fun::Scalar, ECDSA};
+    /// use sha2::Sha256;
+    /// let ecdsa = ECDSA::<ecdsa_fun::nonce::Rfc6979<Sha256>>::rfc6979();
+    /// let secret_key = Scalar::random(&mut rand::thread_rng());
+    /// let message = [42u8; 32];
+    /// assert_eq!(
+    ///     ecdsa.sign(&secret_key, &message),
+    ///     ecdsa.sign(&secret_key, &message)
+    /// );
+    /// ```
+    ///
+    /// [RFC6979]: https://datatracker.ietf.org/doc/html/rfc6979
+    pub fn rfc6979() -> Self {
+        ECDSA {
+            nonce_gen: nonce::Rfc6979::default(),
+            enforce_low_s: false,
+        }
+    }
+}
+
 impl<NG> ECDSA<NG> {
     /// Get the corresponding verification key for a secret key
     ///
@@ -126,7 +162,7 @@ impl<NG> ECDSA<NG> {
         let s_inv = s.invert();
 
         g!((s_inv * m) * G + (s_inv * R_x) * verification_key)
-            .mark::<NonZero>()
+            .non_zero()
             .map_or(false, |implied_R| implied_R.x_eq_scalar(R_x))
     }
 }
@@ -163,6 +199,14 @@ impl<NG: NonceGen> ECDSA<NG> {
     /// assert!(ecdsa.verify(&verification_key, &message_hash, &signature));
     /// ```
     pub fn sign(&self, secret_key: &Scalar, message_hash: &[u8; 32]) -> Signature {
+        self._sign(secret_key, message_hash).0
+    }
+
+    pub(crate) fn _sign(
+        &self,
+        secret_key: &Scalar,
+        message_hash: &[u8; 32],
+    ) -> (Signature, recovery::RecoveryId) {
         let x = secret_key;
         let m = Scalar::from_bytes_mod_order(message_hash.clone()).mark::<Public>();
         let r = derive_nonce!(
@@ -190,12 +234,21 @@ impl<NG: NonceGen> ECDSA<NG> {
 
         // s values must be low (less than half group order), otherwise signatures
         // would be malleable i.e. (R,s) and (R,-s) would both be valid signatures.
-        s.conditional_negate(s.is_high());
+        let is_high = s.is_high();
+        s.conditional_negate(is_high);
 
-        Signature {
-            R_x,
-            s: s.mark::<Public>(),
-        }
+        // negating s is the same as having used -R as the nonce
+        let recovery_id = recovery::RecoveryId(
+            (R.is_y_odd() ^ is_high) as u8 | ((R_x.to_bytes() != R.x_bytes()) as u8) << 1,
+        );
+
+        (
+            Signature {
+                R_x,
+                s: s.mark::<Public>(),
+            },
+            recovery_id,
+        )
     }
 }
 
@@ -226,6 +279,29 @@ mod test {
         }
     }
 
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn rfc6979_nonces() {
+        use sha2::{Digest, Sha256};
+        let ecdsa = ECDSA::<nonce::Rfc6979<Sha256>>::rfc6979();
+        let secret_key = Scalar::one();
+        let message_hash: [u8; 32] = Sha256::digest(b"Satoshi Nakamoto").into();
+        let signature = ecdsa.sign(&secret_key, &message_hash);
+        let k = Scalar::<Secret, Zero>::from_bytes(
+            secp256kfun::hex::decode_array(
+                "8f8a276c19f4149656b280621e358cce24f5f52542772691ee69063b74f15d15",
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .expect_nonzero("not zero");
+        let R = g!(k * G).normalize();
+        assert_eq!(
+            signature.R_x,
+            Scalar::from_bytes_mod_order(R.x_bytes()).mark::<Public>()
+        );
+    }
+
     #[test]
     fn low_s() {
         let ecdsa_enforce_low_s = test_instance!().enforce_low_s();
@@ -241,6 +317,12 @@ mod test {
             sig.s = -sig.s;
             assert!(!ecdsa_enforce_low_s.verify(&public_key, &message, &sig));
             assert!(ecdsa.verify(&public_key, &message, &sig));
+            assert!(!sig.is_low_s());
+            assert!(!Signature::is_canonical(&sig.to_bytes()));
+            sig.normalize_s();
+            assert!(sig.is_low_s());
+            assert!(Signature::is_canonical(&sig.to_bytes()));
+            assert!(ecdsa_enforce_low_s.verify(&public_key, &message, &sig));
         }
     }
 }
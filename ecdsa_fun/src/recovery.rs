@@ -0,0 +1,276 @@
+//! Public key recovery from ECDSA signatures.
+//!
+//! Given a message hash, a signature and a two bit _recovery id_ you can recover the verification
+//! key that produced the signature. Systems like Ethereum transmit `(signature, recovery_id)`
+//! instead of the public key and treat a successful recovery as verification.
+//!
+//! # Synopsis
+//!
+//! ```
+//! use ecdsa_fun::{fun::Scalar, nonce, ECDSA};
+//! use sha2::Sha256;
+//! let ecdsa = ECDSA::new(nonce::Deterministic::<Sha256>::default());
+//! let secret_key = Scalar::random(&mut rand::thread_rng());
+//! let message_hash = [42u8; 32];
+//! let (signature, recovery_id) = ecdsa.sign_recoverable(&secret_key, &message_hash);
+//! assert_eq!(
+//!     ecdsa.recover(&message_hash, &signature, recovery_id),
+//!     Some(ecdsa.verification_key_for(&secret_key))
+//! );
+//! ```
+use crate::{
+    fun::{g, marker::*, FieldElement, Point, Scalar, G},
+    nonce::NonceGen,
+    Signature, ECDSA,
+};
+
+/// The big-endian encoding of the curve order.
+const CURVE_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+/// Identifies which of the (up to) four candidate nonces a signature was made with.
+///
+/// The lowest bit is the parity of the nonce's y-coordinate and the second bit is set if the
+/// nonce's x-coordinate was at least the curve order (this happens with negligible probability).
+/// This is the same as the `v - 27` or `recid` of other libraries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RecoveryId(pub(crate) u8);
+
+impl RecoveryId {
+    /// Creates a recovery id from an integer in `0..4`.
+    pub fn from_u8(id: u8) -> Option<Self> {
+        if id < 4 {
+            Some(RecoveryId(id))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the recovery id as an integer in `0..4`.
+    pub fn to_u8(self) -> u8 {
+        self.0
+    }
+
+    /// Whether the nonce had an odd y-coordinate.
+    pub fn is_y_odd(self) -> bool {
+        self.0 & 0x01 == 0x01
+    }
+
+    /// Whether the nonce's x-coordinate was reduced modulo the curve order.
+    pub fn is_x_reduced(self) -> bool {
+        self.0 & 0x02 == 0x02
+    }
+}
+
+impl<NG: NonceGen> ECDSA<NG> {
+    /// Signs like [`sign`] but also returns the [`RecoveryId`] needed to recover the verification
+    /// key from the signature.
+    ///
+    /// [`sign`]: ECDSA::sign
+    pub fn sign_recoverable(
+        &self,
+        secret_key: &Scalar,
+        message_hash: &[u8; 32],
+    ) -> (Signature, RecoveryId) {
+        self._sign(secret_key, message_hash)
+    }
+}
+
+impl<NG> ECDSA<NG> {
+    /// Recovers the verification key that produced `signature` on `message_hash`.
+    ///
+    /// Returns `None` if there is no such key. If this returns a key then `signature` is valid
+    /// under it. If the instance [enforces low s] then high s signatures are rejected.
+    ///
+    /// [enforces low s]: ECDSA::enforce_low_s
+    pub fn recover(
+        &self,
+        message_hash: &[u8; 32],
+        signature: &Signature,
+        recovery_id: RecoveryId,
+    ) -> Option<Point> {
+        let r_inv = signature.R_x.invert();
+        self.recover_with_r_inv(message_hash, signature, recovery_id, &r_inv)
+    }
+
+    /// Recovers the verification keys for many signatures.
+    ///
+    /// The result is the same as calling [`recover`] on each item. This is mostly a convenience
+    /// loop: every key is a different point so there is no multi-scalar multiplication to share
+    /// and each item still costs the same point multiplication as [`recover`]. The only work it
+    /// saves is inverting each signature's `R_x`. These are shared using Montgomery's trick so
+    /// all of them cost one inversion and three multiplications each.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ecdsa_fun::{fun::Scalar, nonce, ECDSA};
+    /// use sha2::Sha256;
+    /// let ecdsa = ECDSA::new(nonce::Deterministic::<Sha256>::default());
+    /// let secret_keys = (0..10)
+    ///     .map(|_| Scalar::random(&mut rand::thread_rng()))
+    ///     .collect::<Vec<_>>();
+    /// let items = secret_keys
+    ///     .iter()
+    ///     .map(|secret_key| {
+    ///         let message_hash = [7u8; 32];
+    ///         let (signature, recovery_id) = ecdsa.sign_recoverable(secret_key, &message_hash);
+    ///         (message_hash, signature, recovery_id)
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// let recovered = ecdsa.recover_batch(&items);
+    /// for (secret_key, recovered) in secret_keys.iter().zip(recovered) {
+    ///     assert_eq!(recovered, Some(ecdsa.verification_key_for(secret_key)));
+    /// }
+    /// ```
+    ///
+    /// [`recover`]: ECDSA::recover
+    #[cfg(feature = "alloc")]
+    pub fn recover_batch(
+        &self,
+        items: &[([u8; 32], Signature, RecoveryId)],
+    ) -> crate::Vec<Option<Point>> {
+        use crate::fun::s;
+        // prefix_products[i] = R_x_0 * ... * R_x_{i-1}
+        let mut prefix_products = crate::Vec::with_capacity(items.len());
+        let mut product = Scalar::one().mark::<Public>();
+        for (_, signature, _) in items {
+            prefix_products.push(product);
+            product = s!(product * signature.R_x).mark::<Public>();
+        }
+
+        let mut product_inv = product.invert();
+        let mut r_invs = crate::Vec::with_capacity(items.len());
+        for ((_, signature, _), prefix_product) in items.iter().zip(prefix_products).rev() {
+            r_invs.push(s!(product_inv * prefix_product).mark::<Public>());
+            product_inv = s!(product_inv * signature.R_x).mark::<Public>();
+        }
+
+        items
+            .iter()
+            .zip(r_invs.iter().rev())
+            .map(|((message_hash, signature, recovery_id), r_inv)| {
+                self.recover_with_r_inv(message_hash, signature, *recovery_id, r_inv)
+            })
+            .collect()
+    }
+
+    fn recover_with_r_inv(
+        &self,
+        message_hash: &[u8; 32],
+        signature: &Signature,
+        recovery_id: RecoveryId,
+        r_inv: &Scalar<Public>,
+    ) -> Option<Point> {
+        let (R_x, s) = signature.as_tuple();
+        if s.is_high() && self.enforce_low_s {
+            return None;
+        }
+        let R = nonce_from_recovery_id(R_x, recovery_id)?;
+        let m = Scalar::from_bytes_mod_order(*message_hash).mark::<Public>();
+        // s * R = m * G + R_x * X
+        g!((r_inv * s) * R - (r_inv * m) * G)
+            .normalize()
+            .mark::<NonZero>()
+    }
+}
+
+/// Finds the nonce with x-coordinate equal to `R_x` (plus the curve order if the recovery id says
+/// it was reduced) and the parity given by the recovery id.
+fn nonce_from_recovery_id(R_x: &Scalar<Public>, recovery_id: RecoveryId) -> Option<Point> {
+    let mut x = R_x.to_bytes();
+    if recovery_id.is_x_reduced() {
+        let x_plus_n = FieldElement::from_bytes(x)? + FieldElement::from_bytes(CURVE_ORDER)?;
+        // if it wrapped around then R_x + n was not less than p
+        if x_plus_n.to_bytes() < x {
+            return None;
+        }
+        x = x_plus_n.to_bytes();
+    }
+    let mut bytes = [0u8; 33];
+    bytes[0] = 0x02 | recovery_id.is_y_odd() as u8;
+    bytes[1..].copy_from_slice(&x);
+    Point::from_bytes(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use secp256kfun::TEST_SOUNDNESS;
+
+    #[test]
+    fn sign_and_recover() {
+        let ecdsa = crate::test_instance!();
+        for _ in 0..TEST_SOUNDNESS {
+            let secret_key = Scalar::random(&mut rand::thread_rng());
+            let verification_key = ecdsa.verification_key_for(&secret_key);
+            let message_hash = [1u8; 32];
+            let (signature, recovery_id) = ecdsa.sign_recoverable(&secret_key, &message_hash);
+            assert_eq!(signature, ecdsa.sign(&secret_key, &message_hash));
+            assert!(!recovery_id.is_x_reduced());
+            assert_eq!(
+                ecdsa.recover(&message_hash, &signature, recovery_id),
+                Some(verification_key)
+            );
+            let wrong_parity = RecoveryId(recovery_id.to_u8() ^ 0x01);
+            assert_ne!(
+                ecdsa.recover(&message_hash, &signature, wrong_parity),
+                Some(verification_key)
+            );
+            assert_ne!(
+                ecdsa.recover(&[2u8; 32], &signature, recovery_id),
+                Some(verification_key)
+            );
+        }
+    }
+
+    #[test]
+    fn recovery_id_from_u8() {
+        assert_eq!(RecoveryId::from_u8(3).map(RecoveryId::to_u8), Some(3));
+        assert!(RecoveryId::from_u8(4).is_none());
+    }
+
+    #[test]
+    fn reduced_x_beyond_field_fails() {
+        // R_x + n >= p for any R_x this large
+        let R_x = Scalar::from_bytes([0xee; 32])
+            .unwrap()
+            .mark::<(Public, NonZero)>()
+            .unwrap();
+        assert!(nonce_from_recovery_id(&R_x, RecoveryId(2)).is_none());
+        let R_x = Scalar::one().mark::<Public>();
+        assert_eq!(
+            nonce_from_recovery_id(&R_x, RecoveryId(2)).map(|R| R.x_bytes()),
+            FieldElement::from_bytes(CURVE_ORDER)
+                .map(|n| n + FieldElement::one())
+                .filter(|x| x.is_x_coordinate())
+                .map(|x| x.to_bytes())
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn batch_matches_individual() {
+        let ecdsa = crate::test_instance!();
+        let mut items = crate::Vec::new();
+        for i in 0..TEST_SOUNDNESS {
+            let secret_key = Scalar::random(&mut rand::thread_rng());
+            let message_hash = [i as u8; 32];
+            let (signature, recovery_id) = ecdsa.sign_recoverable(&secret_key, &message_hash);
+            items.push((message_hash, signature, recovery_id));
+        }
+        // corrupt one of them
+        items[3].2 = RecoveryId(items[3].2.to_u8() ^ 0x01);
+        let batch = ecdsa.recover_batch(&items);
+        assert_eq!(batch.len(), items.len());
+        for ((message_hash, signature, recovery_id), recovered) in items.iter().zip(batch) {
+            assert_eq!(
+                recovered,
+                ecdsa.recover(message_hash, signature, *recovery_id)
+            );
+        }
+        assert!(ecdsa.recover_batch(&[]).is_empty());
+    }
+}
@@ -24,6 +24,81 @@ impl<S> Signature<S> {
             s: self.s.mark::<SigSec>(),
         }
     }
+
+    /// Whether `s` is at most half the curve order as required by [BIP-146].
+    ///
+    /// [BIP-146]: https://github.com/bitcoin/bips/blob/master/bip-0146.mediawiki#low_s
+    pub fn is_low_s(&self) -> bool {
+        !self.s.is_high()
+    }
+
+    /// Negates `s` if it is high so that the signature satisfies [BIP-146]. The signature stays
+    /// valid for the same message and key.
+    ///
+    /// [BIP-146]: https://github.com/bitcoin/bips/blob/master/bip-0146.mediawiki#low_s
+    pub fn normalize_s(&mut self) {
+        let is_high = self.s.is_high();
+        self.s.conditional_negate(is_high);
+    }
+
+    /// Encodes the signature as a DER `SEQUENCE` of the two integers `r` and `s`.
+    ///
+    /// This is the format OpenSSL, HSMs and Bitcoin scripts (without the sighash byte) use. It is
+    /// at most 72 bytes long.
+    #[cfg(feature = "alloc")]
+    pub fn to_der(&self) -> crate::Vec<u8> {
+        let r = der_integer(&self.R_x.to_bytes());
+        let s = der_integer(&self.s.to_bytes());
+        let mut der = crate::Vec::with_capacity(72);
+        der.extend_from_slice(&[0x30, (r.len() + s.len()) as u8]);
+        der.extend_from_slice(&r);
+        der.extend_from_slice(&s);
+        der
+    }
+}
+
+/// The DER encoding of a positive 32-byte big-endian integer (tag and length included).
+#[cfg(feature = "alloc")]
+fn der_integer(bytes: &[u8; 32]) -> crate::Vec<u8> {
+    let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    let bytes = &bytes[zeros..];
+    let pad = bytes[0] & 0x80 != 0;
+    let mut integer = vec![0x02, (bytes.len() + pad as usize) as u8];
+    if pad {
+        integer.push(0x00);
+    }
+    integer.extend_from_slice(bytes);
+    integer
+}
+
+/// Parses a DER integer into a scalar returning it and the rest of the input.
+fn parse_der_integer(der: &[u8]) -> Option<(Scalar<Public, NonZero>, &[u8])> {
+    let (len, rest) = match der {
+        [0x02, len, rest @ ..] => (*len as usize, rest),
+        _ => return None,
+    };
+    if len == 0 || len > 33 || rest.len() < len {
+        return None;
+    }
+    let (integer, rest) = rest.split_at(len);
+    // negative or not minimally encoded
+    if integer[0] & 0x80 != 0 || (len > 1 && integer[0] == 0 && integer[1] & 0x80 == 0) {
+        return None;
+    }
+    let integer = if len == 33 {
+        if integer[0] != 0 {
+            return None;
+        }
+        &integer[1..]
+    } else {
+        integer
+    };
+    let mut bytes = [0u8; 32];
+    bytes[32 - integer.len()..].copy_from_slice(integer);
+    let scalar = Scalar::from_bytes(bytes)?
+        .mark::<Public>()
+        .mark::<NonZero>()?;
+    Some((scalar, rest))
 }
 
 impl Signature<Public> {
@@ -36,6 +111,35 @@ impl Signature<Public> {
                     .map(|s| Self { R_x, s })
             })
     }
+
+    /// Checks whether `bytes` is the canonical encoding of a signature i.e. it decodes with
+    /// [`from_bytes`] and [`is_low_s`]. Anyone can turn a valid signature into another valid
+    /// signature by negating `s` so consensus systems should reject non-canonical encodings.
+    ///
+    /// [`from_bytes`]: Self::from_bytes
+    /// [`is_low_s`]: Self::is_low_s
+    pub fn is_canonical(bytes: &[u8; 64]) -> bool {
+        Self::from_bytes(*bytes).map_or(false, |signature| signature.is_low_s())
+    }
+
+    /// Decodes a signature from the DER encoding produced by [`to_der`].
+    ///
+    /// Only strict DER is accepted (no extra padding or trailing bytes). `r` and `s` must be
+    /// non-zero and less than the curve order. High `s` values are accepted.
+    ///
+    /// [`to_der`]: Self::to_der
+    pub fn from_der(der: &[u8]) -> Option<Self> {
+        let content = match der {
+            [0x30, len, content @ ..] if *len as usize == content.len() => content,
+            _ => return None,
+        };
+        let (R_x, rest) = parse_der_integer(content)?;
+        let (s, rest) = parse_der_integer(rest)?;
+        if !rest.is_empty() {
+            return None;
+        }
+        Some(Signature { R_x, s })
+    }
 }
 
 secp256kfun::impl_fromstr_deserialize! {
@@ -50,3 +154,63 @@ secp256kfun::impl_display_debug_serialize! {
         sig.to_bytes()
     }
 }
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn der_roundtrip() {
+        let ecdsa = crate::test_instance!();
+        for i in 0u8..20 {
+            let secret_key = Scalar::random(&mut rand::thread_rng());
+            let signature = ecdsa.sign(&secret_key, &[i; 32]);
+            let der = signature.to_der();
+            assert!(der.len() <= 72);
+            assert_eq!(Signature::from_der(&der), Some(signature));
+        }
+    }
+
+    #[test]
+    fn der_from_openssl() {
+        use sha2::{Digest, Sha256};
+        // `openssl dgst -sha256 -sign key.pem` of "hello"
+        let der = secp256kfun::hex::decode(
+            "3045022100cbe871c184a1f8d900b1199c7b4c64a6061c7672d1bef6ab9cd9406fcd1c9e17022073c9a95a0fd16798ddf3b6bd052179fd0b7503540f8cb57e5c4bbb06dee425fa",
+        )
+        .unwrap();
+        let secret_key = Scalar::from_bytes(
+            secp256kfun::hex::decode_array(
+                "04e52b2bc7f818feda405b4f39520f4140bb9c020d6ed9dd9591917d2262c9ca",
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .non_zero()
+        .unwrap();
+        let ecdsa = crate::ECDSA::verify_only();
+        let message_hash: [u8; 32] = Sha256::digest(b"hello").into();
+        let signature = Signature::from_der(&der).unwrap();
+        assert!(ecdsa.verify(
+            &ecdsa.verification_key_for(&secret_key),
+            &message_hash,
+            &signature
+        ));
+        assert_eq!(signature.to_der(), der);
+
+        let mut trailing = der.clone();
+        trailing.push(0);
+        assert_eq!(Signature::from_der(&trailing), None);
+        // an extra zero byte in front of s
+        let mut padded = der.clone();
+        padded[1] += 1;
+        padded[38] += 1;
+        padded.insert(39, 0);
+        assert_eq!(Signature::from_der(&padded), None);
+        // r = 0
+        assert_eq!(
+            Signature::from_der(&[0x30, 0x06, 0x02, 0x01, 0x00, 0x02, 0x01, 0x01]),
+            None
+        );
+    }
+}
@@ -1,8 +1,13 @@
 //! This broken and just as a reference until we get proper bip340 benchmarks from proper rust lib
 #![allow(non_upper_case_globals)]
 use criterion::{criterion_group, criterion_main, Criterion};
-use schnorr_fun::{Message, Schnorr};
-use secp256kfun::{marker::*, nonce::Deterministic, Scalar};
+use schnorr_fun::{Message, Schnorr, VerificationKey};
+use secp256kfun::{
+    hash::{HashAdd, Tagged},
+    marker::*,
+    nonce::Deterministic,
+    Scalar,
+};
 use sha2::Sha256;
 
 const MESSAGE: &'static [u8; 32] = b"hello world you are beautiful!!!";
@@ -46,6 +51,13 @@ fn verify_schnorr(c: &mut Criterion) {
             b.iter(|| schnorr.verify(&verification_key, message, &sig))
         });
 
+        {
+            let verification_key = VerificationKey::new(keypair.public_key());
+            group.bench_function("fun::schnorr_verify_with_key", |b| {
+                b.iter(|| schnorr.verify_with_key(&verification_key, message, &sig))
+            });
+        }
+
         {
             let sig = sig.clone().mark::<Secret>();
             group.bench_function("fun::schnorr_verify_ct", |b| {
@@ -67,5 +79,27 @@ fn verify_schnorr(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, verify_schnorr, sign_schnorr);
+fn challenge_schnorr(c: &mut Criterion) {
+    let mut group = c.benchmark_group("schnorr_challenge");
+    let keypair = schnorr.new_keypair(SK.clone());
+    let message = Message::<Public>::raw(MESSAGE);
+    let sig = schnorr.sign(&keypair, message);
+    let public_key = keypair.public_key();
+    group.bench_function("fun::schnorr_challenge_cached_midstate", |b| {
+        b.iter(|| schnorr.challenge(&sig.R, &public_key, message))
+    });
+    group.bench_function("fun::schnorr_challenge_retag", |b| {
+        b.iter(|| {
+            Scalar::from_hash(
+                Sha256::default()
+                    .tagged(b"BIP0340/challenge")
+                    .add(sig.R)
+                    .add(public_key)
+                    .add(message),
+            )
+        })
+    });
+}
+
+criterion_group!(benches, verify_schnorr, sign_schnorr, challenge_schnorr);
 criterion_main!(benches);
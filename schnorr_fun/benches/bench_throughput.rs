@@ -0,0 +1,272 @@
+//! Throughput of the common operations over a deterministic workload, compared against
+//! libsecp256k1 (through the `secp256k1` crate) where it has an equivalent.
+//!
+//! Run with `cargo bench -p schnorr_fun --features bench-helpers --bench bench_throughput`.
+#![allow(non_snake_case)]
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use schnorr_fun::{
+    adaptor::{Adaptor, EncryptedSign},
+    bench_helpers::{self, Workload},
+    nonce::Deterministic,
+    Schnorr,
+};
+use secp256kfun::{g, marker::*, Scalar};
+use sha2::Sha256;
+
+const SEED: &[u8] = b"schnorr_fun/bench_throughput";
+const BATCH_SIZE: u32 = 64;
+
+fn setup() -> (Schnorr<Sha256, Deterministic<Sha256>>, Workload) {
+    let schnorr = Schnorr::<Sha256, _>::new(Deterministic::<Sha256>::default());
+    let workload = Workload::new::<Sha256, _, _>(&schnorr, SEED, BATCH_SIZE);
+    (schnorr, workload)
+}
+
+fn sign(c: &mut Criterion) {
+    let (schnorr, workload) = setup();
+    let mut group = c.benchmark_group("throughput_sign");
+    group.throughput(Throughput::Elements(1));
+
+    let mut i = 0;
+    group.bench_function("fun", |b| {
+        b.iter(|| {
+            i = (i + 1) % workload.len();
+            schnorr.sign(&workload.keypairs[i], workload.message(i))
+        })
+    });
+
+    {
+        use secp256k1::{KeyPair, Message, Secp256k1};
+        let secp = Secp256k1::new();
+        let keypairs = workload
+            .keypairs
+            .iter()
+            .map(|kp| KeyPair::from_secret_key(&secp, kp.secret_key().clone().into()))
+            .collect::<Vec<_>>();
+        let messages = workload
+            .messages
+            .iter()
+            .map(|msg| Message::from_slice(&msg[..]).unwrap())
+            .collect::<Vec<_>>();
+        let mut i = 0;
+        group.bench_function("secp", |b| {
+            b.iter(|| {
+                i = (i + 1) % workload.len();
+                secp.sign_schnorr_no_aux_rand(&messages[i], &keypairs[i])
+            })
+        });
+    }
+}
+
+fn verify(c: &mut Criterion) {
+    let (schnorr, workload) = setup();
+    let public_keys = workload
+        .keypairs
+        .iter()
+        .map(|kp| kp.public_key())
+        .collect::<Vec<_>>();
+    let mut group = c.benchmark_group("throughput_verify");
+    group.throughput(Throughput::Elements(1));
+
+    let mut i = 0;
+    group.bench_function("fun", |b| {
+        b.iter(|| {
+            i = (i + 1) % workload.len();
+            schnorr.verify(
+                &public_keys[i],
+                workload.message(i),
+                &workload.signatures[i],
+            )
+        })
+    });
+
+    let secp_workload = SecpWorkload::new(&workload);
+    let mut i = 0;
+    group.bench_function("secp", |b| {
+        b.iter(|| {
+            i = (i + 1) % workload.len();
+            secp_workload.verify(i)
+        })
+    });
+}
+
+fn batch_verify(c: &mut Criterion) {
+    let (schnorr, workload) = setup();
+    let public_keys = workload
+        .keypairs
+        .iter()
+        .map(|kp| kp.public_key())
+        .collect::<Vec<_>>();
+    let mut group = c.benchmark_group("throughput_batch_verify");
+    group.throughput(Throughput::Elements(workload.len() as u64));
+
+    group.bench_function("fun", |b| {
+        b.iter(|| {
+            (0..workload.len()).all(|i| {
+                schnorr.verify(
+                    &public_keys[i],
+                    workload.message(i),
+                    &workload.signatures[i],
+                )
+            })
+        })
+    });
+
+    let secp_workload = SecpWorkload::new(&workload);
+    group.bench_function("secp", |b| {
+        b.iter(|| (0..workload.len()).all(|i| secp_workload.verify(i)))
+    });
+}
+
+fn adaptor(c: &mut Criterion) {
+    let (schnorr, workload) = setup();
+    let mut group = c.benchmark_group("throughput_adaptor");
+    group.throughput(Throughput::Elements(1));
+
+    let mut i = 0;
+    group.bench_function("fun::encrypted_sign", |b| {
+        b.iter(|| {
+            i = (i + 1) % workload.len();
+            schnorr.encrypted_sign(
+                &workload.keypairs[i],
+                &workload.encryption_keys[i],
+                workload.message(i),
+            )
+        })
+    });
+
+    let mut i = 0;
+    group.bench_function("fun::verify_encrypted_signature", |b| {
+        b.iter(|| {
+            i = (i + 1) % workload.len();
+            schnorr.verify_encrypted_signature(
+                &workload.keypairs[i].public_key(),
+                &workload.encryption_keys[i],
+                workload.message(i),
+                &workload.encrypted_signatures[i],
+            )
+        })
+    });
+
+    let mut i = 0;
+    group.bench_function("fun::decrypt_signature", |b| {
+        b.iter(|| {
+            i = (i + 1) % workload.len();
+            schnorr.decrypt_signature(
+                workload.decryption_keys[i].clone(),
+                workload.encrypted_signatures[i].clone(),
+            )
+        })
+    });
+
+    let signatures = (0..workload.len())
+        .map(|i| {
+            schnorr.decrypt_signature(
+                workload.decryption_keys[i].clone(),
+                workload.encrypted_signatures[i].clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut i = 0;
+    group.bench_function("fun::recover_decryption_key", |b| {
+        b.iter(|| {
+            i = (i + 1) % workload.len();
+            schnorr.recover_decryption_key(
+                &workload.encryption_keys[i],
+                &workload.encrypted_signatures[i],
+                &signatures[i],
+            )
+        })
+    });
+}
+
+fn point_mul(c: &mut Criterion) {
+    let scalars = (0..BATCH_SIZE)
+        .map(|i| bench_helpers::scalar::<Sha256>(SEED, "scalar", i))
+        .collect::<Vec<_>>();
+    let points = (0..BATCH_SIZE)
+        .map(|i| bench_helpers::point::<Sha256>(SEED, "point", i))
+        .collect::<Vec<_>>();
+    let mut group = c.benchmark_group("throughput_point_mul");
+    group.throughput(Throughput::Elements(1));
+
+    let mut i = 0;
+    group.bench_function("fun::secret", |b| {
+        b.iter(|| {
+            i = (i + 1) % scalars.len();
+            let (x, X) = (&scalars[i], &points[i]);
+            g!(x * X)
+        })
+    });
+
+    let public_scalars = scalars
+        .iter()
+        .map(|x| x.clone().mark::<Public>())
+        .collect::<Vec<Scalar<Public>>>();
+    let mut i = 0;
+    group.bench_function("fun::public", |b| {
+        b.iter(|| {
+            i = (i + 1) % scalars.len();
+            let (x, X) = (&public_scalars[i], &points[i]);
+            g!(x * X)
+        })
+    });
+
+    {
+        use secp256k1::{PublicKey, Secp256k1};
+        let secp = Secp256k1::new();
+        let points = points
+            .iter()
+            .map(|X| PublicKey::from(*X))
+            .collect::<Vec<_>>();
+        let mut i = 0;
+        group.bench_function("secp", |b| {
+            b.iter(|| {
+                i = (i + 1) % scalars.len();
+                let mut X = points[i];
+                X.mul_assign(&secp, &scalars[i].to_bytes()).unwrap();
+                X
+            })
+        });
+    }
+}
+
+/// The workload converted into libsecp256k1 types.
+struct SecpWorkload {
+    secp: secp256k1::Secp256k1<secp256k1::All>,
+    public_keys: Vec<secp256k1::XOnlyPublicKey>,
+    messages: Vec<secp256k1::Message>,
+    signatures: Vec<secp256k1::schnorr::Signature>,
+}
+
+impl SecpWorkload {
+    fn new(workload: &Workload) -> Self {
+        SecpWorkload {
+            secp: secp256k1::Secp256k1::new(),
+            public_keys: workload
+                .keypairs
+                .iter()
+                .map(|kp| kp.public_key().into())
+                .collect(),
+            messages: workload
+                .messages
+                .iter()
+                .map(|msg| secp256k1::Message::from_slice(&msg[..]).unwrap())
+                .collect(),
+            signatures: workload
+                .signatures
+                .iter()
+                .map(|sig| secp256k1::schnorr::Signature::from_slice(&sig.to_bytes()).unwrap())
+                .collect(),
+        }
+    }
+
+    fn verify(&self, i: usize) -> bool {
+        self.secp
+            .verify_schnorr(&self.signatures[i], &self.messages[i], &self.public_keys[i])
+            .is_ok()
+    }
+}
+
+criterion_group!(benches, sign, verify, batch_verify, adaptor, point_mul);
+criterion_main!(benches);
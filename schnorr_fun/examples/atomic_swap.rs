@@ -0,0 +1,81 @@
+//! An atomic swap of coins on two chains with adaptor signatures.
+//!
+//! Alice has coins on chain A and Bob has coins on chain B. Each has locked their coins in an
+//! output that needs a signature from both of them (e.g. a tapscript `2-of-2`). They want to swap
+//! them so that either both transfers happen or neither does.
+//!
+//! 1. Alice picks a secret decryption key `y` and sends Bob the encryption key `Y = y * G`.
+//! 2. They each create an encrypted signature under `Y` on the transaction that pays out their
+//!    coins to the other and swap them. Each checks the one they receive.
+//! 3. Alice decrypts Bob's signature with `y` and claims Bob's coins on chain B. This puts the
+//!    decrypted signature on chain B.
+//! 4. Bob recovers `y` from the signature on chain B and the encrypted signature he created and
+//!    uses it to decrypt Alice's signature and claim Alice's coins on chain A.
+//!
+//! In practice the outputs also need refund paths with timelocks (shorter on chain B) in case
+//! someone stops responding. Those don't involve adaptor signatures so they are left out.
+//!
+//! ```text
+//! cargo run -p schnorr_fun --example atomic_swap
+//! ```
+use schnorr_fun::{
+    adaptor::{Adaptor, EncryptedSign},
+    fun::{marker::*, Scalar},
+    nonce::Deterministic,
+    Message, Schnorr,
+};
+use sha2::Sha256;
+
+fn main() {
+    let schnorr = Schnorr::<Sha256, _>::new(Deterministic::<Sha256>::default());
+    let mut rng = rand::thread_rng();
+    let alice = schnorr.new_keypair(Scalar::random(&mut rng));
+    let bob = schnorr.new_keypair(Scalar::random(&mut rng));
+    // The signature hashes of the transactions. Alice's coins go to Bob on chain A and Bob's go to
+    // Alice on chain B.
+    let tx_a = Message::<Public>::plain("atomic-swap", b"chain A: Alice pays Bob 1 BTC");
+    let tx_b = Message::<Public>::plain("atomic-swap", b"chain B: Bob pays Alice 100 LTC");
+
+    // 1. Alice picks the decryption key
+    let decryption_key = Scalar::random(&mut rng);
+    let encryption_key = schnorr.encryption_key_for(&decryption_key);
+
+    // 2. they swap encrypted signatures and check them
+    let alice_encrypted = schnorr.encrypted_sign(&alice, &encryption_key, tx_a);
+    let bob_encrypted = schnorr.encrypted_sign(&bob, &encryption_key, tx_b);
+    assert!(schnorr.verify_encrypted_signature(
+        &alice.public_key(),
+        &encryption_key,
+        tx_a,
+        &alice_encrypted
+    ));
+    assert!(schnorr.verify_encrypted_signature(
+        &bob.public_key(),
+        &encryption_key,
+        tx_b,
+        &bob_encrypted
+    ));
+
+    // 3. Alice decrypts Bob's signature and broadcasts tx_b along with her own signature on it
+    let bob_signature = schnorr.decrypt_signature(decryption_key.clone(), bob_encrypted.clone());
+    let alice_signature_b = schnorr.sign(&alice, tx_b);
+    assert!(schnorr.verify(&bob.public_key(), tx_b, &bob_signature));
+    assert!(schnorr.verify(&alice.public_key(), tx_b, &alice_signature_b));
+    println!("Alice claims on chain B");
+
+    // 4. Bob sees his signature on chain B and learns the decryption key
+    let recovered = schnorr
+        .recover_decryption_key(&encryption_key, &bob_encrypted, &bob_signature)
+        .expect("the signature on chain B was decrypted from Bob's encrypted signature");
+    assert_eq!(recovered, decryption_key);
+    let alice_signature = schnorr.decrypt_signature(recovered, alice_encrypted);
+    let bob_signature_a = schnorr.sign(&bob, tx_a);
+    assert!(schnorr.verify(&alice.public_key(), tx_a, &alice_signature));
+    assert!(schnorr.verify(&bob.public_key(), tx_a, &bob_signature_a));
+    println!("Bob claims on chain A");
+}
+
+#[test]
+fn run() {
+    main()
+}
@@ -0,0 +1,110 @@
+//! A discreet log contract (DLC) betting on the BTC/USD price.
+//!
+//! Alice and Bob fund an output that needs both of their signatures to spend and bet on the price
+//! an oracle will attest to tomorrow. Each contract execution transaction (CET) spends the
+//! funding output and pays out for a range of prices.
+//!
+//! 1. The oracle publishes an [`Announcement`] with its key and one nonce for each digit of the
+//!    price.
+//! 2. Alice and Bob each sign every CET with signatures encrypted to the outcomes it pays out for
+//!    and swap them (before signing the funding transaction).
+//! 3. The oracle attests to the price.
+//! 4. The attestation decrypts the counterparty's signature on the CET that pays out for that
+//!    price. Either party can add their own signature and broadcast it.
+//!
+//! ```text
+//! cargo run -p schnorr_fun --example dlc
+//! ```
+//!
+//! [`Announcement`]: schnorr_fun::dlc::Announcement
+use schnorr_fun::{
+    dlc::{
+        cet::{AnticipationPoints, Cet},
+        Announcement,
+    },
+    fun::{marker::*, Point, Scalar, G},
+    nonce::Deterministic,
+    Message, Schnorr,
+};
+use sha2::Sha256;
+
+fn main() {
+    let schnorr = Schnorr::<Sha256, _>::new(Deterministic::<Sha256>::default());
+    let mut rng = rand::thread_rng();
+
+    // 1. the oracle will attest to a price from 0 to 99_999 in base 10
+    let oracle = schnorr.new_keypair(Scalar::random(&mut rng));
+    let mut nonce_secrets = (0..5).map(|_| Scalar::random(&mut rng)).collect::<Vec<_>>();
+    let nonces = nonce_secrets
+        .iter_mut()
+        .map(|r| Point::even_y_from_scalar_mul(G, r))
+        .collect();
+    let announcement = Announcement::new(oracle.public_key(), nonces, 10)
+        .expect("five base 10 digits fit in a u64");
+
+    // 2. Alice wins everything below 20_000, Bob wins everything above 30_000 and they split it
+    // in between
+    let alice = schnorr.new_keypair(Scalar::random(&mut rng));
+    let bob = schnorr.new_keypair(Scalar::random(&mut rng));
+    let cets = [
+        Cet::new(
+            0..=19_999,
+            Message::<Public>::plain("dlc-example", b"pay Alice 2 BTC"),
+        ),
+        Cet::new(
+            20_000..=30_000,
+            Message::<Public>::plain("dlc-example", b"pay Alice 1 BTC and Bob 1 BTC"),
+        ),
+        Cet::new(
+            30_001..=99_999,
+            Message::<Public>::plain("dlc-example", b"pay Bob 2 BTC"),
+        ),
+    ];
+    let anticipation_points = AnticipationPoints::new(&schnorr, &announcement);
+    let alice_encrypted = anticipation_points.sign(&schnorr, &alice, &cets);
+    let bob_encrypted = anticipation_points.sign(&schnorr, &bob, &cets);
+    assert!(anticipation_points.verify(
+        &schnorr,
+        &alice.public_key(),
+        &cets,
+        &alice_encrypted,
+        &mut rng
+    ));
+    assert!(anticipation_points.verify(
+        &schnorr,
+        &bob.public_key(),
+        &cets,
+        &bob_encrypted,
+        &mut rng
+    ));
+    let n_encrypted = alice_encrypted
+        .iter()
+        .map(|cet_sigs| cet_sigs.encrypted_signatures.len())
+        .sum::<usize>();
+    println!(
+        "{} encrypted signatures cover {} outcomes",
+        n_encrypted,
+        announcement.n_outcomes()
+    );
+
+    // 3. the oracle attests to the price
+    let attestation = announcement
+        .attest(&schnorr, &oracle, &nonce_secrets, 24_567)
+        .expect("the price is in range");
+    assert!(attestation.verify(&schnorr, &announcement));
+
+    // 4. Bob decrypts Alice's signature on the CET that splits the funds and adds his own
+    let (index, alice_signature) = anticipation_points
+        .decrypt(&schnorr, &attestation, &cets, &alice_encrypted)
+        .expect("a CET covers every outcome");
+    assert_eq!(index, 1);
+    let bob_signature = schnorr.sign(&bob, cets[index].message);
+    assert!(schnorr.verify(&alice.public_key(), cets[index].message, &alice_signature));
+    assert!(schnorr.verify(&bob.public_key(), cets[index].message, &bob_signature));
+    println!("Bob broadcasts the CET for {:?}", cets[index].outcomes);
+}
+
+#[test]
+fn run() {
+    main()
+}
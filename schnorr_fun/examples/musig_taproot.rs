@@ -0,0 +1,98 @@
+//! A 2-of-2 MuSig key path spend of a taproot output.
+//!
+//! Alice and Bob aggregate their keys into a taproot _internal key_ and tweak it with [BIP-341]'s
+//! `TapTweak` to get the _output key_ that goes in the output (and the address). To spend it they
+//! each sign the [BIP-341] signature hash of the spending transaction under the tweaked aggregate
+//! key and combine their partial signatures into an ordinary BIP-340 signature that verifies under
+//! the output key.
+//!
+//! This output has no script tree. With one, add the merkle root to the `TapTweak` hash after the
+//! internal key.
+//!
+//! ```text
+//! cargo run -p schnorr_fun --example musig_taproot
+//! ```
+//!
+//! [BIP-341]: https://bips.xyz/341
+use schnorr_fun::{
+    adaptor::EncryptedSignTweaked,
+    fun::{
+        bech32,
+        hash::{tags::TapTweak, HashAdd, Tagged},
+        Scalar,
+    },
+    musig, Bip341Sighash, TypedMessage,
+};
+use sha2::Sha256;
+
+fn main() {
+    let musig = musig::new_with_deterministic_nonces::<Sha256>();
+    let mut rng = rand::thread_rng();
+    let alice = musig.new_keypair(Scalar::random(&mut rng));
+    let bob = musig.new_keypair(Scalar::random(&mut rng));
+
+    // Both compute the same aggregate key from the public keys (in the same order)
+    let agg_key = musig
+        .new_agg_key(vec![alice.public_key(), bob.public_key()])
+        .into_xonly_key();
+    let internal_key = agg_key.agg_public_key();
+    let tweak = Scalar::from_hash(
+        Sha256::default()
+            .tagged_with::<TapTweak>()
+            .add(&internal_key.to_xonly_bytes()[..]),
+    );
+    let agg_key = agg_key.tweak(tweak).expect("computationally unreachable");
+    let output_key = agg_key.agg_public_key();
+    assert_eq!(
+        output_key,
+        musig.schnorr.taproot_output_key(&internal_key, None)
+    );
+    println!(
+        "send coins to {}",
+        bech32::encode_taproot_address("bc", &output_key)
+    );
+
+    // The signature hash of the transaction spending the output. In practice it comes from your
+    // transaction library.
+    let sighash = [42u8; 32];
+    let message = TypedMessage::<Bip341Sighash>::new(&sighash).message();
+
+    // Each signer generates a nonce with a fresh session id and they swap the public nonces
+    let session_id = Scalar::random(&mut rng).to_bytes();
+    let alice_nonce = musig.gen_nonces(
+        alice.secret_key(),
+        &session_id,
+        Some(output_key),
+        Some(message),
+    );
+    let bob_nonce = musig.gen_nonces(
+        bob.secret_key(),
+        &session_id,
+        Some(output_key),
+        Some(message),
+    );
+    let session = musig.start_sign_session(
+        &agg_key,
+        vec![alice_nonce.public(), bob_nonce.public()],
+        message,
+    );
+
+    // They sign, swap partial signatures and check the one they receive
+    let alice_sig = musig.sign(&agg_key, &session, 0, &alice, alice_nonce);
+    let bob_sig = musig.sign(&agg_key, &session, 1, &bob, bob_nonce);
+    assert!(musig.verify_partial_signature(&agg_key, &session, 1, bob_sig));
+    assert!(musig.verify_partial_signature(&agg_key, &session, 0, alice_sig));
+
+    // Either of them can combine them into the signature for the witness
+    let signature = musig.combine_partial_signatures(&agg_key, &session, [alice_sig, bob_sig]);
+    assert!(musig.schnorr.verify(&output_key, message, &signature));
+    println!(
+        "key path witness: {}",
+        schnorr_fun::fun::hex::encode(&signature.to_bytes())
+    );
+}
+
+#[test]
+fn run() {
+    main()
+}
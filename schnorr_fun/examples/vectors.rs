@@ -0,0 +1,239 @@
+//! Generates deterministic JSON test vectors for the protocols in `schnorr_fun`.
+//!
+//! Every key, nonce and message is derived from a seed by hashing so the output is the same every
+//! time. Implementers in other languages can use it to check their implementation against this
+//! one.
+//!
+//! ```text
+//! cargo run -p schnorr_fun --example vectors [seed] > vectors.json
+//! ```
+//!
+//! The ECDSA vectors are generated by the `vectors` example in `ecdsa_fun`.
+#![allow(non_snake_case)]
+use schnorr_fun::{
+    adaptor::{Adaptor, EncryptedSign},
+    frost::Frost,
+    fun::{hash::HashAdd, hex, marker::*, Scalar},
+    musig,
+    nonce::Deterministic,
+    Message, Schnorr,
+};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+/// How many vectors to generate for the single signer protocols.
+const COUNT: u32 = 3;
+
+/// Derives the `i`th secret scalar labelled `label` from `seed`.
+fn scalar(seed: &str, label: &str, i: u32) -> Scalar {
+    Scalar::from_hash(
+        Sha256::default()
+            .add(seed.as_bytes())
+            .add(label)
+            .add(i.to_be_bytes()),
+    )
+}
+
+/// Derives the `i`th 32-byte message labelled `label` from `seed`.
+fn message(seed: &str, label: &str, i: u32) -> [u8; 32] {
+    scalar(seed, label, i).to_bytes()
+}
+
+fn bip340(seed: &str, schnorr: &Schnorr<Sha256, Deterministic<Sha256>>) -> Value {
+    (0..COUNT)
+        .map(|i| {
+            let keypair = schnorr.new_keypair(scalar(seed, "bip340/secret-key", i));
+            let message_bytes = message(seed, "bip340/message", i);
+            let message = Message::<Public>::raw(&message_bytes);
+            let signature = schnorr.sign(&keypair, message);
+            let challenge = schnorr.challenge(&signature.R, &keypair.public_key(), message);
+            assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+            json!({
+                "secret_key": keypair.secret_key().to_string(),
+                "public_key": keypair.public_key().to_string(),
+                "message": hex::encode(&message_bytes),
+                "nonce": signature.R.to_string(),
+                "challenge": challenge.to_string(),
+                "signature": signature.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn adaptor(seed: &str, schnorr: &Schnorr<Sha256, Deterministic<Sha256>>) -> Value {
+    (0..COUNT)
+        .map(|i| {
+            let keypair = schnorr.new_keypair(scalar(seed, "adaptor/secret-key", i));
+            let decryption_key = scalar(seed, "adaptor/decryption-key", i);
+            let encryption_key = schnorr.encryption_key_for(&decryption_key);
+            let message_bytes = message(seed, "adaptor/message", i);
+            let message = Message::<Public>::raw(&message_bytes);
+            let encrypted_signature = schnorr.encrypted_sign(&keypair, &encryption_key, message);
+            let signature =
+                schnorr.decrypt_signature(decryption_key.clone(), encrypted_signature.clone());
+            assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+            json!({
+                "secret_key": keypair.secret_key().to_string(),
+                "public_key": keypair.public_key().to_string(),
+                "decryption_key": decryption_key.to_string(),
+                "encryption_key": encryption_key.to_string(),
+                "message": hex::encode(&message_bytes),
+                "encrypted_signature": {
+                    "R": encrypted_signature.R.to_string(),
+                    "s_hat": encrypted_signature.s_hat.to_string(),
+                    "needs_negation": encrypted_signature.needs_negation,
+                },
+                "signature": signature.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn musig2(seed: &str) -> Value {
+    let musig = musig::new_with_deterministic_nonces::<Sha256>();
+    let keypairs = (0..3)
+        .map(|i| musig.new_keypair(scalar(seed, "musig2/secret-key", i)))
+        .collect::<Vec<_>>();
+    let agg_key = musig
+        .new_agg_key(keypairs.iter().map(|kp| kp.public_key()).collect())
+        .into_xonly_key();
+    let session_id = message(seed, "musig2/session-id", 0);
+    let message_bytes = message(seed, "musig2/message", 0);
+    let message = Message::<Public>::raw(&message_bytes);
+    let secret_nonces = keypairs
+        .iter()
+        .map(|kp| {
+            musig.gen_nonces(
+                kp.secret_key(),
+                &session_id,
+                Some(agg_key.agg_public_key()),
+                Some(message),
+            )
+        })
+        .collect::<Vec<_>>();
+    let public_nonces = secret_nonces
+        .iter()
+        .map(|nonce| nonce.public())
+        .collect::<Vec<_>>();
+    let session = musig.start_sign_session(&agg_key, public_nonces.clone(), message);
+    let partial_signatures = keypairs
+        .iter()
+        .zip(secret_nonces)
+        .enumerate()
+        .map(|(i, (kp, nonce))| musig.sign(&agg_key, &session, i, kp, nonce))
+        .collect::<Vec<_>>();
+    let signature =
+        musig.combine_partial_signatures(&agg_key, &session, partial_signatures.clone());
+    assert!(musig
+        .schnorr
+        .verify(&agg_key.agg_public_key(), message, &signature));
+
+    json!({
+        "secret_keys": keypairs.iter().map(|kp| kp.secret_key().to_string()).collect::<Vec<_>>(),
+        "public_keys": keypairs.iter().map(|kp| kp.public_key().to_string()).collect::<Vec<_>>(),
+        "agg_public_key": agg_key.agg_public_key().to_string(),
+        "session_id": hex::encode(&session_id),
+        "message": hex::encode(&message_bytes),
+        "public_nonces": public_nonces.iter().map(|nonce| nonce.to_string()).collect::<Vec<_>>(),
+        "partial_signatures": partial_signatures.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+        "signature": signature.to_string(),
+    })
+}
+
+fn frost(seed: &str) -> Value {
+    const THRESHOLD: u32 = 2;
+    const N: u32 = 3;
+    let frost = Frost::new(Schnorr::<Sha256, _>::new(Deterministic::<Sha256>::default()));
+    let keygen_id = message(seed, "frost/keygen-id", 0);
+    let scalar_polys = (0..N)
+        .map(|i| frost.new_scalar_poly(scalar(seed, "frost/secret", i), THRESHOLD, &keygen_id))
+        .collect::<Vec<_>>();
+    let point_polys = scalar_polys
+        .iter()
+        .map(|poly| poly.to_point_poly())
+        .collect::<Vec<_>>();
+    let keygen = frost.new_keygen(point_polys.clone()).unwrap();
+    let (shares, proofs_of_possession): (Vec<_>, Vec<_>) = scalar_polys
+        .into_iter()
+        .map(|poly| frost.create_shares(&keygen, poly))
+        .unzip();
+    let (secret_shares, frost_keys): (Vec<_>, Vec<_>) = (0..N)
+        .map(|i| {
+            let received = shares
+                .iter()
+                .map(|shares| shares[i as usize].clone())
+                .collect();
+            frost
+                .finish_keygen_to_xonly(keygen.clone(), i, received, proofs_of_possession.clone())
+                .unwrap()
+        })
+        .unzip();
+    let frost_key = frost_keys[0].clone();
+
+    // parties 0 and 2 sign
+    let signers = [0u32, 2];
+    let session_id = message(seed, "frost/session-id", 0);
+    let message_bytes = message(seed, "frost/message", 0);
+    let message = Message::<Public>::raw(&message_bytes);
+    let secret_nonces = signers
+        .iter()
+        .map(|i| {
+            frost.gen_nonce(
+                &secret_shares[*i as usize],
+                &session_id,
+                Some(frost_key.public_key()),
+                Some(message),
+            )
+        })
+        .collect::<Vec<_>>();
+    let public_nonces = signers
+        .iter()
+        .zip(&secret_nonces)
+        .map(|(i, nonce)| (*i, nonce.public()))
+        .collect::<Vec<_>>();
+    let session = frost.start_sign_session(&frost_key, public_nonces.clone(), message);
+    let signature_shares = signers
+        .iter()
+        .zip(secret_nonces)
+        .map(|(i, nonce)| frost.sign(&frost_key, &session, *i, &secret_shares[*i as usize], nonce))
+        .collect::<Vec<_>>();
+    let signature = frost.combine_signature_shares(&frost_key, &session, signature_shares.clone());
+    assert!(frost
+        .schnorr
+        .verify(&frost_key.public_key(), message, &signature));
+
+    json!({
+        "threshold": THRESHOLD,
+        "n": N,
+        "keygen_id": hex::encode(&keygen_id),
+        "point_polys": point_polys
+            .iter()
+            .map(|poly| poly.points().iter().map(|point| point.to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>(),
+        "proofs_of_possession": proofs_of_possession.iter().map(|pop| pop.to_string()).collect::<Vec<_>>(),
+        "secret_shares": secret_shares.iter().map(|share| share.to_string()).collect::<Vec<_>>(),
+        "public_key": frost_key.public_key().to_string(),
+        "verification_shares": frost_key.verification_shares().map(|share| share.to_string()).collect::<Vec<_>>(),
+        "signers": signers,
+        "session_id": hex::encode(&session_id),
+        "message": hex::encode(&message_bytes),
+        "public_nonces": public_nonces.iter().map(|(_, nonce)| nonce.to_string()).collect::<Vec<_>>(),
+        "signature_shares": signature_shares.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+        "signature": signature.to_string(),
+    })
+}
+
+fn main() {
+    let seed = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "secp256kfun-test-vectors".into());
+    let schnorr = Schnorr::<Sha256, _>::new(Deterministic::<Sha256>::default());
+    let vectors = json!({
+        "seed": seed,
+        "bip340": bip340(&seed, &schnorr),
+        "adaptor": adaptor(&seed, &schnorr),
+        "musig2": musig2(&seed),
+        "frost": frost(&seed),
+    });
+    println!("{}", serde_json::to_string_pretty(&vectors).unwrap());
+}
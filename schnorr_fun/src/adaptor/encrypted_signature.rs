@@ -1,15 +1,37 @@
-use secp256kfun::{marker::*, Point, Scalar};
+use crate::Signature;
+use secp256kfun::{g, marker::*, s, Point, Scalar, G};
 
 /// A one-time encrypted Schnorr signature or "adaptor signature".
 ///
 /// Sometimes also called a "pre-signature".
-
-#[derive(Clone, Debug, PartialEq)]
-#[cfg_attr(
-    feature = "serde",
-    derive(serde::Deserialize, serde::Serialize),
-    serde(crate = "serde_crate")
-)]
+///
+/// # Malleability
+///
+/// An encrypted signature is only meaningful with respect to the encryption key it was verified
+/// against. Anyone can take an encrypted signature under `Y` and turn it into a valid encrypted
+/// signature under `Y + t * G` for any `t` they like by subtracting `t` from `s_hat`. The
+/// decrypted signature is the same either way. So the fact that an encrypted signature verifies
+/// under some encryption key says nothing about whether the signer intended that key: always
+/// verify against the encryption key _you_ expect. [`CheckedEncryptedSignature`] helps enforce
+/// this by carrying the keys it was checked against.
+///
+/// # Serialization
+///
+/// With the `serde` feature, human readable formats like JSON get an object with the fields `R`
+/// (hex x-only point), `s_hat` (hex scalar) and `needs_negation` (boolean) e.g.
+///
+/// ```json
+/// {
+///   "R": "d7a1f9b3...",
+///   "s_hat": "5c0e2f84...",
+///   "needs_negation": false
+/// }
+/// ```
+///
+/// Binary formats get the 65 bytes from [`to_bytes`].
+///
+/// [`to_bytes`]: Self::to_bytes
+#[derive(Clone, PartialEq)]
 pub struct EncryptedSignature<S = Public> {
     /// The `R` point in the signature
     pub R: Point<EvenY, Public>,
@@ -20,6 +42,16 @@ pub struct EncryptedSignature<S = Public> {
     pub needs_negation: bool,
 }
 
+impl<S: Secrecy> core::fmt::Debug for EncryptedSignature<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("EncryptedSignature")
+            .field("R", &self.R)
+            .field("s_hat", &self.s_hat)
+            .field("needs_negation", &self.needs_negation)
+            .finish()
+    }
+}
+
 impl<OldSec> EncryptedSignature<OldSec> {
     /// Marks the encrypted signature with a [`Secrecy`]. If it is marked as `Secret` the operations
     /// (e.g. verification) on the signature encryption should be done in constant time.
@@ -35,6 +67,274 @@ impl<OldSec> EncryptedSignature<OldSec> {
     }
 }
 
+impl<S> EncryptedSignature<S> {
+    /// Serializes the encrypted signature as 65 bytes: the 32-byte x-coordinate of `R`, the
+    /// 32-byte `s_hat` and a byte that is `1` if `needs_negation` and `0` otherwise.
+    pub fn to_bytes(&self) -> [u8; 65] {
+        let mut bytes = [0u8; 65];
+        bytes[..32].copy_from_slice(&self.R.to_xonly_bytes());
+        bytes[32..64].copy_from_slice(&self.s_hat.to_bytes());
+        bytes[64] = self.needs_negation as u8;
+        bytes
+    }
+}
+
+impl<S: Secrecy> EncryptedSignature<S> {
+    /// Deserializes an encrypted signature from the 65 bytes produced by [`to_bytes`].
+    ///
+    /// Returns `None` if `R` isn't a valid x-coordinate, `s_hat` isn't less than the curve order
+    /// or the last byte isn't `0` or `1`.
+    ///
+    /// [`to_bytes`]: Self::to_bytes
+    pub fn from_bytes(bytes: [u8; 65]) -> Option<Self> {
+        let mut R = [0u8; 32];
+        R.copy_from_slice(&bytes[..32]);
+        let mut s_hat = [0u8; 32];
+        s_hat.copy_from_slice(&bytes[32..64]);
+        let needs_negation = match bytes[64] {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        Some(EncryptedSignature {
+            R: Point::from_xonly_bytes(R)?,
+            s_hat: Scalar::from_bytes(s_hat)?.mark::<S>(),
+            needs_negation,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<S> serde::Serialize for EncryptedSignature<S> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        use serde::ser::{SerializeStruct, SerializeTuple};
+        if serializer.is_human_readable() {
+            let mut object = serializer.serialize_struct("EncryptedSignature", 3)?;
+            object.serialize_field("R", &self.R)?;
+            object.serialize_field("s_hat", &self.s_hat)?;
+            object.serialize_field("needs_negation", &self.needs_negation)?;
+            object.end()
+        } else {
+            let bytes = self.to_bytes();
+            let mut tuple = serializer.serialize_tuple(bytes.len())?;
+            for byte in bytes.iter() {
+                tuple.serialize_element(byte)?;
+            }
+            tuple.end()
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, S: Secrecy> serde::Deserialize<'de> for EncryptedSignature<S> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            #[derive(serde::Deserialize)]
+            #[serde(
+                crate = "serde_crate",
+                rename = "EncryptedSignature",
+                bound = "",
+                deny_unknown_fields
+            )]
+            struct Object<S> {
+                R: Point<EvenY>,
+                s_hat: Scalar<S, Zero>,
+                needs_negation: bool,
+            }
+            let Object {
+                R,
+                s_hat,
+                needs_negation,
+            } = Object::<S>::deserialize(deserializer)?;
+            return Ok(EncryptedSignature {
+                R,
+                s_hat,
+                needs_negation,
+            });
+        }
+
+        struct BytesVisitor<S>(core::marker::PhantomData<S>);
+
+        impl<'de, S: Secrecy> serde::de::Visitor<'de> for BytesVisitor<S> {
+            type Value = EncryptedSignature<S>;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                write!(f, "a valid 65-byte encoding of an encrypted signature")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut bytes = [0u8; 65];
+                for (i, byte) in bytes.iter_mut().enumerate() {
+                    *byte = seq
+                        .next_element()?
+                        .ok_or_else(|| serde::de::Error::invalid_length(i, &self))?;
+                }
+                EncryptedSignature::from_bytes(bytes).ok_or_else(|| {
+                    serde::de::Error::custom("invalid byte encoding of an encrypted signature")
+                })
+            }
+        }
+
+        deserializer.deserialize_tuple(65, BytesVisitor(core::marker::PhantomData))
+    }
+}
+
+/// An [`EncryptedSignature`] that has been verified against a particular verification key,
+/// message and encryption key.
+///
+/// The only way to get one of these is [`Adaptor::check_encrypted_signature`] so APIs that take a
+/// `CheckedEncryptedSignature` know it has been verified. It deliberately doesn't implement
+/// `Deserialize`: store the [`EncryptedSignature`] and check it again after loading it.
+///
+/// [`Adaptor::check_encrypted_signature`]: crate::adaptor::Adaptor::check_encrypted_signature
+#[derive(Clone, Debug, PartialEq)]
+pub struct CheckedEncryptedSignature {
+    pub(crate) encrypted_signature: EncryptedSignature,
+    pub(crate) verification_key: Point<EvenY>,
+    pub(crate) encryption_key: Point,
+}
+
+impl CheckedEncryptedSignature {
+    /// The encrypted signature that was checked.
+    pub fn encrypted_signature(&self) -> &EncryptedSignature {
+        &self.encrypted_signature
+    }
+
+    /// The key the decrypted signature will be valid under.
+    pub fn verification_key(&self) -> Point<EvenY> {
+        self.verification_key
+    }
+
+    /// The encryption key the signature was checked against.
+    pub fn encryption_key(&self) -> Point {
+        self.encryption_key
+    }
+
+    /// Decrypts the signature.
+    ///
+    /// Returns `None` if `decryption_key` doesn't match [`encryption_key`]. Otherwise the
+    /// signature is valid on the message the signature was checked against under
+    /// [`verification_key`].
+    ///
+    /// [`encryption_key`]: Self::encryption_key
+    /// [`verification_key`]: Self::verification_key
+    pub fn decrypt(&self, decryption_key: &Scalar<impl Secrecy>) -> Option<Signature> {
+        if g!(decryption_key * G) != self.encryption_key {
+            return None;
+        }
+        let EncryptedSignature {
+            R,
+            s_hat,
+            needs_negation,
+        } = &self.encrypted_signature;
+        let mut y = decryption_key.clone();
+        y.conditional_negate(*needs_negation);
+        Some(Signature {
+            R: *R,
+            s: s!(s_hat + y).mark::<Public>(),
+        })
+    }
+
+    /// Recovers the decryption key from the decrypted `signature`.
+    ///
+    /// Returns `None` if `signature` is not the decryption of this encrypted signature.
+    pub fn recover_decryption_key(&self, signature: &Signature<impl Secrecy>) -> Option<Scalar> {
+        if signature.R != self.encrypted_signature.R {
+            return None;
+        }
+        let mut y = s!(signature.s - self.encrypted_signature.s_hat);
+        y.conditional_negate(self.encrypted_signature.needs_negation);
+        if g!(y * G) == self.encryption_key {
+            Some(y.expect_nonzero("unreachable - encryption_key is NonZero and y*G equals it"))
+        } else {
+            None
+        }
+    }
+}
+
+impl From<CheckedEncryptedSignature> for EncryptedSignature {
+    fn from(checked: CheckedEncryptedSignature) -> Self {
+        checked.encrypted_signature
+    }
+}
+
+/// Why [`Adaptor::try_recover_decryption_key`] didn't return the decryption key.
+///
+/// [`Adaptor::try_recover_decryption_key`]: crate::adaptor::Adaptor::try_recover_decryption_key
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecoveryError {
+    /// The signature has a different `R` so it wasn't decrypted from the encrypted signature.
+    NonceMismatch,
+    /// The signature has the right `R` but neither its `s` nor its negation is `s_hat` plus the
+    /// discrete logarithm of the encryption key. It was decrypted with a key for a different
+    /// encryption key or isn't a decryption at all.
+    EncryptionKeyMismatch,
+    /// The signature is the decryption with its `s` negated. This is not a valid signature but
+    /// anyone who could negate `s` already knew the decryption key so it is returned here.
+    NegatedSignature {
+        /// The decryption key.
+        decryption_key: Scalar,
+    },
+}
+
+impl core::fmt::Display for RecoveryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            RecoveryError::NonceMismatch => write!(
+                f,
+                "signature nonce doesn't match the encrypted signature's nonce"
+            ),
+            RecoveryError::EncryptionKeyMismatch => write!(
+                f,
+                "signature was not decrypted with the key for the encryption key"
+            ),
+            RecoveryError::NegatedSignature { .. } => {
+                write!(f, "signature is the decryption with s negated")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecoveryError {}
+
+pub(crate) fn try_recover_decryption_key(
+    encryption_key: &Point<impl PointType, impl Secrecy>,
+    encrypted_signature: &EncryptedSignature<impl Secrecy>,
+    signature: &Signature<impl Secrecy>,
+) -> Result<Scalar, RecoveryError> {
+    if signature.R != encrypted_signature.R {
+        return Err(RecoveryError::NonceMismatch);
+    }
+    let EncryptedSignature {
+        s_hat,
+        needs_negation,
+        ..
+    } = encrypted_signature;
+    let s = &signature.s;
+
+    let mut y = s!(s - s_hat);
+    y.conditional_negate(*needs_negation);
+    if g!(y * G) == *encryption_key {
+        return Ok(y.expect_nonzero("unreachable - encryption_key is NonZero and y*G equals it"));
+    }
+
+    // -s = s_hat + y (with y negated if needs_negation)
+    let mut y = s!(s + s_hat);
+    y.conditional_negate(!*needs_negation);
+    if g!(y * G) == *encryption_key {
+        return Err(RecoveryError::NegatedSignature {
+            decryption_key: y
+                .expect_nonzero("unreachable - encryption_key is NonZero and y*G equals it"),
+        });
+    }
+
+    Err(RecoveryError::EncryptionKeyMismatch)
+}
+
 #[cfg(test)]
 mod test {
     #[cfg(feature = "serde")]
@@ -54,5 +354,60 @@ mod test {
         assert_eq!(serialized.len(), 65);
         let deserialized = bincode::deserialize::<EncryptedSignature>(&serialized).unwrap();
         assert_eq!(encrypted_signature, deserialized);
+        assert_eq!(serialized, encrypted_signature.to_bytes());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn encrypted_signature_json() {
+        use super::*;
+        use crate::{adaptor::*, fun::Scalar, Message};
+        let schnorr = crate::test_instance!();
+        let kp = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let encryption_key = Point::random(&mut rand::thread_rng());
+        let encrypted_signature = schnorr.encrypted_sign(
+            &kp,
+            &encryption_key,
+            Message::<Public>::plain("test", b"foo"),
+        );
+        let json = serde_json::to_value(&encrypted_signature).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "R": secp256kfun::hex::encode(&encrypted_signature.R.to_xonly_bytes()),
+                "s_hat": secp256kfun::hex::encode(&encrypted_signature.s_hat.to_bytes()),
+                "needs_negation": encrypted_signature.needs_negation,
+            })
+        );
+        let deserialized = serde_json::from_value::<EncryptedSignature>(json.clone()).unwrap();
+        assert_eq!(deserialized, encrypted_signature);
+
+        let mut extra_field = json;
+        extra_field["foo"] = serde_json::json!(1);
+        assert!(serde_json::from_value::<EncryptedSignature>(extra_field).is_err());
+    }
+
+    #[test]
+    fn encrypted_signature_bytes() {
+        use super::*;
+        let encrypted_signature = EncryptedSignature::<Public> {
+            R: Point::random(&mut rand::thread_rng())
+                .into_point_with_even_y()
+                .0,
+            s_hat: Scalar::random(&mut rand::thread_rng()).mark::<(Public, Zero)>(),
+            needs_negation: true,
+        };
+        let bytes = encrypted_signature.to_bytes();
+        assert_eq!(bytes[64], 1);
+        assert_eq!(
+            EncryptedSignature::from_bytes(bytes),
+            Some(encrypted_signature)
+        );
+        let mut bad_flag = bytes;
+        bad_flag[64] = 2;
+        assert_eq!(EncryptedSignature::<Public>::from_bytes(bad_flag), None);
+        let mut bad_s_hat = bytes;
+        bad_s_hat[32..64].copy_from_slice(&[0xff; 32]);
+        assert_eq!(EncryptedSignature::<Public>::from_bytes(bad_s_hat), None);
     }
 }
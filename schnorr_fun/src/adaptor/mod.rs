@@ -52,14 +52,19 @@ use crate::{
         derive_nonce,
         digest::{generic_array::typenum::U32, Digest},
         g,
+        hash::{tags::TapTweak, HashAdd, Tagged},
         marker::*,
         nonce::NonceGen,
-        s, Point, Scalar, XOnlyKeyPair, G,
+        s,
+        transcript::Transcript,
+        Point, Scalar, XOnlyKeyPair, G,
     },
-    Message, Schnorr, Signature,
+    Message, Schnorr, Signature, VerifyError,
 };
 mod encrypted_signature;
-pub use encrypted_signature::EncryptedSignature;
+pub use encrypted_signature::{CheckedEncryptedSignature, EncryptedSignature, RecoveryError};
+#[cfg(feature = "alloc")]
+pub mod threshold;
 
 /// Extension trait for [`Schnorr`] to add the encrypted signing algorithm.
 ///
@@ -76,11 +81,54 @@ pub trait EncryptedSign {
         encryption_key: &Point<Normal, impl Secrecy>,
         message: Message<'_, impl Secrecy>,
     ) -> EncryptedSignature;
+
+    /// Like [`encrypted_sign`] but mixes `session_id` into the nonce derivation.
+    ///
+    /// With a deterministic [`NonceGen`], `encrypted_sign` always produces the same encrypted
+    /// signature for the same keypair, encryption key and message. That is fine for a single
+    /// session but if an application needs to hand out several independent encrypted signatures
+    /// on the same tuple (e.g. retries of a swap) it can give each one a distinct `session_id`
+    /// and get a distinct nonce without needing an RNG. Signing twice with the same `session_id`
+    /// gives the same encrypted signature.
+    ///
+    /// # Example
+    /// ```
+    /// use schnorr_fun::{
+    ///     adaptor::{Adaptor, EncryptedSign},
+    ///     fun::{marker::*, Scalar},
+    ///     Message,
+    /// };
+    /// let schnorr = schnorr_fun::test_instance!();
+    /// let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+    /// let encryption_key = schnorr.encryption_key_for(&Scalar::random(&mut rand::thread_rng()));
+    /// let message = Message::<Public>::plain("test", b"retry me");
+    /// let first =
+    ///     schnorr.encrypted_sign_with_session_id(&keypair, &encryption_key, message, b"attempt-1");
+    /// let second =
+    ///     schnorr.encrypted_sign_with_session_id(&keypair, &encryption_key, message, b"attempt-2");
+    /// assert_ne!(first.R, second.R);
+    /// assert!(schnorr.verify_encrypted_signature(
+    ///     &keypair.public_key(),
+    ///     &encryption_key,
+    ///     message,
+    ///     &second
+    /// ));
+    /// ```
+    ///
+    /// [`encrypted_sign`]: EncryptedSign::encrypted_sign
+    /// [`NonceGen`]: crate::fun::nonce::NonceGen
+    fn encrypted_sign_with_session_id(
+        &self,
+        signing_keypair: &XOnlyKeyPair,
+        encryption_key: &Point<Normal, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+        session_id: &[u8],
+    ) -> EncryptedSignature;
 }
 
 impl<NG, CH> EncryptedSign for Schnorr<CH, NG>
 where
-    CH: Digest<OutputSize = U32> + Clone,
+    CH: Transcript,
     NG: NonceGen,
 {
     fn encrypted_sign(
@@ -92,12 +140,116 @@ where
         let (x, X) = signing_key.as_tuple();
         let Y = encryption_key;
 
-        let mut r = derive_nonce!(
+        let r = derive_nonce!(
             nonce_gen => self.nonce_gen(),
             secret => x,
             public => [X, Y, message]
         );
 
+        self.encrypted_sign_with_nonce(signing_key, r, encryption_key, message)
+    }
+
+    fn encrypted_sign_with_session_id(
+        &self,
+        signing_key: &XOnlyKeyPair,
+        encryption_key: &Point<Normal, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+        session_id: &[u8],
+    ) -> EncryptedSignature {
+        let (x, X) = signing_key.as_tuple();
+        let Y = encryption_key;
+        let sid_len = (session_id.len() as u64).to_be_bytes();
+
+        // The encoding of Y never starts with a zero byte so this can't be made to collide with
+        // the derivation in encrypted_sign.
+        let r = derive_nonce!(
+            nonce_gen => self.nonce_gen(),
+            secret => x,
+            public => [X, [0u8], sid_len, session_id, Y, message]
+        );
+
+        self.encrypted_sign_with_nonce(signing_key, r, encryption_key, message)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<CH, NG> Schnorr<CH, NG>
+where
+    CH: Transcript,
+    NG: NonceGen,
+{
+    /// Creates encrypted signatures on many `messages` under the same keypair and encryption key.
+    ///
+    /// This gives the same encrypted signatures as calling [`encrypted_sign`] on each message (as
+    /// long as the nonce generator is deterministic) but [`begin_derivation`] is only called once
+    /// per batch. The signing key, public key and encryption key are absorbed into the nonce hash
+    /// up front and the resulting state is cloned for each message so the nonce is still bound to
+    /// every message. Nonce generators that draw from a stream (like [`Synthetic`]) only use it
+    /// once for the whole batch. This is useful when pre-signing many transactions under one
+    /// encryption key like the contract execution transactions of a DLC.
+    ///
+    /// # Example
+    /// ```
+    /// use schnorr_fun::{
+    ///     adaptor::{Adaptor, EncryptedSign},
+    ///     fun::{marker::*, Scalar},
+    ///     Message,
+    /// };
+    /// let schnorr = schnorr_fun::test_instance!();
+    /// let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+    /// let encryption_key = schnorr.encryption_key_for(&Scalar::random(&mut rand::thread_rng()));
+    /// let outcomes = [b"heads", b"tails"];
+    /// let messages = outcomes
+    ///     .iter()
+    ///     .map(|outcome| Message::<Public>::plain("my-dlc", &outcome[..]));
+    /// let encrypted_signatures =
+    ///     schnorr.encrypted_sign_batch(&keypair, &encryption_key, messages.clone());
+    /// for (message, encrypted_signature) in messages.zip(&encrypted_signatures) {
+    ///     assert_eq!(
+    ///         encrypted_signature,
+    ///         &schnorr.encrypted_sign(&keypair, &encryption_key, message)
+    ///     );
+    /// }
+    /// ```
+    ///
+    /// [`encrypted_sign`]: EncryptedSign::encrypted_sign
+    /// [`begin_derivation`]: crate::nonce::NonceGen::begin_derivation
+    /// [`Synthetic`]: crate::nonce::Synthetic
+    pub fn encrypted_sign_batch<'a, S: Secrecy + 'a>(
+        &self,
+        signing_keypair: &XOnlyKeyPair,
+        encryption_key: &Point<Normal, impl Secrecy>,
+        messages: impl IntoIterator<Item = Message<'a, S>>,
+    ) -> crate::Vec<EncryptedSignature>
+    where
+        NG::Hash: Clone,
+    {
+        let (x, X) = signing_keypair.as_tuple();
+        let Y = encryption_key;
+        // same inputs in the same order as derive_nonce! in encrypted_sign
+        let nonce_prefix = self.nonce_gen().begin_derivation(x).add(X).add(Y);
+
+        messages
+            .into_iter()
+            .map(|message| {
+                let r = Scalar::from_hash(nonce_prefix.clone().add(message));
+                self.encrypted_sign_with_nonce(signing_keypair, r, encryption_key, message)
+            })
+            .collect()
+    }
+}
+
+impl<NG, CH: Transcript> Schnorr<CH, NG> {
+    fn encrypted_sign_with_nonce(
+        &self,
+        signing_key: &XOnlyKeyPair,
+        mut r: Scalar,
+        encryption_key: &Point<Normal, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+    ) -> EncryptedSignature {
+        let (x, X) = signing_key.as_tuple();
+        let Y = encryption_key;
+
         let R = g!(r * G + Y)
             // R_hat = r * G is sampled pseudorandomly for every Y which means R_hat + Y is also
             // be pseudoranodm and therefore will not be zero.
@@ -121,6 +273,128 @@ where
     }
 }
 
+/// Extension trait for [`Schnorr`] to create encrypted signatures under [BIP-341] tweaked keys.
+///
+/// Signing under a taproot output key means tweaking the secret key with the `TapTweak` hash of
+/// the internal key and merkle root and then negating it if the output key has an odd
+/// y-coordinate. This is independent of (and easy to confuse with) the negation of the decryption
+/// key recorded in [`EncryptedSignature::needs_negation`]. These methods do the tweaking for you.
+///
+/// Only implemented when the challenge hash is a 32-byte [`Digest`] (i.e. SHA256 for BIP-341).
+///
+/// # Example
+/// ```
+/// use schnorr_fun::{
+///     adaptor::{Adaptor, EncryptedSign, EncryptedSignTweaked},
+///     fun::{marker::*, Scalar},
+///     Message,
+/// };
+/// let schnorr = schnorr_fun::test_instance!();
+/// let internal_keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+/// let merkle_root = Some([42u8; 32]);
+/// let output_key = schnorr.taproot_output_key(&internal_keypair.public_key(), merkle_root);
+/// let decryption_key = Scalar::random(&mut rand::thread_rng());
+/// let encryption_key = schnorr.encryption_key_for(&decryption_key);
+/// let message = Message::<Public>::plain("test", b"spend the taproot output");
+///
+/// let encrypted_signature =
+///     schnorr.encrypted_sign_tweaked(&internal_keypair, merkle_root, &encryption_key, message);
+/// assert!(schnorr.verify_encrypted_signature(
+///     &output_key,
+///     &encryption_key,
+///     message,
+///     &encrypted_signature
+/// ));
+/// let signature = schnorr.decrypt_signature(decryption_key, encrypted_signature);
+/// assert!(schnorr.verify(&output_key, message, &signature));
+/// ```
+///
+/// [BIP-341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+/// [`EncryptedSignature::needs_negation`]: crate::adaptor::EncryptedSignature::needs_negation
+/// [`Digest`]: crate::fun::digest::Digest
+pub trait EncryptedSignTweaked {
+    /// Returns the [BIP-341] output key for `internal_key` committing to `merkle_root` (`None` for
+    /// a key path only output).
+    ///
+    /// [BIP-341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+    fn taproot_output_key(
+        &self,
+        internal_key: &Point<EvenY>,
+        merkle_root: Option<[u8; 32]>,
+    ) -> Point<EvenY>;
+
+    /// Creates a signature on `message` under the [`taproot_output_key`] of `signing_keypair`
+    /// encrypted under `encryption_key`.
+    ///
+    /// [`taproot_output_key`]: Self::taproot_output_key
+    fn encrypted_sign_tweaked(
+        &self,
+        signing_keypair: &XOnlyKeyPair,
+        merkle_root: Option<[u8; 32]>,
+        encryption_key: &Point<Normal, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+    ) -> EncryptedSignature;
+}
+
+impl<CH, NG> EncryptedSignTweaked for Schnorr<CH, NG>
+where
+    CH: Transcript + Tagged + Digest<OutputSize = U32>,
+    NG: NonceGen,
+{
+    fn taproot_output_key(
+        &self,
+        internal_key: &Point<EvenY>,
+        merkle_root: Option<[u8; 32]>,
+    ) -> Point<EvenY> {
+        let t = tap_tweak::<CH>(internal_key, merkle_root);
+        g!(internal_key + t * G)
+            .normalize()
+            .mark::<NonZero>()
+            .expect("computationally unreachable")
+            .into_point_with_even_y()
+            .0
+    }
+
+    fn encrypted_sign_tweaked(
+        &self,
+        signing_keypair: &XOnlyKeyPair,
+        merkle_root: Option<[u8; 32]>,
+        encryption_key: &Point<Normal, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+    ) -> EncryptedSignature {
+        let (x, P) = signing_keypair.as_tuple();
+        let t = tap_tweak::<CH>(&P, merkle_root);
+        // x is already negated so that P has an even y-coordinate. XOnlyKeyPair::new negates x + t
+        // again if the output key has an odd y-coordinate.
+        let tweaked_keypair = XOnlyKeyPair::new(
+            s!(x + t)
+                .mark::<NonZero>()
+                .expect("computationally unreachable"),
+        );
+        self.encrypted_sign(&tweaked_keypair, encryption_key, message)
+    }
+}
+
+/// The [BIP-341] `TapTweak` of `internal_key` and `merkle_root`.
+///
+/// [BIP-341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+fn tap_tweak<H: Tagged + Digest<OutputSize = U32>>(
+    internal_key: &Point<EvenY>,
+    merkle_root: Option<[u8; 32]>,
+) -> Scalar<Public, Zero> {
+    let mut hash = H::default()
+        .tagged_with::<TapTweak>()
+        .add(&internal_key.to_xonly_bytes()[..]);
+    if let Some(merkle_root) = merkle_root {
+        hash = hash.add(&merkle_root[..]);
+    }
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(hash.finalize().as_slice());
+    Scalar::from_bytes(bytes)
+        .expect("computationally unreachable")
+        .mark::<Public>()
+}
+
 /// Extension trait adding the algorithms for the adaptor signature scheme to instances of [`Schnorr`].
 pub trait Adaptor {
     /// Derives the public encryption key corresponding to a secret decryption key.
@@ -146,7 +420,87 @@ pub trait Adaptor {
         encryption_key: &Point<impl PointType, impl Secrecy>,
         message: Message<'_, impl Secrecy>,
         encrypted_signature: &EncryptedSignature<impl Secrecy>,
-    ) -> bool;
+    ) -> bool {
+        self.verify_encrypted_signature_detailed(
+            verification_key,
+            encryption_key,
+            message,
+            encrypted_signature,
+        )
+        .is_ok()
+    }
+
+    /// Verifies an encrypted signature like [`verify_encrypted_signature`] but says why it is
+    /// invalid.
+    ///
+    /// The only failures possible are [`VerifyError::ImpliedNonceIsZero`] and
+    /// [`VerifyError::NonceMismatch`].
+    ///
+    /// [`verify_encrypted_signature`]: Adaptor::verify_encrypted_signature
+    fn verify_encrypted_signature_detailed(
+        &self,
+        verification_key: &Point<EvenY, impl Secrecy>,
+        encryption_key: &Point<impl PointType, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+        encrypted_signature: &EncryptedSignature<impl Secrecy>,
+    ) -> Result<(), VerifyError>;
+
+    /// Verifies an encrypted signature like [`verify_encrypted_signature`] and returns it as a
+    /// [`CheckedEncryptedSignature`] bound to the keys it was checked against.
+    ///
+    /// Returns `None` if the encrypted signature is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schnorr_fun::{
+    ///     adaptor::{Adaptor, EncryptedSign},
+    ///     fun::{marker::*, Scalar},
+    ///     Message,
+    /// };
+    /// # let schnorr = schnorr_fun::test_instance!();
+    /// let signing_keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+    /// let decryption_key = Scalar::random(&mut rand::thread_rng());
+    /// let encryption_key = schnorr.encryption_key_for(&decryption_key);
+    /// let message = Message::<Public>::plain("text-bitcoin", b"send 1 BTC to Bob");
+    /// let encrypted_signature = schnorr.encrypted_sign(&signing_keypair, &encryption_key, message);
+    /// let checked = schnorr
+    ///     .check_encrypted_signature(
+    ///         &signing_keypair.public_key(),
+    ///         &encryption_key,
+    ///         message,
+    ///         encrypted_signature,
+    ///     )
+    ///     .expect("it's valid");
+    /// let signature = checked
+    ///     .decrypt(&decryption_key)
+    ///     .expect("right decryption key");
+    /// assert!(schnorr.verify(&signing_keypair.public_key(), message, &signature));
+    /// ```
+    ///
+    /// [`verify_encrypted_signature`]: Adaptor::verify_encrypted_signature
+    fn check_encrypted_signature(
+        &self,
+        verification_key: &Point<EvenY>,
+        encryption_key: &Point,
+        message: Message<'_, impl Secrecy>,
+        encrypted_signature: EncryptedSignature,
+    ) -> Option<CheckedEncryptedSignature> {
+        if self.verify_encrypted_signature(
+            verification_key,
+            encryption_key,
+            message,
+            &encrypted_signature,
+        ) {
+            Some(CheckedEncryptedSignature {
+                encrypted_signature,
+                verification_key: *verification_key,
+                encryption_key: *encryption_key,
+            })
+        } else {
+            None
+        }
+    }
 
     /// Decrypts an encrypted signature yielding the signature.
     ///
@@ -186,24 +540,79 @@ pub trait Adaptor {
         encrypted_signature: &EncryptedSignature<impl Secrecy>,
         signature: &Signature<impl Secrecy>,
     ) -> Option<Scalar>;
+
+    /// Like [`recover_decryption_key`] but explains why the key couldn't be recovered.
+    ///
+    /// `Ok(decryption_key)` has the same meaning as `Some(decryption_key)` from
+    /// [`recover_decryption_key`]. If `signature` is the decryption with its `s` negated (a
+    /// malleation of the signature that only someone who already knows the decryption key can
+    /// make) the key is returned in [`RecoveryError::NegatedSignature`] since `signature` is not
+    /// valid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schnorr_fun::{
+    ///     adaptor::{Adaptor, EncryptedSign, RecoveryError},
+    ///     fun::{marker::*, s, Scalar},
+    ///     Message, Signature,
+    /// };
+    /// # let schnorr = schnorr_fun::test_instance!();
+    /// let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+    /// let decryption_key = Scalar::random(&mut rand::thread_rng());
+    /// let encryption_key = schnorr.encryption_key_for(&decryption_key);
+    /// let message = Message::<Public>::plain("test", b"pay Bob");
+    /// let encrypted_signature = schnorr.encrypted_sign(&keypair, &encryption_key, message);
+    /// let signature = schnorr.decrypt_signature(decryption_key.clone(), encrypted_signature.clone());
+    /// assert_eq!(
+    ///     schnorr.try_recover_decryption_key(&encryption_key, &encrypted_signature, &signature),
+    ///     Ok(decryption_key.clone())
+    /// );
+    /// let negated = Signature {
+    ///     R: signature.R,
+    ///     s: s!(-signature.s).mark::<Public>(),
+    /// };
+    /// assert_eq!(
+    ///     schnorr.try_recover_decryption_key(&encryption_key, &encrypted_signature, &negated),
+    ///     Err(RecoveryError::NegatedSignature { decryption_key })
+    /// );
+    /// let other_signature = schnorr.sign(&keypair, message);
+    /// assert_eq!(
+    ///     schnorr.try_recover_decryption_key(&encryption_key, &encrypted_signature, &other_signature),
+    ///     Err(RecoveryError::NonceMismatch)
+    /// );
+    /// ```
+    ///
+    /// [`recover_decryption_key`]: Adaptor::recover_decryption_key
+    fn try_recover_decryption_key(
+        &self,
+        encryption_key: &Point<impl PointType, impl Secrecy>,
+        encrypted_signature: &EncryptedSignature<impl Secrecy>,
+        signature: &Signature<impl Secrecy>,
+    ) -> Result<Scalar, RecoveryError> {
+        encrypted_signature::try_recover_decryption_key(
+            encryption_key,
+            encrypted_signature,
+            signature,
+        )
+    }
 }
 
 impl<CH, NG> Adaptor for Schnorr<CH, NG>
 where
-    CH: Digest<OutputSize = U32> + Clone,
+    CH: Transcript,
 {
     fn encryption_key_for(&self, decryption_key: &Scalar) -> Point {
         g!(decryption_key * G).normalize()
     }
 
-    #[must_use]
-    fn verify_encrypted_signature(
+    fn verify_encrypted_signature_detailed(
         &self,
         verification_key: &Point<EvenY, impl Secrecy>,
         encryption_key: &Point<impl PointType, impl Secrecy>,
         message: Message<'_, impl Secrecy>,
         encrypted_signature: &EncryptedSignature<impl Secrecy>,
-    ) -> bool {
+    ) -> Result<(), VerifyError> {
         let EncryptedSignature {
             R,
             s_hat,
@@ -218,7 +627,14 @@ where
 
         let c = self.challenge(R, &X, message);
 
-        R_hat == g!(s_hat * G - c * X)
+        let R_hat_implied = g!(s_hat * G - c * X);
+        if R_hat == R_hat_implied {
+            Ok(())
+        } else if R_hat_implied.is_zero() {
+            Err(VerifyError::ImpliedNonceIsZero)
+        } else {
+            Err(VerifyError::NonceMismatch)
+        }
     }
 
     fn decrypt_signature(
@@ -322,4 +738,269 @@ mod test {
             .expect("recovery works");
         assert_eq!(rec_decryption_key, decryption_key);
     }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn encrypted_sign_batch() {
+        use crate::Vec;
+        let schnorr = Schnorr::<Sha256, Synthetic<Sha256, GlobalRng<ThreadRng>>>::default();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let encryption_key = schnorr.encryption_key_for(&Scalar::random(&mut rand::thread_rng()));
+        let outcomes = (0..10u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+        let messages = outcomes
+            .iter()
+            .map(|outcome| Message::<Public>::plain("test", &outcome[..]))
+            .collect::<Vec<_>>();
+        let encrypted_signatures =
+            schnorr.encrypted_sign_batch(&keypair, &encryption_key, messages.iter().cloned());
+        assert_eq!(encrypted_signatures.len(), messages.len());
+        assert_ne!(encrypted_signatures[0].R, encrypted_signatures[1].R);
+        for (message, encrypted_signature) in messages.iter().zip(&encrypted_signatures) {
+            assert!(schnorr.verify_encrypted_signature(
+                &keypair.public_key(),
+                &encryption_key,
+                *message,
+                encrypted_signature
+            ));
+        }
+        assert!(schnorr
+            .encrypted_sign_batch(&keypair, &encryption_key, Vec::<Message>::new())
+            .is_empty());
+    }
+
+    #[cfg(feature = "alloc")]
+    fn assert_encrypted_sign_batch_nonces_distinct<NG>(schnorr: Schnorr<Sha256, NG>)
+    where
+        NG: NonceGen,
+        NG::Hash: Clone,
+    {
+        use crate::Vec;
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let encryption_key = schnorr.encryption_key_for(&Scalar::random(&mut rand::thread_rng()));
+        let outcomes = (0..10u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+        let messages = outcomes
+            .iter()
+            .map(|outcome| Message::<Public>::plain("test", &outcome[..]))
+            .collect::<Vec<_>>();
+        let encrypted_signatures =
+            schnorr.encrypted_sign_batch(&keypair, &encryption_key, messages.iter().cloned());
+        for (i, encrypted_signature) in encrypted_signatures.iter().enumerate() {
+            assert!(schnorr.verify_encrypted_signature(
+                &keypair.public_key(),
+                &encryption_key,
+                messages[i],
+                encrypted_signature
+            ));
+            for other in &encrypted_signatures[..i] {
+                assert_ne!(encrypted_signature.R, other.R);
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn encrypted_sign_batch_distinct_nonces_for_every_nonce_gen() {
+        use crate::nonce::{ChaCha20, Rfc6979};
+        assert_encrypted_sign_batch_nonces_distinct(
+            Schnorr::<Sha256, Deterministic<Sha256>>::default(),
+        );
+        assert_encrypted_sign_batch_nonces_distinct(Schnorr::<Sha256, Rfc6979<Sha256>>::default());
+        assert_encrypted_sign_batch_nonces_distinct(Schnorr::<Sha256, _>::new(
+            ChaCha20::<Sha256>::new([42u8; 32]),
+        ));
+        #[cfg(feature = "std")]
+        assert_encrypted_sign_batch_nonces_distinct(Schnorr::<Sha256, _>::new(
+            crate::nonce::Guarded::new(Deterministic::<Sha256>::default()),
+        ));
+    }
+
+    #[test]
+    fn recover_decryption_key_from_negated_signature() {
+        let schnorr = Schnorr::<Sha256, Deterministic<Sha256>>::default();
+        let keypair = schnorr.new_keypair(Scalar::from(42u32).non_zero().unwrap());
+        let message = Message::<Public>::plain("test", b"negated");
+        // these decryption keys are fixed so that both values of needs_negation are covered
+        for (decryption_key, needs_negation) in [(1u32, false), (3, true)] {
+            let decryption_key = Scalar::from(decryption_key).non_zero().unwrap();
+            let encryption_key = schnorr.encryption_key_for(&decryption_key);
+            let encrypted_signature = schnorr.encrypted_sign(&keypair, &encryption_key, message);
+            assert_eq!(encrypted_signature.needs_negation, needs_negation);
+            let signature =
+                schnorr.decrypt_signature(decryption_key.clone(), encrypted_signature.clone());
+            let negated = Signature {
+                R: signature.R,
+                s: s!(-signature.s).mark::<Public>(),
+            };
+            assert!(!schnorr.verify(&keypair.public_key(), message, &negated));
+            // the strict entry point only accepts the valid signature
+            assert_eq!(
+                schnorr.recover_decryption_key(&encryption_key, &encrypted_signature, &negated),
+                None
+            );
+            assert_eq!(
+                schnorr.try_recover_decryption_key(&encryption_key, &encrypted_signature, &negated),
+                Err(RecoveryError::NegatedSignature {
+                    decryption_key: decryption_key.clone()
+                })
+            );
+            assert_eq!(
+                schnorr.try_recover_decryption_key(
+                    &encryption_key,
+                    &encrypted_signature,
+                    &signature
+                ),
+                Ok(decryption_key)
+            );
+
+            let other_key = schnorr.encryption_key_for(&Scalar::from(7u32).non_zero().unwrap());
+            assert_eq!(
+                schnorr.try_recover_decryption_key(&other_key, &encrypted_signature, &signature),
+                Err(RecoveryError::EncryptionKeyMismatch)
+            );
+        }
+    }
+
+    #[test]
+    fn checked_encrypted_signature() {
+        let schnorr = Schnorr::<Sha256, Deterministic<Sha256>>::default();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let decryption_key = Scalar::random(&mut rand::thread_rng());
+        let encryption_key = schnorr.encryption_key_for(&decryption_key);
+        let message = Message::<Public>::plain("test", b"checked");
+        let encrypted_signature = schnorr.encrypted_sign(&keypair, &encryption_key, message);
+
+        // re-encrypt it under a related key to show why the keys must be bound
+        let mut tweak = Scalar::random(&mut rand::thread_rng());
+        let tweaked_key = g!(encryption_key + tweak * G)
+            .normalize()
+            .mark::<NonZero>()
+            .unwrap();
+        let mut tweaked = encrypted_signature.clone();
+        tweak.conditional_negate(!tweaked.needs_negation);
+        tweaked.s_hat = s!(tweaked.s_hat + tweak).mark::<Public>();
+        assert!(schnorr
+            .check_encrypted_signature(&keypair.public_key(), &tweaked_key, message, tweaked)
+            .is_some());
+
+        let other_message = Message::<Public>::plain("test", b"other");
+        assert!(schnorr
+            .check_encrypted_signature(
+                &keypair.public_key(),
+                &encryption_key,
+                other_message,
+                encrypted_signature.clone()
+            )
+            .is_none());
+
+        let checked = schnorr
+            .check_encrypted_signature(
+                &keypair.public_key(),
+                &encryption_key,
+                message,
+                encrypted_signature,
+            )
+            .unwrap();
+        assert_eq!(checked.encryption_key(), encryption_key);
+        assert!(checked
+            .decrypt(&Scalar::random(&mut rand::thread_rng()))
+            .is_none());
+        let signature = checked.decrypt(&decryption_key).unwrap();
+        assert!(schnorr.verify(&checked.verification_key(), message, &signature));
+        assert_eq!(
+            checked.recover_decryption_key(&signature),
+            Some(decryption_key)
+        );
+        assert_eq!(
+            checked.recover_decryption_key(&Signature::random(&mut rand::thread_rng())),
+            None
+        );
+    }
+
+    #[test]
+    fn encrypted_sign_with_session_id_is_deterministic_per_session() {
+        let schnorr = Schnorr::<Sha256, Deterministic<Sha256>>::default();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let decryption_key = Scalar::random(&mut rand::thread_rng());
+        let encryption_key = schnorr.encryption_key_for(&decryption_key);
+        let message = Message::<Public>::plain("test", b"repeat");
+        let sign = |session_id: &[u8]| {
+            schnorr.encrypted_sign_with_session_id(&keypair, &encryption_key, message, session_id)
+        };
+
+        assert_eq!(sign(b"one"), sign(b"one"));
+        assert_ne!(sign(b"one").R, sign(b"two").R);
+        assert_ne!(sign(b"").R, sign(b"one").R);
+        assert_ne!(
+            sign(b"").R,
+            schnorr.encrypted_sign(&keypair, &encryption_key, message).R
+        );
+        for session_id in [&b"one"[..], b"two"] {
+            let encrypted_signature = sign(session_id);
+            assert!(schnorr.verify_encrypted_signature(
+                &keypair.public_key(),
+                &encryption_key,
+                message,
+                &encrypted_signature
+            ));
+            let signature = schnorr.decrypt_signature(decryption_key.clone(), encrypted_signature);
+            assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+        }
+    }
+
+    #[test]
+    fn encrypted_sign_tweaked_with_every_negation() {
+        let schnorr = crate::test_instance!();
+        let mut seen = [[false; 2]; 2];
+        while seen.iter().flatten().any(|seen| !seen) {
+            let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+            let merkle_root = Some([7u8; 32]);
+            let (output_key, output_negated) = g!({ keypair.public_key() } + {
+                tap_tweak::<Sha256>(&keypair.public_key(), merkle_root)
+            } * G)
+            .normalize()
+            .mark::<NonZero>()
+            .unwrap()
+            .into_point_with_even_y();
+            assert_eq!(
+                schnorr.taproot_output_key(&keypair.public_key(), merkle_root),
+                output_key
+            );
+
+            let decryption_key = Scalar::random(&mut rand::thread_rng());
+            let encryption_key = schnorr.encryption_key_for(&decryption_key);
+            let message = Message::<Public>::plain("test", b"tweaked");
+            let encrypted_signature =
+                schnorr.encrypted_sign_tweaked(&keypair, merkle_root, &encryption_key, message);
+            seen[output_negated as usize][encrypted_signature.needs_negation as usize] = true;
+            assert!(schnorr.verify_encrypted_signature(
+                &output_key,
+                &encryption_key,
+                message,
+                &encrypted_signature
+            ));
+            let signature = schnorr.decrypt_signature(decryption_key, encrypted_signature);
+            assert!(schnorr.verify(&output_key, message, &signature));
+            assert!(!schnorr.verify(&keypair.public_key(), message, &signature));
+        }
+    }
+
+    #[test]
+    fn taproot_output_key_bip341_vector() {
+        // from the BIP-341 wallet test vectors (scriptPubKey index 0 and 1)
+        let internal_key = Point::<EvenY>::from_xonly_bytes(
+            crate::fun::hex::decode_array(
+                "d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let output_key = crate::test_instance!().taproot_output_key(&internal_key, None);
+        assert_eq!(
+            output_key.to_xonly_bytes(),
+            crate::fun::hex::decode_array::<32>(
+                "53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343"
+            )
+            .unwrap()
+        );
+    }
 }
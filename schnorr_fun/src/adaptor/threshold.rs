@@ -0,0 +1,317 @@
+//! Threshold decryption of encrypted signatures.
+//!
+//! The decryption key `y` is split into Shamir shares so that any `t` of the `n` share holders can
+//! decrypt an [`EncryptedSignature`] under `Y = y * G` but fewer than `t` of them can't. The dealer
+//! publishes the [`PointPoly`] committing to the sharing polynomial (its first point is `Y`) so each
+//! share holder can check their share and everyone can check the partial decryptions.
+//!
+//! Decrypting a signature reveals `y` to anyone who has the encrypted signature (that's the point
+//! of adaptor signatures) so a [`PartialDecryption`] is just the share holder's share of `y`. It
+//! is checked against the commitment before it's used so a faulty share holder can't make the
+//! decryption fail without being identified.
+//!
+//! # Synopsis
+//!
+//! ```
+//! use schnorr_fun::{
+//!     adaptor::{threshold, Adaptor, EncryptedSign},
+//!     fun::{marker::*, Scalar},
+//!     Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let decryption_key = Scalar::random(&mut rand::thread_rng());
+//! // the dealer splits the decryption key 2-of-3
+//! let (commitment, shares) =
+//!     threshold::share_decryption_key(&decryption_key, 2, 3, &mut rand::thread_rng());
+//! assert!(shares.iter().all(|share| share.verify(&commitment)));
+//! let encryption_key = threshold::encryption_key(&commitment);
+//!
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let message = Message::<Public>::plain("test", b"pay the escrow");
+//! let encrypted_signature = schnorr.encrypted_sign(&keypair, &encryption_key, message);
+//!
+//! // any two share holders decrypt it
+//! let partials = [shares[0].partial_decrypt(), shares[2].partial_decrypt()];
+//! let signature =
+//!     threshold::decrypt_signature(&schnorr, &commitment, &partials, encrypted_signature)
+//!         .unwrap();
+//! assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+//! ```
+//!
+//! [`EncryptedSignature`]: crate::adaptor::EncryptedSignature
+use crate::{
+    adaptor::{Adaptor, EncryptedSignature},
+    frost::{lagrange_coefficients, PointPoly, ScalarPoly},
+    fun::{marker::*, rand_core::RngCore, s, Point, Scalar},
+    Signature, Vec,
+};
+
+/// Splits `decryption_key` into `n_parties` shares any `threshold` of which can decrypt.
+///
+/// The share at position `i` in the returned vector has index `i + 1`. The returned [`PointPoly`]
+/// is the commitment to the sharing polynomial which should be given to all share holders.
+///
+/// # Panics
+///
+/// If `threshold` is zero or more than `n_parties`.
+pub fn share_decryption_key(
+    decryption_key: &Scalar,
+    threshold: u32,
+    n_parties: u32,
+    rng: &mut impl RngCore,
+) -> (PointPoly, Vec<DecryptionKeyShare>) {
+    assert!(
+        threshold > 0 && threshold <= n_parties,
+        "threshold must be between 1 and the number of parties"
+    );
+    let poly = ScalarPoly::random_using_secret(threshold, decryption_key.clone(), rng);
+    let shares = (1..=n_parties)
+        .map(|index| DecryptionKeyShare {
+            index,
+            share: poly.eval(index),
+        })
+        .collect();
+    (poly.to_point_poly(), shares)
+}
+
+/// The encryption key `Y` that the shares in `commitment` decrypt.
+pub fn encryption_key(commitment: &PointPoly) -> Point {
+    commitment.points()[0]
+}
+
+/// A share holder's share of the decryption key.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct DecryptionKeyShare {
+    index: u32,
+    share: Scalar<Secret, Zero>,
+}
+
+impl DecryptionKeyShare {
+    /// Creates a share from its index and value.
+    pub fn new(index: u32, share: Scalar<Secret, Zero>) -> Self {
+        DecryptionKeyShare { index, share }
+    }
+
+    /// The index (the x-coordinate the polynomial was evaluated at) of the share.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The share itself.
+    pub fn share(&self) -> &Scalar<Secret, Zero> {
+        &self.share
+    }
+
+    /// Checks the share against the dealer's commitment.
+    #[must_use]
+    pub fn verify(&self, commitment: &PointPoly) -> bool {
+        commitment.verify_share(&index_scalar(self.index), &self.share)
+    }
+
+    /// Creates this share holder's contribution to decrypting a signature.
+    pub fn partial_decrypt(&self) -> PartialDecryption {
+        PartialDecryption {
+            index: self.index,
+            share: self.share.clone().mark::<Public>(),
+        }
+    }
+}
+
+/// A share holder's contribution to decrypting a signature.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct PartialDecryption {
+    /// The index of the share holder.
+    pub index: u32,
+    /// The share holder's share of the decryption key.
+    pub share: Scalar<Public, Zero>,
+}
+
+impl PartialDecryption {
+    /// Checks the partial decryption against the dealer's commitment.
+    #[must_use]
+    pub fn verify(&self, commitment: &PointPoly) -> bool {
+        self.index != 0 && commitment.verify_share(&index_scalar(self.index), &self.share)
+    }
+}
+
+/// Error returned by [`recover_decryption_key`] and [`decrypt_signature`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdDecryptionError {
+    /// There were fewer partial decryptions than the threshold.
+    NotEnoughShares {
+        /// The number of partial decryptions.
+        got: usize,
+        /// The threshold.
+        need: usize,
+    },
+    /// Two partial decryptions had the same index.
+    DuplicateIndex(u32),
+    /// The partial decryption from the share holder at this index doesn't match the commitment.
+    InvalidPartialDecryption(u32),
+}
+
+impl core::fmt::Display for ThresholdDecryptionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            ThresholdDecryptionError::NotEnoughShares { got, need } => {
+                write!(f, "got {} partial decryptions but {} are needed", got, need)
+            }
+            ThresholdDecryptionError::DuplicateIndex(index) => {
+                write!(f, "more than one partial decryption has index {}", index)
+            }
+            ThresholdDecryptionError::InvalidPartialDecryption(index) => write!(
+                f,
+                "the partial decryption with index {} doesn't match the commitment",
+                index
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ThresholdDecryptionError {}
+
+/// Recombines the decryption key from at least a threshold of partial decryptions.
+///
+/// Every partial decryption is checked against `commitment` first. If there are more than enough
+/// only the first threshold of them are used.
+pub fn recover_decryption_key(
+    commitment: &PointPoly,
+    partials: &[PartialDecryption],
+) -> Result<Scalar, ThresholdDecryptionError> {
+    let threshold = commitment.poly_len();
+    if partials.len() < threshold {
+        return Err(ThresholdDecryptionError::NotEnoughShares {
+            got: partials.len(),
+            need: threshold,
+        });
+    }
+    for (i, partial) in partials.iter().enumerate() {
+        if partials[..i]
+            .iter()
+            .any(|other| other.index == partial.index)
+        {
+            return Err(ThresholdDecryptionError::DuplicateIndex(partial.index));
+        }
+        if !partial.verify(commitment) {
+            return Err(ThresholdDecryptionError::InvalidPartialDecryption(
+                partial.index,
+            ));
+        }
+    }
+
+    let partials = &partials[..threshold];
+    let indices = partials
+        .iter()
+        .map(|partial| partial.index)
+        .collect::<Vec<_>>();
+    let decryption_key = lagrange_coefficients(&indices).iter().zip(partials).fold(
+        Scalar::zero(),
+        |acc, (lambda, partial)| {
+            let y_i = &partial.share;
+            s!(acc + lambda * y_i)
+        },
+    );
+    Ok(decryption_key
+        .non_zero()
+        .expect("the shares match a commitment to a non-zero key"))
+}
+
+/// Decrypts `encrypted_signature` with the decryption key recombined from `partials`.
+///
+/// See [`recover_decryption_key`] for how `partials` are checked.
+pub fn decrypt_signature(
+    adaptor: &impl Adaptor,
+    commitment: &PointPoly,
+    partials: &[PartialDecryption],
+    encrypted_signature: EncryptedSignature<impl Secrecy>,
+) -> Result<Signature, ThresholdDecryptionError> {
+    let decryption_key = recover_decryption_key(commitment, partials)?;
+    Ok(adaptor.decrypt_signature(decryption_key, encrypted_signature))
+}
+
+fn index_scalar(index: u32) -> Scalar<Public, Zero> {
+    Scalar::from(index).mark::<Public>()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{adaptor::EncryptedSign, Message};
+
+    #[test]
+    fn threshold_decryption() {
+        let schnorr = crate::test_instance!();
+        let decryption_key = Scalar::random(&mut rand::thread_rng());
+        let (commitment, shares) =
+            share_decryption_key(&decryption_key, 3, 5, &mut rand::thread_rng());
+        assert_eq!(shares.len(), 5);
+        assert!(shares.iter().all(|share| share.verify(&commitment)));
+        let encryption_key = encryption_key(&commitment);
+        assert_eq!(encryption_key, schnorr.encryption_key_for(&decryption_key));
+
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"threshold");
+        let encrypted_signature = schnorr.encrypted_sign(&keypair, &encryption_key, message);
+        let partials = shares
+            .iter()
+            .map(DecryptionKeyShare::partial_decrypt)
+            .collect::<Vec<_>>();
+
+        for subset in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let subset = subset
+                .iter()
+                .map(|i| partials[*i].clone())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                recover_decryption_key(&commitment, &subset),
+                Ok(decryption_key.clone())
+            );
+            let signature =
+                decrypt_signature(&schnorr, &commitment, &subset, encrypted_signature.clone())
+                    .unwrap();
+            assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+        }
+        // more than enough is fine
+        assert_eq!(
+            recover_decryption_key(&commitment, &partials),
+            Ok(decryption_key.clone())
+        );
+
+        assert_eq!(
+            recover_decryption_key(&commitment, &partials[..2]),
+            Err(ThresholdDecryptionError::NotEnoughShares { got: 2, need: 3 })
+        );
+        let duplicate = [
+            partials[0].clone(),
+            partials[1].clone(),
+            partials[0].clone(),
+        ];
+        assert_eq!(
+            recover_decryption_key(&commitment, &duplicate),
+            Err(ThresholdDecryptionError::DuplicateIndex(1))
+        );
+        let mut bad = partials[..3].to_vec();
+        bad[1].share = Scalar::random(&mut rand::thread_rng()).mark::<(Public, Zero)>();
+        assert_eq!(
+            recover_decryption_key(&commitment, &bad),
+            Err(ThresholdDecryptionError::InvalidPartialDecryption(2))
+        );
+        let mut bad = partials[..3].to_vec();
+        bad[0].index = 0;
+        assert_eq!(
+            recover_decryption_key(&commitment, &bad),
+            Err(ThresholdDecryptionError::InvalidPartialDecryption(0))
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! ChaCha20-Poly1305 ([RFC 8439]) built on [`secp256kfun::nonce::chacha20_block`].
+//!
+//! [RFC 8439]: https://www.rfc-editor.org/rfc/rfc8439
+use crate::fun::{nonce::chacha20_block, subtle::ConstantTimeEq};
+
+/// The length of the Poly1305 tag.
+pub(crate) const TAG_LEN: usize = 16;
+
+/// Encrypts `data` in place and returns the tag over it and `associated_data`.
+pub(crate) fn seal(
+    key: &[u8; 32],
+    nonce: &[u8; 12],
+    associated_data: &[u8],
+    data: &mut [u8],
+) -> [u8; TAG_LEN] {
+    apply_keystream(key, nonce, data);
+    tag(key, nonce, associated_data, data)
+}
+
+/// Checks `tag` and decrypts `data` in place. `data` is left as it was if the tag is invalid.
+pub(crate) fn open(
+    key: &[u8; 32],
+    nonce: &[u8; 12],
+    associated_data: &[u8],
+    data: &mut [u8],
+    received_tag: &[u8],
+) -> Result<(), InvalidTag> {
+    let expected_tag = tag(key, nonce, associated_data, data);
+    if !bool::from(expected_tag.ct_eq(received_tag)) {
+        return Err(InvalidTag);
+    }
+    apply_keystream(key, nonce, data);
+    Ok(())
+}
+
+/// The tag passed to [`open`] didn't match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct InvalidTag;
+
+/// XORs `data` with the ChaCha20 keystream starting at block 1 (block 0 is for the Poly1305 key).
+fn apply_keystream(key: &[u8; 32], nonce: &[u8; 12], data: &mut [u8]) {
+    for (i, chunk) in data.chunks_mut(64).enumerate() {
+        let block = chacha20_block(key, i as u32 + 1, nonce);
+        for (byte, key_byte) in chunk.iter_mut().zip(block.iter()) {
+            *byte ^= key_byte;
+        }
+    }
+}
+
+fn tag(
+    key: &[u8; 32],
+    nonce: &[u8; 12],
+    associated_data: &[u8],
+    ciphertext: &[u8],
+) -> [u8; TAG_LEN] {
+    let mut poly_key = [0u8; 32];
+    poly_key.copy_from_slice(&chacha20_block(key, 0, nonce)[..32]);
+    let mut lengths = [0u8; 16];
+    lengths[..8].copy_from_slice(&(associated_data.len() as u64).to_le_bytes());
+    lengths[8..].copy_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+
+    let mut poly = Poly1305::new(&poly_key);
+    // each of these is zero padded to a multiple of 16 bytes
+    poly.update_padded(associated_data);
+    poly.update_padded(ciphertext);
+    poly.update_padded(&lengths);
+    poly.finalize()
+}
+
+/// Poly1305 with 26-bit limbs.
+struct Poly1305 {
+    r: [u32; 5],
+    h: [u32; 5],
+    pad: [u32; 4],
+}
+
+impl Poly1305 {
+    fn new(key: &[u8; 32]) -> Self {
+        let word = |i: usize| u32::from_le_bytes([key[i], key[i + 1], key[i + 2], key[i + 3]]);
+        // r is clamped as the RFC requires
+        let r = [
+            word(0) & 0x3ff_ffff,
+            (word(3) >> 2) & 0x3ff_ff03,
+            (word(6) >> 4) & 0x3ff_c0ff,
+            (word(9) >> 6) & 0x3f0_3fff,
+            (word(12) >> 8) & 0x00f_ffff,
+        ];
+        Self {
+            r,
+            h: [0; 5],
+            pad: [word(16), word(20), word(24), word(28)],
+        }
+    }
+
+    /// Absorbs `data` as if it was zero padded to a multiple of 16 bytes.
+    fn update_padded(&mut self, data: &[u8]) {
+        for chunk in data.chunks(16) {
+            let mut block = [0u8; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            self.block(&block, 1 << 24);
+        }
+    }
+
+    /// Adds `block` to the accumulator and multiplies by `r`. `high_bit` is the bit above the
+    /// block (`1 << 24` for a full block) in the top limb.
+    fn block(&mut self, block: &[u8; 16], high_bit: u32) {
+        let word =
+            |i: usize| u32::from_le_bytes([block[i], block[i + 1], block[i + 2], block[i + 3]]);
+        let [r0, r1, r2, r3, r4] = self.r.map(u64::from);
+        let (s1, s2, s3, s4) = (r1 * 5, r2 * 5, r3 * 5, r4 * 5);
+
+        let h = &mut self.h;
+        h[0] += word(0) & 0x3ff_ffff;
+        h[1] += (word(3) >> 2) & 0x3ff_ffff;
+        h[2] += (word(6) >> 4) & 0x3ff_ffff;
+        h[3] += (word(9) >> 6) & 0x3ff_ffff;
+        h[4] += (word(12) >> 8) | high_bit;
+        let [h0, h1, h2, h3, h4] = h.map(u64::from);
+
+        let d0 = h0 * r0 + h1 * s4 + h2 * s3 + h3 * s2 + h4 * s1;
+        let mut d1 = h0 * r1 + h1 * r0 + h2 * s4 + h3 * s3 + h4 * s2;
+        let mut d2 = h0 * r2 + h1 * r1 + h2 * r0 + h3 * s4 + h4 * s3;
+        let mut d3 = h0 * r3 + h1 * r2 + h2 * r1 + h3 * r0 + h4 * s4;
+        let mut d4 = h0 * r4 + h1 * r3 + h2 * r2 + h3 * r1 + h4 * r0;
+
+        // partially reduce mod 2^130 - 5
+        d1 += d0 >> 26;
+        d2 += d1 >> 26;
+        d3 += d2 >> 26;
+        d4 += d3 >> 26;
+        let mut h0 = (d0 & 0x3ff_ffff) + (d4 >> 26) * 5;
+        let h1 = (d1 & 0x3ff_ffff) + (h0 >> 26);
+        h0 &= 0x3ff_ffff;
+        *h = [
+            h0 as u32,
+            h1 as u32,
+            (d2 & 0x3ff_ffff) as u32,
+            (d3 & 0x3ff_ffff) as u32,
+            (d4 & 0x3ff_ffff) as u32,
+        ];
+    }
+
+    fn finalize(self) -> [u8; TAG_LEN] {
+        let [mut h0, mut h1, mut h2, mut h3, mut h4] = self.h;
+
+        // fully carry h
+        h2 += h1 >> 26;
+        h1 &= 0x3ff_ffff;
+        h3 += h2 >> 26;
+        h2 &= 0x3ff_ffff;
+        h4 += h3 >> 26;
+        h3 &= 0x3ff_ffff;
+        h0 += (h4 >> 26) * 5;
+        h4 &= 0x3ff_ffff;
+        h1 += h0 >> 26;
+        h0 &= 0x3ff_ffff;
+
+        // g = h + 5 - 2^130 and use it instead of h if it didn't go negative
+        let mut g0 = h0 + 5;
+        let mut g1 = h1 + (g0 >> 26);
+        g0 &= 0x3ff_ffff;
+        let mut g2 = h2 + (g1 >> 26);
+        g1 &= 0x3ff_ffff;
+        let mut g3 = h3 + (g2 >> 26);
+        g2 &= 0x3ff_ffff;
+        let mut g4 = (h4 + (g3 >> 26)).wrapping_sub(1 << 26);
+        g3 &= 0x3ff_ffff;
+        let use_g = (g4 >> 31).wrapping_sub(1);
+        g4 &= use_g;
+        let h0 = (h0 & !use_g) | (g0 & use_g);
+        let h1 = (h1 & !use_g) | (g1 & use_g);
+        let h2 = (h2 & !use_g) | (g2 & use_g);
+        let h3 = (h3 & !use_g) | (g3 & use_g);
+        let h4 = (h4 & !use_g) | g4;
+
+        // h mod 2^128 as 32-bit words then add the pad
+        let words = [
+            h0 | (h1 << 26),
+            (h1 >> 6) | (h2 << 20),
+            (h2 >> 12) | (h3 << 14),
+            (h3 >> 18) | (h4 << 8),
+        ];
+        let mut tag = [0u8; TAG_LEN];
+        let mut carry = 0u64;
+        for (i, (word, pad)) in words.iter().zip(self.pad.iter()).enumerate() {
+            carry += u64::from(*word) + u64::from(*pad);
+            tag[i * 4..(i + 1) * 4].copy_from_slice(&(carry as u32).to_le_bytes());
+            carry >>= 32;
+        }
+        tag
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::hex;
+
+    #[test]
+    fn poly1305_test_vector() {
+        // RFC 8439 section 2.5.2
+        let key =
+            hex::decode_array("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b")
+                .unwrap();
+        let message = b"Cryptographic Forum Research Group";
+        let mut poly = Poly1305::new(&key);
+        for chunk in message.chunks(16) {
+            if chunk.len() == 16 {
+                poly.block(chunk.try_into().unwrap(), 1 << 24);
+            } else {
+                // a final partial block gets a 1 byte appended instead of the high bit
+                let mut block = [0u8; 16];
+                block[..chunk.len()].copy_from_slice(chunk);
+                block[chunk.len()] = 1;
+                poly.block(&block, 0);
+            }
+        }
+        assert_eq!(
+            poly.finalize(),
+            hex::decode_array::<16>("a8061dc1305136c6c22b8baf0c0127a9").unwrap()
+        );
+    }
+
+    #[test]
+    fn aead_test_vector() {
+        // RFC 8439 section 2.8.2
+        let key =
+            hex::decode_array("808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f")
+                .unwrap();
+        let nonce = hex::decode_array("070000004041424344454647").unwrap();
+        let associated_data = hex::decode("50515253c0c1c2c3c4c5c6c7").unwrap();
+        let plaintext: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you \
+                                only one tip for the future, sunscreen would be it.";
+        let mut data = plaintext.to_vec();
+        let tag = seal(&key, &nonce, &associated_data, &mut data);
+        assert_eq!(
+            hex::encode(&data),
+            "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6\
+             3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36\
+             92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc\
+             3ff4def08e4b7a9de576d26586cec64b6116"
+        );
+        assert_eq!(
+            tag,
+            hex::decode_array::<16>("1ae10b594f09e26a7e902ecbd0600691").unwrap()
+        );
+
+        assert_eq!(open(&key, &nonce, b"", &mut data, &tag), Err(InvalidTag));
+        assert_eq!(
+            open(&key, &nonce, &associated_data, &mut data, &tag),
+            Ok(())
+        );
+        assert_eq!(data, plaintext);
+    }
+}
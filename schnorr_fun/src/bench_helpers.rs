@@ -0,0 +1,166 @@
+//! Deterministic workloads for benchmarking.
+//!
+//! Benchmarks are only comparable between runs (and between machines) if they do the same work.
+//! Variable time operations like verification take a different amount of time for different keys
+//! and signatures so using random inputs or tiny scalars like `1` gives noisy or misleading
+//! numbers. A [`Workload`] is generated from a seed by hashing so it looks random but is the same
+//! every time.
+//!
+//! This is what the benchmarks in `benches/` use. Enable the `bench-helpers` feature to use it in
+//! your own.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::bench_helpers::Workload;
+//! let schnorr = schnorr_fun::test_instance!();
+//! let workload = Workload::new::<sha2::Sha256, _, _>(&schnorr, b"my-bench", 10);
+//! for i in 0..workload.len() {
+//!     assert!(schnorr.verify(
+//!         &workload.keypairs[i].public_key(),
+//!         workload.message(i),
+//!         &workload.signatures[i]
+//!     ));
+//! }
+//! ```
+use crate::{
+    adaptor::{Adaptor, EncryptedSign, EncryptedSignature},
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        g,
+        hash::HashAdd,
+        marker::*,
+        nonce::NonceGen,
+        transcript::Transcript,
+        Point, Scalar, XOnlyKeyPair, G,
+    },
+    Message, Schnorr, Signature, Vec,
+};
+
+/// Derives the `i`th deterministic secret scalar labelled `label` from `seed`.
+pub fn scalar<H: Digest<OutputSize = U32> + Default>(seed: &[u8], label: &str, i: u32) -> Scalar {
+    Scalar::from_hash(H::default().add(seed).add(label).add(i.to_be_bytes()))
+}
+
+/// Derives the `i`th deterministic point labelled `label` from `seed`.
+pub fn point<H: Digest<OutputSize = U32> + Default>(seed: &[u8], label: &str, i: u32) -> Point {
+    let x = scalar::<H>(seed, label, i);
+    g!(x * G).normalize()
+}
+
+/// A set of keys, messages, signatures and encrypted signatures for benchmarking.
+///
+/// Entry `i` of each list belongs together: `signatures[i]` is `keypairs[i]`'s signature on
+/// [`message(i)`] and `encrypted_signatures[i]` is the same encrypted under `encryption_keys[i]`.
+///
+/// [`message(i)`]: Workload::message
+#[derive(Clone, Debug)]
+pub struct Workload {
+    /// The signing keys.
+    pub keypairs: Vec<XOnlyKeyPair>,
+    /// The message bodies. Use [`Workload::message`] to get them as a [`Message`].
+    pub messages: Vec<[u8; 32]>,
+    /// Signatures on each message.
+    pub signatures: Vec<Signature>,
+    /// Decryption keys for the encrypted signatures.
+    pub decryption_keys: Vec<Scalar>,
+    /// The encryption key of each decryption key.
+    pub encryption_keys: Vec<Point>,
+    /// Signatures on each message encrypted under the encryption key.
+    pub encrypted_signatures: Vec<EncryptedSignature>,
+}
+
+impl Workload {
+    /// Generates a workload of `n` entries from `seed`.
+    ///
+    /// The same `seed`, `n` and hash `H` always give the same workload. The signatures depend on
+    /// `schnorr`'s nonce generator so use a deterministic one if you need them to be the same too.
+    pub fn new<H, CH, NG>(schnorr: &Schnorr<CH, NG>, seed: &[u8], n: u32) -> Self
+    where
+        H: Digest<OutputSize = U32> + Default,
+        CH: Transcript,
+        NG: NonceGen,
+    {
+        let keypairs = (0..n)
+            .map(|i| schnorr.new_keypair(scalar::<H>(seed, "key", i)))
+            .collect::<Vec<_>>();
+        let messages = (0..n)
+            .map(|i| scalar::<H>(seed, "message", i).to_bytes())
+            .collect::<Vec<_>>();
+        let decryption_keys = (0..n)
+            .map(|i| scalar::<H>(seed, "decryption-key", i))
+            .collect::<Vec<_>>();
+        let encryption_keys = decryption_keys
+            .iter()
+            .map(|y| schnorr.encryption_key_for(y))
+            .collect::<Vec<_>>();
+
+        let mut workload = Workload {
+            keypairs,
+            messages,
+            signatures: Vec::with_capacity(n as usize),
+            decryption_keys,
+            encryption_keys,
+            encrypted_signatures: Vec::with_capacity(n as usize),
+        };
+
+        for i in 0..workload.len() {
+            let message = workload.message(i);
+            let keypair = &workload.keypairs[i];
+            let signature = schnorr.sign(keypair, message);
+            let encrypted_signature =
+                schnorr.encrypted_sign(keypair, &workload.encryption_keys[i], message);
+            workload.signatures.push(signature);
+            workload.encrypted_signatures.push(encrypted_signature);
+        }
+
+        workload
+    }
+
+    /// The number of entries in the workload.
+    pub fn len(&self) -> usize {
+        self.keypairs.len()
+    }
+
+    /// Whether the workload has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.keypairs.is_empty()
+    }
+
+    /// The `i`th message.
+    ///
+    /// This is a [`Message::raw`] so other BIP340 implementations can sign and verify the same
+    /// messages for comparison.
+    pub fn message(&self, i: usize) -> Message<'_, Public> {
+        Message::<Public>::raw(&self.messages[i])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn workloads_are_deterministic_and_valid() {
+        let schnorr = crate::test_instance!();
+        let workload = Workload::new::<Sha256, _, _>(&schnorr, b"test", 3);
+        let again = Workload::new::<Sha256, _, _>(&schnorr, b"test", 3);
+        let other = Workload::new::<Sha256, _, _>(&schnorr, b"other", 3);
+        assert_eq!(workload.len(), 3);
+        assert_eq!(workload.signatures, again.signatures);
+        assert_ne!(workload.messages, other.messages);
+        assert_ne!(workload.messages[0], workload.messages[1]);
+
+        for i in 0..workload.len() {
+            let public_key = workload.keypairs[i].public_key();
+            assert!(schnorr.verify(&public_key, workload.message(i), &workload.signatures[i]));
+            assert!(schnorr.verify_encrypted_signature(
+                &public_key,
+                &workload.encryption_keys[i],
+                workload.message(i),
+                &workload.encrypted_signatures[i]
+            ));
+        }
+    }
+}
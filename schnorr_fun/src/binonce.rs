@@ -5,7 +5,16 @@
 //! Derived binonces should be unique and and must not be reused for signing under any circumstances
 //! as this can leak your secret key.
 use crate::Message;
-use secp256kfun::{derive_nonce, g, marker::*, nonce::NonceGen, Point, Scalar, G};
+use secp256kfun::{
+    commit::{Commitment, Committer},
+    derive_nonce,
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    hash::Tagged,
+    marker::*,
+    nonce::NonceGen,
+    Point, Scalar, G,
+};
 
 /// A nonce (pair of points) that each party must share with the others in the first stage of signing.
 ///
@@ -176,3 +185,114 @@ secp256kfun::impl_display_serialize! {
         nkp.to_bytes()
     }
 }
+
+/// A commitment to a [`Nonce`] created with [`NonceCommitter::commit`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NonceCommitment(pub [u8; 32]);
+
+impl NonceCommitment {
+    /// Serializes the commitment as 32 bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Deserializes the commitment from 32 bytes.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        NonceCommitment(bytes)
+    }
+}
+
+secp256kfun::impl_fromstr_deserialize! {
+    name => "nonce commitment",
+    fn from_bytes(bytes: [u8;32]) -> Option<NonceCommitment> {
+        Some(NonceCommitment::from_bytes(bytes))
+    }
+}
+
+secp256kfun::impl_display_debug_serialize! {
+    fn to_bytes(commitment: &NonceCommitment) -> [u8;32] {
+        commitment.to_bytes()
+    }
+}
+
+/// Commit-reveal exchange of public nonces.
+///
+/// MuSig2 and FROST don't need nonce commitments to be secure but when a signer doesn't trust the
+/// transport (or the other party) to deliver messages in order it can be useful to commit to
+/// nonces before revealing them, e.g. to stop a party choosing their nonce after seeing ours.
+/// Each party sends a [`NonceCommitment`] first and only reveals their [`Nonce`] once they have
+/// received everyone else's commitment. The commitment is a tagged hash of the signer's public key,
+/// the session id and the nonce so it can't be replayed by another signer or in another session.
+///
+/// # Example
+///
+/// ```
+/// use schnorr_fun::{
+///     binonce::{NonceCommitter, NonceKeyPair},
+///     fun::{nonce::Deterministic, KeyPair, Scalar},
+/// };
+/// use sha2::Sha256;
+/// let committer = NonceCommitter::<Sha256>::default();
+/// let nonce_gen = Deterministic::<Sha256>::default();
+/// let alice = KeyPair::new(Scalar::random(&mut rand::thread_rng()));
+/// let session_id = b"payment-channel-update-42";
+/// let alice_nonce = NonceKeyPair::generate(
+///     &nonce_gen,
+///     alice.secret_key(),
+///     session_id,
+///     Some(alice.public_key()),
+///     None,
+/// );
+/// // round 1: alice sends her commitment to bob
+/// let commitment = committer.commit(&alice.public_key(), session_id, &alice_nonce.public());
+/// // round 2: once bob has sent his commitment alice reveals her nonce
+/// assert!(committer.verify(
+///     &alice.public_key(),
+///     session_id,
+///     &commitment,
+///     &alice_nonce.public()
+/// ));
+/// assert!(!committer.verify(
+///     &alice.public_key(),
+///     b"another-session",
+///     &commitment,
+///     &alice_nonce.public()
+/// ));
+/// ```
+#[derive(Clone, Debug)]
+pub struct NonceCommitter<H> {
+    committer: Committer<H>,
+}
+
+impl<H: Tagged> Default for NonceCommitter<H> {
+    fn default() -> Self {
+        NonceCommitter {
+            committer: Committer::new(b"secp256kfun/binonce/commit"),
+        }
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone> NonceCommitter<H> {
+    /// Commits to `nonce` for the signer with `signer_key` in the session `session_id`.
+    pub fn commit<Z>(
+        &self,
+        signer_key: &Point<impl Normalized>,
+        session_id: &[u8],
+        nonce: &Nonce<Z>,
+    ) -> NonceCommitment {
+        let committer = self.committer.bind(&signer_key.to_bytes()).bind(session_id);
+        NonceCommitment(Commitment::to(&committer, &nonce.to_bytes()[..]).to_bytes())
+    }
+
+    /// Checks that a revealed `nonce` matches the `commitment` the signer sent earlier.
+    #[must_use]
+    pub fn verify<Z>(
+        &self,
+        signer_key: &Point<impl Normalized>,
+        session_id: &[u8],
+        commitment: &NonceCommitment,
+        nonce: &Nonce<Z>,
+    ) -> bool {
+        self.commit(signer_key, session_id, nonce) == *commitment
+    }
+}
@@ -0,0 +1,554 @@
+//! [BIP32] derivation paths.
+//!
+//! A [`DerivationPath`] is a list of [`ChildIndex`]es from a master key to one of its
+//! descendants. Paths parse from and display as the usual `m/86'/0'/0'/0/1` strings and since
+//! they can be built in `const` contexts well known paths can be declared as constants.
+//!
+//! ```
+//! use schnorr_fun::bip32::{ChildIndex, DerivationPath};
+//! const BIP86_ACCOUNT_0: DerivationPath = DerivationPath::from_static(&[
+//!     ChildIndex::hardened(86),
+//!     ChildIndex::hardened(0),
+//!     ChildIndex::hardened(0),
+//! ]);
+//! let path = "m/86'/0'/0'".parse::<DerivationPath>().unwrap();
+//! assert_eq!(path, BIP86_ACCOUNT_0);
+//! let receive = path
+//!     .child(ChildIndex::normal(0))
+//!     .child(ChildIndex::normal(1));
+//! assert_eq!(receive.to_string(), "m/86'/0'/0'/0/1");
+//! assert!(receive.iter().take(3).all(|index| index.is_hardened()));
+//! ```
+//!
+//! [BIP32]: <https://bips.xyz/32>
+use crate::{
+    fun::{
+        base58::{self, Base58Error},
+        g,
+        hash::hmac,
+        marker::*,
+        Point, Scalar, G,
+    },
+    Vec,
+};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::borrow::Cow;
+use core::fmt;
+use sha2::Sha512;
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+
+pub mod descriptor;
+
+/// Indexes at or above this are hardened.
+pub const HARDENED: u32 = 1 << 31;
+
+/// The index of a child key.
+///
+/// The hardened bit is part of the index so `ChildIndex::hardened(0).to_u32() == HARDENED`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct ChildIndex(u32);
+
+impl ChildIndex {
+    /// The `index`th non-hardened child. The top bit of `index` is ignored.
+    pub const fn normal(index: u32) -> Self {
+        ChildIndex(index & !HARDENED)
+    }
+
+    /// The `index`th hardened child (written `index'`). The top bit of `index` is ignored.
+    pub const fn hardened(index: u32) -> Self {
+        ChildIndex(index | HARDENED)
+    }
+
+    /// Interprets `index` as it appears in BIP32 where indexes at or above [`HARDENED`] are
+    /// hardened.
+    pub const fn from_u32(index: u32) -> Self {
+        ChildIndex(index)
+    }
+
+    /// The index as it appears in BIP32 (with the hardened bit).
+    pub const fn to_u32(self) -> u32 {
+        self.0
+    }
+
+    /// Whether this is a hardened index.
+    pub const fn is_hardened(self) -> bool {
+        self.0 >= HARDENED
+    }
+
+    /// The index without the hardened bit.
+    pub const fn position(self) -> u32 {
+        self.0 & !HARDENED
+    }
+}
+
+impl From<u32> for ChildIndex {
+    fn from(index: u32) -> Self {
+        ChildIndex::from_u32(index)
+    }
+}
+
+impl From<ChildIndex> for u32 {
+    fn from(index: ChildIndex) -> Self {
+        index.to_u32()
+    }
+}
+
+impl fmt::Display for ChildIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.position())?;
+        if self.is_hardened() {
+            write!(f, "'")?;
+        }
+        Ok(())
+    }
+}
+
+impl core::str::FromStr for ChildIndex {
+    type Err = ParsePathError;
+
+    /// Parses a decimal index optionally followed by `'`, `h` or `H` to mark it as hardened.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (digits, hardened) = match s.strip_suffix(|c| c == '\'' || c == 'h' || c == 'H') {
+            Some(digits) => (digits, true),
+            None => (s, false),
+        };
+        if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+            return Err(ParsePathError::InvalidIndex);
+        }
+        let position = digits
+            .parse::<u32>()
+            .ok()
+            .filter(|position| *position < HARDENED)
+            .ok_or(ParsePathError::InvalidIndex)?;
+        Ok(if hardened {
+            ChildIndex::hardened(position)
+        } else {
+            ChildIndex::normal(position)
+        })
+    }
+}
+
+/// Error returned when parsing a [`DerivationPath`] or [`ChildIndex`] fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParsePathError {
+    /// The path doesn't start with `m`.
+    MissingMaster,
+    /// An index isn't a decimal number less than 2<sup>31</sup> optionally followed by `'`, `h`
+    /// or `H`.
+    InvalidIndex,
+}
+
+impl fmt::Display for ParsePathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParsePathError::MissingMaster => write!(f, "derivation path must start with m"),
+            ParsePathError::InvalidIndex => write!(f, "invalid child index in derivation path"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParsePathError {}
+
+/// A list of child indexes from a master key to one of its descendants.
+///
+/// See the [module documentation](crate::bip32).
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct DerivationPath(Cow<'static, [ChildIndex]>);
+
+impl DerivationPath {
+    /// The empty path (`m`).
+    pub const MASTER: Self = DerivationPath(Cow::Borrowed(&[]));
+
+    /// Creates a path from a static list of indexes. This is a `const fn` so you can use it to
+    /// declare constant paths.
+    pub const fn from_static(indexes: &'static [ChildIndex]) -> Self {
+        DerivationPath(Cow::Borrowed(indexes))
+    }
+
+    /// The indexes in the path starting with the master key's child.
+    pub fn as_slice(&self) -> &[ChildIndex] {
+        &self.0
+    }
+
+    /// Iterates over the indexes in the path.
+    pub fn iter(&self) -> impl Iterator<Item = ChildIndex> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// The number of indexes in the path.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether this is the path to the master key itself.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether any index in the path is hardened.
+    pub fn has_hardened(&self) -> bool {
+        self.iter().any(ChildIndex::is_hardened)
+    }
+
+    /// The path to the child `index` of the key at the end of this path.
+    pub fn child(&self, index: ChildIndex) -> Self {
+        let mut indexes = self.0.to_vec();
+        indexes.push(index);
+        DerivationPath(Cow::Owned(indexes))
+    }
+
+    /// This path followed by `path`.
+    pub fn extend(&self, path: &DerivationPath) -> Self {
+        self.iter().chain(path.iter()).collect()
+    }
+}
+
+impl Default for DerivationPath {
+    fn default() -> Self {
+        DerivationPath::MASTER
+    }
+}
+
+impl From<Vec<ChildIndex>> for DerivationPath {
+    fn from(indexes: Vec<ChildIndex>) -> Self {
+        DerivationPath(Cow::Owned(indexes))
+    }
+}
+
+impl core::iter::FromIterator<ChildIndex> for DerivationPath {
+    fn from_iter<I: IntoIterator<Item = ChildIndex>>(iter: I) -> Self {
+        DerivationPath(Cow::Owned(iter.into_iter().collect()))
+    }
+}
+
+impl<'a> IntoIterator for &'a DerivationPath {
+    type Item = ChildIndex;
+    type IntoIter = core::iter::Copied<core::slice::Iter<'a, ChildIndex>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter().copied()
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for index in self {
+            write!(f, "/{}", index)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DerivationPath({})", self)
+    }
+}
+
+impl core::str::FromStr for DerivationPath {
+    type Err = ParsePathError;
+
+    /// Parses paths like `m/86'/0'/0'/0/1`. Hardened indexes may be marked with `'`, `h` or `H`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('/');
+        if parts.next() != Some("m") {
+            return Err(ParsePathError::MissingMaster);
+        }
+        parts.map(str::parse).collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DerivationPath {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(ChildIndex::to_u32))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DerivationPath {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let indexes = Vec::<u32>::deserialize(deserializer)?;
+        Ok(indexes.into_iter().map(ChildIndex::from_u32).collect())
+    }
+}
+
+/// Error returned when deriving a child key fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeriveError {
+    /// A hardened index was given where a non-hardened one was expected.
+    HardenedIndex,
+    /// A non-hardened index was given where a hardened one was expected.
+    NonHardenedIndex,
+    /// The path has a hardened index but no [`HardeningKey`] was given.
+    ///
+    /// [`HardeningKey`]: crate::frost_bip32::HardeningKey
+    MissingHardeningKey,
+    /// The index gives an invalid key (probability less than 1 in 2<sup>127</sup>). BIP32 says to
+    /// skip to the next index.
+    InvalidChild,
+}
+
+impl fmt::Display for DeriveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeriveError::HardenedIndex => write!(f, "index is hardened"),
+            DeriveError::NonHardenedIndex => write!(f, "index is not hardened"),
+            DeriveError::MissingHardeningKey => {
+                write!(f, "hardened derivation needs a hardening key")
+            }
+            DeriveError::InvalidChild => write!(f, "index gives an invalid child key"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DeriveError {}
+
+/// The public half of a [BIP32] extended key: a public key and a chain code.
+///
+/// [BIP32]: <https://bips.xyz/32>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct ExtendedPublicKey {
+    /// The public key.
+    pub public_key: Point,
+    /// The chain code.
+    pub chain_code: [u8; 32],
+}
+
+impl ExtendedPublicKey {
+    /// Derives a non-hardened child key with BIP32's `CKDpub`.
+    ///
+    /// ## Return value
+    ///
+    /// Returns an error if `index` is hardened or in the unlikely event that it gives an invalid key.
+    pub fn derive_child(&self, index: ChildIndex) -> Result<Self, DeriveError> {
+        if index.is_hardened() {
+            return Err(DeriveError::HardenedIndex);
+        }
+        let I = hmac_sha512(&self.chain_code, &[
+            &self.public_key.to_bytes()[..],
+            &index.to_u32().to_be_bytes()[..],
+        ]);
+        let mut IL = [0u8; 32];
+        let mut chain_code = [0u8; 32];
+        IL.copy_from_slice(&I[..32]);
+        chain_code.copy_from_slice(&I[32..]);
+        let tweak = Scalar::from_bytes(IL).ok_or(DeriveError::InvalidChild)?;
+        let public_key = self.public_key;
+        let public_key = g!(tweak * G + public_key)
+            .normalize()
+            .mark::<NonZero>()
+            .ok_or(DeriveError::InvalidChild)?
+            .mark::<Public>();
+        Ok(ExtendedPublicKey {
+            public_key,
+            chain_code,
+        })
+    }
+
+    /// Derives the key at the end of `path` (relative to this key).
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, DeriveError> {
+        path.iter()
+            .try_fold(*self, |key, index| key.derive_child(index))
+    }
+}
+
+/// The version bytes of a mainnet `xpub`.
+pub const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+/// The version bytes of a testnet `tpub`.
+pub const TPUB_VERSION: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+
+/// An [`ExtendedPublicKey`] with the metadata in its serialization (`xpub...` or `tpub...`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Xpub {
+    /// [`XPUB_VERSION`] or [`TPUB_VERSION`].
+    pub version: [u8; 4],
+    /// How many derivations this key is from the master key.
+    pub depth: u8,
+    /// The first four bytes of the HASH160 of the parent's public key (zero for the master key).
+    pub parent_fingerprint: [u8; 4],
+    /// The index of this key in its parent (zero for the master key).
+    pub child_index: ChildIndex,
+    /// The key.
+    pub key: ExtendedPublicKey,
+}
+
+impl Xpub {
+    /// Serializes the key to its 78 bytes (without the base58check encoding).
+    pub fn to_bytes(&self) -> [u8; 78] {
+        let mut bytes = [0u8; 78];
+        bytes[..4].copy_from_slice(&self.version);
+        bytes[4] = self.depth;
+        bytes[5..9].copy_from_slice(&self.parent_fingerprint);
+        bytes[9..13].copy_from_slice(&self.child_index.to_u32().to_be_bytes());
+        bytes[13..45].copy_from_slice(&self.key.chain_code);
+        bytes[45..].copy_from_slice(&self.key.public_key.to_bytes());
+        bytes
+    }
+
+    /// Deserializes a key from the output of [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: [u8; 78]) -> Result<Self, ParseXpubError> {
+        let mut version = [0u8; 4];
+        version.copy_from_slice(&bytes[..4]);
+        if version != XPUB_VERSION && version != TPUB_VERSION {
+            return Err(ParseXpubError::UnknownVersion);
+        }
+        let depth = bytes[4];
+        let mut parent_fingerprint = [0u8; 4];
+        parent_fingerprint.copy_from_slice(&bytes[5..9]);
+        let mut child_index = [0u8; 4];
+        child_index.copy_from_slice(&bytes[9..13]);
+        let child_index = ChildIndex::from_u32(u32::from_be_bytes(child_index));
+        if depth == 0 && (parent_fingerprint != [0u8; 4] || child_index.to_u32() != 0) {
+            return Err(ParseXpubError::InvalidKey);
+        }
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&bytes[13..45]);
+        let mut public_key = [0u8; 33];
+        public_key.copy_from_slice(&bytes[45..]);
+        let public_key = Point::from_bytes(public_key).ok_or(ParseXpubError::InvalidKey)?;
+        Ok(Xpub {
+            version,
+            depth,
+            parent_fingerprint,
+            child_index,
+            key: ExtendedPublicKey {
+                public_key,
+                chain_code,
+            },
+        })
+    }
+}
+
+impl fmt::Display for Xpub {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", base58::encode_check(&self.to_bytes()))
+    }
+}
+
+impl core::str::FromStr for Xpub {
+    type Err = ParseXpubError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let to_parse_error = |e| match e {
+            Base58Error::InvalidCharacter => ParseXpubError::InvalidBase58,
+            Base58Error::InvalidChecksum => ParseXpubError::InvalidChecksum,
+            Base58Error::InvalidLength | Base58Error::InvalidEncoding => {
+                ParseXpubError::InvalidLength
+            }
+        };
+        // check the length first so a truncated key isn't reported as having a bad checksum
+        if base58::decode(s).map_err(to_parse_error)?.len() != 78 + 4 {
+            return Err(ParseXpubError::InvalidLength);
+        }
+        let payload = base58::decode_check(s).map_err(to_parse_error)?;
+        let payload =
+            <[u8; 78]>::try_from(&payload[..]).map_err(|_| ParseXpubError::InvalidLength)?;
+        Xpub::from_bytes(payload)
+    }
+}
+
+/// Error returned when parsing an [`Xpub`] fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseXpubError {
+    /// A character isn't in the base58 alphabet.
+    InvalidBase58,
+    /// The decoded key isn't 78 bytes long.
+    InvalidLength,
+    /// The checksum doesn't match.
+    InvalidChecksum,
+    /// The version is neither [`XPUB_VERSION`] nor [`TPUB_VERSION`].
+    UnknownVersion,
+    /// The public key is invalid or a master key has a parent fingerprint or child index.
+    InvalidKey,
+}
+
+impl fmt::Display for ParseXpubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseXpubError::InvalidBase58 => write!(f, "invalid base58 character"),
+            ParseXpubError::InvalidLength => write!(f, "extended key is not 78 bytes"),
+            ParseXpubError::InvalidChecksum => write!(f, "invalid base58check checksum"),
+            ParseXpubError::UnknownVersion => write!(f, "not an xpub or tpub"),
+            ParseXpubError::InvalidKey => write!(f, "invalid extended public key"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseXpubError {}
+
+pub(crate) fn hmac_sha512(key: &[u8; 32], data: &[&[u8]]) -> [u8; 64] {
+    hmac::<Sha512>(key, data).into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::str::FromStr;
+
+    #[test]
+    fn parse_and_display_paths() {
+        let path = DerivationPath::from_str("m/86'/0h/0H/0/1").unwrap();
+        assert_eq!(
+            path.iter().map(ChildIndex::to_u32).collect::<Vec<_>>(),
+            vec![HARDENED + 86, HARDENED, HARDENED, 0, 1]
+        );
+        assert_eq!(format!("{}", path), "m/86'/0'/0'/0/1");
+        assert!(path.has_hardened());
+        assert_eq!(DerivationPath::from_str("m"), Ok(DerivationPath::MASTER));
+        assert_eq!(format!("{}", DerivationPath::MASTER), "m");
+
+        for bad in [
+            "",
+            "86'/0'",
+            "M/1",
+            "m/",
+            "m//1",
+            "m/1''",
+            "m/-1",
+            "m/+1",
+            "m/2147483648",
+        ] {
+            assert!(DerivationPath::from_str(bad).is_err(), "{}", bad);
+        }
+        assert_eq!(
+            DerivationPath::from_str("n/1"),
+            Err(ParsePathError::MissingMaster)
+        );
+        assert_eq!(
+            DerivationPath::from_str("m/2147483647'")
+                .unwrap()
+                .as_slice(),
+            &[ChildIndex::from_u32(u32::MAX)]
+        );
+    }
+
+    #[test]
+    fn const_paths() {
+        const PATH: DerivationPath =
+            DerivationPath::from_static(&[ChildIndex::hardened(84), ChildIndex::normal(3)]);
+        assert_eq!(PATH, DerivationPath::from_str("m/84'/3").unwrap());
+        assert_eq!(
+            PATH.child(ChildIndex::normal(7)),
+            PATH.extend(&DerivationPath::from(vec![ChildIndex::from_u32(7)]))
+        );
+        assert_eq!(ChildIndex::hardened(HARDENED + 5), ChildIndex::hardened(5));
+        assert_eq!(ChildIndex::hardened(5).position(), 5);
+        assert!(!ChildIndex::normal(HARDENED + 5).is_hardened());
+    }
+}
@@ -0,0 +1,565 @@
+//! Output descriptor key expressions.
+//!
+//! Parses and serializes the `KEY` expressions of [BIP380] output descriptors like
+//!
+//! ```text
+//! [d34db33f/86'/0'/0']xpub6.../<0;1>/*
+//! ```
+//!
+//! into a [`DescriptorPublicKey`]: an optional [`KeyOrigin`] (the fingerprint of the master key
+//! and the path from it) followed by either a single hex public key or an [`Xpub`] with a path
+//! and an optional wildcard. [BIP389] multipath steps like `<0;1>` (for receive and change
+//! addresses) are supported. Private keys (`xprv` or WIF) are not.
+//!
+//! To get the key at a particular index use [`DescriptorPublicKey::derive`]. If you hold the
+//! secret key (or it is a [FROST key]) use [`DescriptorXpub::paths_from_origin`] to get the path
+//! to derive it at.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::bip32::descriptor::DescriptorPublicKey;
+//! let key = "[d34db33f/86'/0'/0']xpub6ERApfZwUNrhLCkDtcHTcxd75RbzS1ed54G1LkBUHQVHQKqhMkhgbmJbZRkrgZw4koxb5JaHWkY4ALHY2grBGRjaDMzQLcgJvLJuZZvRcEL/<0;1>/*"
+//!     .parse::<DescriptorPublicKey>()
+//!     .unwrap();
+//! // the receive and change keys at index 7
+//! let keys = key.derive(7).unwrap();
+//! assert_eq!(keys.len(), 2);
+//! assert_eq!(key.to_string().parse::<DescriptorPublicKey>(), Ok(key));
+//! ```
+//!
+//! [BIP380]: <https://bips.xyz/380>
+//! [BIP389]: <https://bips.xyz/389>
+//! [FROST key]: crate::frost_bip32::ExtendedFrostKey
+use super::{ChildIndex, DerivationPath, DeriveError, ParsePathError, ParseXpubError, Xpub};
+use crate::{
+    fun::{hex, marker::*, Point},
+    Vec,
+};
+use core::{fmt, str::FromStr};
+
+/// Where a key came from: the fingerprint of the master key and the path from it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyOrigin {
+    /// The first four bytes of the HASH160 of the master public key.
+    pub fingerprint: [u8; 4],
+    /// The path from the master key.
+    pub path: DerivationPath,
+}
+
+/// Whether (and how) a key expression ends with a `*`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wildcard {
+    /// No wildcard: the expression is a single key.
+    None,
+    /// `/*`: the index is a non-hardened child.
+    Unhardened,
+    /// `/*'`: the index is a hardened child.
+    Hardened,
+}
+
+/// A single public key written out in hex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SingleKey {
+    /// A 33 byte compressed key.
+    Compressed(Point),
+    /// A 32 byte x-only key (only allowed in `tr` descriptors).
+    XOnly(Point<EvenY>),
+}
+
+impl SingleKey {
+    /// The key as a normal point.
+    pub fn public_key(&self) -> Point {
+        match self {
+            SingleKey::Compressed(key) => *key,
+            SingleKey::XOnly(key) => (*key).mark::<Normal>(),
+        }
+    }
+}
+
+/// An [`Xpub`] followed by one or more derivation paths and an optional wildcard.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DescriptorXpub {
+    origin: Option<KeyOrigin>,
+    xpub: Xpub,
+    // one path or one for each choice of the multipath step
+    paths: Vec<DerivationPath>,
+    multipath_step: Option<usize>,
+    wildcard: Wildcard,
+}
+
+impl DescriptorXpub {
+    /// Creates a key expression with a single path.
+    pub fn new(
+        origin: Option<KeyOrigin>,
+        xpub: Xpub,
+        path: DerivationPath,
+        wildcard: Wildcard,
+    ) -> Self {
+        DescriptorXpub {
+            origin,
+            xpub,
+            paths: vec![path],
+            multipath_step: None,
+            wildcard,
+        }
+    }
+
+    /// The key's origin.
+    pub fn origin(&self) -> Option<&KeyOrigin> {
+        self.origin.as_ref()
+    }
+
+    /// The extended public key.
+    pub fn xpub(&self) -> &Xpub {
+        &self.xpub
+    }
+
+    /// The paths from the xpub (one for each choice of the multipath step if there is one).
+    pub fn paths(&self) -> &[DerivationPath] {
+        &self.paths
+    }
+
+    /// The wildcard at the end of the expression.
+    pub fn wildcard(&self) -> Wildcard {
+        self.wildcard
+    }
+
+    /// Whether the expression has a multipath step like `<0;1>`.
+    pub fn is_multipath(&self) -> bool {
+        self.multipath_step.is_some()
+    }
+
+    /// Splits a multipath expression into one expression for each path.
+    pub fn into_single_paths(self) -> Vec<DescriptorXpub> {
+        let DescriptorXpub {
+            origin,
+            xpub,
+            paths,
+            wildcard,
+            ..
+        } = self;
+        paths
+            .into_iter()
+            .map(|path| DescriptorXpub::new(origin.clone(), xpub, path, wildcard))
+            .collect()
+    }
+
+    /// The paths from the xpub to the keys at `index`.
+    pub fn paths_at(&self, index: u32) -> Vec<DerivationPath> {
+        self.paths
+            .iter()
+            .map(|path| match self.wildcard {
+                Wildcard::None => path.clone(),
+                Wildcard::Unhardened => path.child(ChildIndex::from_u32(index)),
+                Wildcard::Hardened => path.child(ChildIndex::hardened(index)),
+            })
+            .collect()
+    }
+
+    /// The full paths from the origin master key to the keys at `index` (or from the xpub if
+    /// there is no origin).
+    ///
+    /// Pass these to e.g. [`ExtendedFrostKey::derive_path`] to get the keys from the master key.
+    ///
+    /// [`ExtendedFrostKey::derive_path`]: crate::frost_bip32::ExtendedFrostKey::derive_path
+    pub fn paths_from_origin(&self, index: u32) -> Vec<DerivationPath> {
+        let origin_path = self
+            .origin
+            .as_ref()
+            .map(|origin| origin.path.clone())
+            .unwrap_or_default();
+        self.paths_at(index)
+            .iter()
+            .map(|path| origin_path.extend(path))
+            .collect()
+    }
+
+    /// Derives the public keys at `index` (one for each path).
+    ///
+    /// `index` is ignored if there is no wildcard.
+    ///
+    /// ## Return value
+    ///
+    /// Returns an error if any step of the path or the wildcard is hardened (or `index` is at or
+    /// above [`HARDENED`]) since that needs the secret key.
+    ///
+    /// [`HARDENED`]: super::HARDENED
+    pub fn derive(&self, index: u32) -> Result<Vec<Point>, DeriveError> {
+        self.paths_at(index)
+            .iter()
+            .map(|path| Ok(self.xpub.key.derive_path(path)?.public_key))
+            .collect()
+    }
+}
+
+/// A [BIP380] key expression.
+///
+/// See the [module documentation](crate::bip32::descriptor).
+///
+/// [BIP380]: <https://bips.xyz/380>
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DescriptorPublicKey {
+    /// A hex public key.
+    Single {
+        /// The key's origin.
+        origin: Option<KeyOrigin>,
+        /// The key.
+        key: SingleKey,
+    },
+    /// An extended public key.
+    Xpub(DescriptorXpub),
+}
+
+impl DescriptorPublicKey {
+    /// The key's origin.
+    pub fn origin(&self) -> Option<&KeyOrigin> {
+        match self {
+            DescriptorPublicKey::Single { origin, .. } => origin.as_ref(),
+            DescriptorPublicKey::Xpub(xpub) => xpub.origin(),
+        }
+    }
+
+    /// Whether the expression ends with a wildcard.
+    pub fn has_wildcard(&self) -> bool {
+        match self {
+            DescriptorPublicKey::Single { .. } => false,
+            DescriptorPublicKey::Xpub(xpub) => xpub.wildcard != Wildcard::None,
+        }
+    }
+
+    /// Whether the expression has a multipath step like `<0;1>`.
+    pub fn is_multipath(&self) -> bool {
+        match self {
+            DescriptorPublicKey::Single { .. } => false,
+            DescriptorPublicKey::Xpub(xpub) => xpub.is_multipath(),
+        }
+    }
+
+    /// The public keys at `index`: one for a single key or see [`DescriptorXpub::derive`].
+    pub fn derive(&self, index: u32) -> Result<Vec<Point>, DeriveError> {
+        match self {
+            DescriptorPublicKey::Single { key, .. } => Ok(vec![key.public_key()]),
+            DescriptorPublicKey::Xpub(xpub) => xpub.derive(index),
+        }
+    }
+}
+
+/// Error returned when parsing a [`DescriptorPublicKey`] fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseKeyError {
+    /// The `[fingerprint/path]` origin is malformed.
+    InvalidOrigin,
+    /// A step of a path is invalid.
+    InvalidPath(ParsePathError),
+    /// The key is not valid hex or isn't a valid point.
+    InvalidKey,
+    /// The xpub is invalid.
+    InvalidXpub(ParseXpubError),
+    /// There is more than one multipath step, one with fewer than two choices or one with a
+    /// repeated choice.
+    InvalidMultipath,
+}
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseKeyError::InvalidOrigin => write!(f, "invalid key origin"),
+            ParseKeyError::InvalidPath(e) => write!(f, "{}", e),
+            ParseKeyError::InvalidKey => write!(f, "invalid public key"),
+            ParseKeyError::InvalidXpub(e) => write!(f, "{}", e),
+            ParseKeyError::InvalidMultipath => write!(f, "invalid multipath step"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseKeyError {}
+
+impl From<ParsePathError> for ParseKeyError {
+    fn from(e: ParsePathError) -> Self {
+        ParseKeyError::InvalidPath(e)
+    }
+}
+
+impl FromStr for DescriptorPublicKey {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (origin, rest) = match s.strip_prefix('[') {
+            Some(s) => {
+                let (origin, rest) = s.split_once(']').ok_or(ParseKeyError::InvalidOrigin)?;
+                let mut parts = origin.split('/');
+                let fingerprint = parts
+                    .next()
+                    .and_then(|fingerprint| hex::decode_array(fingerprint).ok())
+                    .ok_or(ParseKeyError::InvalidOrigin)?;
+                let path = parts
+                    .map(ChildIndex::from_str)
+                    .collect::<Result<DerivationPath, _>>()?;
+                (Some(KeyOrigin { fingerprint, path }), rest)
+            }
+            None => (None, s),
+        };
+
+        let mut parts = rest.split('/');
+        let key = parts.next().unwrap_or_default();
+        let steps = parts.collect::<Vec<_>>();
+        if key.len() == 66 || key.len() == 64 {
+            if !steps.is_empty() {
+                return Err(ParseKeyError::InvalidKey);
+            }
+            let key = if key.len() == 66 {
+                SingleKey::Compressed(Point::from_str(key).map_err(|_| ParseKeyError::InvalidKey)?)
+            } else {
+                SingleKey::XOnly(
+                    Point::<EvenY>::from_str(key).map_err(|_| ParseKeyError::InvalidKey)?,
+                )
+            };
+            return Ok(DescriptorPublicKey::Single { origin, key });
+        }
+
+        let xpub = Xpub::from_str(key).map_err(ParseKeyError::InvalidXpub)?;
+        let (steps, wildcard) = match steps.split_last() {
+            Some((&"*", steps)) => (steps, Wildcard::Unhardened),
+            Some((&"*'", steps)) | Some((&"*h", steps)) => (steps, Wildcard::Hardened),
+            _ => (&steps[..], Wildcard::None),
+        };
+        let mut paths = vec![Vec::new()];
+        let mut multipath_step = None;
+        for (position, step) in steps.iter().enumerate() {
+            match step.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+                Some(choices) => {
+                    let choices = choices
+                        .split(';')
+                        .map(ChildIndex::from_str)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let repeated = choices
+                        .iter()
+                        .enumerate()
+                        .any(|(i, choice)| choices[..i].contains(choice));
+                    if multipath_step.is_some() || choices.len() < 2 || repeated {
+                        return Err(ParseKeyError::InvalidMultipath);
+                    }
+                    multipath_step = Some(position);
+                    paths = choices
+                        .iter()
+                        .map(|choice| {
+                            let mut path = paths[0].clone();
+                            path.push(*choice);
+                            path
+                        })
+                        .collect();
+                }
+                None => {
+                    let index = ChildIndex::from_str(step)?;
+                    for path in &mut paths {
+                        path.push(index);
+                    }
+                }
+            }
+        }
+        Ok(DescriptorPublicKey::Xpub(DescriptorXpub {
+            origin,
+            xpub,
+            paths: paths.into_iter().map(DerivationPath::from).collect(),
+            multipath_step,
+            wildcard,
+        }))
+    }
+}
+
+impl fmt::Display for KeyOrigin {
+    /// Writes the origin without the surrounding brackets e.g. `d34db33f/86'/0'/0'`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.fingerprint {
+            write!(f, "{:02x}", byte)?;
+        }
+        for index in &self.path {
+            write!(f, "/{}", index)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DescriptorPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(origin) = self.origin() {
+            write!(f, "[{}]", origin)?;
+        }
+        match self {
+            DescriptorPublicKey::Single {
+                key: SingleKey::Compressed(key),
+                ..
+            } => write!(f, "{}", key),
+            DescriptorPublicKey::Single {
+                key: SingleKey::XOnly(key),
+                ..
+            } => write!(f, "{}", key),
+            DescriptorPublicKey::Xpub(xpub) => {
+                write!(f, "{}", xpub.xpub)?;
+                for (position, index) in xpub.paths[0].iter().enumerate() {
+                    if xpub.multipath_step == Some(position) {
+                        write!(f, "/<")?;
+                        for (i, path) in xpub.paths.iter().enumerate() {
+                            if i > 0 {
+                                write!(f, ";")?;
+                            }
+                            write!(f, "{}", path.as_slice()[position])?;
+                        }
+                        write!(f, ">")?;
+                    } else {
+                        write!(f, "/{}", index)?;
+                    }
+                }
+                match xpub.wildcard {
+                    Wildcard::None => Ok(()),
+                    Wildcard::Unhardened => write!(f, "/*"),
+                    Wildcard::Hardened => write!(f, "/*'"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bip32::HARDENED;
+    #[cfg(feature = "std")]
+    use std::string::ToString;
+
+    // BIP32 test vector 1
+    const M_0H: &str = "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw";
+    const M_0H_1: &str = "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ";
+    const M_0H_1_2H_2: &str = "xpub6FHa3pjLCk84BayeJxFW2SP4XRrFd1JYnxeLeU8EqN3vDfZmbqBqaGJAyiLjTAwm6ZLRQUMv1ZACTj37sR62cfN7fe5JnJ7dh8zL4fiyLHV";
+
+    #[test]
+    fn xpub_round_trip_and_ckdpub_matches_bip32() {
+        let xpub = M_0H.parse::<Xpub>().unwrap();
+        assert_eq!(xpub.to_string(), M_0H);
+        assert_eq!(xpub.depth, 1);
+        assert_eq!(xpub.child_index, ChildIndex::hardened(0));
+
+        let child = M_0H_1.parse::<Xpub>().unwrap();
+        assert_eq!(xpub.key.derive_child(ChildIndex::normal(1)), Ok(child.key));
+        assert_eq!(
+            xpub.key.derive_child(ChildIndex::hardened(1)),
+            Err(DeriveError::HardenedIndex)
+        );
+
+        let mut bad = M_0H.as_bytes().to_vec();
+        bad[100] = if bad[100] == b'a' { b'b' } else { b'a' };
+        assert_eq!(
+            core::str::from_utf8(&bad).unwrap().parse::<Xpub>(),
+            Err(ParseXpubError::InvalidChecksum)
+        );
+        assert_eq!("xpub0".parse::<Xpub>(), Err(ParseXpubError::InvalidBase58));
+        assert_eq!(
+            "xpub661MyMwAqRbcF".parse::<Xpub>(),
+            Err(ParseXpubError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn parse_key_expressions() {
+        let s = format!("[deadbeef/0'/1]{}/2'/2", M_0H_1);
+        let key = s.parse::<DescriptorPublicKey>().unwrap();
+        assert_eq!(key.to_string(), format!("[deadbeef/0'/1]{}/2'/2", M_0H_1));
+        assert!(!key.has_wildcard());
+        // 2' is hardened so we can't derive it from the xpub
+        assert_eq!(key.derive(0), Err(DeriveError::HardenedIndex));
+
+        let key = format!("{}/1/*", M_0H)
+            .parse::<DescriptorPublicKey>()
+            .unwrap();
+        let expected = M_0H
+            .parse::<Xpub>()
+            .unwrap()
+            .key
+            .derive_path(&"m/1/5".parse().unwrap())
+            .unwrap()
+            .public_key;
+        assert_eq!(key.derive(5), Ok(vec![expected]));
+        assert_eq!(key.derive(HARDENED), Err(DeriveError::HardenedIndex));
+
+        let key = format!("[d34db33f/84h/0h/0h]{}/<0;1;7>/*", M_0H)
+            .parse::<DescriptorPublicKey>()
+            .unwrap();
+        assert!(key.is_multipath());
+        assert_eq!(
+            key.to_string(),
+            format!("[d34db33f/84'/0'/0']{}/<0;1;7>/*", M_0H)
+        );
+        let keys = key.derive(3).unwrap();
+        assert_eq!(keys.len(), 3);
+        let xpub = match key {
+            DescriptorPublicKey::Xpub(xpub) => xpub,
+            _ => unreachable!(),
+        };
+        assert_eq!(xpub.paths_from_origin(3), vec![
+            "m/84'/0'/0'/0/3".parse().unwrap(),
+            "m/84'/0'/0'/1/3".parse().unwrap(),
+            "m/84'/0'/0'/7/3".parse().unwrap(),
+        ]);
+        let singles = xpub.into_single_paths();
+        assert_eq!(singles[1].derive(3), Ok(vec![keys[1]]));
+        assert_eq!(
+            DescriptorPublicKey::Xpub(singles[2].clone()).to_string(),
+            format!("[d34db33f/84'/0'/0']{}/7/*", M_0H)
+        );
+
+        let hex_key =
+            "[00000000]0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+                .parse::<DescriptorPublicKey>()
+                .unwrap();
+        assert_eq!(
+            hex_key.to_string(),
+            "[00000000]0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        );
+        let xonly = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+            .parse::<DescriptorPublicKey>()
+            .unwrap();
+        assert_eq!(hex_key.derive(0), xonly.derive(99));
+
+        for (bad, error) in [
+            (format!("[deadbee]{}", M_0H), ParseKeyError::InvalidOrigin),
+            (format!("[deadbeef{}", M_0H), ParseKeyError::InvalidOrigin),
+            (
+                format!("{}/<0;1>/<2;3>", M_0H),
+                ParseKeyError::InvalidMultipath,
+            ),
+            (format!("{}/<0>", M_0H), ParseKeyError::InvalidMultipath),
+            (format!("{}/<1;1>", M_0H), ParseKeyError::InvalidMultipath),
+            (
+                format!("{}/*/1", M_0H),
+                ParseKeyError::InvalidPath(ParsePathError::InvalidIndex),
+            ),
+            (
+                "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798/1".into(),
+                ParseKeyError::InvalidKey,
+            ),
+        ] {
+            assert_eq!(bad.parse::<DescriptorPublicKey>(), Err(error), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn extended_public_key_derive_path() {
+        let xpub = M_0H_1_2H_2.parse::<Xpub>().unwrap();
+        let expected = "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy"
+            .parse::<Xpub>()
+            .unwrap();
+        assert_eq!(
+            xpub.key.derive_path(&"m/1000000000".parse().unwrap()),
+            Ok(expected.key)
+        );
+        assert_eq!(
+            M_0H.parse::<Xpub>()
+                .unwrap()
+                .key
+                .derive_path(&"m/1/2'/2".parse().unwrap()),
+            Err(DeriveError::HardenedIndex)
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Certificate-like delegation of authority from one key to another.
+//!
+//! A [`Delegation`] is a signature by a _delegator_ key on a statement that says a _delegate_ key
+//! may act on its behalf within some `scope` until `expires_at`. The delegate can in turn delegate
+//! to another key and so on, giving a chain from a root key (e.g. a long-term identity key kept
+//! offline) to the key that actually signs things (e.g. a session key on a phone).
+//!
+//! The statement is serialized canonically as
+//!
+//! ```text
+//! delegator (32 byte x-only) || delegate (32 byte x-only) || expires_at (8 byte big-endian) || scope
+//! ```
+//!
+//! and signed as a [`Message::plain`] with the app tag `"schnorr-fun/delegation"` so it can't be
+//! confused with any other kind of signed message.
+//!
+//! The meaning of `scope` is up to the application but chains may only narrow it: the scope of
+//! each link must start with the scope of the link before it. The unit of `expires_at` is also up
+//! to the application (UNIX time in seconds is a good choice) as long as you use the same unit for
+//! `now` when verifying.
+//!
+//! # Synopsis
+//!
+//! ```
+//! use schnorr_fun::fun::Scalar;
+//! # let schnorr = schnorr_fun::test_instance!();
+//! let root = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let laptop = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let session = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let now = 1_650_000_000;
+//! let chain = vec![
+//!     schnorr.delegate(
+//!         &root,
+//!         laptop.public_key(),
+//!         b"payments/",
+//!         now + 365 * 24 * 3600,
+//!     ),
+//!     schnorr.delegate(&laptop, session.public_key(), b"payments/small", now + 3600),
+//! ];
+//! assert_eq!(
+//!     schnorr.verify_delegation_chain(&root.public_key(), &chain, now),
+//!     Ok(session.public_key())
+//! );
+//! // an hour later the session key is no longer valid
+//! assert!(schnorr
+//!     .verify_delegation_chain(&root.public_key(), &chain, now + 3600)
+//!     .is_err());
+//! ```
+use crate::{
+    fun::{marker::*, nonce::NonceGen, transcript::Transcript, Point, XOnlyKeyPair},
+    Message, Schnorr, Signature, Vec,
+};
+
+/// The app tag delegation statements are signed under.
+const APP_TAG: &str = "schnorr-fun/delegation";
+
+/// A signed statement that `delegator` lets `delegate` act on its behalf.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct Delegation {
+    /// The key granting authority.
+    pub delegator: Point<EvenY>,
+    /// The key receiving authority.
+    pub delegate: Point<EvenY>,
+    /// What the delegate may do. Interpreted by the application.
+    pub scope: Vec<u8>,
+    /// The delegation is valid while `now < expires_at`.
+    pub expires_at: u64,
+    /// The delegator's signature on the statement.
+    pub signature: Signature,
+}
+
+impl Delegation {
+    /// The canonical serialization of the statement the signature is on.
+    pub fn statement(&self) -> Vec<u8> {
+        statement(
+            &self.delegator,
+            &self.delegate,
+            &self.scope,
+            self.expires_at,
+        )
+    }
+
+    /// Whether the delegation has expired at time `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+}
+
+fn statement(
+    delegator: &Point<EvenY>,
+    delegate: &Point<EvenY>,
+    scope: &[u8],
+    expires_at: u64,
+) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(72 + scope.len());
+    bytes.extend_from_slice(&delegator.to_xonly_bytes());
+    bytes.extend_from_slice(&delegate.to_xonly_bytes());
+    bytes.extend_from_slice(&expires_at.to_be_bytes());
+    bytes.extend_from_slice(scope);
+    bytes
+}
+
+/// Why a delegation chain was rejected. Each variant has the position of the offending link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainError {
+    /// The link's delegator is not the previous link's delegate (or the root key).
+    BrokenLink(usize),
+    /// The link's signature is invalid.
+    InvalidSignature(usize),
+    /// The link has expired.
+    Expired(usize),
+    /// The link's scope doesn't start with the previous link's scope.
+    ScopeWidened(usize),
+}
+
+impl core::fmt::Display for ChainError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            ChainError::BrokenLink(i) => write!(
+                f,
+                "delegation {} is not from the delegate of the one before it",
+                i
+            ),
+            ChainError::InvalidSignature(i) => {
+                write!(f, "delegation {} has an invalid signature", i)
+            }
+            ChainError::Expired(i) => write!(f, "delegation {} has expired", i),
+            ChainError::ScopeWidened(i) => write!(
+                f,
+                "delegation {} has a wider scope than the one before it",
+                i
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ChainError {}
+
+impl<CH: Transcript, NG: NonceGen> Schnorr<CH, NG> {
+    /// Delegates authority from `delegator` to `delegate` within `scope` until `expires_at`.
+    ///
+    /// See the [module documentation] for an example.
+    ///
+    /// [module documentation]: crate::delegation
+    pub fn delegate(
+        &self,
+        delegator: &XOnlyKeyPair,
+        delegate: Point<EvenY>,
+        scope: &[u8],
+        expires_at: u64,
+    ) -> Delegation {
+        let delegator_key = delegator.public_key();
+        let statement = statement(&delegator_key, &delegate, scope, expires_at);
+        let signature = self.sign(delegator, Message::<Public>::plain(APP_TAG, &statement));
+        Delegation {
+            delegator: delegator_key,
+            delegate,
+            scope: scope.into(),
+            expires_at,
+            signature,
+        }
+    }
+}
+
+impl<CH: Transcript, NG> Schnorr<CH, NG> {
+    /// Checks the delegator's signature on `delegation` and that it hasn't expired at `now`.
+    #[must_use]
+    pub fn verify_delegation(&self, delegation: &Delegation, now: u64) -> bool {
+        !delegation.is_expired(now)
+            && self.verify(
+                &delegation.delegator,
+                Message::<Public>::plain(APP_TAG, &delegation.statement()),
+                &delegation.signature,
+            )
+    }
+
+    /// Verifies a chain of delegations starting from `root` at time `now` and returns the key at
+    /// the end of the chain.
+    ///
+    /// The first link must be from `root` and each link after that must be from the delegate of
+    /// the one before it. Every link must be valid at `now` and no link may widen the scope of the
+    /// link before it. The key returned may act within the scope of the last link. An empty chain
+    /// returns `root`.
+    pub fn verify_delegation_chain(
+        &self,
+        root: &Point<EvenY>,
+        chain: &[Delegation],
+        now: u64,
+    ) -> Result<Point<EvenY>, ChainError> {
+        let mut current = *root;
+        let mut scope: &[u8] = &[];
+        for (i, delegation) in chain.iter().enumerate() {
+            if delegation.delegator != current {
+                return Err(ChainError::BrokenLink(i));
+            }
+            if !delegation.scope.starts_with(scope) {
+                return Err(ChainError::ScopeWidened(i));
+            }
+            if delegation.is_expired(now) {
+                return Err(ChainError::Expired(i));
+            }
+            if !self.verify_delegation(delegation, now) {
+                return Err(ChainError::InvalidSignature(i));
+            }
+            current = delegation.delegate;
+            scope = &delegation.scope;
+        }
+        Ok(current)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::Scalar;
+
+    #[test]
+    fn delegation_chain() {
+        let schnorr = crate::test_instance!();
+        let keys = (0..3)
+            .map(|_| schnorr.new_keypair(Scalar::random(&mut rand::thread_rng())))
+            .collect::<Vec<_>>();
+        let root = keys[0].public_key();
+        let chain = vec![
+            schnorr.delegate(&keys[0], keys[1].public_key(), b"a/", 100),
+            schnorr.delegate(&keys[1], keys[2].public_key(), b"a/b", 50),
+        ];
+        assert!(schnorr.verify_delegation(&chain[0], 99));
+        assert!(!schnorr.verify_delegation(&chain[0], 100));
+        assert_eq!(
+            schnorr.verify_delegation_chain(&root, &chain, 10),
+            Ok(keys[2].public_key())
+        );
+        assert_eq!(schnorr.verify_delegation_chain(&root, &[], 10), Ok(root));
+        assert_eq!(
+            schnorr.verify_delegation_chain(&root, &chain, 50),
+            Err(ChainError::Expired(1))
+        );
+        assert_eq!(
+            schnorr.verify_delegation_chain(&keys[1].public_key(), &chain, 10),
+            Err(ChainError::BrokenLink(0))
+        );
+        assert_eq!(
+            schnorr.verify_delegation_chain(&root, &chain[1..], 10),
+            Err(ChainError::BrokenLink(0))
+        );
+
+        let wider = schnorr.delegate(&keys[1], keys[2].public_key(), b"b", 50);
+        assert_eq!(
+            schnorr.verify_delegation_chain(&root, &[chain[0].clone(), wider], 10),
+            Err(ChainError::ScopeWidened(1))
+        );
+
+        let mut tampered = chain.clone();
+        tampered[1].expires_at = 100;
+        assert_eq!(
+            schnorr.verify_delegation_chain(&root, &tampered, 75),
+            Err(ChainError::InvalidSignature(1))
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! Designated verifier Schnorr signatures.
+//!
+//! A designated verifier signature convinces one particular verifier that the signer signed a
+//! message but cannot convince anyone else. It is a non-interactive proof that the prover knows
+//! the secret key of the signer _or_ the secret key of the verifier. Since the verifier knows that
+//! they didn't create it themselves they are convinced the signer did. Everyone else has to
+//! consider that the verifier may have [`simulate`]d it so the signature is not transferable. This
+//! is what you want for receipts or acknowledgements that should not be usable as public evidence.
+//!
+//! The proof is the standard OR composition of two Schnorr proofs of knowledge of discrete
+//! logarithm made non-interactive with a [`Transcript`] tagged with
+//! `secp256kfun/designated-verifier/challenge`.
+//!
+//! # Synopsis
+//! ```
+//! use schnorr_fun::{
+//!     designated_verifier::DesignatedVerifier,
+//!     fun::{marker::*, nonce, KeyPair, Scalar},
+//!     Message,
+//! };
+//! use sha2::Sha256;
+//! let dv = DesignatedVerifier::<Sha256, _>::new(nonce::Deterministic::<Sha256>::default());
+//! let signer = KeyPair::new(Scalar::random(&mut rand::thread_rng()));
+//! let verifier = KeyPair::new(Scalar::random(&mut rand::thread_rng()));
+//! let message = Message::<Public>::plain("my-app", b"received 1 BTC from Alice");
+//!
+//! let signature = dv.sign(&signer, &verifier.public_key(), message);
+//! assert!(dv.verify(
+//!     &signer.public_key(),
+//!     &verifier.public_key(),
+//!     message,
+//!     &signature
+//! ));
+//!
+//! // ...but the verifier could have produced an equally valid signature by themselves
+//! let fake = dv.simulate(&verifier, &signer.public_key(), message);
+//! assert!(dv.verify(&signer.public_key(), &verifier.public_key(), message, &fake));
+//! ```
+//!
+//! [`simulate`]: DesignatedVerifier::simulate
+//! [`Transcript`]: crate::fun::transcript::Transcript
+use crate::{
+    fun::{
+        derive_nonce, g,
+        hash::Tagged,
+        marker::*,
+        nonce::{AddTag, NonceGen},
+        s,
+        transcript::Transcript,
+        KeyPair, Point, Scalar, G,
+    },
+    Message,
+};
+
+/// A designated verifier signature.
+///
+/// Consists of a challenge and response for each of the signer's and verifier's keys. The two
+/// challenges must add up to the Fiat-Shamir challenge.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct DesignatedSignature {
+    /// The challenge for the signer's key.
+    pub c_signer: Scalar<Public, Zero>,
+    /// The response for the signer's key.
+    pub s_signer: Scalar<Public, Zero>,
+    /// The challenge for the verifier's key.
+    pub c_verifier: Scalar<Public, Zero>,
+    /// The response for the verifier's key.
+    pub s_verifier: Scalar<Public, Zero>,
+}
+
+/// Creates and verifies [`DesignatedSignature`]s.
+///
+/// See the [module level documentation] for usage.
+///
+/// [module level documentation]: crate::designated_verifier
+#[derive(Clone, Debug)]
+pub struct DesignatedVerifier<CH, NG = ()> {
+    challenge_hash: CH,
+    nonce_gen: NG,
+}
+
+impl<CH: Transcript + Tagged, NG: AddTag> DesignatedVerifier<CH, NG> {
+    /// Creates a new instance with the given nonce generator.
+    ///
+    /// The nonce generator only needs to be [`NonceGen`] to create (or simulate) signatures. Pass
+    /// `()` if you only want to verify them.
+    ///
+    /// [`NonceGen`]: crate::nonce::NonceGen
+    pub fn new(nonce_gen: NG) -> Self {
+        Self {
+            challenge_hash: CH::default().tagged(b"secp256kfun/designated-verifier/challenge"),
+            nonce_gen: nonce_gen.add_tag("secp256kfun/designated-verifier"),
+        }
+    }
+}
+
+impl<CH: Transcript, NG: NonceGen> DesignatedVerifier<CH, NG> {
+    /// Signs `message` so that only the owner of `verifier_key` is convinced by it.
+    pub fn sign(
+        &self,
+        signing_keypair: &KeyPair,
+        verifier_key: &Point,
+        message: Message<'_, impl Secrecy>,
+    ) -> DesignatedSignature {
+        let (x, X) = signing_keypair.as_tuple();
+        let (real, simulated) = self.prove(x, true, &X, verifier_key, message);
+        DesignatedSignature {
+            c_signer: real.0,
+            s_signer: real.1,
+            c_verifier: simulated.0,
+            s_verifier: simulated.1,
+        }
+    }
+
+    /// Produces a signature that is valid under `signer_key` using only the _verifier's_ secret
+    /// key.
+    ///
+    /// This is indistinguishable from a signature produced by [`sign`] which is why designated
+    /// verifier signatures don't convince anyone other than the verifier.
+    ///
+    /// [`sign`]: Self::sign
+    pub fn simulate(
+        &self,
+        verifier_keypair: &KeyPair,
+        signer_key: &Point,
+        message: Message<'_, impl Secrecy>,
+    ) -> DesignatedSignature {
+        let (y, Y) = verifier_keypair.as_tuple();
+        let (real, simulated) = self.prove(y, false, signer_key, &Y, message);
+        DesignatedSignature {
+            c_signer: simulated.0,
+            s_signer: simulated.1,
+            c_verifier: real.0,
+            s_verifier: real.1,
+        }
+    }
+
+    /// Proves knowledge of `x`, the secret key of the signer if `real_is_signer` or the verifier
+    /// otherwise.
+    ///
+    /// Returns the `(challenge, response)` for the real and simulated branches.
+    #[allow(clippy::type_complexity)]
+    fn prove(
+        &self,
+        x: &Scalar,
+        real_is_signer: bool,
+        signer_key: &Point,
+        verifier_key: &Point,
+        message: Message<'_, impl Secrecy>,
+    ) -> (
+        (Scalar<Public, Zero>, Scalar<Public, Zero>),
+        (Scalar<Public, Zero>, Scalar<Public, Zero>),
+    ) {
+        let r = derive_nonce!(
+            nonce_gen => self.nonce_gen,
+            secret => x,
+            public => [0u8, signer_key, verifier_key, message]
+        );
+        let c_other = derive_nonce!(
+            nonce_gen => self.nonce_gen,
+            secret => x,
+            public => [1u8, signer_key, verifier_key, message]
+        )
+        .mark::<(Public, Zero)>();
+        let s_other = derive_nonce!(
+            nonce_gen => self.nonce_gen,
+            secret => x,
+            public => [2u8, signer_key, verifier_key, message]
+        )
+        .mark::<(Public, Zero)>();
+
+        let other_key = if real_is_signer {
+            verifier_key
+        } else {
+            signer_key
+        };
+        let R_real = g!(r * G).normalize().mark::<Zero>();
+        let R_other = g!(s_other * G - c_other * other_key).normalize();
+        let c = if real_is_signer {
+            self.challenge(signer_key, verifier_key, &R_real, &R_other, message)
+        } else {
+            self.challenge(signer_key, verifier_key, &R_other, &R_real, message)
+        };
+        let c_real = s!(c - c_other).mark::<Public>();
+        let s_real = s!(r + c_real * x).mark::<Public>();
+
+        ((c_real, s_real), (c_other, s_other))
+    }
+}
+
+impl<CH: Transcript, NG> DesignatedVerifier<CH, NG> {
+    /// Verifies a designated verifier signature on `message` by `signer_key` for `verifier_key`.
+    ///
+    /// A valid signature only means that _either_ the signer or the verifier produced it.
+    #[must_use]
+    pub fn verify(
+        &self,
+        signer_key: &Point,
+        verifier_key: &Point,
+        message: Message<'_, impl Secrecy>,
+        signature: &DesignatedSignature,
+    ) -> bool {
+        let DesignatedSignature {
+            c_signer,
+            s_signer,
+            c_verifier,
+            s_verifier,
+        } = signature;
+        let (X, Y) = (signer_key, verifier_key);
+        let R_signer = g!(s_signer * G - c_signer * X).normalize();
+        let R_verifier = g!(s_verifier * G - c_verifier * Y).normalize();
+        let c = self.challenge(X, Y, &R_signer, &R_verifier, message);
+        c == s!(c_signer + c_verifier)
+    }
+
+    fn challenge<S: Secrecy>(
+        &self,
+        signer_key: &Point,
+        verifier_key: &Point,
+        R_signer: &Point<Normal, Public, Zero>,
+        R_verifier: &Point<Normal, Public, Zero>,
+        m: Message<'_, S>,
+    ) -> Scalar<Public, Zero> {
+        let mut transcript = self.challenge_hash.clone();
+        transcript.append_message(b"X", &signer_key.to_bytes());
+        transcript.append_message(b"Y", &verifier_key.to_bytes());
+        transcript.append_message(b"R_X", &R_signer.to_bytes());
+        transcript.append_message(b"R_Y", &R_verifier.to_bytes());
+        m.append_to(&mut transcript);
+        transcript.challenge_scalar::<Public>(b"c")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::nonce::Deterministic;
+    use sha2::Sha256;
+
+    fn test_instance() -> DesignatedVerifier<Sha256, Deterministic<Sha256>> {
+        DesignatedVerifier::new(Deterministic::<Sha256>::default())
+    }
+
+    #[test]
+    fn sign_simulate_and_verify() {
+        let dv = test_instance();
+        let signer = KeyPair::new(Scalar::random(&mut rand::thread_rng()));
+        let verifier = KeyPair::new(Scalar::random(&mut rand::thread_rng()));
+        let third_party = KeyPair::new(Scalar::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"receipt");
+        let (X, Y, Z) = (
+            signer.public_key(),
+            verifier.public_key(),
+            third_party.public_key(),
+        );
+
+        let signature = dv.sign(&signer, &Y, message);
+        assert!(dv.verify(&X, &Y, message, &signature));
+        assert!(!dv.verify(&X, &Z, message, &signature));
+        assert!(!dv.verify(&Y, &X, message, &signature));
+        assert!(!dv.verify(
+            &X,
+            &Y,
+            Message::<Public>::plain("test", b"other"),
+            &signature
+        ));
+
+        let simulated = dv.simulate(&verifier, &X, message);
+        assert!(dv.verify(&X, &Y, message, &simulated));
+        assert_ne!(simulated, signature);
+
+        // a third party can't simulate it
+        let forged = dv.simulate(&third_party, &X, message);
+        assert!(!dv.verify(&X, &Y, message, &forged));
+    }
+
+    #[test]
+    fn tampered_signature_fails() {
+        let dv = test_instance();
+        let signer = KeyPair::new(Scalar::random(&mut rand::thread_rng()));
+        let verifier = KeyPair::new(Scalar::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"receipt");
+        let mut signature = dv.sign(&signer, &verifier.public_key(), message);
+        signature.c_signer = s!(signature.c_signer + 1).mark::<Public>();
+        assert!(!dv.verify(
+            &signer.public_key(),
+            &verifier.public_key(),
+            message,
+            &signature
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+//! Discreet log contracts (DLCs) on numeric outcomes.
+//!
+//! In a DLC an oracle announces in advance a public key and a nonce for each digit of some future
+//! numeric outcome (e.g. the BTC/USD price at noon). When the time comes it attests to the outcome
+//! by signing each digit with BIP340 under the corresponding nonce. Because the nonces are fixed
+//! in advance, anyone can compute the point `s * G` that the signature on each possible value of
+//! each digit will reveal. These are the _anticipation points_ and the parties to the contract use
+//! them (or sums of them) as adaptor signature encryption keys on the contract execution
+//! transactions (CETs) so that only the CET paying out for the actual outcome can be decrypted.
+//!
+//! This module has the oracle's side ([`Announcement`] and [`Attestation`]). [`cet`] builds and
+//! checks the encrypted signatures on the CETs.
+//!
+//! [`cet`]: crate::dlc::cet
+use crate::{
+    fun::{marker::*, s, transcript::Transcript, Point, Scalar, XOnlyKeyPair},
+    Message, Schnorr, Signature, Vec,
+};
+
+pub mod cet;
+
+/// The application tag of the message the oracle signs for each digit.
+pub const DIGIT_TAG: &str = "dlc/digit";
+
+/// An oracle's announcement that it will attest to a numeric outcome in base `base` with one
+/// nonce per digit.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct Announcement {
+    public_key: Point<EvenY>,
+    nonces: Vec<Point<EvenY>>,
+    base: u32,
+}
+
+impl Announcement {
+    /// Creates an announcement for outcomes with `nonces.len()` digits in base `base`. The first
+    /// nonce is for the most significant digit.
+    ///
+    /// ## Return value
+    ///
+    /// Returns `None` if `base` is less than 2, there are no nonces or there are so many digits
+    /// that the outcomes don't fit in a `u64`.
+    pub fn new(public_key: Point<EvenY>, nonces: Vec<Point<EvenY>>, base: u32) -> Option<Self> {
+        if base < 2 || nonces.is_empty() {
+            return None;
+        }
+        (base as u64).checked_pow(nonces.len() as u32)?;
+        Some(Announcement {
+            public_key,
+            nonces,
+            base,
+        })
+    }
+
+    /// The oracle's public key.
+    pub fn public_key(&self) -> Point<EvenY> {
+        self.public_key
+    }
+
+    /// The nonce for each digit starting with the most significant.
+    pub fn nonces(&self) -> &[Point<EvenY>] {
+        &self.nonces
+    }
+
+    /// The base the outcome is written in.
+    pub fn base(&self) -> u32 {
+        self.base
+    }
+
+    /// The number of digits in an outcome.
+    pub fn n_digits(&self) -> usize {
+        self.nonces.len()
+    }
+
+    /// The number of possible outcomes (they go from `0` to `n_outcomes() - 1`).
+    pub fn n_outcomes(&self) -> u64 {
+        (self.base as u64).pow(self.nonces.len() as u32)
+    }
+
+    /// Writes `outcome` as digits starting with the most significant.
+    ///
+    /// ## Return value
+    ///
+    /// Returns `None` if the outcome is out of range.
+    pub fn digits(&self, outcome: u64) -> Option<Vec<u32>> {
+        if outcome >= self.n_outcomes() {
+            return None;
+        }
+        let base = self.base as u64;
+        let mut digits = vec![0; self.n_digits()];
+        let mut rest = outcome;
+        for digit in digits.iter_mut().rev() {
+            *digit = (rest % base) as u32;
+            rest /= base;
+        }
+        Some(digits)
+    }
+
+    /// Signs each digit of `outcome` with the nonce secrets corresponding to [`nonces`].
+    ///
+    /// The nonce secrets must be the ones that give the announced (even y) nonces e.g. from
+    /// [`Point::even_y_from_scalar_mul`]. Never attest to two different outcomes with the same
+    /// nonces: it reveals your secret key.
+    ///
+    /// ## Return value
+    ///
+    /// Returns `None` if the outcome is out of range or the number of nonce secrets is wrong.
+    ///
+    /// [`nonces`]: Self::nonces
+    pub fn attest<CH: Transcript, NG>(
+        &self,
+        schnorr: &Schnorr<CH, NG>,
+        keypair: &XOnlyKeyPair,
+        nonce_secrets: &[Scalar],
+        outcome: u64,
+    ) -> Option<Attestation> {
+        if nonce_secrets.len() != self.n_digits() {
+            return None;
+        }
+        let x = keypair.secret_key();
+        let signatures = self
+            .digits(outcome)?
+            .into_iter()
+            .zip(self.nonces.iter().zip(nonce_secrets))
+            .map(|(digit, (R, r))| {
+                let c = schnorr.challenge(R, &self.public_key, digit_message(&digit.to_be_bytes()));
+                Signature {
+                    R: *R,
+                    s: s!(r + c * x).mark::<Public>(),
+                }
+            })
+            .collect();
+        Some(Attestation {
+            outcome,
+            signatures,
+        })
+    }
+}
+
+/// An oracle's signatures on each digit of an outcome.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct Attestation {
+    /// The outcome.
+    pub outcome: u64,
+    /// A signature on each digit starting with the most significant.
+    pub signatures: Vec<Signature>,
+}
+
+impl Attestation {
+    /// Checks that the attestation was made with the keys in `announcement`.
+    #[must_use]
+    pub fn verify<CH: Transcript, NG>(
+        &self,
+        schnorr: &Schnorr<CH, NG>,
+        announcement: &Announcement,
+    ) -> bool {
+        let digits = match announcement.digits(self.outcome) {
+            Some(digits) => digits,
+            None => return false,
+        };
+        self.signatures.len() == digits.len()
+            && digits
+                .iter()
+                .zip(&self.signatures)
+                .zip(announcement.nonces())
+                .all(|((digit, signature), R)| {
+                    signature.R == *R
+                        && schnorr.verify(
+                            &announcement.public_key,
+                            digit_message(&digit.to_be_bytes()),
+                            signature,
+                        )
+                })
+    }
+}
+
+fn digit_message(digit: &[u8; 4]) -> Message<'_, Public> {
+    Message::plain(DIGIT_TAG, &digit[..])
+}
@@ -0,0 +1,525 @@
+//! Encrypted signatures on contract execution transactions (CETs) for numeric outcomes.
+//!
+//! Each [`Cet`] pays out for a range of outcomes. Rather than encrypting a signature to every
+//! outcome in the range, [`decompose`] covers the range with as few digit prefixes as possible
+//! (e.g. in base 10 the range `[200, 349]` is `2*` and `3[0-4]*`). The encryption key for a prefix
+//! is the sum of the [`AnticipationPoints`] of its digits and the decryption key is the sum of
+//! the oracle's signatures on those digits. A contract with thousands of CETs needs tens of
+//! thousands of encrypted signatures so [`AnticipationPoints`] computes every digit's point once,
+//! [`sign`] normalizes all the encryption keys together and [`verify`] checks every encrypted
+//! signature in one multi-scalar multiplication.
+//!
+//! Once the oracle attests, [`decrypt`] finds the CET for the outcome and decrypts its signature.
+//! When the counterparty broadcasts a CET you can get the same decryption key back with
+//! [`Adaptor::recover_decryption_key`].
+//!
+//! # Synopsis
+//!
+//! ```
+//! use schnorr_fun::{
+//!     dlc::{
+//!         cet::{AnticipationPoints, Cet},
+//!         Announcement,
+//!     },
+//!     fun::{Point, Scalar, G},
+//!     Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let mut rng = rand::thread_rng();
+//! // the oracle will attest to a price from 0 to 1023 in binary
+//! let oracle = schnorr.new_keypair(Scalar::random(&mut rng));
+//! let mut nonce_secrets = (0..10)
+//!     .map(|_| Scalar::random(&mut rng))
+//!     .collect::<Vec<_>>();
+//! let nonces = nonce_secrets
+//!     .iter_mut()
+//!     .map(|r| Point::even_y_from_scalar_mul(G, r))
+//!     .collect();
+//! let announcement = Announcement::new(oracle.public_key(), nonces, 2).unwrap();
+//!
+//! // Alice signs CETs paying out below and above 500 and gives the encrypted signatures to Bob
+//! let alice = schnorr.new_keypair(Scalar::random(&mut rng));
+//! let cets = [
+//!     Cet::new(0..=499, Message::plain("my-dlc", b"cet paying Bob")),
+//!     Cet::new(500..=1023, Message::plain("my-dlc", b"cet paying Alice")),
+//! ];
+//! let anticipation_points = AnticipationPoints::new(&schnorr, &announcement);
+//! let encrypted = anticipation_points.sign(&schnorr, &alice, &cets);
+//! assert!(anticipation_points.verify(&schnorr, &alice.public_key(), &cets, &encrypted, &mut rng));
+//!
+//! // the oracle attests to 321 and Bob decrypts Alice's signature on the first CET
+//! let attestation = announcement
+//!     .attest(&schnorr, &oracle, &nonce_secrets, 321)
+//!     .unwrap();
+//! let (index, signature) = anticipation_points
+//!     .decrypt(&schnorr, &attestation, &cets, &encrypted)
+//!     .unwrap();
+//! assert_eq!(index, 0);
+//! assert!(schnorr.verify(&alice.public_key(), cets[0].message, &signature));
+//! ```
+//!
+//! [`sign`]: AnticipationPoints::sign
+//! [`verify`]: AnticipationPoints::verify
+//! [`decrypt`]: AnticipationPoints::decrypt
+//! [`Adaptor::recover_decryption_key`]: crate::adaptor::Adaptor::recover_decryption_key
+use super::{digit_message, Announcement, Attestation};
+use crate::{
+    adaptor::{Adaptor, EncryptedSign, EncryptedSignature},
+    fun::{
+        g, marker::*, nonce::NonceGen, op, rand_core::RngCore, s, transcript::Transcript, Point,
+        Scalar, XOnlyKeyPair, G,
+    },
+    Message, Schnorr, Signature, Vec,
+};
+use core::ops::RangeInclusive;
+
+/// Covers the outcomes from `start` to `end` (inclusive) with the fewest digit prefixes.
+///
+/// Each prefix is the first few digits (most significant first) of every outcome it covers. The
+/// prefixes are in increasing order of the outcomes they cover. Every prefix has at least one digit
+/// even if the range covers every outcome.
+///
+/// ## Return value
+///
+/// Returns no prefixes if `start > end`. Outcomes past the largest outcome with `n_digits` digits
+/// are ignored. Returns `None` if `base` is less than 2, `n_digits` is zero or there are so many
+/// digits that the outcomes don't fit in a `u64` (the same as [`Announcement::new`]).
+///
+/// # Example
+///
+/// ```
+/// use schnorr_fun::dlc::cet::decompose;
+/// assert_eq!(decompose(200, 349, 10, 3).unwrap(), vec![
+///     vec![2],
+///     vec![3, 0],
+///     vec![3, 1],
+///     vec![3, 2],
+///     vec![3, 3],
+///     vec![3, 4]
+/// ]);
+/// assert_eq!(decompose(5, 5, 10, 3).unwrap(), vec![vec![0, 0, 5]]);
+/// assert_eq!(decompose(5, 5, 10, 0), None);
+/// ```
+pub fn decompose(start: u64, end: u64, base: u32, n_digits: usize) -> Option<Vec<Vec<u32>>> {
+    if base < 2 || n_digits == 0 {
+        return None;
+    }
+    (base as u64).checked_pow(u32::try_from(n_digits).ok()?)?;
+    let mut prefixes = vec![];
+    if start > end {
+        return Some(prefixes);
+    }
+    let block = (base as u64).pow(n_digits as u32 - 1);
+    let mut prefix = Vec::with_capacity(n_digits);
+    for digit in 0..base {
+        prefix.push(digit);
+        cover(
+            start,
+            end,
+            digit as u64 * block,
+            block,
+            base,
+            &mut prefix,
+            &mut prefixes,
+        );
+        prefix.pop();
+    }
+    Some(prefixes)
+}
+
+/// Covers the intersection of `[start, end]` and the `block` outcomes starting at `low` which all
+/// start with `prefix`.
+fn cover(
+    start: u64,
+    end: u64,
+    low: u64,
+    block: u64,
+    base: u32,
+    prefix: &mut Vec<u32>,
+    prefixes: &mut Vec<Vec<u32>>,
+) {
+    let high = low + (block - 1);
+    if high < start || low > end {
+        return;
+    }
+    if start <= low && high <= end {
+        prefixes.push(prefix.clone());
+        return;
+    }
+    let block = block / base as u64;
+    for digit in 0..base {
+        prefix.push(digit);
+        cover(
+            start,
+            end,
+            low + digit as u64 * block,
+            block,
+            base,
+            prefix,
+            prefixes,
+        );
+        prefix.pop();
+    }
+}
+
+/// A contract execution transaction: the `message` to sign if the outcome is in `outcomes`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cet<'a> {
+    /// The outcomes the CET pays out for.
+    pub outcomes: RangeInclusive<u64>,
+    /// What to sign (e.g. the CET's sighash).
+    pub message: Message<'a, Public>,
+}
+
+impl<'a> Cet<'a> {
+    /// Creates a CET.
+    pub fn new(outcomes: RangeInclusive<u64>, message: Message<'a, Public>) -> Self {
+        Cet { outcomes, message }
+    }
+}
+
+/// The encrypted signatures on one [`Cet`], one for each prefix that covers its outcomes.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct CetSignatures {
+    /// The prefixes from [`decompose`].
+    pub prefixes: Vec<Vec<u32>>,
+    /// The encrypted signature for each prefix.
+    pub encrypted_signatures: Vec<EncryptedSignature>,
+}
+
+/// The anticipation point for every value of every digit in an [`Announcement`].
+///
+/// See the [module documentation](crate::dlc::cet).
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnticipationPoints {
+    announcement: Announcement,
+    // points[i * base + d] is the point for digit i having value d
+    points: Vec<Point<Normal, Public, Zero>>,
+}
+
+impl AnticipationPoints {
+    /// Computes the anticipation points of `announcement`.
+    pub fn new<CH: Transcript, NG>(schnorr: &Schnorr<CH, NG>, announcement: &Announcement) -> Self {
+        let X = announcement.public_key();
+        let points = announcement
+            .nonces()
+            .iter()
+            .flat_map(|R| {
+                (0..announcement.base()).map(move |digit| {
+                    let c = schnorr.challenge(R, &X, digit_message(&digit.to_be_bytes()));
+                    g!(R + c * X)
+                })
+            })
+            .collect();
+        AnticipationPoints {
+            announcement: announcement.clone(),
+            points: Point::normalize_batch(points),
+        }
+    }
+
+    /// The announcement the points are for.
+    pub fn announcement(&self) -> &Announcement {
+        &self.announcement
+    }
+
+    /// The encryption key for the outcomes starting with `prefix`.
+    ///
+    /// ## Return value
+    ///
+    /// Returns `None` if the prefix is empty, too long or has a digit that is too big.
+    pub fn encryption_key(&self, prefix: &[u32]) -> Option<Point> {
+        self.prefix_sum(prefix)?.normalize().mark::<NonZero>()
+    }
+
+    fn prefix_sum(&self, prefix: &[u32]) -> Option<Point<Jacobian, Public, Zero>> {
+        let base = self.announcement.base();
+        if prefix.is_empty()
+            || prefix.len() > self.announcement.n_digits()
+            || prefix.iter().any(|digit| *digit >= base)
+        {
+            return None;
+        }
+        Some(
+            prefix
+                .iter()
+                .enumerate()
+                .fold(Point::zero().mark::<Jacobian>(), |sum, (i, digit)| {
+                    g!(sum + { self.points[i * base as usize + *digit as usize] })
+                }),
+        )
+    }
+
+    /// Creates the encrypted signatures on every CET for every prefix that covers its outcomes.
+    ///
+    /// ## Return value
+    ///
+    /// The signatures at index `i` are for `cets[i]`.
+    pub fn sign<CH: Transcript, NG: NonceGen>(
+        &self,
+        schnorr: &Schnorr<CH, NG>,
+        signing_keypair: &XOnlyKeyPair,
+        cets: &[Cet<'_>],
+    ) -> Vec<CetSignatures> {
+        let prefixes = self.cet_prefixes(cets);
+        let keys = Point::normalize_batch(
+            prefixes
+                .iter()
+                .flatten()
+                .map(|prefix| self.prefix_sum(prefix).expect("prefixes are valid"))
+                .collect(),
+        );
+        let mut keys = keys.into_iter();
+        cets.iter()
+            .zip(prefixes)
+            .map(|(cet, prefixes)| {
+                let encrypted_signatures = keys
+                    .by_ref()
+                    .take(prefixes.len())
+                    .map(|key| {
+                        let key = key.mark::<NonZero>().expect("computationally unreachable");
+                        schnorr.encrypted_sign(signing_keypair, &key, cet.message)
+                    })
+                    .collect();
+                CetSignatures {
+                    prefixes,
+                    encrypted_signatures,
+                }
+            })
+            .collect()
+    }
+
+    /// Checks all the encrypted signatures created by [`sign`](Self::sign) at once.
+    ///
+    /// Each encrypted signature's verification equation is multiplied by a random scalar from
+    /// `rng` and they are all added together so only one multi-scalar multiplication is needed. It
+    /// also checks that the prefixes are the ones that cover each CET's outcomes. If it fails you
+    /// can find the culprit with [`Adaptor::verify_encrypted_signature`].
+    #[must_use]
+    pub fn verify<CH: Transcript, NG>(
+        &self,
+        schnorr: &Schnorr<CH, NG>,
+        verification_key: &Point<EvenY>,
+        cets: &[Cet<'_>],
+        signatures: &[CetSignatures],
+        rng: &mut impl RngCore,
+    ) -> bool {
+        if cets.len() != signatures.len() {
+            return false;
+        }
+        let X = verification_key;
+        let mut scalars = vec![];
+        let mut points = vec![];
+        let mut s_sum = s!(0).mark::<Public>();
+        let mut c_sum = s!(0).mark::<Public>();
+        for ((cet, cet_sigs), prefixes) in cets.iter().zip(signatures).zip(self.cet_prefixes(cets))
+        {
+            if cet_sigs.prefixes != prefixes
+                || cet_sigs.encrypted_signatures.len() != prefixes.len()
+            {
+                return false;
+            }
+            for (prefix, encrypted_signature) in prefixes.iter().zip(&cet_sigs.encrypted_signatures)
+            {
+                let EncryptedSignature {
+                    R,
+                    s_hat,
+                    needs_negation,
+                } = encrypted_signature;
+                let Y = self.encryption_key(prefix).expect("prefixes are valid");
+                let a = Scalar::random(rng).mark::<Public>();
+                let c = schnorr.challenge(R, X, cet.message);
+                // s_hat * G == R_hat + c * X where R_hat = R - Y (or R + Y if needs_negation)
+                s_sum = s!(s_sum + a * s_hat).mark::<Public>();
+                c_sum = s!(c_sum + a * c).mark::<Public>();
+                let mut a_Y = a;
+                a_Y.conditional_negate(*needs_negation);
+                scalars.push(-a.mark::<Zero>());
+                points.push((*R).mark::<(Normal, Zero)>());
+                scalars.push(a_Y.mark::<Zero>());
+                points.push(Y.mark::<Zero>());
+            }
+        }
+        scalars.push(s_sum);
+        points.push((*G).mark::<(Normal, Zero)>());
+        scalars.push(-c_sum);
+        points.push((*X).mark::<(Normal, Zero)>());
+        op::lincomb(&scalars, &points).is_zero()
+    }
+
+    /// Decrypts the signature on the CET that pays out for the attested outcome.
+    ///
+    /// ## Return value
+    ///
+    /// Returns the index of the CET and the decrypted signature or `None` if the attestation isn't
+    /// valid for the announcement or no CET covers the outcome.
+    pub fn decrypt<CH: Transcript, NG>(
+        &self,
+        schnorr: &Schnorr<CH, NG>,
+        attestation: &Attestation,
+        cets: &[Cet<'_>],
+        signatures: &[CetSignatures],
+    ) -> Option<(usize, Signature)> {
+        if !attestation.verify(schnorr, &self.announcement) {
+            return None;
+        }
+        let digits = self.announcement.digits(attestation.outcome)?;
+        let index = cets
+            .iter()
+            .position(|cet| cet.outcomes.contains(&attestation.outcome))?;
+        let cet_sigs = signatures.get(index)?;
+        let (prefix, encrypted_signature) = cet_sigs
+            .prefixes
+            .iter()
+            .zip(&cet_sigs.encrypted_signatures)
+            .find(|(prefix, _)| digits.starts_with(prefix))?;
+        let decryption_key = attestation.signatures[..prefix.len()]
+            .iter()
+            .fold(s!(0), |sum, signature| s!(sum + { signature.s }))
+            .mark::<NonZero>()?;
+        Some((
+            index,
+            schnorr.decrypt_signature(decryption_key, encrypted_signature.clone()),
+        ))
+    }
+
+    fn cet_prefixes(&self, cets: &[Cet<'_>]) -> Vec<Vec<Vec<u32>>> {
+        cets.iter()
+            .map(|cet| {
+                decompose(
+                    *cet.outcomes.start(),
+                    *cet.outcomes.end(),
+                    self.announcement.base(),
+                    self.announcement.n_digits(),
+                )
+                .expect("announcement has a valid base and number of digits")
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn decompose_covers_exactly_the_range(base in 2u32..5, n_digits in 1usize..5, a in 0u64..700, b in 0u64..700) {
+            let n_outcomes = (base as u64).pow(n_digits as u32);
+            let (start, end) = (a.min(b) % n_outcomes, a.max(b) % n_outcomes);
+            let prefixes = decompose(start, end, base, n_digits).unwrap();
+            let mut covered = vec![];
+            for prefix in &prefixes {
+                let block = (base as u64).pow((n_digits - prefix.len()) as u32);
+                let low = prefix.iter().fold(0, |acc, digit| acc * base as u64 + *digit as u64) * block;
+                covered.extend(low..low + block);
+            }
+            if start <= end {
+                prop_assert_eq!(covered, (start..=end).collect::<Vec<_>>());
+            } else {
+                prop_assert!(prefixes.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn decompose_rejects_invalid_parameters() {
+        assert_eq!(decompose(0, 1, 10, 0), None);
+        assert_eq!(decompose(0, 1, 1, 3), None);
+        assert_eq!(decompose(0, 1, 0, 3), None);
+        assert_eq!(decompose(0, 1, 2, 64), None);
+        assert_eq!(decompose(0, 1, u32::MAX, 3), None);
+        assert_eq!(
+            decompose(0, 1, 2, 63).map(|prefixes| prefixes.len()),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn sign_verify_and_decrypt_cets() {
+        let schnorr = crate::test_instance!();
+        let mut rng = rand::thread_rng();
+        let oracle = schnorr.new_keypair(Scalar::random(&mut rng));
+        let mut nonce_secrets = (0..3).map(|_| Scalar::random(&mut rng)).collect::<Vec<_>>();
+        let nonces = nonce_secrets
+            .iter_mut()
+            .map(|r| Point::even_y_from_scalar_mul(G, r))
+            .collect();
+        let announcement = Announcement::new(oracle.public_key(), nonces, 10).unwrap();
+        let anticipation_points = AnticipationPoints::new(&schnorr, &announcement);
+        let signer = schnorr.new_keypair(Scalar::random(&mut rng));
+        let cets = [
+            Cet::new(0..=199, Message::plain("test", b"zero")),
+            Cet::new(200..=349, Message::plain("test", b"one")),
+            Cet::new(350..=999, Message::plain("test", b"two")),
+        ];
+        let signatures = anticipation_points.sign(&schnorr, &signer, &cets);
+        assert!(anticipation_points.verify(
+            &schnorr,
+            &signer.public_key(),
+            &cets,
+            &signatures,
+            &mut rng
+        ));
+        for (cet, cet_sigs) in cets.iter().zip(&signatures) {
+            for (prefix, encrypted_signature) in
+                cet_sigs.prefixes.iter().zip(&cet_sigs.encrypted_signatures)
+            {
+                assert!(schnorr.verify_encrypted_signature(
+                    &signer.public_key(),
+                    &anticipation_points.encryption_key(prefix).unwrap(),
+                    cet.message,
+                    encrypted_signature
+                ));
+            }
+        }
+
+        // a signature encrypted to the wrong prefix is caught
+        let mut bad = signatures.clone();
+        bad[1].encrypted_signatures.swap(0, 1);
+        assert!(!anticipation_points.verify(&schnorr, &signer.public_key(), &cets, &bad, &mut rng));
+        let mut bad = signatures.clone();
+        bad[2].encrypted_signatures[0].s_hat = s!(1).mark::<(Public, Zero)>();
+        assert!(!anticipation_points.verify(&schnorr, &signer.public_key(), &cets, &bad, &mut rng));
+
+        let attestation = announcement
+            .attest(&schnorr, &oracle, &nonce_secrets, 321)
+            .unwrap();
+        assert!(attestation.verify(&schnorr, &announcement));
+        let (index, signature) = anticipation_points
+            .decrypt(&schnorr, &attestation, &cets, &signatures)
+            .unwrap();
+        assert_eq!(index, 1);
+        assert!(schnorr.verify(&signer.public_key(), cets[1].message, &signature));
+
+        // the other side recovers the oracle's decryption key from the broadcast signature
+        let prefix = [3, 2];
+        let position = signatures[1]
+            .prefixes
+            .iter()
+            .position(|p| p[..] == prefix)
+            .unwrap();
+        let decryption_key = schnorr
+            .recover_decryption_key(
+                &anticipation_points.encryption_key(&prefix).unwrap(),
+                &signatures[1].encrypted_signatures[position],
+                &signature,
+            )
+            .unwrap();
+        assert_eq!(
+            decryption_key,
+            s!({ attestation.signatures[0].s } + { attestation.signatures[1].s })
+        );
+
+        let mut forged = attestation.clone();
+        forged.outcome = 322;
+        assert!(anticipation_points
+            .decrypt(&schnorr, &forged, &cets, &signatures)
+            .is_none());
+    }
+}
@@ -0,0 +1,723 @@
+//! Async drivers for the multi-round protocols.
+//!
+//! [MuSig] signing, [FROST] key generation and FROST signing all consist of a few rounds where
+//! each party sends something to the others and waits for their replies. Doing this by hand
+//! means keeping the intermediate state of each protocol around between network events. The
+//! functions here run a whole protocol as a single `async fn` against a [`Transport`] that you
+//! implement for your networking stack (e.g. tokio channels or a websocket to a coordinator).
+//!
+//! The transport only needs to move messages around. It is told nothing about the protocol other
+//! than the message to send and it doesn't need to check anything about the messages it receives:
+//! the drivers check everything they would check if you called the protocol functions yourself.
+//!
+//! This module is only available with the `async` feature. It doesn't depend on any particular
+//! async runtime.
+//!
+//! [MuSig]: crate::musig
+//! [FROST]: crate::frost
+use crate::{
+    encoding::{Decode, DecodeError, Encode},
+    frost::{FinishKeyGenError, Frost, NewKeyGenError, PointPoly, XOnlyFrostKey},
+    musig::{MuSig, XOnlyAggKey},
+    Message, Schnorr, Signature, Vec,
+};
+use core::{future::Future, pin::Pin};
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    marker::*,
+    nonce::NonceGen,
+    KeyPair, Scalar,
+};
+
+pub use crate::binonce::Nonce;
+
+pub mod simulation;
+
+/// A boxed future that is `Send` so it can be used from multi-threaded runtimes.
+pub type BoxFuture<'a, T> = Pin<std::boxed::Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Moves protocol messages of type `M` between the parties.
+///
+/// Parties are identified by their position in the protocol (e.g. their index in the MuSig key
+/// list). Each call to a method is one round. Messages to and from other parties must be
+/// authenticated and messages sent with [`send_each`] must also be encrypted.
+///
+/// [`send_each`]: Transport::send_each
+pub trait Transport<M> {
+    /// The error returned when a message can't be sent or received.
+    type Error;
+
+    /// Sends `message` to every other party and resolves to the messages every party sent in this
+    /// round (including ours) in party order.
+    fn broadcast(&mut self, message: M) -> BoxFuture<'_, Result<Vec<M>, Self::Error>>;
+
+    /// Sends `messages[i]` to party `i` and resolves to the messages each party sent to us in
+    /// party order. Our own message should be included as if we sent it to ourselves.
+    fn send_each(&mut self, messages: Vec<M>) -> BoxFuture<'_, Result<Vec<M>, Self::Error>>;
+}
+
+/// The messages sent during [`musig_sign`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+#[allow(clippy::large_enum_variant)] // nonces are big but messages are short lived
+pub enum MuSigMessage {
+    /// A party's public nonce.
+    Nonce(Nonce),
+    /// A party's partial signature.
+    PartialSignature(Scalar<Public, Zero>),
+}
+
+/// The messages sent during [`frost_keygen`] and [`frost_sign`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+#[allow(clippy::large_enum_variant)] // nonces are big but messages are short lived
+pub enum FrostMessage {
+    /// A party's public polynomial.
+    PointPoly(PointPoly),
+    /// A secret share for the receiving party along with the sender's proof of possession.
+    Share {
+        /// The sender's polynomial evaluated at the receiver's index.
+        share: Scalar<Secret, Zero>,
+        /// Proves the sender knows the first coefficient of their polynomial.
+        proof_of_possession: Signature,
+    },
+    /// A signer's public nonce.
+    Nonce(Nonce),
+    /// A signer's signature share.
+    SignatureShare(Scalar<Public, Zero>),
+}
+
+/// Why a protocol run failed.
+#[derive(Debug, Clone)]
+pub enum DriverError<E> {
+    /// The transport failed.
+    Transport(E),
+    /// The transport returned the wrong number of messages for a round.
+    WrongNumberOfMessages {
+        /// The number of parties in the round.
+        expected: usize,
+        /// The number of messages returned.
+        got: usize,
+    },
+    /// The party at this position sent a message that doesn't belong in this round.
+    UnexpectedMessage(usize),
+    /// The party at this position sent an invalid partial signature or signature share.
+    InvalidSignatureShare(usize),
+    /// The transport gave back a different nonce for us than the one we sent.
+    NonceMismatch,
+    /// The first round of FROST key generation failed.
+    NewKeyGen(NewKeyGenError),
+    /// The second round of FROST key generation failed.
+    FinishKeyGen(FinishKeyGenError),
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for DriverError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use DriverError::*;
+        match self {
+            Transport(e) => write!(f, "transport error: {}", e),
+            WrongNumberOfMessages { expected, got } => write!(
+                f,
+                "expected {} messages in the round but got {}",
+                expected, got
+            ),
+            UnexpectedMessage(i) => write!(f, "party at index {} sent an unexpected message", i),
+            InvalidSignatureShare(i) => {
+                write!(f, "party at index {} sent an invalid signature share", i)
+            }
+            NonceMismatch => write!(f, "transport returned a different nonce for us"),
+            NewKeyGen(e) => write!(f, "{}", e),
+            FinishKeyGen(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: std::error::Error> std::error::Error for DriverError<E> {}
+
+/// A tag byte for the variant followed by the body of its contents.
+impl Encode for MuSigMessage {
+    fn encode_body(&self, bytes: &mut Vec<u8>) {
+        match self {
+            MuSigMessage::Nonce(nonce) => {
+                bytes.push(0);
+                nonce.encode_body(bytes);
+            }
+            MuSigMessage::PartialSignature(sig) => {
+                bytes.push(1);
+                sig.encode_body(bytes);
+            }
+        }
+    }
+}
+
+impl Decode for MuSigMessage {
+    fn decode_body(version: u8, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (tag, body) = bytes.split_first().ok_or(DecodeError::WrongLength)?;
+        match tag {
+            0 => Ok(MuSigMessage::Nonce(Nonce::decode_body(version, body)?)),
+            1 => Ok(MuSigMessage::PartialSignature(Scalar::decode_body(
+                version, body,
+            )?)),
+            _ => Err(DecodeError::Invalid),
+        }
+    }
+}
+
+/// A tag byte for the variant followed by the body of its contents. A share is followed by the
+/// proof of possession.
+impl Encode for FrostMessage {
+    fn encode_body(&self, bytes: &mut Vec<u8>) {
+        match self {
+            FrostMessage::PointPoly(point_poly) => {
+                bytes.push(0);
+                point_poly.encode_body(bytes);
+            }
+            FrostMessage::Share {
+                share,
+                proof_of_possession,
+            } => {
+                bytes.push(1);
+                share.encode_body(bytes);
+                proof_of_possession.encode_body(bytes);
+            }
+            FrostMessage::Nonce(nonce) => {
+                bytes.push(2);
+                nonce.encode_body(bytes);
+            }
+            FrostMessage::SignatureShare(share) => {
+                bytes.push(3);
+                share.encode_body(bytes);
+            }
+        }
+    }
+}
+
+impl Decode for FrostMessage {
+    fn decode_body(version: u8, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (tag, body) = bytes.split_first().ok_or(DecodeError::WrongLength)?;
+        match tag {
+            0 => Ok(FrostMessage::PointPoly(PointPoly::decode_body(
+                version, body,
+            )?)),
+            1 => {
+                if body.len() < 32 {
+                    return Err(DecodeError::WrongLength);
+                }
+                let (share, proof_of_possession) = body.split_at(32);
+                Ok(FrostMessage::Share {
+                    share: Scalar::decode_body(version, share)?,
+                    proof_of_possession: Signature::decode_body(version, proof_of_possession)?,
+                })
+            }
+            2 => Ok(FrostMessage::Nonce(Nonce::decode_body(version, body)?)),
+            3 => Ok(FrostMessage::SignatureShare(Scalar::decode_body(
+                version, body,
+            )?)),
+            _ => Err(DecodeError::Invalid),
+        }
+    }
+}
+
+/// Takes what we need out of each message in a round.
+fn extract<M, T, E>(
+    messages: Vec<M>,
+    expected: usize,
+    f: impl Fn(M) -> Option<T>,
+) -> Result<Vec<T>, DriverError<E>> {
+    if messages.len() != expected {
+        return Err(DriverError::WrongNumberOfMessages {
+            expected,
+            got: messages.len(),
+        });
+    }
+    messages
+        .into_iter()
+        .enumerate()
+        .map(|(i, message)| f(message).ok_or(DriverError::UnexpectedMessage(i)))
+        .collect()
+}
+
+/// Runs a MuSig signing session for `message` as the party at `my_index` in `agg_key`.
+///
+/// `session_id` must be unique for each call if `musig` uses deterministic nonces (see
+/// [`MuSig::gen_nonces`]). Every other party's partial signature is checked before the signature
+/// is returned. If the transport gives back a different nonce for us than the one we sent we stop
+/// with [`DriverError::NonceMismatch`] before signing.
+pub async fn musig_sign<H, NG, T>(
+    musig: &MuSig<H, Schnorr<H, NG>>,
+    transport: &mut T,
+    agg_key: &XOnlyAggKey,
+    my_index: usize,
+    keypair: &KeyPair,
+    session_id: &[u8],
+    message: Message<'_, Public>,
+) -> Result<Signature, DriverError<T::Error>>
+where
+    H: Digest<OutputSize = U32> + Clone,
+    NG: NonceGen,
+    T: Transport<MuSigMessage>,
+{
+    let n_parties = agg_key.keys().count();
+    let nonce = musig.gen_nonces(
+        keypair.secret_key(),
+        session_id,
+        Some(agg_key.agg_public_key()),
+        Some(message),
+    );
+    let nonces = transport
+        .broadcast(MuSigMessage::Nonce(nonce.public()))
+        .await
+        .map_err(DriverError::Transport)?;
+    let nonces = extract(nonces, n_parties, |message| match message {
+        MuSigMessage::Nonce(nonce) => Some(nonce),
+        _ => None,
+    })?;
+    if nonces.get(my_index) != Some(&nonce.public()) {
+        return Err(DriverError::NonceMismatch);
+    }
+
+    let session = musig.start_sign_session(agg_key, nonces, message);
+    let my_sig = musig.sign(agg_key, &session, my_index, keypair, nonce);
+    let sigs = transport
+        .broadcast(MuSigMessage::PartialSignature(my_sig))
+        .await
+        .map_err(DriverError::Transport)?;
+    let sigs = extract(sigs, n_parties, |message| match message {
+        MuSigMessage::PartialSignature(sig) => Some(sig),
+        _ => None,
+    })?;
+    for (i, sig) in sigs.iter().enumerate() {
+        if i != my_index && !musig.verify_partial_signature(agg_key, &session, i, *sig) {
+            return Err(DriverError::InvalidSignatureShare(i));
+        }
+    }
+
+    Ok(musig.combine_partial_signatures(agg_key, &session, sigs))
+}
+
+/// Runs FROST key generation as the party at `my_index` of `n_parties`.
+///
+/// `secret` becomes the first coefficient of our polynomial and `session_id` is used to derive
+/// the rest of it so it must be unique if the instance uses deterministic nonces (see
+/// [`Frost::new_scalar_poly`]).
+pub async fn frost_keygen<H, NG, T>(
+    frost: &Frost<H, NG>,
+    transport: &mut T,
+    my_index: u32,
+    n_parties: usize,
+    threshold: u32,
+    secret: Scalar,
+    session_id: &[u8],
+) -> Result<(Scalar, XOnlyFrostKey), DriverError<T::Error>>
+where
+    H: Digest<OutputSize = U32> + Clone,
+    NG: NonceGen,
+    T: Transport<FrostMessage>,
+{
+    let scalar_poly = frost.new_scalar_poly(secret, threshold, session_id);
+    let point_polys = transport
+        .broadcast(FrostMessage::PointPoly(scalar_poly.to_point_poly()))
+        .await
+        .map_err(DriverError::Transport)?;
+    let point_polys = extract(point_polys, n_parties, |message| match message {
+        FrostMessage::PointPoly(point_poly) => Some(point_poly),
+        _ => None,
+    })?;
+
+    let keygen = frost
+        .new_keygen(point_polys)
+        .map_err(DriverError::NewKeyGen)?;
+    let (shares, proof_of_possession) = frost.create_shares(&keygen, scalar_poly);
+    let received = transport
+        .send_each(
+            shares
+                .into_iter()
+                .map(|share| FrostMessage::Share {
+                    share,
+                    proof_of_possession: proof_of_possession.clone(),
+                })
+                .collect(),
+        )
+        .await
+        .map_err(DriverError::Transport)?;
+    let (shares, proofs_of_possession) = extract(received, n_parties, |message| match message {
+        FrostMessage::Share {
+            share,
+            proof_of_possession,
+        } => Some((share, proof_of_possession)),
+        _ => None,
+    })?
+    .into_iter()
+    .unzip();
+
+    frost
+        .finish_keygen_to_xonly(keygen, my_index, shares, proofs_of_possession)
+        .map_err(DriverError::FinishKeyGen)
+}
+
+/// Runs a FROST signing session for `message` as the party at `my_index`.
+///
+/// `signers` are the indices of the parties taking part in the order the transport uses for them.
+/// `session_id` must be unique for each call if `frost` uses deterministic nonces (see
+/// [`Frost::gen_nonce`]). Every other signer's signature share is checked before the signature is
+/// returned. If the transport gives back a different nonce for us than the one we sent we stop
+/// with [`DriverError::NonceMismatch`] before signing.
+///
+/// # Panics
+///
+/// If `my_index` isn't in `signers`.
+#[allow(clippy::too_many_arguments)]
+pub async fn frost_sign<H, NG, T>(
+    frost: &Frost<H, NG>,
+    transport: &mut T,
+    frost_key: &XOnlyFrostKey,
+    my_index: u32,
+    signers: &[u32],
+    secret_share: &Scalar,
+    session_id: &[u8],
+    message: Message<'_, Public>,
+) -> Result<Signature, DriverError<T::Error>>
+where
+    H: Digest<OutputSize = U32> + Clone,
+    NG: NonceGen,
+    T: Transport<FrostMessage>,
+{
+    let my_position = signers
+        .iter()
+        .position(|index| *index == my_index)
+        .expect("we must be one of the signers");
+    let nonce = frost.gen_nonce(
+        secret_share,
+        session_id,
+        Some(frost_key.public_key()),
+        Some(message),
+    );
+    let nonces = transport
+        .broadcast(FrostMessage::Nonce(nonce.public()))
+        .await
+        .map_err(DriverError::Transport)?;
+    let nonces = extract(nonces, signers.len(), |message| match message {
+        FrostMessage::Nonce(nonce) => Some(nonce),
+        _ => None,
+    })?;
+    if nonces[my_position] != nonce.public() {
+        return Err(DriverError::NonceMismatch);
+    }
+
+    let session = frost.start_sign_session(
+        frost_key,
+        signers.iter().copied().zip(nonces).collect(),
+        message,
+    );
+    let my_share = frost.sign(frost_key, &session, my_index, secret_share, nonce);
+    let shares = transport
+        .broadcast(FrostMessage::SignatureShare(my_share))
+        .await
+        .map_err(DriverError::Transport)?;
+    let shares = extract(shares, signers.len(), |message| match message {
+        FrostMessage::SignatureShare(share) => Some(share),
+        _ => None,
+    })?;
+    for (position, (index, share)) in signers.iter().zip(&shares).enumerate() {
+        if *index != my_index && !frost.verify_signature_share(frost_key, &session, *index, *share)
+        {
+            return Err(DriverError::InvalidSignatureShare(position));
+        }
+    }
+
+    Ok(frost.combine_signature_shares(frost_key, &session, shares))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::nonce::Deterministic;
+    use core::convert::Infallible;
+    use secp256kfun::Point;
+    use sha2::Sha256;
+    use simulation::{Party, Simulation};
+    use std::boxed::Box;
+
+    type Task<'a, T> = Party<'a, Result<T, DriverError<Infallible>>>;
+
+    /// Passes everything through to `inner` but `tamper`s with our own message in each broadcast.
+    struct TamperWithOurs<'a, M> {
+        inner: &'a mut simulation::SimulatedTransport<M>,
+        my_position: usize,
+        tamper: fn(&mut M),
+    }
+
+    impl<M: Clone + Send + 'static> Transport<M> for TamperWithOurs<'_, M> {
+        type Error = Infallible;
+
+        fn broadcast(&mut self, message: M) -> BoxFuture<'_, Result<Vec<M>, Infallible>> {
+            Box::pin(async move {
+                let mut messages = self.inner.broadcast(message).await?;
+                (self.tamper)(&mut messages[self.my_position]);
+                Ok(messages)
+            })
+        }
+
+        fn send_each(&mut self, messages: Vec<M>) -> BoxFuture<'_, Result<Vec<M>, Infallible>> {
+            self.inner.send_each(messages)
+        }
+    }
+
+    fn swap_nonce_points(nonce: &mut Nonce) {
+        nonce.0.swap(0, 1);
+    }
+
+    #[test]
+    fn musig_over_transport() {
+        let musig = crate::musig::new_with_deterministic_nonces::<Sha256>();
+        let keypairs = (0..3)
+            .map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())))
+            .collect::<Vec<_>>();
+        let agg_key = musig
+            .new_agg_key(keypairs.iter().map(|kp| kp.public_key()).collect())
+            .into_xonly_key();
+        let message = Message::<Public>::plain("test", b"async musig");
+        let simulation = Simulation::<MuSigMessage>::new(3);
+        let mut transports = simulation.transports();
+        let signatures = simulation.run(
+            transports
+                .iter_mut()
+                .zip(&keypairs)
+                .enumerate()
+                .map(|(i, (transport, keypair))| {
+                    let (musig, agg_key) = (&musig, &agg_key);
+                    Box::pin(async move {
+                        musig_sign(musig, transport, agg_key, i, keypair, b"sid", message).await
+                    }) as Task<_>
+                })
+                .collect(),
+        );
+        for signature in signatures {
+            assert!(musig.schnorr.verify(
+                &agg_key.agg_public_key(),
+                message,
+                &signature.unwrap().unwrap()
+            ));
+        }
+    }
+
+    #[test]
+    fn frost_keygen_and_sign_over_transport() {
+        let frost = Frost::new(Schnorr::<Sha256, Deterministic<Sha256>>::new(
+            Deterministic::<Sha256>::default(),
+        ));
+        let simulation = Simulation::<FrostMessage>::new(3);
+        let mut transports = simulation.transports();
+        let keys = simulation
+            .run::<Result<(Scalar, XOnlyFrostKey), _>>(
+                transports
+                    .iter_mut()
+                    .enumerate()
+                    .map(|(i, transport)| {
+                        let frost = &frost;
+                        Box::pin(async move {
+                            let secret = Scalar::random(&mut rand::thread_rng());
+                            frost_keygen(frost, transport, i as u32, 3, 2, secret, b"keygen").await
+                        }) as Task<_>
+                    })
+                    .collect(),
+            )
+            .into_iter()
+            .map(|keys| keys.unwrap().unwrap())
+            .collect::<Vec<_>>();
+        let frost_key = keys[0].1.clone();
+        assert!(keys.iter().all(|(_, key)| *key == frost_key));
+
+        let signers = [0, 2];
+        let message = Message::<Public>::plain("test", b"async frost");
+        let simulation = Simulation::<FrostMessage>::new(2);
+        let mut transports = simulation.transports();
+        let signatures = simulation.run(
+            transports
+                .iter_mut()
+                .zip(signers)
+                .map(|(transport, index)| {
+                    let (frost, frost_key) = (&frost, &frost_key);
+                    let secret_share = &keys[index as usize].0;
+                    Box::pin(async move {
+                        frost_sign(
+                            frost,
+                            transport,
+                            frost_key,
+                            index,
+                            &signers,
+                            secret_share,
+                            b"sign",
+                            message,
+                        )
+                        .await
+                    }) as Task<_>
+                })
+                .collect(),
+        );
+        for signature in signatures {
+            assert!(frost.schnorr.verify(
+                &frost_key.public_key(),
+                message,
+                &signature.unwrap().unwrap()
+            ));
+        }
+    }
+
+    #[test]
+    fn musig_rejects_a_different_nonce_for_us() {
+        let musig = crate::musig::new_with_deterministic_nonces::<Sha256>();
+        let keypairs = (0..2)
+            .map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())))
+            .collect::<Vec<_>>();
+        let agg_key = musig
+            .new_agg_key(keypairs.iter().map(|kp| kp.public_key()).collect())
+            .into_xonly_key();
+        let message = Message::<Public>::plain("test", b"async musig");
+        let simulation = Simulation::<MuSigMessage>::new(2);
+        let mut transports = simulation.transports();
+        let (first, second) = transports.split_at_mut(1);
+        let mut tampered = TamperWithOurs {
+            inner: &mut first[0],
+            my_position: 0,
+            tamper: |message| {
+                if let MuSigMessage::Nonce(nonce) = message {
+                    swap_nonce_points(nonce)
+                }
+            },
+        };
+        let results = simulation.run::<Result<Signature, _>>(vec![
+            Box::pin(musig_sign(
+                &musig,
+                &mut tampered,
+                &agg_key,
+                0,
+                &keypairs[0],
+                b"sid",
+                message,
+            )),
+            Box::pin(musig_sign(
+                &musig,
+                &mut second[0],
+                &agg_key,
+                1,
+                &keypairs[1],
+                b"sid",
+                message,
+            )),
+        ]);
+        assert!(matches!(results[0], Some(Err(DriverError::NonceMismatch))));
+    }
+
+    #[test]
+    fn frost_rejects_a_different_nonce_for_us() {
+        let frost = Frost::new(Schnorr::<Sha256, Deterministic<Sha256>>::new(
+            Deterministic::<Sha256>::default(),
+        ));
+        let mut rng = rand::thread_rng();
+        let scalar_poly = crate::frost::ScalarPoly::random(1, &mut rng);
+        let keygen = frost.new_keygen(vec![scalar_poly.to_point_poly()]).unwrap();
+        let (shares, proof_of_possession) = frost.create_shares(&keygen, scalar_poly);
+        let (secret_share, frost_key) = frost
+            .finish_keygen_to_xonly(keygen, 0, shares, vec![proof_of_possession])
+            .unwrap();
+        let message = Message::<Public>::plain("test", b"async frost");
+        let simulation = Simulation::<FrostMessage>::new(1);
+        let mut transports = simulation.transports();
+        let mut tampered = TamperWithOurs {
+            inner: &mut transports[0],
+            my_position: 0,
+            tamper: |message| {
+                if let FrostMessage::Nonce(nonce) = message {
+                    swap_nonce_points(nonce)
+                }
+            },
+        };
+        let results = simulation.run::<Result<Signature, _>>(vec![Box::pin(frost_sign(
+            &frost,
+            &mut tampered,
+            &frost_key,
+            0,
+            &[0],
+            &secret_share,
+            b"sign",
+            message,
+        ))]);
+        assert!(matches!(results[0], Some(Err(DriverError::NonceMismatch))));
+    }
+
+    #[test]
+    fn messages_encode_and_decode() {
+        let mut rng = rand::thread_rng();
+        let nonce = Nonce([Point::random(&mut rng), Point::random(&mut rng)]);
+        let scalar = Scalar::random(&mut rng).mark::<Zero>();
+        for message in [
+            MuSigMessage::Nonce(nonce),
+            MuSigMessage::PartialSignature(scalar.clone().mark::<Public>()),
+        ] {
+            assert_eq!(MuSigMessage::decode(&message.encode()), Ok(message));
+        }
+        for message in [
+            FrostMessage::PointPoly(crate::frost::ScalarPoly::random(2, &mut rng).to_point_poly()),
+            FrostMessage::Share {
+                share: scalar.clone(),
+                proof_of_possession: Signature::random(&mut rng),
+            },
+            FrostMessage::Nonce(nonce),
+            FrostMessage::SignatureShare(scalar.mark::<Public>()),
+        ] {
+            assert_eq!(FrostMessage::decode(&message.encode()), Ok(message));
+        }
+        assert_eq!(
+            MuSigMessage::decode(&[crate::encoding::VERSION, 2]),
+            Err(DecodeError::Invalid)
+        );
+    }
+
+    #[test]
+    fn wrong_message_is_rejected() {
+        let musig = crate::musig::new_with_deterministic_nonces::<Sha256>();
+        let keypairs = (0..2)
+            .map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())))
+            .collect::<Vec<_>>();
+        let agg_key = musig
+            .new_agg_key(keypairs.iter().map(|kp| kp.public_key()).collect())
+            .into_xonly_key();
+        let message = Message::<Public>::plain("test", b"async musig");
+        let simulation = Simulation::<MuSigMessage>::new(2);
+        let mut transports = simulation.transports();
+        let (honest, cheater) = transports.split_at_mut(1);
+        let results = simulation.run::<Result<Signature, _>>(vec![
+            Box::pin(musig_sign(
+                &musig,
+                &mut honest[0],
+                &agg_key,
+                0,
+                &keypairs[0],
+                b"sid",
+                message,
+            )),
+            Box::pin(async {
+                cheater[0]
+                    .broadcast(MuSigMessage::PartialSignature(
+                        Scalar::zero().mark::<Public>(),
+                    ))
+                    .await
+                    .unwrap();
+                Err(DriverError::UnexpectedMessage(1))
+            }),
+        ]);
+        assert!(matches!(
+            results[0],
+            Some(Err(DriverError::UnexpectedMessage(1)))
+        ));
+    }
+}
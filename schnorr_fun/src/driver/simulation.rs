@@ -0,0 +1,458 @@
+//! Run every party of a protocol in one process and make some of them misbehave.
+//!
+//! A [`Simulation`] hands out a [`SimulatedTransport`] for each party. The transports deliver
+//! messages through shared memory so the drivers in [`driver`] (or your own code built on
+//! [`Transport`]) can be run for all parties at once with [`Simulation::run`]. Any transport can
+//! be told to [`tamper`] with what its party sends in a round. This lets you check that your
+//! integration blames the right party or aborts when someone:
+//!
+//! - sends an invalid partial signature ([`replace`] their message in the last round);
+//! - replays a nonce from another session ([`replace`] their nonce with one from an earlier
+//!   simulation's [`posted`] messages). The drivers notice their own nonce coming back different
+//!   and stop with [`DriverError::NonceMismatch`];
+//! - uses the wrong index (give their driver a `my_index` that doesn't match their transport).
+//!   This also shows up as [`DriverError::NonceMismatch`];
+//! - gives up part way through (return early from their future).
+//!
+//! Parties that are still waiting for messages when nobody can make progress any more are
+//! reported as stalled rather than hanging the test.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     driver::{
+//!         musig_sign,
+//!         simulation::{Party, Simulation},
+//!         DriverError, MuSigMessage,
+//!     },
+//!     fun::{marker::*, Scalar},
+//!     musig, Message,
+//! };
+//! let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let keypairs = (0..3)
+//!     .map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())))
+//!     .collect::<Vec<_>>();
+//! let agg_key = musig
+//!     .new_agg_key(keypairs.iter().map(|kp| kp.public_key()).collect())
+//!     .into_xonly_key();
+//! let message = Message::<Public>::plain("test", b"simulated");
+//!
+//! let simulation = Simulation::<MuSigMessage>::new(3);
+//! let mut transports = simulation.transports();
+//! // party 1 sends a bogus partial signature in the second round
+//! transports[1].replace(
+//!     1,
+//!     MuSigMessage::PartialSignature(Scalar::zero().mark::<Public>()),
+//! );
+//! let results = simulation.run(
+//!     transports
+//!         .iter_mut()
+//!         .zip(&keypairs)
+//!         .enumerate()
+//!         .map(|(i, (transport, keypair))| {
+//!             Box::pin(musig_sign(
+//!                 &musig, transport, &agg_key, i, keypair, b"sid", message,
+//!             )) as Party<_>
+//!         })
+//!         .collect(),
+//! );
+//! assert!(matches!(
+//!     results[0],
+//!     Some(Err(DriverError::InvalidSignatureShare(1)))
+//! ));
+//! assert!(matches!(
+//!     results[2],
+//!     Some(Err(DriverError::InvalidSignatureShare(1)))
+//! ));
+//! ```
+//!
+//! [`driver`]: crate::driver
+//! [`tamper`]: SimulatedTransport::tamper
+//! [`replace`]: SimulatedTransport::replace
+//! [`posted`]: Simulation::posted
+//! [`DriverError::NonceMismatch`]: crate::driver::DriverError::NonceMismatch
+use super::{BoxFuture, Transport};
+use crate::Vec;
+use core::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+use std::{
+    boxed::Box,
+    sync::{Arc, Mutex},
+};
+
+/// The future for one party in a [`Simulation`].
+pub type Party<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+type Tamper<M> = Box<dyn FnMut(Vec<M>) -> Vec<M> + Send>;
+
+/// What every party has posted in every round.
+struct Board<M> {
+    rounds: Vec<Vec<Option<Vec<M>>>>,
+    // bumped on every post so the scheduler can tell whether anything happened
+    posts: usize,
+}
+
+/// Runs every party of a protocol in the current thread.
+///
+/// See the [module documentation](self).
+pub struct Simulation<M> {
+    n_parties: usize,
+    board: Arc<Mutex<Board<M>>>,
+}
+
+/// A [`Transport`] for one party in a [`Simulation`].
+pub struct SimulatedTransport<M> {
+    me: usize,
+    n_parties: usize,
+    round: usize,
+    board: Arc<Mutex<Board<M>>>,
+    tampers: Vec<(usize, Tamper<M>)>,
+}
+
+impl<M: Clone + Send + 'static> Simulation<M> {
+    /// Creates a simulation of `n_parties`.
+    pub fn new(n_parties: usize) -> Self {
+        Simulation {
+            n_parties,
+            board: Arc::new(Mutex::new(Board {
+                rounds: Vec::new(),
+                posts: 0,
+            })),
+        }
+    }
+
+    /// The number of parties.
+    pub fn n_parties(&self) -> usize {
+        self.n_parties
+    }
+
+    /// A transport for each party in party order.
+    pub fn transports(&self) -> Vec<SimulatedTransport<M>> {
+        (0..self.n_parties)
+            .map(|me| SimulatedTransport {
+                me,
+                n_parties: self.n_parties,
+                round: 0,
+                board: self.board.clone(),
+                tampers: Vec::new(),
+            })
+            .collect()
+    }
+
+    /// What `party` posted in `round` after any tampering.
+    ///
+    /// This is one message for [`Transport::broadcast`] and one message per party for
+    /// [`Transport::send_each`]. Returns `None` if the party didn't post in that round.
+    pub fn posted(&self, round: usize, party: usize) -> Option<Vec<M>> {
+        let board = self.board.lock().unwrap();
+        board.rounds.get(round)?.get(party)?.clone()
+    }
+
+    /// Polls every party's future until they have all finished or none of them can make progress.
+    ///
+    /// Returns each party's output in the same order or `None` for parties that were still
+    /// waiting for messages. Parties must only wait on their [`SimulatedTransport`].
+    pub fn run<T>(&self, mut parties: Vec<Party<'_, T>>) -> Vec<Option<T>> {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut outputs: Vec<Option<T>> = parties.iter().map(|_| None).collect();
+        loop {
+            let posts_before = self.board.lock().unwrap().posts;
+            let mut finished = false;
+            for (party, output) in parties.iter_mut().zip(outputs.iter_mut()) {
+                if output.is_none() {
+                    if let Poll::Ready(result) = party.as_mut().poll(&mut cx) {
+                        *output = Some(result);
+                        finished = true;
+                    }
+                }
+            }
+            let all_done = outputs.iter().all(Option::is_some);
+            let posted = self.board.lock().unwrap().posts != posts_before;
+            if all_done || !(finished || posted) {
+                return outputs;
+            }
+        }
+    }
+}
+
+impl<M: Clone + Send + 'static> SimulatedTransport<M> {
+    /// The party this transport belongs to.
+    pub fn party(&self) -> usize {
+        self.me
+    }
+
+    /// Changes what this party posts in `round` (counting from `0`).
+    ///
+    /// `f` gets the messages the party tried to post and returns what is actually posted: one
+    /// message for a broadcast and one per party for [`Transport::send_each`]. Returning the
+    /// wrong number of messages makes the receivers see the wrong number of messages.
+    pub fn tamper(&mut self, round: usize, f: impl FnMut(Vec<M>) -> Vec<M> + Send + 'static) {
+        self.tampers.push((round, Box::new(f)));
+    }
+
+    /// Makes this party send `message` to everyone in `round` instead of what it meant to.
+    pub fn replace(&mut self, round: usize, message: M) {
+        self.tamper(round, move |messages| {
+            messages.iter().map(|_| message.clone()).collect()
+        })
+    }
+
+    fn post(&mut self, mut messages: Vec<M>) -> BoxFuture<'_, Result<Vec<M>, Infallible>> {
+        let round = self.round;
+        self.round += 1;
+        for (_, tamper) in self.tampers.iter_mut().filter(|(r, _)| *r == round) {
+            messages = tamper(messages);
+        }
+        {
+            let mut board = self.board.lock().unwrap();
+            while board.rounds.len() <= round {
+                let empty = (0..self.n_parties).map(|_| None).collect();
+                board.rounds.push(empty);
+            }
+            board.rounds[round][self.me] = Some(messages);
+            board.posts += 1;
+        }
+        Box::pin(Round {
+            me: self.me,
+            round,
+            board: self.board.clone(),
+        })
+    }
+}
+
+/// Waits for every party to post in a round.
+struct Round<M> {
+    me: usize,
+    round: usize,
+    board: Arc<Mutex<Board<M>>>,
+}
+
+impl<M: Clone> Future for Round<M> {
+    type Output = Result<Vec<M>, Infallible>;
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let board = self.board.lock().unwrap();
+        match board.rounds[self.round]
+            .iter()
+            .cloned()
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(posted) => Poll::Ready(Ok(posted
+                .into_iter()
+                .filter_map(|messages| {
+                    // broadcasts go to everyone
+                    let i = if messages.len() == 1 { 0 } else { self.me };
+                    messages.get(i).cloned()
+                })
+                .collect())),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// The scheduler polls everything in a loop so it doesn't need to be woken up.
+fn noop_waker() -> Waker {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+    // SAFETY: the vtable functions do nothing with the (null) data pointer
+    unsafe { Waker::from_raw(clone(core::ptr::null())) }
+}
+
+impl<M: Clone + Send + 'static> Transport<M> for SimulatedTransport<M> {
+    type Error = Infallible;
+
+    fn broadcast(&mut self, message: M) -> BoxFuture<'_, Result<Vec<M>, Infallible>> {
+        self.post(vec![message])
+    }
+
+    fn send_each(&mut self, messages: Vec<M>) -> BoxFuture<'_, Result<Vec<M>, Infallible>> {
+        self.post(messages)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        driver::{musig_sign, DriverError, MuSigMessage},
+        fun::{marker::*, s, KeyPair, Scalar},
+        musig::{MuSig, XOnlyAggKey},
+        nonce::Deterministic,
+        Message, Schnorr, Signature,
+    };
+    use sha2::Sha256;
+
+    type TestMuSig = MuSig<Sha256, Schnorr<Sha256, Deterministic<Sha256>>>;
+
+    fn setup(n: usize) -> (TestMuSig, Vec<KeyPair>, XOnlyAggKey) {
+        let musig = crate::musig::new_with_deterministic_nonces::<Sha256>();
+        let keypairs = (0..n)
+            .map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())))
+            .collect::<Vec<_>>();
+        let agg_key = musig
+            .new_agg_key(keypairs.iter().map(|kp| kp.public_key()).collect())
+            .into_xonly_key();
+        (musig, keypairs, agg_key)
+    }
+
+    /// Runs MuSig with party `i` claiming to be at `indices[i]`.
+    fn run_musig(
+        simulation: &Simulation<MuSigMessage>,
+        mut transports: Vec<SimulatedTransport<MuSigMessage>>,
+        (musig, keypairs, agg_key): &(TestMuSig, Vec<KeyPair>, XOnlyAggKey),
+        indices: &[usize],
+        session_id: &[u8],
+    ) -> Vec<Option<Result<Signature, DriverError<Infallible>>>> {
+        let message = Message::<Public>::plain("test", b"simulation");
+        simulation.run(
+            transports
+                .iter_mut()
+                .zip(keypairs)
+                .zip(indices)
+                .map(|((transport, keypair), i)| {
+                    Box::pin(musig_sign(
+                        musig, transport, agg_key, *i, keypair, session_id, message,
+                    )) as Party<_>
+                })
+                .collect(),
+        )
+    }
+
+    fn blamed(
+        results: &[Option<Result<Signature, DriverError<Infallible>>>],
+        party: usize,
+    ) -> bool {
+        results.iter().enumerate().all(|(i, result)| {
+            i == party
+                || matches!(result, Some(Err(DriverError::InvalidSignatureShare(p))) if *p == party)
+        })
+    }
+
+    #[test]
+    fn honest_run() {
+        let setup = setup(3);
+        let simulation = Simulation::new(3);
+        let results = run_musig(
+            &simulation,
+            simulation.transports(),
+            &setup,
+            &[0, 1, 2],
+            b"sid",
+        );
+        assert!(results
+            .into_iter()
+            .all(|result| matches!(result, Some(Ok(_)))));
+        assert!(simulation.posted(1, 2).is_some());
+        assert!(simulation.posted(2, 0).is_none());
+    }
+
+    #[test]
+    fn bad_partial_signature() {
+        let setup = setup(3);
+        let simulation = Simulation::new(3);
+        let mut transports = simulation.transports();
+        transports[2].tamper(1, |messages| {
+            messages
+                .into_iter()
+                .map(|message| match message {
+                    MuSigMessage::PartialSignature(sig) => {
+                        MuSigMessage::PartialSignature(s!(sig + { Scalar::one() }).mark::<Public>())
+                    }
+                    other => other,
+                })
+                .collect()
+        });
+        let results = run_musig(&simulation, transports, &setup, &[0, 1, 2], b"sid");
+        assert!(blamed(&results, 2));
+    }
+
+    #[test]
+    fn replayed_nonce() {
+        let setup = setup(3);
+        let earlier = Simulation::new(3);
+        run_musig(
+            &earlier,
+            earlier.transports(),
+            &setup,
+            &[0, 1, 2],
+            b"earlier",
+        );
+        let old_nonce = earlier.posted(0, 1).unwrap().remove(0);
+
+        let simulation = Simulation::new(3);
+        let mut transports = simulation.transports();
+        transports[1].replace(0, old_nonce);
+        let results = run_musig(&simulation, transports, &setup, &[0, 1, 2], b"sid");
+        // party 1 sees that the nonce it is about to sign with isn't the one it posted
+        assert!(matches!(results[1], Some(Err(DriverError::NonceMismatch))));
+        assert!(results[0].is_none() && results[2].is_none());
+    }
+
+    #[test]
+    fn wrong_index() {
+        let (musig, mut keypairs, agg_key) = setup(3);
+        let simulation = Simulation::new(3);
+        // parties 0 and 1 each think they are the other one
+        keypairs.swap(0, 1);
+        let setup = (musig, keypairs, agg_key);
+        let results = run_musig(
+            &simulation,
+            simulation.transports(),
+            &setup,
+            &[1, 0, 2],
+            b"sid",
+        );
+        // the nonces at the indices they claim aren't theirs
+        assert!(matches!(results[0], Some(Err(DriverError::NonceMismatch))));
+        assert!(matches!(results[1], Some(Err(DriverError::NonceMismatch))));
+        assert!(results[2].is_none());
+    }
+
+    #[test]
+    fn party_gives_up() {
+        let (musig, keypairs, agg_key) = setup(2);
+        let simulation = Simulation::<MuSigMessage>::new(2);
+        let mut transports = simulation.transports();
+        let message = Message::<Public>::plain("test", b"simulation");
+        let (honest, quitter) = transports.split_at_mut(1);
+        let results = simulation.run(vec![
+            Box::pin(musig_sign(
+                &musig,
+                &mut honest[0],
+                &agg_key,
+                0,
+                &keypairs[0],
+                b"sid",
+                message,
+            )),
+            Box::pin(async {
+                // sends a nonce and never signs
+                let nonce = musig.gen_nonces(
+                    keypairs[1].secret_key(),
+                    b"sid",
+                    Some(agg_key.agg_public_key()),
+                    Some(message),
+                );
+                quitter[0]
+                    .broadcast(MuSigMessage::Nonce(nonce.public()))
+                    .await
+                    .unwrap();
+                Err(DriverError::UnexpectedMessage(1))
+            }),
+        ]);
+        assert!(results[0].is_none());
+        assert!(matches!(
+            results[1],
+            Some(Err(DriverError::UnexpectedMessage(1)))
+        ));
+    }
+}
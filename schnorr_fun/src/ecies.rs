@@ -0,0 +1,232 @@
+//! Encrypting messages to secp256k1 public keys (ECIES).
+//!
+//! If your application already identifies people by their secp256k1 public keys (e.g. the keys
+//! they sign with) this lets you encrypt a payload so only the holder of the secret key can read
+//! it. It works with both ordinary and x-only (BIP340) public keys because only the x-coordinate of
+//! the shared point is used.
+//!
+//! The sender generates an ephemeral key `r` and does ECDH with the recipient's key `X`. The
+//! x-coordinate of `r * X` goes through HKDF-SHA256 together with the ephemeral public key and
+//! the recipient's key to derive a key for ChaCha20-Poly1305 ([RFC 8439]) which encrypts the
+//! payload and authenticates it along with any associated data. Each key is only ever used for
+//! one message so the AEAD nonce is always zero.
+//!
+//! The wire format is:
+//!
+//! ```text
+//! version (1) | ephemeral public key (33) | ciphertext (same length as the payload) | tag (16)
+//! ```
+//!
+//! The only version is [`VERSION`]. A future version with a different construction will use a
+//! different first byte.
+//!
+//! # Synopsis
+//!
+//! ```
+//! use schnorr_fun::{ecies, fun::Scalar};
+//! # let schnorr = schnorr_fun::test_instance!();
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let ciphertext = ecies::encrypt(
+//!     &keypair.public_key(),
+//!     b"meet me at the usual place",
+//!     b"message-42",
+//!     &mut rand::thread_rng(),
+//! );
+//! let plaintext = ecies::decrypt(keypair.secret_key(), &ciphertext, b"message-42").unwrap();
+//! assert_eq!(plaintext, b"meet me at the usual place");
+//! // the associated data must match
+//! assert!(ecies::decrypt(keypair.secret_key(), &ciphertext, b"message-43").is_err());
+//! ```
+//!
+//! [RFC 8439]: https://www.rfc-editor.org/rfc/rfc8439
+use crate::{
+    aead::{self, TAG_LEN},
+    fun::{
+        g,
+        marker::*,
+        rand_core::{CryptoRng, RngCore},
+        Point, Scalar, G,
+    },
+    hmac::hkdf_sha256,
+    Vec,
+};
+
+/// The version byte at the start of ciphertexts produced by this module.
+pub const VERSION: u8 = 1;
+
+/// The HKDF salt.
+const SALT: &[u8] = b"schnorr_fun/ecies";
+const HEADER_LEN: usize = 1 + 33;
+/// Every key encrypts exactly one message so a fixed nonce is fine.
+const NONCE: [u8; 12] = [0u8; 12];
+
+/// Encrypts `plaintext` to `recipient`.
+///
+/// `associated_data` isn't encrypted or included in the output but it is authenticated: the
+/// ciphertext only decrypts if the same associated data is given to [`decrypt`]. Use it to bind
+/// the ciphertext to its context (e.g. the sender and a message id) so it can't be replayed
+/// somewhere else. It can be empty.
+pub fn encrypt(
+    recipient: &Point<impl Normalized>,
+    plaintext: &[u8],
+    associated_data: &[u8],
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Vec<u8> {
+    let r = Scalar::random(rng);
+    let X = recipient;
+    let shared = g!(r * X).normalize();
+    let ephemeral_key = g!(r * G).normalize();
+
+    let mut out = Vec::with_capacity(HEADER_LEN + plaintext.len() + TAG_LEN);
+    out.push(VERSION);
+    out.extend_from_slice(&ephemeral_key.to_bytes());
+    let key = derive_key(shared, &out, &X.to_xonly_bytes());
+    out.extend_from_slice(plaintext);
+    let tag = aead::seal(&key, &NONCE, associated_data, &mut out[HEADER_LEN..]);
+    out.extend_from_slice(&tag);
+    out
+}
+
+/// Decrypts a ciphertext from [`encrypt`] with the recipient's secret key.
+///
+/// `secret_key` can be the secret key of either the ordinary or the x-only public key the
+/// ciphertext was encrypted to.
+pub fn decrypt(
+    secret_key: &Scalar,
+    ciphertext: &[u8],
+    associated_data: &[u8],
+) -> Result<Vec<u8>, DecryptError> {
+    match ciphertext.first() {
+        None => return Err(DecryptError::TooShort),
+        Some(&VERSION) => {}
+        Some(&version) => return Err(DecryptError::UnknownVersion(version)),
+    }
+    if ciphertext.len() < HEADER_LEN + TAG_LEN {
+        return Err(DecryptError::TooShort);
+    }
+    let (header, rest) = ciphertext.split_at(HEADER_LEN);
+    let (body, received_tag) = rest.split_at(rest.len() - TAG_LEN);
+    let R = Point::<Normal, Public, NonZero>::from_slice(&header[1..])
+        .ok_or(DecryptError::InvalidEphemeralKey)?;
+    let x = secret_key;
+    let shared = g!(x * R).normalize();
+    let X = g!(x * G).normalize();
+    let key = derive_key(shared, header, &X.to_xonly_bytes());
+    let mut plaintext = body.to_vec();
+    aead::open(&key, &NONCE, associated_data, &mut plaintext, received_tag)
+        .map_err(|_| DecryptError::InvalidTag)?;
+    Ok(plaintext)
+}
+
+/// Why a ciphertext couldn't be decrypted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecryptError {
+    /// The ciphertext was too short to have a header and a tag.
+    TooShort,
+    /// The ciphertext starts with a version byte that isn't [`VERSION`].
+    UnknownVersion(u8),
+    /// The ephemeral public key isn't a valid point.
+    InvalidEphemeralKey,
+    /// The ciphertext was encrypted to another key, was modified or the associated data is
+    /// different.
+    InvalidTag,
+}
+
+impl core::fmt::Display for DecryptError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            DecryptError::TooShort => write!(f, "ciphertext is too short"),
+            DecryptError::UnknownVersion(version) => {
+                write!(f, "unknown ciphertext version {}", version)
+            }
+            DecryptError::InvalidEphemeralKey => write!(f, "invalid ephemeral public key"),
+            DecryptError::InvalidTag => write!(f, "ciphertext failed authentication"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecryptError {}
+
+/// Derives the AEAD key from the shared point, the header (version and ephemeral key) and the
+/// recipient's x-only key.
+fn derive_key(
+    shared: Point<Normal, impl Secrecy>,
+    header: &[u8],
+    recipient: &[u8; 32],
+) -> [u8; 32] {
+    hkdf_sha256::<32>(SALT, &shared.x_bytes(), &[header, &recipient[..]])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::{KeyPair, XOnlyKeyPair};
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let mut rng = rand::thread_rng();
+        for len in [0, 1, 31, 32, 33, 100] {
+            let keypair = KeyPair::new(Scalar::random(&mut rng));
+            let xonly = XOnlyKeyPair::new(keypair.secret_key().clone());
+            let plaintext = (0..len).map(|i| i as u8).collect::<Vec<_>>();
+            let ciphertext = encrypt(&keypair.public_key(), &plaintext, b"ad", &mut rng);
+            assert_eq!(ciphertext.len(), len + HEADER_LEN + TAG_LEN);
+            assert_eq!(ciphertext[0], VERSION);
+            assert_eq!(
+                decrypt(keypair.secret_key(), &ciphertext, b"ad").unwrap(),
+                plaintext
+            );
+            // the x-only key for the same secret works either way
+            let ciphertext = encrypt(&xonly.public_key(), &plaintext, b"ad", &mut rng);
+            assert_eq!(
+                decrypt(keypair.secret_key(), &ciphertext, b"ad").unwrap(),
+                plaintext
+            );
+            assert_eq!(
+                decrypt(xonly.secret_key(), &ciphertext, b"ad").unwrap(),
+                plaintext
+            );
+        }
+    }
+
+    #[test]
+    fn decrypt_failures() {
+        let mut rng = rand::thread_rng();
+        let keypair = KeyPair::new(Scalar::random(&mut rng));
+        let ciphertext = encrypt(&keypair.public_key(), b"hello", b"ad", &mut rng);
+        let x = keypair.secret_key();
+
+        assert_eq!(
+            decrypt(x, &ciphertext, b"da"),
+            Err(DecryptError::InvalidTag)
+        );
+        let other = Scalar::random(&mut rng);
+        assert_eq!(
+            decrypt(&other, &ciphertext, b"ad"),
+            Err(DecryptError::InvalidTag)
+        );
+        for i in [HEADER_LEN, ciphertext.len() - 1] {
+            let mut tampered = ciphertext.clone();
+            tampered[i] ^= 1;
+            assert_eq!(decrypt(x, &tampered, b"ad"), Err(DecryptError::InvalidTag));
+        }
+        let mut tampered = ciphertext.clone();
+        tampered[0] = 2;
+        assert_eq!(
+            decrypt(x, &tampered, b"ad"),
+            Err(DecryptError::UnknownVersion(2))
+        );
+        let mut tampered = ciphertext.clone();
+        tampered[1] = 5;
+        assert_eq!(
+            decrypt(x, &tampered, b"ad"),
+            Err(DecryptError::InvalidEphemeralKey)
+        );
+        assert_eq!(
+            decrypt(x, &ciphertext[..HEADER_LEN + TAG_LEN - 1], b"ad"),
+            Err(DecryptError::TooShort)
+        );
+        assert_eq!(decrypt(x, &[], b"ad"), Err(DecryptError::TooShort));
+    }
+}
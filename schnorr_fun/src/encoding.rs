@@ -0,0 +1,268 @@
+//! Canonical versioned binary encodings for protocol messages.
+//!
+//! Systems that run MuSig or FROST between peers tend to live a long time and their peers don't
+//! all upgrade at once. To let message formats change without breaking older peers every
+//! encoding produced by [`Encode`] starts with a one byte version ([`VERSION`]) followed by the
+//! body of the message. [`Decode`] checks the version and hands it to the type being decoded so
+//! that newer versions of this library can keep reading the messages of older ones.
+//!
+//! The encodings are canonical: there is exactly one encoding of each value and decoding rejects
+//! anything else (trailing bytes, scalars that aren't reduced, invalid points). This makes them
+//! safe to hash or sign. If you use a format like bincode or CBOR for the rest of your protocol
+//! you can embed these as byte strings.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     binonce::Nonce,
+//!     encoding::{Decode, Encode, VERSION},
+//!     fun::Point,
+//! };
+//! let nonce = Nonce([
+//!     Point::random(&mut rand::thread_rng()),
+//!     Point::random(&mut rand::thread_rng()),
+//! ]);
+//! let bytes = nonce.encode();
+//! assert_eq!(bytes[0], VERSION);
+//! assert_eq!(Nonce::decode(&bytes), Ok(nonce));
+//! ```
+use crate::{
+    binonce::{Nonce, NonceCommitment},
+    frost::PointPoly,
+    fun::{marker::*, Point, Scalar},
+    policy::ThresholdPolicy,
+    Signature, Vec,
+};
+
+/// The version of the encodings produced by [`Encode`].
+pub const VERSION: u8 = 1;
+
+/// Types that have a canonical versioned binary encoding.
+pub trait Encode {
+    /// Appends the encoding of `self` without the version prefix to `bytes`.
+    fn encode_body(&self, bytes: &mut Vec<u8>);
+
+    /// Encodes `self` as [`VERSION`] followed by its body.
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = vec![VERSION];
+        self.encode_body(&mut bytes);
+        bytes
+    }
+}
+
+/// Types that can be decoded from what [`Encode`] produces.
+pub trait Decode: Sized {
+    /// Decodes the body of a message that was encoded with `version`.
+    ///
+    /// The body must be consumed entirely. `version` is always a version this library knows about.
+    fn decode_body(version: u8, bytes: &[u8]) -> Result<Self, DecodeError>;
+
+    /// Decodes a message starting with its version.
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (version, body) = bytes.split_first().ok_or(DecodeError::Empty)?;
+        if !(1..=VERSION).contains(version) {
+            return Err(DecodeError::UnknownVersion(*version));
+        }
+        Self::decode_body(*version, body)
+    }
+}
+
+/// Why a message couldn't be decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// There were no bytes to decode.
+    Empty,
+    /// The message was encoded with a version this library doesn't know about.
+    UnknownVersion(u8),
+    /// The body was not the right length for the type.
+    WrongLength,
+    /// The body had the right length but was not a valid encoding (e.g. an invalid point).
+    Invalid,
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            DecodeError::Empty => write!(f, "message was empty"),
+            DecodeError::UnknownVersion(version) => {
+                write!(f, "message has unknown encoding version {}", version)
+            }
+            DecodeError::WrongLength => write!(f, "message body had the wrong length"),
+            DecodeError::Invalid => write!(f, "message body was not a valid encoding"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+/// Reads a fixed length body.
+fn fixed<const N: usize>(bytes: &[u8]) -> Result<[u8; N], DecodeError> {
+    use core::convert::TryInto;
+    bytes.try_into().map_err(|_| DecodeError::WrongLength)
+}
+
+impl Encode for Nonce {
+    fn encode_body(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_bytes())
+    }
+}
+
+impl Decode for Nonce {
+    fn decode_body(_version: u8, bytes: &[u8]) -> Result<Self, DecodeError> {
+        Nonce::from_bytes(fixed(bytes)?).ok_or(DecodeError::Invalid)
+    }
+}
+
+impl Encode for NonceCommitment {
+    fn encode_body(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_bytes())
+    }
+}
+
+impl Decode for NonceCommitment {
+    fn decode_body(_version: u8, bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(NonceCommitment::from_bytes(fixed(bytes)?))
+    }
+}
+
+/// Partial signatures, signature shares and secret shares.
+impl<S> Encode for Scalar<S, Zero> {
+    fn encode_body(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_bytes())
+    }
+}
+
+impl<S: Secrecy> Decode for Scalar<S, Zero> {
+    fn decode_body(_version: u8, bytes: &[u8]) -> Result<Self, DecodeError> {
+        Scalar::from_bytes(fixed(bytes)?)
+            .map(|scalar| scalar.mark::<S>())
+            .ok_or(DecodeError::Invalid)
+    }
+}
+
+impl Encode for Signature {
+    fn encode_body(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_bytes())
+    }
+}
+
+impl Decode for Signature {
+    fn decode_body(_version: u8, bytes: &[u8]) -> Result<Self, DecodeError> {
+        Signature::from_bytes(fixed(bytes)?).ok_or(DecodeError::Invalid)
+    }
+}
+
+/// The compressed points one after the other. The number of coefficients is implied by the length.
+impl Encode for PointPoly {
+    fn encode_body(&self, bytes: &mut Vec<u8>) {
+        for point in &self.0 {
+            bytes.extend_from_slice(&point.to_bytes())
+        }
+    }
+}
+
+impl Decode for PointPoly {
+    fn decode_body(_version: u8, bytes: &[u8]) -> Result<Self, DecodeError> {
+        if bytes.is_empty() || bytes.len() % 33 != 0 {
+            return Err(DecodeError::WrongLength);
+        }
+        bytes
+            .chunks(33)
+            .map(|chunk| Point::from_slice(chunk).ok_or(DecodeError::Invalid))
+            .collect::<Result<Vec<_>, _>>()
+            .map(PointPoly)
+    }
+}
+
+/// The threshold as a big-endian `u32` followed by the x-only keys in canonical order.
+impl Encode for ThresholdPolicy {
+    fn encode_body(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.threshold().to_be_bytes());
+        for key in self.keys() {
+            bytes.extend_from_slice(&key.to_xonly_bytes());
+        }
+    }
+}
+
+impl Decode for ThresholdPolicy {
+    fn decode_body(_version: u8, bytes: &[u8]) -> Result<Self, DecodeError> {
+        if bytes.len() < 4 || (bytes.len() - 4) % 32 != 0 {
+            return Err(DecodeError::WrongLength);
+        }
+        let (threshold, keys) = bytes.split_at(4);
+        let keys = keys
+            .chunks(32)
+            .map(|chunk| Point::from_xonly_bytes(fixed(chunk)?).ok_or(DecodeError::Invalid))
+            .collect::<Result<Vec<_>, _>>()?;
+        let policy = ThresholdPolicy::new(u32::from_be_bytes(fixed(threshold)?), keys.clone())
+            .map_err(|_| DecodeError::Invalid)?;
+        // only the canonical (sorted) order is a valid encoding
+        if policy.keys() != keys.as_slice() {
+            return Err(DecodeError::Invalid);
+        }
+        Ok(policy)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frost::ScalarPoly;
+
+    fn round_trip<T: Encode + Decode + PartialEq + core::fmt::Debug>(value: T) {
+        let bytes = value.encode();
+        assert_eq!(bytes[0], VERSION);
+        assert_eq!(T::decode(&bytes), Ok(value));
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(T::decode(&trailing).is_err());
+        assert_eq!(
+            T::decode(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::WrongLength)
+        );
+    }
+
+    #[test]
+    fn encodings_round_trip() {
+        let mut rng = rand::thread_rng();
+        round_trip(Nonce([Point::random(&mut rng), Point::random(&mut rng)]));
+        round_trip(NonceCommitment([7u8; 32]));
+        round_trip(Scalar::random(&mut rng).mark::<(Public, Zero)>());
+        round_trip(Scalar::random(&mut rng).mark::<Zero>());
+        round_trip(Signature::random(&mut rng));
+        round_trip(ScalarPoly::random(3, &mut rng).to_point_poly());
+    }
+
+    #[test]
+    fn versions_are_checked() {
+        let sig = Scalar::random(&mut rand::thread_rng()).mark::<(Public, Zero)>();
+        let mut bytes = sig.encode();
+        bytes[0] = VERSION + 1;
+        assert_eq!(
+            Scalar::<Public, Zero>::decode(&bytes),
+            Err(DecodeError::UnknownVersion(VERSION + 1))
+        );
+        bytes[0] = 0;
+        assert_eq!(
+            Scalar::<Public, Zero>::decode(&bytes),
+            Err(DecodeError::UnknownVersion(0))
+        );
+        assert_eq!(Scalar::<Public, Zero>::decode(&[]), Err(DecodeError::Empty));
+    }
+
+    #[test]
+    fn non_canonical_encodings_are_rejected() {
+        let mut bytes = vec![VERSION];
+        bytes.extend_from_slice(&[0xff; 32]);
+        assert_eq!(
+            Scalar::<Public, Zero>::decode(&bytes),
+            Err(DecodeError::Invalid)
+        );
+        let mut bytes = vec![VERSION];
+        bytes.extend_from_slice(&[0x05; 66]);
+        assert_eq!(Nonce::decode(&bytes), Err(DecodeError::Invalid));
+        assert_eq!(PointPoly::decode(&[VERSION]), Err(DecodeError::WrongLength));
+    }
+}
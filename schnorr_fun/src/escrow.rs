@@ -0,0 +1,611 @@
+//! Scriptless 2-of-3 escrow between a buyer and a seller with a mediator.
+//!
+//! The buyer locks the coins under a 2-of-2 [MuSig] key shared with the seller. Before the funding
+//! transaction is broadcast the two of them jointly produce two [adaptor] signatures:
+//!
+//! - one on the _release_ transaction (paying the seller) encrypted under the mediator's
+//!   `release` key.
+//! - one on the _refund_ transaction (paying the buyer) encrypted under the mediator's `refund`
+//!   key.
+//!
+//! If the buyer and seller agree they just sign a transaction with ordinary MuSig. If there is a
+//! dispute the mediator publishes a [`Decision`] containing the decryption key for one of the
+//! outcomes and whoever it favours can decrypt the corresponding signature. The mediator can never
+//! spend the coins and learns nothing about them unless there is a dispute. Any two of the three
+//! parties can move the coins but on-chain it looks like a single key spend.
+//!
+//! Each party goes through the states [`Escrow`] → [`AwaitingNonces`] →
+//! [`AwaitingPartialSignatures`] → [`Presigned`] by exchanging a [`NonceMessage`] and then a
+//! [`PartialSignatureMessage`] with the other. The funding transaction must only be broadcast
+//! once the buyer reaches [`Presigned`].
+//!
+//! ## Synopsis
+//!
+//! ```
+//! use schnorr_fun::{
+//!     escrow::{Escrow, MediatorSecrets, Outcome, Role},
+//!     fun::Scalar,
+//!     musig, Message,
+//! };
+//! let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let mut rng = rand::thread_rng();
+//! let mediator = MediatorSecrets::random(&mut rng);
+//! let buyer_keypair = musig.new_keypair(Scalar::random(&mut rng));
+//! let seller_keypair = musig.new_keypair(Scalar::random(&mut rng));
+//! # let buyer_public_key = buyer_keypair.public_key();
+//! let buyer = Escrow::new(
+//!     &musig,
+//!     Role::Buyer,
+//!     buyer_keypair,
+//!     seller_keypair.public_key(),
+//!     mediator.public(),
+//! );
+//! # let seller = Escrow::new(
+//! #     &musig,
+//! #     Role::Seller,
+//! #     seller_keypair,
+//! #     buyer_public_key,
+//! #     mediator.public(),
+//! # );
+//! // the buyer sends the coins here (but not yet!)
+//! let escrow_key = buyer.agg_key().agg_public_key();
+//!
+//! let (buyer, buyer_nonces) = buyer.start(&musig, b"escrow-42", &mut rng);
+//! # let (seller, seller_nonces) = seller.start(&musig, b"escrow-42", &mut rng);
+//! let release = Message::<_>::plain("escrow", b"release tx");
+//! let refund = Message::<_>::plain("escrow", b"refund tx");
+//! // send buyer_nonces to the seller and receive theirs
+//! let (buyer, buyer_sigs) = buyer
+//!     .receive_nonces(&musig, seller_nonces, release, refund)
+//!     .unwrap();
+//! # let (seller, seller_sigs) = seller
+//! #     .receive_nonces(&musig, buyer_nonces, release, refund)
+//! #     .unwrap();
+//! // send buyer_sigs to the seller and receive theirs
+//! let buyer = buyer
+//!     .receive_partial_signatures(&musig, seller_sigs)
+//!     .expect("seller's partial signatures were valid");
+//! # let seller = seller.receive_partial_signatures(&musig, buyer_sigs).unwrap();
+//! // now it's safe to fund the escrow.
+//!
+//! // the buyer never pays so the mediator decides for the seller
+//! let decision = mediator.decide(Outcome::Release);
+//! let signature = seller.resolve(&musig, &decision).unwrap();
+//! assert!(musig.schnorr.verify(&escrow_key, release, &signature));
+//! ```
+//!
+//! [MuSig]: crate::musig
+//! [adaptor]: crate::adaptor
+use crate::{
+    adaptor::{Adaptor, EncryptedSignature},
+    binonce::{Nonce, NonceKeyPair},
+    musig::{self, MuSig, SignSession, XOnlyAggKey},
+    Message, Schnorr, Signature,
+};
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    marker::*,
+    nonce::NonceGen,
+    rand_core::{CryptoRng, RngCore},
+    KeyPair, Point, Scalar, G,
+};
+
+/// Which side of the escrow a party is on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub enum Role {
+    /// The party paying into the escrow. Their key is first in the aggregate key.
+    Buyer,
+    /// The party being paid. Their key is second in the aggregate key.
+    Seller,
+}
+
+impl Role {
+    fn index(self) -> usize {
+        match self {
+            Role::Buyer => 0,
+            Role::Seller => 1,
+        }
+    }
+}
+
+/// The two ways a dispute can be resolved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub enum Outcome {
+    /// The coins go to the seller.
+    Release,
+    /// The coins go back to the buyer.
+    Refund,
+}
+
+/// The mediator's public encryption keys for each [`Outcome`].
+///
+/// The mediator should use fresh keys for each escrow.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct MediatorKeys {
+    /// Encryption key for the [`Outcome::Release`] signature.
+    pub release: Point,
+    /// Encryption key for the [`Outcome::Refund`] signature.
+    pub refund: Point,
+}
+
+impl MediatorKeys {
+    /// The encryption key for `outcome`.
+    pub fn key_for(&self, outcome: Outcome) -> Point {
+        match outcome {
+            Outcome::Release => self.release,
+            Outcome::Refund => self.refund,
+        }
+    }
+}
+
+/// The mediator's secret decryption keys for a single escrow.
+#[derive(Clone, Debug)]
+pub struct MediatorSecrets {
+    release: Scalar,
+    refund: Scalar,
+}
+
+impl MediatorSecrets {
+    /// Generates the decryption keys for a new escrow.
+    pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        MediatorSecrets {
+            release: Scalar::random(rng),
+            refund: Scalar::random(rng),
+        }
+    }
+
+    /// The encryption keys to give to the buyer and seller.
+    pub fn public(&self) -> MediatorKeys {
+        MediatorKeys {
+            release: g!(self.release * G).normalize(),
+            refund: g!(self.refund * G).normalize(),
+        }
+    }
+
+    /// Resolves a dispute in favour of `outcome`.
+    ///
+    /// The mediator must only ever do this for one outcome.
+    pub fn decide(&self, outcome: Outcome) -> Decision {
+        let decryption_key = match outcome {
+            Outcome::Release => self.release.clone(),
+            Outcome::Refund => self.refund.clone(),
+        };
+        Decision {
+            outcome,
+            decryption_key,
+        }
+    }
+}
+
+/// The mediator's resolution of a dispute.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct Decision {
+    /// The outcome the mediator decided on.
+    pub outcome: Outcome,
+    /// The decryption key for that outcome's encrypted signature.
+    pub decryption_key: Scalar,
+}
+
+/// The public nonces for the release and refund signing sessions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct NonceMessage {
+    /// Nonce for the release signature.
+    pub release: Nonce,
+    /// Nonce for the refund signature.
+    pub refund: Nonce,
+}
+
+/// The partial encrypted signatures on the release and refund transactions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct PartialSignatureMessage {
+    /// Partial encrypted signature on the release transaction.
+    pub release: Scalar<Public, Zero>,
+    /// Partial encrypted signature on the refund transaction.
+    pub refund: Scalar<Public, Zero>,
+}
+
+/// A party's view of an escrow before any messages have been exchanged.
+///
+/// This deliberately doesn't implement `Clone` so that each escrow is only [`start`]ed once.
+///
+/// [`start`]: Escrow::start
+#[derive(Debug)]
+pub struct Escrow {
+    keypair: KeyPair,
+    terms: Terms,
+}
+
+/// The public parts of an escrow that every state needs.
+#[derive(Debug, Clone)]
+struct Terms {
+    role: Role,
+    agg_key: XOnlyAggKey,
+    mediator: MediatorKeys,
+}
+
+impl Escrow {
+    /// Sets up an escrow from one party's point of view.
+    pub fn new<H: Digest<OutputSize = U32> + Clone, S>(
+        musig: &MuSig<H, S>,
+        role: Role,
+        keypair: KeyPair,
+        other_key: Point,
+        mediator: MediatorKeys,
+    ) -> Self {
+        let keys = match role {
+            Role::Buyer => vec![keypair.public_key(), other_key],
+            Role::Seller => vec![other_key, keypair.public_key()],
+        };
+        Escrow {
+            keypair,
+            terms: Terms {
+                role,
+                agg_key: musig.new_agg_key(keys).into_xonly_key(),
+                mediator,
+            },
+        }
+    }
+
+    /// The key the coins are locked under.
+    pub fn agg_key(&self) -> &XOnlyAggKey {
+        &self.terms.agg_key
+    }
+
+    /// Generates nonces for the release and refund signatures.
+    ///
+    /// The nonces can't depend on the other party's nonces (we have to send ours first) so 32
+    /// bytes from `rng` are mixed into them along with `session_id`. This keeps them fresh even
+    /// if `musig` uses deterministic nonces and the same escrow is somehow started twice: reusing
+    /// a nonce against different nonces from the other party would leak our secret key.
+    pub fn start<H: Digest<OutputSize = U32> + Clone, NG: NonceGen>(
+        self,
+        musig: &MuSig<H, Schnorr<H, NG>>,
+        session_id: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (AwaitingNonces, NonceMessage) {
+        let agg_public_key = Some(self.terms.agg_key.agg_public_key());
+        let mut randomness = [0u8; 32];
+        rng.fill_bytes(&mut randomness);
+        let mut release_id = session_id.to_vec();
+        release_id.extend_from_slice(b"/release/");
+        release_id.extend_from_slice(&randomness);
+        let mut refund_id = session_id.to_vec();
+        refund_id.extend_from_slice(b"/refund/");
+        refund_id.extend_from_slice(&randomness);
+        let release =
+            musig.gen_nonces(self.keypair.secret_key(), &release_id, agg_public_key, None);
+        let refund = musig.gen_nonces(self.keypair.secret_key(), &refund_id, agg_public_key, None);
+        let message = NonceMessage {
+            release: release.public(),
+            refund: refund.public(),
+        };
+        (
+            AwaitingNonces {
+                escrow: self,
+                nonces: [release, refund],
+                public_nonces: message,
+            },
+            message,
+        )
+    }
+}
+
+/// Waiting for the other party's [`NonceMessage`].
+///
+/// This holds our secret nonces so it deliberately doesn't implement `Clone`: signing twice from
+/// copies of it with different nonces from the other party would leak our secret key.
+#[derive(Debug)]
+pub struct AwaitingNonces {
+    escrow: Escrow,
+    nonces: [NonceKeyPair; 2],
+    public_nonces: NonceMessage,
+}
+
+impl AwaitingNonces {
+    /// Starts the signing sessions for the `release` and `refund` transactions with the other
+    /// party's nonces and produces our partial encrypted signatures.
+    ///
+    /// ## Return value
+    ///
+    /// Returns `None` if the signing sessions couldn't be started (see
+    /// [`MuSig::start_encrypted_sign_session`]).
+    pub fn receive_nonces<H: Digest<OutputSize = U32> + Clone, NG>(
+        self,
+        musig: &MuSig<H, Schnorr<H, NG>>,
+        their_nonces: NonceMessage,
+        release: Message<'_, Public>,
+        refund: Message<'_, Public>,
+    ) -> Option<(AwaitingPartialSignatures, PartialSignatureMessage)> {
+        let AwaitingNonces {
+            escrow,
+            nonces: [release_nonce, refund_nonce],
+            public_nonces: our_nonces,
+        } = self;
+        let (mut release_nonces, mut refund_nonces) =
+            (vec![our_nonces.release, their_nonces.release], vec![
+                our_nonces.refund,
+                their_nonces.refund,
+            ]);
+        let terms = escrow.terms;
+        if terms.role == Role::Seller {
+            release_nonces.reverse();
+            refund_nonces.reverse();
+        }
+        let sessions = [
+            musig.start_encrypted_sign_session(
+                &terms.agg_key,
+                release_nonces,
+                release,
+                &terms.mediator.release,
+            )?,
+            musig.start_encrypted_sign_session(
+                &terms.agg_key,
+                refund_nonces,
+                refund,
+                &terms.mediator.refund,
+            )?,
+        ];
+        let index = terms.role.index();
+        let message = PartialSignatureMessage {
+            release: musig.sign(
+                &terms.agg_key,
+                &sessions[0],
+                index,
+                &escrow.keypair,
+                release_nonce,
+            ),
+            refund: musig.sign(
+                &terms.agg_key,
+                &sessions[1],
+                index,
+                &escrow.keypair,
+                refund_nonce,
+            ),
+        };
+        Some((
+            AwaitingPartialSignatures {
+                terms,
+                sessions,
+                partial_signatures: message,
+            },
+            message,
+        ))
+    }
+}
+
+/// Waiting for the other party's [`PartialSignatureMessage`].
+#[derive(Debug, Clone)]
+pub struct AwaitingPartialSignatures {
+    terms: Terms,
+    sessions: [SignSession<musig::Adaptor>; 2],
+    partial_signatures: PartialSignatureMessage,
+}
+
+impl AwaitingPartialSignatures {
+    /// Checks the other party's partial encrypted signatures and combines them with ours.
+    ///
+    /// ## Return value
+    ///
+    /// Returns `None` if either of the other party's partial signatures is invalid.
+    pub fn receive_partial_signatures<H: Digest<OutputSize = U32> + Clone, NG>(
+        self,
+        musig: &MuSig<H, Schnorr<H, NG>>,
+        their_signatures: PartialSignatureMessage,
+    ) -> Option<Presigned> {
+        let AwaitingPartialSignatures {
+            terms,
+            sessions,
+            partial_signatures: ours,
+        } = self;
+        let their_index = 1 - terms.role.index();
+        if !musig.verify_partial_signature(
+            &terms.agg_key,
+            &sessions[0],
+            their_index,
+            their_signatures.release,
+        ) || !musig.verify_partial_signature(
+            &terms.agg_key,
+            &sessions[1],
+            their_index,
+            their_signatures.refund,
+        ) {
+            return None;
+        }
+        let release = musig.combine_partial_encrypted_signatures(&terms.agg_key, &sessions[0], [
+            ours.release,
+            their_signatures.release,
+        ]);
+        let refund = musig.combine_partial_encrypted_signatures(&terms.agg_key, &sessions[1], [
+            ours.refund,
+            their_signatures.refund,
+        ]);
+        Some(Presigned {
+            terms,
+            release,
+            refund,
+        })
+    }
+}
+
+/// Both encrypted signatures are complete. It's now safe to fund the escrow.
+#[derive(Debug, Clone)]
+pub struct Presigned {
+    terms: Terms,
+    release: EncryptedSignature,
+    refund: EncryptedSignature,
+}
+
+impl Presigned {
+    /// The key the coins are locked under.
+    pub fn agg_key(&self) -> &XOnlyAggKey {
+        &self.terms.agg_key
+    }
+
+    /// The encrypted signature for `outcome`.
+    pub fn encrypted_signature(&self, outcome: Outcome) -> &EncryptedSignature {
+        match outcome {
+            Outcome::Release => &self.release,
+            Outcome::Refund => &self.refund,
+        }
+    }
+
+    /// Decrypts the signature the mediator decided on.
+    ///
+    /// ## Return value
+    ///
+    /// Returns `None` if the decryption key in `decision` is not the mediator's key for its
+    /// outcome.
+    pub fn resolve<H: Digest<OutputSize = U32> + Clone, NG>(
+        &self,
+        musig: &MuSig<H, Schnorr<H, NG>>,
+        decision: &Decision,
+    ) -> Option<Signature> {
+        let encryption_key = self.terms.mediator.key_for(decision.outcome);
+        if g!(decision.decryption_key * G) != encryption_key {
+            return None;
+        }
+        Some(musig.schnorr.decrypt_signature(
+            decision.decryption_key.clone(),
+            self.encrypted_signature(decision.outcome).clone(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn presign() -> (MediatorSecrets, Presigned, Presigned) {
+        let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+        let mut rng = rand::thread_rng();
+        let mediator = MediatorSecrets::random(&mut rng);
+        let buyer_keypair = musig.new_keypair(Scalar::random(&mut rng));
+        let seller_keypair = musig.new_keypair(Scalar::random(&mut rng));
+        let buyer = Escrow::new(
+            &musig,
+            Role::Buyer,
+            buyer_keypair.clone(),
+            seller_keypair.public_key(),
+            mediator.public(),
+        );
+        let seller = Escrow::new(
+            &musig,
+            Role::Seller,
+            seller_keypair,
+            buyer_keypair.public_key(),
+            mediator.public(),
+        );
+        assert_eq!(
+            buyer.agg_key().agg_public_key(),
+            seller.agg_key().agg_public_key()
+        );
+        let (release, refund) = (
+            Message::<Public>::plain("test", b"release"),
+            Message::<Public>::plain("test", b"refund"),
+        );
+        let (buyer, buyer_nonces) = buyer.start(&musig, b"test", &mut rng);
+        let (seller, seller_nonces) = seller.start(&musig, b"test", &mut rng);
+        let (buyer, buyer_sigs) = buyer
+            .receive_nonces(&musig, seller_nonces, release, refund)
+            .unwrap();
+        let (seller, seller_sigs) = seller
+            .receive_nonces(&musig, buyer_nonces, release, refund)
+            .unwrap();
+        let bad_sigs = PartialSignatureMessage {
+            release: seller_sigs.refund,
+            refund: seller_sigs.release,
+        };
+        assert!(buyer
+            .clone()
+            .receive_partial_signatures(&musig, bad_sigs)
+            .is_none());
+        let buyer = buyer
+            .receive_partial_signatures(&musig, seller_sigs)
+            .unwrap();
+        let seller = seller
+            .receive_partial_signatures(&musig, buyer_sigs)
+            .unwrap();
+        (mediator, buyer, seller)
+    }
+
+    #[test]
+    fn restarting_gives_fresh_nonces() {
+        let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+        let mut rng = rand::thread_rng();
+        let mediator = MediatorSecrets::random(&mut rng).public();
+        let keypair = musig.new_keypair(Scalar::random(&mut rng));
+        let other_key = Point::random(&mut rng);
+        let start = |rng: &mut rand::rngs::ThreadRng| {
+            Escrow::new(&musig, Role::Buyer, keypair.clone(), other_key, mediator)
+                .start(&musig, b"test", rng)
+                .1
+        };
+        let (first, second) = (start(&mut rng), start(&mut rng));
+        assert_ne!(first.release, second.release);
+        assert_ne!(first.refund, second.refund);
+    }
+
+    #[test]
+    fn mediator_resolves_either_way() {
+        let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+        let (mediator, buyer, seller) = presign();
+        let key = buyer.agg_key().agg_public_key();
+        for (outcome, message) in [
+            (Outcome::Release, b"release".as_ref()),
+            (Outcome::Refund, b"refund".as_ref()),
+        ] {
+            let message = Message::<Public>::plain("test", message);
+            assert!(musig.schnorr.verify_encrypted_signature(
+                &key,
+                &mediator.public().key_for(outcome),
+                message,
+                seller.encrypted_signature(outcome),
+            ));
+            let decision = mediator.decide(outcome);
+            for party in [&buyer, &seller] {
+                let signature = party.resolve(&musig, &decision).unwrap();
+                assert!(musig.schnorr.verify(&key, message, &signature));
+            }
+        }
+    }
+
+    #[test]
+    fn forged_decision_is_rejected() {
+        let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+        let (mediator, buyer, _) = presign();
+        let mut decision = mediator.decide(Outcome::Refund);
+        decision.outcome = Outcome::Release;
+        assert!(buyer.resolve(&musig, &decision).is_none());
+    }
+}
@@ -102,6 +102,10 @@
 //! ));
 //! ```
 pub use crate::binonce::{Nonce, NonceKeyPair};
+pub mod audit;
+pub mod blame;
+pub mod repair;
+pub mod reshare;
 use crate::{Message, Schnorr, Signature, Vec};
 use core::iter;
 use rand_core::RngCore;
@@ -114,7 +118,7 @@ use secp256kfun::{
     nonce::{AddTag, NonceGen},
     rand_core, s, Point, Scalar, G,
 };
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 /// The FROST context.
 /// H: hash for challenges and creating a keygen_id
@@ -150,12 +154,20 @@ impl ScalarPoly {
         let x = Scalar::from(x)
             .expect_nonzero("must be non-zero")
             .mark::<Public>();
-        let mut xpow = s!(1).mark::<Public>();
+        self.eval_at(&x)
+    }
+
+    /// Evaluate the scalar polynomial at an arbitrary scalar `x`.
+    ///
+    /// Use this when shares are indexed by something other than small integers (e.g. the hash of
+    /// a committee member's public key).
+    pub fn eval_at(&self, x: &Scalar<Public, impl ZeroChoice>) -> Scalar<Secret, Zero> {
+        let mut xpow = s!(1).mark::<(Public, Zero)>();
         self.0
             .iter()
             .skip(1)
             .fold(self.0[0].clone().mark::<Zero>(), move |sum, coeff| {
-                xpow = s!(xpow * x).mark::<Public>();
+                xpow = s!(xpow * x).mark::<(Public, Zero)>();
                 s!(sum + xpow * coeff)
             })
     }
@@ -224,14 +236,49 @@ impl<Z> PointPoly<Z> {
         let x = Scalar::from(x)
             .expect_nonzero("must be non-zero")
             .mark::<Public>();
-        let xpows = iter::successors(Some(s!(1).mark::<Public>()), |xpow| {
-            Some(s!(x * xpow).mark::<Public>())
+        self.eval_at(&x)
+    }
+
+    /// Evaluate the point polynomial at an arbitrary scalar `x`.
+    ///
+    /// If this is the commitment to a [`ScalarPoly`] then the result is the evaluation of the
+    /// scalar polynomial at `x` multiplied by [`G`].
+    ///
+    /// [`G`]: crate::fun::G
+    pub fn eval_at(&self, x: &Scalar<Public, impl ZeroChoice>) -> Point<Jacobian, Public, Zero> {
+        let xpows = iter::successors(Some(s!(1).mark::<(Public, Zero)>()), |xpow| {
+            Some(s!(x * xpow).mark::<(Public, Zero)>())
         })
         .take(self.0.len())
         .collect::<Vec<_>>();
         crate::fun::op::lincomb(&xpows, &self.0)
     }
 
+    /// Checks that `share` is the evaluation at `x` of the polynomial this commits to.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schnorr_fun::{
+    ///     frost::ScalarPoly,
+    ///     fun::{marker::*, Scalar},
+    /// };
+    /// let poly = ScalarPoly::random(3, &mut rand::thread_rng());
+    /// let commitment = poly.to_point_poly();
+    /// let x = Scalar::random(&mut rand::thread_rng()).mark::<Public>();
+    /// let share = poly.eval_at(&x);
+    /// assert!(commitment.verify_share(&x, &share));
+    /// assert!(!commitment.verify_share(&x, &poly.eval(1)));
+    /// ```
+    #[must_use]
+    pub fn verify_share(
+        &self,
+        x: &Scalar<Public, impl ZeroChoice>,
+        share: &Scalar<impl Secrecy, impl ZeroChoice>,
+    ) -> bool {
+        g!(share * G) == self.eval_at(x)
+    }
+
     /// Combine a vector of point polynomials into a joint polynomial.
     fn combine(mut polys: impl Iterator<Item = Self>) -> PointPoly<Zero> {
         // take the first point polynomial and collect its coefficients
@@ -279,6 +326,11 @@ impl KeyGen {
     pub fn n_parties(&self) -> usize {
         self.point_polys.len()
     }
+
+    /// The point polynomials of each party in the order they were given to [`Frost::new_keygen`].
+    pub fn point_polys(&self) -> &[PointPoly] {
+        &self.point_polys
+    }
 }
 
 /// First round keygen errors
@@ -668,8 +720,10 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
 
         let mut total_secret_share = s!(0);
         for (i, (secret_share, poly)) in secret_shares.iter().zip(&KeyGen.point_polys).enumerate() {
-            let expected_public_share = poly.eval((my_index + 1) as u32);
-            if g!(secret_share * G) != expected_public_share {
+            let x = Scalar::from(my_index + 1)
+                .expect_nonzero("must be non-zero")
+                .mark::<Public>();
+            if !poly.verify_share(&x, secret_share) {
                 return Err(FinishKeyGenError::InvalidShare(i));
             }
             total_secret_share = s!(total_secret_share + secret_share);
@@ -700,17 +754,95 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
     }
 }
 
-/// Calculate the lagrange coefficient for participant with index x_j and other signers indexes x_ms
-fn lagrange_lambda(x_j: u32, x_ms: &[u32]) -> Scalar {
-    let x_j = Scalar::from(x_j).expect_nonzero("target xcoord can not be zero");
-    x_ms.iter()
+/// Calculates the Lagrange coefficient at zero for the participant at `my_index` when the set of
+/// participants is `participant_indices`.
+///
+/// Multiplying each participant's share by their coefficient and adding them together gives the
+/// shared secret (Shamir reconstruction) and the same coefficients are used to combine FROST
+/// signature shares. The indices are the x-coordinates that the shares were evaluated at and so
+/// must be distinct and non-zero. Note that in [`Frost`] the party at `i` has the share evaluated
+/// at `i + 1`. Any entry equal to `my_index` is ignored so it doesn't matter whether
+/// `participant_indices` includes it. Only one scalar inversion is done.
+///
+/// # Example
+///
+/// ```
+/// use schnorr_fun::{
+///     frost::{lagrange_coefficient, ScalarPoly},
+///     fun::{s, Scalar},
+/// };
+/// let poly = ScalarPoly::random(2, &mut rand::thread_rng());
+/// let (share1, share3) = (poly.eval(1), poly.eval(3));
+/// let secret =
+///     s!(
+///         { lagrange_coefficient(&[1, 3], 1) } * share1 + { lagrange_coefficient(&[1, 3], 3) }
+///             * share3
+///     );
+/// assert_eq!(&secret, poly.first_coef());
+/// ```
+///
+/// # Panics
+///
+/// If any of the indices are zero or they are not distinct.
+pub fn lagrange_coefficient(participant_indices: &[u32], my_index: u32) -> Scalar {
+    let x_j = Scalar::from(my_index).expect_nonzero("index can not be zero");
+    let (numerator, denominator) = participant_indices
+        .iter()
+        .filter(|x_m| **x_m != my_index)
         .map(|x_m| Scalar::from(*x_m).expect_nonzero("index can not be zero"))
-        .fold(Scalar::one(), |acc, x_m| {
-            let denominator = s!(x_m - x_j)
-                .expect_nonzero("removed duplicate indexes")
-                .invert();
-            s!(acc * x_m * denominator)
+        .fold(
+            (Scalar::one(), Scalar::one()),
+            |(numerator, denominator), x_m| {
+                let difference = s!(x_m - x_j).expect_nonzero("indices must be distinct");
+                (s!(numerator * x_m), s!(denominator * difference))
+            },
+        );
+    s!(numerator * { denominator.invert() })
+}
+
+/// Calculates the Lagrange coefficients at zero for every participant in `participant_indices`.
+///
+/// The result is the same as calling [`lagrange_coefficient`] for each index but all the
+/// inversions are shared using Montgomery's trick so only one is done in total.
+///
+/// # Panics
+///
+/// If any of the indices are zero or they are not distinct.
+pub fn lagrange_coefficients(participant_indices: &[u32]) -> Vec<Scalar> {
+    let xs = participant_indices
+        .iter()
+        .map(|x| Scalar::from(*x).expect_nonzero("index can not be zero"))
+        .collect::<Vec<_>>();
+    let numerator = xs.iter().fold(Scalar::one(), |acc, x| s!(acc * x));
+    // lambda_j = (x_0 * ... * x_n) / (x_j * prod_{m != j} (x_m - x_j))
+    let denominators = xs
+        .iter()
+        .enumerate()
+        .map(|(j, x_j)| {
+            xs.iter()
+                .enumerate()
+                .filter(|(m, _)| *m != j)
+                .fold(x_j.clone(), |acc, (_, x_m)| {
+                    s!(acc * (x_m - x_j)).expect_nonzero("indices must be distinct")
+                })
         })
+        .collect::<Vec<_>>();
+
+    // prefix_products[i] = denominators[0] * ... * denominators[i-1]
+    let mut prefix_products = Vec::with_capacity(denominators.len());
+    let mut product = Scalar::one();
+    for denominator in &denominators {
+        prefix_products.push(product.clone());
+        product = s!(product * denominator);
+    }
+    let mut product_inv = s!(numerator * { product.invert() });
+    let mut coefficients = Vec::with_capacity(denominators.len());
+    for (denominator, prefix_product) in denominators.iter().zip(prefix_products).rev() {
+        coefficients.push(s!(product_inv * prefix_product));
+        product_inv = s!(product_inv * denominator);
+    }
+    coefficients.reverse();
+    coefficients
 }
 
 /// A FROST signing session
@@ -725,6 +857,66 @@ pub struct SignSession {
     agg_nonce: Point<EvenY>,
     challenge: Scalar<Public, Zero>,
     nonces: BTreeMap<u32, Nonce>,
+    /// The share indices of each signer keyed by the index their nonce is under.
+    signer_shares: BTreeMap<u32, Vec<u32>>,
+}
+
+impl SignSession {
+    /// The share indices of every signer in the session in order.
+    pub fn share_indices(&self) -> impl Iterator<Item = u32> + '_ {
+        let mut indices = self
+            .signer_shares
+            .values()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        indices.into_iter()
+    }
+
+    /// The Lagrange coefficients for `my_indices` (negated if the key needs it).
+    ///
+    /// When there is more than one share all the coefficients are computed with a single inversion.
+    fn lagrange_coefficients_for(
+        &self,
+        frost_key: &XOnlyFrostKey,
+        my_indices: &[u32],
+    ) -> Vec<Scalar> {
+        let indices = self.share_indices().map(|j| j + 1).collect::<Vec<_>>();
+        let mut lambdas = match my_indices {
+            [my_index] => vec![lagrange_coefficient(&indices, my_index + 1)],
+            _ => {
+                let all = lagrange_coefficients(&indices);
+                my_indices
+                    .iter()
+                    .map(|my_index| {
+                        let position = indices
+                            .iter()
+                            .position(|j| *j == my_index + 1)
+                            .expect("index is not part of the signing coalition");
+                        all[position].clone()
+                    })
+                    .collect()
+            }
+        };
+        for lambda in &mut lambdas {
+            lambda.conditional_negate(frost_key.needs_negation);
+        }
+        lambdas
+    }
+
+    /// Looks up the nonce of the signer with `my_indices`.
+    fn signer_nonce(&self, my_indices: &[u32]) -> &Nonce {
+        let first = my_indices
+            .first()
+            .expect("a signer must have at least one share");
+        assert_eq!(
+            self.signer_shares.get(first).map(Vec::as_slice),
+            Some(my_indices),
+            "verifying indices that are not part of signing coalition"
+        );
+        &self.nonces[first]
+    }
 }
 
 impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
@@ -742,8 +934,111 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
         nonces: Vec<(u32, Nonce)>,
         message: Message,
     ) -> SignSession {
-        let mut nonce_map: BTreeMap<_, _> =
-            nonces.into_iter().map(|(i, nonce)| (i, nonce)).collect();
+        self.start_weighted_sign_session(
+            frost_key,
+            nonces
+                .into_iter()
+                .map(|(i, nonce)| (vec![i], nonce))
+                .collect(),
+            message,
+        )
+    }
+
+    /// Start a FROST signing session where signers can hold more than one share.
+    ///
+    /// A party that was given several indices in key generation (i.e. has a higher weight) still
+    /// only generates one nonce and one signature share (see [`sign_weighted`]) for all of them.
+    /// Each entry of `nonces` is the share indices of a signer and their public nonce. The shares
+    /// of all the signers together have to reach the threshold so a coalition can be any mix of
+    /// single and multi-share signers e.g. with a threshold of 4 a board member with 3 shares and
+    /// an officer with 1 or four officers.
+    ///
+    /// # Panics
+    ///
+    /// If a signer has no shares or two signers have a share in common.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schnorr_fun::{
+    ///     frost::Frost,
+    ///     fun::{marker::*, Scalar},
+    ///     nonce::Deterministic,
+    ///     Message, Schnorr,
+    /// };
+    /// use sha2::Sha256;
+    /// let frost = Frost::new(Schnorr::<Sha256, Deterministic<Sha256>>::new(
+    ///     Deterministic::<Sha256>::default(),
+    /// ));
+    /// // three shares with a threshold of two: the first party holds two of them
+    /// let polys = (0..3)
+    ///     .map(|_| frost.new_scalar_poly(Scalar::random(&mut rand::thread_rng()), 2, b"weighted"))
+    ///     .collect::<Vec<_>>();
+    /// let keygen = frost
+    ///     .new_keygen(polys.iter().map(|poly| poly.to_point_poly()).collect())
+    ///     .unwrap();
+    /// let (shares, pops): (Vec<_>, Vec<_>) = polys
+    ///     .into_iter()
+    ///     .map(|poly| frost.create_shares(&keygen, poly))
+    ///     .unzip();
+    /// let (secret_shares, frost_keys): (Vec<_>, Vec<_>) = (0..3)
+    ///     .map(|i| {
+    ///         let received = shares.iter().map(|shares| shares[i].clone()).collect();
+    ///         frost
+    ///             .finish_keygen_to_xonly(keygen.clone(), i as u32, received, pops.clone())
+    ///             .unwrap()
+    ///     })
+    ///     .unzip();
+    /// let frost_key = &frost_keys[0];
+    ///
+    /// // the first party signs alone with both of their shares
+    /// let my_indices = [0, 1];
+    /// let my_shares = [secret_shares[0].clone(), secret_shares[1].clone()];
+    /// let nonce = frost.gen_nonce(
+    ///     &my_shares[0],
+    ///     b"weighted-session",
+    ///     Some(frost_key.public_key()),
+    ///     None,
+    /// );
+    /// let message = Message::<Public>::plain("test", b"board resolution");
+    /// let session = frost.start_weighted_sign_session(
+    ///     frost_key,
+    ///     vec![(my_indices.to_vec(), nonce.public())],
+    ///     message,
+    /// );
+    /// let share = frost.sign_weighted(frost_key, &session, &my_indices, &my_shares, nonce);
+    /// assert!(frost.verify_weighted_signature_share(frost_key, &session, &my_indices, share));
+    /// let signature = frost.combine_signature_shares(frost_key, &session, vec![share]);
+    /// assert!(frost
+    ///     .schnorr
+    ///     .verify(&frost_key.public_key(), message, &signature));
+    /// ```
+    ///
+    /// [`sign_weighted`]: Self::sign_weighted
+    pub fn start_weighted_sign_session(
+        &self,
+        frost_key: &XOnlyFrostKey,
+        nonces: Vec<(Vec<u32>, Nonce)>,
+        message: Message,
+    ) -> SignSession {
+        let mut nonce_map = BTreeMap::new();
+        let mut signer_shares = BTreeMap::new();
+        let mut seen = BTreeSet::new();
+        for (mut indices, nonce) in nonces {
+            indices.sort_unstable();
+            let first = *indices
+                .first()
+                .expect("a signer must have at least one share");
+            for index in &indices {
+                assert!(
+                    seen.insert(*index),
+                    "share index {} is held by more than one signer",
+                    index
+                );
+            }
+            nonce_map.insert(first, nonce);
+            signer_shares.insert(first, indices);
+        }
 
         let agg_nonce_jac: [Point<Jacobian, Public, Zero>; 2] =
             nonce_map
@@ -796,6 +1091,7 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
             agg_nonce,
             challenge,
             nonces: nonce_map,
+            signer_shares,
         }
     }
 
@@ -812,24 +1108,51 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
         secret_share: &Scalar,
         secret_nonce: NonceKeyPair,
     ) -> Scalar<Public, Zero> {
-        let mut lambda = lagrange_lambda(
-            my_index as u32 + 1,
-            &session
-                .nonces
-                .iter()
-                .filter(|(j, _)| **j != (my_index as u32))
-                .map(|(j, _)| *j as u32 + 1)
-                .collect::<Vec<_>>(),
+        self.sign_weighted(
+            frost_key,
+            session,
+            &[my_index],
+            core::slice::from_ref(secret_share),
+            secret_nonce,
+        )
+    }
+
+    /// Generates one signature share for all the shares of a signer holding more than one.
+    ///
+    /// `my_indices` and `secret_shares` are the signer's share indices and the secret shares at
+    /// those indices. See [`start_weighted_sign_session`] for an example.
+    ///
+    /// # Panics
+    ///
+    /// If `my_indices` and `secret_shares` have different lengths or `my_indices` isn't a signer
+    /// in the session.
+    ///
+    /// [`start_weighted_sign_session`]: Self::start_weighted_sign_session
+    pub fn sign_weighted(
+        &self,
+        frost_key: &XOnlyFrostKey,
+        session: &SignSession,
+        my_indices: &[u32],
+        secret_shares: &[Scalar],
+        secret_nonce: NonceKeyPair,
+    ) -> Scalar<Public, Zero> {
+        assert_eq!(
+            my_indices.len(),
+            secret_shares.len(),
+            "need one secret share for each index"
         );
-        lambda.conditional_negate(frost_key.needs_negation);
+        let lambdas = session.lagrange_coefficients_for(frost_key, my_indices);
+        let x = lambdas
+            .iter()
+            .zip(secret_shares)
+            .fold(Scalar::zero(), |acc, (lambda, x_i)| s!(acc + lambda * x_i));
         let [mut r1, mut r2] = secret_nonce.secret;
         r1.conditional_negate(session.nonces_need_negation);
         r2.conditional_negate(session.nonces_need_negation);
 
         let b = &session.binding_coeff;
-        let x = secret_share;
         let c = &session.challenge;
-        s!(r1 + (r2 * b) + lambda * x * c).mark::<Public>()
+        s!(r1 + (r2 * b) + x * c).mark::<Public>()
     }
 
     /// Verify a partial signature at `index`.
@@ -845,27 +1168,35 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> Frost<H, NG> {
         session: &SignSession,
         index: u32,
         signature_share: Scalar<Public, Zero>,
+    ) -> bool {
+        self.verify_weighted_signature_share(frost_key, session, &[index], signature_share)
+    }
+
+    /// Verify a signature share from [`sign_weighted`] by the signer holding `indices`.
+    ///
+    /// # Panics
+    ///
+    /// If `indices` isn't a signer in the session.
+    ///
+    /// [`sign_weighted`]: Self::sign_weighted
+    pub fn verify_weighted_signature_share(
+        &self,
+        frost_key: &XOnlyFrostKey,
+        session: &SignSession,
+        indices: &[u32],
+        signature_share: Scalar<Public, Zero>,
     ) -> bool {
         let s = signature_share;
-        let mut lambda = lagrange_lambda(
-            index as u32 + 1,
-            &session
-                .nonces
-                .iter()
-                .filter(|(j, _)| **j != (index as u32))
-                .map(|(j, _)| *j as u32 + 1)
-                .collect::<Vec<_>>(),
-        );
-        lambda.conditional_negate(frost_key.needs_negation);
+        let [ref R1, ref R2] = session.signer_nonce(indices).0;
+        let lambdas = session.lagrange_coefficients_for(frost_key, indices);
+        let verification_shares = indices
+            .iter()
+            .map(|index| frost_key.verification_shares[*index as usize])
+            .collect::<Vec<_>>();
+        let X = crate::fun::op::lincomb(&lambdas, &verification_shares);
         let c = &session.challenge;
         let b = &session.binding_coeff;
-        let X = frost_key.verification_shares().nth(index as usize).unwrap();
-        let [ref R1, ref R2] = session
-            .nonces
-            .get(&(index as u32))
-            .expect("verifying index that is not part of signing coalition")
-            .0;
-        g!(R1 + b * R2 + (c * lambda) * X - s * G).is_zero()
+        g!(R1 + b * R2 + c * X - s * G).is_zero()
     }
 
     /// Combine a vector of partial signatures into an aggregate signature.
@@ -1246,6 +1577,151 @@ mod test {
     #[test]
     fn test_lagrange_lambda() {
         let res = s!((1 * 4 * 5) * { s!((1 - 2) * (4 - 2) * (5 - 2)).expect_nonzero("").invert() });
-        assert_eq!(res, lagrange_lambda(2, &[1, 4, 5]));
+        assert_eq!(res, lagrange_coefficient(&[1, 4, 5], 2));
+        assert_eq!(res, lagrange_coefficient(&[1, 2, 4, 5], 2));
+    }
+
+    #[test]
+    fn lagrange_coefficients_match_individual() {
+        let indices = [1, 3, 4, 7, 9];
+        let coefficients = lagrange_coefficients(&indices);
+        for (index, coefficient) in indices.iter().zip(&coefficients) {
+            assert_eq!(*coefficient, lagrange_coefficient(&indices, *index));
+        }
+        assert!(lagrange_coefficients(&[]).is_empty());
+
+        let poly = ScalarPoly::random(5, &mut rand::thread_rng());
+        let secret = indices
+            .iter()
+            .zip(coefficients)
+            .fold(Scalar::zero(), |acc, (index, coefficient)| {
+                s!(acc + coefficient * { poly.eval(*index) })
+            });
+        assert_eq!(&secret, poly.first_coef());
+    }
+
+    #[test]
+    fn point_poly_verifies_shares() {
+        let poly = ScalarPoly::random(3, &mut rand::thread_rng());
+        let commitment = poly.to_point_poly();
+        assert_eq!(
+            commitment.eval_at(&Scalar::zero().mark::<Public>()),
+            g!({ poly.first_coef() } * G)
+        );
+        for i in 1..5u32 {
+            let x = Scalar::from(i).mark::<Public>();
+            assert_eq!(poly.eval_at(&x), poly.eval(i));
+            assert_eq!(commitment.eval_at(&x), commitment.eval(i));
+            assert!(commitment.verify_share(&x, &poly.eval(i)));
+            assert!(!commitment.verify_share(&x, &poly.eval(i + 1)));
+        }
+        let x = Scalar::random(&mut rand::thread_rng()).mark::<Public>();
+        assert!(commitment.verify_share(&x, &poly.eval_at(&x)));
+    }
+
+    #[test]
+    fn weighted_signing_with_mixed_coalitions() {
+        let frost = Frost::new(Schnorr::<Sha256, Deterministic<Sha256>>::new(
+            Deterministic::<Sha256>::default(),
+        ));
+        let polys = (0..5)
+            .map(|_| ScalarPoly::random(3, &mut rand::thread_rng()))
+            .collect::<Vec<_>>();
+        let keygen = frost
+            .new_keygen(polys.iter().map(ScalarPoly::to_point_poly).collect())
+            .unwrap();
+        let (shares, pops): (Vec<_>, Vec<_>) = polys
+            .into_iter()
+            .map(|poly| frost.create_shares(&keygen, poly))
+            .unzip();
+        let (secret_shares, frost_keys): (Vec<_>, Vec<_>) = (0..5)
+            .map(|i| {
+                let received = shares.iter().map(|shares| shares[i].clone()).collect();
+                frost
+                    .finish_keygen_to_xonly(keygen.clone(), i as u32, received, pops.clone())
+                    .unwrap()
+            })
+            .unzip();
+        let frost_key = frost_keys[0].clone().tweak(Scalar::from(42u32)).unwrap();
+
+        // a holds two shares, b one and c two
+        let signers: [&[u32]; 3] = [&[0, 1], &[2], &[3, 4]];
+        let message = Message::<Public>::plain("test", b"weighted");
+        for coalition in [[0, 1], [1, 2], [0, 2]] {
+            let nonces = coalition
+                .iter()
+                .map(|signer| {
+                    let indices = signers[*signer];
+                    frost.gen_nonce(
+                        &secret_shares[indices[0] as usize],
+                        &[*signer as u8],
+                        Some(frost_key.public_key()),
+                        Some(message),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let session = frost.start_weighted_sign_session(
+                &frost_key,
+                coalition
+                    .iter()
+                    .zip(&nonces)
+                    .map(|(signer, nonce)| (signers[*signer].to_vec(), nonce.public()))
+                    .collect(),
+                message,
+            );
+            assert_eq!(
+                session.share_indices().collect::<Vec<_>>(),
+                coalition
+                    .iter()
+                    .flat_map(|signer| signers[*signer].iter().copied())
+                    .collect::<Vec<_>>()
+            );
+            let signature_shares = coalition
+                .iter()
+                .zip(nonces)
+                .map(|(signer, nonce)| {
+                    let indices = signers[*signer];
+                    let my_shares = indices
+                        .iter()
+                        .map(|i| secret_shares[*i as usize].clone())
+                        .collect::<Vec<_>>();
+                    let share =
+                        frost.sign_weighted(&frost_key, &session, indices, &my_shares, nonce);
+                    assert!(
+                        frost.verify_weighted_signature_share(&frost_key, &session, indices, share)
+                    );
+                    let wrong = s!(share + 1).mark::<Public>();
+                    assert!(!frost
+                        .verify_weighted_signature_share(&frost_key, &session, indices, wrong));
+                    share
+                })
+                .collect::<Vec<_>>();
+            let signature = frost.combine_signature_shares(&frost_key, &session, signature_shares);
+            assert!(frost
+                .schnorr
+                .verify(&frost_key.public_key(), message, &signature));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn weighted_session_rejects_shared_index() {
+        let frost = Frost::new(Schnorr::<Sha256, Deterministic<Sha256>>::new(
+            Deterministic::<Sha256>::default(),
+        ));
+        let poly = ScalarPoly::random(2, &mut rand::thread_rng());
+        let keygen = frost.new_keygen(vec![poly.to_point_poly()]).unwrap();
+        let (shares, pop) = frost.create_shares(&keygen, poly);
+        let (secret_share, frost_key) = frost
+            .finish_keygen_to_xonly(keygen, 0, shares, vec![pop])
+            .unwrap();
+        let nonce = frost
+            .gen_nonce(&secret_share, b"test", Some(frost_key.public_key()), None)
+            .public();
+        let _ = frost.start_weighted_sign_session(
+            &frost_key,
+            vec![(vec![0, 1], nonce), (vec![1], nonce)],
+            Message::<Public>::plain("test", b"test"),
+        );
     }
 }
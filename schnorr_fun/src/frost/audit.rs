@@ -0,0 +1,298 @@
+//! Publicly verifiable key generation transcripts.
+//!
+//! A [`KeyGenTranscript`] lets someone who didn't take part in a FROST key generation (an auditor,
+//! a federation member that joins later or a counterparty) check that the group key was generated
+//! correctly by the declared participants. Each participant has a long-term identity key and,
+//! once [`Frost::finish_keygen`] has succeeded for them, signs the key generation with
+//! [`certify_keygen`]. The transcript is the participants' identity keys, the point polynomials
+//! and proofs of possession they sent during the key generation and their certifications.
+//!
+//! [`KeyGenTranscript::verify`] recomputes the key generation from the point polynomials, checks
+//! every proof of possession and every certification and returns the [`FrostKey`] (the group
+//! public key and everyone's verification shares). The transcript is compact: besides the point
+//! polynomials it's one key and two signatures per participant.
+//!
+//! The transcript can't show that the secret shares were delivered correctly since they are
+//! private. That's what the certifications are for: a participant only certifies after checking
+//! the shares it received so a transcript with every certification means every participant got
+//! valid shares.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{audit, Frost, ScalarPoly},
+//!     fun::{Scalar, XOnlyKeyPair},
+//!     nonce::Deterministic,
+//!     Schnorr,
+//! };
+//! use sha2::Sha256;
+//! let frost = Frost::new(Schnorr::<Sha256, Deterministic<Sha256>>::new(
+//!     Deterministic::<Sha256>::default(),
+//! ));
+//! let identities = (0..3)
+//!     .map(|_| XOnlyKeyPair::new(Scalar::random(&mut rand::thread_rng())))
+//!     .collect::<Vec<_>>();
+//! let participants = identities
+//!     .iter()
+//!     .map(|identity| identity.public_key())
+//!     .collect::<Vec<_>>();
+//! # let polys = (0..3)
+//! #     .map(|_| ScalarPoly::random(2, &mut rand::thread_rng()))
+//! #     .collect::<Vec<_>>();
+//! # let keygen = frost
+//! #     .new_keygen(polys.iter().map(ScalarPoly::to_point_poly).collect())
+//! #     .unwrap();
+//! # let (shares, pops): (Vec<_>, Vec<_>) = polys
+//! #     .into_iter()
+//! #     .map(|poly| frost.create_shares(&keygen, poly))
+//! #     .unzip();
+//! // after finishing the key generation each participant certifies it
+//! let certifications = identities
+//!     .iter()
+//!     .map(|identity| audit::certify_keygen(&frost, &keygen, &participants, identity))
+//!     .collect::<Vec<_>>();
+//! let transcript = audit::KeyGenTranscript::new(&keygen, participants, pops, certifications);
+//! // anyone can check the transcript and learn the key
+//! let frost_key = transcript.verify(&frost).unwrap();
+//! # let (_, expected) = frost
+//! #     .finish_keygen(
+//! #         keygen,
+//! #         0,
+//! #         shares.iter().map(|s| s[0].clone()).collect(),
+//! #         transcript.proofs_of_possession.clone(),
+//! #     )
+//! #     .unwrap();
+//! # assert_eq!(frost_key, expected);
+//! ```
+use super::{Frost, FrostKey, KeyGen, PointPoly};
+use crate::{
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        marker::*,
+        nonce::NonceGen,
+        Point, XOnlyKeyPair,
+    },
+    Message, Signature, Vec,
+};
+
+/// The app tag of the message participants sign in [`certify_keygen`].
+const CERTIFICATION_TAG: &str = "frost/keygen-certification";
+
+/// Everything an outside verifier needs to check a key generation.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct KeyGenTranscript {
+    /// The identity key of each participant (in index order).
+    pub participants: Vec<Point<EvenY>>,
+    /// The point polynomial of each participant.
+    pub point_polys: Vec<PointPoly>,
+    /// The proof of possession of each participant.
+    pub proofs_of_possession: Vec<Signature>,
+    /// Each participant's signature with their identity key from [`certify_keygen`].
+    pub certifications: Vec<Signature>,
+}
+
+/// Why a [`KeyGenTranscript`] didn't verify.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptError {
+    /// The number of identity keys, point polynomials, proofs of possession and certifications
+    /// differ.
+    LengthMismatch,
+    /// The point polynomials don't make a valid key generation (see [`Frost::new_keygen`]).
+    InvalidKeyGen,
+    /// The proof of possession of the participant at this index is invalid.
+    InvalidProofOfPossession(usize),
+    /// The certification of the participant at this index is invalid.
+    InvalidCertification(usize),
+}
+
+impl core::fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            TranscriptError::LengthMismatch => {
+                write!(
+                    f,
+                    "the transcript doesn't have one of everything per participant"
+                )
+            }
+            TranscriptError::InvalidKeyGen => {
+                write!(f, "the point polynomials don't make a valid key generation")
+            }
+            TranscriptError::InvalidProofOfPossession(i) => write!(
+                f,
+                "the proof of possession of the participant at index {} is invalid",
+                i
+            ),
+            TranscriptError::InvalidCertification(i) => write!(
+                f,
+                "the certification of the participant at index {} is invalid",
+                i
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TranscriptError {}
+
+/// Signs `keygen` with a participant's identity key.
+///
+/// Only call this once [`Frost::finish_keygen`] has succeeded for you. `participants` are the
+/// identity keys of all the participants in index order and the signature commits to them along
+/// with all the point polynomials.
+pub fn certify_keygen<H: Digest<OutputSize = U32> + Clone, NG: NonceGen>(
+    frost: &Frost<H, NG>,
+    keygen: &KeyGen,
+    participants: &[Point<EvenY>],
+    identity: &XOnlyKeyPair,
+) -> Signature {
+    let bytes = certification_bytes(keygen, participants);
+    frost.schnorr.sign(
+        identity,
+        Message::<Public>::plain(CERTIFICATION_TAG, &bytes),
+    )
+}
+
+impl KeyGenTranscript {
+    /// Collects the transcript of `keygen`.
+    pub fn new(
+        keygen: &KeyGen,
+        participants: Vec<Point<EvenY>>,
+        proofs_of_possession: Vec<Signature>,
+        certifications: Vec<Signature>,
+    ) -> Self {
+        KeyGenTranscript {
+            participants,
+            point_polys: keygen.point_polys.clone(),
+            proofs_of_possession,
+            certifications,
+        }
+    }
+
+    /// Checks the transcript and returns the key it generated.
+    pub fn verify<H: Digest<OutputSize = U32> + Clone, NG>(
+        &self,
+        frost: &Frost<H, NG>,
+    ) -> Result<FrostKey, TranscriptError> {
+        let n = self.point_polys.len();
+        if n == 0
+            || self.participants.len() != n
+            || self.proofs_of_possession.len() != n
+            || self.certifications.len() != n
+        {
+            return Err(TranscriptError::LengthMismatch);
+        }
+        let keygen = frost
+            .new_keygen(self.point_polys.clone())
+            .map_err(|_| TranscriptError::InvalidKeyGen)?;
+        for (i, (poly, pop)) in self
+            .point_polys
+            .iter()
+            .zip(&self.proofs_of_possession)
+            .enumerate()
+        {
+            if !frost.verify_pop(&keygen, poly, pop.clone()) {
+                return Err(TranscriptError::InvalidProofOfPossession(i));
+            }
+        }
+        let bytes = certification_bytes(&keygen, &self.participants);
+        let message = Message::<Public>::plain(CERTIFICATION_TAG, &bytes);
+        for (i, (participant, certification)) in self
+            .participants
+            .iter()
+            .zip(&self.certifications)
+            .enumerate()
+        {
+            if !frost.schnorr.verify(participant, message, certification) {
+                return Err(TranscriptError::InvalidCertification(i));
+            }
+        }
+        Ok(keygen.frost_key)
+    }
+}
+
+/// The key generation id (which commits to the point polynomials) followed by the identity keys.
+fn certification_bytes(keygen: &KeyGen, participants: &[Point<EvenY>]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(32 * (participants.len() + 1));
+    bytes.extend_from_slice(&keygen.keygen_id);
+    for participant in participants {
+        bytes.extend_from_slice(&participant.to_xonly_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{frost::ScalarPoly, fun::Scalar, nonce::Deterministic, Schnorr};
+    use sha2::Sha256;
+
+    #[test]
+    fn verify_transcript() {
+        let frost = Frost::new(Schnorr::<Sha256, _>::new(Deterministic::<Sha256>::default()));
+        let identities = (0..3)
+            .map(|_| XOnlyKeyPair::new(Scalar::random(&mut rand::thread_rng())))
+            .collect::<Vec<_>>();
+        let participants = identities
+            .iter()
+            .map(|identity| identity.public_key())
+            .collect::<Vec<_>>();
+        let polys = (0..3)
+            .map(|_| ScalarPoly::random(2, &mut rand::thread_rng()))
+            .collect::<Vec<_>>();
+        let keygen = frost
+            .new_keygen(polys.iter().map(ScalarPoly::to_point_poly).collect())
+            .unwrap();
+        let (shares, pops): (Vec<_>, Vec<_>) = polys
+            .into_iter()
+            .map(|poly| frost.create_shares(&keygen, poly))
+            .unzip();
+        let (_, frost_key) = frost
+            .finish_keygen(
+                keygen.clone(),
+                1,
+                shares.iter().map(|shares| shares[1].clone()).collect(),
+                pops.clone(),
+            )
+            .unwrap();
+        let certifications = identities
+            .iter()
+            .map(|identity| certify_keygen(&frost, &keygen, &participants, identity))
+            .collect::<Vec<_>>();
+        let transcript = KeyGenTranscript::new(&keygen, participants, pops, certifications);
+        assert_eq!(transcript.verify(&frost), Ok(frost_key));
+
+        let mut bad = transcript.clone();
+        bad.participants.swap(0, 2);
+        assert_eq!(
+            bad.verify(&frost),
+            Err(TranscriptError::InvalidCertification(0))
+        );
+        let mut bad = transcript.clone();
+        bad.participants[1] = identities[0].public_key();
+        assert_eq!(
+            bad.verify(&frost),
+            Err(TranscriptError::InvalidCertification(0))
+        );
+        let mut bad = transcript.clone();
+        bad.proofs_of_possession.swap(1, 2);
+        assert_eq!(
+            bad.verify(&frost),
+            Err(TranscriptError::InvalidProofOfPossession(1))
+        );
+        let mut bad = transcript.clone();
+        bad.point_polys.swap(0, 1);
+        assert_eq!(
+            bad.verify(&frost),
+            Err(TranscriptError::InvalidProofOfPossession(0))
+        );
+        let mut bad = transcript;
+        bad.certifications.pop();
+        assert_eq!(bad.verify(&frost), Err(TranscriptError::LengthMismatch));
+    }
+}
@@ -0,0 +1,398 @@
+//! Evidence that a FROST participant misbehaved.
+//!
+//! When [`Frost::finish_keygen`] fails or a signature share doesn't pass
+//! [`Frost::verify_signature_share`] the error only tells you who to blame. The types here package
+//! up everything a third party needs to check the accusation for themselves: a [`KeyGenBlame`] for
+//! a bad share or proof of possession during key generation and a [`SignatureShareBlame`] for a
+//! bad signature share. They're serializable so they can be handed to whoever has to act on them
+//! (a coordinator, an auditor or the other participants) and their `verify` methods return `true`
+//! only if the accused's message really is invalid in the context recorded in the certificate.
+//!
+//! A certificate can't show that the accused actually *sent* the message it contains. If that
+//! matters the application should have each participant sign what it sends (e.g. with a long-term
+//! identity key) and keep those signatures with the certificate. Note that revealing the share in a
+//! [`KeyGenFault::InvalidShare`] certificate is harmless since the key generation it was for has to
+//! be abandoned anyway.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{blame::SignatureShareBlame, Frost, ScalarPoly},
+//!     fun::{marker::*, Scalar},
+//!     nonce::Deterministic,
+//!     Message, Schnorr,
+//! };
+//! use sha2::Sha256;
+//! let frost = Frost::new(Schnorr::<Sha256, Deterministic<Sha256>>::new(
+//!     Deterministic::<Sha256>::default(),
+//! ));
+//! # let polys = (0..2)
+//! #     .map(|_| ScalarPoly::random(2, &mut rand::thread_rng()))
+//! #     .collect::<Vec<_>>();
+//! # let keygen = frost
+//! #     .new_keygen(polys.iter().map(ScalarPoly::to_point_poly).collect())
+//! #     .unwrap();
+//! # let (shares, pops): (Vec<_>, Vec<_>) = polys
+//! #     .into_iter()
+//! #     .map(|poly| frost.create_shares(&keygen, poly))
+//! #     .unzip();
+//! # let (secret_shares, frost_keys): (Vec<_>, Vec<_>) = (0..2)
+//! #     .map(|i| {
+//! #         let received = shares.iter().map(|shares| shares[i].clone()).collect();
+//! #         frost.finish_keygen_to_xonly(keygen.clone(), i as u32, received, pops.clone()).unwrap()
+//! #     })
+//! #     .unzip();
+//! let frost_key = &frost_keys[0];
+//! let message = Message::<Public>::plain("test", b"blame");
+//! let nonces = (0..2)
+//!     .map(|i| {
+//!         frost.gen_nonce(
+//!             &secret_shares[i],
+//!             &[i as u8],
+//!             Some(frost_key.public_key()),
+//!             Some(message),
+//!         )
+//!     })
+//!     .collect::<Vec<_>>();
+//! let public_nonces = vec![(0, nonces[0].public()), (1, nonces[1].public())];
+//! let session = frost.start_sign_session(frost_key, public_nonces.clone(), message);
+//! // party 1 sends garbage
+//! let bad_share = Scalar::random(&mut rand::thread_rng()).mark::<(Public, Zero)>();
+//! assert!(!frost.verify_signature_share(frost_key, &session, 1, bad_share));
+//! let blame = SignatureShareBlame::new(frost_key, public_nonces, message, 1, bad_share);
+//! // anyone can check it
+//! assert!(blame.verify(&frost));
+//! ```
+use super::{FinishKeyGenError, Frost, KeyGen, PointPoly, XOnlyFrostKey};
+use crate::{
+    binonce::Nonce,
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        marker::*,
+        Scalar,
+    },
+    Message, Signature, Vec,
+};
+
+/// Evidence that a participant in a key generation sent an invalid share or proof of
+/// possession.
+///
+/// Create it with [`KeyGenBlame::new`] from a [`FinishKeyGenError`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct KeyGenBlame {
+    /// The point polynomials of every participant (in order) that the key generation was started
+    /// with.
+    pub point_polys: Vec<PointPoly>,
+    /// The index of the accused participant.
+    pub accused: u32,
+    /// What they did wrong.
+    pub fault: KeyGenFault,
+}
+
+/// The invalid message in a [`KeyGenBlame`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub enum KeyGenFault {
+    /// The share the accused sent to `recipient` doesn't match their point polynomial.
+    InvalidShare {
+        /// The index of the participant that received the share.
+        recipient: u32,
+        /// The share.
+        share: Scalar<Secret, Zero>,
+    },
+    /// The accused's proof of possession doesn't match their point polynomial.
+    InvalidProofOfPossession(Signature),
+}
+
+impl KeyGenBlame {
+    /// Creates the certificate for `error` which [`Frost::finish_keygen`] returned for the
+    /// participant at `my_index` when called with `secret_shares` and `proofs_of_possession`.
+    pub fn new(
+        keygen: &KeyGen,
+        my_index: u32,
+        error: &FinishKeyGenError,
+        secret_shares: &[Scalar<Secret, Zero>],
+        proofs_of_possession: &[Signature],
+    ) -> Self {
+        let (accused, fault) = match *error {
+            FinishKeyGenError::InvalidShare(i) => (i, KeyGenFault::InvalidShare {
+                recipient: my_index,
+                share: secret_shares[i].clone(),
+            }),
+            FinishKeyGenError::InvalidProofOfPossession(i) => (
+                i,
+                KeyGenFault::InvalidProofOfPossession(proofs_of_possession[i].clone()),
+            ),
+        };
+        KeyGenBlame {
+            point_polys: keygen.point_polys.clone(),
+            accused: accused as u32,
+            fault,
+        }
+    }
+
+    /// Checks that the accused's message is invalid.
+    ///
+    /// Returns `false` if the message is valid or the certificate is malformed.
+    #[must_use]
+    pub fn verify<H: Digest<OutputSize = U32> + Clone, NG>(&self, frost: &Frost<H, NG>) -> bool {
+        let keygen = match frost.new_keygen(self.point_polys.clone()) {
+            Ok(keygen) => keygen,
+            Err(_) => return false,
+        };
+        let poly = match self.point_polys.get(self.accused as usize) {
+            Some(poly) => poly,
+            None => return false,
+        };
+        match &self.fault {
+            KeyGenFault::InvalidShare { recipient, share } => {
+                if *recipient as usize >= self.point_polys.len() {
+                    return false;
+                }
+                let x = Scalar::from(recipient + 1)
+                    .expect_nonzero("must be non-zero")
+                    .mark::<Public>();
+                !poly.verify_share(&x, share)
+            }
+            KeyGenFault::InvalidProofOfPossession(pop) => {
+                !frost.verify_pop(&keygen, poly, pop.clone())
+            }
+        }
+    }
+}
+
+/// Evidence that a signer sent an invalid signature share.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct SignatureShareBlame {
+    /// The key being signed under.
+    pub frost_key: XOnlyFrostKey,
+    /// The share indices and nonce of every signer in the session.
+    pub nonces: Vec<(Vec<u32>, Nonce)>,
+    /// The message as it is hashed: the app tag padded to 64 bytes (if there is one) followed by
+    /// the message bytes.
+    pub message: Vec<u8>,
+    /// The share indices of the accused signer.
+    pub accused: Vec<u32>,
+    /// The accused's signature share.
+    pub signature_share: Scalar<Public, Zero>,
+}
+
+impl SignatureShareBlame {
+    /// Creates the certificate against the signer at `accused` in a session started with
+    /// [`Frost::start_sign_session`].
+    pub fn new(
+        frost_key: &XOnlyFrostKey,
+        nonces: Vec<(u32, Nonce)>,
+        message: Message,
+        accused: u32,
+        signature_share: Scalar<Public, Zero>,
+    ) -> Self {
+        Self::new_weighted(
+            frost_key,
+            nonces
+                .into_iter()
+                .map(|(index, nonce)| (vec![index], nonce))
+                .collect(),
+            message,
+            vec![accused],
+            signature_share,
+        )
+    }
+
+    /// Creates the certificate against the signer holding `accused` in a session started with
+    /// [`Frost::start_weighted_sign_session`].
+    pub fn new_weighted(
+        frost_key: &XOnlyFrostKey,
+        nonces: Vec<(Vec<u32>, Nonce)>,
+        message: Message,
+        accused: Vec<u32>,
+        signature_share: Scalar<Public, Zero>,
+    ) -> Self {
+        SignatureShareBlame {
+            frost_key: frost_key.clone(),
+            nonces,
+            message: message.to_hashed_bytes(),
+            accused,
+            signature_share,
+        }
+    }
+
+    /// Checks that the accused's signature share is invalid.
+    ///
+    /// Returns `false` if the share is valid or the certificate is malformed.
+    #[must_use]
+    pub fn verify<H: Digest<OutputSize = U32> + Clone, NG>(&self, frost: &Frost<H, NG>) -> bool {
+        let n_signers = self.frost_key.verification_shares.len() as u32;
+        let mut accused = self.accused.clone();
+        accused.sort_unstable();
+        let mut seen = Vec::new();
+        for (indices, _) in &self.nonces {
+            for index in indices {
+                if *index >= n_signers || seen.contains(index) {
+                    return false;
+                }
+                seen.push(*index);
+            }
+        }
+        let is_signer = self.nonces.iter().any(|(indices, _)| {
+            let mut indices = indices.clone();
+            indices.sort_unstable();
+            indices == accused
+        });
+        if accused.is_empty() || !is_signer {
+            return false;
+        }
+        let session = frost.start_weighted_sign_session(
+            &self.frost_key,
+            self.nonces.clone(),
+            Message::raw(&self.message),
+        );
+        !frost.verify_weighted_signature_share(
+            &self.frost_key,
+            &session,
+            &accused,
+            self.signature_share,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{frost::ScalarPoly, fun::s, nonce::Deterministic, Schnorr};
+    use sha2::Sha256;
+
+    type TestFrost = Frost<Sha256, Deterministic<Sha256>>;
+
+    fn frost() -> TestFrost {
+        Frost::new(Schnorr::new(Deterministic::<Sha256>::default()))
+    }
+
+    #[test]
+    fn keygen_blame() {
+        let frost = frost();
+        let polys = (0..3)
+            .map(|_| ScalarPoly::random(2, &mut rand::thread_rng()))
+            .collect::<Vec<_>>();
+        let keygen = frost
+            .new_keygen(polys.iter().map(ScalarPoly::to_point_poly).collect())
+            .unwrap();
+        let (shares, mut pops): (Vec<_>, Vec<_>) = polys
+            .into_iter()
+            .map(|poly| frost.create_shares(&keygen, poly))
+            .unzip();
+        let mut received = shares
+            .iter()
+            .map(|shares| shares[0].clone())
+            .collect::<Vec<_>>();
+
+        // honest shares can't be blamed
+        let honest = KeyGenBlame::new(
+            &keygen,
+            0,
+            &FinishKeyGenError::InvalidShare(1),
+            &received,
+            &pops,
+        );
+        assert!(!honest.verify(&frost));
+
+        received[1] = s!({ &received[1] } + 1);
+        let error = frost
+            .finish_keygen(keygen.clone(), 0, received.clone(), pops.clone())
+            .unwrap_err();
+        let blame = KeyGenBlame::new(&keygen, 0, &error, &received, &pops);
+        assert_eq!(blame.accused, 1);
+        assert!(blame.verify(&frost));
+
+        pops[2] = pops[0].clone();
+        let error = frost
+            .finish_keygen(keygen.clone(), 0, received.clone(), pops.clone())
+            .unwrap_err();
+        let blame = KeyGenBlame::new(&keygen, 0, &error, &received, &pops);
+        assert_eq!(blame.accused, 2);
+        assert!(blame.verify(&frost));
+        let mut wrong = blame.clone();
+        wrong.accused = 0;
+        assert!(!wrong.verify(&frost));
+        let mut wrong = blame;
+        wrong.accused = 3;
+        assert!(!wrong.verify(&frost));
+    }
+
+    #[test]
+    fn signature_share_blame() {
+        let frost = frost();
+        let polys = (0..3)
+            .map(|_| ScalarPoly::random(2, &mut rand::thread_rng()))
+            .collect::<Vec<_>>();
+        let keygen = frost
+            .new_keygen(polys.iter().map(ScalarPoly::to_point_poly).collect())
+            .unwrap();
+        let (shares, pops): (Vec<_>, Vec<_>) = polys
+            .into_iter()
+            .map(|poly| frost.create_shares(&keygen, poly))
+            .unzip();
+        let (secret_shares, frost_keys): (Vec<_>, Vec<_>) = (0..3)
+            .map(|i| {
+                let received = shares.iter().map(|shares| shares[i].clone()).collect();
+                frost
+                    .finish_keygen_to_xonly(keygen.clone(), i as u32, received, pops.clone())
+                    .unwrap()
+            })
+            .unzip();
+        let frost_key = &frost_keys[0];
+        let message = Message::<Public>::plain("test", b"blame");
+        let signers = [0u32, 2];
+        let nonces = signers
+            .iter()
+            .map(|i| {
+                frost.gen_nonce(
+                    &secret_shares[*i as usize],
+                    &[*i as u8],
+                    Some(frost_key.public_key()),
+                    Some(message),
+                )
+            })
+            .collect::<Vec<_>>();
+        let public_nonces = signers
+            .iter()
+            .zip(&nonces)
+            .map(|(i, nonce)| (*i, nonce.public()))
+            .collect::<Vec<_>>();
+        let session = frost.start_sign_session(frost_key, public_nonces.clone(), message);
+        let good_share = frost.sign(frost_key, &session, 2, &secret_shares[2], nonces[1].clone());
+
+        let blame =
+            SignatureShareBlame::new(frost_key, public_nonces.clone(), message, 2, good_share);
+        assert!(!blame.verify(&frost));
+
+        let bad_share = s!(good_share + 1).mark::<Public>();
+        assert!(!frost.verify_signature_share(frost_key, &session, 2, bad_share));
+        let blame =
+            SignatureShareBlame::new(frost_key, public_nonces.clone(), message, 2, bad_share);
+        assert!(blame.verify(&frost));
+
+        // the context has to be right
+        let mut wrong = blame.clone();
+        wrong.accused = vec![1];
+        assert!(!wrong.verify(&frost));
+        let mut wrong = blame.clone();
+        wrong.nonces[0].0 = vec![2];
+        assert!(!wrong.verify(&frost));
+    }
+}
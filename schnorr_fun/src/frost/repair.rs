@@ -0,0 +1,423 @@
+//! Repairing a lost FROST secret share.
+//!
+//! If a party loses its secret share a threshold of the others (the *helpers*) can give it back
+//! without any of them learning it or the group's secret. This is the repairable threshold scheme
+//! of [Laing and Stinson]. Each helper `i` weights its share by the Lagrange coefficient that
+//! interpolates the lost party's index from the helpers' indices and splits the result into random
+//! [`RepairDelta`]s, one for each helper (including itself). Each helper sums the deltas it is sent
+//! into a [`RepairShare`] for the lost party who sums those into its share. A helper only ever
+//! sees random-looking deltas from the others and the lost party only sees their sums.
+//!
+//! The lost party checks the repaired share against its verification share in the [`FrostKey`]
+//! (which it can get from any of the other parties since it's public). A wrong result means a
+//! helper misbehaved but not which one.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{repair, Frost, ScalarPoly},
+//!     nonce::Deterministic,
+//!     Schnorr,
+//! };
+//! use sha2::Sha256;
+//! # let frost = Frost::new(Schnorr::<Sha256, Deterministic<Sha256>>::new(
+//! #     Deterministic::<Sha256>::default(),
+//! # ));
+//! # let polys = (0..3)
+//! #     .map(|_| ScalarPoly::random(2, &mut rand::thread_rng()))
+//! #     .collect::<Vec<_>>();
+//! # let keygen = frost
+//! #     .new_keygen(polys.iter().map(ScalarPoly::to_point_poly).collect())
+//! #     .unwrap();
+//! # let (shares, pops): (Vec<_>, Vec<_>) = polys
+//! #     .into_iter()
+//! #     .map(|poly| frost.create_shares(&keygen, poly))
+//! #     .unzip();
+//! # let (secret_shares, frost_keys): (Vec<_>, Vec<_>) = (0..3)
+//! #     .map(|i| {
+//! #         let received = shares.iter().map(|shares| shares[i].clone()).collect();
+//! #         frost.finish_keygen(keygen.clone(), i as u32, received, pops.clone()).unwrap()
+//! #     })
+//! #     .unzip();
+//! // party 1 of a 2-of-3 key loses its share and parties 0 and 2 help it
+//! let frost_key = &frost_keys[0];
+//! let helpers = [0, 2];
+//! let deltas = helpers
+//!     .iter()
+//!     .map(|i| {
+//!         repair::repair_deltas(
+//!             &helpers,
+//!             *i,
+//!             &secret_shares[*i as usize],
+//!             1,
+//!             &mut rand::thread_rng(),
+//!         )
+//!     })
+//!     .collect::<Vec<_>>();
+//! // each helper sums the deltas sent to it
+//! let repair_shares = helpers
+//!     .iter()
+//!     .map(|i| {
+//!         let received = deltas
+//!             .iter()
+//!             .flatten()
+//!             .filter(|delta| delta.to == *i)
+//!             .cloned()
+//!             .collect::<Vec<_>>();
+//!         repair::combine_deltas(&helpers, *i, 1, &received).unwrap()
+//!     })
+//!     .collect::<Vec<_>>();
+//! let repaired = repair::finish_repair(frost_key, &helpers, 1, &repair_shares).unwrap();
+//! assert_eq!(repaired, secret_shares[1]);
+//! ```
+//!
+//! [Laing and Stinson]: https://eprint.iacr.org/2017/1155
+use super::FrostKey;
+use crate::{
+    fun::{g, marker::*, rand_core::RngCore, s, Scalar, G},
+    Vec,
+};
+
+/// A helper's random piece of its contribution to the repair (sent privately to the helper at
+/// `to`).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct RepairDelta {
+    /// The index of the helper that made it.
+    pub from: u32,
+    /// The index of the helper it's for.
+    pub to: u32,
+    /// The index of the party whose share is being repaired.
+    pub lost_index: u32,
+    /// The random piece.
+    pub delta: Scalar<Secret, Zero>,
+}
+
+/// A helper's sum of the deltas sent to it (sent privately to the party whose share is being
+/// repaired).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct RepairShare {
+    /// The index of the helper.
+    pub from: u32,
+    /// The index of the party whose share is being repaired.
+    pub lost_index: u32,
+    /// The sum of the deltas.
+    pub sigma: Scalar<Secret, Zero>,
+}
+
+/// Error returned by [`combine_deltas`] and [`finish_repair`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairError {
+    /// There were fewer helpers than the threshold.
+    NotEnoughHelpers,
+    /// There was no message from the helper at this index.
+    Missing(u32),
+    /// There was more than one message from the helper at this index or it was for a different
+    /// repair.
+    Unexpected(u32),
+    /// The repaired share doesn't match the lost party's verification share.
+    InvalidRepairedShare,
+}
+
+impl core::fmt::Display for RepairError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            RepairError::NotEnoughHelpers => write!(f, "not enough helpers"),
+            RepairError::Missing(i) => write!(f, "no message from helper {}", i),
+            RepairError::Unexpected(i) => write!(f, "unexpected message from helper {}", i),
+            RepairError::InvalidRepairedShare => {
+                write!(f, "the repaired share doesn't match the verification share")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RepairError {}
+
+/// The first step of a repair run by the helper at `my_index`.
+///
+/// `helpers` are the indices of all the helpers and must be the same for all of them. Send the
+/// [`RepairDelta`] at position `j` to the helper at `helpers[j]` (including the one to yourself).
+///
+/// # Panics
+///
+/// If `my_index` isn't in `helpers`, `lost_index` is or there are duplicate helpers.
+pub fn repair_deltas(
+    helpers: &[u32],
+    my_index: u32,
+    my_secret_share: &Scalar,
+    lost_index: u32,
+    rng: &mut impl RngCore,
+) -> Vec<RepairDelta> {
+    assert!(
+        helpers.contains(&my_index),
+        "my_index must be one of the helpers"
+    );
+    assert!(
+        !helpers.contains(&lost_index),
+        "the lost party can't be a helper"
+    );
+    let lambda = lagrange_coefficient_at(helpers, my_index, lost_index);
+    let x = my_secret_share;
+    let mut remaining = s!(lambda * x).mark::<Zero>();
+    let mut deltas = helpers[1..]
+        .iter()
+        .map(|to| {
+            let delta = Scalar::random(rng).mark::<Zero>();
+            remaining = s!(remaining - delta);
+            RepairDelta {
+                from: my_index,
+                to: *to,
+                lost_index,
+                delta,
+            }
+        })
+        .collect::<Vec<_>>();
+    deltas.insert(0, RepairDelta {
+        from: my_index,
+        to: helpers[0],
+        lost_index,
+        delta: remaining,
+    });
+    deltas
+}
+
+/// The second step of a repair run by the helper at `my_index`.
+///
+/// `deltas` must be the [`RepairDelta`]s sent to `my_index` by every helper. Send the returned
+/// [`RepairShare`] to the party at `lost_index`.
+pub fn combine_deltas(
+    helpers: &[u32],
+    my_index: u32,
+    lost_index: u32,
+    deltas: &[RepairDelta],
+) -> Result<RepairShare, RepairError> {
+    for delta in deltas {
+        if !helpers.contains(&delta.from)
+            || delta.to != my_index
+            || delta.lost_index != lost_index
+            || deltas
+                .iter()
+                .filter(|other| other.from == delta.from)
+                .count()
+                > 1
+        {
+            return Err(RepairError::Unexpected(delta.from));
+        }
+    }
+    let mut sigma = Scalar::zero();
+    for helper in helpers {
+        let delta = &deltas
+            .iter()
+            .find(|delta| delta.from == *helper)
+            .ok_or(RepairError::Missing(*helper))?
+            .delta;
+        sigma = s!(sigma + delta);
+    }
+    Ok(RepairShare {
+        from: my_index,
+        lost_index,
+        sigma,
+    })
+}
+
+/// The last step of a repair run by the party at `lost_index` returning its secret share.
+///
+/// `repair_shares` must be the [`RepairShare`]s from every helper.
+pub fn finish_repair(
+    frost_key: &FrostKey,
+    helpers: &[u32],
+    lost_index: u32,
+    repair_shares: &[RepairShare],
+) -> Result<Scalar, RepairError> {
+    if helpers.len() < frost_key.threshold() as usize {
+        return Err(RepairError::NotEnoughHelpers);
+    }
+    for share in repair_shares {
+        if !helpers.contains(&share.from)
+            || share.lost_index != lost_index
+            || repair_shares
+                .iter()
+                .filter(|other| other.from == share.from)
+                .count()
+                > 1
+        {
+            return Err(RepairError::Unexpected(share.from));
+        }
+    }
+    let mut secret_share = Scalar::zero();
+    for helper in helpers {
+        let sigma = &repair_shares
+            .iter()
+            .find(|share| share.from == *helper)
+            .ok_or(RepairError::Missing(*helper))?
+            .sigma;
+        secret_share = s!(secret_share + sigma);
+    }
+    let expected = frost_key
+        .verification_shares
+        .get(lost_index as usize)
+        .ok_or(RepairError::InvalidRepairedShare)?;
+    if g!(secret_share * G) != *expected {
+        return Err(RepairError::InvalidRepairedShare);
+    }
+    Ok(secret_share
+        .non_zero()
+        .expect("it matches a non-zero verification share"))
+}
+
+/// The Lagrange coefficient of the party at `my_index` for interpolating the polynomial at
+/// `target`'s x-coordinate from the x-coordinates of `indices`.
+fn lagrange_coefficient_at(indices: &[u32], my_index: u32, target: u32) -> Scalar {
+    let x_i = Scalar::from(my_index + 1);
+    let x_t = Scalar::from(target + 1);
+    let (numerator, denominator) = indices
+        .iter()
+        .filter(|index| **index != my_index)
+        .map(|index| Scalar::from(index + 1))
+        .fold(
+            (Scalar::one(), Scalar::one()),
+            |(numerator, denominator), x_m| {
+                let numerator = s!(numerator * (x_t - x_m))
+                    .non_zero()
+                    .expect("target is not one of the indices");
+                let difference = s!(x_i - x_m).non_zero().expect("indices must be distinct");
+                (numerator, s!(denominator * difference))
+            },
+        );
+    s!(numerator * { denominator.invert() })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        frost::{Frost, ScalarPoly},
+        nonce::Deterministic,
+        Schnorr,
+    };
+    use sha2::Sha256;
+
+    fn keygen(threshold: u32, n: u32) -> (Vec<Scalar>, FrostKey) {
+        let frost = Frost::new(Schnorr::<Sha256, _>::new(Deterministic::<Sha256>::default()));
+        let polys = (0..n)
+            .map(|_| ScalarPoly::random(threshold, &mut rand::thread_rng()))
+            .collect::<Vec<_>>();
+        let keygen = frost
+            .new_keygen(polys.iter().map(ScalarPoly::to_point_poly).collect())
+            .unwrap();
+        let (shares, pops): (Vec<_>, Vec<_>) = polys
+            .into_iter()
+            .map(|poly| frost.create_shares(&keygen, poly))
+            .unzip();
+        let (secret_shares, frost_keys): (Vec<_>, Vec<_>) = (0..n as usize)
+            .map(|i| {
+                let received = shares.iter().map(|shares| shares[i].clone()).collect();
+                frost
+                    .finish_keygen(keygen.clone(), i as u32, received, pops.clone())
+                    .unwrap()
+            })
+            .unzip();
+        (secret_shares, frost_keys[0].clone())
+    }
+
+    fn run_repair(
+        secret_shares: &[Scalar],
+        helpers: &[u32],
+        lost_index: u32,
+    ) -> (Vec<Vec<RepairDelta>>, Vec<RepairShare>) {
+        let deltas = helpers
+            .iter()
+            .map(|i| {
+                repair_deltas(
+                    helpers,
+                    *i,
+                    &secret_shares[*i as usize],
+                    lost_index,
+                    &mut rand::thread_rng(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let repair_shares = helpers
+            .iter()
+            .map(|i| {
+                let received = deltas
+                    .iter()
+                    .flatten()
+                    .filter(|delta| delta.to == *i)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                combine_deltas(helpers, *i, lost_index, &received).unwrap()
+            })
+            .collect();
+        (deltas, repair_shares)
+    }
+
+    #[test]
+    fn repair_lost_share() {
+        let (secret_shares, frost_key) = keygen(3, 5);
+        for (helpers, lost_index) in [
+            (vec![0, 1, 2], 4),
+            (vec![4, 2, 0, 3], 1),
+            (vec![1, 2, 3], 0),
+        ] {
+            let (_, repair_shares) = run_repair(&secret_shares, &helpers, lost_index);
+            assert_eq!(
+                finish_repair(&frost_key, &helpers, lost_index, &repair_shares),
+                Ok(secret_shares[lost_index as usize].clone())
+            );
+        }
+    }
+
+    #[test]
+    fn repair_failures() {
+        let (secret_shares, frost_key) = keygen(3, 5);
+        let helpers = [0, 1, 2];
+        let (deltas, repair_shares) = run_repair(&secret_shares, &helpers, 4);
+
+        assert_eq!(
+            finish_repair(&frost_key, &helpers[..2], 4, &repair_shares[..2]),
+            Err(RepairError::NotEnoughHelpers)
+        );
+        assert_eq!(
+            finish_repair(&frost_key, &helpers, 4, &repair_shares[..2]),
+            Err(RepairError::Missing(2))
+        );
+        assert_eq!(
+            finish_repair(&frost_key, &helpers, 3, &repair_shares),
+            Err(RepairError::Unexpected(0))
+        );
+        let mut bad = repair_shares.clone();
+        bad[1].sigma = s!({ &bad[1].sigma } + 1);
+        assert_eq!(
+            finish_repair(&frost_key, &helpers, 4, &bad),
+            Err(RepairError::InvalidRepairedShare)
+        );
+
+        let received = deltas
+            .iter()
+            .flatten()
+            .filter(|delta| delta.to == 1)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            combine_deltas(&helpers, 1, 4, &received[..2]),
+            Err(RepairError::Missing(2))
+        );
+        assert_eq!(
+            combine_deltas(&helpers, 2, 4, &received),
+            Err(RepairError::Unexpected(0))
+        );
+    }
+}
@@ -0,0 +1,737 @@
+//! Refreshing and resharing FROST secret shares without changing the public key.
+//!
+//! Long-lived threshold keys need their shares changed from time to time: an attacker who steals
+//! fewer than a threshold of shares before a [refresh](refresh_shares) and a few more after it
+//! can't combine them. Parties also come and go which is what [resharing](reshare) is for: a
+//! threshold of the current share holders deal fresh shares of the same secret to a new set of
+//! parties with a new threshold.
+//!
+//! Both protocols are a single round. Each dealer broadcasts a [`Commitment`] to their polynomial
+//! and sends each recipient their [`Share`] privately. Recipients check the shares against the
+//! commitments and combine them into their new secret share and the new [`FrostKey`]. Every
+//! recipient has to use the same set of commitments or they'll end up with incompatible keys so
+//! the commitments should be agreed on (e.g. with a broadcast channel) first.
+//!
+//! - **Refresh**: every party deals a random polynomial `f_i` with `f_i(0) = 0` to all the parties
+//!   at the same indices. The new share of the party at `j` is its old share plus the sum of all
+//!   the `f_i(j)`. The threshold and indices stay the same.
+//! - **Reshare**: each member `i` of a coalition of at least the old threshold deals a polynomial
+//!   `h_i` of the new threshold's degree with `h_i(0) = λ_i * x_i` (its Lagrange weighted old share)
+//!   to the new parties. The commitment to `h_i(0)` is checked against `i`'s old verification
+//!   share so a dealer can't change the secret.
+//!
+//! The tweak of the key (see [`FrostKey::tweak`]) is kept in both cases.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{reshare, Frost, ScalarPoly},
+//!     nonce::Deterministic,
+//!     Schnorr,
+//! };
+//! use sha2::Sha256;
+//! # let frost = Frost::new(Schnorr::<Sha256, Deterministic<Sha256>>::new(
+//! #     Deterministic::<Sha256>::default(),
+//! # ));
+//! # let polys = (0..3)
+//! #     .map(|_| ScalarPoly::random(2, &mut rand::thread_rng()))
+//! #     .collect::<Vec<_>>();
+//! # let keygen = frost
+//! #     .new_keygen(polys.iter().map(ScalarPoly::to_point_poly).collect())
+//! #     .unwrap();
+//! # let (shares, pops): (Vec<_>, Vec<_>) = polys
+//! #     .into_iter()
+//! #     .map(|poly| frost.create_shares(&keygen, poly))
+//! #     .unzip();
+//! # let (secret_shares, frost_keys): (Vec<_>, Vec<_>) = (0..3)
+//! #     .map(|i| {
+//! #         let received = shares.iter().map(|shares| shares[i].clone()).collect();
+//! #         frost.finish_keygen(keygen.clone(), i as u32, received, pops.clone()).unwrap()
+//! #     })
+//! #     .unzip();
+//! // a 2-of-3 key held by parties 0, 1 and 2. Parties 0 and 2 reshare it 3-of-4.
+//! let frost_key = &frost_keys[0];
+//! let coalition = [0, 2];
+//! let (commitments, shares): (Vec<_>, Vec<_>) = coalition
+//!     .iter()
+//!     .map(|i| {
+//!         reshare::reshare(
+//!             frost_key,
+//!             &coalition,
+//!             *i,
+//!             &secret_shares[*i as usize],
+//!             3,
+//!             4,
+//!             &mut rand::thread_rng(),
+//!         )
+//!     })
+//!     .unzip();
+//! // each new party collects the shares addressed to it
+//! let new_keys = (0..4)
+//!     .map(|j| {
+//!         let my_shares = shares
+//!             .iter()
+//!             .map(|shares| shares[j].clone())
+//!             .collect::<Vec<_>>();
+//!         reshare::finish_reshare(
+//!             frost_key,
+//!             &coalition,
+//!             3,
+//!             4,
+//!             j as u32,
+//!             &commitments,
+//!             &my_shares,
+//!         )
+//!         .unwrap()
+//!     })
+//!     .collect::<Vec<_>>();
+//! assert_eq!(new_keys[0].1.public_key(), frost_key.public_key());
+//! assert_eq!(new_keys[0].1.threshold(), 3);
+//! assert_eq!(new_keys[0].1.n_signers(), 4);
+//! ```
+use super::{lagrange_coefficient, FrostKey, PointPoly, ScalarPoly};
+use crate::{
+    fun::{g, marker::*, rand_core::RngCore, s, Point, Scalar, G},
+    Vec,
+};
+
+/// A dealer's commitment to their polynomial (broadcast to every recipient).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct Commitment {
+    /// The index of the dealer.
+    pub from: u32,
+    /// The commitment to the polynomial.
+    ///
+    /// For a refresh this is the commitment to `g_i` where the dealt polynomial is `f_i(x) = x *
+    /// g_i(x)` so that `f_i(0) = 0`.
+    pub poly: PointPoly,
+}
+
+/// A share of a dealer's polynomial for one recipient (sent to them privately).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct Share {
+    /// The index of the dealer.
+    pub from: u32,
+    /// The index of the recipient.
+    pub to: u32,
+    /// The dealer's polynomial evaluated at the recipient's index.
+    pub share: Scalar<Secret, Zero>,
+}
+
+/// Error returned by [`finish_refresh`] and [`finish_reshare`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReshareError {
+    /// There were fewer dealers than needed.
+    NotEnoughDealers,
+    /// The commitment from the dealer at this index is malformed, from a dealer that isn't
+    /// expected or a duplicate.
+    InvalidCommitment(u32),
+    /// There was no share from the dealer at this index.
+    MissingShare(u32),
+    /// The share from the dealer at this index doesn't match their commitment.
+    InvalidShare(u32),
+    /// The commitments add up to give some party a zero share. Only a dealer who chose their
+    /// polynomial after seeing everyone else's commitment can do this so the round should be run
+    /// again with dealers committing to their commitments in advance.
+    ZeroShare,
+}
+
+impl core::fmt::Display for ReshareError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            ReshareError::NotEnoughDealers => write!(f, "not enough dealers"),
+            ReshareError::InvalidCommitment(i) => {
+                write!(f, "the commitment from dealer {} is invalid", i)
+            }
+            ReshareError::MissingShare(i) => write!(f, "no share from dealer {}", i),
+            ReshareError::InvalidShare(i) => {
+                write!(
+                    f,
+                    "the share from dealer {} doesn't match their commitment",
+                    i
+                )
+            }
+            ReshareError::ZeroShare => write!(f, "the commitments give a party a zero share"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReshareError {}
+
+/// Starts a refresh as the party at `my_index`.
+///
+/// Broadcast the [`Commitment`] and send the [`Share`] at position `j` to the party at `j`
+/// (including the one to yourself).
+///
+/// # Panics
+///
+/// If the key's threshold is 1 since every party knows the secret then and there is nothing to
+/// refresh.
+pub fn refresh_shares(
+    frost_key: &FrostKey,
+    my_index: u32,
+    rng: &mut impl RngCore,
+) -> (Commitment, Vec<Share>) {
+    assert!(frost_key.threshold > 1, "can't refresh a 1-of-n key");
+    let poly = ScalarPoly::random(frost_key.threshold - 1, rng);
+    let shares = (0..frost_key.n_signers())
+        .map(|to| {
+            let x = index_scalar(to);
+            Share {
+                from: my_index,
+                to,
+                share: s!(x * { poly.eval_at(&x) }),
+            }
+        })
+        .collect();
+    (
+        Commitment {
+            from: my_index,
+            poly: poly.to_point_poly(),
+        },
+        shares,
+    )
+}
+
+/// Finishes a refresh as the party at `my_index` returning the new secret share and key.
+///
+/// `commitments` are the commitments from all the dealers and `shares` the shares sent to
+/// `my_index`. The public key, threshold and indices stay the same.
+pub fn finish_refresh(
+    frost_key: &FrostKey,
+    my_index: u32,
+    my_secret_share: &Scalar,
+    commitments: &[Commitment],
+    shares: &[Share],
+) -> Result<(Scalar, FrostKey), ReshareError> {
+    let n_signers = frost_key.n_signers();
+    if commitments.is_empty() {
+        return Err(ReshareError::NotEnoughDealers);
+    }
+    for (i, commitment) in commitments.iter().enumerate() {
+        if commitment.from >= n_signers
+            || commitment.poly.poly_len() != (frost_key.threshold - 1) as usize
+            || commitments[..i]
+                .iter()
+                .any(|other| other.from == commitment.from)
+        {
+            return Err(ReshareError::InvalidCommitment(commitment.from));
+        }
+    }
+
+    let x = index_scalar(my_index);
+    let mut secret_share = my_secret_share.clone().mark::<Zero>();
+    for commitment in commitments {
+        let share = find_share(shares, commitment.from, my_index)?;
+        let G_i = commitment.poly.eval_at(&x);
+        if g!(share * G) != g!(x * G_i) {
+            return Err(ReshareError::InvalidShare(commitment.from));
+        }
+        secret_share = s!(secret_share + share);
+    }
+
+    let verification_shares = frost_key
+        .verification_shares
+        .iter()
+        .enumerate()
+        .map(|(j, X_j)| {
+            let x_j = index_scalar(j as u32);
+            let delta = sum_evals(commitments, &x_j);
+            g!(X_j + x_j * delta).normalize().non_zero()
+        })
+        .collect::<Option<Vec<Point>>>()
+        .ok_or(ReshareError::ZeroShare)?;
+
+    Ok((
+        secret_share.non_zero().ok_or(ReshareError::ZeroShare)?,
+        FrostKey {
+            verification_shares,
+            ..frost_key.clone()
+        },
+    ))
+}
+
+/// Starts resharing the key as the party at `my_index` in `coalition`.
+///
+/// `coalition` is the indices of the current share holders that are dealing (at least the
+/// current threshold of them) and every one of them must use the same one. The new key will be
+/// `new_threshold`-of-`new_n_signers`. Broadcast the [`Commitment`] and send the [`Share`] at
+/// position `j` to the new party at `j`.
+///
+/// # Panics
+///
+/// If `coalition` is smaller than the threshold, `new_threshold` is zero or more than
+/// `new_n_signers` or `my_index` is not in `coalition`.
+pub fn reshare(
+    frost_key: &FrostKey,
+    coalition: &[u32],
+    my_index: u32,
+    my_secret_share: &Scalar,
+    new_threshold: u32,
+    new_n_signers: u32,
+    rng: &mut impl RngCore,
+) -> (Commitment, Vec<Share>) {
+    assert!(
+        coalition.len() >= frost_key.threshold as usize,
+        "the coalition must have at least the threshold of members"
+    );
+    assert!(
+        new_threshold > 0 && new_threshold <= new_n_signers,
+        "new threshold must be between 1 and the new number of signers"
+    );
+    assert!(
+        coalition.contains(&my_index),
+        "my_index must be part of the coalition"
+    );
+    let lambda = coalition_lagrange_coefficient(coalition, my_index);
+    let x = my_secret_share;
+    let poly = ScalarPoly::random_using_secret(new_threshold, s!(lambda * x), rng);
+    let shares = (0..new_n_signers)
+        .map(|to| Share {
+            from: my_index,
+            to,
+            share: poly.eval(to + 1),
+        })
+        .collect();
+    (
+        Commitment {
+            from: my_index,
+            poly: poly.to_point_poly(),
+        },
+        shares,
+    )
+}
+
+/// Finishes resharing as the new party at `my_new_index` returning its secret share of the new
+/// `new_threshold`-of-`new_n_signers` key.
+///
+/// `frost_key` is the current key, `commitments` must have one commitment from every member of
+/// `coalition` and `shares` are the shares sent to `my_new_index`. A `coalition` with an index
+/// that isn't a current party or with the same index twice is rejected with
+/// [`ReshareError::InvalidCommitment`] for that index.
+pub fn finish_reshare(
+    frost_key: &FrostKey,
+    coalition: &[u32],
+    new_threshold: u32,
+    new_n_signers: u32,
+    my_new_index: u32,
+    commitments: &[Commitment],
+    shares: &[Share],
+) -> Result<(Scalar, FrostKey), ReshareError> {
+    for (i, index) in coalition.iter().enumerate() {
+        if *index as usize >= frost_key.verification_shares.len() || coalition[..i].contains(index)
+        {
+            return Err(ReshareError::InvalidCommitment(*index));
+        }
+    }
+    if coalition.len() < frost_key.threshold as usize || commitments.len() != coalition.len() {
+        return Err(ReshareError::NotEnoughDealers);
+    }
+    for (i, commitment) in commitments.iter().enumerate() {
+        let valid = coalition.contains(&commitment.from)
+            && !commitments[..i]
+                .iter()
+                .any(|other| other.from == commitment.from)
+            && commitment.poly.poly_len() == new_threshold as usize
+            && {
+                let lambda = coalition_lagrange_coefficient(coalition, commitment.from);
+                let X_i = frost_key.verification_shares[commitment.from as usize];
+                commitment.poly.points()[0] == g!(lambda * X_i)
+            };
+        if !valid {
+            return Err(ReshareError::InvalidCommitment(commitment.from));
+        }
+    }
+
+    let x = index_scalar(my_new_index);
+    let mut secret_share = Scalar::zero();
+    for commitment in commitments {
+        let share = find_share(shares, commitment.from, my_new_index)?;
+        if !commitment.poly.verify_share(&x, share) {
+            return Err(ReshareError::InvalidShare(commitment.from));
+        }
+        secret_share = s!(secret_share + share);
+    }
+
+    let verification_shares = (0..new_n_signers)
+        .map(|j| {
+            sum_evals(commitments, &index_scalar(j))
+                .normalize()
+                .non_zero()
+        })
+        .collect::<Option<Vec<Point>>>()
+        .ok_or(ReshareError::ZeroShare)?;
+
+    Ok((
+        secret_share.non_zero().ok_or(ReshareError::ZeroShare)?,
+        FrostKey {
+            verification_shares,
+            threshold: new_threshold,
+            ..frost_key.clone()
+        },
+    ))
+}
+
+/// The x-coordinate of the party at `index`.
+fn index_scalar(index: u32) -> Scalar<Public> {
+    Scalar::from(index + 1)
+        .non_zero()
+        .expect("index must be less than u32::MAX")
+        .mark::<Public>()
+}
+
+fn coalition_lagrange_coefficient(coalition: &[u32], index: u32) -> Scalar {
+    lagrange_coefficient(
+        &coalition.iter().map(|i| i + 1).collect::<Vec<_>>(),
+        index + 1,
+    )
+}
+
+fn find_share(shares: &[Share], from: u32, to: u32) -> Result<&Scalar<Secret, Zero>, ReshareError> {
+    shares
+        .iter()
+        .find(|share| share.from == from && share.to == to)
+        .map(|share| &share.share)
+        .ok_or(ReshareError::MissingShare(from))
+}
+
+/// Sums the evaluations at `x` of all the committed polynomials.
+fn sum_evals(commitments: &[Commitment], x: &Scalar<Public>) -> Point<Jacobian, Public, Zero> {
+    commitments
+        .iter()
+        .fold(Point::zero().mark::<Jacobian>(), |acc, commitment| {
+            let eval = commitment.poly.eval_at(x);
+            g!(acc + eval)
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{frost::Frost, nonce::Deterministic, Message, Schnorr};
+    use sha2::Sha256;
+
+    type TestFrost = Frost<Sha256, Deterministic<Sha256>>;
+
+    fn frost() -> TestFrost {
+        Frost::new(Schnorr::new(Deterministic::<Sha256>::default()))
+    }
+
+    fn keygen(frost: &TestFrost, threshold: u32, n: u32) -> (Vec<Scalar>, FrostKey) {
+        let polys = (0..n)
+            .map(|_| ScalarPoly::random(threshold, &mut rand::thread_rng()))
+            .collect::<Vec<_>>();
+        let keygen = frost
+            .new_keygen(polys.iter().map(ScalarPoly::to_point_poly).collect())
+            .unwrap();
+        let (shares, pops): (Vec<_>, Vec<_>) = polys
+            .into_iter()
+            .map(|poly| frost.create_shares(&keygen, poly))
+            .unzip();
+        let (secret_shares, frost_keys): (Vec<_>, Vec<_>) = (0..n as usize)
+            .map(|i| {
+                let received = shares.iter().map(|shares| shares[i].clone()).collect();
+                frost
+                    .finish_keygen(keygen.clone(), i as u32, received, pops.clone())
+                    .unwrap()
+            })
+            .unzip();
+        (secret_shares, frost_keys[0].clone())
+    }
+
+    fn sign_with(frost: &TestFrost, frost_key: &FrostKey, signers: &[(u32, &Scalar)]) {
+        let frost_key = frost_key.clone().into_xonly_key();
+        let message = Message::<Public>::plain("test", b"reshare");
+        let nonces = signers
+            .iter()
+            .map(|(i, x)| {
+                frost.gen_nonce(x, &[*i as u8], Some(frost_key.public_key()), Some(message))
+            })
+            .collect::<Vec<_>>();
+        let session = frost.start_sign_session(
+            &frost_key,
+            signers
+                .iter()
+                .zip(&nonces)
+                .map(|((i, _), nonce)| (*i, nonce.public()))
+                .collect(),
+            message,
+        );
+        let signature_shares = signers
+            .iter()
+            .zip(nonces)
+            .map(|((i, x), nonce)| {
+                let share = frost.sign(&frost_key, &session, *i, x, nonce);
+                assert!(frost.verify_signature_share(&frost_key, &session, *i, share));
+                share
+            })
+            .collect();
+        let signature = frost.combine_signature_shares(&frost_key, &session, signature_shares);
+        assert!(frost
+            .schnorr
+            .verify(&frost_key.public_key(), message, &signature));
+    }
+
+    #[test]
+    fn refresh_keeps_key() {
+        let frost = frost();
+        let (secret_shares, mut frost_key) = keygen(&frost, 2, 3);
+        let mut frost_key = frost_key.tweak(Scalar::from(7u32)).unwrap();
+        let (commitments, shares): (Vec<_>, Vec<_>) = (0..3)
+            .map(|i| refresh_shares(&frost_key, i, &mut rand::thread_rng()))
+            .unzip();
+        let refreshed = (0..3)
+            .map(|j| {
+                let my_shares = shares
+                    .iter()
+                    .map(|shares| shares[j].clone())
+                    .collect::<Vec<_>>();
+                finish_refresh(
+                    &frost_key,
+                    j as u32,
+                    &secret_shares[j],
+                    &commitments,
+                    &my_shares,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        for (j, (secret_share, new_key)) in refreshed.iter().enumerate() {
+            assert_ne!(secret_share, &secret_shares[j]);
+            assert_eq!(new_key, &refreshed[0].1);
+            assert_eq!(new_key.public_key(), frost_key.public_key());
+            assert_eq!(
+                new_key.verification_shares().nth(j).unwrap(),
+                g!(secret_share * G)
+            );
+        }
+        sign_with(&frost, &refreshed[0].1, &[
+            (0, &refreshed[0].0),
+            (2, &refreshed[2].0),
+        ]);
+
+        // a bad share is caught
+        let mut bad_shares = shares
+            .iter()
+            .map(|shares| shares[1].clone())
+            .collect::<Vec<_>>();
+        bad_shares[2].share = s!({ &bad_shares[2].share } + 1);
+        assert_eq!(
+            finish_refresh(&frost_key, 1, &secret_shares[1], &commitments, &bad_shares),
+            Err(ReshareError::InvalidShare(2))
+        );
+        assert_eq!(
+            finish_refresh(
+                &frost_key,
+                1,
+                &secret_shares[1],
+                &commitments,
+                &bad_shares[..2]
+            ),
+            Err(ReshareError::MissingShare(2))
+        );
+        frost_key.threshold = 3;
+        assert_eq!(
+            finish_refresh(&frost_key, 1, &secret_shares[1], &commitments, &bad_shares),
+            Err(ReshareError::InvalidCommitment(0))
+        );
+    }
+
+    #[test]
+    fn reshare_changes_parameters() {
+        let frost = frost();
+        let (secret_shares, frost_key) = keygen(&frost, 2, 3);
+        let coalition = [2, 0];
+        let (commitments, shares): (Vec<_>, Vec<_>) = coalition
+            .iter()
+            .map(|i| {
+                reshare(
+                    &frost_key,
+                    &coalition,
+                    *i,
+                    &secret_shares[*i as usize],
+                    3,
+                    5,
+                    &mut rand::thread_rng(),
+                )
+            })
+            .unzip();
+        let new_keys = (0..5)
+            .map(|j| {
+                let my_shares = shares
+                    .iter()
+                    .map(|shares| shares[j].clone())
+                    .collect::<Vec<_>>();
+                finish_reshare(
+                    &frost_key,
+                    &coalition,
+                    3,
+                    5,
+                    j as u32,
+                    &commitments,
+                    &my_shares,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let new_key = &new_keys[0].1;
+        assert_eq!(new_key.public_key(), frost_key.public_key());
+        assert_eq!(new_key.threshold(), 3);
+        assert_eq!(new_key.n_signers(), 5);
+        for (j, (secret_share, key)) in new_keys.iter().enumerate() {
+            assert_eq!(key, new_key);
+            assert_eq!(
+                key.verification_shares().nth(j).unwrap(),
+                g!(secret_share * G)
+            );
+        }
+        sign_with(&frost, new_key, &[
+            (1, &new_keys[1].0),
+            (3, &new_keys[3].0),
+            (4, &new_keys[4].0),
+        ]);
+
+        // a dealer can't change the secret
+        let mut bad_commitments = commitments.clone();
+        let (bad_commitment, _) = reshare(
+            &frost_key,
+            &coalition,
+            0,
+            &Scalar::random(&mut rand::thread_rng()),
+            3,
+            5,
+            &mut rand::thread_rng(),
+        );
+        bad_commitments[1] = bad_commitment;
+        let my_shares = shares
+            .iter()
+            .map(|shares| shares[0].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            finish_reshare(
+                &frost_key,
+                &coalition,
+                3,
+                5,
+                0,
+                &bad_commitments,
+                &my_shares
+            ),
+            Err(ReshareError::InvalidCommitment(0))
+        );
+        assert_eq!(
+            finish_reshare(
+                &frost_key,
+                &coalition[..1],
+                3,
+                5,
+                0,
+                &commitments[..1],
+                &my_shares
+            ),
+            Err(ReshareError::NotEnoughDealers)
+        );
+
+        // the coalition must be distinct current parties
+        assert_eq!(
+            finish_reshare(&frost_key, &[2, 3], 3, 5, 0, &commitments, &my_shares),
+            Err(ReshareError::InvalidCommitment(3))
+        );
+        assert_eq!(
+            finish_reshare(&frost_key, &[2, 2], 3, 5, 0, &commitments, &my_shares),
+            Err(ReshareError::InvalidCommitment(2))
+        );
+    }
+
+    #[test]
+    fn rushing_dealer_zeroing_a_share_is_an_error() {
+        let frost = frost();
+        let (secret_shares, frost_key) = keygen(&frost, 3, 3);
+        // dealers 0 and 1 are honest
+        let (mut commitments, shares): (Vec<_>, Vec<_>) = (0..2)
+            .map(|i| refresh_shares(&frost_key, i, &mut rand::thread_rng()))
+            .unzip();
+        let mut my_shares = shares
+            .iter()
+            .map(|shares| shares[0].clone())
+            .collect::<Vec<_>>();
+        // dealer 2 waits for their commitments and picks g_2 so that party 1's new verification
+        // share is zero while still giving party 0 a valid share
+        let (x_me, x_victim) = (index_scalar(0), index_scalar(1));
+        let k = Scalar::random(&mut rand::thread_rng());
+        let K = g!(k * G);
+        let X_victim = frost_key.verification_shares().nth(1).unwrap();
+        let T = g!(-{ x_victim.invert() } * X_victim - { sum_evals(&commitments, &x_victim) });
+        let A1 = g!({ s!(x_victim - x_me).non_zero().unwrap().invert() } * (T - K));
+        let A0 = g!(K - x_me * A1);
+        commitments.push(Commitment {
+            from: 2,
+            poly: PointPoly(vec![
+                A0.normalize().non_zero().unwrap(),
+                A1.normalize().non_zero().unwrap(),
+            ]),
+        });
+        my_shares.push(Share {
+            from: 2,
+            to: 0,
+            share: s!(x_me * k).mark::<Zero>(),
+        });
+        assert_eq!(
+            finish_refresh(&frost_key, 0, &secret_shares[0], &commitments, &my_shares),
+            Err(ReshareError::ZeroShare)
+        );
+
+        let (secret_shares, frost_key) = keygen(&frost, 2, 3);
+        let coalition = [0, 1];
+        // dealer 0 is honest
+        let (commitment, shares) = reshare(
+            &frost_key,
+            &coalition,
+            0,
+            &secret_shares[0],
+            3,
+            3,
+            &mut rand::thread_rng(),
+        );
+        let mut commitments = vec![commitment];
+        let mut my_shares = vec![shares[0].clone()];
+        // dealer 1 has to keep h_1(0) fixed but has two more coefficients to make the evaluation
+        // at the victim cancel out and give party 0 a valid share
+        let lambda = coalition_lagrange_coefficient(&coalition, 1);
+        let A0 = g!(lambda * { frost_key.verification_shares().nth(1).unwrap() });
+        let U = g!({ x_me.invert() } * (K - A0));
+        let V = g!({ x_victim.invert() } * (-{ sum_evals(&commitments, &x_victim) } - A0));
+        let A2 = g!({ s!(x_victim - x_me).non_zero().unwrap().invert() } * (V - U));
+        let A1 = g!(U - x_me * A2);
+        commitments.push(Commitment {
+            from: 1,
+            poly: PointPoly(vec![
+                A0.normalize(),
+                A1.normalize().non_zero().unwrap(),
+                A2.normalize().non_zero().unwrap(),
+            ]),
+        });
+        my_shares.push(Share {
+            from: 1,
+            to: 0,
+            share: k.mark::<Zero>(),
+        });
+        assert_eq!(
+            finish_reshare(&frost_key, &coalition, 3, 3, 0, &commitments, &my_shares),
+            Err(ReshareError::ZeroShare)
+        );
+    }
+}
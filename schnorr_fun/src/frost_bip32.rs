@@ -0,0 +1,315 @@
+//! BIP32 style derivation of child keys from a [FROST] key.
+//!
+//! Non-hardened [BIP32] derivation only needs the parent public key and chain code so an
+//! [`ExtendedFrostKey`] can derive child public keys exactly as an `xpub` would. The child key is
+//! the parent key plus a public tweak which [`FrostKey::tweak`] applies to the joint key. The
+//! signers keep using the secret shares they got from key generation and nobody ever learns the
+//! master secret.
+//!
+//! Hardened BIP32 derivation hashes the parent *secret* key which can't be done without
+//! reconstructing it (or evaluating HMAC-SHA512 inside an MPC protocol). Instead
+//! [`ExtendedFrostKey::derive_hardened_child`] puts a [`HardeningKey`] where the secret key would
+//! go. The hardening key is a random secret that the signers agree on at key generation and store
+//! alongside their shares. Like hardened BIP32 keys, someone who learns the chain code and a child
+//! secret key can't work back to the parent secret key or derive the child's siblings unless they
+//! also know the hardening key. Unlike hardened BIP32 keys every signer knows the hardening key so
+//! this only protects against outsiders, not against a signer who also learns a child secret key.
+//! Hardened children are not compatible with other BIP32 implementations.
+//!
+//! # Example
+//!
+//! ```
+//! # use schnorr_fun::{frost::{Frost, ScalarPoly}, nonce::Deterministic, Schnorr, fun::Scalar};
+//! # use sha2::Sha256;
+//! use schnorr_fun::frost_bip32::{ExtendedFrostKey, HardeningKey};
+//! # let frost = Frost::new(Schnorr::<Sha256, Deterministic<Sha256>>::new(Deterministic::<Sha256>::default()));
+//! # let scalar_poly = ScalarPoly::random(2, &mut rand::thread_rng());
+//! # let scalar_poly2 = ScalarPoly::random(2, &mut rand::thread_rng());
+//! # let keygen = frost.new_keygen(vec![scalar_poly.to_point_poly(), scalar_poly2.to_point_poly()]).unwrap();
+//! # let (shares, pop) = frost.create_shares(&keygen, scalar_poly);
+//! # let (shares2, pop2) = frost.create_shares(&keygen, scalar_poly2);
+//! # let (_secret_share, frost_key) = frost.finish_keygen(keygen, 0, vec![shares[0].clone(), shares2[0].clone()], vec![pop, pop2]).unwrap();
+//! // after key generation the signers agree on a chain code and a hardening key
+//! let chain_code = [42u8; 32];
+//! let hardening_key = HardeningKey::random(&mut rand::thread_rng());
+//! let master = ExtendedFrostKey::new(frost_key, chain_code);
+//! let path = "m/1'/0/7".parse().unwrap();
+//! let child = master.derive_path(Some(&hardening_key), &path).unwrap();
+//! // sign under the child key with the original secret shares
+//! let xonly_child_key = child.into_frost_key().into_xonly_key();
+//! ```
+//!
+//! [FROST]: crate::frost
+//! [BIP32]: <https://bips.xyz/32>
+use crate::{
+    bip32::{hmac_sha512, ChildIndex, DerivationPath},
+    frost::FrostKey,
+    fun::{
+        rand_core::{CryptoRng, RngCore},
+        Scalar,
+    },
+};
+use core::fmt;
+
+pub use crate::bip32::{DeriveError, HARDENED};
+
+/// The secret the signers of a FROST key share for hardened derivation.
+///
+/// See the [module documentation](crate::frost_bip32).
+#[derive(Clone, PartialEq, Eq)]
+pub struct HardeningKey([u8; 32]);
+
+impl HardeningKey {
+    /// Generates a new hardening key.
+    pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes);
+        HardeningKey(bytes)
+    }
+
+    /// Creates a hardening key from bytes that the signers already agree on.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        HardeningKey(bytes)
+    }
+
+    /// The hardening key as bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl fmt::Debug for HardeningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("HardeningKey").field(&"<redacted>").finish()
+    }
+}
+
+/// A [`FrostKey`] with a chain code so that child keys can be derived from it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct ExtendedFrostKey {
+    frost_key: FrostKey,
+    chain_code: [u8; 32],
+}
+
+impl ExtendedFrostKey {
+    /// Creates the master extended key from the key produced by key generation.
+    pub fn new(frost_key: FrostKey, chain_code: [u8; 32]) -> Self {
+        ExtendedFrostKey {
+            frost_key,
+            chain_code,
+        }
+    }
+
+    /// The FROST key at this point in the derivation.
+    pub fn frost_key(&self) -> &FrostKey {
+        &self.frost_key
+    }
+
+    /// Converts into the FROST key to sign with (usually after calling
+    /// [`FrostKey::into_xonly_key`]).
+    pub fn into_frost_key(self) -> FrostKey {
+        self.frost_key
+    }
+
+    /// The chain code.
+    pub fn chain_code(&self) -> [u8; 32] {
+        self.chain_code
+    }
+
+    /// Derives a non-hardened child key the same way as BIP32's `CKDpub`.
+    ///
+    /// ## Return value
+    ///
+    /// Returns an error if `index` is hardened or in the unlikely event that it gives an invalid key.
+    pub fn derive_child(&self, index: u32) -> Result<Self, DeriveError> {
+        if index >= HARDENED {
+            return Err(DeriveError::HardenedIndex);
+        }
+        let public_key = self.frost_key.public_key().to_bytes();
+        self.tweak_child(&[&public_key[..], &index.to_be_bytes()[..]])
+    }
+
+    /// Derives a hardened child key using a [`HardeningKey`] in place of the parent secret key.
+    ///
+    /// ## Return value
+    ///
+    /// Returns an error if `index` isn't hardened or in the unlikely event that it gives an invalid key.
+    pub fn derive_hardened_child(
+        &self,
+        hardening_key: &HardeningKey,
+        index: u32,
+    ) -> Result<Self, DeriveError> {
+        if index < HARDENED {
+            return Err(DeriveError::NonHardenedIndex);
+        }
+        self.tweak_child(&[&[0u8], &hardening_key.0[..], &index.to_be_bytes()[..]])
+    }
+
+    /// Derives the key at the end of `path` (relative to this key).
+    ///
+    /// `hardening_key` is only needed if the path has a hardened index.
+    pub fn derive_path(
+        &self,
+        hardening_key: Option<&HardeningKey>,
+        path: &DerivationPath,
+    ) -> Result<Self, DeriveError> {
+        let mut key = self.clone();
+        for index in path.iter().map(ChildIndex::to_u32) {
+            key = if index >= HARDENED {
+                key.derive_hardened_child(
+                    hardening_key.ok_or(DeriveError::MissingHardeningKey)?,
+                    index,
+                )?
+            } else {
+                key.derive_child(index)?
+            };
+        }
+        Ok(key)
+    }
+
+    fn tweak_child(&self, data: &[&[u8]]) -> Result<Self, DeriveError> {
+        let I = hmac_sha512(&self.chain_code, data);
+        let mut IL = [0u8; 32];
+        let mut chain_code = [0u8; 32];
+        IL.copy_from_slice(&I[..32]);
+        chain_code.copy_from_slice(&I[32..]);
+        let tweak = Scalar::from_bytes(IL).ok_or(DeriveError::InvalidChild)?;
+        let frost_key = self
+            .frost_key
+            .clone()
+            .tweak(tweak)
+            .ok_or(DeriveError::InvalidChild)?;
+        Ok(ExtendedFrostKey {
+            frost_key,
+            chain_code,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        frost::{Frost, ScalarPoly},
+        fun::{g, s, Point, G},
+        nonce::Deterministic,
+        Message, Schnorr, Vec,
+    };
+    use sha2::Sha256;
+
+    #[test]
+    fn derived_keys_match_bip32_and_can_sign() {
+        let frost = Frost::new(Schnorr::<Sha256, Deterministic<Sha256>>::new(
+            Deterministic::<Sha256>::default(),
+        ));
+        // the joint secret key is 3 + 11 + 17 = 31
+        let scalar_polys = vec![
+            ScalarPoly::new(vec![s!(3), s!(7)]),
+            ScalarPoly::new(vec![s!(11), s!(13)]),
+            ScalarPoly::new(vec![s!(17), s!(19)]),
+        ];
+        let keygen = frost
+            .new_keygen(scalar_polys.iter().map(|sp| sp.to_point_poly()).collect())
+            .unwrap();
+        let (shares, pops): (Vec<_>, Vec<_>) = scalar_polys
+            .into_iter()
+            .map(|sp| frost.create_shares(&keygen, sp))
+            .unzip();
+        let (secret_shares, frost_keys): (Vec<_>, Vec<_>) = (0..3)
+            .map(|i| {
+                frost
+                    .finish_keygen(
+                        keygen.clone(),
+                        i as u32,
+                        shares.iter().map(|s| s[i].clone()).collect(),
+                        pops.clone(),
+                    )
+                    .unwrap()
+            })
+            .unzip();
+        assert_eq!(frost_keys[0].public_key(), g!({ s!(31) } * G).normalize());
+
+        let master = ExtendedFrostKey::new(frost_keys[0].clone(), [7u8; 32]);
+        let hardening_key = HardeningKey::from_bytes([9u8; 32]);
+
+        // expected values computed with an independent BIP32 implementation
+        let child = master.derive_path(None, &"m/0/5".parse().unwrap()).unwrap();
+        assert_eq!(
+            child.frost_key().public_key(),
+            "0375da53d9e554ac61db371191b518254d0060cd63e09ab0088ac3a9a4492d2a49"
+                .parse::<Point>()
+                .unwrap()
+        );
+        assert_eq!(
+            child.chain_code(),
+            crate::fun::hex::decode_array::<32>(
+                "ccfc33fc8f891b7f124810e76a26bac09ea1a16f2d04af9ade960564f0710105"
+            )
+            .unwrap()
+        );
+        let hardened_child = child
+            .derive_hardened_child(&hardening_key, HARDENED + 1)
+            .unwrap();
+        assert_eq!(
+            hardened_child.frost_key().public_key(),
+            "03261a350412a1b0134e01569890e14701d851e47921df698aec716b9af129c390"
+                .parse::<Point>()
+                .unwrap()
+        );
+        assert_eq!(
+            master.derive_path(Some(&hardening_key), &"m/0/5/1'".parse().unwrap()),
+            Ok(hardened_child.clone())
+        );
+
+        assert_eq!(
+            master.derive_child(HARDENED),
+            Err(DeriveError::HardenedIndex)
+        );
+        assert_eq!(
+            master.derive_hardened_child(&hardening_key, 0),
+            Err(DeriveError::NonHardenedIndex)
+        );
+        assert_eq!(
+            master.derive_path(None, &"m/0/0'".parse().unwrap()),
+            Err(DeriveError::MissingHardeningKey)
+        );
+
+        // signers 0 and 2 sign under the hardened child with their original shares
+        let frost_key = hardened_child.into_frost_key().into_xonly_key();
+        let message = Message::plain("test", b"derived");
+        let nonces: Vec<_> = [0usize, 2]
+            .iter()
+            .map(|&i| {
+                frost.gen_nonce(
+                    &secret_shares[i],
+                    &[b"frost-bip32-test".as_slice(), &[i as u8]].concat(),
+                    Some(frost_key.public_key()),
+                    Some(message),
+                )
+            })
+            .collect();
+        let session = frost.start_sign_session(
+            &frost_key,
+            vec![(0, nonces[0].public()), (2, nonces[1].public())],
+            message,
+        );
+        let sigs = [0usize, 2]
+            .iter()
+            .zip(nonces)
+            .map(|(&i, nonce)| {
+                let sig = frost.sign(&frost_key, &session, i as u32, &secret_shares[i], nonce);
+                assert!(frost.verify_signature_share(&frost_key, &session, i as u32, sig));
+                sig
+            })
+            .collect();
+        let signature = frost.combine_signature_shares(&frost_key, &session, sigs);
+        assert!(frost
+            .schnorr
+            .verify(&frost_key.public_key(), message, &signature));
+    }
+}
@@ -0,0 +1,57 @@
+//! HMAC-SHA256 and HKDF-SHA256 built on [`secp256kfun::hash::Hmac`].
+use crate::fun::hash::{hmac, Hmac};
+use sha2::Sha256;
+
+/// HMAC-SHA256 of the concatenation of the slices in `data`.
+pub(crate) fn hmac_sha256(key: &[u8], data: &[&[u8]]) -> [u8; 32] {
+    hmac::<Sha256>(key, data).into()
+}
+
+/// HKDF-SHA256 ([RFC 5869]) where `info` is the concatenation of the slices.
+///
+/// [RFC 5869]: https://www.rfc-editor.org/rfc/rfc5869
+pub(crate) fn hkdf_sha256<const N: usize>(salt: &[u8], ikm: &[u8], info: &[&[u8]]) -> [u8; N] {
+    let prk = hmac_sha256(salt, &[ikm]);
+    let mut okm = [0u8; N];
+    let mut previous: Option<[u8; 32]> = None;
+    for (i, chunk) in okm.chunks_mut(32).enumerate() {
+        let mut mac = Hmac::<Sha256>::new(&prk);
+        if let Some(previous) = &previous {
+            mac.update(previous);
+        }
+        for bytes in info {
+            mac.update(bytes);
+        }
+        mac.update(&[i as u8 + 1]);
+        let block = mac.finalize();
+        chunk.copy_from_slice(&block[..chunk.len()]);
+        previous = Some(block.into());
+    }
+    okm
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::hex;
+
+    #[test]
+    fn hmac_and_hkdf_test_vectors() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            hmac_sha256(b"Jefe", &[b"what do ya want ", b"for nothing?"]),
+            hex::decode_array("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843")
+                .unwrap()
+        );
+        // RFC 5869 test case 1
+        let okm = hkdf_sha256::<42>(
+            &hex::decode("000102030405060708090a0b0c").unwrap(),
+            &[0x0b; 22],
+            &[&hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap()],
+        );
+        assert_eq!(
+            hex::encode(&okm),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+    }
+}
@@ -31,11 +31,64 @@ pub mod musig;
 #[cfg(feature = "alloc")]
 pub mod frost;
 
+#[cfg(all(feature = "alloc", feature = "sha2"))]
+pub mod bip32;
+
+#[cfg(all(feature = "alloc", feature = "sha2"))]
+pub mod frost_bip32;
+
+#[cfg(feature = "alloc")]
+pub mod statechain;
+
+#[cfg(feature = "alloc")]
+pub mod escrow;
+
+#[cfg(feature = "alloc")]
+pub mod encoding;
+
+#[cfg(all(feature = "alloc", feature = "sha2"))]
+mod aead;
+#[cfg(all(feature = "alloc", feature = "sha2"))]
+pub mod ecies;
+
+#[cfg(feature = "alloc")]
+pub mod policy;
+
+#[cfg(feature = "alloc")]
+pub mod pvss;
+
+#[cfg(feature = "alloc")]
+pub mod rotation;
+
+#[cfg(all(feature = "alloc", feature = "sha2"))]
+pub mod silent_payments;
+
 mod signature;
-pub use signature::Signature;
+pub use signature::{Signature, SignatureParseError};
 pub mod adaptor;
+#[cfg(feature = "bench-helpers")]
+pub mod bench_helpers;
+#[cfg(feature = "alloc")]
+pub mod delegation;
+pub mod designated_verifier;
+#[cfg(feature = "alloc")]
+pub mod dlc;
+#[cfg(feature = "async")]
+pub mod driver;
+#[cfg(feature = "sha2")]
+mod hmac;
+#[cfg(feature = "sha2")]
+pub mod noise;
 mod schnorr;
+pub mod signer;
+pub mod timed;
 pub use schnorr::*;
+mod verifier;
+pub use verifier::*;
+#[cfg(feature = "alloc")]
+mod verification_key;
+#[cfg(feature = "alloc")]
+pub use verification_key::*;
 mod message;
 pub use message::*;
 
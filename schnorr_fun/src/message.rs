@@ -1,4 +1,10 @@
-use secp256kfun::{digest::Digest, hash::HashInto, marker::*, Slice};
+use secp256kfun::{
+    digest::{generic_array::typenum::U32, Digest},
+    hash::HashInto,
+    marker::*,
+    transcript::Transcript,
+    Slice,
+};
 
 /// A message to be signed.
 ///
@@ -44,19 +50,211 @@ impl<'a, 'b, S: Secrecy> Message<'a, S> {
             None => self.bytes.as_inner().len(),
         }
     }
+
+    /// The bytes of the message as it is hashed (the padded app tag followed by the message).
+    ///
+    /// [`Message::raw`] of these bytes hashes the same as `self` so they can be stored in place of
+    /// the message.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn to_hashed_bytes(self) -> crate::Vec<u8> {
+        let mut bytes = crate::Vec::with_capacity(self.len());
+        if let Some(prefix) = self.app_tag {
+            bytes.extend_from_slice(prefix.as_bytes());
+            bytes.resize(64, 0);
+        }
+        bytes.extend_from_slice(self.bytes.as_inner());
+        bytes
+    }
+}
+
+impl<S> Message<'_, S> {
+    /// The app tag zero padded to 64 bytes.
+    fn padded_app_tag(&self) -> Option<[u8; 64]> {
+        self.app_tag.map(|app_tag| {
+            let mut padded_app_tag = [0u8; 64];
+            padded_app_tag[..app_tag.len()].copy_from_slice(app_tag.as_bytes());
+            padded_app_tag
+        })
+    }
+
+    /// Appends the padded app tag (if any) and then the message bytes to `transcript`.
+    pub(crate) fn append_to(self, transcript: &mut impl Transcript) {
+        if let Some(padded_app_tag) = self.padded_app_tag() {
+            transcript.append_message(b"app_tag", &padded_app_tag);
+        }
+        transcript.append_message(b"m", self.bytes.as_inner());
+    }
 }
 
 impl<S> HashInto for Message<'_, S> {
     fn hash_into(self, hash: &mut impl Digest) {
-        if let Some(prefix) = self.app_tag {
-            let mut padded_prefix = [0u8; 64];
-            padded_prefix[..prefix.len()].copy_from_slice(prefix.as_bytes());
-            hash.update(padded_prefix);
+        if let Some(padded_app_tag) = self.padded_app_tag() {
+            hash.update(padded_app_tag);
         }
         hash.update(<&[u8]>::from(self.bytes));
     }
 }
 
+/// A kind of message that is always signed with the same app tag.
+///
+/// Implement this on a type for each kind of message your application signs and take
+/// [`TypedMessage`]s of that kind rather than [`Message`]s so the compiler catches you passing
+/// the wrong kind of message. Since each kind has its own app tag a signature on one kind is never
+/// valid as a signature on another.
+pub trait MessageKind {
+    /// The app tag messages of this kind are signed under. `None` means the message is signed
+    /// raw (see [`Message::raw`]).
+    const APP_TAG: Option<&'static str>;
+}
+
+/// The [BIP341] signature hash of a taproot key path spend.
+///
+/// These are signed raw (without an app tag) as required by consensus.
+///
+/// [BIP341]: https://bips.xyz/341
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bip341Sighash;
+
+impl MessageKind for Bip341Sighash {
+    const APP_TAG: Option<&'static str> = None;
+}
+
+/// A [`Message`] of a particular [`MessageKind`].
+///
+/// Convert it into a [`Message`] (with `into` or [`message`]) to sign or verify it.
+///
+/// [`message`]: Self::message
+pub struct TypedMessage<'a, K, S = Public> {
+    message: Message<'a, S>,
+    kind: core::marker::PhantomData<fn() -> K>,
+}
+
+impl<'a, K: MessageKind, S: Secrecy> TypedMessage<'a, K, S> {
+    /// Creates a message of kind `K`.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        let message = match K::APP_TAG {
+            Some(app_tag) => Message::plain(app_tag, bytes),
+            None => Message::raw(bytes),
+        };
+        TypedMessage {
+            message,
+            kind: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<'a, K, S: Copy> TypedMessage<'a, K, S> {
+    /// The underlying message.
+    pub fn message(&self) -> Message<'a, S> {
+        self.message
+    }
+}
+
+impl<K, S: Copy> Clone for TypedMessage<'_, K, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, S: Copy> Copy for TypedMessage<'_, K, S> {}
+
+impl<K, S: core::fmt::Debug> core::fmt::Debug for TypedMessage<'_, K, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("TypedMessage")
+            .field("kind", &core::any::type_name::<K>())
+            .field("message", &self.message)
+            .finish()
+    }
+}
+
+impl<K, S: PartialEq> PartialEq for TypedMessage<'_, K, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.message == other.message
+    }
+}
+
+impl<'a, K, S> From<TypedMessage<'a, K, S>> for Message<'a, S> {
+    fn from(typed: TypedMessage<'a, K, S>) -> Self {
+        typed.message
+    }
+}
+
+/// A 32-byte hash of a message to be signed with [`Schnorr::sign_prehashed`].
+///
+/// [BIP-340] signs messages of any length as they are: there's no hashing step that
+/// implementations could disagree on. Applications that sign a hash of their message instead
+/// have to make sure the signer and every verifier agree that the message was hashed and how. A
+/// `Prehashed` can only be signed and verified with [`Schnorr::sign_prehashed`] and
+/// [`Schnorr::verify_prehashed`] and the message bytes taken by [`Schnorr::sign_raw`] and
+/// [`Schnorr::verify_raw`] can't be passed where a hash is expected so one side can't hash the
+/// message while the other signs it raw without it showing up in the types.
+///
+/// The signature on a `Prehashed` is the BIP-340 signature on its 32 bytes as a raw message.
+///
+/// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+/// [`Schnorr::sign_prehashed`]: crate::Schnorr::sign_prehashed
+/// [`Schnorr::verify_prehashed`]: crate::Schnorr::verify_prehashed
+/// [`Schnorr::sign_raw`]: crate::Schnorr::sign_raw
+/// [`Schnorr::verify_raw`]: crate::Schnorr::verify_raw
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Prehashed([u8; 32]);
+
+impl Prehashed {
+    /// Wraps a message hash computed elsewhere.
+    pub fn new(hash: [u8; 32]) -> Self {
+        Prehashed(hash)
+    }
+
+    /// Finalizes `digest` to get the message hash.
+    pub fn from_digest(digest: impl Digest<OutputSize = U32>) -> Self {
+        Prehashed(digest.finalize().into())
+    }
+
+    /// The hash bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// The hash as the [`Message::raw`] that is actually signed.
+    pub fn message(&self) -> Message<'_, Public> {
+        Message::raw(&self.0[..])
+    }
+}
+
+impl From<[u8; 32]> for Prehashed {
+    fn from(hash: [u8; 32]) -> Self {
+        Prehashed::new(hash)
+    }
+}
+
+/// Feeds everything read from `reader` into `digest` and returns it.
+///
+/// This lets you hash a large message (e.g. a file) without holding all of it in memory so it can
+/// then be signed with [`Schnorr::sign_digest`].
+///
+/// # Example
+///
+/// ```
+/// use schnorr_fun::{fun::hash::Tagged, hash_reader};
+/// use sha2::Sha256;
+/// let file = std::io::Cursor::new(vec![42u8; 1_000_000]);
+/// let hash = hash_reader(Sha256::default().tagged(b"my-app/file"), file).unwrap();
+/// ```
+///
+/// [`Schnorr::sign_digest`]: crate::Schnorr::sign_digest
+#[cfg(feature = "std")]
+pub fn hash_reader<D: Digest>(mut digest: D, mut reader: impl std::io::Read) -> std::io::Result<D> {
+    let mut buf = [0u8; 4096];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(digest),
+            Ok(n) => digest.update(&buf[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -74,4 +272,29 @@ mod test {
 
         assert_eq!(hash1.finalize(), hash2.finalize());
     }
+
+    #[test]
+    fn typed_message_app_tags() {
+        struct Invoice;
+        impl MessageKind for Invoice {
+            const APP_TAG: Option<&'static str> = Some("shop/invoice");
+        }
+        let invoice = TypedMessage::<Invoice>::new(b"hello");
+        assert_eq!(
+            Message::from(invoice),
+            Message::<Public>::plain("shop/invoice", b"hello")
+        );
+        let sighash = TypedMessage::<Bip341Sighash>::new(&[42u8; 32]);
+        assert_eq!(sighash.message(), Message::<Public>::raw(&[42u8; 32]));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_reader_matches_hashing_all_at_once() {
+        let message = (0..10_000u32)
+            .map(|i| i as u8)
+            .collect::<std::vec::Vec<_>>();
+        let streamed = hash_reader(Sha256::default(), &message[..]).unwrap();
+        assert_eq!(streamed.finalize(), Sha256::digest(&message));
+    }
 }
@@ -61,7 +61,14 @@
 //! [the excellent paper]: https://eprint.iacr.org/2020/1261.pdf
 //! [secp256k1-zkp]: https://github.com/ElementsProject/secp256k1-zkp/pull/131
 pub use crate::binonce::{Nonce, NonceKeyPair};
-use crate::{adaptor::EncryptedSignature, Message, Schnorr, Signature, Vec};
+pub mod accountable;
+pub mod blame;
+pub mod nonce_pool;
+use crate::{
+    adaptor::EncryptedSignature,
+    signer::{SignRequest, Signer},
+    Message, Schnorr, Signature, Vec,
+};
 use secp256kfun::{
     digest::{generic_array::typenum::U32, Digest},
     g,
@@ -69,9 +76,12 @@ use secp256kfun::{
     marker::*,
     nonce::{self, NonceGen},
     rand_core::RngCore,
-    s, KeyPair, Point, Scalar, G,
+    s, KeyPair, Point, Scalar, XOnlyKeyPair, G,
 };
 
+/// The application tag of the message signed by a proof of possession.
+const POP_TAG: &str = "MuSig/pop";
+
 /// The MuSig context.
 pub struct MuSig<H, S = ()> {
     /// The hash used to compress the key list to 32 bytes.
@@ -119,10 +129,16 @@ impl<H: Tagged, NG> MuSig<H, Schnorr<H, NG>> {
 ///
 /// Created using [`MuSig::new_agg_key`].
 ///
-/// The `AggKey` can't be serialized but it's very efficient to re-create it from the initial list of keys.
+/// It's very efficient to re-create the `AggKey` from the initial list of keys so you don't usually
+/// need to serialize it.
 ///
 /// [`MuSig::new_agg_key`]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
 pub struct AggKey {
     /// The keys involved in the key aggregation.
     keys: Vec<Point>,
@@ -198,7 +214,12 @@ impl AggKey {
 /// A [`AggKey`] that has been converted into a [BIP340] x-only key.
 ///
 /// [BIP340]: https://bips.xyz/340
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
 pub struct XOnlyAggKey {
     /// The keys involved in the key aggregation.
     keys: Vec<Point>,
@@ -305,6 +326,81 @@ impl<H: Digest<OutputSize = U32> + Clone, S> MuSig<H, S> {
     }
 }
 
+impl<H, S> MuSig<H, S> {
+    /// Aggregates keys that have proven possession of their secret keys by simply adding them
+    /// together.
+    ///
+    /// MuSig multiplies each key by a hash of the whole key list so that nobody can choose their
+    /// key as a function of the others' to cancel them out (a "rogue key" attack). If every key
+    /// was registered ahead of time with a proof of possession (see [`create_pop`]) this isn't
+    /// possible so the coefficients can all be one. This saves hashing the key list and a scalar
+    /// multiplication per key every time a new set of keys is aggregated and the aggregate key
+    /// doesn't depend on the order of the keys. Signing works exactly the same as with
+    /// [`new_agg_key`].
+    ///
+    /// Don't mix the two modes: a key aggregated with [`new_agg_key`] is a different key.
+    ///
+    /// ## Return value
+    ///
+    /// Returns `None` if the keys add up to zero. Since everyone proved they know their secret key
+    /// this can only happen if the owners of the keys colluded to make it so.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use schnorr_fun::{fun::Scalar, musig};
+    /// let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+    /// let keypairs = (0..3)
+    ///     .map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())))
+    ///     .collect::<Vec<_>>();
+    /// // each party registers their key and proof of possession once
+    /// let registered = keypairs
+    ///     .iter()
+    ///     .map(|keypair| (keypair.public_key(), musig.create_pop(keypair)))
+    ///     .collect::<Vec<_>>();
+    /// // ...and then anyone can verify them
+    /// let verified = registered
+    ///     .iter()
+    ///     .map(|(key, pop)| musig.verify_pop(*key, pop).expect("pop is valid"))
+    ///     .collect::<Vec<_>>();
+    /// // aggregate any subset of registered keys by adding them together
+    /// let agg_key = musig
+    ///     .new_pop_agg_key(verified[..2].to_vec())
+    ///     .unwrap()
+    ///     .into_xonly_key();
+    /// ```
+    ///
+    /// [`create_pop`]: MuSig::create_pop
+    /// [`new_agg_key`]: MuSig::new_agg_key
+    pub fn new_pop_agg_key(&self, keys: Vec<PopVerifiedKey>) -> Option<AggKey> {
+        let keys = keys.into_iter().map(|key| key.0).collect::<Vec<_>>();
+        let agg_key = keys
+            .iter()
+            .fold(Point::zero().mark::<Jacobian>(), |sum, key| g!(sum + key))
+            .normalize()
+            .mark::<NonZero>()?;
+        Some(AggKey {
+            coefs: vec![Scalar::one().mark::<Public>(); keys.len()],
+            keys,
+            agg_key,
+            tweak: Scalar::zero().mark::<Public>(),
+        })
+    }
+}
+
+/// A public key whose proof of possession has been checked with [`MuSig::verify_pop`].
+///
+/// Only keys like this can be passed to [`MuSig::new_pop_agg_key`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PopVerifiedKey(Point);
+
+impl PopVerifiedKey {
+    /// The public key.
+    pub fn public_key(&self) -> Point {
+        self.0
+    }
+}
+
 impl<H: Digest<OutputSize = U32> + Clone, NG: NonceGen> MuSig<H, Schnorr<H, NG>> {
     /// Generate nonces for signing.
     ///
@@ -327,6 +423,21 @@ impl<H: Digest<OutputSize = U32> + Clone, NG: NonceGen> MuSig<H, Schnorr<H, NG>>
     pub fn nonce_gen(&self) -> &NG {
         self.schnorr.nonce_gen()
     }
+
+    /// Creates a proof of possession of the secret key of `keypair` so that its public key can be
+    /// aggregated with [`new_pop_agg_key`].
+    ///
+    /// The proof is a BIP340 signature on the (33 byte) public key with the application tag
+    /// `MuSig/pop`.
+    ///
+    /// [`new_pop_agg_key`]: MuSig::new_pop_agg_key
+    pub fn create_pop(&self, keypair: &KeyPair) -> Signature {
+        let public_key = keypair.public_key().to_bytes();
+        self.schnorr.sign(
+            &XOnlyKeyPair::from(keypair.clone()),
+            Message::<Public>::plain(POP_TAG, &public_key),
+        )
+    }
 }
 
 /// Marker type for indicating the [`SignSession`] is being used to create an ordinary Schnorr
@@ -375,6 +486,27 @@ pub struct SignSession<T = Ordinary> {
 }
 
 impl<H: Digest<OutputSize = U32> + Clone, NG> MuSig<H, Schnorr<H, NG>> {
+    /// Checks a proof of possession created with [`create_pop`].
+    ///
+    /// ## Return value
+    ///
+    /// Returns the key ready to be passed to [`new_pop_agg_key`] or `None` if the proof is invalid.
+    ///
+    /// [`create_pop`]: MuSig::create_pop
+    /// [`new_pop_agg_key`]: MuSig::new_pop_agg_key
+    pub fn verify_pop(&self, public_key: Point, pop: &Signature) -> Option<PopVerifiedKey> {
+        let bytes = public_key.to_bytes();
+        let (even_y_key, _) = public_key.into_point_with_even_y();
+        if self
+            .schnorr
+            .verify(&even_y_key, Message::<Public>::plain(POP_TAG, &bytes), pop)
+        {
+            Some(PopVerifiedKey(public_key))
+        } else {
+            None
+        }
+    }
+
     /// Start a signing session.
     ///
     /// You must provide the public nonces for this signing session in the correct order.
@@ -532,6 +664,120 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> MuSig<H, Schnorr<H, NG>> {
         s!(c * a * x_i + r1 + b * r2).mark::<(Public, Zero)>()
     }
 
+    /// Generates a partial signature (or partial encrypted signature depending on `T`) with a
+    /// [`Signer`] that holds the secret key.
+    ///
+    /// The nonce at `my_index` in the session must be the one the signer returned from
+    /// [`commit_nonces`] for `session_id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the signer's public key isn't the key at `my_index`.
+    ///
+    /// [`Signer`]: crate::signer::Signer
+    /// [`commit_nonces`]: crate::signer::Signer::commit_nonces
+    pub fn sign_with_signer<T, S: Signer>(
+        &self,
+        agg_key: &XOnlyAggKey,
+        session: &SignSession<T>,
+        my_index: usize,
+        signer: &mut S,
+        session_id: &[u8],
+    ) -> Result<Scalar<Public, Zero>, S::Error> {
+        assert_eq!(
+            signer.public_key(),
+            agg_key.keys().nth(my_index).unwrap(),
+            "key at index {} didn't match",
+            my_index
+        );
+        let mut a = agg_key.coefs[my_index];
+        a.conditional_negate(agg_key.needs_negation);
+        signer.sign(session_id, &SignRequest {
+            nonce_coefficient: session.b,
+            negate_nonce: session.nonce_needs_negation,
+            key_challenge: s!(session.c * a).mark::<Public>(),
+        })
+    }
+
+    /// Signs as the last signer with a nonce derived deterministically from the other signers'
+    /// nonces so no nonce state has to be kept between rounds.
+    ///
+    /// The secret nonce is a hash of the secret key, the sum of `other_nonces`, the aggregate key
+    /// and the message so the same inputs always give the same partial signature and any change to
+    /// them gives a different nonce. This lets a stateless signer (e.g. a hardware device) take
+    /// part safely but **only if it receives every other signer's nonce before signing**: if
+    /// someone could learn its nonce and then change theirs the protocol would be broken. `rand`
+    /// is optional extra randomness which protects against side channel attacks on the
+    /// derivation. The idea is the same as `DeterministicSign` from [BIP327] but the derivation is
+    /// not checked against its test vectors so don't expect the same nonces as other
+    /// implementations.
+    ///
+    /// `other_nonces` are the nonces of every other signer in order. Ours is inserted at
+    /// `my_index` to start the session. Returns our public nonce (which must be sent to whoever
+    /// combines the signatures) and our partial signature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `keypair`'s public key isn't the key at `my_index` or the number of nonces is
+    /// wrong.
+    ///
+    /// [BIP327]: https://github.com/bitcoin/bips/blob/master/bip-0327.mediawiki
+    pub fn deterministic_sign(
+        &self,
+        agg_key: &XOnlyAggKey,
+        my_index: usize,
+        keypair: &KeyPair,
+        other_nonces: Vec<Nonce>,
+        message: Message<'_, Public>,
+        rand: Option<[u8; 32]>,
+    ) -> (Nonce, Scalar<Public, Zero>)
+    where
+        H: Tagged,
+    {
+        assert_eq!(
+            other_nonces.len() + 1,
+            agg_key.keys().count(),
+            "wrong number of nonces"
+        );
+        let mut secret_key = keypair.secret_key().to_bytes();
+        if let Some(rand) = rand {
+            let aux = H::default().tagged(b"MuSig/aux").add(rand).finalize();
+            for (byte, aux_byte) in secret_key.iter_mut().zip(aux.iter()) {
+                *byte ^= aux_byte;
+            }
+        }
+        let agg_other_nonce =
+            other_nonces
+                .iter()
+                .fold([Point::zero().mark::<Jacobian>(); 2], |acc, nonce| {
+                    [
+                        g!({ acc[0] } + { nonce.0[0] }),
+                        g!({ acc[1] } + { nonce.0[1] }),
+                    ]
+                });
+        let agg_other_nonce = Nonce::<Zero>([
+            agg_other_nonce[0].normalize(),
+            agg_other_nonce[1].normalize(),
+        ]);
+        let nonce_hash = H::default()
+            .tagged(b"MuSig/deterministic/nonce")
+            .add(secret_key)
+            .add(agg_other_nonce.to_bytes())
+            .add(agg_key.agg_public_key())
+            .add((message.len() as u64).to_be_bytes())
+            .add(message);
+        let secret_nonce = NonceKeyPair::from_secrets(
+            [0u8, 1].map(|i| Scalar::from_hash(nonce_hash.clone().add(i))),
+        );
+        let public_nonce = secret_nonce.public();
+
+        let mut nonces = other_nonces;
+        nonces.insert(my_index, public_nonce);
+        let session = self.start_sign_session(agg_key, nonces, message);
+        let partial_sig = self.sign(agg_key, &session, my_index, keypair, secret_nonce);
+        (public_nonce, partial_sig)
+    }
+
     #[must_use]
     /// Verifies a partial signature (or partial encrypted signature depending on `T`).
     ///
@@ -601,6 +847,34 @@ impl<H: Digest<OutputSize = U32> + Clone, NG> MuSig<H, Schnorr<H, NG>> {
         }
     }
 
+    /// Combines all the partial encrypted signatures and the decryption key into a signature.
+    ///
+    /// This is for when the holder of the decryption key is taking part in the session (e.g. the
+    /// cooperative close of a PTLC channel). It gives the same signature as decrypting the result
+    /// of [`combine_partial_encrypted_signatures`] but never produces the encrypted signature.
+    ///
+    /// Note this does not check the validity of any of the partial signatures or that
+    /// `decryption_key` is the one for the session's encryption key. You should either check
+    /// each partial signature using [`verify_partial_signature`] or use [`verify`] on the
+    /// returned `Signature` to check validity.
+    ///
+    /// [`combine_partial_encrypted_signatures`]: Self::combine_partial_encrypted_signatures
+    /// [`verify_partial_signature`]: Self::verify_partial_signature
+    /// [`verify`]: crate::Schnorr::verify
+    pub fn combine_partial_encrypted_signatures_with_decryption_key(
+        &self,
+        agg_key: &XOnlyAggKey,
+        session: &SignSession<Adaptor>,
+        partial_encrypted_sigs: impl IntoIterator<Item = Scalar<Public, Zero>>,
+        decryption_key: &Scalar<impl Secrecy>,
+    ) -> Signature {
+        let (R, s_hat) = self._combine_partial_signatures(agg_key, session, partial_encrypted_sigs);
+        let mut y = decryption_key.clone();
+        y.conditional_negate(session.signing_type.y_needs_negation);
+        let s = s!(s_hat + y).mark::<Public>();
+        Signature { R, s }
+    }
+
     fn _combine_partial_signatures<T>(
         &self,
         agg_key: &XOnlyAggKey,
@@ -866,6 +1140,7 @@ mod test {
             let partial_sigs = vec![p1_sig, p2_sig, p3_sig];
             let combined_sig_p1 = musig.combine_partial_encrypted_signatures(&agg_key, &p1_session, partial_sigs.clone());
             let combined_sig_p2 = musig.combine_partial_encrypted_signatures(&agg_key2, &p2_session, partial_sigs.clone());
+            let partial_sigs_copy = partial_sigs.clone();
             let combined_sig_p3 = musig.combine_partial_encrypted_signatures(&agg_key3, &p3_session, partial_sigs);
             assert_eq!(combined_sig_p1, combined_sig_p2);
             assert_eq!(combined_sig_p1, combined_sig_p3);
@@ -878,6 +1153,161 @@ mod test {
             assert!(musig
                 .schnorr
                 .verify_encrypted_signature(&agg_key2.agg_public_key(), &encryption_key, message, &combined_sig_p3));
+
+            let signature = musig.combine_partial_encrypted_signatures_with_decryption_key(&agg_key, &p1_session, partial_sigs_copy, &y);
+            assert_eq!(signature, musig.schnorr.decrypt_signature(y, combined_sig_p1));
+            assert!(musig.schnorr.verify(&agg_key.agg_public_key(), message, &signature));
         }
     }
+
+    #[test]
+    fn deterministic_sign_as_last_signer() {
+        let musig = new_with_deterministic_nonces::<Sha256>();
+        let keypairs = (1u32..4)
+            .map(|i| musig.new_keypair(Scalar::from(i * 11).mark::<NonZero>().unwrap()))
+            .collect::<Vec<_>>();
+        let agg_key = musig
+            .new_agg_key(keypairs.iter().map(|kp| kp.public_key()).collect())
+            .into_xonly_key();
+        let message = Message::<Public>::plain("test", b"stateless");
+        let nonces = keypairs[..2]
+            .iter()
+            .map(|keypair| musig.gen_nonces(keypair.secret_key(), b"test", None::<Point>, None))
+            .collect::<Vec<_>>();
+        let other_nonces = nonces.iter().map(|nonce| nonce.public).collect::<Vec<_>>();
+
+        let (det_nonce, det_sig) = musig.deterministic_sign(
+            &agg_key,
+            2,
+            &keypairs[2],
+            other_nonces.clone(),
+            message,
+            None,
+        );
+        assert_eq!(
+            musig.deterministic_sign(
+                &agg_key,
+                2,
+                &keypairs[2],
+                other_nonces.clone(),
+                message,
+                None
+            ),
+            (det_nonce, det_sig),
+            "same inputs give the same output"
+        );
+        let (rand_nonce, _) = musig.deterministic_sign(
+            &agg_key,
+            2,
+            &keypairs[2],
+            other_nonces.clone(),
+            message,
+            Some([7u8; 32]),
+        );
+        assert_ne!(rand_nonce, det_nonce);
+        let mut changed_nonces = other_nonces.clone();
+        changed_nonces[0] = musig
+            .gen_nonces(keypairs[0].secret_key(), b"other", None::<Point>, None)
+            .public;
+        let (changed_nonce, _) =
+            musig.deterministic_sign(&agg_key, 2, &keypairs[2], changed_nonces, message, None);
+        assert_ne!(changed_nonce, det_nonce);
+
+        let mut all_nonces = other_nonces;
+        all_nonces.push(det_nonce);
+        let session = musig.start_sign_session(&agg_key, all_nonces, message);
+        assert!(musig.verify_partial_signature(&agg_key, &session, 2, det_sig));
+        let mut partial_sigs = keypairs[..2]
+            .iter()
+            .zip(nonces)
+            .enumerate()
+            .map(|(i, (keypair, nonce))| musig.sign(&agg_key, &session, i, keypair, nonce))
+            .collect::<Vec<_>>();
+        partial_sigs.push(det_sig);
+        let sig = musig.combine_partial_signatures(&agg_key, &session, partial_sigs);
+        assert!(musig
+            .schnorr
+            .verify(&agg_key.agg_public_key(), message, &sig));
+    }
+
+    #[test]
+    fn pop_agg_key_sign_verify() {
+        let musig = new_with_deterministic_nonces::<Sha256>();
+        let keypairs = (1u32..4)
+            .map(|i| musig.new_keypair(Scalar::from(i * 7).mark::<NonZero>().unwrap()))
+            .collect::<Vec<_>>();
+        let keys = keypairs
+            .iter()
+            .map(|keypair| {
+                let pop = musig.create_pop(keypair);
+                musig.verify_pop(keypair.public_key(), &pop).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let agg_key = musig.new_pop_agg_key(keys.clone()).unwrap();
+        let (X1, X2, X3) = (
+            keys[0].public_key(),
+            keys[1].public_key(),
+            keys[2].public_key(),
+        );
+        let sum = g!(X1 + X2 + X3).normalize().mark::<NonZero>().unwrap();
+        assert_eq!(agg_key.agg_key(), sum);
+        let mut reordered = keys.clone();
+        reordered.reverse();
+        assert_eq!(musig.new_pop_agg_key(reordered).unwrap().agg_key(), sum);
+
+        let agg_key = agg_key.into_xonly_key();
+        let message = Message::<Public>::plain("test", b"pop");
+        let nonces = keypairs
+            .iter()
+            .map(|keypair| {
+                musig.gen_nonces(
+                    keypair.secret_key(),
+                    b"test",
+                    Some(agg_key.agg_public_key()),
+                    Some(message),
+                )
+            })
+            .collect::<Vec<_>>();
+        let session = musig.start_sign_session(
+            &agg_key,
+            nonces.iter().map(|nonce| nonce.public).collect(),
+            message,
+        );
+        let partial_sigs = keypairs
+            .iter()
+            .zip(nonces)
+            .enumerate()
+            .map(|(i, (keypair, nonce))| {
+                let sig = musig.sign(&agg_key, &session, i, keypair, nonce);
+                assert!(musig.verify_partial_signature(&agg_key, &session, i, sig));
+                sig
+            })
+            .collect::<Vec<_>>();
+        let sig = musig.combine_partial_signatures(&agg_key, &session, partial_sigs);
+        assert!(musig
+            .schnorr
+            .verify(&agg_key.agg_public_key(), message, &sig));
+    }
+
+    #[test]
+    fn pop_rejects_rogue_keys() {
+        let musig = new_with_deterministic_nonces::<Sha256>();
+        let honest = musig.new_keypair(Scalar::from(3u32).mark::<NonZero>().unwrap());
+        let attacker = musig.new_keypair(Scalar::from(5u32).mark::<NonZero>().unwrap());
+        let honest_pop = musig.create_pop(&honest);
+
+        // a proof for one key doesn't work for another
+        assert!(musig
+            .verify_pop(attacker.public_key(), &honest_pop)
+            .is_none());
+
+        // the attacker can't prove possession of a key that cancels out the honest one
+        let (X_attacker, X_honest) = (attacker.public_key(), honest.public_key());
+        let rogue_key = g!(X_attacker - X_honest)
+            .normalize()
+            .mark::<NonZero>()
+            .unwrap();
+        let attacker_pop = musig.create_pop(&attacker);
+        assert!(musig.verify_pop(rogue_key, &attacker_pop).is_none());
+    }
 }
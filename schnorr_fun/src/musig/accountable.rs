@@ -0,0 +1,343 @@
+//! Accountable threshold signatures where the signature says which keys signed it.
+//!
+//! A [`Committee`] is a list of keys registered with proofs of possession (see
+//! [`MuSig::verify_pop`]) and a threshold. Any subset of at least `threshold` members can sign by
+//! running an ordinary MuSig session under the sum of their keys ([`Committee::signer_key`]). The
+//! resulting [`AccountableSignature`] carries the indices of the signers alongside the signature
+//! so a verifier learns exactly who signed. Since a signature is only valid under the sum of the
+//! keys of the subset that made it, nobody can claim that a different subset signed.
+//!
+//! [`Committee::encode`] writes the signer set as a bitmap with one bit per member so a signature
+//! takes `64 + ceil(n/8)` bytes.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{marker::*, Scalar},
+//!     musig::{self, accountable::Committee},
+//!     Message,
+//! };
+//! let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let keypairs = (0..3)
+//!     .map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())))
+//!     .collect::<Vec<_>>();
+//! let keys = keypairs
+//!     .iter()
+//!     .map(|keypair| {
+//!         let pop = musig.create_pop(keypair);
+//!         musig.verify_pop(keypair.public_key(), &pop).unwrap()
+//!     })
+//!     .collect();
+//! let committee = Committee::new(keys, 2).unwrap();
+//!
+//! // members 0 and 2 sign
+//! let signers = vec![0, 2];
+//! let signer_key = committee.signer_key(&musig, &signers).unwrap();
+//! let message = Message::<Public>::plain("my-app", b"proposal #42 passes");
+//! let nonces = signers
+//!     .iter()
+//!     .map(|&i| {
+//!         musig.gen_nonces(
+//!             keypairs[i as usize].secret_key(),
+//!             b"proposal-42",
+//!             Some(signer_key.agg_public_key()),
+//!             Some(message),
+//!         )
+//!     })
+//!     .collect::<Vec<_>>();
+//! let session = musig.start_sign_session(
+//!     &signer_key,
+//!     nonces.iter().map(|nonce| nonce.public).collect(),
+//!     message,
+//! );
+//! let partial_sigs = signers
+//!     .iter()
+//!     .zip(nonces)
+//!     .enumerate()
+//!     .map(|(j, (&i, nonce))| musig.sign(&signer_key, &session, j, &keypairs[i as usize], nonce))
+//!     .collect::<Vec<_>>();
+//! let signature = committee
+//!     .signature(
+//!         signers,
+//!         musig.combine_partial_signatures(&signer_key, &session, partial_sigs),
+//!     )
+//!     .unwrap();
+//! let bytes = committee.encode(&signature);
+//! let decoded = committee.decode(&bytes).unwrap();
+//! assert!(committee.verify(&musig, message, &decoded));
+//! assert_eq!(decoded.signers(), &[0, 2]);
+//! ```
+use super::{MuSig, PopVerifiedKey, XOnlyAggKey};
+use crate::{fun::marker::*, Message, Schnorr, Signature, Vec};
+use secp256kfun::digest::{generic_array::typenum::U32, Digest};
+
+/// A list of keys that have proved possession of their secret keys and the number of them needed
+/// to sign.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Committee {
+    keys: Vec<PopVerifiedKey>,
+    threshold: usize,
+}
+
+/// A signature along with the indices (into [`Committee::keys`]) of the members that made it.
+///
+/// Make one with [`Committee::signature`] or [`Committee::decode`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountableSignature {
+    signers: Vec<u32>,
+    signature: Signature,
+}
+
+impl AccountableSignature {
+    /// The indices of the signers in ascending order.
+    pub fn signers(&self) -> &[u32] {
+        &self.signers
+    }
+
+    /// The signature under the sum of the signers' keys.
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+}
+
+impl Committee {
+    /// Creates a committee where any `threshold` of `keys` can sign.
+    ///
+    /// ## Return value
+    ///
+    /// Returns `None` if `threshold` is zero or greater than the number of keys.
+    pub fn new(keys: Vec<PopVerifiedKey>, threshold: usize) -> Option<Self> {
+        if threshold == 0 || threshold > keys.len() || keys.len() > u32::MAX as usize {
+            return None;
+        }
+        Some(Committee { keys, threshold })
+    }
+
+    /// The members' keys.
+    pub fn keys(&self) -> &[PopVerifiedKey] {
+        &self.keys
+    }
+
+    /// The number of members needed to sign.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Attaches the indices of the members that made `signature`.
+    ///
+    /// ## Return value
+    ///
+    /// Returns `None` if `signers` is not strictly ascending or has an index out of range. The
+    /// signature itself is not checked.
+    pub fn signature(
+        &self,
+        signers: Vec<u32>,
+        signature: Signature,
+    ) -> Option<AccountableSignature> {
+        if !self.signers_in_range(&signers) {
+            return None;
+        }
+        Some(AccountableSignature { signers, signature })
+    }
+
+    fn signers_in_range(&self, signers: &[u32]) -> bool {
+        signers.windows(2).all(|pair| pair[0] < pair[1])
+            && signers
+                .last()
+                .map_or(true, |&last| (last as usize) < self.keys.len())
+    }
+
+    /// The key that the members at the indices `signers` sign under.
+    ///
+    /// The signers should use this to run a MuSig signing session where each signer's index in
+    /// the session is their position in `signers` (not their index in the committee).
+    ///
+    /// ## Return value
+    ///
+    /// Returns `None` if `signers` is not strictly ascending, has an index out of range or has
+    /// fewer than [`threshold`] members.
+    ///
+    /// [`threshold`]: Self::threshold
+    pub fn signer_key<H, S>(&self, musig: &MuSig<H, S>, signers: &[u32]) -> Option<XOnlyAggKey> {
+        if signers.len() < self.threshold || !self.signers_in_range(signers) {
+            return None;
+        }
+        let keys = signers
+            .iter()
+            .map(|&i| self.keys[i as usize])
+            .collect::<Vec<_>>();
+        Some(musig.new_pop_agg_key(keys)?.into_xonly_key())
+    }
+
+    /// Checks that `signature` was made on `message` by the signers it claims and that there are
+    /// enough of them.
+    #[must_use]
+    pub fn verify<H: Digest<OutputSize = U32> + Clone, NG>(
+        &self,
+        musig: &MuSig<H, Schnorr<H, NG>>,
+        message: Message<'_, impl Secrecy>,
+        signature: &AccountableSignature,
+    ) -> bool {
+        match self.signer_key(musig, &signature.signers) {
+            Some(signer_key) => {
+                musig
+                    .schnorr
+                    .verify(&signer_key.agg_public_key(), message, &signature.signature)
+            }
+            None => false,
+        }
+    }
+
+    /// Encodes `signature` as the 64 byte signature followed by a bitmap of the signers with one
+    /// bit per member (member `i` is bit `i % 8` of byte `i / 8`).
+    ///
+    /// # Panics
+    ///
+    /// If `signature` was made for a committee with more members than this one.
+    pub fn encode(&self, signature: &AccountableSignature) -> Vec<u8> {
+        let mut bytes = signature.signature.to_bytes().to_vec();
+        let mut bitmap = vec![0u8; (self.keys.len() + 7) / 8];
+        for &i in &signature.signers {
+            bitmap[i as usize / 8] |= 1 << (i % 8);
+        }
+        bytes.extend(bitmap);
+        bytes
+    }
+
+    /// Decodes a signature written with [`encode`].
+    ///
+    /// ## Return value
+    ///
+    /// Returns `None` if the length is wrong, the signature is malformed or a bit is set for a
+    /// member that doesn't exist. The signature itself is not checked.
+    ///
+    /// [`encode`]: Self::encode
+    pub fn decode(&self, bytes: &[u8]) -> Option<AccountableSignature> {
+        if bytes.len() != 64 + (self.keys.len() + 7) / 8 {
+            return None;
+        }
+        let mut signature = [0u8; 64];
+        signature.copy_from_slice(&bytes[..64]);
+        let signature = Signature::from_bytes(signature)?;
+        let bitmap = &bytes[64..];
+        let signers = (0..bitmap.len() * 8)
+            .filter(|i| bitmap[i / 8] & (1 << (i % 8)) != 0)
+            .map(|i| i as u32)
+            .collect::<Vec<_>>();
+        self.signature(signers, signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        fun::{KeyPair, Scalar},
+        musig::new_with_deterministic_nonces,
+    };
+    use sha2::Sha256;
+
+    #[test]
+    fn sign_and_verify_subsets() {
+        let musig = new_with_deterministic_nonces::<Sha256>();
+        let keypairs = (1u32..=5)
+            .map(|i| musig.new_keypair(Scalar::from(i * 11).mark::<NonZero>().unwrap()))
+            .collect::<Vec<_>>();
+        let keys = keypairs
+            .iter()
+            .map(|keypair| {
+                musig
+                    .verify_pop(keypair.public_key(), &musig.create_pop(keypair))
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let committee = Committee::new(keys, 3).unwrap();
+        let message = Message::<Public>::plain("test", b"accountable");
+
+        let sign = |signers: Vec<u32>, keypairs: &[KeyPair]| {
+            let signer_key = committee.signer_key(&musig, &signers).unwrap();
+            let nonces = signers
+                .iter()
+                .map(|&i| {
+                    musig.gen_nonces(
+                        keypairs[i as usize].secret_key(),
+                        b"test",
+                        Some(signer_key.agg_public_key()),
+                        Some(message),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let session = musig.start_sign_session(
+                &signer_key,
+                nonces.iter().map(|nonce| nonce.public).collect(),
+                message,
+            );
+            let partial_sigs = signers
+                .iter()
+                .zip(nonces)
+                .enumerate()
+                .map(|(j, (&i, nonce))| {
+                    musig.sign(&signer_key, &session, j, &keypairs[i as usize], nonce)
+                })
+                .collect::<Vec<_>>();
+            committee
+                .signature(
+                    signers,
+                    musig.combine_partial_signatures(&signer_key, &session, partial_sigs),
+                )
+                .unwrap()
+        };
+
+        let signature = sign(vec![0, 2, 4], &keypairs);
+        assert!(committee.verify(&musig, message, &signature));
+        let bytes = committee.encode(&signature);
+        assert_eq!(bytes.len(), 65);
+        assert_eq!(bytes[64], 0b10101);
+        assert_eq!(committee.decode(&bytes), Some(signature.clone()));
+
+        // claiming a different set of signers doesn't work
+        for signers in [vec![0, 2, 3], vec![0, 1, 2, 4], vec![2, 4]] {
+            let mut lying = signature.clone();
+            lying.signers = signers;
+            assert!(!committee.verify(&musig, message, &lying));
+        }
+
+        // too few signers even if they sign correctly
+        let signer_key = committee.signer_key(&musig, &[1, 3]);
+        assert!(signer_key.is_none());
+
+        let all = sign(vec![0, 1, 2, 3, 4], &keypairs);
+        assert!(committee.verify(&musig, message, &all));
+        assert_eq!(committee.encode(&all)[64], 0b11111);
+
+        // non-canonical signer lists
+        assert!(committee.signer_key(&musig, &[2, 0, 4]).is_none());
+        assert!(committee.signer_key(&musig, &[0, 2, 2, 4]).is_none());
+        assert!(committee.signer_key(&musig, &[0, 2, 5]).is_none());
+        assert!(committee
+            .signature(vec![0, 2, 5], signature.signature.clone())
+            .is_none());
+        assert!(committee
+            .signature(vec![2, 0, 4], signature.signature.clone())
+            .is_none());
+
+        // bits for members that don't exist
+        let mut bad = committee.encode(&signature);
+        bad[64] |= 0b100000;
+        assert!(committee.decode(&bad).is_none());
+        assert!(committee.decode(&bytes[..64]).is_none());
+    }
+
+    #[test]
+    fn committee_threshold_bounds() {
+        let musig = new_with_deterministic_nonces::<Sha256>();
+        let keypair = musig.new_keypair(Scalar::one());
+        let key = musig
+            .verify_pop(keypair.public_key(), &musig.create_pop(&keypair))
+            .unwrap();
+        assert!(Committee::new(vec![key], 0).is_none());
+        assert!(Committee::new(vec![key], 2).is_none());
+        assert!(Committee::new(vec![key], 1).is_some());
+    }
+}
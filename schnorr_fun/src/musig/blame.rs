@@ -0,0 +1,243 @@
+//! Evidence that a MuSig signer sent an invalid partial signature.
+//!
+//! [`MuSig::verify_partial_signature`] tells you *who* sent a bad partial signature. A
+//! [`PartialSignatureBlame`] packages up the partial signature with everything needed to check it
+//! (the aggregate key, every signer's public nonce, the message and the encryption key for
+//! encrypted sessions) so a third party can confirm the accusation with
+//! [`PartialSignatureBlame::verify`] without trusting the accuser. It is serializable so it can be
+//! passed on to whoever has to act on it.
+//!
+//! The certificate shows the partial signature is invalid in the recorded context. It can't show
+//! that the accused actually sent it: if that matters the application should have signers sign
+//! what they send (e.g. with the keys being aggregated) and keep those signatures with the
+//! certificate.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{marker::*, Scalar},
+//!     musig::{self, blame::PartialSignatureBlame},
+//!     Message,
+//! };
+//! let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let keypairs = (0..2)
+//!     .map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())))
+//!     .collect::<Vec<_>>();
+//! let agg_key = musig
+//!     .new_agg_key(keypairs.iter().map(|kp| kp.public_key()).collect())
+//!     .into_xonly_key();
+//! let message = Message::<Public>::plain("test", b"blame");
+//! let nonces = keypairs
+//!     .iter()
+//!     .map(|kp| {
+//!         musig.gen_nonces(
+//!             kp.secret_key(),
+//!             b"session-id",
+//!             Some(agg_key.agg_public_key()),
+//!             Some(message),
+//!         )
+//!     })
+//!     .collect::<Vec<_>>();
+//! let public_nonces = nonces
+//!     .iter()
+//!     .map(|nonce| nonce.public())
+//!     .collect::<Vec<_>>();
+//! let session = musig.start_sign_session(&agg_key, public_nonces.clone(), message);
+//! // signer 1 sends garbage
+//! let bad_sig = Scalar::random(&mut rand::thread_rng()).mark::<(Public, Zero)>();
+//! assert!(!musig.verify_partial_signature(&agg_key, &session, 1, bad_sig));
+//! let blame = PartialSignatureBlame::new(&agg_key, public_nonces, message, 1, bad_sig);
+//! // anyone can check it
+//! assert!(blame.verify(&musig));
+//! ```
+use super::{MuSig, XOnlyAggKey};
+use crate::{
+    binonce::Nonce,
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        marker::*,
+        Point, Scalar,
+    },
+    Message, Schnorr, Vec,
+};
+
+/// Evidence that a signer sent an invalid partial signature.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct PartialSignatureBlame {
+    /// The key being signed under.
+    pub agg_key: XOnlyAggKey,
+    /// The public nonce of every signer (in the same order as the keys).
+    pub nonces: Vec<Nonce>,
+    /// The message as it is hashed: the app tag padded to 64 bytes (if there is one) followed by
+    /// the message bytes.
+    pub message: Vec<u8>,
+    /// The encryption key if the session was started with
+    /// [`MuSig::start_encrypted_sign_session`].
+    pub encryption_key: Option<Point>,
+    /// The index of the accused signer.
+    pub accused: usize,
+    /// The accused's partial signature.
+    pub partial_signature: Scalar<Public, Zero>,
+}
+
+impl PartialSignatureBlame {
+    /// Creates the certificate against the signer at `accused` in a session started with
+    /// [`MuSig::start_sign_session`].
+    pub fn new(
+        agg_key: &XOnlyAggKey,
+        nonces: Vec<Nonce>,
+        message: Message<'_, Public>,
+        accused: usize,
+        partial_signature: Scalar<Public, Zero>,
+    ) -> Self {
+        PartialSignatureBlame {
+            agg_key: agg_key.clone(),
+            nonces,
+            message: message.to_hashed_bytes(),
+            encryption_key: None,
+            accused,
+            partial_signature,
+        }
+    }
+
+    /// Creates the certificate against the signer at `accused` in a session started with
+    /// [`MuSig::start_encrypted_sign_session`].
+    pub fn new_encrypted(
+        agg_key: &XOnlyAggKey,
+        nonces: Vec<Nonce>,
+        message: Message<'_, Public>,
+        encryption_key: Point,
+        accused: usize,
+        partial_signature: Scalar<Public, Zero>,
+    ) -> Self {
+        PartialSignatureBlame {
+            encryption_key: Some(encryption_key),
+            ..Self::new(agg_key, nonces, message, accused, partial_signature)
+        }
+    }
+
+    /// Checks that the accused's partial signature is invalid.
+    ///
+    /// Returns `false` if the partial signature is valid or the certificate is malformed.
+    #[must_use]
+    pub fn verify<H: Digest<OutputSize = U32> + Clone, NG>(
+        &self,
+        musig: &MuSig<H, Schnorr<H, NG>>,
+    ) -> bool {
+        let n_keys = self.agg_key.keys.len();
+        if self.nonces.len() != n_keys || self.accused >= n_keys {
+            return false;
+        }
+        let message = Message::raw(&self.message);
+        let valid = match &self.encryption_key {
+            None => {
+                let session = musig.start_sign_session(&self.agg_key, self.nonces.clone(), message);
+                musig.verify_partial_signature(
+                    &self.agg_key,
+                    &session,
+                    self.accused,
+                    self.partial_signature,
+                )
+            }
+            Some(encryption_key) => match musig.start_encrypted_sign_session(
+                &self.agg_key,
+                self.nonces.clone(),
+                message,
+                encryption_key,
+            ) {
+                Some(session) => musig.verify_partial_signature(
+                    &self.agg_key,
+                    &session,
+                    self.accused,
+                    self.partial_signature,
+                ),
+                None => return false,
+            },
+        };
+        !valid
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{adaptor::Adaptor, musig::new_with_deterministic_nonces};
+    use secp256kfun::s;
+    use sha2::Sha256;
+
+    #[test]
+    fn partial_signature_blame() {
+        let musig = new_with_deterministic_nonces::<Sha256>();
+        let keypairs = (0..3)
+            .map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())))
+            .collect::<Vec<_>>();
+        let agg_key = musig
+            .new_agg_key(keypairs.iter().map(|kp| kp.public_key()).collect())
+            .tweak(Scalar::from(3u32).mark::<(Public, Zero)>())
+            .unwrap()
+            .into_xonly_key();
+        let message = Message::<Public>::plain("test", b"blame");
+        let encryption_key = musig
+            .schnorr
+            .encryption_key_for(&Scalar::random(&mut rand::thread_rng()));
+        let nonces = keypairs
+            .iter()
+            .map(|kp| {
+                musig.gen_nonces(
+                    kp.secret_key(),
+                    b"blame",
+                    Some(agg_key.agg_public_key()),
+                    Some(message),
+                )
+            })
+            .collect::<Vec<_>>();
+        let public_nonces = nonces
+            .iter()
+            .map(|nonce| nonce.public())
+            .collect::<Vec<_>>();
+
+        let session = musig.start_sign_session(&agg_key, public_nonces.clone(), message);
+        let good = musig.sign(&agg_key, &session, 2, &keypairs[2], nonces[2].clone());
+        let blame = PartialSignatureBlame::new(&agg_key, public_nonces.clone(), message, 2, good);
+        assert!(!blame.verify(&musig));
+        let bad = s!(good + 1).mark::<Public>();
+        let blame = PartialSignatureBlame::new(&agg_key, public_nonces.clone(), message, 2, bad);
+        assert!(blame.verify(&musig));
+        let mut wrong = blame.clone();
+        wrong.accused = 3;
+        assert!(!wrong.verify(&musig));
+        let mut wrong = blame;
+        wrong.nonces.pop();
+        assert!(!wrong.verify(&musig));
+
+        let session = musig
+            .start_encrypted_sign_session(&agg_key, public_nonces.clone(), message, &encryption_key)
+            .unwrap();
+        let good = musig.sign(&agg_key, &session, 0, &keypairs[0], nonces[0].clone());
+        let blame = PartialSignatureBlame::new_encrypted(
+            &agg_key,
+            public_nonces.clone(),
+            message,
+            encryption_key,
+            0,
+            good,
+        );
+        assert!(!blame.verify(&musig));
+        let bad = s!(good + 1).mark::<Public>();
+        let blame = PartialSignatureBlame::new_encrypted(
+            &agg_key,
+            public_nonces,
+            message,
+            encryption_key,
+            0,
+            bad,
+        );
+        assert!(blame.verify(&musig));
+    }
+}
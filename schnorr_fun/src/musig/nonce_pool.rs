@@ -0,0 +1,324 @@
+//! Pre-generated MuSig2 nonces for signing in a single round trip.
+//!
+//! MuSig2 needs each signer's public nonce before anyone can sign. A signing service can send its
+//! nonces out ahead of time so that a signing request already contains everyone's nonces and the
+//! service can reply with its partial signature straight away. The danger is that a nonce must
+//! never be used to sign twice and a service that crashes and restarts must not hand out a nonce
+//! it already used.
+//!
+//! A [`NoncePool`] numbers every nonce it generates with a counter. Before generating a batch it
+//! durably records the counter after the end of the batch in a [`CounterStore`] so after a restart
+//! it starts from there and never hands out the same counter again. Nonces that were handed out
+//! before a restart but not used are lost: the secret parts only live in memory.
+//! [`NoncePool::take`] removes a nonce from the pool so it can only be used once.
+//!
+//! Use a separate store for each secret key and never share a store between two pools that are
+//! alive at the same time.
+//!
+//! **⚠ The counter alone can't be trusted** to keep nonces unique. Restoring a backup or a VM
+//! snapshot rolls the store back and the pool would hand out the same counters again. That's why
+//! [`NoncePool::refill`] only accepts a [`MuSig`] with [`Synthetic`] nonces: fresh randomness is
+//! mixed into every nonce so a rolled back pool still generates new ones. Signing with a nonce
+//! derived only from the secret key and the counter twice would leak the secret key.
+//!
+//! [`Synthetic`]: crate::fun::nonce::Synthetic
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{marker::*, Scalar},
+//!     musig::{
+//!         self,
+//!         nonce_pool::{CounterStore, NoncePool},
+//!     },
+//!     Message,
+//! };
+//! // a real store would write the counter to disk and fsync before returning
+//! #[derive(Default)]
+//! struct Counter(u64);
+//! impl CounterStore for Counter {
+//!     type Error = core::convert::Infallible;
+//!     fn load(&mut self) -> Result<u64, Self::Error> {
+//!         Ok(self.0)
+//!     }
+//!     fn store(&mut self, counter: u64) -> Result<(), Self::Error> {
+//!         self.0 = counter;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let musig = musig::new_with_synthetic_nonces::<sha2::Sha256, rand::rngs::ThreadRng>();
+//! let keypair = musig.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let mut pool = NoncePool::new(Counter::default()).unwrap();
+//! pool.refill(&musig, keypair.secret_key(), 100).unwrap();
+//! // publish the public nonces and their counters
+//! let (counter, my_nonce) = pool.public_nonces().next().unwrap();
+//! # let other = musig.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! # let agg_key = musig.new_agg_key(vec![keypair.public_key(), other.public_key()]).into_xonly_key();
+//! # let message = Message::<Public>::plain("test", b"hello");
+//! # let other_nonce = musig.gen_nonces(other.secret_key(), b"other", None::<schnorr_fun::fun::Point>, Some(message));
+//! // later a request arrives for `counter` with everyone's nonces
+//! let session = musig.start_sign_session(&agg_key, vec![my_nonce, other_nonce.public], message);
+//! let partial_sig = pool
+//!     .sign(&musig, &agg_key, &session, 0, &keypair, counter)
+//!     .expect("nonce hasn't been used");
+//! // the nonce is gone so the same request can't get a second signature
+//! assert!(pool
+//!     .sign(&musig, &agg_key, &session, 0, &keypair, counter)
+//!     .is_none());
+//! ```
+use super::{MuSig, SignSession, XOnlyAggKey};
+use crate::{
+    binonce::{Nonce, NonceKeyPair},
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        hash::Tagged,
+        marker::*,
+        nonce::{NonceRng, Synthetic},
+        KeyPair, Point, Scalar,
+    },
+    Schnorr,
+};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+/// Durable storage for a [`NoncePool`]'s counter.
+pub trait CounterStore {
+    /// The error returned when reading or writing fails.
+    type Error;
+
+    /// Returns the counter from the last successful [`store`] (or zero if there never was one).
+    ///
+    /// [`store`]: Self::store
+    fn load(&mut self) -> Result<u64, Self::Error>;
+
+    /// Records `counter`. This must not return `Ok` until the counter would survive a crash.
+    fn store(&mut self, counter: u64) -> Result<(), Self::Error>;
+}
+
+/// A pool of pre-generated nonces numbered by a counter.
+///
+/// See the [module documentation](crate::musig::nonce_pool).
+#[derive(Debug)]
+pub struct NoncePool<S> {
+    store: S,
+    next_counter: u64,
+    nonces: BTreeMap<u64, NonceKeyPair>,
+}
+
+impl<S: CounterStore> NoncePool<S> {
+    /// Creates an empty pool that will generate nonces from the counter in `store`.
+    pub fn new(mut store: S) -> Result<Self, S::Error> {
+        let next_counter = store.load()?;
+        Ok(NoncePool {
+            store,
+            next_counter,
+            nonces: BTreeMap::new(),
+        })
+    }
+
+    /// Generates `n` more nonces for `secret`.
+    ///
+    /// The counter after the new nonces is stored before any of them are generated. `musig` must
+    /// use [`Synthetic`] nonces so that nonces stay unique even if the store is rolled back (see
+    /// the [module documentation](crate::musig::nonce_pool)).
+    ///
+    /// # Panics
+    ///
+    /// If the counter would overflow a `u64`.
+    pub fn refill<H: Tagged + Digest<OutputSize = U32> + Clone, R: NonceRng>(
+        &mut self,
+        musig: &MuSig<H, Schnorr<H, Synthetic<H, R>>>,
+        secret: &Scalar,
+        n: u64,
+    ) -> Result<(), S::Error> {
+        let end = self
+            .next_counter
+            .checked_add(n)
+            .expect("nonce counter overflowed");
+        self.store.store(end)?;
+        for counter in self.next_counter..end {
+            let nonce = musig.gen_nonces(secret, &session_id(counter), None::<Point>, None);
+            self.nonces.insert(counter, nonce);
+        }
+        self.next_counter = end;
+        Ok(())
+    }
+
+    /// The number of unused nonces in the pool.
+    pub fn len(&self) -> usize {
+        self.nonces.len()
+    }
+
+    /// Whether the pool has run out of nonces.
+    pub fn is_empty(&self) -> bool {
+        self.nonces.is_empty()
+    }
+
+    /// The counter and public nonce of each unused nonce in the pool.
+    pub fn public_nonces(&self) -> impl Iterator<Item = (u64, Nonce)> + '_ {
+        self.nonces
+            .iter()
+            .map(|(counter, nonce)| (*counter, nonce.public()))
+    }
+
+    /// Removes the nonce for `counter` from the pool so that it can be used to sign.
+    ///
+    /// Returns `None` if there is no such nonce (it was already taken, was never generated or was
+    /// generated before a restart).
+    pub fn take(&mut self, counter: u64) -> Option<NonceKeyPair> {
+        self.nonces.remove(&counter)
+    }
+
+    /// Takes the nonce for `counter` and signs with it (see [`MuSig::sign`]).
+    ///
+    /// Returns `None` if the nonce isn't in the pool.
+    pub fn sign<H: Digest<OutputSize = U32> + Clone, NG, T>(
+        &mut self,
+        musig: &MuSig<H, Schnorr<H, NG>>,
+        agg_key: &XOnlyAggKey,
+        session: &SignSession<T>,
+        my_index: usize,
+        keypair: &KeyPair,
+        counter: u64,
+    ) -> Option<Scalar<Public, Zero>> {
+        let nonce = self.take(counter)?;
+        Some(musig.sign(agg_key, session, my_index, keypair, nonce))
+    }
+}
+
+fn session_id(counter: u64) -> [u8; 24] {
+    let mut session_id = [0u8; 24];
+    session_id[..16].copy_from_slice(b"musig/nonce-pool");
+    session_id[16..].copy_from_slice(&counter.to_be_bytes());
+    session_id
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{musig::new_with_synthetic_nonces, Message, Vec};
+    use rand::rngs::ThreadRng;
+    use sha2::Sha256;
+
+    struct FailingStore {
+        counter: u64,
+        fail: bool,
+    }
+
+    impl CounterStore for FailingStore {
+        type Error = ();
+        fn load(&mut self) -> Result<u64, ()> {
+            Ok(self.counter)
+        }
+        fn store(&mut self, counter: u64) -> Result<(), ()> {
+            if self.fail {
+                return Err(());
+            }
+            self.counter = counter;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn nonces_are_never_regenerated() {
+        let musig = new_with_synthetic_nonces::<Sha256, ThreadRng>();
+        let keypair = musig.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let mut pool = NoncePool::new(FailingStore {
+            counter: 0,
+            fail: false,
+        })
+        .unwrap();
+        pool.refill(&musig, keypair.secret_key(), 3).unwrap();
+        assert_eq!(pool.len(), 3);
+        assert_eq!(pool.store.counter, 3);
+        let before_restart = pool.public_nonces().collect::<Vec<_>>();
+        assert_eq!(
+            before_restart
+                .iter()
+                .map(|(counter, _)| *counter)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+
+        // restart
+        let mut pool = NoncePool::new(pool.store).unwrap();
+        assert!(pool.is_empty());
+        assert!(pool.take(1).is_none());
+        pool.refill(&musig, keypair.secret_key(), 2).unwrap();
+        let after_restart = pool.public_nonces().collect::<Vec<_>>();
+        assert_eq!(after_restart[0].0, 3);
+        for (_, nonce) in &after_restart {
+            assert!(before_restart.iter().all(|(_, before)| before != nonce));
+        }
+
+        // a store that gets rolled back still gives fresh nonces
+        let mut rolled_back = NoncePool::new(FailingStore {
+            counter: 0,
+            fail: false,
+        })
+        .unwrap();
+        rolled_back.refill(&musig, keypair.secret_key(), 3).unwrap();
+        for ((counter, nonce), (before_counter, before)) in
+            rolled_back.public_nonces().zip(&before_restart)
+        {
+            assert_eq!(counter, *before_counter);
+            assert_ne!(nonce, *before);
+        }
+
+        // nothing is generated if the counter can't be stored
+        pool.store.fail = true;
+        assert!(pool.refill(&musig, keypair.secret_key(), 2).is_err());
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[test]
+    fn sign_with_pooled_nonces() {
+        let musig = new_with_synthetic_nonces::<Sha256, ThreadRng>();
+        let keypairs = (0..2)
+            .map(|_| musig.new_keypair(Scalar::random(&mut rand::thread_rng())))
+            .collect::<Vec<_>>();
+        let agg_key = musig
+            .new_agg_key(keypairs.iter().map(|kp| kp.public_key()).collect())
+            .into_xonly_key();
+        let mut pools = (0..2)
+            .map(|_| {
+                NoncePool::new(FailingStore {
+                    counter: 0,
+                    fail: false,
+                })
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        for (pool, keypair) in pools.iter_mut().zip(&keypairs) {
+            pool.refill(&musig, keypair.secret_key(), 1).unwrap();
+        }
+        let nonces = pools
+            .iter()
+            .map(|pool| pool.public_nonces().next().unwrap().1)
+            .collect::<Vec<_>>();
+        let message = Message::<Public>::plain("test", b"one round trip");
+        let session = musig.start_sign_session(&agg_key, nonces, message);
+        let partial_sigs = pools
+            .iter_mut()
+            .zip(&keypairs)
+            .enumerate()
+            .map(|(i, (pool, keypair))| {
+                let sig = pool
+                    .sign(&musig, &agg_key, &session, i, keypair, 0)
+                    .unwrap();
+                assert!(pool
+                    .sign(&musig, &agg_key, &session, i, keypair, 0)
+                    .is_none());
+                sig
+            })
+            .collect::<Vec<_>>();
+        let signature = musig.combine_partial_signatures(&agg_key, &session, partial_sigs);
+        assert!(musig
+            .schnorr
+            .verify(&agg_key.agg_public_key(), message, &signature));
+    }
+}
@@ -0,0 +1,203 @@
+//! Diffie-Hellman over secp256k1 for the [Noise protocol framework].
+//!
+//! Noise handshakes are generic over a DH function. Lightning's transport ([BOLT8]) uses secp256k1
+//! with compressed 33-byte public keys and takes the SHA256 of the compressed shared point as the
+//! DH output. [`Secp256k1Dh`] is a keypair that does exactly that and [`bolt8`] has the rest of
+//! the BOLT8 handshake's key schedule.
+//!
+//! This module only provides the DH function. It doesn't implement the `Dh` trait of the [`snow`]
+//! crate and there is no `snow` feature: `snow` only uses DH functions handed out by a
+//! `CryptoResolver` so plugging secp256k1 into it is done by the application (or a crate that
+//! depends on `snow`) with its own resolver. The methods here are named after the ones in `Dh`
+//! (`name`, `pub_len`, `priv_len`, `set`, `generate`, `pubkey` and `dh`) so that resolver's `Dh`
+//! can forward to them. `privkey` can be written with [`Secp256k1Dh::keypair`] and
+//! [`KeyPair::to_secret_bytes`].
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::noise::Secp256k1Dh;
+//! let alice = Secp256k1Dh::generate(&mut rand::thread_rng());
+//! let bob = Secp256k1Dh::generate(&mut rand::thread_rng());
+//! assert_eq!(
+//!     alice.dh(bob.pubkey()).unwrap(),
+//!     bob.dh(alice.pubkey()).unwrap()
+//! );
+//! ```
+//!
+//! [Noise protocol framework]: https://noiseprotocol.org/noise.html
+//! [BOLT8]: https://github.com/lightning/bolts/blob/master/08-transport.md
+//! [`snow`]: https://docs.rs/snow
+use crate::fun::{g, rand_core::RngCore, KeyPair, Point, Scalar};
+use sha2::{Digest, Sha256};
+
+pub mod bolt8;
+
+/// The name of the DH function in Noise protocol names e.g. `Noise_XK_secp256k1_ChaChaPoly_SHA256`.
+pub const NAME: &str = "secp256k1";
+/// The length of a serialized (compressed) public key.
+pub const PUB_LEN: usize = 33;
+/// The length of a serialized secret key.
+pub const PRIV_LEN: usize = 32;
+/// The length of the DH output.
+pub const DH_LEN: usize = 32;
+
+/// The secp256k1 ECDH function from [BOLT8]: the SHA256 of the compressed encoding of
+/// `secret_key * public_key`.
+///
+/// [BOLT8]: https://github.com/lightning/bolts/blob/master/08-transport.md
+pub fn ecdh(secret_key: &Scalar, public_key: &Point) -> [u8; DH_LEN] {
+    let shared = g!(secret_key * public_key).normalize();
+    Sha256::digest(shared.to_bytes()).into()
+}
+
+/// A secp256k1 keypair that can do Noise DH.
+///
+/// See the [module documentation](crate::noise).
+#[derive(Clone, PartialEq)]
+pub struct Secp256k1Dh {
+    keypair: KeyPair,
+    public_key_bytes: [u8; PUB_LEN],
+}
+
+impl Secp256k1Dh {
+    /// Creates a DH keypair from an existing keypair.
+    pub fn new(keypair: KeyPair) -> Self {
+        Secp256k1Dh {
+            public_key_bytes: keypair.public_key().to_bytes(),
+            keypair,
+        }
+    }
+
+    /// Generates a random keypair (e.g. an ephemeral key for a handshake).
+    pub fn generate(rng: &mut impl RngCore) -> Self {
+        Self::new(KeyPair::new(Scalar::random(rng)))
+    }
+
+    /// Creates a keypair from a serialized secret key.
+    pub fn from_privkey(privkey: &[u8]) -> Result<Self, DhError> {
+        let mut bytes = [0u8; PRIV_LEN];
+        if privkey.len() != PRIV_LEN {
+            return Err(DhError::InvalidPrivateKey);
+        }
+        bytes.copy_from_slice(privkey);
+        KeyPair::from_secret_bytes(bytes)
+            .map(Self::new)
+            .ok_or(DhError::InvalidPrivateKey)
+    }
+
+    /// Replaces the keypair with the one for a serialized secret key.
+    pub fn set(&mut self, privkey: &[u8]) -> Result<(), DhError> {
+        *self = Self::from_privkey(privkey)?;
+        Ok(())
+    }
+
+    /// Always [`NAME`].
+    pub fn name(&self) -> &'static str {
+        NAME
+    }
+
+    /// Always [`PUB_LEN`].
+    pub fn pub_len(&self) -> usize {
+        PUB_LEN
+    }
+
+    /// Always [`PRIV_LEN`].
+    pub fn priv_len(&self) -> usize {
+        PRIV_LEN
+    }
+
+    /// The compressed public key.
+    pub fn pubkey(&self) -> &[u8; PUB_LEN] {
+        &self.public_key_bytes
+    }
+
+    /// The underlying keypair.
+    pub fn keypair(&self) -> &KeyPair {
+        &self.keypair
+    }
+
+    /// Does [`ecdh`] with a compressed public key.
+    pub fn dh(&self, pubkey: &[u8]) -> Result<[u8; DH_LEN], DhError> {
+        let public_key = Point::from_slice(pubkey).ok_or(DhError::InvalidPublicKey)?;
+        Ok(ecdh(self.keypair.secret_key(), &public_key))
+    }
+}
+
+impl core::fmt::Debug for Secp256k1Dh {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_tuple("Secp256k1Dh")
+            .field(&self.keypair.public_key())
+            .finish()
+    }
+}
+
+/// Why a DH operation failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DhError {
+    /// The secret key wasn't 32 bytes encoding a non-zero scalar less than the curve order.
+    InvalidPrivateKey,
+    /// The public key wasn't a valid compressed point.
+    InvalidPublicKey,
+}
+
+impl core::fmt::Display for DhError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            DhError::InvalidPrivateKey => write!(f, "invalid secp256k1 private key"),
+            DhError::InvalidPublicKey => write!(f, "invalid secp256k1 public key"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DhError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::hex;
+
+    #[test]
+    fn bolt8_ecdh() {
+        // the responder's static key and the initiator's ephemeral key from the BOLT8 test vectors
+        let rs = Secp256k1Dh::from_privkey(
+            &hex::decode("2121212121212121212121212121212121212121212121212121212121212121")
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(rs.pubkey()),
+            "028d7500dd4c12685d1f568b4c2b5048e8534b873319f3a8daa612b469132ec7f7"
+        );
+        let e = Secp256k1Dh::from_privkey(
+            &hex::decode("1212121212121212121212121212121212121212121212121212121212121212")
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(e.pubkey()),
+            "036360e856310ce5d294e8be33fc807077dc56ac80d95d9cd4ddbd21325eff73f7"
+        );
+        let ss = "1e2fb3c8fe8fb9f262f649f64d26ecf0f2c0a805a767cf02dc2d77a6ef1fdcc3";
+        assert_eq!(hex::encode(&e.dh(rs.pubkey()).unwrap()), ss);
+        assert_eq!(hex::encode(&rs.dh(e.pubkey()).unwrap()), ss);
+    }
+
+    #[test]
+    fn invalid_keys() {
+        assert_eq!(
+            Secp256k1Dh::from_privkey(&[0u8; 32]),
+            Err(DhError::InvalidPrivateKey)
+        );
+        assert_eq!(
+            Secp256k1Dh::from_privkey(&[1u8; 31]),
+            Err(DhError::InvalidPrivateKey)
+        );
+        let mut dh = Secp256k1Dh::generate(&mut rand::thread_rng());
+        assert_eq!(dh.dh(&[2u8; 32]), Err(DhError::InvalidPublicKey));
+        dh.set(&[42u8; 32]).unwrap();
+        assert_eq!(dh, Secp256k1Dh::from_privkey(&[42u8; 32]).unwrap());
+        assert_eq!(dh.set(&[0xff; 32]), Err(DhError::InvalidPrivateKey));
+    }
+}
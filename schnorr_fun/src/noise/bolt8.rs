@@ -0,0 +1,363 @@
+//! The key schedule of Lightning's [BOLT8] transport handshake.
+//!
+//! BOLT8 is the Noise `XK` handshake over secp256k1 with ChaCha20-Poly1305 and SHA256. The
+//! initiator must already know the responder's static public key. It takes three acts:
+//!
+//! 1. The initiator sends an ephemeral key and proves it knows the responder's static key (`es`).
+//! 2. The responder replies with its own ephemeral key (`ee`).
+//! 3. The initiator sends its static key encrypted and proves it owns it (`se`).
+//!
+//! [`Handshake`] tracks the chaining key `ck` and handshake hash `h` through the acts and does the
+//! ECDH and HKDF steps. It doesn't do the ChaCha20-Poly1305 encryption: each act method returns the
+//! temporary key and the caller encrypts (or decrypts) with it using [`nonce`] and [`hash`] as the
+//! associated data and then passes the ciphertext to [`mix_hash`]. After act three [`split`]
+//! returns the [`CipherKey`]s for the transport messages which take care of BOLT8's key rotation.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::noise::{
+//!     bolt8::{Handshake, Role},
+//!     Secp256k1Dh,
+//! };
+//! let mut rng = rand::thread_rng();
+//! let initiator_static = Secp256k1Dh::generate(&mut rng);
+//! let responder_static = Secp256k1Dh::generate(&mut rng);
+//! let responder_public_key = responder_static.keypair().public_key();
+//! let mut initiator = Handshake::new(&responder_public_key);
+//! let mut responder = Handshake::new(&responder_public_key);
+//!
+//! // act one
+//! let initiator_ephemeral = Secp256k1Dh::generate(&mut rng);
+//! let ie = initiator_ephemeral.keypair().public_key();
+//! let temp_k1 = initiator.act_one(&initiator_ephemeral, &responder_public_key);
+//! assert_eq!(temp_k1, responder.receive_act_one(&responder_static, &ie));
+//! // both sides would encrypt/decrypt the empty string with temp_k1 and then call mix_hash
+//! // with the 16 byte tag.
+//! # let tag = [1u8; 16];
+//! # initiator.mix_hash(&tag);
+//! # responder.mix_hash(&tag);
+//!
+//! // act two
+//! let responder_ephemeral = Secp256k1Dh::generate(&mut rng);
+//! let re = responder_ephemeral.keypair().public_key();
+//! let temp_k2 = responder.act_two(&responder_ephemeral, &ie);
+//! assert_eq!(
+//!     temp_k2,
+//!     initiator.receive_act_two(&initiator_ephemeral, &re)
+//! );
+//! # initiator.mix_hash(&tag);
+//! # responder.mix_hash(&tag);
+//!
+//! // act three (the initiator's static key is encrypted under temp_k2 with nonce 1 first)
+//! # let encrypted_static_key = [2u8; 49];
+//! # initiator.mix_hash(&encrypted_static_key);
+//! # responder.mix_hash(&encrypted_static_key);
+//! let temp_k3 = initiator.act_three(&initiator_static, &re);
+//! let initiator_public_key = initiator_static.keypair().public_key();
+//! assert_eq!(
+//!     temp_k3,
+//!     responder.receive_act_three(&responder_ephemeral, &initiator_public_key)
+//! );
+//!
+//! let initiator_keys = initiator.split(Role::Initiator);
+//! let responder_keys = responder.split(Role::Responder);
+//! assert_eq!(initiator_keys.sending, responder_keys.receiving);
+//! assert_eq!(initiator_keys.receiving, responder_keys.sending);
+//! ```
+//!
+//! [BOLT8]: https://github.com/lightning/bolts/blob/master/08-transport.md
+//! [`nonce`]: crate::noise::bolt8::nonce
+//! [`hash`]: Handshake::hash
+//! [`mix_hash`]: Handshake::mix_hash
+//! [`split`]: Handshake::split
+use super::{ecdh, Secp256k1Dh};
+use crate::{fun::Point, hmac::hkdf_sha256};
+use sha2::{Digest, Sha256};
+
+/// The Noise protocol name BOLT8 uses.
+pub const PROTOCOL_NAME: &[u8] = b"Noise_XK_secp256k1_ChaChaPoly_SHA256";
+/// The Noise prologue BOLT8 uses.
+pub const PROLOGUE: &[u8] = b"lightning";
+/// The number of nonces a transport key is used with before it is rotated (two per message).
+pub const ROTATION_INTERVAL: u64 = 1000;
+
+/// Which side of the handshake we are on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// The side that knows the other's static key in advance and sends act one.
+    Initiator,
+    /// The side that receives act one.
+    Responder,
+}
+
+/// The chaining key and handshake hash as they change through the acts.
+///
+/// See the [module documentation](crate::noise::bolt8).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Handshake {
+    ck: [u8; 32],
+    h: [u8; 32],
+}
+
+impl Handshake {
+    /// Starts the handshake state for a connection to (or for the initiator, from) the responder
+    /// with static key `responder_static`.
+    pub fn new(responder_static: &Point) -> Self {
+        let h: [u8; 32] = Sha256::digest(PROTOCOL_NAME).into();
+        let mut handshake = Handshake { ck: h, h };
+        handshake.mix_hash(PROLOGUE);
+        handshake.mix_hash(&responder_static.to_bytes());
+        handshake
+    }
+
+    /// The handshake hash `h` which is the associated data for the next encryption.
+    pub fn hash(&self) -> [u8; 32] {
+        self.h
+    }
+
+    /// The chaining key `ck`.
+    pub fn chaining_key(&self) -> [u8; 32] {
+        self.ck
+    }
+
+    /// Sets `h = SHA256(h || data)`.
+    ///
+    /// Call this with each ciphertext (including the tag) after it has been encrypted or
+    /// decrypted.
+    pub fn mix_hash(&mut self, data: &[u8]) {
+        self.h = Sha256::new()
+            .chain_update(self.h)
+            .chain_update(data)
+            .finalize()
+            .into();
+    }
+
+    /// Sets `ck, temp_k = HKDF(ck, shared_secret)` and returns `temp_k`.
+    pub fn mix_key(&mut self, shared_secret: &[u8; 32]) -> [u8; 32] {
+        let (ck, temp_k) = hkdf(&self.ck, shared_secret);
+        self.ck = ck;
+        temp_k
+    }
+
+    /// Act one for the initiator: mixes in the ephemeral key and does `es`. Returns `temp_k1`.
+    pub fn act_one(&mut self, ephemeral: &Secp256k1Dh, responder_static: &Point) -> [u8; 32] {
+        self.mix_hash(ephemeral.pubkey());
+        self.mix_key(&ecdh(ephemeral.keypair().secret_key(), responder_static))
+    }
+
+    /// Act one for the responder: mixes in the initiator's ephemeral key and does `es`. Returns
+    /// `temp_k1`.
+    pub fn receive_act_one(
+        &mut self,
+        static_key: &Secp256k1Dh,
+        initiator_ephemeral: &Point,
+    ) -> [u8; 32] {
+        self.mix_hash(&initiator_ephemeral.to_bytes());
+        self.mix_key(&ecdh(
+            static_key.keypair().secret_key(),
+            initiator_ephemeral,
+        ))
+    }
+
+    /// Act two for the responder: mixes in the ephemeral key and does `ee`. Returns `temp_k2`.
+    pub fn act_two(&mut self, ephemeral: &Secp256k1Dh, initiator_ephemeral: &Point) -> [u8; 32] {
+        self.mix_hash(ephemeral.pubkey());
+        self.mix_key(&ecdh(ephemeral.keypair().secret_key(), initiator_ephemeral))
+    }
+
+    /// Act two for the initiator: mixes in the responder's ephemeral key and does `ee`. Returns
+    /// `temp_k2`.
+    pub fn receive_act_two(
+        &mut self,
+        ephemeral: &Secp256k1Dh,
+        responder_ephemeral: &Point,
+    ) -> [u8; 32] {
+        self.mix_hash(&responder_ephemeral.to_bytes());
+        self.mix_key(&ecdh(ephemeral.keypair().secret_key(), responder_ephemeral))
+    }
+
+    /// Act three for the initiator: does `se`. Returns `temp_k3`.
+    ///
+    /// Call this after encrypting the static public key with `temp_k2` and passing it to
+    /// [`mix_hash`](Self::mix_hash).
+    pub fn act_three(&mut self, static_key: &Secp256k1Dh, responder_ephemeral: &Point) -> [u8; 32] {
+        self.mix_key(&ecdh(
+            static_key.keypair().secret_key(),
+            responder_ephemeral,
+        ))
+    }
+
+    /// Act three for the responder: does `se` with the initiator's (decrypted) static key. Returns
+    /// `temp_k3`.
+    pub fn receive_act_three(
+        &mut self,
+        ephemeral: &Secp256k1Dh,
+        initiator_static: &Point,
+    ) -> [u8; 32] {
+        self.mix_key(&ecdh(ephemeral.keypair().secret_key(), initiator_static))
+    }
+
+    /// Derives the transport keys once act three is done.
+    pub fn split(self, role: Role) -> TransportKeys {
+        let (initiator_sending, initiator_receiving) = hkdf(&self.ck, &[]);
+        let initiator_sending = CipherKey::new(self.ck, initiator_sending);
+        let initiator_receiving = CipherKey::new(self.ck, initiator_receiving);
+        match role {
+            Role::Initiator => TransportKeys {
+                sending: initiator_sending,
+                receiving: initiator_receiving,
+            },
+            Role::Responder => TransportKeys {
+                sending: initiator_receiving,
+                receiving: initiator_sending,
+            },
+        }
+    }
+}
+
+/// The keys for each direction after a handshake.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransportKeys {
+    /// The key for encrypting messages we send.
+    pub sending: CipherKey,
+    /// The key for decrypting messages we receive.
+    pub receiving: CipherKey,
+}
+
+/// A ChaCha20-Poly1305 key for one direction of a connection with its nonce counter.
+///
+/// Every message uses two nonces (one for the length and one for the body). After
+/// [`ROTATION_INTERVAL`] nonces the key is rotated with `ck, k = HKDF(ck, k)` and the nonce goes
+/// back to zero.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CipherKey {
+    ck: [u8; 32],
+    key: [u8; 32],
+    nonce: u64,
+}
+
+impl CipherKey {
+    fn new(ck: [u8; 32], key: [u8; 32]) -> Self {
+        CipherKey { ck, key, nonce: 0 }
+    }
+
+    /// The key and nonce to use for the next encryption (or decryption).
+    pub fn next_key_and_nonce(&mut self) -> ([u8; 32], [u8; 12]) {
+        let next = (self.key, nonce(self.nonce));
+        self.nonce += 1;
+        if self.nonce == ROTATION_INTERVAL {
+            let (ck, key) = hkdf(&self.ck, &self.key);
+            *self = CipherKey::new(ck, key);
+        }
+        next
+    }
+}
+
+/// The 96-bit ChaCha20-Poly1305 nonce for the counter `n`: 32 zero bits followed by `n` in little
+/// endian.
+pub fn nonce(n: u64) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&n.to_le_bytes());
+    nonce
+}
+
+/// BOLT8's `HKDF(salt, ikm)`: HKDF-SHA256 with an empty info producing two 32-byte keys.
+fn hkdf(salt: &[u8; 32], ikm: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let okm = hkdf_sha256::<64>(salt, ikm, &[]);
+    let mut first = [0u8; 32];
+    let mut second = [0u8; 32];
+    first.copy_from_slice(&okm[..32]);
+    second.copy_from_slice(&okm[32..]);
+    (first, second)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::hex;
+
+    fn dh(hex_secret: &str) -> Secp256k1Dh {
+        Secp256k1Dh::from_privkey(&hex::decode(hex_secret).unwrap()).unwrap()
+    }
+
+    // from the BOLT8 test vectors. The ciphertexts mixed into `h` come from the vectors too since
+    // this module doesn't encrypt.
+    #[test]
+    fn bolt8_key_schedule() {
+        let ls = dh("1111111111111111111111111111111111111111111111111111111111111111");
+        let rs = dh("2121212121212121212121212121212121212121212121212121212121212121");
+        let ie = dh("1212121212121212121212121212121212121212121212121212121212121212");
+        let re = dh("2222222222222222222222222222222222222222222222222222222222222222");
+        let rs_pub = rs.keypair().public_key();
+
+        let mut initiator = Handshake::new(&rs_pub);
+        assert_eq!(
+            hex::encode(&initiator.chaining_key()),
+            "2640f52eebcd9e882958951c794250eedb28002c05d7dc2ea0f195406042caf1"
+        );
+        assert_eq!(
+            hex::encode(&initiator.hash()),
+            "8401b3fdcaaa710b5405400536a3d5fd7792fe8e7fe29cd8b687216fe323ecbd"
+        );
+        let temp_k1 = initiator.act_one(&ie, &rs_pub);
+        assert_eq!(
+            hex::encode(&initiator.hash()),
+            "9e0e7de8bb75554f21db034633de04be41a2b8a18da7a319a03c803bf02b396c"
+        );
+        assert_eq!(
+            hex::encode(&initiator.chaining_key()),
+            "b61ec1191326fa240decc9564369dbb3ae2b34341d1e11ad64ed89f89180582f"
+        );
+        assert_eq!(
+            hex::encode(&temp_k1),
+            "e68f69b7f096d7917245f5e5cf8ae1595febe4d4644333c99f9c4a1282031c9f"
+        );
+
+        let temp_k2 = initiator.receive_act_two(&ie, &re.keypair().public_key());
+        assert_eq!(
+            hex::encode(&initiator.chaining_key()),
+            "e89d31033a1b6bf68c07d22e08ea4d7884646c4b60a9528598ccb4ee2c8f56ba"
+        );
+        assert_eq!(
+            hex::encode(&temp_k2),
+            "908b166535c01a935cf1e130a5fe895ab4e6f3ef8855d87e9b7581c4ab663ddc"
+        );
+
+        let temp_k3 = initiator.act_three(&ls, &re.keypair().public_key());
+        assert_eq!(
+            hex::encode(&initiator.chaining_key()),
+            "919219dbb2920afa8db80f9a51787a840bcf111ed8d588caf9ab4be716e42b01"
+        );
+        assert_eq!(
+            hex::encode(&temp_k3),
+            "981a46c820fb7a241bc8184ba4bb1f01bcdfafb00dde80098cb8c38db9141520"
+        );
+
+        let keys = initiator.split(Role::Initiator);
+        assert_eq!(
+            hex::encode(&keys.sending.key),
+            "969ab31b4d288cedf6218839b27a3e2140827047f2c0f01bf5c04435d43511a9"
+        );
+        assert_eq!(
+            hex::encode(&keys.receiving.key),
+            "bb9020b8965f4df047e07f955f3c4b88418984aadc5cdb35096b9ea8fa5c3442"
+        );
+    }
+
+    #[test]
+    fn key_rotation() {
+        let mut key = CipherKey::new([1u8; 32], [2u8; 32]);
+        let (first_key, first_nonce) = key.next_key_and_nonce();
+        assert_eq!(first_key, [2u8; 32]);
+        assert_eq!(first_nonce, [0u8; 12]);
+        for n in 1..ROTATION_INTERVAL {
+            let (k, nonce_bytes) = key.next_key_and_nonce();
+            assert_eq!(k, [2u8; 32]);
+            assert_eq!(nonce_bytes, nonce(n));
+        }
+        let (ck, rotated) = hkdf(&[1u8; 32], &[2u8; 32]);
+        assert_eq!(key.next_key_and_nonce(), (rotated, nonce(0)));
+        assert_eq!(key.ck, ck);
+    }
+}
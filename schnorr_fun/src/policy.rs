@@ -0,0 +1,465 @@
+//! Declarative "k-of-n" signing policies over x-only keys.
+//!
+//! A [`ThresholdPolicy`] says that any `k` of a set of `n` x-only public keys may sign. Wallets can
+//! store, display and exchange the policy and then compile it into key material for one of two
+//! schemes:
+//!
+//! - [`compile_musig`] produces a k-of-k [MuSig] aggregate key for each of the `n choose k`
+//!   subsets of signers. Each subset key can be put in its own taproot leaf (or the one you expect
+//!   to use most as the internal key). Signing only needs the keys the signers already have but the
+//!   number of subsets grows quickly with `n`.
+//! - [`compile_frost`] gives each signer what they need to take part in [FROST] key generation for
+//!   a single threshold key. This works for any `n` but the signers have to run key generation
+//!   before they can receive coins. Each signer's polynomial is freshly random so the resulting
+//!   key is unrelated to their policy keys.
+//!
+//! Policies are canonical: the keys are kept sorted and duplicates are rejected so that two
+//! wallets that describe the same policy always agree on its encoding (`thresh(k,<key>,...)` as a
+//! string or the binary [`Encode`] form) and on the key material compiled from it.
+//!
+//! # Example
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{Point, Scalar, XOnlyKeyPair},
+//!     musig,
+//!     policy::ThresholdPolicy,
+//! };
+//! let keypairs = (0..3)
+//!     .map(|_| XOnlyKeyPair::new(Scalar::random(&mut rand::thread_rng())))
+//!     .collect::<Vec<_>>();
+//! let policy =
+//!     ThresholdPolicy::new(2, keypairs.iter().map(|kp| kp.public_key()).collect()).unwrap();
+//! assert_eq!(
+//!     policy.to_string().parse::<ThresholdPolicy>(),
+//!     Ok(policy.clone())
+//! );
+//!
+//! let musig = musig::new_with_deterministic_nonces::<sha2::Sha256>();
+//! let subsets = policy.compile_musig(&musig);
+//! assert_eq!(subsets.len(), 3);
+//! ```
+//!
+//! [`compile_musig`]: ThresholdPolicy::compile_musig
+//! [`compile_frost`]: ThresholdPolicy::compile_frost
+//! [MuSig]: crate::musig
+//! [FROST]: crate::frost
+//! [`Encode`]: crate::encoding::Encode
+use crate::{
+    frost::ScalarPoly,
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        marker::*,
+        rand_core::RngCore,
+        Point,
+    },
+    musig::{MuSig, XOnlyAggKey},
+    Vec,
+};
+use core::fmt;
+
+/// Why a [`ThresholdPolicy`] couldn't be created or parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyError {
+    /// The threshold was zero.
+    ZeroThreshold,
+    /// The threshold was higher than the number of keys.
+    ThresholdTooHigh,
+    /// The same key appeared more than once.
+    DuplicateKey,
+    /// A key was not a valid x-only public key.
+    InvalidKey,
+    /// The string was not of the form `thresh(k,<key>,...)`.
+    InvalidSyntax,
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use PolicyError::*;
+        match self {
+            ZeroThreshold => write!(f, "policy threshold was zero"),
+            ThresholdTooHigh => write!(f, "policy threshold was higher than the number of keys"),
+            DuplicateKey => write!(f, "policy has the same key more than once"),
+            InvalidKey => write!(f, "policy has an invalid x-only key"),
+            InvalidSyntax => write!(f, "policy was not of the form thresh(k,<key>,...)"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PolicyError {}
+
+/// Any `threshold` of `keys` may sign.
+///
+/// See the [module documentation](crate::policy).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThresholdPolicy {
+    threshold: u32,
+    keys: Vec<Point<EvenY>>,
+}
+
+impl ThresholdPolicy {
+    /// Creates a policy where any `threshold` of `keys` may sign.
+    ///
+    /// The keys may be in any order. They are sorted by their x-only encoding.
+    pub fn new(threshold: u32, mut keys: Vec<Point<EvenY>>) -> Result<Self, PolicyError> {
+        if threshold == 0 {
+            return Err(PolicyError::ZeroThreshold);
+        }
+        if threshold as usize > keys.len() {
+            return Err(PolicyError::ThresholdTooHigh);
+        }
+        keys.sort_by_key(|key| key.to_xonly_bytes());
+        if keys.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(PolicyError::DuplicateKey);
+        }
+        Ok(ThresholdPolicy { threshold, keys })
+    }
+
+    /// The number of keys needed to sign.
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    /// The keys in canonical order.
+    pub fn keys(&self) -> &[Point<EvenY>] {
+        &self.keys
+    }
+
+    /// The position of `key` in [`keys`](Self::keys).
+    ///
+    /// This is the signer's index in the key material compiled from the policy.
+    pub fn index_of(&self, key: &Point<EvenY>) -> Option<usize> {
+        self.keys
+            .binary_search_by_key(&key.to_xonly_bytes(), |key| key.to_xonly_bytes())
+            .ok()
+    }
+
+    /// Every set of `threshold` signers as indices into [`keys`](Self::keys) in lexicographic
+    /// order.
+    pub fn signing_sets(&self) -> Vec<Vec<usize>> {
+        let (n, k) = (self.keys.len(), self.threshold as usize);
+        let mut sets = Vec::new();
+        let mut set = (0..k).collect::<Vec<_>>();
+        loop {
+            sets.push(set.clone());
+            // find the rightmost index that can still move right
+            let i = match (0..k).rev().find(|&i| set[i] < n - k + i) {
+                Some(i) => i,
+                None => return sets,
+            };
+            set[i] += 1;
+            for j in i + 1..k {
+                set[j] = set[j - 1] + 1;
+            }
+        }
+    }
+
+    /// Compiles the policy into a MuSig aggregate key for each of the
+    /// [`signing_sets`](Self::signing_sets).
+    ///
+    /// Signers should use [`KeyPair::from`] on their [`XOnlyKeyPair`] to sign since the keys in
+    /// the aggregate are the even-y points from the policy.
+    ///
+    /// [`KeyPair::from`]: crate::fun::KeyPair
+    /// [`XOnlyKeyPair`]: crate::fun::XOnlyKeyPair
+    pub fn compile_musig<H: Digest<OutputSize = U32> + Clone, S>(
+        &self,
+        musig: &MuSig<H, S>,
+    ) -> Vec<MusigSubset> {
+        self.signing_sets()
+            .into_iter()
+            .map(|signers| {
+                let keys = signers
+                    .iter()
+                    .map(|i| self.keys[*i].mark::<Normal>())
+                    .collect();
+                MusigSubset {
+                    agg_key: musig.new_agg_key(keys).into_xonly_key(),
+                    signers,
+                }
+            })
+            .collect()
+    }
+
+    /// Compiles the policy into what the owner of `public_key` needs to start FROST key
+    /// generation.
+    ///
+    /// Every coefficient of the scalar polynomial (including the constant term) is sampled from
+    /// `rng` so nothing about the policy key's secret ends up in the FROST key. Running this again
+    /// gives a different polynomial: keep the [`FrostSetup`] until key generation has finished.
+    /// Returns `None` if `public_key` is not in the policy.
+    pub fn compile_frost(
+        &self,
+        public_key: Point<EvenY>,
+        rng: &mut impl RngCore,
+    ) -> Option<FrostSetup> {
+        let index = self.index_of(&public_key)?;
+        let scalar_poly = ScalarPoly::random(self.threshold, rng);
+        Some(FrostSetup {
+            index: index as u32,
+            scalar_poly,
+        })
+    }
+}
+
+/// A set of signers from a [`ThresholdPolicy`] and their MuSig aggregate key.
+#[derive(Clone, Debug)]
+pub struct MusigSubset {
+    /// The signers as indices into [`ThresholdPolicy::keys`].
+    pub signers: Vec<usize>,
+    /// The aggregate key of the signers' keys in that order.
+    pub agg_key: XOnlyAggKey,
+}
+
+/// What a signer needs to take part in FROST key generation for a [`ThresholdPolicy`].
+///
+/// Send `scalar_poly.to_point_poly()` to the other signers and order everyone's point polynomials
+/// by their `index` when calling [`Frost::new_keygen`]. Use `index` as `my_index` when finishing
+/// key generation.
+#[derive(Clone, Debug)]
+pub struct FrostSetup {
+    /// The signer's index (their position in [`ThresholdPolicy::keys`]).
+    pub index: u32,
+    /// The signer's secret polynomial with `threshold` coefficients.
+    pub scalar_poly: ScalarPoly,
+}
+
+impl fmt::Display for ThresholdPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "thresh({}", self.threshold)?;
+        for key in &self.keys {
+            write!(f, ",")?;
+            for byte in key.to_xonly_bytes() {
+                write!(f, "{:02x}", byte)?;
+            }
+        }
+        write!(f, ")")
+    }
+}
+
+impl core::str::FromStr for ThresholdPolicy {
+    type Err = PolicyError;
+
+    /// Parses `thresh(k,<key>,...)` where each key is 64 hex digits. The keys may be in any order.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let inner = s
+            .strip_prefix("thresh(")
+            .and_then(|s| s.strip_suffix(')'))
+            .ok_or(PolicyError::InvalidSyntax)?;
+        let mut parts = inner.split(',');
+        let threshold = parts
+            .next()
+            .filter(|threshold| threshold.bytes().all(|c| c.is_ascii_digit()))
+            .and_then(|threshold| threshold.parse::<u32>().ok())
+            .ok_or(PolicyError::InvalidSyntax)?;
+        let keys = parts
+            .map(|key| {
+                let bytes =
+                    crate::fun::hex::decode_array(key).map_err(|_| PolicyError::InvalidKey)?;
+                Point::from_xonly_bytes(bytes).ok_or(PolicyError::InvalidKey)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        ThresholdPolicy::new(threshold, keys)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        binonce::Nonce,
+        encoding::{Decode, DecodeError, Encode},
+        frost::Frost,
+        fun::{Scalar, XOnlyKeyPair},
+        musig,
+        nonce::Deterministic,
+        Message, Schnorr,
+    };
+    use secp256kfun::KeyPair;
+    use sha2::Sha256;
+    #[cfg(feature = "std")]
+    use std::string::ToString;
+
+    fn keypairs(n: usize) -> Vec<XOnlyKeyPair> {
+        (0..n)
+            .map(|_| XOnlyKeyPair::new(Scalar::random(&mut rand::thread_rng())))
+            .collect()
+    }
+
+    fn policy(threshold: u32, keypairs: &[XOnlyKeyPair]) -> ThresholdPolicy {
+        ThresholdPolicy::new(
+            threshold,
+            keypairs.iter().map(|kp| kp.public_key()).collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn policies_are_canonical() {
+        let keypairs = keypairs(3);
+        let mut keys = keypairs
+            .iter()
+            .map(|kp| kp.public_key())
+            .collect::<Vec<_>>();
+        let policy = ThresholdPolicy::new(2, keys.clone()).unwrap();
+        keys.reverse();
+        assert_eq!(ThresholdPolicy::new(2, keys.clone()), Ok(policy.clone()));
+        assert_eq!(policy.to_string().parse(), Ok(policy.clone()));
+        assert_eq!(
+            ThresholdPolicy::decode(&policy.encode()),
+            Ok(policy.clone())
+        );
+
+        // unsorted binary encodings are rejected
+        let mut bytes = policy.encode();
+        let (a, b) = bytes[5..].split_at_mut(32);
+        a.swap_with_slice(&mut b[..32]);
+        assert_eq!(ThresholdPolicy::decode(&bytes), Err(DecodeError::Invalid));
+
+        assert_eq!(
+            ThresholdPolicy::new(0, keys.clone()),
+            Err(PolicyError::ZeroThreshold)
+        );
+        assert_eq!(
+            ThresholdPolicy::new(4, keys.clone()),
+            Err(PolicyError::ThresholdTooHigh)
+        );
+        let mut duplicated = keys.clone();
+        duplicated.push(keys[0]);
+        assert_eq!(
+            ThresholdPolicy::new(2, duplicated),
+            Err(PolicyError::DuplicateKey)
+        );
+        assert_eq!(
+            "thresh(2,zz)".parse::<ThresholdPolicy>(),
+            Err(PolicyError::InvalidKey)
+        );
+        assert_eq!(
+            "multi(2)".parse::<ThresholdPolicy>(),
+            Err(PolicyError::InvalidSyntax)
+        );
+        assert_eq!(
+            "thresh(+1,)".parse::<ThresholdPolicy>(),
+            Err(PolicyError::InvalidSyntax)
+        );
+    }
+
+    #[test]
+    fn signing_sets() {
+        let keypairs = keypairs(4);
+        assert_eq!(policy(2, &keypairs).signing_sets(), vec![
+            vec![0, 1],
+            vec![0, 2],
+            vec![0, 3],
+            vec![1, 2],
+            vec![1, 3],
+            vec![2, 3]
+        ]);
+        assert_eq!(policy(4, &keypairs).signing_sets(), vec![vec![0, 1, 2, 3]]);
+        assert_eq!(policy(1, &keypairs).signing_sets().len(), 4);
+    }
+
+    #[test]
+    fn compile_musig_and_sign() {
+        let keypairs = keypairs(3);
+        let policy = policy(2, &keypairs);
+        let musig = musig::new_with_deterministic_nonces::<Sha256>();
+        let subsets = policy.compile_musig(&musig);
+        assert_eq!(subsets.len(), 3);
+
+        let subset = &subsets[1];
+        let message = Message::<Public>::plain("test", b"policy");
+        let signers = subset
+            .signers
+            .iter()
+            .map(|i| {
+                let keypair = keypairs
+                    .iter()
+                    .find(|kp| kp.public_key() == policy.keys()[*i])
+                    .unwrap();
+                KeyPair::from(keypair.clone())
+            })
+            .collect::<Vec<_>>();
+        let nonces = signers
+            .iter()
+            .map(|kp| {
+                musig.gen_nonces(
+                    kp.secret_key(),
+                    b"session",
+                    Some(subset.agg_key.agg_public_key()),
+                    Some(message),
+                )
+            })
+            .collect::<Vec<_>>();
+        let session = musig.start_sign_session(
+            &subset.agg_key,
+            nonces
+                .iter()
+                .map(|nonce| nonce.public())
+                .collect::<Vec<Nonce>>(),
+            message,
+        );
+        let partial_signatures: Vec<_> = signers
+            .iter()
+            .zip(nonces)
+            .enumerate()
+            .map(|(i, (kp, nonce))| musig.sign(&subset.agg_key, &session, i, kp, nonce))
+            .collect();
+        let signature =
+            musig.combine_partial_signatures(&subset.agg_key, &session, partial_signatures);
+        assert!(musig
+            .schnorr
+            .verify(&subset.agg_key.agg_public_key(), message, &signature));
+    }
+
+    #[test]
+    fn compile_frost_and_keygen() {
+        let keypairs = keypairs(4);
+        let policy = policy(3, &keypairs);
+        let frost = Frost::new(Schnorr::<Sha256, _>::new(Deterministic::<Sha256>::default()));
+        let mut rng = rand::thread_rng();
+        let mut setups = keypairs
+            .iter()
+            .map(|kp| {
+                let setup = policy.compile_frost(kp.public_key(), &mut rng).unwrap();
+                assert_ne!(
+                    &setup.scalar_poly.eval_at(&Scalar::zero().mark::<Public>()),
+                    kp.secret_key()
+                );
+                setup
+            })
+            .collect::<Vec<_>>();
+        assert!(policy
+            .compile_frost(Point::random(&mut rng).into_point_with_even_y().0, &mut rng)
+            .is_none());
+        setups.sort_by_key(|setup| setup.index);
+        assert_eq!(
+            setups.iter().map(|setup| setup.index).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
+        );
+        assert!(setups.iter().all(|setup| setup.scalar_poly.poly_len() == 3));
+
+        let keygen = frost
+            .new_keygen(
+                setups
+                    .iter()
+                    .map(|setup| setup.scalar_poly.to_point_poly())
+                    .collect(),
+            )
+            .unwrap();
+        let (shares, pops): (Vec<_>, Vec<_>) = setups
+            .iter()
+            .map(|setup| frost.create_shares(&keygen, setup.scalar_poly.clone()))
+            .unzip();
+        for setup in &setups {
+            let received = shares
+                .iter()
+                .map(|shares| shares[setup.index as usize].clone())
+                .collect();
+            assert!(frost
+                .finish_keygen_to_xonly(keygen.clone(), setup.index, received, pops.clone())
+                .is_ok());
+        }
+    }
+}
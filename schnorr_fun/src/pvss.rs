@@ -0,0 +1,451 @@
+//! Publicly verifiable secret sharing (PVSS) to a committee's public keys.
+//!
+//! In [FROST] key generation each party sends a secret share to every other party over a private
+//! channel and the recipient checks it against the sender's [`PointPoly`]. If the check fails
+//! nobody else can tell whether the sender or the recipient is lying. With PVSS the dealer instead
+//! encrypts each share to its recipient's public key and attaches a proof that the ciphertext
+//! decrypts to the evaluation of the dealer's polynomial at the recipient's index. Anyone can check
+//! the proofs so the shares can be posted to a broadcast channel (e.g. a bulletin board or a
+//! blockchain) and a dealer who cheats is caught by everyone.
+//!
+//! Each of the 256 bits of the share is encrypted with exponential ElGamal and comes with an OR
+//! proof that it encrypts `0` or `1`. A final proof of equality of discrete logarithms ties the
+//! weighted sum of the bits to the share's image under the dealer's polynomial. This makes an
+//! [`EncryptedShare`] about 40KB but decryption is cheap and it needs no assumptions beyond the
+//! discrete logarithm problem on secp256k1. The proofs are made non-interactive with a
+//! [`Transcript`] tagged with `secp256kfun/pvss/challenge`.
+//!
+//! Like share indexes in [`Frost`], the member at index `i` in the committee gets the polynomial
+//! evaluated at `i + 1`.
+//!
+//! # Synopsis
+//!
+//! Key generation for a 2-of-3 FROST key where every message is broadcast:
+//!
+//! ```
+//! use schnorr_fun::{
+//!     frost::{Frost, ScalarPoly},
+//!     fun::{KeyPair, Scalar},
+//!     nonce::Deterministic,
+//!     pvss::Pvss,
+//!     Schnorr,
+//! };
+//! use sha2::Sha256;
+//! let frost = Frost::new(Schnorr::<Sha256, Deterministic<Sha256>>::new(
+//!     Deterministic::<Sha256>::default(),
+//! ));
+//! let pvss = Pvss::<Sha256>::default();
+//! let mut rng = rand::thread_rng();
+//! // every member of the committee has a long term keypair
+//! let members = (0..3)
+//!     .map(|_| KeyPair::new(Scalar::random(&mut rng)))
+//!     .collect::<Vec<_>>();
+//! let committee = members.iter().map(|kp| kp.public_key()).collect::<Vec<_>>();
+//! let scalar_polys = (0..3)
+//!     .map(|_| ScalarPoly::random(2, &mut rng))
+//!     .collect::<Vec<_>>();
+//! // round 1: everyone broadcasts their point polynomial
+//! let keygen = frost
+//!     .new_keygen(scalar_polys.iter().map(|sp| sp.to_point_poly()).collect())
+//!     .unwrap();
+//! // round 2: everyone broadcasts encrypted shares and their proof of possession
+//! let (encrypted_shares, pops): (Vec<_>, Vec<_>) = scalar_polys
+//!     .into_iter()
+//!     .map(|sp| {
+//!         let encrypted = pvss.encrypt_shares(&sp, &committee, b"dkg-42", &mut rng);
+//!         let (_, pop) = frost.create_shares(&keygen, sp);
+//!         (encrypted, pop)
+//!     })
+//!     .unzip();
+//! // anyone can check that every dealer shared correctly
+//! for (point_poly, shares) in keygen.point_polys().iter().zip(&encrypted_shares) {
+//!     assert!(pvss.verify_shares(point_poly, &committee, shares, b"dkg-42"));
+//! }
+//! // member 1 decrypts the shares sent to them and finishes key generation
+//! let my_shares = encrypted_shares
+//!     .iter()
+//!     .map(|shares| shares[1].decrypt(members[1].secret_key()).unwrap())
+//!     .collect();
+//! let (secret_share, frost_key) = frost.finish_keygen(keygen, 1, my_shares, pops).unwrap();
+//! ```
+//!
+//! [FROST]: crate::frost
+//! [`Frost`]: crate::frost::Frost
+//! [`Transcript`]: crate::fun::transcript::Transcript
+use crate::{
+    frost::{PointPoly, ScalarPoly},
+    fun::{
+        g,
+        hash::Tagged,
+        marker::*,
+        op,
+        rand_core::{CryptoRng, RngCore},
+        s,
+        subtle::{Choice, ConditionallySelectable},
+        transcript::Transcript,
+        Point, Scalar, G,
+    },
+    Vec,
+};
+
+const N_BITS: usize = 256;
+
+/// The encryption of one bit of a share and the proof that it is a `0` or a `1`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+struct EncryptedBit {
+    R: Point,
+    C: Point<Normal, Public, Zero>,
+    e0: Scalar<Public, Zero>,
+    z0: Scalar<Public, Zero>,
+    z1: Scalar<Public, Zero>,
+}
+
+/// A share encrypted to one member of the committee along with a proof that it is correct.
+///
+/// Created with [`Pvss::encrypt_shares`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct EncryptedShare {
+    bits: Vec<EncryptedBit>,
+    challenge: Scalar<Public, Zero>,
+    response: Scalar<Public, Zero>,
+}
+
+impl EncryptedShare {
+    /// Decrypts the share with the recipient's secret key.
+    ///
+    /// The bits are read back in constant time (every bit is compared against both `0` and `1`)
+    /// and the result is reduced modulo the curve order since [`Pvss::verify_share`] only checks
+    /// the weighted sum of the bits modulo the order.
+    ///
+    /// ## Return value
+    ///
+    /// Returns `None` if the ciphertext is malformed. This can't happen if it passed
+    /// [`Pvss::verify_share`] and was encrypted to the public key of `secret_key`.
+    pub fn decrypt(&self, secret_key: &Scalar) -> Option<Scalar<Secret, Zero>> {
+        if self.bits.len() != N_BITS {
+            return None;
+        }
+        let mut bytes = [0u8; 32];
+        let mut valid = true;
+        for (k, bit) in self.bits.iter().enumerate() {
+            let D = g!(bit.C - secret_key * bit.R);
+            let is_one = D == *G;
+            valid &= is_one | D.is_zero();
+            bytes[31 - k / 8] |= (is_one as u8) << (k % 8);
+        }
+        if !valid {
+            return None;
+        }
+        Some(Scalar::from_bytes_mod_order(bytes))
+    }
+}
+
+/// Encrypts and verifies shares.
+///
+/// See the [module level documentation] for usage.
+///
+/// [module level documentation]: crate::pvss
+#[derive(Clone, Debug)]
+pub struct Pvss<CH> {
+    challenge_hash: CH,
+}
+
+impl<CH: Transcript + Tagged> Default for Pvss<CH> {
+    fn default() -> Self {
+        Self {
+            challenge_hash: CH::default().tagged(b"secp256kfun/pvss/challenge"),
+        }
+    }
+}
+
+impl<CH: Transcript> Pvss<CH> {
+    /// Encrypts the evaluation of `scalar_poly` for each member of the committee to their public
+    /// key.
+    ///
+    /// `context` should identify the key generation session so the proofs can't be replayed in
+    /// another one.
+    ///
+    /// ## Return value
+    ///
+    /// The encrypted share at index `i` is for `recipients[i]`.
+    pub fn encrypt_shares<R: RngCore + CryptoRng>(
+        &self,
+        scalar_poly: &ScalarPoly,
+        recipients: &[Point],
+        context: &[u8],
+        rng: &mut R,
+    ) -> Vec<EncryptedShare> {
+        recipients
+            .iter()
+            .enumerate()
+            .map(|(i, recipient)| {
+                self.encrypt_share(&scalar_poly.eval(i as u32 + 1), recipient, context, rng)
+            })
+            .collect()
+    }
+
+    fn encrypt_share<R: RngCore + CryptoRng>(
+        &self,
+        share: &Scalar<Secret, Zero>,
+        recipient: &Point,
+        context: &[u8],
+        rng: &mut R,
+    ) -> EncryptedShare {
+        let X = g!(share * G).normalize();
+        self.encrypt_share_bytes(share.to_bytes(), X, recipient, context, rng)
+    }
+
+    /// Encrypts the bits of `share_bytes` where `X` is the image of the share they encode.
+    fn encrypt_share_bytes<R: RngCore + CryptoRng>(
+        &self,
+        share_bytes: [u8; 32],
+        X: Point<Normal, Public, Zero>,
+        recipient: &Point,
+        context: &[u8],
+        rng: &mut R,
+    ) -> EncryptedShare {
+        let Y = recipient;
+        let mut bits = Vec::with_capacity(N_BITS);
+        let mut commitments = Vec::with_capacity(4 * N_BITS + 2);
+        // (bit, r, w, e_fake, z_fake) for each bit
+        let mut secrets = Vec::with_capacity(N_BITS);
+        let mut r_sum = s!(0);
+        let mut pow = s!(1).mark::<Public>();
+        for k in 0..N_BITS {
+            let bit = Choice::from((share_bytes[31 - k / 8] >> (k % 8)) & 1);
+            let b = Scalar::conditional_select(&s!(0), &s!(1).mark::<Zero>(), bit.into());
+            let r = Scalar::random(rng);
+            let R = g!(r * G).normalize();
+            let C = g!(b * G + r * Y).normalize();
+            // prove we know the randomness for C if it encrypts the bit and simulate the proof
+            // for the other one
+            let w = Scalar::random(rng);
+            let e_fake = Scalar::random(rng).mark::<(Public, Zero)>();
+            let z_fake = Scalar::random(rng).mark::<(Public, Zero)>();
+            let C_minus_G = g!(C - G).normalize();
+            let fake_target = Point::conditional_select(&C_minus_G, &C, bit);
+            let A_real = g!(w * G).normalize().mark::<Zero>();
+            let B_real = g!(w * Y).normalize().mark::<Zero>();
+            let A_fake = g!(z_fake * G - e_fake * R).normalize();
+            let B_fake = g!(z_fake * Y - e_fake * fake_target).normalize();
+            commitments.extend([
+                Point::conditional_select(&A_real, &A_fake, bit),
+                Point::conditional_select(&B_real, &B_fake, bit),
+                Point::conditional_select(&A_fake, &A_real, bit),
+                Point::conditional_select(&B_fake, &B_real, bit),
+            ]);
+            r_sum = s!(r_sum + pow * r);
+            pow = s!(pow + pow).mark::<(Public, NonZero)>().expect("2^k < n");
+            bits.push((R, C));
+            secrets.push((bit, r, w, e_fake, z_fake));
+        }
+        let a = Scalar::random(rng);
+        commitments.push(g!(a * G).normalize().mark::<Zero>());
+        commitments.push(g!(a * Y).normalize().mark::<Zero>());
+
+        let e = self.challenge(context, Y, &X, &bits, &commitments);
+        let bits = bits
+            .into_iter()
+            .zip(secrets)
+            .map(|((R, C), (bit, r, w, e_fake, z_fake))| {
+                let e_real = s!(e - e_fake).mark::<Public>();
+                let z_real = s!(w + e_real * r).mark::<Public>();
+                let bit = bool::from(bit);
+                EncryptedBit {
+                    R,
+                    C,
+                    e0: Scalar::conditional_select(&e_real, &e_fake, bit),
+                    z0: Scalar::conditional_select(&z_real, &z_fake, bit),
+                    z1: Scalar::conditional_select(&z_fake, &z_real, bit),
+                }
+            })
+            .collect();
+
+        EncryptedShare {
+            bits,
+            challenge: e,
+            response: s!(a + e * r_sum).mark::<Public>(),
+        }
+    }
+
+    /// Checks that `encrypted_share` encrypts the evaluation of the polynomial committed to by
+    /// `point_poly` for the member at `index` whose public key is `recipient`.
+    #[must_use]
+    pub fn verify_share(
+        &self,
+        point_poly: &PointPoly,
+        index: u32,
+        recipient: &Point,
+        encrypted_share: &EncryptedShare,
+        context: &[u8],
+    ) -> bool {
+        let EncryptedShare {
+            bits,
+            challenge: e,
+            response: z,
+        } = encrypted_share;
+        if bits.len() != N_BITS {
+            return false;
+        }
+        let Y = recipient;
+        let X = point_poly.eval(index + 1).normalize();
+        let mut commitments = Vec::with_capacity(4 * N_BITS + 2);
+        let mut pows = Vec::with_capacity(N_BITS);
+        let mut pow = s!(1).mark::<Public>();
+        for bit in bits {
+            let EncryptedBit { R, C, e0, z0, z1 } = bit;
+            let e1 = s!(e - e0).mark::<Public>();
+            commitments.extend([
+                g!(z0 * G - e0 * R).normalize(),
+                g!(z0 * Y - e0 * C).normalize(),
+                g!(z1 * G - e1 * R).normalize(),
+                g!(z1 * Y - e1 * (C - G)).normalize(),
+            ]);
+            pows.push(pow);
+            pow = s!(pow + pow).mark::<(Public, NonZero)>().expect("2^k < n");
+        }
+        let R_sum = op::lincomb(&pows, bits.iter().map(|bit| &bit.R));
+        let C_sum = g!({ op::lincomb(&pows, bits.iter().map(|bit| &bit.C)) } - X);
+        commitments.push(g!(z * G - e * R_sum).normalize());
+        commitments.push(g!(z * Y - e * C_sum).normalize());
+
+        let bits = bits.iter().map(|bit| (bit.R, bit.C)).collect::<Vec<_>>();
+        self.challenge(context, Y, &X, &bits, &commitments) == *e
+    }
+
+    /// Checks every share the dealer of `point_poly` encrypted to `recipients` with
+    /// [`encrypt_shares`](Self::encrypt_shares).
+    #[must_use]
+    pub fn verify_shares(
+        &self,
+        point_poly: &PointPoly,
+        recipients: &[Point],
+        encrypted_shares: &[EncryptedShare],
+        context: &[u8],
+    ) -> bool {
+        recipients.len() == encrypted_shares.len()
+            && recipients
+                .iter()
+                .zip(encrypted_shares)
+                .enumerate()
+                .all(|(i, (recipient, share))| {
+                    self.verify_share(point_poly, i as u32, recipient, share, context)
+                })
+    }
+
+    fn challenge(
+        &self,
+        context: &[u8],
+        Y: &Point,
+        X: &Point<Normal, Public, Zero>,
+        bits: &[(Point, Point<Normal, Public, Zero>)],
+        commitments: &[Point<Normal, Public, Zero>],
+    ) -> Scalar<Public, Zero> {
+        let mut transcript = self.challenge_hash.clone();
+        transcript.append_message(b"context-len", &(context.len() as u64).to_be_bytes());
+        transcript.append_message(b"context", context);
+        transcript.append_message(b"Y", &Y.to_bytes());
+        transcript.append_message(b"X", &X.to_bytes());
+        for (R, C) in bits {
+            transcript.append_message(b"R", &R.to_bytes());
+            transcript.append_message(b"C", &C.to_bytes());
+        }
+        for commitment in commitments {
+            transcript.append_message(b"A", &commitment.to_bytes());
+        }
+        transcript.challenge_scalar::<Public>(b"e")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::KeyPair;
+    use sha2::Sha256;
+
+    #[test]
+    fn encrypted_shares_verify_and_decrypt() {
+        let pvss = Pvss::<Sha256>::default();
+        let mut rng = rand::thread_rng();
+        let members = (0..2)
+            .map(|_| KeyPair::new(Scalar::random(&mut rng)))
+            .collect::<Vec<_>>();
+        let committee = members.iter().map(|kp| kp.public_key()).collect::<Vec<_>>();
+        let scalar_poly = ScalarPoly::random(2, &mut rng);
+        let point_poly = scalar_poly.to_point_poly();
+        let shares = pvss.encrypt_shares(&scalar_poly, &committee, b"test", &mut rng);
+
+        assert!(pvss.verify_shares(&point_poly, &committee, &shares, b"test"));
+        for (i, member) in members.iter().enumerate() {
+            assert_eq!(
+                shares[i].decrypt(member.secret_key()).unwrap(),
+                scalar_poly.eval(i as u32 + 1)
+            );
+        }
+        // the wrong key gets garbage
+        assert_ne!(
+            shares[0].decrypt(members[1].secret_key()),
+            Some(scalar_poly.eval(1))
+        );
+
+        assert!(!pvss.verify_shares(&point_poly, &committee, &shares, b"other-session"));
+        assert!(!pvss.verify_share(&point_poly, 1, &committee[1], &shares[0], b"test"));
+        assert!(!pvss.verify_share(&point_poly, 0, &committee[1], &shares[0], b"test"));
+        let other_poly = ScalarPoly::random(2, &mut rng).to_point_poly();
+        assert!(!pvss.verify_shares(&other_poly, &committee, &shares, b"test"));
+
+        // flipping a bit in the ciphertext is caught
+        let mut tampered = shares[0].clone();
+        tampered.bits[3].C = g!({ tampered.bits[3].C } + G).normalize();
+        assert!(!pvss.verify_share(&point_poly, 0, &committee[0], &tampered, b"test"));
+    }
+
+    #[test]
+    fn dealer_cannot_encrypt_the_wrong_share() {
+        let pvss = Pvss::<Sha256>::default();
+        let mut rng = rand::thread_rng();
+        let recipient = KeyPair::new(Scalar::random(&mut rng));
+        let scalar_poly = ScalarPoly::random(2, &mut rng);
+        let point_poly = scalar_poly.to_point_poly();
+        let wrong_share = scalar_poly.eval(2);
+        let encrypted =
+            pvss.encrypt_share(&wrong_share, &recipient.public_key(), b"test", &mut rng);
+        assert!(!pvss.verify_share(&point_poly, 0, &recipient.public_key(), &encrypted, b"test"));
+        assert!(pvss.verify_share(&point_poly, 1, &recipient.public_key(), &encrypted, b"test"));
+    }
+
+    #[test]
+    fn unreduced_shares_still_decrypt() {
+        // a dealer can encode f(i) + n instead of f(i) when f(i) is small and it still verifies
+        let pvss = Pvss::<Sha256>::default();
+        let mut rng = rand::thread_rng();
+        let recipient = KeyPair::new(Scalar::random(&mut rng));
+        let four = Scalar::from(4u32).non_zero().unwrap();
+        let scalar_poly = ScalarPoly::new(vec![four, s!(1)]);
+        let point_poly = scalar_poly.to_point_poly();
+        let share = scalar_poly.eval(1);
+        let share_plus_n = crate::fun::hex::decode_array(
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364146",
+        )
+        .unwrap();
+        let encrypted = pvss.encrypt_share_bytes(
+            share_plus_n,
+            g!(share * G).normalize(),
+            &recipient.public_key(),
+            b"test",
+            &mut rng,
+        );
+        assert!(pvss.verify_share(&point_poly, 0, &recipient.public_key(), &encrypted, b"test"));
+        assert_eq!(encrypted.decrypt(recipient.secret_key()), Some(share));
+    }
+}
@@ -0,0 +1,309 @@
+//! Rotating an identity key to a new one with signatures from both.
+//!
+//! A [`Rotation`] is a statement that says the `old` key is replaced by the `new` key from
+//! `effective_at` and that the `old` key stays valid until `overlap_until` so that messages signed
+//! just before the rotation can still be accepted. The statement is signed by **both** keys: the
+//! old key's signature says the new key is its successor and the new key's signature says the new
+//! key's owner accepts being the successor of the old key (so nobody can claim someone else's key
+//! as their own new key).
+//!
+//! The statement is serialized canonically as
+//!
+//! ```text
+//! old (32 byte x-only) || new (32 byte x-only) || effective_at (8 byte big-endian) || overlap_until (8 byte big-endian)
+//! ```
+//!
+//! and signed as a [`Message::plain`] with the app tag `"schnorr-fun/rotation"`. Like
+//! [`delegation`](crate::delegation) the unit of time is up to the application.
+//!
+//! Rotations form a chain from the first key a service ever had to its current key.
+//! [`Schnorr::verify_rotation_chain`] checks a chain and returns the current key while
+//! [`Schnorr::valid_keys_at`] tells you which keys should be accepted at a particular time.
+//!
+//! # Synopsis
+//!
+//! ```
+//! use schnorr_fun::fun::Scalar;
+//! # let schnorr = schnorr_fun::test_instance!();
+//! let key_2021 = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let key_2022 = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let key_2023 = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let day = 24 * 3600;
+//! let chain = vec![
+//!     schnorr.rotate(&key_2021, &key_2022, 1_640_995_200, 1_640_995_200 + 7 * day),
+//!     schnorr.rotate(&key_2022, &key_2023, 1_672_531_200, 1_672_531_200 + 7 * day),
+//! ];
+//! assert_eq!(
+//!     schnorr.verify_rotation_chain(&key_2021.public_key(), &chain),
+//!     Ok(key_2023.public_key())
+//! );
+//! // a few days after the second rotation both 2022 and 2023 keys are accepted
+//! assert_eq!(
+//!     schnorr.valid_keys_at(&key_2021.public_key(), &chain, 1_672_531_200 + 3 * day),
+//!     Ok(vec![key_2022.public_key(), key_2023.public_key()])
+//! );
+//! ```
+use crate::{
+    fun::{marker::*, nonce::NonceGen, transcript::Transcript, Point, XOnlyKeyPair},
+    Message, Schnorr, Signature, Vec,
+};
+
+/// The app tag rotation statements are signed under.
+const APP_TAG: &str = "schnorr-fun/rotation";
+
+/// A statement signed by both `old` and `new` that `new` replaces `old`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct Rotation {
+    /// The key being replaced.
+    pub old: Point<EvenY>,
+    /// The key replacing it.
+    pub new: Point<EvenY>,
+    /// When `new` becomes valid.
+    pub effective_at: u64,
+    /// `old` is valid while `now < overlap_until`.
+    pub overlap_until: u64,
+    /// The old key's signature on the statement.
+    pub old_signature: Signature,
+    /// The new key's signature on the statement.
+    pub new_signature: Signature,
+}
+
+impl Rotation {
+    /// The canonical serialization of the statement the signatures are on.
+    pub fn statement(&self) -> [u8; 80] {
+        statement(&self.old, &self.new, self.effective_at, self.overlap_until)
+    }
+}
+
+fn statement(
+    old: &Point<EvenY>,
+    new: &Point<EvenY>,
+    effective_at: u64,
+    overlap_until: u64,
+) -> [u8; 80] {
+    let mut bytes = [0u8; 80];
+    bytes[..32].copy_from_slice(&old.to_xonly_bytes());
+    bytes[32..64].copy_from_slice(&new.to_xonly_bytes());
+    bytes[64..72].copy_from_slice(&effective_at.to_be_bytes());
+    bytes[72..].copy_from_slice(&overlap_until.to_be_bytes());
+    bytes
+}
+
+/// Why a rotation chain was rejected. Each variant has the position of the offending rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationError {
+    /// The rotation's old key is not the previous rotation's new key (or the root key).
+    BrokenLink(usize),
+    /// One of the rotation's signatures is invalid.
+    InvalidSignature(usize),
+    /// The rotation's `overlap_until` is before its `effective_at`.
+    InvalidWindow(usize),
+    /// The rotation takes effect before the previous one's overlap ends.
+    OutOfOrder(usize),
+}
+
+impl core::fmt::Display for RotationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            RotationError::BrokenLink(i) => write!(
+                f,
+                "rotation {} is not from the new key of the one before it",
+                i
+            ),
+            RotationError::InvalidSignature(i) => {
+                write!(f, "rotation {} has an invalid signature", i)
+            }
+            RotationError::InvalidWindow(i) => {
+                write!(f, "rotation {} has its overlap end before it starts", i)
+            }
+            RotationError::OutOfOrder(i) => write!(
+                f,
+                "rotation {} takes effect before the one before it has finished",
+                i
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RotationError {}
+
+impl<CH: Transcript, NG: NonceGen> Schnorr<CH, NG> {
+    /// Creates a rotation from `old` to `new` effective from `effective_at` with `old` remaining
+    /// valid until `overlap_until`.
+    ///
+    /// `overlap_until` should be no earlier than `effective_at` (if they are equal there is no
+    /// overlap).
+    ///
+    /// See the [module documentation] for an example.
+    ///
+    /// [module documentation]: crate::rotation
+    pub fn rotate(
+        &self,
+        old: &XOnlyKeyPair,
+        new: &XOnlyKeyPair,
+        effective_at: u64,
+        overlap_until: u64,
+    ) -> Rotation {
+        let (old_key, new_key) = (old.public_key(), new.public_key());
+        let statement = statement(&old_key, &new_key, effective_at, overlap_until);
+        let message = Message::<Public>::plain(APP_TAG, &statement);
+        Rotation {
+            old: old_key,
+            new: new_key,
+            effective_at,
+            overlap_until,
+            old_signature: self.sign(old, message),
+            new_signature: self.sign(new, message),
+        }
+    }
+}
+
+impl<CH: Transcript, NG> Schnorr<CH, NG> {
+    /// Checks both signatures on `rotation`.
+    #[must_use]
+    pub fn verify_rotation(&self, rotation: &Rotation) -> bool {
+        let statement = rotation.statement();
+        let message = Message::<Public>::plain(APP_TAG, &statement);
+        self.verify(&rotation.old, message, &rotation.old_signature)
+            && self.verify(&rotation.new, message, &rotation.new_signature)
+    }
+
+    /// Verifies a chain of rotations starting from `root` and returns the key at the end of the
+    /// chain.
+    ///
+    /// The first rotation must be from `root` and each rotation after that must be from the new
+    /// key of the one before it. Each rotation must take effect no earlier than the end of the
+    /// previous rotation's overlap. An empty chain returns `root`.
+    pub fn verify_rotation_chain(
+        &self,
+        root: &Point<EvenY>,
+        chain: &[Rotation],
+    ) -> Result<Point<EvenY>, RotationError> {
+        let mut current = *root;
+        let mut previous_end = 0;
+        for (i, rotation) in chain.iter().enumerate() {
+            if rotation.old != current {
+                return Err(RotationError::BrokenLink(i));
+            }
+            if rotation.overlap_until < rotation.effective_at {
+                return Err(RotationError::InvalidWindow(i));
+            }
+            if rotation.effective_at < previous_end {
+                return Err(RotationError::OutOfOrder(i));
+            }
+            if !self.verify_rotation(rotation) {
+                return Err(RotationError::InvalidSignature(i));
+            }
+            current = rotation.new;
+            previous_end = rotation.overlap_until;
+        }
+        Ok(current)
+    }
+
+    /// Verifies a chain of rotations (see [`verify_rotation_chain`]) and returns the keys that
+    /// are valid at `now`, oldest first.
+    ///
+    /// The root key is valid until the first rotation's `overlap_until` and each new key is valid
+    /// from its rotation's `effective_at` until the next rotation's `overlap_until` (the current
+    /// key never stops being valid). There are two keys during an overlap and one otherwise.
+    ///
+    /// [`verify_rotation_chain`]: Self::verify_rotation_chain
+    pub fn valid_keys_at(
+        &self,
+        root: &Point<EvenY>,
+        chain: &[Rotation],
+        now: u64,
+    ) -> Result<Vec<Point<EvenY>>, RotationError> {
+        self.verify_rotation_chain(root, chain)?;
+        let mut valid_from = 0;
+        let mut keys = Vec::new();
+        for (key, rotation) in core::iter::once(root)
+            .chain(chain.iter().map(|rotation| &rotation.new))
+            .zip(chain.iter().map(Some).chain(core::iter::once(None)))
+        {
+            let valid_until = rotation.map_or(u64::MAX, |rotation| rotation.overlap_until);
+            if valid_from <= now && (now < valid_until || rotation.is_none()) {
+                keys.push(*key);
+            }
+            if let Some(rotation) = rotation {
+                valid_from = rotation.effective_at;
+            }
+        }
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fun::Scalar;
+
+    #[test]
+    fn rotation_chain() {
+        let schnorr = crate::test_instance!();
+        let keys = (0..3)
+            .map(|_| schnorr.new_keypair(Scalar::random(&mut rand::thread_rng())))
+            .collect::<Vec<_>>();
+        let root = keys[0].public_key();
+        let chain = vec![
+            schnorr.rotate(&keys[0], &keys[1], 100, 110),
+            schnorr.rotate(&keys[1], &keys[2], 200, 200),
+        ];
+        assert!(schnorr.verify_rotation(&chain[0]));
+        assert_eq!(
+            schnorr.verify_rotation_chain(&root, &chain),
+            Ok(keys[2].public_key())
+        );
+        assert_eq!(schnorr.verify_rotation_chain(&root, &[]), Ok(root));
+
+        let valid = |now| schnorr.valid_keys_at(&root, &chain, now).unwrap();
+        assert_eq!(valid(0), vec![keys[0].public_key()]);
+        assert_eq!(valid(100), vec![keys[0].public_key(), keys[1].public_key()]);
+        assert_eq!(valid(109), vec![keys[0].public_key(), keys[1].public_key()]);
+        assert_eq!(valid(110), vec![keys[1].public_key()]);
+        assert_eq!(valid(199), vec![keys[1].public_key()]);
+        assert_eq!(valid(200), vec![keys[2].public_key()]);
+        assert_eq!(valid(u64::MAX), vec![keys[2].public_key()]);
+
+        assert_eq!(
+            schnorr.verify_rotation_chain(&root, &chain[1..]),
+            Err(RotationError::BrokenLink(0))
+        );
+        let early = schnorr.rotate(&keys[1], &keys[2], 105, 120);
+        assert_eq!(
+            schnorr.verify_rotation_chain(&root, &[chain[0].clone(), early]),
+            Err(RotationError::OutOfOrder(1))
+        );
+        let backwards = schnorr.rotate(&keys[0], &keys[1], 100, 99);
+        assert_eq!(
+            schnorr.verify_rotation_chain(&root, &[backwards]),
+            Err(RotationError::InvalidWindow(0))
+        );
+
+        // the new key has to sign too
+        let mut hijack = chain[0].clone();
+        hijack.new = keys[2].public_key();
+        hijack.old_signature = schnorr.sign(
+            &keys[0],
+            Message::<Public>::plain(APP_TAG, &hijack.statement()),
+        );
+        assert!(!schnorr.verify_rotation(&hijack));
+        assert_eq!(
+            schnorr.verify_rotation_chain(&root, &[hijack]),
+            Err(RotationError::InvalidSignature(0))
+        );
+
+        let mut tampered = chain.clone();
+        tampered[1].overlap_until = 300;
+        assert_eq!(
+            schnorr.verify_rotation_chain(&root, &tampered),
+            Err(RotationError::InvalidSignature(1))
+        );
+    }
+}
@@ -2,35 +2,54 @@ use crate::{
     fun::{
         derive_nonce,
         digest::{generic_array::typenum::U32, Digest},
-        g,
-        hash::{HashAdd, Tagged},
+        hash::Tagged,
         marker::*,
         nonce::{AddTag, NonceGen},
-        s, Point, Scalar, XOnlyKeyPair, G,
+        s,
+        transcript::Transcript,
+        Point, Scalar, XOnlyKeyPair,
     },
-    Message, Signature,
+    Message, Prehashed, Signature, Verifier, VerifyError,
 };
+use core::marker::PhantomData;
 
 /// An instance of a [BIP-340] style Schnorr signature scheme.
 ///
 /// Each instance is defined by its:
-/// - `challenge_hash`: The hash function instance that is used to produce the [_Fiat-Shamir_] challenge.
+/// - `challenge_hash`: The [`Transcript`] that is used to produce the [_Fiat-Shamir_] challenge.
+///   Usually this is just a hash like `Sha256` which produces [BIP-340] compatible signatures.
 /// - `nonce_gen`: The [`NonceGen`] used to hash the signing inputs (and perhaps additional randomness) to produce the secret nonce.
 ///
+/// The challenge hash is tagged once when the instance is created and the tagged state (the
+/// _midstate_) is cloned for each challenge so the 64-byte tag prefix is never hashed again. This
+/// means you should create a `Schnorr` once and reuse it rather than creating one per signature.
+///
+/// If you only need to verify signatures use [`Verifier`] instead.
+///
+/// The base point is [`G`] unless the instance was created with [`new_with_base`], in which
+/// case signatures are created with [`sign_with_base`]. Only [`G`] is a [`BasePoint`] so only
+/// it benefits from pre-computed multiplication tables. The protocols built on top of `Schnorr`
+/// in this crate (adaptor signatures, MuSig, FROST) are only implemented for [`G`].
+///
+/// [`G`]: crate::fun::G
+/// [`BasePoint`]: crate::fun::marker::BasePoint
+/// [`new_with_base`]: Schnorr::new_with_base
+/// [`sign_with_base`]: Schnorr::sign_with_base
 /// [_Fiat-Shamir_]: https://en.wikipedia.org/wiki/Fiat%E2%80%93Shamir_heuristic
 /// [`NonceGen<H>`]: crate::fun::hash::NonceGen
+/// [`Transcript`]: crate::fun::transcript::Transcript
 /// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
 #[derive(Clone)]
-pub struct Schnorr<CH, NG = ()> {
+pub struct Schnorr<CH, NG = (), GT = BasePoint> {
     /// The [`NonceGen`] used to generate nonces.
     ///
     /// [`NonceGen`]: crate::nonce::NonceGen
     nonce_gen: NG,
-    /// The challenge hash
-    challenge_hash: CH,
+    /// The verification context which holds the challenge hash (already tagged) and base point
+    verifier: Verifier<CH, GT>,
 }
 
-impl<H: Digest<OutputSize = U32> + Tagged> Schnorr<H, ()> {
+impl<H: Transcript + Tagged> Schnorr<H, ()> {
     /// Create a new instance that can only verify signatures.
     ///
     /// # Example
@@ -48,7 +67,7 @@ impl<H: Digest<OutputSize = U32> + Tagged> Schnorr<H, ()> {
 
 impl<CH, NG> Schnorr<CH, NG>
 where
-    CH: Digest<OutputSize = U32> + Tagged,
+    CH: Transcript + Tagged,
     NG: AddTag,
 {
     /// Creates a instance capable of signing and verifying.
@@ -73,14 +92,60 @@ where
         let nonce_gen = nonce_gen.add_tag("BIP0340");
         Self {
             nonce_gen,
-            challenge_hash: CH::default().tagged("BIP0340/challenge".as_bytes()),
+            verifier: Verifier::default(),
         }
     }
 }
 
-impl<CH: Default + Tagged + Digest<OutputSize = U32>, NG: Default + AddTag> Default
-    for Schnorr<CH, NG>
+impl<CH, NG> Schnorr<CH, NG, Normal>
+where
+    CH: Transcript + Tagged,
+    NG: AddTag,
 {
+    /// Creates an instance that signs and verifies signatures relative to `base` rather than
+    /// [`G`].
+    ///
+    /// The nonce generator is tagged with `base` so the same secret key never produces the same
+    /// nonce under two different base points.
+    ///
+    /// Only available with the `alloc` feature.
+    ///
+    /// # Examples
+    /// ```
+    /// use schnorr_fun::{
+    ///     fun::{marker::*, Point, Scalar},
+    ///     nonce::Deterministic,
+    ///     Message, Schnorr,
+    /// };
+    /// use sha2::Sha256;
+    /// let H = Point::random(&mut rand::thread_rng());
+    /// let schnorr = Schnorr::<Sha256, _, _>::new_with_base(Deterministic::<Sha256>::default(), H);
+    /// let secret_key = Scalar::random(&mut rand::thread_rng());
+    /// let message = Message::<Public>::plain("my-app", b"signed relative to H");
+    /// let signature = schnorr.sign_with_base(&secret_key, message);
+    /// let public_key = schnorr.public_key_for(&secret_key);
+    /// assert!(schnorr.verify(&public_key, message, &signature));
+    /// ```
+    ///
+    /// [`G`]: crate::fun::G
+    #[cfg(feature = "alloc")]
+    pub fn new_with_base(nonce_gen: NG, base: Point) -> Self {
+        Self {
+            nonce_gen: nonce_gen
+                .add_tag("BIP0340")
+                .add_tag(&crate::fun::hex::encode(&base.to_bytes())),
+            verifier: Verifier::with_base(base),
+        }
+    }
+}
+
+impl<CH, NG, GT> From<Schnorr<CH, NG, GT>> for Verifier<CH, GT> {
+    fn from(schnorr: Schnorr<CH, NG, GT>) -> Self {
+        schnorr.verifier
+    }
+}
+
+impl<CH: Transcript + Tagged, NG: Default + AddTag> Default for Schnorr<CH, NG> {
     /// Returns a Schnorr instance tagged in the default way according to BIP340.
     ///
     /// # Examples
@@ -96,9 +161,132 @@ impl<CH: Default + Tagged + Digest<OutputSize = U32>, NG: Default + AddTag> Defa
     }
 }
 
+impl<CH: Transcript + Tagged> Schnorr<CH> {
+    /// Starts building an instance with [`SchnorrBuilder`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schnorr_fun::{nonce::Deterministic, Schnorr};
+    /// use sha2::Sha256;
+    /// let schnorr = Schnorr::<Sha256>::builder()
+    ///     .nonce_gen(Deterministic::<Sha256>::default())
+    ///     .build();
+    /// let verifier = Schnorr::<Sha256>::builder().build();
+    /// ```
+    pub fn builder() -> SchnorrBuilder<CH> {
+        SchnorrBuilder {
+            challenge_tag: None,
+            nonce_gen: NoNonceGen,
+            challenge_hash: PhantomData,
+        }
+    }
+}
+
+/// Builds a [`Schnorr`] instance for signing or a [`Verifier`] for verifying.
+///
+/// Without a nonce generator [`build`] returns a [`Verifier`] and with one it returns a
+/// [`Schnorr`] that can sign, so a signer that was never given a nonce generator fails to compile
+/// rather than at runtime. Unless you set a [`challenge_tag`] the result is the same as
+/// [`Schnorr::new`] (or [`Verifier::default`]) and produces [BIP-340] signatures.
+///
+/// # Example
+///
+/// ```
+/// use schnorr_fun::{
+///     fun::{marker::*, Scalar},
+///     nonce::Deterministic,
+///     Message, Schnorr,
+/// };
+/// use sha2::Sha256;
+/// let signer = Schnorr::<Sha256>::builder()
+///     .challenge_tag("my-app/challenge")
+///     .nonce_gen(Deterministic::<Sha256>::default())
+///     .build();
+/// let verifier = Schnorr::<Sha256>::builder()
+///     .challenge_tag("my-app/challenge")
+///     .build();
+/// let keypair = signer.new_keypair(Scalar::random(&mut rand::thread_rng()));
+/// let message = Message::<Public>::plain("my-app", b"hello");
+/// let signature = signer.sign(&keypair, message);
+/// assert!(verifier.verify(&keypair.public_key(), message, &signature));
+/// // not a BIP-340 signature
+/// assert!(!Schnorr::<Sha256>::verify_only().verify(&keypair.public_key(), message, &signature));
+/// ```
+///
+/// Forgetting the nonce generator gives you something that can't sign:
+///
+/// ```compile_fail
+/// use schnorr_fun::{fun::Scalar, Schnorr};
+/// let signer = Schnorr::<sha2::Sha256>::builder().build();
+/// let keypair = signer.new_keypair(Scalar::random(&mut rand::thread_rng()));
+/// ```
+///
+/// [`build`]: SchnorrBuilder::build
+/// [`challenge_tag`]: SchnorrBuilder::challenge_tag
+/// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+#[derive(Clone, Debug)]
+pub struct SchnorrBuilder<CH, NG = NoNonceGen> {
+    challenge_tag: Option<&'static str>,
+    nonce_gen: NG,
+    challenge_hash: PhantomData<CH>,
+}
+
+/// The nonce generator of a [`SchnorrBuilder`] that hasn't been given one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NoNonceGen;
+
+impl<CH, NG> SchnorrBuilder<CH, NG> {
+    /// Tags the challenge hash with `challenge_tag` instead of `BIP0340/challenge`.
+    ///
+    /// The nonce generator is tagged with `challenge_tag` too (instead of `BIP0340`). Signatures
+    /// made with a custom tag only verify with an instance that has the same tag.
+    pub fn challenge_tag(self, challenge_tag: &'static str) -> Self {
+        SchnorrBuilder {
+            challenge_tag: Some(challenge_tag),
+            ..self
+        }
+    }
+
+    /// Sets the [`NonceGen`] used to sign so that [`build`] returns a [`Schnorr`].
+    ///
+    /// [`NonceGen`]: crate::nonce::NonceGen
+    /// [`build`]: SchnorrBuilder::build
+    pub fn nonce_gen<NG2: NonceGen + AddTag>(self, nonce_gen: NG2) -> SchnorrBuilder<CH, NG2> {
+        SchnorrBuilder {
+            challenge_tag: self.challenge_tag,
+            nonce_gen,
+            challenge_hash: PhantomData,
+        }
+    }
+}
+
+impl<CH: Transcript + Tagged> SchnorrBuilder<CH> {
+    /// Returns a [`Verifier`] since no nonce generator was set.
+    pub fn build(self) -> Verifier<CH> {
+        match self.challenge_tag {
+            Some(challenge_tag) => Verifier::with_challenge_tag(challenge_tag),
+            None => Verifier::default(),
+        }
+    }
+}
+
+impl<CH: Transcript + Tagged, NG: NonceGen + AddTag> SchnorrBuilder<CH, NG> {
+    /// Returns a [`Schnorr`] that signs with the nonce generator.
+    pub fn build(self) -> Schnorr<CH, NG> {
+        match self.challenge_tag {
+            Some(challenge_tag) => Schnorr {
+                nonce_gen: self.nonce_gen.add_tag(challenge_tag),
+                verifier: Verifier::with_challenge_tag(challenge_tag),
+            },
+            None => Schnorr::new(self.nonce_gen),
+        }
+    }
+}
+
 impl<NG, CH> Schnorr<CH, NG>
 where
-    CH: Digest<OutputSize = U32> + Clone,
+    CH: Transcript,
     NG: NonceGen,
 {
     /// Sign a message using a secret key and a particular nonce derivation scheme.
@@ -121,45 +309,294 @@ where
     /// ```
     pub fn sign(&self, keypair: &XOnlyKeyPair, message: Message<'_, impl Secrecy>) -> Signature {
         let (x, X) = keypair.as_tuple();
+        self._sign(x, &X, message)
+    }
+
+    /// Signs the output of a hash that the message has been incrementally fed into.
+    ///
+    /// This is useful when the message is very large or arrives as a stream (see [`hash_reader`]).
+    /// The signature is a normal signature on the 32-byte hash output as a [`Message::raw`] so it
+    /// can be verified with [`verify_digest`] or by anyone who computes the same hash.
+    ///
+    /// You should domain separate the hash (e.g. with [`Tagged`]) since no `app_tag` is applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use schnorr_fun::fun::{digest::Digest, hash::Tagged, Scalar};
+    /// use sha2::Sha256;
+    /// # let schnorr = schnorr_fun::test_instance!();
+    /// let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+    /// let mut hash = Sha256::default().tagged(b"my-app/file");
+    /// for chunk in [&b"a very "[..], &b"long message"[..]] {
+    ///     hash.update(chunk);
+    /// }
+    /// let signature = schnorr.sign_digest(&keypair, hash.clone());
+    /// assert!(schnorr.verify_digest(&keypair.public_key(), hash, &signature));
+    /// ```
+    ///
+    /// [`hash_reader`]: crate::hash_reader
+    /// [`verify_digest`]: Self::verify_digest
+    /// [`Tagged`]: crate::fun::hash::Tagged
+    pub fn sign_digest(
+        &self,
+        keypair: &XOnlyKeyPair,
+        digest: impl Digest<OutputSize = U32>,
+    ) -> Signature {
+        self.sign_prehashed(keypair, Prehashed::from_digest(digest))
+    }
+
+    /// Signs a 32-byte message hash.
+    ///
+    /// The signature is the [BIP-340] signature on the hash bytes. Verify it with
+    /// [`verify_prehashed`]. See [`Prehashed`] for why the hash has its own type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use schnorr_fun::{fun::Scalar, Prehashed};
+    /// use sha2::{Digest, Sha256};
+    /// # let schnorr = schnorr_fun::test_instance!();
+    /// let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+    /// let message_hash = Prehashed::from_digest(Sha256::new().chain_update(b"hello"));
+    /// let signature = schnorr.sign_prehashed(&keypair, message_hash);
+    /// assert!(schnorr.verify_prehashed(&keypair.public_key(), message_hash, &signature));
+    /// ```
+    ///
+    /// [`verify_prehashed`]: Self::verify_prehashed
+    /// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+    pub fn sign_prehashed(&self, keypair: &XOnlyKeyPair, message_hash: Prehashed) -> Signature {
+        self.sign(keypair, message_hash.message())
+    }
+
+    /// Signs a message of any length as it is, without an `app_tag`.
+    ///
+    /// This is the signing algorithm exactly as [BIP-340] defines it. Verify it with
+    /// [`verify_raw`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use schnorr_fun::fun::Scalar;
+    /// # let schnorr = schnorr_fun::test_instance!();
+    /// let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+    /// let signature = schnorr.sign_raw(&keypair, b"hello");
+    /// assert!(schnorr.verify_raw(&keypair.public_key(), b"hello", &signature));
+    /// ```
+    ///
+    /// [`verify_raw`]: Self::verify_raw
+    /// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+    pub fn sign_raw(&self, keypair: &XOnlyKeyPair, message: &[u8]) -> Signature {
+        self.sign(keypair, Message::<Public>::raw(message))
+    }
+
+    /// Create a new signing keypair.
+    ///
+    /// Short form of [`XOnlyKeyPair::new`].
+    pub fn new_keypair(&self, sk: Scalar) -> XOnlyKeyPair {
+        XOnlyKeyPair::new(sk)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<NG, CH> Schnorr<CH, NG>
+where
+    CH: Transcript,
+    NG: NonceGen,
+{
+    /// Signs each of `messages` with `keypair`.
+    ///
+    /// This gives the same signatures as calling [`sign`] on each message (as long as the nonce
+    /// generator is deterministic) but the keypair is only split up once and [`begin_derivation`]
+    /// is only called once per batch. The secret and public key are absorbed into the nonce hash
+    /// up front and the resulting state is cloned for each message so each nonce is still bound
+    /// to its message. Nonce generators that draw from a stream (like [`Synthetic`]) only use it
+    /// once for the whole batch. This is meant for signing many sighashes of a transaction or a
+    /// batch of transactions at once. Use [`TypedMessage`] to make sure each message can only be
+    /// interpreted the way you intended.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use schnorr_fun::{
+    ///     fun::{marker::*, Scalar},
+    ///     Message, MessageKind, TypedMessage,
+    /// };
+    /// # let schnorr = schnorr_fun::test_instance!();
+    /// struct Invoice;
+    /// impl MessageKind for Invoice {
+    ///     const APP_TAG: Option<&'static str> = Some("my-shop/invoice");
+    /// }
+    /// let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+    /// let invoices = [&b"invoice #1"[..], &b"invoice #2"[..]];
+    /// let messages = invoices
+    ///     .iter()
+    ///     .map(|invoice| TypedMessage::<Invoice>::new(invoice).into())
+    ///     .collect::<Vec<Message>>();
+    /// let signatures = schnorr.sign_all(&keypair, &messages);
+    /// for (message, signature) in messages.iter().zip(&signatures) {
+    ///     assert!(schnorr.verify(&keypair.public_key(), *message, signature));
+    /// }
+    /// ```
+    ///
+    /// [`sign`]: Self::sign
+    /// [`begin_derivation`]: crate::nonce::NonceGen::begin_derivation
+    /// [`TypedMessage`]: crate::TypedMessage
+    /// [`Synthetic`]: crate::nonce::Synthetic
+    pub fn sign_all<S: Secrecy>(
+        &self,
+        keypair: &XOnlyKeyPair,
+        messages: &[Message<'_, S>],
+    ) -> crate::Vec<Signature>
+    where
+        NG::Hash: Clone,
+    {
+        use crate::fun::hash::HashAdd;
+        let (x, X) = keypair.as_tuple();
+        // same inputs in the same order as derive_nonce! in _sign
+        let nonce_prefix = self.nonce_gen().begin_derivation(x).add(X);
+        messages
+            .iter()
+            .map(|message| {
+                let r = Scalar::from_hash(nonce_prefix.clone().add(*message));
+                self._sign_with_nonce(x, &X, r, *message)
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(feature = "std", feature = "sha2"))]
+impl<NG, CH> Schnorr<CH, crate::nonce::Guarded<NG>>
+where
+    CH: Transcript,
+    NG: NonceGen,
+{
+    /// Signs like [`sign`] but returns an error instead of a signature if the nonce was already
+    /// used to sign a different message under the same key.
+    ///
+    /// See [`Guarded`] for why you might want this.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use schnorr_fun::{
+    ///     fun::{marker::*, Scalar},
+    ///     nonce::{Deterministic, Guarded},
+    ///     Message, Schnorr,
+    /// };
+    /// use sha2::Sha256;
+    /// let schnorr = Schnorr::<Sha256, _>::new(Guarded::new(Deterministic::<Sha256>::default()));
+    /// let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+    /// let message = Message::<Public>::plain("test", b"hello");
+    /// let signature = schnorr
+    ///     .try_sign(&keypair, message)
+    ///     .expect("nonce was not reused");
+    /// assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+    /// ```
+    ///
+    /// [`sign`]: Self::sign
+    /// [`Guarded`]: crate::nonce::Guarded
+    pub fn try_sign(
+        &self,
+        keypair: &XOnlyKeyPair,
+        message: Message<'_, impl Secrecy>,
+    ) -> Result<Signature, crate::nonce::NonceReuse> {
+        let signature = self.sign(keypair, message);
+        self.nonce_gen()
+            .record(keypair.public_key(), signature.R, message)?;
+        Ok(signature)
+    }
+}
+
+impl<NG, CH> Schnorr<CH, NG, Normal>
+where
+    CH: Transcript,
+    NG: NonceGen,
+{
+    /// Signs a message with `secret_key` relative to the instance's base point.
+    ///
+    /// The corresponding public key is [`public_key_for`] the secret key. Like
+    /// [`XOnlyKeyPair::new`] the secret key is negated if necessary before signing so the
+    /// public key has an even y-coordinate.
+    ///
+    /// See [`new_with_base`] for an example.
+    ///
+    /// [`public_key_for`]: Schnorr::public_key_for
+    /// [`new_with_base`]: Schnorr::new_with_base
+    pub fn sign_with_base(
+        &self,
+        secret_key: &Scalar,
+        message: Message<'_, impl Secrecy>,
+    ) -> Signature {
+        let mut x = secret_key.clone();
+        let X = Point::even_y_from_scalar_mul(self.verifier.base(), &mut x);
+        self._sign(&x, &X, message)
+    }
+
+    /// Returns the public key for `secret_key` relative to the instance's base point.
+    pub fn public_key_for(&self, secret_key: &Scalar) -> Point<EvenY> {
+        Point::even_y_from_scalar_mul(self.verifier.base(), &mut secret_key.clone())
+    }
+}
 
-        let mut r = derive_nonce!(
+impl<NG, CH, GT> Schnorr<CH, NG, GT>
+where
+    CH: Transcript,
+    NG: NonceGen,
+    GT: PointType,
+{
+    fn _sign(&self, x: &Scalar, X: &Point<EvenY>, message: Message<'_, impl Secrecy>) -> Signature {
+        let r = derive_nonce!(
             nonce_gen => self.nonce_gen(),
             secret => x,
             public => [X, message]
         );
+        self._sign_with_nonce(x, X, r, message)
+    }
 
-        let R = Point::even_y_from_scalar_mul(&G, &mut r);
-        let c = self.challenge(&R, &X, message);
+    fn _sign_with_nonce(
+        &self,
+        x: &Scalar,
+        X: &Point<EvenY>,
+        mut r: Scalar,
+        message: Message<'_, impl Secrecy>,
+    ) -> Signature {
+        let R = Point::even_y_from_scalar_mul(self.verifier.base(), &mut r);
+        let c = self.challenge(&R, X, message);
         let s = s!(r + c * x).mark::<Public>();
 
         Signature { R, s }
     }
+}
 
+impl<NG, CH: Transcript, GT: PointType> Schnorr<CH, NG, GT> {
     /// Returns the [`NonceGen`] instance being used to genreate nonces.
     ///
     /// [`NonceGen`]: crate::nonce::NonceGen
     pub fn nonce_gen(&self) -> &NG {
         &self.nonce_gen
     }
-}
 
-impl<NG, CH: Digest<OutputSize = U32> + Clone> Schnorr<CH, NG> {
     /// Returns the challenge hash being used to sign/verify signatures
+    ///
+    /// The returned value has already absorbed the tag so it is the cached midstate.
     pub fn challenge_hash(&self) -> CH {
-        self.challenge_hash.clone()
+        self.verifier.challenge_hash()
     }
 
-    /// Create a new signing keypair.
-    ///
-    /// Short form of [`XOnlyKeyPair::new`].
-    pub fn new_keypair(&self, sk: Scalar) -> XOnlyKeyPair {
-        XOnlyKeyPair::new(sk)
+    /// Returns the verification only part of the instance.
+    pub fn verifier(&self) -> &Verifier<CH, GT> {
+        &self.verifier
     }
 
     /// Produces the Fiat-Shamir challenge for a Schnorr signature in the form specified by [BIP-340].
     ///
-    /// Concretely computes the hash `H(R || X || m)`. The [`Secrecy`] of the message is inherited
-    /// by the returned scalar.
+    /// Concretely computes the hash `H(R || X || m)` by appending `R`, `X` and `m` to the
+    /// challenge [`Transcript`]. The [`Secrecy`] of the message is inherited by the returned scalar.
+    ///
+    /// This is the exact computation used by [`sign`] and [`verify`] so protocols built on top of
+    /// BIP-340 (adaptor signatures, MuSig, FROST etc) should use it rather than hashing themselves.
+    /// See [`challenges`] to compute many at once.
     ///
     /// # Example
     ///
@@ -183,25 +620,70 @@ impl<NG, CH: Digest<OutputSize = U32> + Clone> Schnorr<CH, NG> {
     ///
     /// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
     /// [`Secrecy`]: secp256kfun::marker::Secrecy
+    /// [`Transcript`]: secp256kfun::transcript::Transcript
+    /// [`sign`]: Self::sign
+    /// [`verify`]: Self::verify
+    /// [`challenges`]: Self::challenges
     pub fn challenge<S: Secrecy>(
         &self,
         R: &Point<EvenY, impl Secrecy>,
         X: &Point<EvenY, impl Secrecy>,
         m: Message<'_, S>,
     ) -> Scalar<S, Zero> {
-        let hash = self.challenge_hash.clone();
-        let challenge = Scalar::from_hash(hash.add(R).add(X).add(&m));
+        self.verifier.challenge(R, X, m)
+    }
 
-        challenge
-            // Since the challenge pre-image is adversarially controlled we
-            // conservatively allow for it to be zero
-            .mark::<Zero>()
-            // The resulting challenge should take the secrecy of the message
-            .mark::<S>()
+    /// Produces the [`challenge`] for each `(R, X, m)` in `items`.
+    ///
+    /// This is exactly the same as calling [`challenge`] on each item but it is convenient when
+    /// implementing things like batch verification or signature aggregation where you need the
+    /// challenges for many signatures at once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schnorr_fun::{
+    ///     fun::{marker::*, Scalar},
+    ///     Message,
+    /// };
+    /// # let schnorr = schnorr_fun::test_instance!();
+    /// let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+    /// let messages = [
+    ///     Message::<Public>::plain("my-app", b"one"),
+    ///     Message::<Public>::plain("my-app", b"two"),
+    /// ];
+    /// let signatures = messages.map(|message| schnorr.sign(&keypair, message));
+    /// let challenges = schnorr.challenges(
+    ///     signatures
+    ///         .iter()
+    ///         .zip(messages)
+    ///         .map(|(signature, message)| (signature.R, keypair.public_key(), message)),
+    /// );
+    /// for (challenge, (signature, message)) in challenges.zip(signatures.iter().zip(messages)) {
+    ///     assert_eq!(
+    ///         challenge,
+    ///         schnorr.challenge(&signature.R, &keypair.public_key(), message)
+    ///     );
+    /// }
+    /// ```
+    ///
+    /// [`challenge`]: Self::challenge
+    pub fn challenges<'a, S: Secrecy>(
+        &'a self,
+        items: impl IntoIterator<Item = (Point<EvenY>, Point<EvenY>, Message<'a, S>)> + 'a,
+    ) -> impl Iterator<Item = Scalar<S, Zero>> + 'a {
+        self.verifier.challenges(items)
     }
 
     /// Verifies a signature on a message under a given public key.
     ///
+    /// [BIP-340] public keys are x-only: only the x-coordinate of `public_key` matters. A
+    /// [`Normal`] key with an odd y-coordinate (e.g. one deserialized from 33 bytes) is treated
+    /// as the key with the same x-coordinate and an even y-coordinate, so a signature verifies
+    /// under `X` if and only if it verifies under `-X`. This matches signing: [`XOnlyKeyPair`]
+    /// negates the secret key if its public key has an odd y-coordinate. If the y-coordinate of
+    /// the key matters to your protocol check it before verifying.
+    ///
     /// # Example
     ///
     /// ```
@@ -220,19 +702,151 @@ impl<NG, CH: Digest<OutputSize = U32> + Clone> Schnorr<CH, NG> {
     ///
     /// // We could also say the message is secret if we want to use a constant time algorithm to verify the signature.
     /// assert!(schnorr.verify(&public_key, Message::<Secret>::raw(&message), &signature));
+    ///
+    /// // A key with the other y-coordinate has the same x-only key
+    /// let normal_key = -public_key.mark::<Normal>();
+    /// assert!(schnorr.verify(&normal_key, Message::<Secret>::raw(&message), &signature));
     /// ```
+    ///
+    /// [`Normal`]: crate::fun::marker::Normal
+    /// [`XOnlyKeyPair`]: crate::fun::XOnlyKeyPair
+    /// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
     #[must_use]
     pub fn verify(
         &self,
-        public_key: &Point<EvenY, impl Secrecy>,
+        public_key: &Point<impl Normalized, impl Secrecy>,
         message: Message<'_, impl Secrecy>,
         signature: &Signature<impl Secrecy>,
     ) -> bool {
-        let X = public_key;
-        let (R, s) = signature.as_tuple();
-        let c = self.challenge(&R, X, message);
-        let R_implied = g!(s * G - c * X).mark::<Normal>();
-        R_implied == R
+        self.verifier.verify(public_key, message, signature)
+    }
+
+    /// Verifies a signature like [`verify`] but says why it is invalid.
+    ///
+    /// This is useful for services that want to log or count the different ways signatures they
+    /// receive fail. The result is always the same as [`verify`]: `Ok(())` if and only if
+    /// [`verify`] returns `true`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schnorr_fun::{
+    ///     fun::{marker::*, Scalar},
+    ///     Message, VerifyError,
+    /// };
+    /// # let schnorr = schnorr_fun::test_instance!();
+    /// let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+    /// let message = Message::<Public>::plain("my-app", b"hello");
+    /// let signature = schnorr.sign(&keypair, message);
+    /// assert_eq!(
+    ///     schnorr.verify_detailed(&keypair.public_key(), message, &signature),
+    ///     Ok(())
+    /// );
+    /// let other_message = Message::<Public>::plain("my-app", b"goodbye");
+    /// assert_eq!(
+    ///     schnorr.verify_detailed(&keypair.public_key(), other_message, &signature),
+    ///     Err(VerifyError::NonceMismatch)
+    /// );
+    /// ```
+    ///
+    /// [`verify`]: Self::verify
+    pub fn verify_detailed(
+        &self,
+        public_key: &Point<impl Normalized, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+        signature: &Signature<impl Secrecy>,
+    ) -> Result<(), VerifyError> {
+        self.verifier
+            .verify_detailed(public_key, message, signature)
+    }
+
+    /// Checks that `R == s * G - c * X` for a challenge `c` that was computed some other way.
+    ///
+    /// This is the equation [`verify_detailed`] checks after it has computed the [BIP-340]
+    /// [`challenge`]. It lets protocols that hash the challenge differently (e.g. a custom
+    /// transcript or a challenge shared between several signatures) use the same verification
+    /// code. The security of the result depends entirely on how the challenge was computed: it
+    /// must commit to at least `R`, `X` and the message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schnorr_fun::{
+    ///     fun::{marker::*, s, Point, Scalar, G},
+    ///     Signature,
+    /// };
+    /// use sha2::{Digest, Sha256};
+    /// # let schnorr = schnorr_fun::test_instance!();
+    /// let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+    /// let (x, X) = keypair.as_tuple();
+    /// let mut r = Scalar::random(&mut rand::thread_rng());
+    /// let R = Point::even_y_from_scalar_mul(G, &mut r);
+    /// let c = Scalar::from_hash(
+    ///     Sha256::new()
+    ///         .chain_update(b"my-protocol")
+    ///         .chain_update(R.to_xonly_bytes())
+    ///         .chain_update(X.to_xonly_bytes())
+    ///         .chain_update(b"hello"),
+    /// )
+    /// .mark::<Public>();
+    /// let signature = Signature {
+    ///     R,
+    ///     s: s!(r + c * x).mark::<Public>(),
+    /// };
+    /// assert_eq!(schnorr.verify_with_challenge(&X, &c, &signature), Ok(()));
+    /// ```
+    ///
+    /// [`verify_detailed`]: Self::verify_detailed
+    /// [`challenge`]: Self::challenge
+    /// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+    pub fn verify_with_challenge(
+        &self,
+        public_key: &Point<EvenY, impl Secrecy>,
+        challenge: &Scalar<impl Secrecy, impl ZeroChoice>,
+        signature: &Signature<impl Secrecy>,
+    ) -> Result<(), VerifyError> {
+        self.verifier
+            .verify_with_challenge(public_key, challenge, signature)
+    }
+
+    /// Verifies a signature produced by [`sign_digest`].
+    ///
+    /// [`sign_digest`]: Self::sign_digest
+    #[must_use]
+    pub fn verify_digest(
+        &self,
+        public_key: &Point<impl Normalized, impl Secrecy>,
+        digest: impl Digest<OutputSize = U32>,
+        signature: &Signature<impl Secrecy>,
+    ) -> bool {
+        self.verifier.verify_digest(public_key, digest, signature)
+    }
+
+    /// Verifies a signature produced by [`sign_prehashed`].
+    ///
+    /// [`sign_prehashed`]: Self::sign_prehashed
+    #[must_use]
+    pub fn verify_prehashed(
+        &self,
+        public_key: &Point<impl Normalized, impl Secrecy>,
+        message_hash: Prehashed,
+        signature: &Signature<impl Secrecy>,
+    ) -> bool {
+        self.verifier
+            .verify_prehashed(public_key, message_hash, signature)
+    }
+
+    /// Verifies a signature produced by [`sign_raw`].
+    ///
+    /// [`sign_raw`]: Self::sign_raw
+    #[must_use]
+    pub fn verify_raw(
+        &self,
+        public_key: &Point<impl Normalized, impl Secrecy>,
+        message: &[u8],
+        signature: &Signature<impl Secrecy>,
+    ) -> bool {
+        self.verifier.verify_raw(public_key, message, signature)
     }
 
     /// _Anticipates_ a Schnorr signature given the nonce `R` that will be used ahead of time.
@@ -244,20 +858,67 @@ impl<NG, CH: Digest<OutputSize = U32> + Clone> Schnorr<CH, NG> {
         R: &Point<EvenY, impl Secrecy>,
         m: Message<'_, impl Secrecy>,
     ) -> Point<Jacobian, Public, Zero> {
-        let c = self.challenge(R, X, m);
-        g!(R + c * X)
+        self.verifier.anticipate_signature(X, R, m)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<NG, CH: Transcript> Schnorr<CH, NG> {
+    /// Verifies a signature like [`verify`] but uses the pre-computed tables in
+    /// `verification_key` to do it faster.
+    ///
+    /// See [`VerificationKey`] for an example.
+    ///
+    /// [`verify`]: Self::verify
+    /// [`VerificationKey`]: crate::VerificationKey
+    #[must_use]
+    pub fn verify_with_key(
+        &self,
+        verification_key: &crate::VerificationKey,
+        message: Message<'_, Public>,
+        signature: &Signature,
+    ) -> bool {
+        self.verifier
+            .verify_with_key(verification_key, message, signature)
     }
 }
 
 #[cfg(test)]
 pub mod test {
-    use crate::fun::nonce::Deterministic;
+    use crate::fun::{g, nonce::Deterministic, G};
 
     use super::*;
     use crate::fun::proptest::prelude::*;
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::wasm_bindgen_test as test;
 
+    #[cfg(all(feature = "std", feature = "sha2"))]
+    #[test]
+    fn try_sign_refuses_reused_nonce() {
+        use crate::nonce::{Guarded, NonceReuse};
+        use sha2::Sha256;
+        let schnorr = Schnorr::<Sha256, _>::new(Guarded::new(Deterministic::<Sha256>::default()));
+        let unguarded = Schnorr::<Sha256, _>::new(Deterministic::<Sha256>::default());
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"one");
+        let signature = schnorr.try_sign(&keypair, message).unwrap();
+        assert_eq!(signature, unguarded.sign(&keypair, message));
+        assert_eq!(schnorr.try_sign(&keypair, message), Ok(signature));
+
+        // pretend a faulty nonce derivation already used the next nonce for something else
+        let message = Message::<Public>::plain("test", b"two");
+        let R = unguarded.sign(&keypair, message).R;
+        schnorr
+            .nonce_gen()
+            .record(
+                keypair.public_key(),
+                R,
+                Message::<Public>::plain("test", b"three"),
+            )
+            .unwrap();
+        assert_eq!(schnorr.try_sign(&keypair, message), Err(NonceReuse));
+    }
+
     #[test]
     fn deterministic_nonces_for_different_message_kinds() {
         use core::str::FromStr;
@@ -287,6 +948,192 @@ pub mod test {
         assert_eq!(schnorr.sign(&keypair, Message::<Public>::plain("one", b"foo")), Signature::<Public>::from_str("2fcf6fd140bbc4048e802c62f028e24f6534e0d15d450963265b67eead774d8b4aa7638bec9d70aa60b97e86bc4a60bf43ad2ff58e981ee1bba4f45ce02ff2c0").unwrap());
     }
 
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn sign_all_matches_sign() {
+        use crate::nonce::{GlobalRng, Synthetic};
+        use sha2::Sha256;
+        let schnorr = Schnorr::<Sha256, _>::new(Deterministic::<Sha256>::default());
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let messages = [
+            Message::<Public>::raw(&[1u8; 32]),
+            Message::<Public>::plain("test", b"two"),
+            Message::<Public>::plain("test", b"three"),
+        ];
+        let signatures = schnorr.sign_all(&keypair, &messages);
+        assert_eq!(signatures.len(), 3);
+        for (message, signature) in messages.iter().zip(&signatures) {
+            assert_eq!(signature, &schnorr.sign(&keypair, *message));
+        }
+        assert!(schnorr.sign_all(&keypair, &[] as &[Message]).is_empty());
+
+        let synthetic = Schnorr::<Sha256, _>::new(Synthetic::<
+            Sha256,
+            GlobalRng<rand::rngs::ThreadRng>,
+        >::default());
+        let signatures = synthetic.sign_all(&keypair, &messages);
+        assert_ne!(signatures[1].R, signatures[2].R);
+        for (message, signature) in messages.iter().zip(&signatures) {
+            assert!(synthetic.verify(&keypair.public_key(), *message, signature));
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn assert_sign_all_nonces_distinct<NG>(schnorr: Schnorr<sha2::Sha256, NG>)
+    where
+        NG: NonceGen,
+        NG::Hash: Clone,
+    {
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let hashes = (0..10u8).map(|i| [i; 32]).collect::<crate::Vec<_>>();
+        let messages = hashes
+            .iter()
+            .map(|hash| Message::<Public>::raw(hash))
+            .collect::<crate::Vec<_>>();
+        let signatures = schnorr.sign_all(&keypair, &messages);
+        for (i, signature) in signatures.iter().enumerate() {
+            assert!(schnorr.verify(&keypair.public_key(), messages[i], signature));
+            for other in &signatures[..i] {
+                assert_ne!(signature.R, other.R);
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn sign_all_distinct_nonces_for_every_nonce_gen() {
+        use crate::nonce::{ChaCha20, Rfc6979};
+        use sha2::Sha256;
+        assert_sign_all_nonces_distinct(Schnorr::<Sha256, Deterministic<Sha256>>::default());
+        assert_sign_all_nonces_distinct(Schnorr::<Sha256, Rfc6979<Sha256>>::default());
+        assert_sign_all_nonces_distinct(Schnorr::<Sha256, _>::new(ChaCha20::<Sha256>::new(
+            [42u8; 32],
+        )));
+        #[cfg(feature = "std")]
+        assert_sign_all_nonces_distinct(Schnorr::<Sha256, _>::new(crate::nonce::Guarded::new(
+            Deterministic::<Sha256>::default(),
+        )));
+    }
+
+    #[test]
+    fn prehashed_and_raw_entry_points() {
+        use sha2::Sha256;
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let public_key = keypair.public_key();
+        let message_hash = Prehashed::from_digest(Sha256::new().chain_update(b"hello"));
+        assert_eq!(
+            message_hash,
+            Prehashed::from(<[u8; 32]>::from(Sha256::digest(b"hello")))
+        );
+
+        let signature = schnorr.sign_prehashed(&keypair, message_hash);
+        assert!(schnorr.verify_prehashed(&public_key, message_hash, &signature));
+        assert!(schnorr.verify_digest(
+            &public_key,
+            Sha256::new().chain_update(b"hello"),
+            &signature
+        ));
+        assert!(!schnorr.verify_raw(&public_key, b"hello", &signature));
+        assert_eq!(
+            signature,
+            schnorr.sign(&keypair, Message::<Public>::raw(&message_hash.to_bytes()))
+        );
+
+        let signature = schnorr.sign_raw(&keypair, b"hello");
+        assert!(schnorr.verify_raw(&public_key, b"hello", &signature));
+        assert!(!schnorr.verify_prehashed(&public_key, message_hash, &signature));
+        assert!(schnorr
+            .verifier()
+            .verify_raw(&public_key, b"hello", &signature));
+    }
+
+    #[test]
+    fn challenge_uses_cached_midstate() {
+        use crate::fun::hash::HashAdd;
+        use sha2::Sha256;
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"attack at dawn");
+        let signature = schnorr.sign(&keypair, message);
+        let expected = Scalar::from_hash(
+            Sha256::default()
+                .tagged(b"BIP0340/challenge")
+                .add(signature.R)
+                .add(keypair.public_key())
+                .add(message),
+        );
+        assert_eq!(
+            schnorr.challenge(&signature.R, &keypair.public_key(), message),
+            expected
+        );
+        assert_eq!(
+            schnorr.challenge(&signature.R, &keypair.public_key(), message),
+            expected,
+            "challenge hash state must not be mutated between calls"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "merlin")]
+    fn sign_and_verify_with_merlin_transcript() {
+        use crate::fun::transcript::MerlinTranscript;
+        use sha2::Sha256;
+        let schnorr = Schnorr::<MerlinTranscript, _>::new(Deterministic::<Sha256>::default());
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"attack at dawn");
+        let signature = schnorr.sign(&keypair, message);
+        assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+        assert!(!crate::test_instance!().verify(&keypair.public_key(), message, &signature));
+    }
+
+    #[test]
+    fn builder_matches_new() {
+        use sha2::Sha256;
+        let schnorr = Schnorr::<Sha256>::builder()
+            .nonce_gen(Deterministic::<Sha256>::default())
+            .build();
+        let expected = Schnorr::<Sha256, _>::new(Deterministic::<Sha256>::default());
+        let verifier = Schnorr::<Sha256>::builder().build();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"built");
+        let signature = schnorr.sign(&keypair, message);
+        assert_eq!(signature, expected.sign(&keypair, message));
+        assert!(verifier.verify(&keypair.public_key(), message, &signature));
+
+        let tagged = Schnorr::<Sha256>::builder()
+            .challenge_tag("test/challenge")
+            .nonce_gen(Deterministic::<Sha256>::default())
+            .build();
+        let tagged_verifier = Schnorr::<Sha256>::builder()
+            .challenge_tag("test/challenge")
+            .build();
+        let tagged_signature = tagged.sign(&keypair, message);
+        assert_ne!(tagged_signature.R, signature.R);
+        assert!(tagged_verifier.verify(&keypair.public_key(), message, &tagged_signature));
+        assert!(tagged.verify(&keypair.public_key(), message, &tagged_signature));
+        assert!(!verifier.verify(&keypair.public_key(), message, &tagged_signature));
+        assert!(!tagged_verifier.verify(&keypair.public_key(), message, &signature));
+    }
+
+    #[test]
+    fn verify_with_normal_key() {
+        let schnorr = crate::test_instance!();
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let public_key = g!(secret_key * G).normalize();
+        let keypair = schnorr.new_keypair(secret_key);
+        let message = Message::<Public>::plain("test", b"normal key");
+        let signature = schnorr.sign(&keypair, message);
+        assert_eq!(
+            schnorr.verify_detailed(&public_key, message, &signature),
+            Ok(())
+        );
+        assert!(schnorr.verify(&-public_key, message, &signature));
+        let signature = schnorr.sign_raw(&keypair, b"raw");
+        assert!(schnorr.verify_raw(&public_key, b"raw", &signature));
+        assert!(!schnorr.verify(&public_key, message, &signature));
+    }
+
     proptest! {
 
         #[test]
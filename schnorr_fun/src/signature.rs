@@ -1,4 +1,4 @@
-use crate::fun::{marker::*, rand_core::RngCore, Point, Scalar};
+use crate::fun::{marker::*, rand_core::RngCore, Point, Scalar, ToBytes};
 
 /// A Schnorr signature.
 #[derive(Clone)]
@@ -99,17 +99,89 @@ impl Signature<Public> {
     ///
     /// [`to_bytes`]: crate::Signature::to_bytes
     pub fn from_bytes(bytes: [u8; 64]) -> Option<Self> {
+        Self::try_from_bytes(bytes).ok()
+    }
+
+    /// Deserializes a signature like [`from_bytes`] but says which half of the encoding was
+    /// invalid.
+    ///
+    /// # Examples
+    /// ```
+    /// use schnorr_fun::{Signature, SignatureParseError};
+    /// let mut bytes = Signature::random(&mut rand::thread_rng()).to_bytes();
+    /// bytes[32..].copy_from_slice(&[0xff; 32]);
+    /// assert_eq!(
+    ///     Signature::try_from_bytes(bytes),
+    ///     Err(SignatureParseError::SOutOfRange)
+    /// );
+    /// ```
+    ///
+    /// [`from_bytes`]: crate::Signature::from_bytes
+    pub fn try_from_bytes(bytes: [u8; 64]) -> Result<Self, SignatureParseError> {
         let mut R = [0u8; 32];
         R.copy_from_slice(&bytes[0..32]);
         let mut s = [0u8; 32];
         s.copy_from_slice(&bytes[32..64]);
 
-        let R = Point::from_xonly_bytes(R)?;
-        Some(Signature {
+        let R = Point::from_xonly_bytes(R).ok_or(SignatureParseError::InvalidR)?;
+        let s = Scalar::from_bytes(s).ok_or(SignatureParseError::SOutOfRange)?;
+        Ok(Signature {
             R,
-            s: Scalar::from_bytes(s)?.mark::<Public>(),
+            s: s.mark::<Public>(),
         })
     }
+
+    /// Checks whether `bytes` is the canonical encoding of a signature i.e. that the first 32
+    /// bytes are the x-coordinate of a point on the curve and the last 32 bytes are a scalar less
+    /// than the curve order.
+    ///
+    /// This is exactly when [`from_bytes`] succeeds. A decoded [`Signature`] only has one encoding
+    /// so you can use this to reject malleated encodings at the boundary of a consensus system
+    /// without keeping the decoded signature around.
+    ///
+    /// # Examples
+    /// ```
+    /// use schnorr_fun::Signature;
+    /// let signature = Signature::random(&mut rand::thread_rng());
+    /// assert!(Signature::is_canonical(&signature.to_bytes()));
+    /// let mut high_s = signature.to_bytes();
+    /// high_s[32..].copy_from_slice(&[0xff; 32]);
+    /// assert!(!Signature::is_canonical(&high_s));
+    /// ```
+    ///
+    /// [`from_bytes`]: crate::Signature::from_bytes
+    pub fn is_canonical(bytes: &[u8; 64]) -> bool {
+        Self::from_bytes(*bytes).is_some()
+    }
+}
+
+/// Why bytes couldn't be parsed as a [`Signature`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureParseError {
+    /// The first 32 bytes were not the x-coordinate of a point on the curve.
+    InvalidR,
+    /// The last 32 bytes were not less than the curve order.
+    SOutOfRange,
+}
+
+impl core::fmt::Display for SignatureParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            SignatureParseError::InvalidR => write!(f, "signature R is not a valid x-only point"),
+            SignatureParseError::SOutOfRange => {
+                write!(f, "signature s is not less than the curve order")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SignatureParseError {}
+
+impl<S> ToBytes<64> for Signature<S> {
+    fn write_bytes(&self, buf: &mut [u8; 64]) {
+        *buf = self.to_bytes()
+    }
 }
 
 secp256kfun::impl_fromstr_deserialize! {
@@ -127,6 +199,41 @@ secp256kfun::impl_display_debug_serialize! {
 
 #[cfg(test)]
 mod test {
+    #[test]
+    fn to_byte_array_matches_to_bytes() {
+        use super::*;
+        let signature = Signature::random(&mut rand::thread_rng());
+        assert_eq!(signature.to_byte_array(), signature.to_bytes());
+    }
+
+    #[test]
+    fn non_canonical_encodings() {
+        use super::*;
+        let signature = Signature::random(&mut rand::thread_rng());
+        assert!(Signature::is_canonical(&signature.to_bytes()));
+        // x = 5 is not on the curve
+        let mut bad_R = signature.to_bytes();
+        bad_R[..32].copy_from_slice(&[0u8; 32]);
+        bad_R[31] = 5;
+        assert!(!Signature::is_canonical(&bad_R));
+        assert_eq!(
+            Signature::try_from_bytes(bad_R),
+            Err(SignatureParseError::InvalidR)
+        );
+        // n + 1 is equal to 1 mod n
+        let mut s_plus_n = signature.to_bytes();
+        s_plus_n[32..].copy_from_slice(
+            &secp256kfun::hex::decode_array::<32>(
+                "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364142",
+            )
+            .unwrap(),
+        );
+        assert!(!Signature::is_canonical(&s_plus_n));
+        assert_eq!(
+            Signature::try_from_bytes(s_plus_n),
+            Err(SignatureParseError::SOutOfRange)
+        );
+    }
 
     #[cfg(feature = "serde")]
     #[test]
@@ -0,0 +1,369 @@
+//! Signing with keys held outside of this crate.
+//!
+//! Hardware wallets, HSMs and secure elements never hand out their secret keys. All they can do is
+//! commit to a fresh nonce and later answer a challenge with the `s` part of a signature. The
+//! [`Signer`] trait captures exactly those two steps so that [`Schnorr`], [adaptor] and [MuSig]
+//! signatures can be produced by such a device. Everything else (hashing the message, computing
+//! the challenge, dealing with x-only negation) is done on the host by methods like
+//! [`Schnorr::sign_with_signer`].
+//!
+//! The trait methods are blocking callbacks. If talking to your device is asynchronous, do the
+//! I/O before or after calling into this crate or block on it inside your implementation.
+//!
+//! [`LocalSigner`] is a software implementation which is useful for testing and shows what a
+//! device has to do. [`SignRequest::sign`] is the arithmetic a device written in Rust can use.
+//!
+//! # Synopsis
+//!
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! use schnorr_fun::{
+//!     fun::{marker::*, Scalar},
+//!     nonce::Deterministic,
+//!     signer::{LocalSigner, Signer},
+//!     Message,
+//! };
+//! use sha2::Sha256;
+//! let schnorr = schnorr_fun::test_instance!();
+//! let mut signer = LocalSigner::new(
+//!     Scalar::random(&mut rand::thread_rng()),
+//!     Deterministic::<Sha256>::default(),
+//! );
+//! let message = Message::<Public>::plain("test", b"sign me on a device");
+//! let signature = schnorr
+//!     .sign_with_signer(&mut signer, b"session-1", message)
+//!     .unwrap();
+//! let (public_key, _) = signer.public_key().into_point_with_even_y();
+//! assert!(schnorr.verify(&public_key, message, &signature));
+//! # }
+//! ```
+//!
+//! [adaptor]: crate::adaptor
+//! [MuSig]: crate::musig
+#[cfg(feature = "alloc")]
+use crate::fun::{nonce::NonceGen, KeyPair};
+use crate::{
+    adaptor::EncryptedSignature,
+    binonce::{Nonce, NonceKeyPair},
+    fun::{g, marker::*, s, transcript::Transcript, Point, Scalar},
+    Message, Schnorr, Signature,
+};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+/// Something that holds a secret key and can sign without revealing it.
+pub trait Signer {
+    /// The error returned when the signer fails or refuses to sign.
+    type Error;
+
+    /// The public key of the secret key the signer holds.
+    fn public_key(&self) -> Point;
+
+    /// Generates and stores a fresh pair of secret nonces for `session_id` and returns the public
+    /// nonces.
+    ///
+    /// This is the commitment step. The signer must never use the nonces for more than one
+    /// [`sign`] call.
+    ///
+    /// [`sign`]: Signer::sign
+    fn commit_nonces(&mut self, session_id: &[u8]) -> Result<Nonce, Self::Error>;
+
+    /// Answers `request` with the nonces committed to for `session_id` and then forgets them.
+    ///
+    /// The device should return an error if there are no nonces for `session_id` rather than
+    /// generating new ones.
+    fn sign(
+        &mut self,
+        session_id: &[u8],
+        request: &SignRequest,
+    ) -> Result<Scalar<Public, Zero>, Self::Error>;
+}
+
+/// What the host asks a [`Signer`] to compute.
+///
+/// With the committed secret nonces `r1`, `r2` and secret key `x` the answer is
+/// `±(r1 + nonce_coefficient * r2) + key_challenge * x` where the nonce part is negated if
+/// `negate_nonce` is set. The host folds the key's negation and any key coefficients into
+/// `key_challenge` so the signer doesn't need to know which scheme it is signing for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct SignRequest {
+    /// The coefficient of the second nonce (zero if the scheme only uses one nonce).
+    pub nonce_coefficient: Scalar<Public, Zero>,
+    /// Whether the nonces need to be negated (to make the final nonce have an even y-coordinate).
+    pub negate_nonce: bool,
+    /// What the secret key is multiplied by.
+    pub key_challenge: Scalar<Public, Zero>,
+}
+
+impl SignRequest {
+    /// Computes the answer to the request from the secret key and secret nonces.
+    pub fn sign(&self, secret_key: &Scalar, secret_nonce: NonceKeyPair) -> Scalar<Public, Zero> {
+        let [mut r1, mut r2] = secret_nonce.secret;
+        r1.conditional_negate(self.negate_nonce);
+        r2.conditional_negate(self.negate_nonce);
+        s!(r1 + self.nonce_coefficient * r2 + self.key_challenge * secret_key).mark::<Public>()
+    }
+}
+
+/// A [`Signer`] that keeps the secret key in memory.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct LocalSigner<NG> {
+    keypair: KeyPair,
+    nonce_gen: NG,
+    nonces: BTreeMap<crate::Vec<u8>, NonceKeyPair>,
+}
+
+#[cfg(feature = "alloc")]
+impl<NG: NonceGen> LocalSigner<NG> {
+    /// Creates a signer for `secret_key`.
+    ///
+    /// Nonces are derived with `nonce_gen` from the secret key and session id. With a
+    /// deterministic `nonce_gen` you must never commit to nonces twice for the same session id.
+    pub fn new(secret_key: Scalar, nonce_gen: NG) -> Self {
+        LocalSigner {
+            keypair: KeyPair::new(secret_key),
+            nonce_gen,
+            nonces: Default::default(),
+        }
+    }
+}
+
+/// Returned by [`LocalSigner`] when asked to sign for a session it has no nonces for.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnknownSession;
+
+#[cfg(feature = "alloc")]
+impl core::fmt::Display for UnknownSession {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "no nonces have been committed to for this session")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownSession {}
+
+#[cfg(feature = "alloc")]
+impl<NG: NonceGen> Signer for LocalSigner<NG> {
+    type Error = UnknownSession;
+
+    fn public_key(&self) -> Point {
+        self.keypair.public_key()
+    }
+
+    fn commit_nonces(&mut self, session_id: &[u8]) -> Result<Nonce, Self::Error> {
+        let nonce = NonceKeyPair::generate(
+            &self.nonce_gen,
+            self.keypair.secret_key(),
+            session_id,
+            Some(self.keypair.public_key()),
+            None,
+        );
+        let public = nonce.public();
+        self.nonces.insert(session_id.to_vec(), nonce);
+        Ok(public)
+    }
+
+    fn sign(
+        &mut self,
+        session_id: &[u8],
+        request: &SignRequest,
+    ) -> Result<Scalar<Public, Zero>, Self::Error> {
+        let nonce = self.nonces.remove(session_id).ok_or(UnknownSession)?;
+        Ok(request.sign(self.keypair.secret_key(), nonce))
+    }
+}
+
+impl<CH: Transcript, NG> Schnorr<CH, NG> {
+    /// Signs `message` with a [`Signer`].
+    ///
+    /// The signature is under the x-only version of the signer's public key. See the [module
+    /// documentation] for an example.
+    ///
+    /// [module documentation]: crate::signer
+    pub fn sign_with_signer<S: Signer>(
+        &self,
+        signer: &mut S,
+        session_id: &[u8],
+        message: Message<'_, impl Secrecy>,
+    ) -> Result<Signature, S::Error> {
+        let (X, negate_key) = signer.public_key().into_point_with_even_y();
+        let nonce = signer.commit_nonces(session_id)?;
+        let (R, negate_nonce) = nonce.0[0].into_point_with_even_y();
+        let mut key_challenge = self.challenge(&R, &X, message).mark::<Public>();
+        key_challenge.conditional_negate(negate_key);
+        let s = signer.sign(session_id, &SignRequest {
+            nonce_coefficient: Scalar::zero().mark::<Public>(),
+            negate_nonce,
+            key_challenge,
+        })?;
+        Ok(Signature { R, s })
+    }
+
+    /// Creates an [adaptor] signature on `message` encrypted under `encryption_key` with a
+    /// [`Signer`].
+    ///
+    /// This produces the same kind of encrypted signature as [`EncryptedSign::encrypted_sign`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// use schnorr_fun::{
+    ///     adaptor::Adaptor,
+    ///     fun::{marker::*, Scalar},
+    ///     nonce::Deterministic,
+    ///     signer::{LocalSigner, Signer},
+    ///     Message,
+    /// };
+    /// use sha2::Sha256;
+    /// let schnorr = schnorr_fun::test_instance!();
+    /// let mut signer = LocalSigner::new(
+    ///     Scalar::random(&mut rand::thread_rng()),
+    ///     Deterministic::<Sha256>::default(),
+    /// );
+    /// let decryption_key = Scalar::random(&mut rand::thread_rng());
+    /// let encryption_key = schnorr.encryption_key_for(&decryption_key);
+    /// let message = Message::<Public>::plain("test", b"encrypt me");
+    /// let encrypted_signature = schnorr
+    ///     .encrypted_sign_with_signer(&mut signer, b"session-1", &encryption_key, message)
+    ///     .unwrap();
+    /// let (public_key, _) = signer.public_key().into_point_with_even_y();
+    /// assert!(schnorr.verify_encrypted_signature(
+    ///     &public_key,
+    ///     &encryption_key,
+    ///     message,
+    ///     &encrypted_signature
+    /// ));
+    /// # }
+    /// ```
+    ///
+    /// [adaptor]: crate::adaptor
+    /// [`EncryptedSign::encrypted_sign`]: crate::adaptor::EncryptedSign::encrypted_sign
+    pub fn encrypted_sign_with_signer<S: Signer>(
+        &self,
+        signer: &mut S,
+        session_id: &[u8],
+        encryption_key: &Point<Normal, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+    ) -> Result<EncryptedSignature, S::Error> {
+        let (X, negate_key) = signer.public_key().into_point_with_even_y();
+        let nonce = signer.commit_nonces(session_id)?;
+        let (R, needs_negation) = g!({ nonce.0[0] } + encryption_key)
+            .normalize()
+            // the signer's nonce is committed to before it could know the encryption key
+            .expect_nonzero("computationally unreachable")
+            .into_point_with_even_y();
+        let mut key_challenge = self.challenge(&R, &X, message).mark::<Public>();
+        key_challenge.conditional_negate(negate_key);
+        let s_hat = signer.sign(session_id, &SignRequest {
+            nonce_coefficient: Scalar::zero().mark::<Public>(),
+            negate_nonce: needs_negation,
+            key_challenge,
+        })?;
+        Ok(EncryptedSignature {
+            R,
+            s_hat,
+            needs_negation,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::*;
+    use crate::{adaptor::Adaptor, fun::nonce::Deterministic};
+    use secp256kfun::TEST_SOUNDNESS;
+    use sha2::Sha256;
+
+    #[test]
+    fn signer_matches_local_signing() {
+        let schnorr = crate::test_instance!();
+        for _ in 0..TEST_SOUNDNESS {
+            let secret_key = Scalar::random(&mut rand::thread_rng());
+            let keypair = schnorr.new_keypair(secret_key.clone());
+            let mut signer = LocalSigner::new(secret_key, Deterministic::<Sha256>::default());
+            let message = Message::<Public>::plain("test", b"device");
+            let signature = schnorr
+                .sign_with_signer(&mut signer, b"sign", message)
+                .unwrap();
+            assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+
+            let decryption_key = Scalar::random(&mut rand::thread_rng());
+            let encryption_key = schnorr.encryption_key_for(&decryption_key);
+            let encrypted_signature = schnorr
+                .encrypted_sign_with_signer(&mut signer, b"adaptor", &encryption_key, message)
+                .unwrap();
+            assert!(schnorr.verify_encrypted_signature(
+                &keypair.public_key(),
+                &encryption_key,
+                message,
+                &encrypted_signature
+            ));
+            let signature =
+                schnorr.decrypt_signature(decryption_key.clone(), encrypted_signature.clone());
+            assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+            assert_eq!(
+                schnorr.recover_decryption_key(&encryption_key, &encrypted_signature, &signature),
+                Some(decryption_key)
+            );
+        }
+    }
+
+    #[test]
+    fn signer_in_musig() {
+        let musig = crate::musig::new_with_deterministic_nonces::<Sha256>();
+        let mut signer = LocalSigner::new(
+            Scalar::random(&mut rand::thread_rng()),
+            Deterministic::<Sha256>::default(),
+        );
+        let keypair = musig.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let agg_key = musig
+            .new_agg_key(vec![signer.public_key(), keypair.public_key()])
+            .into_xonly_key();
+        let message = Message::<Public>::plain("test", b"musig device");
+        let signer_nonce = signer.commit_nonces(b"musig").unwrap();
+        let nonce = musig.gen_nonces(
+            keypair.secret_key(),
+            b"musig",
+            Some(agg_key.agg_public_key()),
+            Some(message),
+        );
+        let session =
+            musig.start_sign_session(&agg_key, vec![signer_nonce, nonce.public()], message);
+        let signer_sig = musig
+            .sign_with_signer(&agg_key, &session, 0, &mut signer, b"musig")
+            .unwrap();
+        assert!(musig.verify_partial_signature(&agg_key, &session, 0, signer_sig));
+        let sig = musig.sign(&agg_key, &session, 1, &keypair, nonce);
+        let signature = musig.combine_partial_signatures(&agg_key, &session, [signer_sig, sig]);
+        assert!(musig
+            .schnorr
+            .verify(&agg_key.agg_public_key(), message, &signature));
+    }
+
+    #[test]
+    fn nonces_are_single_use() {
+        let mut signer = LocalSigner::new(
+            Scalar::random(&mut rand::thread_rng()),
+            Deterministic::<Sha256>::default(),
+        );
+        let request = SignRequest {
+            nonce_coefficient: Scalar::zero().mark::<Public>(),
+            negate_nonce: false,
+            key_challenge: Scalar::one().mark::<(Public, Zero)>(),
+        };
+        assert_eq!(signer.sign(b"one", &request), Err(UnknownSession));
+        signer.commit_nonces(b"one").unwrap();
+        assert!(signer.sign(b"one", &request).is_ok());
+        assert_eq!(signer.sign(b"one", &request), Err(UnknownSession));
+    }
+}
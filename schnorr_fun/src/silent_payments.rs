@@ -0,0 +1,433 @@
+//! Scanning for [BIP352] silent payments.
+//!
+//! A silent payment address is a scan key `B_scan` and a spend key `B_spend`. The sender sums the
+//! secret keys `a` of its eligible inputs (negating those of taproot inputs with odd y) and pays to
+//! `P_k = B_spend + t_k * G` where `t_k = hash(input_hash * a * B_scan || k)` and `k` counts the
+//! outputs to the same recipient. The receiver finds the same shared secret as `input_hash *
+//! b_scan * A` where `A = a * G` is the sum of the input public keys.
+//!
+//! Scanning the chain means doing that multiplication for every transaction with eligible inputs
+//! and then checking its outputs for `P_0`, `P_1` and so on. [`Receiver::scan`] does this for a
+//! whole block (or more) of transactions at a time:
+//!
+//! - `input_hash * b_scan` is computed first so each transaction needs a single point
+//!   multiplication.
+//! - The shared secrets have to be serialized to be hashed so they are all normalized with one
+//!   field inversion ([`Point::normalize_batch`]).
+//! - `t_k * G` uses the pre-computed multiplication tables of [`G`] and the candidate outputs
+//!   for every transaction still being scanned are normalized together again.
+//!
+//! Labels (`B_m = B_spend + hash(b_scan || m) * G`) are not supported yet.
+//!
+//! # Synopsis
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{g, marker::*, Scalar, G},
+//!     silent_payments::{self, Receiver, ScanTx},
+//! };
+//! let scan_key = Scalar::random(&mut rand::thread_rng());
+//! let spend_key = Scalar::random(&mut rand::thread_rng());
+//! let receiver = Receiver::new(scan_key, g!(spend_key * G).normalize());
+//!
+//! // the sender pays to the receiver's address
+//! let a = Scalar::random(&mut rand::thread_rng());
+//! let A = g!(a * G).normalize();
+//! let input_hash = silent_payments::input_hash(&[7u8; 36], &A);
+//! let output = silent_payments::sender_output(
+//!     &a,
+//!     &input_hash,
+//!     &receiver.scan_public_key(),
+//!     &receiver.spend_public_key(),
+//!     0,
+//! );
+//!
+//! // the receiver scans the transaction
+//! let found = receiver.scan(&[ScanTx {
+//!     input_hash,
+//!     input_key_sum: A,
+//!     outputs: &[output],
+//! }]);
+//! assert_eq!(found[0].len(), 1);
+//! let secret_key = found[0][0].secret_key(&spend_key);
+//! assert_eq!(g!(secret_key * G), output);
+//! ```
+//!
+//! [BIP352]: https://github.com/bitcoin/bips/blob/master/bip-0352.mediawiki
+use crate::{
+    fun::{
+        g,
+        hash::{HashAdd, Tagged},
+        marker::*,
+        s, Point, Scalar, G,
+    },
+    Vec,
+};
+use sha2::{Digest, Sha256};
+
+/// Computes `input_hash` from the smallest outpoint spent by the transaction and the sum of its
+/// eligible input public keys `A`.
+///
+/// The outpoint is the 32-byte txid (in the byte order it is serialized in transactions) followed
+/// by the 4-byte little-endian output index. "Smallest" is lexicographically by these 36 bytes.
+pub fn input_hash(smallest_outpoint: &[u8; 36], input_key_sum: &Point) -> Scalar<Public> {
+    let hash = Sha256::default()
+        .tagged(b"BIP0352/Inputs")
+        .add(&smallest_outpoint[..])
+        .add(input_key_sum)
+        .finalize();
+    hash_to_scalar(hash.into())
+}
+
+/// Computes the tweak `t_k` of the `k`th output paying to a recipient from the shared secret.
+pub fn output_tweak(shared_secret: &Point, k: u32) -> Scalar<Public> {
+    let hash = Sha256::default()
+        .tagged(b"BIP0352/SharedSecret")
+        .add(shared_secret)
+        .add(&k.to_be_bytes()[..])
+        .finalize();
+    hash_to_scalar(hash.into())
+}
+
+/// Computes the `k`th output paying to the address with `scan_key` and `spend_key` from the
+/// sender's side.
+///
+/// `input_secret_sum` is `a`: the sum of the secret keys of the eligible inputs where the keys of
+/// taproot inputs have already been negated if needed to match their x-only public keys.
+pub fn sender_output(
+    input_secret_sum: &Scalar,
+    input_hash: &Scalar<Public>,
+    scan_key: &Point,
+    spend_key: &Point,
+    k: u32,
+) -> Point<EvenY> {
+    let (a, B_scan) = (input_secret_sum, scan_key);
+    let shared_secret = g!({ s!(input_hash * a) } * B_scan).normalize();
+    output(spend_key, &output_tweak(&shared_secret, k))
+}
+
+fn output(spend_key: &Point, tweak: &Scalar<Public>) -> Point<EvenY> {
+    let B_spend = spend_key;
+    g!(B_spend + tweak * G)
+        .normalize()
+        .non_zero()
+        .expect("computationally unreachable: the tweak would be the negation of the spend key")
+        .into_point_with_even_y()
+        .0
+}
+
+fn hash_to_scalar(bytes: [u8; 32]) -> Scalar<Public> {
+    Scalar::from_bytes(bytes)
+        .and_then(|scalar| scalar.non_zero())
+        .expect("computationally unreachable: hash is zero or more than the curve order")
+        .mark::<Public>()
+}
+
+/// The transaction data needed to scan it.
+#[derive(Clone, Copy, Debug)]
+pub struct ScanTx<'a> {
+    /// The transaction's [`input_hash`].
+    pub input_hash: Scalar<Public>,
+    /// The sum `A` of the public keys of the transaction's eligible inputs.
+    pub input_key_sum: Point,
+    /// The transaction's taproot output keys.
+    pub outputs: &'a [Point<EvenY>],
+}
+
+/// An output found by [`Receiver::scan`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FoundOutput {
+    /// The index of the output in [`ScanTx::outputs`].
+    pub index: usize,
+    /// The `k` the output was paid with.
+    pub k: u32,
+    /// The tweak `t_k` to add to the spend secret key.
+    pub tweak: Scalar<Public>,
+}
+
+impl FoundOutput {
+    /// The secret key for the output (`b_spend + t_k` negated if needed so the output key has an
+    /// even y-coordinate) given the secret spend key.
+    pub fn secret_key(&self, spend_key: &Scalar) -> Scalar {
+        let (b_spend, t_k) = (spend_key, &self.tweak);
+        let mut secret_key = s!(b_spend + t_k).non_zero().expect(
+            "computationally unreachable: the tweak would be the negation of the spend key",
+        );
+        let needs_negation = !g!(secret_key * G).normalize().is_y_even();
+        secret_key.conditional_negate(needs_negation);
+        secret_key
+    }
+}
+
+/// The scanning side of a silent payment address.
+#[derive(Clone, Debug)]
+pub struct Receiver {
+    scan_key: Scalar,
+    scan_public_key: Point,
+    spend_public_key: Point,
+}
+
+impl Receiver {
+    /// Creates a receiver from the secret scan key `b_scan` and the public spend key `B_spend`.
+    ///
+    /// Scanning doesn't need the secret spend key so it can be kept somewhere else.
+    pub fn new(scan_key: Scalar, spend_public_key: Point) -> Self {
+        Receiver {
+            scan_public_key: g!(scan_key * G).normalize(),
+            scan_key,
+            spend_public_key,
+        }
+    }
+
+    /// The public scan key `B_scan`.
+    pub fn scan_public_key(&self) -> Point {
+        self.scan_public_key
+    }
+
+    /// The public spend key `B_spend`.
+    pub fn spend_public_key(&self) -> Point {
+        self.spend_public_key
+    }
+
+    /// Computes the shared secret `input_hash * b_scan * A` for one transaction.
+    pub fn shared_secret(&self, input_hash: &Scalar<Public>, input_key_sum: &Point) -> Point {
+        self.shared_secrets(&[(*input_hash, *input_key_sum)])[0]
+    }
+
+    /// Computes the shared secrets for many `(input_hash, A)` pairs normalizing them all at once.
+    pub fn shared_secrets(&self, inputs: &[(Scalar<Public>, Point)]) -> Vec<Point> {
+        let b_scan = &self.scan_key;
+        let shared_secrets = inputs
+            .iter()
+            .map(|(input_hash, A)| g!({ s!(input_hash * b_scan) } * A))
+            .collect();
+        Point::normalize_batch(shared_secrets)
+    }
+
+    /// Finds the outputs paying to this receiver in each of `txs`.
+    ///
+    /// The result has the outputs found in each transaction in the same order as `txs`. All the
+    /// transactions are scanned for `k = 0` together and then the ones that had an output for `k`
+    /// are scanned for `k + 1` together until there are none left.
+    pub fn scan(&self, txs: &[ScanTx<'_>]) -> Vec<Vec<FoundOutput>> {
+        let mut found = txs.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+        let mut pending = (0..txs.len())
+            .filter(|&i| !txs[i].outputs.is_empty())
+            .collect::<Vec<_>>();
+        let shared_secrets = self.shared_secrets(
+            &pending
+                .iter()
+                .map(|&i| (txs[i].input_hash, txs[i].input_key_sum))
+                .collect::<Vec<_>>(),
+        );
+        let mut shared_secrets = pending
+            .iter()
+            .copied()
+            .zip(shared_secrets)
+            .collect::<Vec<_>>();
+
+        let B_spend = &self.spend_public_key;
+        let mut k = 0u32;
+        while !pending.is_empty() {
+            let tweaks = shared_secrets
+                .iter()
+                .map(|(_, shared_secret)| output_tweak(shared_secret, k))
+                .collect::<Vec<_>>();
+            let candidates = Point::normalize_batch(
+                tweaks.iter().map(|tweak| g!(B_spend + tweak * G)).collect(),
+            );
+
+            let mut next = Vec::new();
+            for (((i, shared_secret), tweak), candidate) in
+                shared_secrets.into_iter().zip(tweaks).zip(candidates)
+            {
+                let candidate = match candidate.non_zero() {
+                    Some(candidate) => candidate.to_xonly_bytes(),
+                    None => continue,
+                };
+                if let Some(index) = txs[i]
+                    .outputs
+                    .iter()
+                    .position(|output| output.to_xonly_bytes() == candidate)
+                {
+                    found[i].push(FoundOutput { index, k, tweak });
+                    next.push((i, shared_secret));
+                }
+            }
+            shared_secrets = next;
+            pending = shared_secrets.iter().map(|(i, _)| *i).collect();
+            k += 1;
+        }
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scan_finds_outputs() {
+        let mut rng = rand::thread_rng();
+        let spend_key = Scalar::random(&mut rng);
+        let receiver = Receiver::new(Scalar::random(&mut rng), g!(spend_key * G).normalize());
+        let (B_scan, B_spend) = (receiver.scan_public_key(), receiver.spend_public_key());
+
+        let mut txs_data = Vec::new();
+        for n in 0..5u32 {
+            let a = Scalar::random(&mut rng);
+            let A = g!(a * G).normalize();
+            let input_hash = input_hash(&[n as u8; 36], &A);
+            // transaction n pays n times to the receiver between some unrelated outputs
+            let mut outputs = (0..n)
+                .map(|k| sender_output(&a, &input_hash, &B_scan, &B_spend, k))
+                .collect::<Vec<_>>();
+            outputs.insert(0, Point::random(&mut rng).into_point_with_even_y().0);
+            outputs.push(Point::random(&mut rng).into_point_with_even_y().0);
+            outputs.reverse();
+            txs_data.push((input_hash, A, outputs));
+        }
+        let txs = txs_data
+            .iter()
+            .map(|(input_hash, input_key_sum, outputs)| ScanTx {
+                input_hash: *input_hash,
+                input_key_sum: *input_key_sum,
+                outputs,
+            })
+            .collect::<Vec<_>>();
+
+        let found = receiver.scan(&txs);
+        for (n, (found, tx)) in found.iter().zip(&txs).enumerate() {
+            assert_eq!(found.len(), n);
+            for (k, output) in found.iter().enumerate() {
+                assert_eq!(output.k, k as u32);
+                let secret_key = output.secret_key(&spend_key);
+                assert_eq!(g!(secret_key * G), tx.outputs[output.index]);
+            }
+        }
+
+        // nothing is found for a different receiver
+        let other = Receiver::new(Scalar::random(&mut rng), B_spend);
+        assert!(other.scan(&txs).iter().all(|found| found.is_empty()));
+        assert!(receiver.scan(&[]).is_empty());
+    }
+
+    #[test]
+    fn batch_shared_secrets_match_single() {
+        let mut rng = rand::thread_rng();
+        let receiver = Receiver::new(Scalar::random(&mut rng), Point::random(&mut rng));
+        let inputs = (0..10)
+            .map(|_| {
+                (
+                    Scalar::random(&mut rng).mark::<Public>(),
+                    Point::random(&mut rng),
+                )
+            })
+            .collect::<Vec<_>>();
+        let shared_secrets = receiver.shared_secrets(&inputs);
+        for ((input_hash, A), shared_secret) in inputs.iter().zip(shared_secrets) {
+            assert_eq!(receiver.shared_secret(input_hash, A), shared_secret);
+            let b_scan = &receiver.scan_key;
+            assert_eq!(g!({ s!(input_hash * b_scan) } * A), shared_secret);
+        }
+    }
+
+    #[test]
+    fn bip352_test_vectors() {
+        use crate::fun::hex;
+        // from send_and_receive_test_vectors.json in the BIP. These all use the same two
+        // (non-taproot) input keys and pay one output to the same address.
+        let outpoint = |txid: &str, vout: u32| {
+            let mut txid = hex::decode_array::<32>(txid).unwrap();
+            txid.reverse();
+            let mut outpoint = [0u8; 36];
+            outpoint[..32].copy_from_slice(&txid);
+            outpoint[32..].copy_from_slice(&vout.to_le_bytes());
+            outpoint
+        };
+        let secret = |hex: &str| {
+            Scalar::from_bytes(hex::decode_array(hex).unwrap())
+                .and_then(|scalar| scalar.non_zero())
+                .unwrap()
+        };
+        let txid_1 = "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16";
+        let txid_2 = "a1075db55d416d3ca199f55b6084e2115b9345e16c5cf302fc80e9d5fbf5d48d";
+        let vectors = [
+            (
+                "Simple send: two inputs",
+                [outpoint(txid_1, 0), outpoint(txid_2, 0)],
+                "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1",
+            ),
+            (
+                "Simple send: two inputs, order reversed",
+                [outpoint(txid_2, 0), outpoint(txid_1, 0)],
+                "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1",
+            ),
+            (
+                "Simple send: two inputs from the same transaction",
+                [outpoint(txid_1, 3), outpoint(txid_1, 7)],
+                "79e71baa2ba3fc66396de3a04f168c7bf24d6870ec88ca877754790c1db357b6",
+            ),
+            (
+                "Outpoint ordering byte-lexicographically vs. vout integer",
+                [outpoint(txid_1, 1), outpoint(txid_1, 256)],
+                "a85ef8701394b517a4b35217c4bd37ac01ebeed4b008f8d0879f9e09ba95319c",
+            ),
+        ];
+        let (a_1, a_2) = (
+            secret("eadc78165ff1f8ea94ad7cfdc54990738a4c53f6e0507b42154201b8e5dff3b1"),
+            secret("93f5ed907ad5b2bdbbdcb5d9116ebc0a4e1f92f910d5260237fa45a9408aad16"),
+        );
+        let scan_key = secret("0f694e068028a717f8af6b9411f9a133dd3565258714cc226594b34db90c1f2c");
+        let spend_key = secret("9d6ad855ce3417ef84e836892e5a56392bfba05fa5d97ccea30e266f540e08b3");
+        let receiver = Receiver::new(scan_key, g!(spend_key * G).normalize());
+        assert_eq!(
+            hex::encode(&receiver.scan_public_key().to_bytes()),
+            "0220bcfac5b99e04ad1a06ddfb016ee13582609d60b6291e98d01a9bc9a16c96d4"
+        );
+        assert_eq!(
+            hex::encode(&receiver.spend_public_key().to_bytes()),
+            "025cc9856d6f8375350e123978daac200c260cb5b5ae83106cab90484dcd8fcf36"
+        );
+
+        let a = s!(a_1 + a_2).non_zero().unwrap();
+        let A = g!(a * G).normalize();
+        for (comment, outpoints, expected) in vectors {
+            let smallest_outpoint = outpoints.iter().min().unwrap();
+            let input_hash = input_hash(smallest_outpoint, &A);
+            let output = sender_output(
+                &a,
+                &input_hash,
+                &receiver.scan_public_key(),
+                &receiver.spend_public_key(),
+                0,
+            );
+            assert_eq!(
+                hex::encode(&output.to_xonly_bytes()),
+                expected,
+                "{}",
+                comment
+            );
+
+            let found = receiver.scan(&[ScanTx {
+                input_hash,
+                input_key_sum: A,
+                outputs: &[output],
+            }]);
+            assert_eq!(found[0].len(), 1, "{}", comment);
+            assert_eq!(
+                g!({ found[0][0].secret_key(&spend_key) } * G),
+                output,
+                "{}",
+                comment
+            );
+        }
+        // the receiver's tweak for the first vector
+        let input_hash = input_hash(&outpoint(txid_1, 0).min(outpoint(txid_2, 0)), &A);
+        assert_eq!(
+            hex::encode(&output_tweak(&receiver.shared_secret(&input_hash, &A), 0).to_bytes()),
+            "f438b40179a3c4262de12986c0e6cce0634007cdc79c1dcd3e20b9ebc2e7eef6"
+        );
+    }
+}
@@ -0,0 +1,392 @@
+//! Statechain key updates.
+//!
+//! A statechain lets the owner of a UTXO hand it to someone else without an on-chain transaction.
+//! The coins are locked under a key whose secret is shared _multiplicatively_ between the owner and
+//! a server (the _statechain entity_): if the server's share is `s` and the owner's is `x` the
+//! public key is `x * s * G`. To transfer the coins the server and the new owner re-share the
+//! _same_ joint secret key so that `x1 * s1 = x2 * s2` and the old owner's share no longer combines
+//! with anything the server holds. This relies on the server deleting its old share after the
+//! transfer -- nothing here can enforce that.
+//!
+//! The re-sharing is blinded so that no single message reveals anyone's share:
+//!
+//! 1. The server picks a random `blinding` and sends it to the current owner.
+//! 2. The current owner sends the output of [`blind_share`] to the new owner.
+//! 3. The new owner picks their secret key and sends the output of [`reblind_share`] to the server.
+//! 4. The server computes its new keypair and publishes the new [`StatechainKey`] with
+//!    [`StatechainKey::transfer`]. It then deletes its old secret key and the `blinding`.
+//! 5. The new owner checks the new key with [`StatechainKey::verify_transfer`].
+//!
+//! The server learns the ratio of the old and new owner's shares (since it knows `s1` and `s2`) but
+//! not the shares themselves.
+//!
+//! ## Blind signing
+//!
+//! Signing produces an ordinary [BIP-340] signature under [`StatechainKey::public_key`]. The
+//! server sends a nonce and answers a single _blinded_ challenge with [`server_sign`]. The owner
+//! creates the challenge with [`StatechainKey::start_sign_session`] and unblinds the server's
+//! reply with [`SignSession::finish`]. The server never sees the message, the final nonce or the
+//! real challenge so it can't link the signature to the session. A blind signing server must never
+//! have more than one signing session open at once since concurrent sessions can be used to forge
+//! signatures.
+//!
+//! ## Synopsis
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{g, KeyPair, Scalar, G},
+//!     statechain, Message,
+//! };
+//! let schnorr = schnorr_fun::test_instance!();
+//! let mut rng = rand::thread_rng();
+//! let server_keypair = KeyPair::new(Scalar::random(&mut rng));
+//! let alice_secret = Scalar::random(&mut rng);
+//! let key = statechain::StatechainKey::new(server_keypair.public_key(), &alice_secret);
+//! // the coins are deposited to this key
+//! let deposit_key = key.public_key();
+//!
+//! // alice transfers to bob
+//! let blinding = Scalar::random(&mut rng); // server -> alice
+//! let blinded_share = statechain::blind_share(&alice_secret, &blinding); // alice -> bob
+//! let bob_secret = Scalar::random(&mut rng);
+//! let reblinded_share = statechain::reblind_share(&blinded_share, &bob_secret); // bob -> server
+//! let (new_server_keypair, new_key) = key
+//!     .transfer(&server_keypair, &blinding, &reblinded_share)
+//!     .expect("we are the server for this key");
+//! // bob checks that the server did the right thing
+//! assert!(key.verify_transfer(&new_key, &bob_secret));
+//! assert_eq!(new_key.public_key(), deposit_key);
+//!
+//! // bob and the server sign under the same key
+//! let message = Message::plain("test", b"spend it");
+//! let server_nonce = Scalar::random(&mut rng);
+//! let session = new_key.start_sign_session(
+//!     &schnorr,
+//!     &bob_secret,
+//!     g!(server_nonce * G).normalize(),
+//!     message,
+//!     &mut rng,
+//! );
+//! // the server only sees the blinded challenge
+//! let server_sig = statechain::server_sign(
+//!     &new_server_keypair,
+//!     server_nonce,
+//!     session.blinded_challenge(),
+//! );
+//! let signature = session.finish(server_sig).expect("server signed correctly");
+//! assert!(schnorr.verify(&deposit_key, message, &signature));
+//! ```
+//!
+//! [BIP-340]: https://bips.xyz/340
+use crate::{Message, Schnorr, Signature};
+use secp256kfun::{
+    g, marker::*, rand_core::RngCore, s, transcript::Transcript, KeyPair, Point, Scalar, G,
+};
+
+/// The keys of a statechain at one point in its history.
+///
+/// The public key stays the same from transfer to transfer while the server's key changes.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct StatechainKey {
+    server_key: Point,
+    public_key: Point,
+}
+
+impl StatechainKey {
+    /// Run by the first owner to create the key for a new statechain from the server's public key
+    /// and their secret key.
+    pub fn new(server_key: Point, owner_secret: &Scalar) -> Self {
+        StatechainKey {
+            server_key,
+            public_key: g!(owner_secret * server_key).normalize(),
+        }
+    }
+
+    /// The server's current public key.
+    pub fn server_key(&self) -> Point {
+        self.server_key
+    }
+
+    /// The [BIP-340] public key the coins are locked under.
+    ///
+    /// This is the same for every key in the history of the statechain.
+    ///
+    /// [BIP-340]: https://bips.xyz/340
+    pub fn public_key(&self) -> Point<EvenY> {
+        self.public_key.into_point_with_even_y().0
+    }
+
+    /// Run by the server to finish a transfer to a new owner.
+    ///
+    /// `reblinded_share` is what the new owner sent (see [`reblind_share`]) and `blinding` is what
+    /// the server sent to the old owner at the start of the transfer. After this returns the server
+    /// must delete its old secret key and the `blinding`.
+    ///
+    /// ## Return value
+    ///
+    /// Returns the server's new keypair and the new key or `None` if `server_keypair` isn't the
+    /// server's key for this statechain.
+    pub fn transfer(
+        &self,
+        server_keypair: &KeyPair,
+        blinding: &Scalar,
+        reblinded_share: &Scalar,
+    ) -> Option<(KeyPair, StatechainKey)> {
+        if server_keypair.public_key() != self.server_key {
+            return None;
+        }
+        // s2 = s1 * x1 / x2
+        let new_server_keypair = KeyPair::new(s!({ server_keypair.secret_key() }
+            * reblinded_share
+            * { blinding.invert() }));
+        let new_key = StatechainKey {
+            server_key: new_server_keypair.public_key(),
+            public_key: self.public_key,
+        };
+        Some((new_server_keypair, new_key))
+    }
+
+    /// Run by the new owner to check the key the server published after a transfer.
+    ///
+    /// Checks that `new_key` has the same public key as `self` and that `new_owner_secret` combines
+    /// with its server key to produce it. This catches both the old owner and the server cheating
+    /// during the transfer.
+    #[must_use]
+    pub fn verify_transfer(&self, new_key: &StatechainKey, new_owner_secret: &Scalar) -> bool {
+        new_key.public_key == self.public_key
+            && g!(new_owner_secret * new_key.server_key) == self.public_key
+    }
+
+    /// Run by the owner to start signing `message` after receiving `server_nonce` from the server.
+    ///
+    /// The [`SignSession::blinded_challenge`] is sent to the server which replies with
+    /// [`server_sign`].
+    pub fn start_sign_session<CH: Transcript, NG>(
+        &self,
+        schnorr: &Schnorr<CH, NG>,
+        owner_secret: &Scalar,
+        server_nonce: Point,
+        message: Message<'_, Public>,
+        rng: &mut impl RngCore,
+    ) -> SignSession {
+        let (public_key, key_needs_negation) = self.public_key.into_point_with_even_y();
+        let mut owner_secret = owner_secret.clone();
+        owner_secret.conditional_negate(key_needs_negation);
+        let mut owner_nonce = Scalar::random(rng);
+        let blinding = Scalar::random(rng);
+        let (R, nonce_needs_negation) =
+            g!(owner_secret * server_nonce + owner_nonce * G + blinding * public_key)
+                .normalize()
+                .non_zero()
+                .expect("computationally unreachable: owner_nonce is random")
+                .into_point_with_even_y();
+        owner_secret.conditional_negate(nonce_needs_negation);
+        owner_nonce.conditional_negate(nonce_needs_negation);
+        let mut challenge = schnorr.challenge(&R, &public_key, message);
+        challenge.conditional_negate(nonce_needs_negation);
+        let blinded_challenge = s!(challenge + blinding).mark::<Public>();
+
+        SignSession {
+            server_key: self.server_key,
+            server_nonce,
+            blinded_challenge,
+            owner_secret,
+            owner_nonce,
+            R,
+        }
+    }
+}
+
+/// The owner's state for a [blind signing] session with the server.
+///
+/// Created with [`StatechainKey::start_sign_session`].
+///
+/// [blind signing]: crate::statechain#blind-signing
+#[derive(Debug, Clone)]
+pub struct SignSession {
+    server_key: Point,
+    server_nonce: Point,
+    blinded_challenge: Scalar<Public, Zero>,
+    owner_secret: Scalar,
+    owner_nonce: Scalar,
+    R: Point<EvenY>,
+}
+
+impl SignSession {
+    /// The challenge to send to the server.
+    pub fn blinded_challenge(&self) -> Scalar<Public, Zero> {
+        self.blinded_challenge
+    }
+
+    /// Unblinds the server's reply to [`blinded_challenge`] into the final signature.
+    ///
+    /// Returns `None` if the server's reply is invalid.
+    ///
+    /// [`blinded_challenge`]: Self::blinded_challenge
+    pub fn finish(self, server_signature: Scalar<Public, Zero>) -> Option<Signature> {
+        let SignSession {
+            server_key,
+            server_nonce,
+            blinded_challenge,
+            owner_secret,
+            owner_nonce,
+            R,
+        } = self;
+        if g!(server_signature * G) != g!(server_nonce + blinded_challenge * server_key) {
+            return None;
+        }
+        let s = s!(owner_secret * server_signature + owner_nonce).mark::<Public>();
+        Some(Signature { R, s })
+    }
+}
+
+/// Run by the server to answer the owner's blinded challenge.
+///
+/// `server_nonce` is the secret nonce whose public nonce (`server_nonce * G`) the server sent to the
+/// owner at the start of the session. It must be freshly sampled for each session and never used
+/// again.
+pub fn server_sign(
+    server_keypair: &KeyPair,
+    server_nonce: Scalar,
+    blinded_challenge: Scalar<Public, Zero>,
+) -> Scalar<Public, Zero> {
+    s!(server_nonce + blinded_challenge * { server_keypair.secret_key() }).mark::<Public>()
+}
+
+/// Run by the current owner to blind their secret key for the new owner.
+///
+/// `blinding` comes from the server. The result must be sent to the new owner over an encrypted
+/// channel.
+pub fn blind_share(owner_secret: &Scalar, blinding: &Scalar) -> Scalar {
+    s!(owner_secret * blinding)
+}
+
+/// Run by the new owner to swap the old owner's share for their own.
+///
+/// The result is sent to the server. It is still blinded by the server's blinding so neither the
+/// server nor the new owner learns the old owner's share.
+pub fn reblind_share(blinded_share: &Scalar, new_owner_secret: &Scalar) -> Scalar {
+    s!(blinded_share * { new_owner_secret.invert() })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use secp256kfun::TEST_SOUNDNESS;
+
+    #[test]
+    fn old_owner_share_is_useless_after_transfers() {
+        let schnorr = crate::test_instance!();
+        let mut rng = rand::thread_rng();
+        let mut server_keypair = KeyPair::new(Scalar::random(&mut rng));
+        let mut owner_secret = Scalar::random(&mut rng);
+        let mut key = StatechainKey::new(server_keypair.public_key(), &owner_secret);
+        let deposit_key = key.public_key();
+
+        for _ in 0..TEST_SOUNDNESS {
+            let blinding = Scalar::random(&mut rng);
+            let blinded_share = blind_share(&owner_secret, &blinding);
+            let new_owner_secret = Scalar::random(&mut rng);
+            let reblinded_share = reblind_share(&blinded_share, &new_owner_secret);
+            let (new_server_keypair, new_key) = key
+                .transfer(&server_keypair, &blinding, &reblinded_share)
+                .unwrap();
+            assert!(key.verify_transfer(&new_key, &new_owner_secret));
+            assert_eq!(new_key.public_key(), deposit_key);
+            // x1 * s1 = x2 * s2
+            assert_eq!(
+                s!(owner_secret * { server_keypair.secret_key() }),
+                s!(new_owner_secret * { new_server_keypair.secret_key() })
+            );
+            // the old owner's secret doesn't combine with the new server key
+            assert!(!key.verify_transfer(&new_key, &owner_secret));
+
+            let message = Message::<Public>::plain("test", b"statechain");
+            let server_nonce = Scalar::random(&mut rng);
+            let session = new_key.start_sign_session(
+                &schnorr,
+                &new_owner_secret,
+                g!(server_nonce * G).normalize(),
+                message,
+                &mut rng,
+            );
+            let server_sig = server_sign(
+                &new_server_keypair,
+                server_nonce,
+                session.blinded_challenge(),
+            );
+            let signature = session.finish(server_sig).unwrap();
+            assert!(schnorr.verify(&deposit_key, message, &signature));
+
+            key = new_key;
+            server_keypair = new_server_keypair;
+            owner_secret = new_owner_secret;
+        }
+    }
+
+    #[test]
+    fn server_sees_only_blinded_challenge() {
+        let schnorr = crate::test_instance!();
+        let mut rng = rand::thread_rng();
+        let server_keypair = KeyPair::new(Scalar::random(&mut rng));
+        let owner_secret = Scalar::random(&mut rng);
+        let key = StatechainKey::new(server_keypair.public_key(), &owner_secret);
+        let message = Message::<Public>::plain("test", b"statechain");
+        let server_nonce = Scalar::random(&mut rng);
+        let session = key.start_sign_session(
+            &schnorr,
+            &owner_secret,
+            g!(server_nonce * G).normalize(),
+            message,
+            &mut rng,
+        );
+        let blinded_challenge = session.blinded_challenge();
+        let signature = session
+            .clone()
+            .finish(server_sign(
+                &server_keypair,
+                server_nonce.clone(),
+                blinded_challenge,
+            ))
+            .unwrap();
+        let mut challenge = schnorr.challenge(&signature.R, &key.public_key(), message);
+        assert_ne!(challenge, blinded_challenge);
+        challenge.conditional_negate(true);
+        assert_ne!(challenge, blinded_challenge);
+        assert_ne!(signature.R, g!(server_nonce * G).normalize());
+
+        // a bad reply from the server is caught
+        let bad_reply = server_sign(
+            &server_keypair,
+            server_nonce,
+            s!(blinded_challenge + 1).mark::<Public>(),
+        );
+        assert!(session.finish(bad_reply).is_none());
+    }
+
+    #[test]
+    fn cheating_old_owner_is_caught() {
+        let mut rng = rand::thread_rng();
+        let server_keypair = KeyPair::new(Scalar::random(&mut rng));
+        let owner_secret = Scalar::random(&mut rng);
+        let key = StatechainKey::new(server_keypair.public_key(), &owner_secret);
+
+        let blinding = Scalar::random(&mut rng);
+        // the old owner sends something other than their blinded share
+        let blinded_share = blind_share(&Scalar::random(&mut rng), &blinding);
+        let new_owner_secret = Scalar::random(&mut rng);
+        let reblinded_share = reblind_share(&blinded_share, &new_owner_secret);
+        let (new_server_keypair, new_key) = key
+            .transfer(&server_keypair, &blinding, &reblinded_share)
+            .unwrap();
+        assert!(!key.verify_transfer(&new_key, &new_owner_secret));
+        assert!(key
+            .transfer(&new_server_keypair, &blinding, &reblinded_share)
+            .is_none());
+    }
+}
@@ -0,0 +1,310 @@
+//! Verifiable timed commitments to signatures.
+//!
+//! A timed signature is a Schnorr signature where `s` has been locked in a _time-lock puzzle_ such
+//! as the [RSW] repeated squaring puzzle. Anyone can check that the puzzle contains a valid
+//! signature without solving it but actually getting the signature requires doing a (tunably) long
+//! sequential computation. In a swap this gives the counterparty a cryptographic timeout fallback:
+//! if the protocol stalls they can force open the puzzle and get the signature after the delay.
+//!
+//! This module is the secp256k1 side of the construction. The puzzle itself is abstracted by the
+//! [`TimeLockPuzzle`] trait which you have to implement for your puzzle backend. The backend must be
+//! able to prove that a puzzle contains the discrete logarithm of a given point. For a signature
+//! `(R, s)` on `m` under `X` that point is `s * G = R + c * X` which the verifier can compute from
+//! public information.
+//!
+//! The same trait can be used to give an [adaptor signature] a timeout by locking its decryption key
+//! `y` and having the counterparty check it with [`TimeLockPuzzle::verify`] against the encryption
+//! key `Y`.
+//!
+//! # Synopsis
+//!
+//! ```
+//! use schnorr_fun::{
+//!     fun::{marker::*, Scalar},
+//!     timed::{Timed, TimedSign},
+//!     Message,
+//! };
+//! # use schnorr_fun::{fun::{g, G, Point, rand_core::RngCore}, timed::TimeLockPuzzle};
+//! # /// An "instant" puzzle which only exists so this example compiles. Never use this.
+//! # struct MyPuzzleBackend;
+//! # impl TimeLockPuzzle for MyPuzzleBackend {
+//! #     type Puzzle = Scalar<Secret, Zero>;
+//! #     fn lock(&self, secret: &Scalar<Secret, Zero>, _rng: &mut impl RngCore) -> Self::Puzzle { secret.clone() }
+//! #     fn verify(&self, commitment: &Point<Normal, Public, Zero>, puzzle: &Self::Puzzle) -> bool { g!(puzzle * G) == *commitment }
+//! #     fn solve(&self, puzzle: &Self::Puzzle) -> Option<Scalar<Secret, Zero>> { Some(puzzle.clone()) }
+//! # }
+//! let puzzle_backend = MyPuzzleBackend;
+//! let schnorr = schnorr_fun::test_instance!();
+//! let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+//! let message = Message::<Public>::plain("my-swap", b"refund Alice");
+//!
+//! let timed_signature =
+//!     schnorr.timed_sign(&puzzle_backend, &keypair, message, &mut rand::thread_rng());
+//! // the receiver checks the puzzle has the signature in it
+//! assert!(schnorr.verify_timed_signature(
+//!     &puzzle_backend,
+//!     &keypair.public_key(),
+//!     message,
+//!     &timed_signature
+//! ));
+//! // if need be they can get the signature after the delay
+//! let signature = schnorr
+//!     .open_timed_signature(
+//!         &puzzle_backend,
+//!         &keypair.public_key(),
+//!         message,
+//!         &timed_signature,
+//!     )
+//!     .expect("puzzle was verified");
+//! assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+//! ```
+//!
+//! [RSW]: https://people.csail.mit.edu/rivest/pubs/RSW96.pdf
+//! [adaptor signature]: crate::adaptor
+use crate::{
+    fun::{
+        g, marker::*, nonce::NonceGen, rand_core::RngCore, transcript::Transcript, Point, Scalar,
+        XOnlyKeyPair,
+    },
+    Message, Schnorr, Signature,
+};
+
+/// A verifiable time-lock puzzle that can lock secp256k1 scalars.
+///
+/// Implementations must make sure that [`verify`] only accepts a puzzle if [`solve`] will return
+/// the discrete logarithm of `commitment`.
+///
+/// [`verify`]: Self::verify
+/// [`solve`]: Self::solve
+pub trait TimeLockPuzzle {
+    /// The puzzle (including any proof that it is well formed).
+    type Puzzle;
+
+    /// Locks `secret` in a puzzle.
+    fn lock(&self, secret: &Scalar<Secret, Zero>, rng: &mut impl RngCore) -> Self::Puzzle;
+
+    /// Checks that `puzzle` contains the discrete logarithm of `commitment` with respect to [`G`].
+    ///
+    /// [`G`]: crate::fun::G
+    #[must_use]
+    fn verify(&self, commitment: &Point<Normal, Public, Zero>, puzzle: &Self::Puzzle) -> bool;
+
+    /// Solves the puzzle. This is the slow part.
+    fn solve(&self, puzzle: &Self::Puzzle) -> Option<Scalar<Secret, Zero>>;
+}
+
+/// A signature whose `s` is locked in a time-lock puzzle.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct TimedSignature<P> {
+    /// The signature's public nonce.
+    pub R: Point<EvenY>,
+    /// The puzzle containing the signature's `s`.
+    pub puzzle: P,
+}
+
+/// Extension trait for [`Schnorr`] to add timed signing.
+///
+/// [`Schnorr`]: crate::Schnorr
+pub trait TimedSign {
+    /// Signs `message` and locks the signature in a puzzle created by `puzzle_backend`.
+    ///
+    /// See the [synopsis] for usage.
+    ///
+    /// [synopsis]: crate::timed#synopsis
+    fn timed_sign<P: TimeLockPuzzle>(
+        &self,
+        puzzle_backend: &P,
+        signing_keypair: &XOnlyKeyPair,
+        message: Message<'_, impl Secrecy>,
+        rng: &mut impl RngCore,
+    ) -> TimedSignature<P::Puzzle>;
+}
+
+impl<CH, NG> TimedSign for Schnorr<CH, NG>
+where
+    CH: Transcript,
+    NG: NonceGen,
+{
+    fn timed_sign<P: TimeLockPuzzle>(
+        &self,
+        puzzle_backend: &P,
+        signing_keypair: &XOnlyKeyPair,
+        message: Message<'_, impl Secrecy>,
+        rng: &mut impl RngCore,
+    ) -> TimedSignature<P::Puzzle> {
+        let Signature { R, s } = self.sign(signing_keypair, message);
+        TimedSignature {
+            R,
+            puzzle: puzzle_backend.lock(&s.mark::<Secret>(), rng),
+        }
+    }
+}
+
+/// Extension trait adding the algorithms for verifying and opening [`TimedSignature`]s to
+/// [`Schnorr`].
+///
+/// [`Schnorr`]: crate::Schnorr
+pub trait Timed {
+    /// Checks that the puzzle in `timed_signature` contains a valid signature on `message` under
+    /// `verification_key`.
+    ///
+    /// See the [synopsis] for usage.
+    ///
+    /// [synopsis]: crate::timed#synopsis
+    #[must_use]
+    fn verify_timed_signature<P: TimeLockPuzzle>(
+        &self,
+        puzzle_backend: &P,
+        verification_key: &Point<EvenY, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+        timed_signature: &TimedSignature<P::Puzzle>,
+    ) -> bool;
+
+    /// Solves the puzzle in `timed_signature` and returns the signature.
+    ///
+    /// Returns `None` if the puzzle could not be solved or didn't contain a valid signature.
+    fn open_timed_signature<P: TimeLockPuzzle>(
+        &self,
+        puzzle_backend: &P,
+        verification_key: &Point<EvenY, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+        timed_signature: &TimedSignature<P::Puzzle>,
+    ) -> Option<Signature>;
+}
+
+impl<CH, NG> Timed for Schnorr<CH, NG>
+where
+    CH: Transcript,
+{
+    fn verify_timed_signature<P: TimeLockPuzzle>(
+        &self,
+        puzzle_backend: &P,
+        verification_key: &Point<EvenY, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+        timed_signature: &TimedSignature<P::Puzzle>,
+    ) -> bool {
+        let R = &timed_signature.R;
+        let X = verification_key;
+        let c = self.challenge(R, X, message);
+        // s * G = R + c * X
+        let S = g!(R + c * X).normalize().mark::<Public>();
+        puzzle_backend.verify(&S, &timed_signature.puzzle)
+    }
+
+    fn open_timed_signature<P: TimeLockPuzzle>(
+        &self,
+        puzzle_backend: &P,
+        verification_key: &Point<EvenY, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+        timed_signature: &TimedSignature<P::Puzzle>,
+    ) -> Option<Signature> {
+        let s = puzzle_backend.solve(&timed_signature.puzzle)?;
+        let signature = Signature {
+            R: timed_signature.R,
+            s: s.mark::<Public>(),
+        };
+        if self.verify(verification_key, message, &signature) {
+            Some(signature)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        adaptor::{Adaptor, EncryptedSign},
+        fun::G,
+    };
+
+    /// Stores the secret in the clear. Only useful for testing the secp256k1 side.
+    struct ClearPuzzle;
+
+    impl TimeLockPuzzle for ClearPuzzle {
+        type Puzzle = Scalar<Secret, Zero>;
+
+        fn lock(&self, secret: &Scalar<Secret, Zero>, _rng: &mut impl RngCore) -> Self::Puzzle {
+            secret.clone()
+        }
+
+        fn verify(&self, commitment: &Point<Normal, Public, Zero>, puzzle: &Self::Puzzle) -> bool {
+            g!(puzzle * G) == *commitment
+        }
+
+        fn solve(&self, puzzle: &Self::Puzzle) -> Option<Scalar<Secret, Zero>> {
+            Some(puzzle.clone())
+        }
+    }
+
+    #[test]
+    fn timed_sign_verify_and_open() {
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"refund");
+        let timed_signature =
+            schnorr.timed_sign(&ClearPuzzle, &keypair, message, &mut rand::thread_rng());
+        assert!(schnorr.verify_timed_signature(
+            &ClearPuzzle,
+            &keypair.public_key(),
+            message,
+            &timed_signature
+        ));
+        assert!(!schnorr.verify_timed_signature(
+            &ClearPuzzle,
+            &keypair.public_key(),
+            Message::<Public>::plain("test", b"other"),
+            &timed_signature
+        ));
+        let signature = schnorr
+            .open_timed_signature(
+                &ClearPuzzle,
+                &keypair.public_key(),
+                message,
+                &timed_signature,
+            )
+            .unwrap();
+        assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+
+        let mut bad = timed_signature;
+        bad.puzzle = Scalar::random(&mut rand::thread_rng()).mark::<Zero>();
+        assert!(!schnorr.verify_timed_signature(
+            &ClearPuzzle,
+            &keypair.public_key(),
+            message,
+            &bad
+        ));
+        assert!(schnorr
+            .open_timed_signature(&ClearPuzzle, &keypair.public_key(), message, &bad)
+            .is_none());
+    }
+
+    #[test]
+    fn timed_adaptor_decryption_key() {
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let decryption_key = Scalar::random(&mut rand::thread_rng());
+        let encryption_key = schnorr.encryption_key_for(&decryption_key);
+        let message = Message::<Public>::plain("test", b"swap");
+        let encrypted_signature = schnorr.encrypted_sign(&keypair, &encryption_key, message);
+
+        let puzzle = ClearPuzzle.lock(
+            &decryption_key.clone().mark::<Zero>(),
+            &mut rand::thread_rng(),
+        );
+        assert!(ClearPuzzle.verify(&encryption_key.mark::<Zero>(), &puzzle));
+        let decryption_key = ClearPuzzle
+            .solve(&puzzle)
+            .unwrap()
+            .mark::<NonZero>()
+            .unwrap();
+        let signature = schnorr.decrypt_signature(decryption_key, encrypted_signature);
+        assert!(schnorr.verify(&keypair.public_key(), message, &signature));
+    }
+}
@@ -0,0 +1,426 @@
+use crate::{
+    fun::{
+        digest::{generic_array::typenum::U32, Digest},
+        g,
+        hash::{tags::Bip340Challenge, Tagged},
+        marker::*,
+        transcript::Transcript,
+        Point, Scalar, G,
+    },
+    Message, Prehashed, Signature,
+};
+
+/// A [BIP-340] verification context.
+///
+/// This is the part of [`Schnorr`] that can verify signatures and compute challenges. It has no
+/// nonce generation configuration and no code paths that touch secret keys so it is what you want
+/// if you are only ever going to verify signatures.
+///
+/// Usually the base point is [`G`] but a verifier for signatures relative to some other generator
+/// can be created with [`with_base`]. Only [`G`] is a [`BasePoint`] so only it benefits from
+/// pre-computed multiplication tables.
+///
+/// # Example
+///
+/// ```
+/// use schnorr_fun::{
+///     fun::{marker::*, Scalar},
+///     Message, Verifier,
+/// };
+/// # let schnorr = schnorr_fun::test_instance!();
+/// # let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+/// # let message = Message::<Public>::plain("my-app", b"hello");
+/// # let signature = schnorr.sign(&keypair, message);
+/// let verifier = Verifier::<sha2::Sha256>::default();
+/// assert!(verifier.verify(&keypair.public_key(), message, &signature));
+/// ```
+///
+/// [`Schnorr`]: crate::Schnorr
+/// [`G`]: crate::fun::G
+/// [`BasePoint`]: crate::fun::marker::BasePoint
+/// [`with_base`]: Verifier::with_base
+/// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+#[derive(Clone, Debug)]
+pub struct Verifier<CH, GT = BasePoint> {
+    challenge_hash: CH,
+    base: Point<GT>,
+}
+
+impl<CH: Transcript + Tagged> Default for Verifier<CH> {
+    /// Returns a verifier tagged according to [BIP-340].
+    ///
+    /// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+    fn default() -> Self {
+        Self {
+            challenge_hash: CH::default().tagged_with::<Bip340Challenge>(),
+            base: *G,
+        }
+    }
+}
+
+impl<CH: Transcript + Tagged> Verifier<CH> {
+    /// Creates a verifier whose challenge hash is tagged with `challenge_tag` rather than
+    /// `BIP0340/challenge`.
+    pub(crate) fn with_challenge_tag(challenge_tag: &str) -> Self {
+        Self {
+            challenge_hash: CH::default().tagged(challenge_tag.as_bytes()),
+            base: *G,
+        }
+    }
+}
+
+impl<CH: Transcript + Tagged> Verifier<CH, Normal> {
+    /// Creates a verifier for signatures where the public key and nonce are multiples of `base`
+    /// rather than [`G`].
+    ///
+    /// [`G`]: crate::fun::G
+    pub fn with_base(base: Point) -> Self {
+        Self {
+            challenge_hash: CH::default().tagged_with::<Bip340Challenge>(),
+            base,
+        }
+    }
+}
+
+impl<CH: Transcript, GT: PointType> Verifier<CH, GT> {
+    /// Returns the challenge hash being used to verify signatures.
+    pub fn challenge_hash(&self) -> CH {
+        self.challenge_hash.clone()
+    }
+
+    /// The base point signatures are verified against.
+    pub fn base(&self) -> &Point<GT> {
+        &self.base
+    }
+
+    /// Produces the Fiat-Shamir challenge for a Schnorr signature in the form specified by [BIP-340].
+    ///
+    /// See [`Schnorr::challenge`].
+    ///
+    /// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+    /// [`Schnorr::challenge`]: crate::Schnorr::challenge
+    pub fn challenge<S: Secrecy>(
+        &self,
+        R: &Point<EvenY, impl Secrecy>,
+        X: &Point<EvenY, impl Secrecy>,
+        m: Message<'_, S>,
+    ) -> Scalar<S, Zero> {
+        let mut transcript = self.challenge_hash.clone();
+        transcript.append_message(b"R", &R.to_xonly_bytes());
+        transcript.append_message(b"X", &X.to_xonly_bytes());
+        m.append_to(&mut transcript);
+
+        // Since the challenge pre-image is adversarially controlled the transcript conservatively
+        // allows for it to be zero. The resulting challenge takes the secrecy of the message.
+        transcript.challenge_scalar::<S>(b"e")
+    }
+
+    /// Produces the [`challenge`] for each `(R, X, m)` in `items`.
+    ///
+    /// See [`Schnorr::challenges`].
+    ///
+    /// [`challenge`]: Self::challenge
+    /// [`Schnorr::challenges`]: crate::Schnorr::challenges
+    pub fn challenges<'a, S: Secrecy>(
+        &'a self,
+        items: impl IntoIterator<Item = (Point<EvenY>, Point<EvenY>, Message<'a, S>)> + 'a,
+    ) -> impl Iterator<Item = Scalar<S, Zero>> + 'a {
+        items
+            .into_iter()
+            .map(move |(R, X, m)| self.challenge(&R, &X, m))
+    }
+
+    /// Verifies a signature on a message under a given public key.
+    ///
+    /// See [`Schnorr::verify`].
+    ///
+    /// [`Schnorr::verify`]: crate::Schnorr::verify
+    #[must_use]
+    pub fn verify(
+        &self,
+        public_key: &Point<impl Normalized, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+        signature: &Signature<impl Secrecy>,
+    ) -> bool {
+        self.verify_detailed(public_key, message, signature).is_ok()
+    }
+
+    /// Verifies a signature like [`verify`] but returns why it failed.
+    ///
+    /// See [`Schnorr::verify_detailed`].
+    ///
+    /// [`verify`]: Self::verify
+    /// [`Schnorr::verify_detailed`]: crate::Schnorr::verify_detailed
+    pub fn verify_detailed(
+        &self,
+        public_key: &Point<impl Normalized, impl Secrecy>,
+        message: Message<'_, impl Secrecy>,
+        signature: &Signature<impl Secrecy>,
+    ) -> Result<(), VerifyError> {
+        // BIP-340 public keys are x-only so a key with an odd y-coordinate stands for its negation
+        let (public_key, _) = public_key.clone().into_point_with_even_y();
+        let c = self.challenge(&signature.R, &public_key, message);
+        self.verify_with_challenge(&public_key, &c, signature)
+    }
+
+    /// Checks that `R == s * G - c * X` for a challenge `c` that was computed some other way.
+    ///
+    /// See [`Schnorr::verify_with_challenge`].
+    ///
+    /// [`Schnorr::verify_with_challenge`]: crate::Schnorr::verify_with_challenge
+    pub fn verify_with_challenge(
+        &self,
+        public_key: &Point<EvenY, impl Secrecy>,
+        challenge: &Scalar<impl Secrecy, impl ZeroChoice>,
+        signature: &Signature<impl Secrecy>,
+    ) -> Result<(), VerifyError> {
+        let base = &self.base;
+        let X = public_key;
+        let c = challenge;
+        let (R, s) = signature.as_tuple();
+        check_implied_nonce(g!(s * base - c * X), &R)
+    }
+
+    /// Verifies a signature produced by [`Schnorr::sign_digest`].
+    ///
+    /// [`Schnorr::sign_digest`]: crate::Schnorr::sign_digest
+    #[must_use]
+    pub fn verify_digest(
+        &self,
+        public_key: &Point<impl Normalized, impl Secrecy>,
+        digest: impl Digest<OutputSize = U32>,
+        signature: &Signature<impl Secrecy>,
+    ) -> bool {
+        self.verify_prehashed(public_key, Prehashed::from_digest(digest), signature)
+    }
+
+    /// Verifies a signature produced by [`Schnorr::sign_prehashed`].
+    ///
+    /// [`Schnorr::sign_prehashed`]: crate::Schnorr::sign_prehashed
+    #[must_use]
+    pub fn verify_prehashed(
+        &self,
+        public_key: &Point<impl Normalized, impl Secrecy>,
+        message_hash: Prehashed,
+        signature: &Signature<impl Secrecy>,
+    ) -> bool {
+        self.verify(public_key, message_hash.message(), signature)
+    }
+
+    /// Verifies a signature produced by [`Schnorr::sign_raw`].
+    ///
+    /// [`Schnorr::sign_raw`]: crate::Schnorr::sign_raw
+    #[must_use]
+    pub fn verify_raw(
+        &self,
+        public_key: &Point<impl Normalized, impl Secrecy>,
+        message: &[u8],
+        signature: &Signature<impl Secrecy>,
+    ) -> bool {
+        self.verify(public_key, Message::<Public>::raw(message), signature)
+    }
+
+    /// _Anticipates_ a Schnorr signature given the nonce `R` that will be used ahead of time.
+    ///
+    /// See [`Schnorr::anticipate_signature`].
+    ///
+    /// [`Schnorr::anticipate_signature`]: crate::Schnorr::anticipate_signature
+    pub fn anticipate_signature(
+        &self,
+        X: &Point<EvenY, impl Secrecy>,
+        R: &Point<EvenY, impl Secrecy>,
+        m: Message<'_, impl Secrecy>,
+    ) -> Point<Jacobian, Public, Zero> {
+        let c = self.challenge(R, X, m);
+        g!(R + c * X)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<CH: Transcript> Verifier<CH> {
+    /// Verifies a signature under a [`VerificationKey`] using its pre-computed tables.
+    ///
+    /// See [`Schnorr::verify_with_key`].
+    ///
+    /// [`VerificationKey`]: crate::VerificationKey
+    /// [`Schnorr::verify_with_key`]: crate::Schnorr::verify_with_key
+    #[must_use]
+    pub fn verify_with_key(
+        &self,
+        verification_key: &crate::VerificationKey,
+        message: Message<'_, Public>,
+        signature: &Signature,
+    ) -> bool {
+        let (R, s) = signature.as_tuple();
+        let c = self.challenge(&R, &verification_key.public_key(), message);
+        check_implied_nonce(verification_key.implied_nonce(s, &c), &R).is_ok()
+    }
+}
+
+/// Checks that the nonce implied by the rest of a signature is `R`.
+pub(crate) fn check_implied_nonce(
+    R_implied: Point<Jacobian, Public, Zero>,
+    R: &Point<EvenY, impl Secrecy>,
+) -> Result<(), VerifyError> {
+    let R_implied = R_implied.mark::<Normal>();
+    if R_implied == *R {
+        return Ok(());
+    }
+    // only work out why it failed once we know it did so valid signatures stay fast
+    match R_implied.non_zero() {
+        None => Err(VerifyError::ImpliedNonceIsZero),
+        Some(R_implied) if R_implied.x_bytes() == R.to_xonly_bytes() => {
+            Err(VerifyError::ImpliedNonceHasOddY)
+        }
+        Some(_) => Err(VerifyError::NonceMismatch),
+    }
+}
+
+/// Why a signature (or encrypted signature) failed to verify.
+///
+/// Any of these means the signature is invalid. They are only distinguished so that services can
+/// log and count the different kinds of failure. Other than [`NonceMismatch`] they are extremely
+/// unlikely to happen by accident and indicate someone crafting bad signatures.
+///
+/// [`NonceMismatch`]: VerifyError::NonceMismatch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The nonce implied by `s`, the challenge and the public key is the point at infinity.
+    ImpliedNonceIsZero,
+    /// The implied nonce has the same x-coordinate as `R` but an odd y-coordinate.
+    ImpliedNonceHasOddY,
+    /// The implied nonce is not `R`. This is what a signature with the wrong key, the wrong
+    /// message or a tampered `s` looks like.
+    NonceMismatch,
+}
+
+impl core::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            VerifyError::ImpliedNonceIsZero => write!(f, "implied nonce is the point at infinity"),
+            VerifyError::ImpliedNonceHasOddY => {
+                write!(f, "implied nonce has an odd y-coordinate")
+            }
+            VerifyError::NonceMismatch => write!(f, "implied nonce doesn't match the signature"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerifyError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{fun::s, Schnorr};
+    use sha2::Sha256;
+
+    #[test]
+    fn verifier_agrees_with_schnorr() {
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let message = Message::<Public>::plain("test", b"attack at dawn");
+        let signature = schnorr.sign(&keypair, message);
+        let verifier = Verifier::<Sha256>::default();
+        assert!(verifier.verify(&keypair.public_key(), message, &signature));
+        assert_eq!(
+            verifier.challenge(&signature.R, &keypair.public_key(), message),
+            schnorr.challenge(&signature.R, &keypair.public_key(), message)
+        );
+        let from_schnorr: Verifier<Sha256> = Schnorr::<Sha256>::verify_only().into();
+        assert!(from_schnorr.verify(&keypair.public_key(), message, &signature));
+        assert!(!verifier.verify(
+            &keypair.public_key(),
+            Message::<Public>::plain("test", b"retreat at noon"),
+            &signature
+        ));
+    }
+
+    #[test]
+    fn verify_detailed_failures() {
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let X = keypair.public_key();
+        let x = keypair.secret_key();
+        let message = Message::<Public>::plain("test", b"attack at dawn");
+        let signature = schnorr.sign(&keypair, message);
+        assert_eq!(schnorr.verify_detailed(&X, message, &signature), Ok(()));
+        assert_eq!(
+            schnorr.verify_detailed(
+                &X,
+                Message::<Public>::plain("test", b"retreat at noon"),
+                &signature
+            ),
+            Err(VerifyError::NonceMismatch)
+        );
+
+        let R = signature.R;
+        let c = schnorr.challenge(&R, &X, message);
+        // s = c * x implies R = s * G - c * X = 0
+        let zero_nonce = Signature {
+            R,
+            s: s!(c * x).mark::<(Public, Zero)>(),
+        };
+        assert_eq!(
+            schnorr.verify_detailed(&X, message, &zero_nonce),
+            Err(VerifyError::ImpliedNonceIsZero)
+        );
+        // s = 2 * c * x - s implies -R
+        let s = &signature.s;
+        let odd_y_nonce = Signature {
+            R,
+            s: s!(2 * c * x - s).mark::<Public>(),
+        };
+        assert_eq!(
+            schnorr.verify_detailed(&X, message, &odd_y_nonce),
+            Err(VerifyError::ImpliedNonceHasOddY)
+        );
+        assert!(!schnorr.verify(&X, message, &odd_y_nonce));
+    }
+
+    #[test]
+    fn verify_with_challenge() {
+        let schnorr = crate::test_instance!();
+        let keypair = schnorr.new_keypair(Scalar::random(&mut rand::thread_rng()));
+        let X = keypair.public_key();
+        let message = Message::<Public>::plain("test", b"attack at dawn");
+        let signature = schnorr.sign(&keypair, message);
+        let verifier = Verifier::<Sha256>::default();
+        let c = verifier.challenge(&signature.R, &X, message);
+        assert_eq!(verifier.verify_with_challenge(&X, &c, &signature), Ok(()));
+        assert_eq!(
+            verifier.verify_with_challenge(&X, &c.mark::<Secret>(), &signature),
+            Ok(())
+        );
+        let other_c = s!(c + 1).mark::<Public>();
+        assert_eq!(
+            verifier.verify_with_challenge(&X, &other_c, &signature),
+            Err(VerifyError::NonceMismatch)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn sign_and_verify_with_custom_base() {
+        use crate::fun::nonce::Deterministic;
+        let H = Point::random(&mut rand::thread_rng());
+        let schnorr = Schnorr::<Sha256, _, _>::new_with_base(Deterministic::<Sha256>::default(), H);
+        let secret_key = Scalar::random(&mut rand::thread_rng());
+        let public_key = Point::even_y_from_scalar_mul(&H, &mut secret_key.clone());
+        assert_eq!(public_key, schnorr.public_key_for(&secret_key));
+        let message = Message::<Public>::plain("test", b"attack at dawn");
+        let signature = schnorr.sign_with_base(&secret_key, message);
+        assert!(Verifier::<Sha256, _>::with_base(H).verify(&public_key, message, &signature));
+        assert!(!Verifier::<Sha256>::default().verify(&public_key, message, &signature));
+
+        let other_base = Schnorr::<Sha256, _, _>::new_with_base(
+            Deterministic::<Sha256>::default(),
+            Point::random(&mut rand::thread_rng()),
+        );
+        assert_ne!(
+            other_base.sign_with_base(&secret_key, message).R,
+            signature.R,
+            "nonces must depend on the base point"
+        );
+    }
+}
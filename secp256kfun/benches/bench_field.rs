@@ -0,0 +1,22 @@
+//! Field arithmetic is what dominates point multiplication so this is the thing to measure when
+//! comparing targets (e.g. a 32-bit microcontroller or wasm32 against x86_64).
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use secp256kfun::{FieldElement, Scalar};
+
+fn random_element() -> FieldElement {
+    FieldElement::from_bytes_mod_order(Scalar::random(&mut rand::thread_rng()).to_bytes())
+}
+
+fn field(c: &mut Criterion) {
+    let mut group = c.benchmark_group("field");
+    let (a, b) = (random_element(), random_element());
+
+    group.bench_function("mul", |bench| bench.iter(|| black_box(a) * black_box(b)));
+    group.bench_function("square", |bench| bench.iter(|| black_box(a).square()));
+    group.bench_function("add", |bench| bench.iter(|| black_box(a) + black_box(b)));
+    group.bench_function("invert", |bench| bench.iter(|| black_box(a).invert()));
+    group.bench_function("sqrt", |bench| bench.iter(|| black_box(a).sqrt()));
+}
+
+criterion_group!(benches, field);
+criterion_main!(benches);
@@ -1,9 +1,13 @@
 use core::ops::{Add, Neg};
-pub use secp256kfun_k256_backend::Scalar;
-use secp256kfun_k256_backend::{lincomb, AffinePoint, FieldBytes, FieldElement, ProjectivePoint};
+use secp256kfun_k256_backend::{lincomb, AffinePoint, FieldBytes, ProjectivePoint};
+pub use secp256kfun_k256_backend::{FieldElement, Scalar};
 use subtle::{Choice, ConditionallyNegatable, ConditionallySelectable, ConstantTimeEq};
 
 use super::{BackendPoint, BackendScalar, BackendXOnly, TimeSensitive};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::vec::Vec;
 pub type Point = ProjectivePoint;
 pub type BasePoint = ProjectivePoint;
 
@@ -207,6 +211,10 @@ impl TimeSensitive for ConstantTime {
         lhs + &rhs
     }
 
+    fn point_double(point: &Point) -> Point {
+        point.double()
+    }
+
     fn any_point_neg(point: &mut Point) {
         point.y = point.y.negate(1).normalize()
     }
@@ -315,6 +323,12 @@ impl TimeSensitive for ConstantTime {
         points: A,
         scalars: B,
     ) -> Point {
+        let mut points = points.peekable();
+        let mut scalars = scalars.peekable();
+        // the backend can't handle empty input
+        if points.peek().is_none() || scalars.peek().is_none() {
+            return ProjectivePoint::identity();
+        }
         secp256kfun_k256_backend::lincomb_iter(points, scalars)
     }
 }
@@ -359,6 +373,10 @@ impl TimeSensitive for VariableTime {
         ConstantTime::point_add_norm_point(lhs, rhs)
     }
 
+    fn point_double(point: &Point) -> Point {
+        ConstantTime::point_double(point)
+    }
+
     fn any_point_neg(point: &mut Point) {
         ConstantTime::any_point_neg(point)
     }
@@ -456,6 +474,36 @@ impl TimeSensitive for VariableTime {
     }
 }
 
+impl ConstantTime {
+    /// Normalizes all the points with a single field inversion (Montgomery's trick).
+    #[cfg(feature = "alloc")]
+    pub fn point_normalize_batch(points: &mut [Point]) {
+        // substitute one for the z of points at infinity so the product stays invertible. They
+        // are left alone just like in `point_normalize`.
+        let z_or_one = |point: &Point| {
+            FieldElement::conditional_select(&point.z, &FieldElement::one(), point.is_identity())
+        };
+        let mut acc = FieldElement::one();
+        let mut prefixes = Vec::with_capacity(points.len());
+        for point in points.iter() {
+            prefixes.push(acc);
+            acc *= z_or_one(point);
+        }
+        let mut inv = acc.invert().unwrap();
+        for (point, prefix) in points.iter_mut().zip(prefixes).rev() {
+            let z = z_or_one(point);
+            let zinv = inv * &prefix;
+            inv *= z;
+            let was_zero = point.is_identity();
+            point.x *= zinv;
+            point.y *= zinv;
+            point.x = point.x.normalize();
+            point.y = point.y.normalize();
+            point.z.conditional_assign(&FieldElement::one(), !was_zero);
+        }
+    }
+}
+
 impl VariableTime {
     pub fn point_x_eq_scalar(point: &Point, scalar: &Scalar) -> bool {
         if point.is_identity().into() {
@@ -37,6 +37,7 @@ pub trait TimeSensitive {
     fn point_eq_xonly(lhs: &Point, rhs: &XOnly) -> bool;
     fn point_add_point(lhs: &Point, rhs: &Point) -> Point;
     fn point_add_norm_point(lhs: &Point, rhs: &Point) -> Point;
+    fn point_double(point: &Point) -> Point;
     fn point_sub_point(lhs: &Point, rhs: &Point) -> Point {
         let mut rhs = rhs.clone();
         Self::point_neg(&mut rhs);
@@ -0,0 +1,318 @@
+//! Base58Check encoding for legacy Bitcoin key formats.
+//!
+//! Wallets from before segwit export secret keys in _Wallet Import Format_ ([`Wif`]) and some
+//! protocols show compressed public keys in base58check. This module lets you read and write
+//! these so migrating away from those wallets can be done with this crate alone.
+//!
+//! Only available with the `base58` feature.
+//!
+//! ```
+//! use secp256kfun::base58::{Network, Wif};
+//! let wif = Wif::decode("KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617").unwrap();
+//! assert_eq!(wif.network, Network::Mainnet);
+//! assert!(wif.compressed);
+//! assert_eq!(
+//!     wif.encode(),
+//!     "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617"
+//! );
+//! ```
+use crate::{marker::*, Point, Scalar};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+use digest::Digest;
+#[cfg(feature = "std")]
+use std::{string::String, vec::Vec};
+
+/// Why a string couldn't be decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base58Error {
+    /// There was a character that isn't in the base58 alphabet.
+    InvalidCharacter,
+    /// The checksum didn't match.
+    InvalidChecksum,
+    /// The decoded data was not the right length for the target type.
+    InvalidLength,
+    /// The decoded data did not encode a valid value for the target type (e.g. an unknown version
+    /// byte or an invalid point).
+    InvalidEncoding,
+}
+
+impl fmt::Display for Base58Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Base58Error::*;
+        match self {
+            InvalidCharacter => write!(f, "base58 string has an invalid character"),
+            InvalidChecksum => write!(f, "base58check string has an invalid checksum"),
+            InvalidLength => write!(f, "base58 data has the wrong length"),
+            InvalidEncoding => write!(f, "base58 data did not encode the expected type"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Base58Error {}
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+fn checksum(bytes: &[u8]) -> [u8; 4] {
+    let hash = sha2::Sha256::digest(sha2::Sha256::digest(bytes));
+    let mut checksum = [0u8; 4];
+    checksum.copy_from_slice(&hash[..4]);
+    checksum
+}
+
+/// Encodes `bytes` as base58 without a checksum.
+pub fn encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    // little-endian base58 digits
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for byte in &bytes[zeros..] {
+        let mut carry = u32::from(*byte);
+        for digit in digits.iter_mut() {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    core::iter::repeat('1')
+        .take(zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|digit| ALPHABET[*digit as usize] as char),
+        )
+        .collect()
+}
+
+/// Decodes a base58 string without a checksum.
+pub fn decode(string: &str) -> Result<Vec<u8>, Base58Error> {
+    let zeros = string.bytes().take_while(|c| *c == b'1').count();
+    // little-endian bytes
+    let mut bytes: Vec<u8> = Vec::with_capacity(string.len() * 733 / 1000 + 1);
+    for c in string.bytes().skip(zeros) {
+        let mut carry = ALPHABET
+            .iter()
+            .position(|x| *x == c)
+            .ok_or(Base58Error::InvalidCharacter)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    Ok(core::iter::repeat(0)
+        .take(zeros)
+        .chain(bytes.into_iter().rev())
+        .collect())
+}
+
+/// Encodes `bytes` followed by a four byte double SHA256 checksum as base58.
+///
+/// # Examples
+/// ```
+/// use secp256kfun::base58;
+/// let encoded = base58::encode_check(b"hello");
+/// assert_eq!(base58::decode_check(&encoded).unwrap(), b"hello");
+/// ```
+pub fn encode_check(bytes: &[u8]) -> String {
+    let mut with_checksum = Vec::with_capacity(bytes.len() + 4);
+    with_checksum.extend_from_slice(bytes);
+    with_checksum.extend_from_slice(&checksum(bytes));
+    encode(&with_checksum)
+}
+
+/// Decodes a base58check string and checks its checksum.
+pub fn decode_check(string: &str) -> Result<Vec<u8>, Base58Error> {
+    let mut bytes = decode(string)?;
+    if bytes.len() < 4 {
+        return Err(Base58Error::InvalidLength);
+    }
+    let expected = bytes.split_off(bytes.len() - 4);
+    if checksum(&bytes)[..] != expected[..] {
+        return Err(Base58Error::InvalidChecksum);
+    }
+    Ok(bytes)
+}
+
+/// Encodes a point's compressed encoding as base58check.
+///
+/// # Examples
+/// ```
+/// use secp256kfun::{base58, marker::*, G};
+/// let encoded = base58::encode_point(G);
+/// assert_eq!(
+///     encoded,
+///     "5p78kHbL33Rn3JWkTWRE2B9uz6gy4r1KbfAKLNQGE3ovKxJ2W1"
+/// );
+/// assert_eq!(base58::decode_point(&encoded).unwrap(), *G);
+/// ```
+pub fn encode_point<T: Normalized, S>(point: &Point<T, S, NonZero>) -> String {
+    encode_check(&point.to_bytes())
+}
+
+/// Decodes a point from what [`encode_point`] produces.
+pub fn decode_point(string: &str) -> Result<Point, Base58Error> {
+    let bytes = decode_check(string)?;
+    if bytes.len() != 33 {
+        return Err(Base58Error::InvalidLength);
+    }
+    Point::from_slice(&bytes).ok_or(Base58Error::InvalidEncoding)
+}
+
+/// The network a [`Wif`] key is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    /// Bitcoin mainnet (version byte `0x80`).
+    Mainnet,
+    /// Any of the Bitcoin test networks (version byte `0xef`).
+    Testnet,
+}
+
+impl Network {
+    fn wif_version(self) -> u8 {
+        match self {
+            Network::Mainnet => 0x80,
+            Network::Testnet => 0xef,
+        }
+    }
+}
+
+/// A secret key in _Wallet Import Format_.
+///
+/// `compressed` says whether the wallet used the compressed encoding of the public key. It
+/// doesn't change the secret key but legacy wallets derive different addresses from the same key
+/// depending on it so you'll need it to find the funds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wif {
+    /// The secret key.
+    pub secret_key: Scalar,
+    /// The network the key is for.
+    pub network: Network,
+    /// Whether the corresponding public key should be compressed.
+    pub compressed: bool,
+}
+
+impl Wif {
+    /// Decodes a WIF string.
+    pub fn decode(string: &str) -> Result<Self, Base58Error> {
+        let bytes = decode_check(string)?;
+        let compressed = match bytes.len() {
+            33 => false,
+            34 if bytes[33] == 0x01 => true,
+            34 => return Err(Base58Error::InvalidEncoding),
+            _ => return Err(Base58Error::InvalidLength),
+        };
+        let network = [Network::Mainnet, Network::Testnet]
+            .iter()
+            .find(|network| network.wif_version() == bytes[0])
+            .cloned()
+            .ok_or(Base58Error::InvalidEncoding)?;
+        let mut secret_key = [0u8; 32];
+        secret_key.copy_from_slice(&bytes[1..33]);
+        let secret_key = Scalar::from_bytes(secret_key)
+            .and_then(|secret_key| secret_key.mark::<NonZero>())
+            .ok_or(Base58Error::InvalidEncoding)?;
+        Ok(Wif {
+            secret_key,
+            network,
+            compressed,
+        })
+    }
+
+    /// Encodes the key as a WIF string.
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::with_capacity(34);
+        bytes.push(self.network.wif_version());
+        bytes.extend_from_slice(&self.secret_key.to_bytes());
+        if self.compressed {
+            bytes.push(0x01);
+        }
+        encode_check(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wif_test_vectors() {
+        let secret_key = Scalar::from_bytes(
+            crate::hex::decode_array(
+                "0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d",
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .mark::<NonZero>()
+        .unwrap();
+        for (string, network, compressed) in [
+            (
+                "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ",
+                Network::Mainnet,
+                false,
+            ),
+            (
+                "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617",
+                Network::Mainnet,
+                true,
+            ),
+            (
+                "cMzLdeGd5vEqxB8B6VFQoRopQ3sLAAvEzDAoQgvX54xwofSWj1fx",
+                Network::Testnet,
+                true,
+            ),
+        ] {
+            let wif = Wif {
+                secret_key: secret_key.clone(),
+                network,
+                compressed,
+            };
+            assert_eq!(Wif::decode(string), Ok(wif.clone()));
+            assert_eq!(wif.encode(), string);
+        }
+
+        assert_eq!(
+            Wif::decode("KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98618"),
+            Err(Base58Error::InvalidChecksum)
+        );
+        assert_eq!(
+            Wif::decode("KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP9861l"),
+            Err(Base58Error::InvalidCharacter)
+        );
+    }
+
+    #[test]
+    fn leading_zeros() {
+        assert_eq!(encode_check(&[0, 0, 1]), "11BwW2qR");
+        assert_eq!(decode_check("11BwW2qR"), Ok(vec![0, 0, 1]));
+        assert_eq!(decode(""), Ok(vec![]));
+        assert_eq!(encode(&[]), "");
+    }
+
+    #[test]
+    fn decode_point_checks_length() {
+        let point = Point::random(&mut rand::thread_rng());
+        assert_eq!(decode_point(&encode_point(&point)), Ok(point));
+        assert_eq!(
+            decode_point(&encode_check(&point.to_bytes_uncompressed())),
+            Err(Base58Error::InvalidLength)
+        );
+        let mut bad_prefix = point.to_bytes();
+        bad_prefix[0] = 0x04;
+        assert_eq!(
+            decode_point(&encode_check(&bad_prefix)),
+            Err(Base58Error::InvalidEncoding)
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Base64 encoding as specified in [RFC 4648].
+//!
+//! [`Variant::Standard`] is the padded encoding with `+` and `/` used by PEM and [`Variant::Url`]
+//! is the unpadded URL and filename safe encoding with `-` and `_` used by JWTs.
+//!
+//! Decoding is strict. It only accepts the string [`encode`] would produce for the decoded bytes:
+//! there can't be any whitespace, the padding has to be exactly right and the unused bits of the
+//! last character have to be zero. This means every byte string has only one encoding which
+//! matters when the encoding is signed or used as an identifier.
+//!
+//! Only available with the `alloc` feature.
+//!
+//! ```
+//! use secp256kfun::base64::{self, Variant};
+//! assert_eq!(base64::encode(b"\xfb\xff", Variant::Standard), "+/8=");
+//! assert_eq!(base64::encode(b"\xfb\xff", Variant::Url), "-_8");
+//! assert_eq!(
+//!     base64::decode("-_8", Variant::Url).as_deref(),
+//!     Some(&b"\xfb\xff"[..])
+//! );
+//! // the last character has unused bits set
+//! assert_eq!(base64::decode("-_9", Variant::Url), None);
+//! ```
+//!
+//! [RFC 4648]: https://datatracker.ietf.org/doc/html/rfc4648
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{string::String, vec::Vec};
+
+/// Which alphabet (and padding) to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// The standard alphabet ending in `+` and `/` padded with `=` to a multiple of four
+    /// characters.
+    Standard,
+    /// The URL and filename safe alphabet ending in `-` and `_` without padding.
+    Url,
+}
+
+impl Variant {
+    fn alphabet(self) -> &'static [u8; 64] {
+        match self {
+            Variant::Standard => {
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"
+            }
+            Variant::Url => b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+        }
+    }
+
+    fn is_padded(self) -> bool {
+        self == Variant::Standard
+    }
+}
+
+/// Encodes `bytes` as base64.
+pub fn encode(bytes: &[u8], variant: Variant) -> String {
+    let alphabet = variant.alphabet();
+    let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let mut block = [0u8; 3];
+        block[..chunk.len()].copy_from_slice(chunk);
+        let n = u32::from(block[0]) << 16 | u32::from(block[1]) << 8 | u32::from(block[2]);
+        for i in 0..=chunk.len() {
+            out.push(alphabet[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+        if variant.is_padded() {
+            for _ in chunk.len()..3 {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes base64.
+///
+/// Returns `None` if `string` isn't exactly what [`encode`] would produce for some bytes.
+pub fn decode(string: &str, variant: Variant) -> Option<Vec<u8>> {
+    let alphabet = variant.alphabet();
+    let mut data = string.as_bytes();
+    if variant.is_padded() {
+        if data.len() % 4 != 0 {
+            return None;
+        }
+        let padding = data.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 {
+            return None;
+        }
+        data = &data[..data.len() - padding];
+    }
+    if data.len() % 4 == 1 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        let mut n = 0u32;
+        for c in chunk {
+            let value = alphabet.iter().position(|a| a == c)?;
+            n = n << 6 | value as u32;
+        }
+        n <<= 6 * (4 - chunk.len()) as u32;
+        let bytes = n.to_be_bytes();
+        let len = chunk.len() - 1;
+        // the bits of the last character that don't make up a whole byte must be zero
+        if bytes[1 + len..].iter().any(|byte| *byte != 0) {
+            return None;
+        }
+        out.extend_from_slice(&bytes[1..1 + len]);
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rfc4648_test_vectors() {
+        for (bytes, standard, url) in [
+            (&b""[..], "", ""),
+            (b"f", "Zg==", "Zg"),
+            (b"fo", "Zm8=", "Zm8"),
+            (b"foo", "Zm9v", "Zm9v"),
+            (b"foob", "Zm9vYg==", "Zm9vYg"),
+            (b"fooba", "Zm9vYmE=", "Zm9vYmE"),
+            (b"foobar", "Zm9vYmFy", "Zm9vYmFy"),
+            (b"\xfb\xff", "+/8=", "-_8"),
+        ] {
+            assert_eq!(encode(bytes, Variant::Standard), standard);
+            assert_eq!(decode(standard, Variant::Standard).as_deref(), Some(bytes));
+            assert_eq!(encode(bytes, Variant::Url), url);
+            assert_eq!(decode(url, Variant::Url).as_deref(), Some(bytes));
+        }
+    }
+
+    #[test]
+    fn only_canonical_encodings_decode() {
+        for string in [
+            "Zg=", "Zg", "Zg==Zg==", "Z===", "Z!==", "Zh==", "Zm9=", " Zg==", "Zg==\n", "-_8=",
+        ] {
+            assert_eq!(decode(string, Variant::Standard), None, "{}", string);
+        }
+        for string in ["Zg==", "Z", "Zh", "Zm9", "+/8", "Zm9v Yg"] {
+            assert_eq!(decode(string, Variant::Url), None, "{}", string);
+        }
+    }
+}
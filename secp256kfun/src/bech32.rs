@@ -0,0 +1,446 @@
+//! Bech32 and bech32m encoding as specified in [BIP-173] and [BIP-350].
+//!
+//! These encode bytes with a _human readable part_ (HRP) that says what they are and a checksum
+//! that catches typos. Use them with the bytes of any [`ToBytes`] type and decode with
+//! [`decode_array`] followed by the type's `from_bytes`. For example [NIP-19] Nostr keys use
+//! plain bech32:
+//!
+//! ```
+//! use secp256kfun::{bech32, marker::*, Point, Scalar};
+//! let npub = "npub10elfcs4fr0l0r8af98jlmgdh9c8tcxjvz9qkw038js35mp4dma8qzvjptg";
+//! let bytes = bech32::decode_array::<32>("npub", npub, bech32::Variant::Bech32).unwrap();
+//! let public_key = Point::<EvenY>::from_xonly_bytes(bytes).unwrap();
+//! assert_eq!(
+//!     bech32::encode(
+//!         "npub",
+//!         &public_key.to_xonly_bytes(),
+//!         bech32::Variant::Bech32
+//!     ),
+//!     npub
+//! );
+//!
+//! let secret_key = Scalar::random(&mut rand::thread_rng());
+//! let nsec = bech32::encode("nsec", &secret_key.to_bytes(), bech32::Variant::Bech32);
+//! let bytes = bech32::decode_array::<32>("nsec", &nsec, bech32::Variant::Bech32).unwrap();
+//! assert_eq!(Scalar::from_bytes(bytes), Some(secret_key.mark::<Zero>()));
+//! ```
+//!
+//! Taproot addresses are bech32m with a witness version in front of the data so they have their
+//! own functions: [`encode_taproot_address`] and [`decode_taproot_address`].
+//!
+//! The 90 character limit from [BIP-173] is not enforced since many users of bech32 (like Nostr)
+//! don't follow it.
+//!
+//! [BIP-173]: https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki
+//! [BIP-350]: https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki
+//! [NIP-19]: https://github.com/nostr-protocol/nips/blob/master/19.md
+//! [`ToBytes`]: crate::ToBytes
+use crate::{marker::*, Point};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::{string::String, vec::Vec};
+
+/// Which checksum to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// The original checksum from [BIP-173].
+    ///
+    /// [BIP-173]: https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki
+    Bech32,
+    /// The improved checksum from [BIP-350].
+    ///
+    /// [BIP-350]: https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(self) -> u32 {
+        match self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => 0x2bc830a3,
+        }
+    }
+}
+
+/// Why a string couldn't be decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bech32Error {
+    /// There was no `1` separating the human readable part from the data.
+    MissingSeparator,
+    /// There was a character that isn't allowed.
+    InvalidCharacter,
+    /// The string had both upper and lower case characters.
+    MixedCase,
+    /// The checksum was wrong (or it was the other [`Variant`]).
+    InvalidChecksum,
+    /// The human readable part was not the one expected.
+    WrongHrp,
+    /// The data was not the right length for the target type.
+    InvalidLength,
+    /// The data had non-zero padding bits.
+    InvalidPadding,
+    /// The data did not encode a valid value for the target type.
+    InvalidEncoding,
+}
+
+impl fmt::Display for Bech32Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Bech32Error::*;
+        match self {
+            MissingSeparator => write!(f, "bech32 string has no separator"),
+            InvalidCharacter => write!(f, "bech32 string has an invalid character"),
+            MixedCase => write!(f, "bech32 string has mixed case"),
+            InvalidChecksum => write!(f, "bech32 string has an invalid checksum"),
+            WrongHrp => write!(f, "bech32 string has the wrong human readable part"),
+            InvalidLength => write!(f, "bech32 data has the wrong length"),
+            InvalidPadding => write!(f, "bech32 data has invalid padding"),
+            InvalidEncoding => write!(f, "bech32 data did not encode the expected type"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Bech32Error {}
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+fn polymod(values: impl Iterator<Item = u8>) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut chk = 1u32;
+    for value in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ u32::from(value);
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> impl Iterator<Item = u8> + Clone + '_ {
+    let lower = hrp.bytes().map(|c| c.to_ascii_lowercase());
+    lower
+        .clone()
+        .map(|c| c >> 5)
+        .chain(core::iter::once(0))
+        .chain(lower.map(|c| c & 0x1f))
+}
+
+/// Splits `bytes` into five bit groups padding the last one with zeros.
+#[cfg_attr(not(any(feature = "alloc", feature = "sha2")), allow(dead_code))]
+fn to_5bit(bytes: &[u8]) -> impl Iterator<Item = u8> + Clone + '_ {
+    let groups = (bytes.len() * 8 + 4) / 5;
+    (0..groups).map(move |i| {
+        let bit = i * 5;
+        let window =
+            u16::from(bytes[bit / 8]) << 8 | u16::from(*bytes.get(bit / 8 + 1).unwrap_or(&0));
+        ((window >> (11 - bit % 8)) & 0x1f) as u8
+    })
+}
+
+/// Writes the bech32 encoding of `data` (already in five bit groups).
+#[cfg_attr(not(any(feature = "alloc", feature = "sha2")), allow(dead_code))]
+pub(crate) fn write(
+    f: &mut impl fmt::Write,
+    hrp: &str,
+    data: impl Iterator<Item = u8> + Clone,
+    variant: Variant,
+) -> fmt::Result {
+    let checksum = polymod(
+        hrp_expand(hrp)
+            .chain(data.clone())
+            .chain(core::iter::repeat(0).take(6)),
+    ) ^ variant.constant();
+    for c in hrp.chars() {
+        f.write_char(c.to_ascii_lowercase())?;
+    }
+    f.write_char('1')?;
+    let checksum = (0..6).map(|i| ((checksum >> (5 * (5 - i))) & 0x1f) as u8);
+    for value in data.chain(checksum) {
+        f.write_char(CHARSET[value as usize] as char)?;
+    }
+    Ok(())
+}
+
+/// Writes the bech32 encoding of `bytes`.
+#[cfg(any(feature = "alloc", feature = "sha2"))]
+pub(crate) fn write_bytes(
+    f: &mut impl fmt::Write,
+    hrp: &str,
+    bytes: &[u8],
+    variant: Variant,
+) -> fmt::Result {
+    write(f, hrp, to_5bit(bytes), variant)
+}
+
+/// Checks the checksum and splits `string` into its human readable part and data characters
+/// (without the checksum).
+fn parse(string: &str, variant: Variant) -> Result<(&str, &[u8]), Bech32Error> {
+    let has_lower = string.bytes().any(|c| c.is_ascii_lowercase());
+    let has_upper = string.bytes().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Err(Bech32Error::MixedCase);
+    }
+    let separator = string.rfind('1').ok_or(Bech32Error::MissingSeparator)?;
+    let (hrp, data) = (&string[..separator], &string.as_bytes()[separator + 1..]);
+    if hrp.is_empty() || data.len() < 6 {
+        return Err(Bech32Error::InvalidLength);
+    }
+    if hrp.bytes().any(|c| !(33..=126).contains(&c)) {
+        return Err(Bech32Error::InvalidCharacter);
+    }
+    if data.iter().any(|c| char_value(*c).is_none()) {
+        return Err(Bech32Error::InvalidCharacter);
+    }
+    if polymod(hrp_expand(hrp).chain(data.iter().map(|c| char_value(*c).unwrap())))
+        != variant.constant()
+    {
+        return Err(Bech32Error::InvalidChecksum);
+    }
+    Ok((hrp, &data[..data.len() - 6]))
+}
+
+fn char_value(c: u8) -> Option<u8> {
+    CHARSET
+        .iter()
+        .position(|x| *x == c.to_ascii_lowercase())
+        .map(|value| value as u8)
+}
+
+/// Converts already validated data characters back into bytes.
+fn from_5bit(data: &[u8], mut push: impl FnMut(u8)) -> Result<(), Bech32Error> {
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for c in data {
+        acc = (acc << 5) | u32::from(char_value(*c).expect("validated"));
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    if bits >= 5 || acc != 0 {
+        return Err(Bech32Error::InvalidPadding);
+    }
+    Ok(())
+}
+
+fn from_5bit_array<const N: usize>(data: &[u8]) -> Result<[u8; N], Bech32Error> {
+    if data.len() * 5 / 8 != N {
+        return Err(Bech32Error::InvalidLength);
+    }
+    let mut bytes = [0u8; N];
+    let mut i = 0;
+    from_5bit(data, |byte| {
+        bytes[i] = byte;
+        i += 1;
+    })?;
+    Ok(bytes)
+}
+
+#[cfg(feature = "alloc")]
+/// Encodes `bytes` with the human readable part `hrp`.
+///
+/// The output is always lower case.
+///
+/// # Examples
+/// ```
+/// use secp256kfun::{bech32, G};
+/// let encoded = bech32::encode("point", &G.to_bytes(), bech32::Variant::Bech32m);
+/// assert!(encoded.starts_with("point1"));
+/// ```
+pub fn encode(hrp: &str, bytes: &[u8], variant: Variant) -> String {
+    let mut string = String::new();
+    write_bytes(&mut string, hrp, bytes, variant).unwrap();
+    string
+}
+
+#[cfg(feature = "alloc")]
+/// Decodes a string into its human readable part (in lower case) and bytes.
+pub fn decode(string: &str, variant: Variant) -> Result<(String, Vec<u8>), Bech32Error> {
+    let (hrp, data) = parse(string, variant)?;
+    let mut bytes = Vec::with_capacity(data.len() * 5 / 8);
+    from_5bit(data, |byte| bytes.push(byte))?;
+    Ok((hrp.to_ascii_lowercase(), bytes))
+}
+
+/// Decodes a string with the human readable part `hrp` into a fixed length array.
+///
+/// # Examples
+/// ```
+/// use secp256kfun::{bech32, marker::*, Point, G};
+/// let encoded = bech32::encode("point", &G.to_bytes(), bech32::Variant::Bech32m);
+/// let bytes = bech32::decode_array::<33>("point", &encoded, bech32::Variant::Bech32m).unwrap();
+/// assert_eq!(
+///     Point::<Normal, Public, NonZero>::from_bytes(bytes).unwrap(),
+///     *G
+/// );
+/// assert_eq!(
+///     bech32::decode_array::<33>("key", &encoded, bech32::Variant::Bech32m),
+///     Err(bech32::Bech32Error::WrongHrp)
+/// );
+/// ```
+pub fn decode_array<const N: usize>(
+    hrp: &str,
+    string: &str,
+    variant: Variant,
+) -> Result<[u8; N], Bech32Error> {
+    let (found_hrp, data) = parse(string, variant)?;
+    if !found_hrp.eq_ignore_ascii_case(hrp) {
+        return Err(Bech32Error::WrongHrp);
+    }
+    from_5bit_array(data)
+}
+
+#[cfg(feature = "alloc")]
+/// Encodes a [BIP-341] output key as a taproot (segwit version 1) address.
+///
+/// `hrp` is `"bc"` for mainnet, `"tb"` for testnet and signet and `"bcrt"` for regtest.
+///
+/// # Examples
+/// ```
+/// use secp256kfun::{bech32, marker::*, G};
+/// let (output_key, _) = G.clone().mark::<Normal>().into_point_with_even_y();
+/// let address = bech32::encode_taproot_address("bc", &output_key);
+/// assert_eq!(
+///     address,
+///     "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"
+/// );
+/// assert_eq!(
+///     bech32::decode_taproot_address("bc", &address),
+///     Ok(output_key)
+/// );
+/// ```
+///
+/// [BIP-341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+pub fn encode_taproot_address(hrp: &str, output_key: &Point<EvenY, impl Secrecy>) -> String {
+    let mut string = String::new();
+    let program = output_key.to_xonly_bytes();
+    let data = core::iter::once(1).chain(to_5bit(&program));
+    write(&mut string, hrp, data, Variant::Bech32m).unwrap();
+    string
+}
+
+/// Decodes a taproot address with the human readable part `hrp` into its output key.
+///
+/// Returns [`Bech32Error::InvalidEncoding`] if the address is not for segwit version 1 or the
+/// output key is not a valid x-only point.
+pub fn decode_taproot_address(hrp: &str, address: &str) -> Result<Point<EvenY>, Bech32Error> {
+    let (found_hrp, data) = parse(address, Variant::Bech32m)?;
+    if !found_hrp.eq_ignore_ascii_case(hrp) {
+        return Err(Bech32Error::WrongHrp);
+    }
+    let (version, program) = data.split_first().ok_or(Bech32Error::InvalidLength)?;
+    if char_value(*version) != Some(1) {
+        return Err(Bech32Error::InvalidEncoding);
+    }
+    let program = from_5bit_array::<32>(program)?;
+    Point::from_xonly_bytes(program).ok_or(Bech32Error::InvalidEncoding)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bip350_test_vectors() {
+        for valid in [
+            "A1LQFN3A",
+            "a1lqfn3a",
+            "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx",
+            "split1checkupstagehandshakeupstreamerranterredcaperredlc445v",
+            "?1v759aa",
+        ] {
+            assert!(parse(valid, Variant::Bech32m).is_ok(), "{}", valid);
+            assert_eq!(
+                parse(valid, Variant::Bech32),
+                Err(Bech32Error::InvalidChecksum)
+            );
+        }
+        assert_eq!(
+            parse("qyrz8wqd2c9m", Variant::Bech32m),
+            Err(Bech32Error::MissingSeparator)
+        );
+        assert_eq!(
+            parse("1qyrz8wqd2c9m", Variant::Bech32m),
+            Err(Bech32Error::InvalidLength)
+        );
+        assert_eq!(
+            parse("y1b0jsk6g", Variant::Bech32m),
+            Err(Bech32Error::InvalidCharacter)
+        );
+        assert_eq!(
+            parse("M1VUXWEZ", Variant::Bech32m),
+            Err(Bech32Error::InvalidChecksum)
+        );
+        assert_eq!(
+            parse("A1lqfn3a", Variant::Bech32m),
+            Err(Bech32Error::MixedCase)
+        );
+    }
+
+    #[test]
+    fn nip19_test_vectors() {
+        let npub = "npub10elfcs4fr0l0r8af98jlmgdh9c8tcxjvz9qkw038js35mp4dma8qzvjptg";
+        let nsec = "nsec1vl029mgpspedva04g90vltkh6fvh240zqtv9k0t9af8935ke9laqsnlfe5";
+        let public_key = crate::hex::decode_array::<32>(
+            "7e7e9c42a91bfef19fa929e5fda1b72e0ebc1a4c1141673e2794234d86addf4e",
+        )
+        .unwrap();
+        let secret_key = crate::hex::decode_array::<32>(
+            "67dea2ed018072d675f5415ecfaed7d2597555e202d85b3d65ea4e58d2d92ffa",
+        )
+        .unwrap();
+        assert_eq!(
+            decode_array::<32>("npub", npub, Variant::Bech32),
+            Ok(public_key)
+        );
+        assert_eq!(
+            decode_array::<32>("nsec", nsec, Variant::Bech32),
+            Ok(secret_key)
+        );
+        assert_eq!(
+            decode_array::<33>("npub", npub, Variant::Bech32),
+            Err(Bech32Error::InvalidLength)
+        );
+        assert_eq!(
+            decode_array::<32>("npub", npub, Variant::Bech32m),
+            Err(Bech32Error::InvalidChecksum)
+        );
+        #[cfg(feature = "alloc")]
+        {
+            assert_eq!(encode("npub", &public_key, Variant::Bech32), npub);
+            assert_eq!(encode("nsec", &secret_key, Variant::Bech32), nsec);
+            assert_eq!(
+                decode(&npub.to_uppercase(), Variant::Bech32),
+                Ok(("npub".into(), public_key.to_vec()))
+            );
+        }
+    }
+
+    #[test]
+    fn taproot_addresses() {
+        assert_eq!(
+            decode_taproot_address(
+                "bc",
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"
+            ),
+            Ok((*crate::G).mark::<Normal>().into_point_with_even_y().0)
+        );
+        // segwit v0 addresses use bech32 so fail the checksum
+        assert_eq!(
+            decode_taproot_address("bc", "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"),
+            Err(Bech32Error::InvalidChecksum)
+        );
+        // a segwit version 2 address
+        assert_eq!(
+            decode_taproot_address("bc", "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs"),
+            Err(Bech32Error::InvalidEncoding)
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Commit-then-reveal with domain separated hashes.
+//!
+//! Many protocols have each party commit to a value (usually a point) before anyone reveals
+//! theirs so nobody can choose their value after seeing the others'. Examples are MuSig1 style
+//! nonce exchange, some distributed key generation protocols and sealed bid auctions. A
+//! [`Committer`] holds a hash tagged for one protocol and [`Commitment::to`] and
+//! [`Commitment::open`] do the hashing so each protocol doesn't have to roll its own.
+//!
+//! Use [`Committer::bind`] to bind commitments to whatever they must not be replayed in, like the
+//! session id and the committing party's public key. Otherwise another party could copy your
+//! commitment and then copy your value once you reveal it.
+//!
+//! A commitment only hides the value if it's hard to guess (e.g. a fresh nonce). To commit to
+//! something guessable like a bid, also bind a random salt and reveal it along with the value.
+//!
+//! # Examples
+//!
+//! ```
+//! use secp256kfun::{
+//!     commit::{Commitment, Committer},
+//!     g, Scalar, G,
+//! };
+//! let committer = Committer::<sha2::Sha256>::new(b"my-protocol/nonce-commit");
+//! let alice_key = g!({ Scalar::random(&mut rand::thread_rng()) } * G).normalize();
+//! let alice = committer.bind(&alice_key.to_bytes()).bind(b"session-42");
+//! let nonce = g!({ Scalar::random(&mut rand::thread_rng()) } * G).normalize();
+//! // round 1: send the commitment
+//! let commitment = Commitment::to(&alice, nonce);
+//! // round 2: reveal the nonce
+//! assert!(commitment.open(&alice, nonce));
+//! // the same nonce in a different session doesn't open it
+//! let other_session = committer.bind(&alice_key.to_bytes()).bind(b"session-43");
+//! assert!(!commitment.open(&other_session, nonce));
+//! ```
+use crate::{
+    digest::{generic_array::typenum::U32, Digest},
+    hash::{HashAdd, HashInto, Tagged},
+};
+
+/// Makes [`Commitment`]s for one protocol.
+///
+/// See the [module documentation](crate::commit).
+#[derive(Clone, Debug)]
+pub struct Committer<H> {
+    hash: H,
+}
+
+impl<H: Tagged> Committer<H> {
+    /// Creates a committer whose commitments are domain separated by `tag`.
+    ///
+    /// The tag should name your protocol and what is being committed to so the commitments can't
+    /// be confused with those of another protocol.
+    pub fn new(tag: &[u8]) -> Self {
+        Committer {
+            hash: H::default().tagged(tag),
+        }
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone> Committer<H> {
+    /// Returns a committer whose commitments are also bound to `context`.
+    ///
+    /// The context is length prefixed so binding `a` then `b` is never the same as binding some
+    /// other split of the same bytes.
+    pub fn bind(&self, context: &[u8]) -> Self {
+        let len = (context.len() as u64).to_be_bytes();
+        Committer {
+            hash: self.hash.clone().add(&len[..]).add(context),
+        }
+    }
+}
+
+/// A commitment to a value created with [`Commitment::to`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Commitment(pub [u8; 32]);
+
+impl Commitment {
+    /// Commits to `value` (typically a [`Point`]).
+    ///
+    /// [`Point`]: crate::Point
+    pub fn to<H: Digest<OutputSize = U32> + Clone>(
+        committer: &Committer<H>,
+        value: impl HashInto,
+    ) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(committer.hash.clone().add(value).finalize().as_slice());
+        Commitment(bytes)
+    }
+
+    /// Checks that the revealed `value` is what was committed to.
+    #[must_use]
+    pub fn open<H: Digest<OutputSize = U32> + Clone>(
+        &self,
+        committer: &Committer<H>,
+        value: impl HashInto,
+    ) -> bool {
+        Commitment::to(committer, value) == *self
+    }
+
+    /// Serializes the commitment as 32 bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Deserializes the commitment from 32 bytes.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Commitment(bytes)
+    }
+}
+
+crate::impl_fromstr_deserialize! {
+    name => "commitment",
+    fn from_bytes(bytes: [u8;32]) -> Option<Commitment> {
+        Some(Commitment::from_bytes(bytes))
+    }
+}
+
+crate::impl_display_debug_serialize! {
+    fn to_bytes(commitment: &Commitment) -> [u8;32] {
+        commitment.to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Point;
+    use sha2::Sha256;
+    #[cfg(feature = "std")]
+    use std::string::ToString;
+
+    #[test]
+    fn commitments_are_bound_to_tag_context_and_value() {
+        let committer = Committer::<Sha256>::new(b"test");
+        let point = Point::random(&mut rand::thread_rng());
+        let commitment = Commitment::to(&committer.bind(b"ab"), point);
+        assert!(commitment.open(&committer.bind(b"ab"), point));
+        assert!(!commitment.open(
+            &committer.bind(b"ab"),
+            Point::random(&mut rand::thread_rng())
+        ));
+        assert!(!commitment.open(&committer.bind(b"a").bind(b"b"), point));
+        assert!(!commitment.open(&committer, point));
+        assert!(!commitment.open(&Committer::<Sha256>::new(b"other").bind(b"ab"), point));
+        #[cfg(feature = "std")]
+        assert_eq!(
+            commitment.to_string().parse::<Commitment>().unwrap(),
+            commitment
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Elligator-squared encoding of points as uniformly random bytes.
+//!
+//! [`encode`] turns a point into 64 bytes that are indistinguishable from uniformly random bytes
+//! and [`decode`] turns _any_ 64 bytes back into a point. This is useful for protocols that need to
+//! hide the fact that they are exchanging public keys or nonces, e.g. censorship resistant
+//! transports where a 33-byte compressed point would stand out.
+//!
+//! The encoding is Elligator-squared instantiated with the SwiftEC map, i.e. _ElligatorSwift_ as
+//! specified in [BIP-324]. The x-coordinate decoded from 64 bytes is the same as the one BIP-324
+//! decodes. BIP-324 only transmits x-coordinates so to recover the full point we additionally take
+//! the parity of the y-coordinate from the parity of `t` (the last 32 bytes). Since the SwiftEC map
+//! only depends on `t²` this does not change the decoded x-coordinate or the distribution of the
+//! output.
+//!
+//! # Examples
+//!
+//! ```
+//! use secp256kfun::{elligator, Point};
+//! let point = Point::random(&mut rand::thread_rng());
+//! let bytes = elligator::encode(&point, &mut rand::thread_rng());
+//! assert_eq!(elligator::decode(&bytes), point);
+//! ```
+//!
+//! [BIP-324]: https://github.com/bitcoin/bips/blob/master/bip-0324.mediawiki
+use crate::{
+    digest::{generic_array::typenum::U32, Digest},
+    hash::HashAdd,
+    marker::*,
+    FieldElement, Point,
+};
+use rand_core::RngCore;
+
+/// Encodes a point as 64 bytes that are indistinguishable from random.
+///
+/// Every point has many encodings and a different one is sampled each time using `rng` so encoding
+/// the same point twice will (with overwhelming probability) give different bytes.
+///
+/// Encoding runs in variable time.
+pub fn encode<T: Normalized>(
+    point: &Point<T, Public, NonZero>,
+    rng: &mut impl RngCore,
+) -> [u8; 64] {
+    let x = FieldElement::from_bytes_mod_order(point.to_xonly_bytes());
+    loop {
+        let mut bytes = [0u8; 33];
+        rng.fill_bytes(&mut bytes[..]);
+        let u = FieldElement::from_bytes_mod_order(bytes[..32].try_into().unwrap());
+        if u.is_zero() {
+            continue;
+        }
+        if let Some(t) = xswiftec_inv(&x, &u, bytes[32] & 0x07) {
+            if t.is_zero() {
+                continue;
+            }
+            // t and -t decode to the same x-coordinate so we use its parity to encode y's
+            let t = t.conditional_negate(t.is_odd() == point.is_y_even());
+            let mut encoded = [0u8; 64];
+            encoded[..32].copy_from_slice(&u.to_bytes());
+            encoded[32..].copy_from_slice(&t.to_bytes());
+            return encoded;
+        }
+    }
+}
+
+/// Decodes 64 bytes into a point.
+///
+/// Decoding never fails. Any 64 byte string decodes to a valid point.
+pub fn decode(bytes: &[u8; 64]) -> Point {
+    let u = FieldElement::from_bytes_mod_order(bytes[..32].try_into().unwrap());
+    let t = FieldElement::from_bytes_mod_order(bytes[32..].try_into().unwrap());
+    let x = xswiftec(u, t);
+    let mut compressed = [0u8; 33];
+    compressed[0] = 0x02 | t.is_odd() as u8;
+    compressed[1..].copy_from_slice(&x.to_bytes());
+    Point::from_bytes(compressed).expect("xswiftec always returns a valid x-coordinate")
+}
+
+/// Decodes 64 bytes of output from `hash` into a point nobody knows the discrete logarithm of.
+///
+/// The bytes are the output of `hash` with a `0` byte added followed by the output with a `1`
+/// byte added.
+pub(crate) fn decode_hash<H: Digest<OutputSize = U32> + Clone>(hash: H) -> Point {
+    let mut bytes = [0u8; 64];
+    bytes[..32].copy_from_slice(hash.clone().add(0u8).finalize().as_slice());
+    bytes[32..].copy_from_slice(hash.add(1u8).finalize().as_slice());
+    decode(&bytes)
+}
+
+/// The SwiftEC map from `(u, t)` to an x-coordinate on the curve.
+fn xswiftec(mut u: FieldElement, mut t: FieldElement) -> FieldElement {
+    if u.is_zero() {
+        u = FieldElement::one();
+    }
+    if t.is_zero() {
+        t = FieldElement::one();
+    }
+    if (u.square() * u + t.square() + seven()).is_zero() {
+        t = t + t;
+    }
+    let c = minus_3_sqrt();
+    let X = (u.square() * u + seven() - t.square()) * invert_or_zero(t + t);
+    let Y = (X + t) * invert_or_zero(c * u);
+    let x1 = u + Y.square() * FieldElement::from(4);
+    if x1.is_x_coordinate() {
+        return x1;
+    }
+    let X_over_Y = X * invert_or_zero(Y);
+    let half = invert_or_zero(FieldElement::from(2));
+    let x2 = (-X_over_Y - u) * half;
+    if x2.is_x_coordinate() {
+        return x2;
+    }
+    let x3 = (X_over_Y - u) * half;
+    debug_assert!(x3.is_x_coordinate());
+    x3
+}
+
+/// Finds a `t` such that `xswiftec(u, t) == x` if there is one for the given `case` (0..8).
+fn xswiftec_inv(x: &FieldElement, u: &FieldElement, case: u8) -> Option<FieldElement> {
+    let (x, u) = (*x, *u);
+    let u3_plus_7 = u.square() * u + seven();
+    let half = invert_or_zero(FieldElement::from(2));
+    let (s, v) = if case & 2 == 0 {
+        if (-x - u).is_x_coordinate() {
+            return None;
+        }
+        let v = x;
+        let s = -u3_plus_7 * invert_or_zero(u.square() + u * v + v.square());
+        (s, v)
+    } else {
+        let s = x - u;
+        if s.is_zero() {
+            return None;
+        }
+        let r = (-s * (u3_plus_7 * FieldElement::from(4) + s * u.square() * FieldElement::from(3)))
+            .sqrt()?;
+        if case & 1 == 1 && r.is_zero() {
+            return None;
+        }
+        let v = (r * invert_or_zero(s) - u) * half;
+        (s, v)
+    };
+    let w = s.sqrt()?;
+    let c = minus_3_sqrt();
+    let t = match case & 5 {
+        0 => -w * (u * (FieldElement::one() - c) * half + v),
+        1 => w * (u * (FieldElement::one() + c) * half + v),
+        4 => w * (u * (FieldElement::one() - c) * half + v),
+        _ => -w * (u * (FieldElement::one() + c) * half + v),
+    };
+    Some(t)
+}
+
+fn seven() -> FieldElement {
+    FieldElement::from(7)
+}
+
+fn minus_3_sqrt() -> FieldElement {
+    (-FieldElement::from(3))
+        .sqrt()
+        .expect("-3 is a square mod p")
+}
+
+/// Inverts `fe` treating the inverse of zero as zero (as BIP-324 does).
+fn invert_or_zero(fe: FieldElement) -> FieldElement {
+    fe.invert().unwrap_or_else(FieldElement::zero)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::G;
+
+    #[test]
+    fn encode_decode_roundtrip() {
+        for _ in 0..crate::TEST_SOUNDNESS {
+            let point = Point::random(&mut rand::thread_rng());
+            let encoded = encode(&point, &mut rand::thread_rng());
+            assert_eq!(decode(&encoded), point);
+            assert_eq!(decode(&encode(&-point, &mut rand::thread_rng())), -point);
+        }
+        assert_eq!(decode(&encode(G, &mut rand::thread_rng())), *G);
+    }
+
+    #[test]
+    fn every_inverse_case_decodes_to_x() {
+        let mut rng = rand::thread_rng();
+        let mut found = [false; 8];
+        for _ in 0..100 {
+            let x = FieldElement::from_bytes_mod_order(Point::random(&mut rng).to_xonly_bytes());
+            let mut u_bytes = [0u8; 32];
+            rng.fill_bytes(&mut u_bytes);
+            let u = FieldElement::from_bytes_mod_order(u_bytes);
+            for case in 0..8 {
+                if let Some(t) = xswiftec_inv(&x, &u, case) {
+                    found[case as usize] = true;
+                    assert_eq!(xswiftec(u, t).to_bytes(), x.to_bytes());
+                }
+            }
+        }
+        assert!(found.iter().all(|found| *found));
+    }
+
+    #[test]
+    fn any_bytes_decode() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..crate::TEST_SOUNDNESS {
+            let mut bytes = [0u8; 64];
+            rng.fill_bytes(&mut bytes);
+            let _ = decode(&bytes);
+        }
+        let _ = decode(&[0u8; 64]);
+        let _ = decode(&[0xff; 64]);
+    }
+}
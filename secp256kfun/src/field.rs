@@ -0,0 +1,261 @@
+use crate::{backend, limbs};
+
+/// An element of the field that secp256k1 coordinates are defined over i.e. the integers modulo
+/// `p = 2^256 - 2^32 - 977`.
+///
+/// Most protocols never need this but some constructions (hash to curve, [`elligator`] etc) need
+/// to do arithmetic on coordinates directly. The element is always kept fully reduced so equality
+/// and serialization are well defined. All operations run in constant time.
+///
+/// # Examples
+///
+/// ```
+/// use secp256kfun::FieldElement;
+/// let two = FieldElement::from(2);
+/// let root = (two * two).sqrt().unwrap();
+/// assert!(root == two || root == -two);
+/// assert_eq!(two * two.invert().unwrap(), FieldElement::one());
+/// ```
+///
+/// [`elligator`]: crate::elligator
+#[derive(Clone, Copy)]
+pub struct FieldElement(backend::FieldElement);
+
+impl FieldElement {
+    /// The additive identity.
+    pub fn zero() -> Self {
+        FieldElement(backend::FieldElement::zero())
+    }
+
+    /// The multiplicative identity.
+    pub fn one() -> Self {
+        FieldElement(backend::FieldElement::one())
+    }
+
+    /// Interprets `bytes` as a big-endian integer and returns `None` if it is not less than `p`.
+    pub fn from_bytes(bytes: [u8; 32]) -> Option<Self> {
+        Option::from(backend::FieldElement::from_bytes(&bytes.into())).map(FieldElement)
+    }
+
+    /// Interprets `bytes` as a big-endian integer and reduces it modulo `p`.
+    pub fn from_bytes_mod_order(bytes: [u8; 32]) -> Self {
+        // 2^256 < 2p so a single reduction in normalize is enough
+        FieldElement(backend::FieldElement::from_bytes_unchecked(&bytes).normalize())
+    }
+
+    /// Encodes the element as 32 big-endian bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes().into()
+    }
+
+    /// Interprets eight 32-bit limbs (least significant first) as an integer and returns `None` if
+    /// it is not less than `p`.
+    ///
+    /// Each limb is a native `u32` so the limbs can come straight from hardware that works on
+    /// platform-endian words. See [`to_u32_limbs`](Self::to_u32_limbs).
+    pub fn from_u32_limbs(limbs: [u32; 8]) -> Option<Self> {
+        Self::from_bytes(limbs::from_u32_limbs(&limbs))
+    }
+
+    /// Interprets sixteen 16-bit limbs (least significant first) as an integer and returns `None`
+    /// if it is not less than `p`.
+    pub fn from_u16_limbs(limbs: [u16; 16]) -> Option<Self> {
+        Self::from_bytes(limbs::from_u16_limbs(&limbs))
+    }
+
+    /// Encodes the element as eight 32-bit limbs with the least significant limb first.
+    ///
+    /// Use [`u32::to_be`] or [`u32::to_le`] on each limb if the destination wants a fixed byte
+    /// order rather than the platform's.
+    pub fn to_u32_limbs(&self) -> [u32; 8] {
+        limbs::to_u32_limbs(&self.to_bytes())
+    }
+
+    /// Encodes the element as sixteen 16-bit limbs with the least significant limb first.
+    pub fn to_u16_limbs(&self) -> [u16; 16] {
+        limbs::to_u16_limbs(&self.to_bytes())
+    }
+
+    /// Whether the element is zero.
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero().into()
+    }
+
+    /// Whether the element is odd (when interpreted as an integer in `[0, p)`).
+    pub fn is_odd(&self) -> bool {
+        self.0.is_odd().into()
+    }
+
+    /// Squares the element.
+    pub fn square(&self) -> Self {
+        FieldElement(self.0.square().normalize())
+    }
+
+    /// Returns the multiplicative inverse or `None` if the element is zero.
+    pub fn invert(&self) -> Option<Self> {
+        Option::from(self.0.invert())
+            .map(|inverse: backend::FieldElement| FieldElement(inverse.normalize()))
+    }
+
+    /// Returns a square root of the element or `None` if it doesn't have one.
+    ///
+    /// If `r` is returned then `-r` is also a square root. The one returned is the one that is
+    /// itself a square.
+    pub fn sqrt(&self) -> Option<Self> {
+        Option::from(self.0.sqrt())
+            .map(|root: backend::FieldElement| FieldElement(root.normalize()))
+    }
+
+    /// Negates the element if `cond` is true.
+    pub fn conditional_negate(&self, cond: bool) -> Self {
+        use subtle::ConditionallySelectable;
+        FieldElement(backend::FieldElement::conditional_select(
+            &self.0,
+            &(-*self).0,
+            (cond as u8).into(),
+        ))
+    }
+
+    /// Whether there is a point on the curve with this x-coordinate.
+    pub fn is_x_coordinate(&self) -> bool {
+        (self.square() * *self + FieldElement::from(7))
+            .sqrt()
+            .is_some()
+    }
+}
+
+impl From<u32> for FieldElement {
+    fn from(int: u32) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[28..].copy_from_slice(&int.to_be_bytes());
+        FieldElement::from_bytes_mod_order(bytes)
+    }
+}
+
+impl PartialEq for FieldElement {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.0 == rhs.0
+    }
+}
+
+impl Eq for FieldElement {}
+
+impl core::ops::Add for FieldElement {
+    type Output = FieldElement;
+
+    fn add(self, rhs: FieldElement) -> FieldElement {
+        FieldElement((self.0 + &rhs.0).normalize())
+    }
+}
+
+impl core::ops::Sub for FieldElement {
+    type Output = FieldElement;
+
+    fn sub(self, rhs: FieldElement) -> FieldElement {
+        self + -rhs
+    }
+}
+
+impl core::ops::Mul for FieldElement {
+    type Output = FieldElement;
+
+    fn mul(self, rhs: FieldElement) -> FieldElement {
+        FieldElement(self.0.mul(&rhs.0).normalize())
+    }
+}
+
+impl core::ops::Neg for FieldElement {
+    type Output = FieldElement;
+
+    fn neg(self) -> FieldElement {
+        FieldElement(self.0.negate(1).normalize())
+    }
+}
+
+crate::impl_display_debug_serialize! {
+    fn to_bytes(field_element: &FieldElement) -> [u8;32] {
+        field_element.to_bytes()
+    }
+}
+
+crate::impl_fromstr_deserialize! {
+    name => "secp256k1 field element",
+    fn from_bytes(bytes: [u8;32]) -> Option<FieldElement> {
+        FieldElement::from_bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::str::FromStr;
+
+    #[test]
+    fn from_bytes_rejects_modulus() {
+        let p = crate::hex::decode_array(
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+        )
+        .unwrap();
+        assert!(FieldElement::from_bytes(p).is_none());
+        assert_eq!(FieldElement::from_bytes_mod_order(p), FieldElement::zero());
+        // 2^256 - 1 - p = 2^32 + 976
+        assert_eq!(
+            FieldElement::from_bytes_mod_order([0xff; 32]),
+            FieldElement::from(u32::MAX) + FieldElement::from(977)
+        );
+    }
+
+    #[test]
+    fn limbs() {
+        let minus_one = -FieldElement::one();
+        let limbs = minus_one.to_u32_limbs();
+        assert_eq!(limbs[0], 0xfffffc2e);
+        assert_eq!(limbs[1], 0xfffffffe);
+        assert_eq!(FieldElement::from_u32_limbs(limbs), Some(minus_one));
+        assert_eq!(minus_one.to_u16_limbs()[0], 0xfc2e);
+        assert_eq!(
+            FieldElement::from_u16_limbs(minus_one.to_u16_limbs()),
+            Some(minus_one)
+        );
+        assert!(FieldElement::from_u32_limbs([u32::MAX; 8]).is_none());
+        assert!(FieldElement::from_u16_limbs([u16::MAX; 16]).is_none());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let minus_one = -FieldElement::one();
+        assert_eq!(
+            minus_one,
+            FieldElement::from_str(
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e"
+            )
+            .unwrap()
+        );
+        assert_eq!(minus_one + FieldElement::one(), FieldElement::zero());
+        assert_eq!(minus_one * minus_one, FieldElement::one());
+        assert_eq!(
+            FieldElement::from(3) - FieldElement::from(5),
+            -FieldElement::from(2)
+        );
+        assert!(FieldElement::zero().invert().is_none());
+        assert!(minus_one.sqrt().is_none());
+        assert_eq!(
+            FieldElement::from(5).conditional_negate(true),
+            -FieldElement::from(5)
+        );
+        assert_eq!(
+            FieldElement::from(5).conditional_negate(false),
+            FieldElement::from(5)
+        );
+        assert!(!minus_one.is_odd());
+    }
+
+    #[test]
+    fn g_x_coordinate() {
+        let (x, y) = crate::G.coordinates();
+        let x = FieldElement::from_bytes(x).unwrap();
+        let y = FieldElement::from_bytes(y).unwrap();
+        assert!(x.is_x_coordinate());
+        assert_eq!(y.square(), x.square() * x + FieldElement::from(7));
+    }
+}
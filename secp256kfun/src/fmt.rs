@@ -0,0 +1,223 @@
+//! How secret values are shown by [`Debug`].
+//!
+//! It is easy for a secret key to end up in a log file by way of a `{:?}` or a `dbg!` on some
+//! struct that happens to contain it. To make this safe by default the [`Debug`] implementations of
+//! [`Scalar`] and [`Point`] (and so of anything that derives [`Debug`] and contains them) only
+//! print the bytes of values marked [`Public`]. With the `sha2` feature values with any other
+//! [`Secrecy`] are printed as a _fingerprint_: the first 8 bytes of a tagged SHA256 hash of their
+//! encoding. The same secret always has the same fingerprint so you can still tell which secret a
+//! log line is talking about. Without it they are printed as `<redacted>`.
+//!
+//! To see a particular secret while debugging wrap it with `reveal` e.g. [`Scalar::reveal`].
+//!
+//! ```
+//! use secp256kfun::{marker::*, Scalar};
+//! let secret = Scalar::from_bytes_mod_order([42u8; 32])
+//!     .mark::<NonZero>()
+//!     .unwrap();
+//! let debug = format!("{:?}", secret);
+//! assert!(debug.starts_with("Scalar<Secret,NonZero>("));
+//! assert!(!debug.contains(&secret.to_string()));
+//! // public values are shown as they are
+//! let public = secret.clone().mark::<Public>();
+//! assert_eq!(
+//!     format!("{:?}", public),
+//!     format!("Scalar<Public,NonZero>({})", public)
+//! );
+//! // and so are secret ones you explicitly reveal
+//! assert_eq!(
+//!     format!("{:?}", secret.reveal()),
+//!     format!("Scalar<Secret,NonZero>({})", secret)
+//! );
+//! ```
+//!
+//! Note that this only affects [`Debug`]. [`Display`] (and so `to_string`) always shows the bytes
+//! since it is used for serialization.
+//!
+//! [`Debug`]: core::fmt::Debug
+//! [`Display`]: core::fmt::Display
+//! [`Scalar`]: crate::Scalar
+//! [`Scalar::reveal`]: crate::Scalar::reveal
+//! [`Point`]: crate::Point
+//! [`Public`]: crate::marker::Public
+//! [`Secrecy`]: crate::marker::Secrecy
+#[cfg(feature = "sha2")]
+use crate::hash::Tagged;
+use crate::marker::Secrecy;
+#[cfg(feature = "sha2")]
+use digest::Digest;
+
+/// Shows the value it wraps with [`Debug`] even if it is secret.
+///
+/// Get one from [`Scalar::reveal`] or [`Point::reveal`].
+///
+/// [`Debug`]: core::fmt::Debug
+/// [`Scalar::reveal`]: crate::Scalar::reveal
+/// [`Point::reveal`]: crate::Point::reveal
+#[derive(Clone, Copy)]
+pub struct Reveal<'a, T>(pub(crate) &'a T);
+
+/// The fingerprint [`Debug`] shows for a secret value with the encoding `bytes`.
+///
+/// [`Debug`]: core::fmt::Debug
+#[cfg(feature = "sha2")]
+pub fn fingerprint(bytes: &[u8]) -> [u8; 8] {
+    let hash = sha2::Sha256::default()
+        .tagged(b"secp256kfun/debug-fingerprint")
+        .chain_update(bytes)
+        .finalize();
+    let mut fingerprint = [0u8; 8];
+    fingerprint.copy_from_slice(&hash[..8]);
+    fingerprint
+}
+
+/// A short identifier for a public key.
+///
+/// This is the first 8 bytes of a tagged SHA256 hash of the key's compressed encoding. It is
+/// meant to tell keys apart in user interfaces and logs (e.g. to show which party in a multi-party
+/// protocol sent a message) where showing the full 33 bytes would be unwieldy. It is **not** long
+/// enough to securely identify a key: it is easy to find two keys with the same fingerprint, so
+/// always use the full key to check signatures and so on.
+///
+/// [`Display`] renders it [bech32m] style with the human readable part `fp` e.g.
+/// `fp1qy352euf40x77etpz7d`. The checksum catches typos when someone reads one out or copies it by
+/// hand. [`LowerHex`] renders the bytes as hex.
+///
+/// Get one from [`Point::fingerprint`].
+///
+/// ```
+/// use secp256kfun::{g, Scalar, G};
+/// let point = g!({ Scalar::random(&mut rand::thread_rng()) } * G).normalize();
+/// let fingerprint = point.fingerprint();
+/// assert!(fingerprint.to_string().starts_with("fp1"));
+/// assert_eq!(format!("{:x}", fingerprint).len(), 16);
+/// // the same point has the same fingerprint however it is marked
+/// let (xonly, _) = point.into_point_with_even_y();
+/// assert_eq!(xonly.fingerprint() == fingerprint, point.is_y_even());
+/// ```
+///
+/// [`Display`]: core::fmt::Display
+/// [`LowerHex`]: core::fmt::LowerHex
+/// [`Point::fingerprint`]: crate::Point::fingerprint
+/// [bech32m]: https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki
+#[cfg(feature = "sha2")]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fingerprint(pub [u8; 8]);
+
+#[cfg(feature = "sha2")]
+impl Fingerprint {
+    /// Computes the fingerprint of a point from its compressed encoding.
+    pub fn from_point_bytes(bytes: &[u8; 33]) -> Self {
+        let hash = sha2::Sha256::default()
+            .tagged(b"secp256kfun/fingerprint")
+            .chain_update(bytes)
+            .finalize();
+        let mut fingerprint = [0u8; 8];
+        fingerprint.copy_from_slice(&hash[..8]);
+        Fingerprint(fingerprint)
+    }
+}
+
+#[cfg(feature = "sha2")]
+impl core::fmt::Display for Fingerprint {
+    /// Displays as bech32m with the human readable part `fp`.
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        crate::bech32::write_bytes(f, "fp", &self.0, crate::bech32::Variant::Bech32m)
+    }
+}
+
+#[cfg(feature = "sha2")]
+impl core::fmt::LowerHex for Fingerprint {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+#[cfg(feature = "sha2")]
+impl core::fmt::Debug for Fingerprint {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "Fingerprint({})", self)
+    }
+}
+
+/// Writes `bytes` as hex if `S` is [`Public`] or `reveal` is set and hides them otherwise.
+///
+/// [`Public`]: crate::marker::Public
+pub(crate) fn write_bytes<S: Secrecy>(
+    f: &mut core::fmt::Formatter,
+    bytes: &[u8],
+    reveal: bool,
+) -> core::fmt::Result {
+    if reveal || !S::IS_SECRET {
+        return write_hex(f, bytes);
+    }
+    #[cfg(feature = "sha2")]
+    {
+        write!(f, "#")?;
+        write_hex(f, &fingerprint(bytes))
+    }
+    #[cfg(not(feature = "sha2"))]
+    write!(f, "<redacted>")
+}
+
+/// The name of `T` without its path.
+pub(crate) fn short_type_name<T: ?Sized>() -> &'static str {
+    core::any::type_name::<T>().rsplit("::").next().unwrap()
+}
+
+fn write_hex(f: &mut core::fmt::Formatter, bytes: &[u8]) -> core::fmt::Result {
+    for byte in bytes {
+        write!(f, "{:02x}", byte)?
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    use crate::{g, marker::*, Scalar, G};
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn fingerprint_display_is_bech32m() {
+        let fingerprint = Fingerprint([0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]);
+        assert_eq!(format!("{}", fingerprint), "fp1qy352euf40x77etpz7d");
+        assert_eq!(format!("{:x}", fingerprint), "0123456789abcdef");
+    }
+
+    #[test]
+    fn secrets_are_hidden_unless_revealed() {
+        let secret = Scalar::from_bytes_mod_order([7u8; 32])
+            .mark::<NonZero>()
+            .unwrap();
+        let point = g!(secret * G).normalize();
+        let secret_point = point.mark::<Secret>();
+        let hidden = format!("{:?}", secret);
+        #[cfg(feature = "sha2")]
+        assert_eq!(
+            hidden,
+            format!(
+                "Scalar<Secret,NonZero>(#{})",
+                crate::hex::encode(&fingerprint(&secret.to_bytes()))
+            )
+        );
+        #[cfg(not(feature = "sha2"))]
+        assert_eq!(hidden, "Scalar<Secret,NonZero>(<redacted>)");
+        assert_eq!(hidden, format!("{:?}", secret.clone()));
+        assert!(!format!("{:?}", secret_point).contains(&format!("{}", point)));
+        assert_eq!(
+            format!("{:?}", point),
+            format!("Point<Normal,Public,NonZero>({})", point)
+        );
+
+        assert_eq!(
+            format!("{:?}", secret.reveal()),
+            format!("Scalar<Secret,NonZero>({})", secret)
+        );
+        assert_eq!(
+            format!("{:?}", secret_point.reveal()),
+            format!("Point<Normal,Secret,NonZero>({})", point)
+        );
+    }
+}
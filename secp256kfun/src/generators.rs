@@ -0,0 +1,197 @@
+//! Vectors of independent generators.
+//!
+//! Inner-product arguments, bulletproofs and vector commitments need many points whose discrete
+//! logarithms with respect to each other (and [`G`]) nobody knows. [`Generators`] derives them by
+//! hashing a label and an index onto the curve so anyone can re-derive the same points from the
+//! label alone.
+//!
+//! Each point is the [`elligator::decode`] of 64 bytes of hash output. Since any 64 bytes decode
+//! to a point and the hash output is random looking there is no way to choose a point you know
+//! the discrete logarithm of.
+//!
+//! Only available with the `alloc` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use secp256kfun::generators::Generators;
+//! let mut gens = Generators::<sha2::Sha256>::new(b"my-protocol/bulletproofs", 4);
+//! assert_eq!(gens.len(), 4);
+//! assert_ne!(gens[0], gens[1]);
+//! // need more? they're derived on demand and the existing ones stay the same
+//! let first = gens[0];
+//! gens.extend_to(64);
+//! assert_eq!(gens[0], first);
+//! assert_eq!(gens.len(), 64);
+//! ```
+//!
+//! [`G`]: crate::G
+//! [`elligator::decode`]: crate::elligator::decode
+use crate::{
+    digest::{generic_array::typenum::U32, Digest},
+    elligator,
+    hash::{HashAdd, Tagged},
+    Point,
+};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// Derives the generator at `index` for `label`.
+///
+/// This is what [`Generators`] uses to derive its points. Use it directly if you only need a few
+/// of them and don't want to allocate.
+///
+/// # Examples
+///
+/// ```
+/// use secp256kfun::generators::{generator, Generators};
+/// let gens = Generators::<sha2::Sha256>::new(b"my-protocol", 3);
+/// assert_eq!(generator::<sha2::Sha256>(b"my-protocol", 2), gens[2]);
+/// ```
+pub fn generator<H: Tagged + Digest<OutputSize = U32>>(label: &[u8], index: u32) -> Point {
+    elligator::decode_hash(
+        H::default()
+            .tagged(b"secp256kfun/generators")
+            .add(label)
+            .add(index.to_be_bytes()),
+    )
+}
+
+/// A cached vector of independent generators derived from a label.
+///
+/// Index it like a slice to get the `i`th generator. Use [`extend_to`] to derive more and
+/// [`as_slice`] to pass them to functions like [`lincomb`].
+///
+/// [`extend_to`]: Self::extend_to
+/// [`as_slice`]: Self::as_slice
+/// [`lincomb`]: crate::op::lincomb
+#[derive(Clone)]
+pub struct Generators<H> {
+    label: Vec<u8>,
+    points: Vec<Point>,
+    hash: PhantomData<H>,
+}
+
+impl<H: Tagged + Digest<OutputSize = U32>> Generators<H> {
+    /// Derives the first `n` generators for `label`.
+    pub fn new(label: &[u8], n: usize) -> Self {
+        let mut generators = Generators {
+            label: label.to_vec(),
+            points: Vec::with_capacity(n),
+            hash: PhantomData,
+        };
+        generators.extend_to(n);
+        generators
+    }
+
+    /// Derives more generators so that there are at least `n` of them.
+    ///
+    /// Does nothing if there are already `n` or more.
+    pub fn extend_to(&mut self, n: usize) {
+        let start = self.points.len();
+        if n <= start {
+            return;
+        }
+        self.points.reserve(n - start);
+        for i in start..n {
+            self.points.push(generator::<H>(&self.label, i as u32));
+        }
+    }
+}
+
+impl<H> Generators<H> {
+    /// The label the generators were derived from.
+    pub fn label(&self) -> &[u8] {
+        &self.label
+    }
+
+    /// The number of generators derived so far.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Whether no generators have been derived.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The `i`th generator if it has been derived.
+    pub fn get(&self, i: usize) -> Option<&Point> {
+        self.points.get(i)
+    }
+
+    /// The generators derived so far.
+    pub fn as_slice(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// Iterates over the generators derived so far.
+    pub fn iter(&self) -> core::slice::Iter<'_, Point> {
+        self.points.iter()
+    }
+}
+
+impl<H> core::fmt::Debug for Generators<H> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Generators")
+            .field("label", &self.label)
+            .field("points", &self.points)
+            .finish()
+    }
+}
+
+impl<H> PartialEq for Generators<H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.label == other.label && self.points == other.points
+    }
+}
+
+impl<H> Eq for Generators<H> {}
+
+impl<H> core::ops::Index<usize> for Generators<H> {
+    type Output = Point;
+
+    fn index(&self, i: usize) -> &Point {
+        &self.points[i]
+    }
+}
+
+impl<'a, H> IntoIterator for &'a Generators<H> {
+    type Item = &'a Point;
+    type IntoIter = core::slice::Iter<'a, Point>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::G;
+    use sha2::Sha256;
+
+    #[test]
+    fn generators_are_deterministic_and_distinct() {
+        let mut gens = Generators::<Sha256>::new(b"test", 8);
+        let again = Generators::<Sha256>::new(b"test", 8);
+        let other = Generators::<Sha256>::new(b"other", 8);
+        assert_eq!(gens, again);
+        assert_ne!(gens[0], other[0]);
+
+        for (i, point) in gens.iter().enumerate() {
+            assert_ne!(point, G);
+            assert!(gens.iter().skip(i + 1).all(|other| other != point));
+        }
+
+        gens.extend_to(4);
+        assert_eq!(gens.len(), 8);
+        gens.extend_to(16);
+        assert_eq!(&gens.as_slice()[..8], again.as_slice());
+        assert_eq!(gens[15], generator::<Sha256>(b"test", 15));
+        assert!(Generators::<Sha256>::new(b"test", 0).is_empty());
+    }
+}
@@ -9,6 +9,7 @@ use crate::digest::{
     generic_array::typenum::{PartialDiv, Unsigned},
     Digest,
 };
+use core::marker::PhantomData;
 /// Extension trait to "tag" a hash as described in [BIP-340].
 ///
 /// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
@@ -24,6 +25,24 @@ pub trait Tagged: Default + Clone {
     /// println!("{:?}", hash.finalize());
     /// ```
     fn tagged(&self, tag: &[u8]) -> Self;
+
+    /// Returns the instance tagged with the [`HashTag`] `T`.
+    ///
+    /// # Example
+    /// ```
+    /// use digest::Digest;
+    /// use secp256kfun::hash::{tags::Bip340Challenge, Tagged};
+    /// let hash = sha2::Sha256::default().tagged_with::<Bip340Challenge>();
+    /// assert_eq!(
+    ///     hash.finalize(),
+    ///     sha2::Sha256::default()
+    ///         .tagged(b"BIP0340/challenge")
+    ///         .finalize()
+    /// );
+    /// ```
+    fn tagged_with<T: HashTag>(&self) -> Self {
+        self.tagged(T::TAG.as_bytes())
+    }
 }
 
 impl<H: BlockSizeUser + Digest + Default + Clone> Tagged for H
@@ -47,6 +66,118 @@ where
     }
 }
 
+/// A tag for a [BIP-340] style tagged hash that is known at compile time.
+///
+/// The tags used by Bitcoin are in [`tags`].
+///
+/// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+pub trait HashTag {
+    /// The tag
+    const TAG: &'static str;
+}
+
+/// The [`HashTag`]s defined by [BIP-340] and [BIP-341].
+///
+/// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+/// [BIP-341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+pub mod tags {
+    use super::HashTag;
+
+    macro_rules! hash_tag {
+        ($(#[$attr:meta])* $name:ident => $tag:literal) => {
+            $(#[$attr])*
+            #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+            pub struct $name;
+
+            impl HashTag for $name {
+                const TAG: &'static str = $tag;
+            }
+        };
+    }
+
+    hash_tag! {
+        /// `BIP0340/challenge`: the Fiat-Shamir challenge of a [BIP-340] signature.
+        ///
+        /// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+        Bip340Challenge => "BIP0340/challenge"
+    }
+    hash_tag! {
+        /// `BIP0340/nonce`: the nonce derivation of a [BIP-340] signature.
+        ///
+        /// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+        Bip340Nonce => "BIP0340/nonce"
+    }
+    hash_tag! {
+        /// `BIP0340/aux`: the hash of the auxiliary randomness of a [BIP-340] signature.
+        ///
+        /// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+        Bip340Aux => "BIP0340/aux"
+    }
+    hash_tag! {
+        /// `TapTweak`: the taproot output key tweak from [BIP-341].
+        ///
+        /// [BIP-341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+        TapTweak => "TapTweak"
+    }
+    hash_tag! {
+        /// `TapLeaf`: the hash of a taproot script leaf from [BIP-341].
+        ///
+        /// [BIP-341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+        TapLeaf => "TapLeaf"
+    }
+    hash_tag! {
+        /// `TapBranch`: the hash of a taproot script tree branch from [BIP-341].
+        ///
+        /// [BIP-341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+        TapBranch => "TapBranch"
+    }
+}
+
+/// A hash that has been tagged with the [`HashTag`] `T` ahead of time.
+///
+/// Tagging a hash absorbs a full block into its state. `TaggedHash` does this once when it is
+/// created and stores the resulting state (the _midstate_) so that each call to [`hash`] only has
+/// to clone it.
+///
+/// # Example
+///
+/// ```
+/// use secp256kfun::hash::{tags::TapTweak, HashAdd, Tagged, TaggedHash};
+/// use sha2::{Digest, Sha256};
+/// let tap_tweak = TaggedHash::<TapTweak, Sha256>::default();
+/// let tweak = tap_tweak.hash().add(&[42u8; 32][..]).finalize();
+/// assert_eq!(
+///     tweak,
+///     Sha256::default()
+///         .tagged(b"TapTweak")
+///         .add(&[42u8; 32][..])
+///         .finalize()
+/// );
+/// ```
+///
+/// [`hash`]: TaggedHash::hash
+#[derive(Clone, Debug)]
+pub struct TaggedHash<T, H> {
+    midstate: H,
+    tag: PhantomData<T>,
+}
+
+impl<T: HashTag, H: Tagged> Default for TaggedHash<T, H> {
+    fn default() -> Self {
+        TaggedHash {
+            midstate: H::default().tagged_with::<T>(),
+            tag: PhantomData,
+        }
+    }
+}
+
+impl<T, H: Clone> TaggedHash<T, H> {
+    /// Returns a new hash instance starting from the tagged midstate.
+    pub fn hash(&self) -> H {
+        self.midstate.clone()
+    }
+}
+
 /// Anything that can be hashed.
 ///
 /// The implementations of this trait decide how the type will be converted into
@@ -123,3 +254,104 @@ impl<D: Digest> HashAdd for D {
         self
     }
 }
+
+/// HMAC ([RFC 2104]) over the hash `H` computed incrementally.
+///
+/// This is the one HMAC implementation in the library. [`Rfc6979`] uses it with the hash it was
+/// given and `schnorr_fun` builds BIP32 and HKDF on top of it.
+///
+/// # Examples
+///
+/// ```
+/// use secp256kfun::hash::{hmac, Hmac};
+/// use sha2::Sha256;
+/// let mut mac = Hmac::<Sha256>::new(b"key");
+/// mac.update(b"hello ");
+/// mac.update(b"world");
+/// assert_eq!(mac.finalize(), hmac::<Sha256>(b"key", &[b"hello world"]));
+/// ```
+///
+/// [RFC 2104]: https://www.rfc-editor.org/rfc/rfc2104
+/// [`Rfc6979`]: crate::nonce::Rfc6979
+#[derive(Clone, Debug)]
+pub struct Hmac<H> {
+    inner: H,
+    outer: H,
+}
+
+impl<H: Digest + BlockSizeUser> Hmac<H> {
+    /// Starts a MAC under `key`. Keys longer than the block size of `H` are hashed first.
+    pub fn new(key: &[u8]) -> Self {
+        let mut block_key = digest::generic_array::GenericArray::<u8, H::BlockSize>::default();
+        if key.len() > H::BlockSize::USIZE {
+            let hashed_key = H::digest(key);
+            block_key[..hashed_key.len()].copy_from_slice(&hashed_key);
+        } else {
+            block_key[..key.len()].copy_from_slice(key);
+        }
+        let mut inner = H::new();
+        let mut outer = H::new();
+        for byte in block_key.iter() {
+            Digest::update(&mut inner, [byte ^ 0x36]);
+            Digest::update(&mut outer, [byte ^ 0x5c]);
+        }
+        Self { inner, outer }
+    }
+
+    /// Adds `bytes` to the message.
+    pub fn update(&mut self, bytes: &[u8]) {
+        Digest::update(&mut self.inner, bytes);
+    }
+
+    /// Returns the MAC of everything added so far.
+    pub fn finalize(mut self) -> digest::Output<H> {
+        Digest::update(&mut self.outer, self.inner.finalize());
+        self.outer.finalize()
+    }
+}
+
+/// The [`Hmac`] of the concatenation of the slices in `data` under `key`.
+pub fn hmac<H: Digest + BlockSizeUser>(key: &[u8], data: &[&[u8]]) -> digest::Output<H> {
+    let mut mac = Hmac::<H>::new(key);
+    for bytes in data {
+        mac.update(bytes);
+    }
+    mac.finalize()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hex;
+    use sha2::{Sha256, Sha512};
+
+    #[test]
+    fn hmac_test_vectors() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            hmac::<Sha256>(b"Jefe", &[b"what do ya want ", b"for nothing?"])[..],
+            hex::decode_array::<32>(
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+            )
+            .unwrap()
+        );
+        // RFC 4231 test case 6 which has a key longer than the block size
+        let key = [0xaa; 131];
+        let data = b"Test Using Larger Than Block-Size Key - Hash Key First";
+        assert_eq!(
+            hmac::<Sha256>(&key, &[data])[..],
+            hex::decode_array::<32>(
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            hmac::<Sha512>(&key, &[data])[..],
+            hex::decode_array::<64>(
+                "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f352\
+                 6b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598"
+            )
+            .unwrap()
+        );
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{g, marker::*, Point, Scalar, G};
+#[cfg(any(test, feature = "test_keys"))]
+use crate::hash::{HashAdd, Tagged};
+#[cfg(feature = "alloc")]
+use crate::secret_box::SecretBox;
+use crate::{g, marker::*, s, Point, Scalar, G};
 /// A secret and public key pair.
 ///
 /// The secret key is a [`Scalar`] and the public key is the [`Point`] resulting from multiplying the scalar by [`G`].
@@ -12,7 +16,7 @@ use crate::{g, marker::*, Point, Scalar, G};
 /// [`Scalar`]: crate::Scalar
 /// [`G`]: crate::G
 /// [`Point`]: crate::Point
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct KeyPair {
     sk: Scalar,
     pk: Point,
@@ -27,6 +31,59 @@ impl KeyPair {
         }
     }
 
+    /// Creates a `KeyPair` from a `secret_key` and moves it into a [`SecretBox`].
+    ///
+    /// Use this for keys that are kept around for a long time. See [`secret_box`] for what the
+    /// box does and doesn't protect against.
+    ///
+    /// [`SecretBox`]: crate::secret_box::SecretBox
+    /// [`secret_box`]: crate::secret_box
+    #[cfg(feature = "alloc")]
+    pub fn new_boxed(secret_key: Scalar) -> SecretBox<Self> {
+        SecretBox::new(Self::new(secret_key))
+    }
+
+    /// Derives a keypair from a human readable `label` like `"alice"` for tests and examples.
+    ///
+    /// The same label always gives the same keypair so multi-party examples are reproducible and
+    /// can refer to participants by name. **Anyone can derive the secret key from the label** so
+    /// never use these keys for anything real. Only available with the `test_keys` feature.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "test_keys")] {
+    /// use secp256kfun::KeyPair;
+    /// let alice = KeyPair::test_from_label("alice");
+    /// assert_eq!(alice, KeyPair::test_from_label("alice"));
+    /// assert_ne!(alice, KeyPair::test_from_label("bob"));
+    /// # }
+    /// ```
+    #[cfg(any(test, feature = "test_keys"))]
+    pub fn test_from_label(label: &str) -> Self {
+        Self::new(test_secret_key(label))
+    }
+
+    /// Creates a `KeyPair` from the 32-byte big-endian encoding of the secret key.
+    ///
+    /// Returns `None` if the bytes are zero or are not less than the curve order.
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::KeyPair;
+    /// assert!(KeyPair::from_secret_bytes([42u8; 32]).is_some());
+    /// assert!(KeyPair::from_secret_bytes([0u8; 32]).is_none());
+    /// ```
+    pub fn from_secret_bytes(bytes: [u8; 32]) -> Option<Self> {
+        Scalar::from_bytes(bytes)
+            .and_then(|secret_key| secret_key.mark::<NonZero>())
+            .map(Self::new)
+    }
+
+    /// Encodes the secret key as 32 big-endian bytes.
+    pub fn to_secret_bytes(&self) -> [u8; 32] {
+        self.sk.to_bytes()
+    }
+
     /// Returns a reference to the secret key.
     pub fn secret_key(&self) -> &Scalar {
         &self.sk
@@ -47,12 +104,46 @@ impl KeyPair {
     pub fn as_tuple(&self) -> (&Scalar, Point) {
         (&self.sk, self.pk)
     }
+
+    /// Blinds the keypair by multiplying the secret key by `tweak`.
+    ///
+    /// The public key is the same as [`Point::blind`] with the same `tweak`.
+    pub fn blind(&self, tweak: &Scalar<impl Secrecy>) -> Self {
+        Self {
+            sk: s!(tweak * self.sk),
+            pk: self.pk.blind(tweak),
+        }
+    }
+
+    /// Removes the blinding `tweak` from a keypair made with [`blind`].
+    ///
+    /// [`blind`]: KeyPair::blind
+    pub fn unblind(&self, tweak: &Scalar<impl Secrecy>) -> Self {
+        let inverse = tweak.invert();
+        Self {
+            sk: s!(inverse * self.sk),
+            pk: self.pk.unblind(tweak),
+        }
+    }
+}
+
+/// The secret key [`KeyPair::test_from_label`] derives from `label`.
+#[cfg(any(test, feature = "test_keys"))]
+fn test_secret_key(label: &str) -> Scalar {
+    Scalar::from_hash(
+        sha2::Sha256::default()
+            .tagged(b"secp256kfun/test-key")
+            .add(label),
+    )
 }
 
 /// A secret and public key pair where the public key has an even y-coordinate.
 ///
+/// The secret key is negated if necessary when the keypair is created so the even y-coordinate
+/// public key only has to be computed once.
+///
 /// [`Scalar`]: crate::Scalar
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct XOnlyKeyPair {
     sk: Scalar,
     pk: Point<EvenY>,
@@ -89,6 +180,40 @@ impl XOnlyKeyPair {
         Self { sk: secret_key, pk }
     }
 
+    /// Creates a `XOnlyKeyPair` from a `secret_key` and moves it into a [`SecretBox`].
+    ///
+    /// See [`KeyPair::new_boxed`].
+    ///
+    /// [`SecretBox`]: crate::secret_box::SecretBox
+    #[cfg(feature = "alloc")]
+    pub fn new_boxed(secret_key: Scalar) -> SecretBox<Self> {
+        SecretBox::new(Self::new(secret_key))
+    }
+
+    /// Derives a keypair from a human readable `label` for tests and examples.
+    ///
+    /// See [`KeyPair::test_from_label`]. The secret key is the same or the negation of the one in
+    /// the `KeyPair` with the same label. Only available with the `test_keys` feature.
+    #[cfg(any(test, feature = "test_keys"))]
+    pub fn test_from_label(label: &str) -> Self {
+        Self::new(test_secret_key(label))
+    }
+
+    /// Creates a `XOnlyKeyPair` from the 32-byte big-endian encoding of the secret key.
+    ///
+    /// Returns `None` if the bytes are zero or are not less than the curve order. As with
+    /// [`XOnlyKeyPair::new`] the resulting secret key may be the negation of the one encoded.
+    pub fn from_secret_bytes(bytes: [u8; 32]) -> Option<Self> {
+        Scalar::from_bytes(bytes)
+            .and_then(|secret_key| secret_key.mark::<NonZero>())
+            .map(Self::new)
+    }
+
+    /// Encodes the secret key as 32 big-endian bytes.
+    pub fn to_secret_bytes(&self) -> [u8; 32] {
+        self.sk.to_bytes()
+    }
+
     /// Returns a reference to the secret key.
     ///
     /// The secret key will always correspond to a point with an even y-coordinate when multiplied
@@ -112,6 +237,24 @@ impl XOnlyKeyPair {
     pub fn as_tuple(&self) -> (&Scalar, Point<EvenY>) {
         (&self.sk, self.pk)
     }
+
+    /// Blinds the keypair by multiplying the secret key by `tweak`.
+    ///
+    /// As with [`XOnlyKeyPair::new`] the secret key is negated if necessary so the public key is
+    /// the same as [`Point::blind`] with the same `tweak`.
+    pub fn blind(&self, tweak: &Scalar<impl Secrecy>) -> Self {
+        Self::new(s!(tweak * self.sk))
+    }
+
+    /// Removes the blinding `tweak` from a keypair made with [`blind`].
+    ///
+    /// This gives back the original keypair including its secret key.
+    ///
+    /// [`blind`]: XOnlyKeyPair::blind
+    pub fn unblind(&self, tweak: &Scalar<impl Secrecy>) -> Self {
+        let inverse = tweak.invert();
+        Self::new(s!(inverse * self.sk))
+    }
 }
 
 impl From<XOnlyKeyPair> for (Scalar, Point<EvenY>) {
@@ -137,3 +280,61 @@ impl From<KeyPair> for XOnlyKeyPair {
         Self { sk, pk }
     }
 }
+
+/// Only shows the public key.
+///
+/// ```
+/// use secp256kfun::{KeyPair, Scalar};
+/// let secret_key = Scalar::random(&mut rand::thread_rng());
+/// let keypair = KeyPair::new(secret_key.clone());
+/// let debug = format!("{:?}", keypair);
+/// assert!(!debug.contains(&secret_key.to_string()));
+/// assert!(debug.contains(&keypair.public_key().to_string()));
+/// ```
+impl core::fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("sk", &format_args!("<redacted>"))
+            .field("pk", &self.pk)
+            .finish()
+    }
+}
+
+/// Only shows the public key.
+impl core::fmt::Debug for XOnlyKeyPair {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("XOnlyKeyPair")
+            .field("sk", &format_args!("<redacted>"))
+            .field("pk", &self.pk)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_keys_from_labels() {
+        let alice = KeyPair::test_from_label("alice");
+        assert_eq!(alice, KeyPair::test_from_label("alice"));
+        assert_ne!(alice, KeyPair::test_from_label("bob"));
+        assert_ne!(alice, KeyPair::test_from_label("Alice"));
+        let xonly_alice = XOnlyKeyPair::test_from_label("alice");
+        assert_eq!(
+            xonly_alice.public_key().to_xonly_bytes(),
+            alice.public_key().to_xonly_bytes()
+        );
+    }
+    #[test]
+    fn secret_bytes_round_trip() {
+        let keypair = KeyPair::new(Scalar::random(&mut rand::thread_rng()));
+        let bytes = keypair.to_secret_bytes();
+        assert_eq!(KeyPair::from_secret_bytes(bytes), Some(keypair.clone()));
+        let xonly = XOnlyKeyPair::from(keypair);
+        assert_eq!(
+            XOnlyKeyPair::from_secret_bytes(xonly.to_secret_bytes()),
+            Some(xonly)
+        );
+    }
+}
@@ -13,29 +13,55 @@ extern crate alloc;
 #[macro_use]
 extern crate std;
 
+#[cfg(feature = "base58")]
+pub mod base58;
+#[cfg(feature = "alloc")]
+pub mod base64;
+pub mod bech32;
+pub mod commit;
+#[cfg(feature = "der")]
+pub mod der;
+pub mod elligator;
+pub mod fmt;
+#[cfg(feature = "alloc")]
+pub mod generators;
 pub mod hash;
 pub mod hex;
 pub mod nonce;
+pub mod nums;
+pub mod p2c;
+#[cfg(feature = "alloc")]
+pub mod pedersen;
+#[cfg(feature = "alloc")]
+pub mod poly;
+#[cfg(feature = "alloc")]
+pub mod secret_box;
+pub mod transcript;
 
 pub use digest;
 pub use rand_core;
 pub use subtle;
 
 mod keypair;
+mod limbs;
 mod point;
 mod scalar;
 mod slice;
+mod to_bytes;
 
 #[macro_use]
 mod macros;
 mod backend;
+mod field;
 pub mod marker;
 pub mod op;
 
+pub use field::FieldElement;
 pub use keypair::*;
 pub use point::Point;
-pub use scalar::Scalar;
+pub use scalar::{Scalar, ScalarFromBytesError};
 pub use slice::Slice;
+pub use to_bytes::ToBytes;
 
 #[cfg(feature = "secp256k1")]
 pub extern crate secp256k1;
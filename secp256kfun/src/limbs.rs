@@ -0,0 +1,42 @@
+//! Conversions between 32 big-endian bytes and arrays of limbs.
+//!
+//! Limbs are ordered from least to most significant. Each limb is a native integer so how the
+//! array is laid out in memory is up to the platform.
+
+pub(crate) fn to_u32_limbs(bytes: &[u8; 32]) -> [u32; 8] {
+    let mut limbs = [0u32; 8];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        let start = 32 - 4 * (i + 1);
+        let mut word = [0u8; 4];
+        word.copy_from_slice(&bytes[start..start + 4]);
+        *limb = u32::from_be_bytes(word);
+    }
+    limbs
+}
+
+pub(crate) fn from_u32_limbs(limbs: &[u32; 8]) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (i, limb) in limbs.iter().enumerate() {
+        let start = 32 - 4 * (i + 1);
+        bytes[start..start + 4].copy_from_slice(&limb.to_be_bytes());
+    }
+    bytes
+}
+
+pub(crate) fn to_u16_limbs(bytes: &[u8; 32]) -> [u16; 16] {
+    let mut limbs = [0u16; 16];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        let start = 32 - 2 * (i + 1);
+        *limb = u16::from_be_bytes([bytes[start], bytes[start + 1]]);
+    }
+    limbs
+}
+
+pub(crate) fn from_u16_limbs(limbs: &[u16; 16]) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (i, limb) in limbs.iter().enumerate() {
+        let start = 32 - 2 * (i + 1);
+        bytes[start..start + 2].copy_from_slice(&limb.to_be_bytes());
+    }
+    bytes
+}
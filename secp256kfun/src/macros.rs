@@ -14,6 +14,9 @@ macro_rules! _s {
     (@scalar [$($a:tt)*] - $($t:tt)+) => {
         $crate::_s!(@scalar [neg $($a)*] $($t)+)
     };
+    (@scalar [$($a:tt)*] dot($scalars_a:expr, $scalars_b:expr) $($t:tt)*) => {
+        $crate::_s!(@next [{$crate::op::scalar_dot($scalars_a.iter(), $scalars_b.iter())} $($a)*] $($t)*)
+    };
     (@scalar [$($a:tt)*] $scalar:ident $($t:tt)*) => {
         $crate::_s!(@dot [$($a)*] [$scalar] $($t)*)
     };
@@ -74,6 +77,22 @@ macro_rules! _s {
 }
 
 /// Scalar expression macro.
+///
+/// Works like [`g!`] but for scalar expressions.
+///
+/// `dot(a, b)` is the sum of the products of the scalars in `a` and `b` (anything with an `iter()`
+/// method over scalars like a slice or `Vec`). This is handy for evaluating polynomials or
+/// aggregating responses.
+///
+/// ```
+/// use secp256kfun::{s, Scalar};
+/// let a = [Scalar::from(2u32), Scalar::from(3u32)];
+/// let b = vec![Scalar::from(5u32), Scalar::from(7u32)];
+/// let one = Scalar::from(1u32);
+/// assert_eq!(s!(dot(a, b) + one), Scalar::from(32u32));
+/// ```
+///
+/// [`g!`]: crate::g
 #[macro_export]
 macro_rules! s {
     (DEBUG $($t:tt)*) => {{
@@ -100,6 +119,9 @@ macro_rules! _g {
     (@scalar [$($a:tt)*] ($($expr:tt)+) * $($t:tt)+) => {
         $crate::_g!(@point [s {$crate::_s!(@scalar [] $($expr)+)}  $($a)*] $($t)+)
     };
+    (@scalar [$($a:tt)*] dot($scalars:expr, $points:expr) $($t:tt)*) => {
+        $crate::_g!(@next [{$crate::op::lincomb($scalars.iter(), $points.iter())} $($a)*] $($t)*)
+    };
     (@scalar [$($a:tt)*] $ident:ident $($t:tt)*) => {
         // We've got an identifier "foo" go and try to match foo.bar
         // we don't know if this is a scalar yet.
@@ -137,6 +159,9 @@ macro_rules! _g {
         // so go onto the next operator
         $crate::_g!(@next [{$($aa).+.borrow()} $($a)*] $($t)*)
     };
+    (@point [$($a:tt)*] dot($scalars:expr, $points:expr) $($t:tt)*) => {
+        $crate::_g!(@next [{$crate::op::lincomb($scalars.iter(), $points.iter())} $($a)*] $($t)*)
+    };
     (@point [$($a:tt)*] $point:ident $($t:tt)*) => {
         $crate::_g!(@dot [$($a)*] [$point] $($t)*)
     };
@@ -264,7 +289,47 @@ macro_rules! _g {
 /// assert_eq!(g!(x * Xinv), *G);
 /// ```
 ///
+/// `dot(scalars, points)` is the sum of each scalar times the corresponding point (anything with
+/// an `iter()` method like a slice or `Vec` will do). It compiles to a single multi-scalar
+/// multiplication with [`lincomb`] which is much faster than adding up the terms one by one. Put
+/// parentheses around it if you want a scalar dot product instead.
+///
+/// ```
+/// # use secp256kfun::{g, s, Point, Scalar, G};
+/// let coeffs = vec![
+///     Scalar::random(&mut rand::thread_rng()),
+///     Scalar::random(&mut rand::thread_rng()),
+/// ];
+/// let points = vec![
+///     Point::random(&mut rand::thread_rng()),
+///     Point::random(&mut rand::thread_rng()),
+/// ];
+/// let (c0, c1, P0, P1) = (&coeffs[0], &coeffs[1], &points[0], &points[1]);
+/// assert_eq!(g!(dot(coeffs, points) + G), g!(c0 * P0 + c1 * P1 + G));
+/// assert_eq!(
+///     g!((dot(coeffs, coeffs)) * G),
+///     g!({ s!(c0 * c0 + c1 * c1) } * G)
+/// );
+/// ```
+///
+/// Additions, subtractions and negations never panic. Since the sum of two points may be the
+/// identity element the result is marked `Zero` unless the compiler can prove otherwise. This is
+/// what you want when checking an equation on untrusted inputs: use [`non_zero`] to turn the zero
+/// case into `None` and handle it like any other invalid input.
+///
+/// ```
+/// # use secp256kfun::{g, marker::*, Point, Scalar, G};
+/// let x = Scalar::random(&mut rand::thread_rng());
+/// let X = g!(x * G).normalize();
+/// let zero = g!(x * G - X);
+/// assert!(zero.is_zero());
+/// assert_eq!(g!(-zero + X), X);
+/// assert!(zero.normalize().non_zero().is_none());
+/// ```
+///
 /// [`double_mul`]: crate::op::double_mul
+/// [`non_zero`]: crate::Point::non_zero
+/// [`lincomb`]: crate::op::lincomb
 /// [`G`]: crate::G
 /// [`Point`]: crate::Point
 /// [`op`]: crate::op
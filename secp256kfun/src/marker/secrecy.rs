@@ -36,13 +36,43 @@
 /// let X = g!(x * H); // This will run faster (in variable time)
 /// ```
 ///
+/// # Custom secrecy levels
+///
+/// Downstream crates can define their own levels (e.g. `Shared` for values known to a quorum of
+/// parties but secret from everyone else) with [`impl_secrecy_level!`]. The rules for how levels
+/// relate are:
+///
+/// - A [`Public`] value may be [`coerce`]d to any level and any value may be coerced to [`Secret`].
+/// - Going to a less secret level is only possible with [`mark`].
+///
+/// ```
+/// use secp256kfun::{impl_secrecy_level, marker::*, Scalar};
+///
+/// /// Known to all members of the quorum.
+/// #[derive(Debug, Clone, Copy, Default, PartialEq)]
+/// pub struct Shared;
+/// impl_secrecy_level!(Shared, is_secret = true);
+///
+/// let public = Scalar::random(&mut rand::thread_rng()).mark::<Public>();
+/// let shared: Scalar<Shared> = public.coerce();
+/// let secret: Scalar<Secret> = shared.coerce();
+/// ```
+///
 /// [`Secret`]: crate::marker::Secret
 /// [`Point`]: crate::marker::Public
 /// [`Scalar`s]: crate::Scalar
 /// [`Point`s]: crate::Point
 /// [`mark`]: crate::marker::Mark::mark
+/// [`coerce`]: crate::Scalar::coerce
+/// [`impl_secrecy_level!`]: crate::impl_secrecy_level!
 /// [_specialization_]: https://github.com/rust-lang/rust/issues/31844
-pub trait Secrecy: Default + Clone + PartialEq + Copy + 'static {}
+pub trait Secrecy: Default + Clone + PartialEq + Copy + 'static {
+    /// Whether values with this marker are secret.
+    ///
+    /// `Debug` uses this to keep secret values out of logs (see [`fmt`](crate::fmt)). Defaults to
+    /// `true` so a marker is treated as secret unless it opts out.
+    const IS_SECRET: bool = true;
+}
 
 /// Indicates that the value is secret and therefore makes core operations
 /// executed on it to use  _constant time_ versions of the operations.
@@ -63,9 +93,41 @@ pub struct Secret;
 )]
 pub struct Public;
 
-impl Secrecy for Secret {}
+impl Secrecy for Secret {
+    const IS_SECRET: bool = true;
+}
+
+impl Secrecy for Public {
+    const IS_SECRET: bool = false;
+}
+
+/// Implemented by a [`Secrecy`] if its values may be used where `SNew` is expected without
+/// becoming any less secret.
+///
+/// See [`Secrecy`] for the rules.
+pub trait CoerceTo<SNew: Secrecy>: Secrecy {}
+
+impl<S: Secrecy> CoerceTo<Secret> for S {}
+impl CoerceTo<Public> for Public {}
 
-impl Secrecy for Public {}
+/// Implements [`Secrecy`] and the coercion rules against [`Secret`] and [`Public`] for a custom
+/// secrecy level.
+///
+/// The type must be `Debug + Clone + Copy + Default + PartialEq`. See [`Secrecy`] for an example.
+///
+/// [`Secrecy`]: crate::marker::Secrecy
+/// [`Secret`]: crate::marker::Secret
+/// [`Public`]: crate::marker::Public
+#[macro_export]
+macro_rules! impl_secrecy_level {
+    ($level:ty,is_secret = $is_secret:expr) => {
+        impl $crate::marker::Secrecy for $level {
+            const IS_SECRET: bool = $is_secret;
+        }
+        impl $crate::marker::CoerceTo<$level> for $level {}
+        impl $crate::marker::CoerceTo<$level> for $crate::marker::Public {}
+    };
+}
 
 mod change_marks {
     use super::*;
@@ -102,3 +164,32 @@ mod change_marks {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{marker::*, Point, Scalar};
+
+    #[derive(Debug, Clone, Copy, Default, PartialEq)]
+    struct Shared;
+    crate::impl_secrecy_level!(Shared, is_secret = true);
+
+    #[test]
+    fn custom_secrecy_level() {
+        let public = Scalar::random(&mut rand::thread_rng()).mark::<Public>();
+        let shared: Scalar<Shared> = public.coerce();
+        let secret: Scalar<Secret> = shared.clone().coerce();
+        assert_eq!(public, secret);
+
+        let point: Point<Normal, Shared> = Point::random(&mut rand::thread_rng()).coerce();
+        let _ = crate::g!(shared * point);
+        assert!(Shared::IS_SECRET);
+    }
+    #[test]
+    fn is_secret_defaults_to_true() {
+        #[derive(Debug, Clone, Copy, Default, PartialEq)]
+        struct Custom;
+        impl Secrecy for Custom {}
+        assert!(Custom::IS_SECRET);
+    }
+}
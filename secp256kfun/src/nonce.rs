@@ -18,9 +18,13 @@
 //! [`NonceGen`]: crate::nonce::NonceGen
 //! [`derive_nonce!`]: crate::derive_nonce!
 use crate::{hash::*, Scalar};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
 use core::marker::PhantomData;
-use digest::{generic_array::typenum::U32, Digest};
+use digest::{crypto_common::BlockSizeUser, generic_array::typenum::U32, Digest};
 use rand_core::RngCore;
+#[cfg(feature = "std")]
+use std::vec::Vec;
 
 /// A helper trait over RNGs that handle internal mutablility.
 ///
@@ -212,6 +216,425 @@ impl<H: Tagged, R> AddTag for Synthetic<H, R> {
     }
 }
 
+/// A nonce generator that implements [RFC6979] deterministic nonce generation with HMAC-DRBG.
+///
+/// The secret is the private key `x` and the data added to the hash after
+/// [`begin_derivation`](NonceGen::begin_derivation) is used as `h1`. ECDSA adds exactly the 32
+/// byte message hash so with `H` set to SHA-256 the nonces are the ones in the RFC and the test
+/// vectors of other implementations. Data of any other length (e.g. from Schnorr signing which adds
+/// the public key and message) is passed to the DRBG as is.
+///
+/// [`with_extra_entropy`](Rfc6979::with_extra_entropy) mixes 32 random bytes into each nonce
+/// as the additional data `k'` described in section 3.6 of the RFC. [`AddTag`] puts the hash of
+/// the tag in `k'` in front of them so an untagged `Rfc6979` still gives the nonces in the RFC.
+///
+/// # Examples
+///
+/// ```
+/// use secp256kfun::{derive_nonce, nonce::Rfc6979, s, Scalar};
+/// use sha2::{Digest, Sha256};
+/// let nonce_gen = Rfc6979::<Sha256>::default();
+/// let secret_key = s!(1);
+/// let message_hash = Sha256::digest(b"Satoshi Nakamoto");
+/// let nonce = derive_nonce!(
+///     nonce_gen => nonce_gen,
+///     secret => &secret_key,
+///     public => [&message_hash[..]]
+/// );
+/// assert_eq!(
+///     nonce.to_string(),
+///     "8f8a276c19f4149656b280621e358cce24f5f52542772691ee69063b74f15d15"
+/// );
+/// ```
+///
+/// [RFC6979]: https://datatracker.ietf.org/doc/html/rfc6979
+#[cfg(feature = "alloc")]
+#[derive(Debug, Default, Clone)]
+pub struct Rfc6979<H, R = ()> {
+    rng: R,
+    tag: Option<[u8; 32]>,
+    hash: PhantomData<H>,
+}
+
+#[cfg(feature = "alloc")]
+impl<H> Rfc6979<H> {
+    /// Adds 32 bytes of extra entropy from `rng` to each nonce.
+    pub fn with_extra_entropy<R: NonceRng>(rng: R) -> Rfc6979<H, R> {
+        Rfc6979 {
+            rng,
+            tag: None,
+            hash: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<H: Digest<OutputSize = U32> + BlockSizeUser + Clone + Default> NonceGen for Rfc6979<H> {
+    type Hash = Rfc6979Hash<H>;
+    fn begin_derivation(&self, secret: &Scalar) -> Self::Hash {
+        Rfc6979Hash::new(secret, self.tag, None)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<H, R> NonceGen for Rfc6979<H, R>
+where
+    H: Digest<OutputSize = U32> + BlockSizeUser + Clone + Default,
+    R: NonceRng,
+{
+    type Hash = Rfc6979Hash<H>;
+    fn begin_derivation(&self, secret: &Scalar) -> Self::Hash {
+        let mut extra_entropy = [0u8; 32];
+        self.rng.fill_bytes(&mut extra_entropy);
+        Rfc6979Hash::new(secret, self.tag, Some(extra_entropy))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<H: Digest<OutputSize = U32>, R> AddTag for Rfc6979<H, R> {
+    fn add_tag(self, tag: &str) -> Self {
+        let mut hash = H::new();
+        if let Some(previous) = self.tag {
+            Digest::update(&mut hash, previous);
+        }
+        Digest::update(&mut hash, tag.as_bytes());
+        Self {
+            tag: Some(hash.finalize().into()),
+            ..self
+        }
+    }
+}
+
+/// The [`Digest`] returned by [`Rfc6979`]. It collects `h1` and runs HMAC-DRBG when finalized.
+///
+/// Created with `Default` it uses the zero key.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default)]
+pub struct Rfc6979Hash<H> {
+    key: [u8; 32],
+    tag: Option<[u8; 32]>,
+    extra_entropy: Option<[u8; 32]>,
+    h1: Vec<u8>,
+    hash: PhantomData<H>,
+}
+
+#[cfg(feature = "alloc")]
+impl<H> core::fmt::Debug for Rfc6979Hash<H> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Rfc6979Hash").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<H> Rfc6979Hash<H> {
+    fn new(secret: &Scalar, tag: Option<[u8; 32]>, extra_entropy: Option<[u8; 32]>) -> Self {
+        Self {
+            key: secret.to_bytes(),
+            tag,
+            extra_entropy,
+            h1: Vec::new(),
+            hash: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<H: Digest<OutputSize = U32> + BlockSizeUser + Clone> Rfc6979Hash<H> {
+    fn hmac(key: &[u8; 32], data: &[&[u8]]) -> [u8; 32] {
+        crate::hash::hmac::<H>(key, data).into()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<H> digest::Update for Rfc6979Hash<H> {
+    fn update(&mut self, data: &[u8]) {
+        self.h1.extend_from_slice(data)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<H> digest::OutputSizeUser for Rfc6979Hash<H> {
+    type OutputSize = U32;
+}
+
+#[cfg(feature = "alloc")]
+impl<H> digest::HashMarker for Rfc6979Hash<H> {}
+
+#[cfg(feature = "alloc")]
+impl<H: Digest<OutputSize = U32> + BlockSizeUser + Clone> digest::FixedOutput for Rfc6979Hash<H> {
+    fn finalize_into(self, out: &mut digest::Output<Self>) {
+        // bits2octets(h1) for a hash the size of the curve order
+        let h1 = match <[u8; 32]>::try_from(&self.h1[..]) {
+            Ok(h1) => Scalar::from_bytes_mod_order(h1).to_bytes().to_vec(),
+            Err(_) => self.h1,
+        };
+        let x = &self.key[..];
+        // k' is the tag followed by the extra entropy
+        let tag = self.tag.as_ref().map(|t| &t[..]).unwrap_or(&[]);
+        let extra_entropy = self.extra_entropy.as_ref().map(|e| &e[..]).unwrap_or(&[]);
+
+        let mut v = [0x01u8; 32];
+        let mut k = [0x00u8; 32];
+        k = Self::hmac(&k, &[&v, &[0x00], x, &h1, tag, extra_entropy]);
+        v = Self::hmac(&k, &[&v]);
+        k = Self::hmac(&k, &[&v, &[0x01], x, &h1, tag, extra_entropy]);
+        v = Self::hmac(&k, &[&v]);
+        loop {
+            v = Self::hmac(&k, &[&v]);
+            if matches!(Scalar::from_bytes(v), Some(candidate) if !candidate.is_zero()) {
+                out.copy_from_slice(&v);
+                return;
+            }
+            k = Self::hmac(&k, &[&v, &[0x00]]);
+            v = Self::hmac(&k, &[&v]);
+        }
+    }
+}
+
+/// A fast random nonce generator that mixes a keyed ChaCha20 stream into each nonce.
+///
+/// [`Synthetic`] asks its [`NonceRng`] for fresh randomness for every nonce, which can be slow if
+/// the randomness comes from the OS. `ChaCha20` is seeded once (e.g. from the OS when the process
+/// starts) and then each call to [`begin_derivation`] takes the next block of the stream instead.
+/// The nonce is the hash of the block, the secret and every public input added afterwards so,
+/// like [`Synthetic`], it never repeats for different inputs even if the stream does (e.g. two
+/// generators created from the same seed) and it stays secret even if the seed is weak or leaks.
+///
+/// Even so, don't let two copies of the same generator run. This is why it doesn't implement
+/// `Clone`. If the process forks after the generator is created both processes will produce the
+/// same nonces for the same inputs. Create it after forking or re-seed each child.
+///
+/// # Examples
+///
+/// ```
+/// use rand::RngCore;
+/// use secp256kfun::{derive_nonce, nonce::ChaCha20, Scalar};
+/// use sha2::Sha256;
+/// let mut seed = [0u8; 32];
+/// rand::thread_rng().fill_bytes(&mut seed);
+/// let nonce_gen = ChaCha20::<Sha256>::new(seed);
+/// let secret = Scalar::random(&mut rand::thread_rng());
+/// let nonce_1 = derive_nonce!(nonce_gen => nonce_gen, secret => &secret, public => [b"hello"]);
+/// let nonce_2 = derive_nonce!(nonce_gen => nonce_gen, secret => &secret, public => [b"hello"]);
+/// assert_ne!(nonce_1, nonce_2);
+/// ```
+///
+/// [`begin_derivation`]: NonceGen::begin_derivation
+pub struct ChaCha20<H> {
+    key: [u8; 32],
+    counter: core::sync::atomic::AtomicU64,
+    nonce_hash: H,
+}
+
+impl<H: Default> ChaCha20<H> {
+    /// Creates the generator from a 32 byte seed which must be uniformly random and secret.
+    pub fn new(seed: [u8; 32]) -> Self {
+        Self {
+            key: seed,
+            counter: core::sync::atomic::AtomicU64::new(0),
+            nonce_hash: H::default(),
+        }
+    }
+}
+
+impl<H> ChaCha20<H> {
+    fn next_block(&self) -> [u8; 64] {
+        let counter = self
+            .counter
+            .fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+        assert_ne!(counter, u64::MAX, "ChaCha20 nonce stream exhausted");
+        // the high half of the 64-bit counter goes in the first word of the nonce
+        let mut nonce = [0u8; 12];
+        nonce[..4].copy_from_slice(&((counter >> 32) as u32).to_le_bytes());
+        chacha20_block(&self.key, counter as u32, &nonce)
+    }
+}
+
+impl<H> core::fmt::Debug for ChaCha20<H> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("ChaCha20").finish_non_exhaustive()
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone> NonceGen for ChaCha20<H> {
+    type Hash = H;
+    fn begin_derivation(&self, secret: &Scalar) -> Self::Hash {
+        self.nonce_hash
+            .clone()
+            .add(&self.next_block()[..32])
+            .add(secret)
+    }
+}
+
+impl<H: Tagged> AddTag for ChaCha20<H> {
+    fn add_tag(self, tag: &str) -> Self {
+        Self {
+            nonce_hash: self
+                .nonce_hash
+                .tagged(&[tag.as_bytes(), b"/nonce"].concat()),
+            ..self
+        }
+    }
+}
+
+/// The ChaCha20 block function from [RFC 8439] with its 32-bit block counter and 96-bit nonce.
+///
+/// [`ChaCha20`] is built on this. It is public so other crates in the family can build stream
+/// ciphers on the same implementation.
+///
+/// [RFC 8439]: https://www.rfc-editor.org/rfc/rfc8439
+pub fn chacha20_block(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u8; 64] {
+    fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+        state[a] = state[a].wrapping_add(state[b]);
+        state[d] = (state[d] ^ state[a]).rotate_left(16);
+        state[c] = state[c].wrapping_add(state[d]);
+        state[b] = (state[b] ^ state[c]).rotate_left(12);
+        state[a] = state[a].wrapping_add(state[b]);
+        state[d] = (state[d] ^ state[a]).rotate_left(8);
+        state[c] = state[c].wrapping_add(state[d]);
+        state[b] = (state[b] ^ state[c]).rotate_left(7);
+    }
+    let word = |bytes: &[u8]| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+    let mut initial = [0u32; 16];
+    // "expand 32-byte k"
+    initial[..4].copy_from_slice(&[0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]);
+    for (i, chunk) in key.chunks(4).enumerate() {
+        initial[4 + i] = word(chunk);
+    }
+    initial[12] = counter;
+    initial[13] = word(&nonce[..4]);
+    initial[14] = word(&nonce[4..8]);
+    initial[15] = word(&nonce[8..]);
+
+    let mut state = initial;
+    for _ in 0..10 {
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    let mut block = [0u8; 64];
+    for (i, (word, initial)) in state.iter().zip(initial.iter()).enumerate() {
+        block[i * 4..(i + 1) * 4].copy_from_slice(&word.wrapping_add(*initial).to_le_bytes());
+    }
+    block
+}
+
+/// A [`NonceGen`] wrapper that remembers which message each nonce was used for.
+///
+/// A correctly implemented signing algorithm never uses the same nonce for two different messages
+/// under the same key but a bug, a misconfigured [`NonceGen`] or a hardware fault could make it do
+/// so and that would leak the secret key. For long running signing services that want a second
+/// line of defence, the signing algorithm can call [`record`] after deriving each nonce and
+/// refuse to release the signature if it returns an error.
+///
+/// It stores two 32-byte hashes per signature for as long as it lives so it is not suitable for
+/// signing an unbounded number of messages. Tagging it with [`AddTag::add_tag`] keeps what it has
+/// recorded so far.
+///
+/// # Examples
+///
+/// ```
+/// use secp256kfun::{
+///     nonce::{Deterministic, Guarded},
+///     Point,
+/// };
+/// use sha2::Sha256;
+/// let nonce_gen = Guarded::new(Deterministic::<Sha256>::default());
+/// let (key, nonce) = (
+///     Point::random(&mut rand::thread_rng()),
+///     Point::random(&mut rand::thread_rng()),
+/// );
+/// assert!(nonce_gen.record(key, nonce, b"hello".as_ref()).is_ok());
+/// assert!(nonce_gen.record(key, nonce, b"hello".as_ref()).is_ok());
+/// assert!(nonce_gen.record(key, nonce, b"goodbye".as_ref()).is_err());
+/// ```
+///
+/// [`record`]: Guarded::record
+#[cfg(all(feature = "std", feature = "sha2"))]
+#[derive(Debug, Default)]
+pub struct Guarded<NG> {
+    nonce_gen: NG,
+    used: std::sync::Mutex<std::collections::BTreeMap<[u8; 32], [u8; 32]>>,
+}
+
+#[cfg(all(feature = "std", feature = "sha2"))]
+impl<NG: NonceGen> Guarded<NG> {
+    /// Wraps `nonce_gen`.
+    pub fn new(nonce_gen: NG) -> Self {
+        Self {
+            nonce_gen,
+            used: Default::default(),
+        }
+    }
+
+    /// Records that `nonce` was used to sign `message` under `public_key`.
+    ///
+    /// `public_key` and `nonce` should have fixed length encodings. Returns an error if the same
+    /// nonce was previously recorded for the same key with a different message. Recording the same
+    /// triple more than once is fine.
+    pub fn record(
+        &self,
+        public_key: impl HashInto,
+        nonce: impl HashInto,
+        message: impl HashInto,
+    ) -> Result<(), NonceReuse> {
+        // a fixed hash so the record doesn't depend on how the nonce generator is configured
+        let key_and_nonce = sha2::Sha256::default()
+            .add(public_key)
+            .add(nonce)
+            .finalize()
+            .into();
+        let message = sha2::Sha256::default().add(message).finalize().into();
+        let mut used = self.used.lock().expect("lock poisoned");
+        match used.insert(key_and_nonce, message) {
+            Some(previous) if previous != message => {
+                used.insert(key_and_nonce, previous);
+                Err(NonceReuse)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(all(feature = "std", feature = "sha2"))]
+impl<NG: NonceGen> NonceGen for Guarded<NG> {
+    type Hash = NG::Hash;
+    fn begin_derivation(&self, secret: &Scalar) -> Self::Hash {
+        self.nonce_gen.begin_derivation(secret)
+    }
+}
+
+#[cfg(all(feature = "std", feature = "sha2"))]
+impl<NG: AddTag + NonceGen> AddTag for Guarded<NG> {
+    fn add_tag(self, tag: &str) -> Self {
+        // keep the history so tagging can't be used to clear it
+        Self {
+            nonce_gen: self.nonce_gen.add_tag(tag),
+            used: self.used,
+        }
+    }
+}
+
+/// Error returned by [`Guarded::record`] when a nonce would be reused for a different message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonceReuse;
+
+impl core::fmt::Display for NonceReuse {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "nonce was already used to sign a different message")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NonceReuse {}
+
 /// Trait for things that can domain separate themselves.
 pub trait AddTag {
     /// Tells the invocant to return a new version of itself modified with the
@@ -286,6 +709,182 @@ mod test {
         )
     }
 
+    #[test]
+    fn bip340_tag_matches_hash_tags() {
+        use crate::hash::{
+            tags::{Bip340Aux, Bip340Nonce},
+            HashAdd, TaggedHash,
+        };
+        let secret = s!(42);
+        let nonce_gen = Deterministic::<Sha256>::default().add_tag("BIP0340");
+        assert_eq!(
+            nonce_gen.begin_derivation(&secret).finalize(),
+            TaggedHash::<Bip340Nonce, Sha256>::default()
+                .hash()
+                .add(&secret)
+                .finalize()
+        );
+        let nonce_gen = Synthetic::<Sha256, GlobalRng<ThreadRng>>::default().add_tag("BIP0340");
+        assert_eq!(
+            nonce_gen.aux_hash.finalize(),
+            TaggedHash::<Bip340Aux, Sha256>::default().hash().finalize()
+        );
+    }
+
+    #[cfg(all(feature = "std", feature = "sha2"))]
+    #[test]
+    fn guarded_detects_reuse_for_different_message() {
+        let nonce_gen = Guarded::new(Deterministic::<Sha256>::default());
+        let key = crate::Point::random(&mut rand::thread_rng());
+        let nonce = crate::Point::random(&mut rand::thread_rng());
+        let other_nonce = crate::Point::random(&mut rand::thread_rng());
+        assert_eq!(nonce_gen.record(key, nonce, b"one".as_ref()), Ok(()));
+        assert_eq!(nonce_gen.record(key, other_nonce, b"two".as_ref()), Ok(()));
+        assert_eq!(nonce_gen.record(nonce, key, b"two".as_ref()), Ok(()));
+        assert_eq!(
+            nonce_gen.record(key, nonce, b"two".as_ref()),
+            Err(NonceReuse)
+        );
+        // the original message is still the one on record
+        assert_eq!(nonce_gen.record(key, nonce, b"one".as_ref()), Ok(()));
+        // tagging keeps the record
+        let nonce_gen = nonce_gen.add_tag("test");
+        assert_eq!(
+            nonce_gen.record(key, nonce, b"two".as_ref()),
+            Err(NonceReuse)
+        );
+        assert_eq!(nonce_gen.record(key, nonce, b"one".as_ref()), Ok(()));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn rfc6979_test_vectors() {
+        use core::str::FromStr;
+        // from https://github.com/bitcoinjs/bitcoinjs-lib/blob/master/test/fixtures/ecdsa.json
+        let vectors = [
+            (
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                &b"Satoshi Nakamoto"[..],
+                "8f8a276c19f4149656b280621e358cce24f5f52542772691ee69063b74f15d15",
+            ),
+            (
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                &b"All those moments will be lost in time, like tears in rain. Time to die..."[..],
+                "38aa22d72376b4dbc472e06c3ba403ee0a394da63fc58d88686c611aba98d6b3",
+            ),
+            (
+                "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140",
+                &b"Satoshi Nakamoto"[..],
+                "33a19b60e25fb6f4435af53a3d42d493644827367e6453928554f43e49aa6f90",
+            ),
+            (
+                "f8b8af8ce3c7cca5e300d33939540c10d45ce001b8f252bfbc57ba0342904181",
+                &b"Alan Turing"[..],
+                "525a82b70e67874398067543fd84c83d30c175fdc45fdeee082fe13b1d7cfdf1",
+            ),
+        ];
+        let nonce_gen = Rfc6979::<Sha256>::default();
+        for (secret, message, nonce) in vectors {
+            let secret = Scalar::<Secret>::from_str(secret).unwrap();
+            let message_hash = Sha256::digest(message);
+            assert_eq!(
+                derive_nonce!(
+                    nonce_gen => nonce_gen,
+                    secret => &secret,
+                    public => [&message_hash[..]]
+                ),
+                Scalar::<Secret>::from_str(nonce).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn rfc6979_add_tag_changes_nonce() {
+        let one = s!(1);
+        let untagged = Rfc6979::<Sha256>::default();
+        let tagged = Rfc6979::<Sha256>::default().add_tag("one");
+        assert_ne!(get_nonce!(untagged, one), get_nonce!(tagged, one));
+        assert_eq!(
+            get_nonce!(tagged, one),
+            get_nonce!(Rfc6979::<Sha256>::default().add_tag("one"), one)
+        );
+        assert_ne!(
+            get_nonce!(tagged, one),
+            get_nonce!(Rfc6979::<Sha256>::default().add_tag("two"), one)
+        );
+        assert_ne!(
+            get_nonce!(tagged, one),
+            get_nonce!(tagged.clone().add_tag("two"), one)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn rfc6979_extra_entropy_is_random() {
+        let nonce_gen = Rfc6979::<Sha256>::with_extra_entropy(GlobalRng::<ThreadRng>::default());
+        let one = s!(1);
+        assert_ne!(get_nonce!(nonce_gen, one), get_nonce!(nonce_gen, one));
+        let nonce_gen = Rfc6979::<Sha256>::default();
+        assert_eq!(get_nonce!(nonce_gen, one), get_nonce!(nonce_gen, one));
+    }
+
+    #[test]
+    fn chacha20_block_test_vector() {
+        // RFC 8439 section 2.3.2
+        let key: [u8; 32] = crate::hex::decode_array(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        )
+        .unwrap();
+        let block = chacha20_block(&key, 1, &[
+            0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 0x4a, 0x00, 0x00, 0x00, 0x00,
+        ]);
+        let expected: [u8; 64] = crate::hex::decode_array(
+            "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e\
+             d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e",
+        )
+        .unwrap();
+        assert_eq!(block, expected);
+    }
+
+    #[test]
+    fn chacha20_nonce_gen() {
+        let nonce_gen = ChaCha20::<Sha256>::new([42u8; 32]);
+        let one = s!(1);
+        let nonce_1 = get_nonce!(nonce_gen, one);
+        let nonce_2 = get_nonce!(nonce_gen, one);
+        assert_ne!(nonce_1, nonce_2);
+        // the nonce depends on the secret even at the same point in the stream
+        let nonce_gen = ChaCha20::<Sha256>::new([42u8; 32]);
+        assert_ne!(get_nonce!(nonce_gen, s!(2)), nonce_1);
+        // and on the tag
+        let nonce_gen = ChaCha20::<Sha256>::new([42u8; 32]).add_tag("test");
+        assert_ne!(get_nonce!(nonce_gen, one), nonce_1);
+    }
+
+    #[test]
+    fn chacha20_same_seed_different_messages() {
+        let secret = s!(1);
+        let nonce = |nonce_gen: &ChaCha20<Sha256>, message: &[u8]| {
+            derive_nonce!(
+                nonce_gen => nonce_gen,
+                secret => &secret,
+                public => [message]
+            )
+        };
+        // re-seeding restarts the stream but the messages still separate the nonces
+        let nonce_1 = nonce(&ChaCha20::new([42u8; 32]), b"one");
+        let nonce_2 = nonce(&ChaCha20::new([42u8; 32]), b"two");
+        assert_ne!(nonce_1, nonce_2);
+        assert_eq!(nonce(&ChaCha20::new([42u8; 32]), b"one"), nonce_1);
+        // a derivation that has begun still absorbs what is added to it
+        let prefix = ChaCha20::<Sha256>::new([42u8; 32]).begin_derivation(&secret);
+        assert_ne!(
+            prefix.clone().add(b"one".as_ref()).finalize(),
+            prefix.add(b"two".as_ref()).finalize()
+        );
+    }
+
     #[test]
     fn synthetic_nonce_gen_is_random() {
         let nonce_gen_1 = Synthetic::<Sha256, GlobalRng<ThreadRng>>::default().add_tag("PROTO_ONE");
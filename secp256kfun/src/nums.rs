@@ -0,0 +1,174 @@
+//! Proving that nobody knows the secret key of a point ("nothing up my sleeve" points).
+//!
+//! A Taproot output that should only be spendable through its script paths needs an internal key
+//! that nobody can sign for. [BIP341] suggests `H + r * G` where `H` is a point nobody knows the
+//! discrete logarithm of and `r` is a random blinding so the key can't be recognised on chain. In
+//! a multi-party setting every party needs to be convinced that nobody (in particular not the
+//! party who proposed the key) knows its secret key.
+//!
+//! [`hash_to_curve`] derives `H` from some preimage data (e.g. the session id and the parties'
+//! public keys) and a [`NumsProof`] is the blinding `r`. Given the preimage and the proof anyone
+//! can recompute the key and check it's the one they were given. Since `H` is the
+//! [`elligator::decode`] of a hash output, nobody can choose it so that they know its discrete
+//! logarithm, and so nobody knows the discrete logarithm of `H + r * G` either.
+//!
+//! # Examples
+//!
+//! ```
+//! use secp256kfun::nums::NumsProof;
+//! let preimage = b"my-protocol/session-42";
+//! // the proposer picks a random blinding so the key looks like any other key on chain
+//! let proof = NumsProof::random(&mut rand::thread_rng());
+//! let internal_key = proof.point::<sha2::Sha256>(preimage);
+//! // everyone else checks the internal key against the preimage and the proof
+//! assert!(proof.verify::<sha2::Sha256>(preimage, &internal_key));
+//! assert!(!proof.verify::<sha2::Sha256>(b"my-protocol/session-43", &internal_key));
+//! ```
+//!
+//! [BIP341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki#constructing-and-spending-taproot-outputs
+//! [`elligator::decode`]: crate::elligator::decode
+use crate::{
+    digest::{generic_array::typenum::U32, Digest},
+    elligator, g,
+    hash::{HashAdd, Tagged},
+    marker::*,
+    rand_core::RngCore,
+    Point, Scalar, G,
+};
+
+/// Hashes `preimage` to a point nobody knows the discrete logarithm of.
+///
+/// # Examples
+///
+/// ```
+/// use secp256kfun::nums::hash_to_curve;
+/// let H = hash_to_curve::<sha2::Sha256>(b"my-protocol");
+/// assert_eq!(H, hash_to_curve::<sha2::Sha256>(b"my-protocol"));
+/// assert_ne!(H, hash_to_curve::<sha2::Sha256>(b"other-protocol"));
+/// ```
+pub fn hash_to_curve<H: Tagged + Digest<OutputSize = U32>>(preimage: &[u8]) -> Point {
+    elligator::decode_hash(H::default().tagged(b"secp256kfun/nums").add(preimage))
+}
+
+/// Proves that the point `hash_to_curve(preimage) + blinding * G` has no known secret key.
+///
+/// See the [module documentation](crate::nums).
+#[derive(Clone, Copy, PartialEq)]
+pub struct NumsProof {
+    /// The blinding `r` added to the hashed point (as `r * G`).
+    pub blinding: Scalar<Public, Zero>,
+}
+
+impl NumsProof {
+    /// Creates a proof with a particular blinding.
+    pub fn new(blinding: Scalar<Public, Zero>) -> Self {
+        NumsProof { blinding }
+    }
+
+    /// Creates a proof with a random blinding.
+    pub fn random(rng: &mut impl RngCore) -> Self {
+        NumsProof::new(Scalar::random(rng).mark::<(Public, Zero)>())
+    }
+
+    /// A proof with a zero blinding i.e. the point is just the hashed point (made to have an even
+    /// y-coordinate). Use this when you don't mind the key being recognisable.
+    pub fn unblinded() -> Self {
+        NumsProof::new(Scalar::zero().mark::<Public>())
+    }
+
+    /// Computes the x-only point this proof is for.
+    ///
+    /// The point is negated if needed to have an even y-coordinate (so it can be used as a Taproot
+    /// internal key) which doesn't change that nobody knows its discrete logarithm.
+    pub fn point<H: Tagged + Digest<OutputSize = U32>>(&self, preimage: &[u8]) -> Point<EvenY> {
+        let H = hash_to_curve::<H>(preimage);
+        let r = &self.blinding;
+        g!(H + r * G)
+            .normalize()
+            .non_zero()
+            .expect("computationally unreachable: would need to know the dlog of H")
+            .into_point_with_even_y()
+            .0
+    }
+
+    /// Checks that `point` is the point for `preimage` and this proof.
+    #[must_use]
+    pub fn verify<H: Tagged + Digest<OutputSize = U32>>(
+        &self,
+        preimage: &[u8],
+        point: &Point<EvenY>,
+    ) -> bool {
+        self.point::<H>(preimage) == *point
+    }
+
+    /// Serializes the proof as the 32 bytes of the blinding.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.blinding.to_bytes()
+    }
+
+    /// Deserializes a proof from 32 bytes.
+    ///
+    /// Returns `None` if the bytes aren't a scalar less than the curve order.
+    pub fn from_bytes(bytes: [u8; 32]) -> Option<Self> {
+        Scalar::from_bytes(bytes).map(|blinding| NumsProof::new(blinding.mark::<Public>()))
+    }
+}
+
+crate::impl_fromstr_deserialize! {
+    name => "NUMS proof",
+    fn from_bytes(bytes: [u8;32]) -> Option<NumsProof> {
+        NumsProof::from_bytes(bytes)
+    }
+}
+
+crate::impl_display_debug_serialize! {
+    fn to_bytes(proof: &NumsProof) -> [u8;32] {
+        proof.to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+    #[cfg(feature = "std")]
+    use std::string::ToString;
+
+    #[test]
+    fn verify_nums_proof() {
+        let preimage = b"test";
+        let proof = NumsProof::random(&mut rand::thread_rng());
+        let point = proof.point::<Sha256>(preimage);
+        assert!(proof.verify::<Sha256>(preimage, &point));
+        assert!(!proof.verify::<Sha256>(b"tset", &point));
+        assert!(!NumsProof::random(&mut rand::thread_rng()).verify::<Sha256>(preimage, &point));
+        assert!(!NumsProof::unblinded().verify::<Sha256>(preimage, &point));
+
+        // blinding changes the point by exactly r * G
+        let H = hash_to_curve::<Sha256>(preimage);
+        let r = proof.blinding;
+        assert_eq!(
+            point.to_xonly_bytes(),
+            g!(H + r * G)
+                .normalize()
+                .non_zero()
+                .unwrap()
+                .to_xonly_bytes()
+        );
+        assert_eq!(
+            NumsProof::unblinded()
+                .point::<Sha256>(preimage)
+                .to_xonly_bytes(),
+            H.to_xonly_bytes()
+        );
+    }
+
+    #[test]
+    fn nums_proof_roundtrip() {
+        let proof = NumsProof::random(&mut rand::thread_rng());
+        assert_eq!(NumsProof::from_bytes(proof.to_bytes()), Some(proof));
+        assert_eq!(NumsProof::from_bytes([0xff; 32]), None);
+        #[cfg(feature = "std")]
+        assert_eq!(proof.to_string().parse::<NumsProof>().unwrap(), proof);
+    }
+}
@@ -19,16 +19,47 @@
 //! let X2 = op::scalar_mul_point(&x, &H); // slow
 //! assert_eq!(X1, X2);
 //! ```
+//!
+//! # Using the operations directly
+//!
+//! The functions in this module are a stable part of the public API. Calling them directly is
+//! useful in hot inner loops where you want to control exactly which operation is done and avoid
+//! the intermediate values the macros may create. They follow the same rules as the macros:
+//!
+//! - Point operations take points by reference and return a `Point<Jacobian, Public, _>`. Call
+//!   [`point_normalize`] (or [`Point::normalize`]) once at the end rather than after each step.
+//! - Operations that could result in zero return a `Zero` marked value.
+//! - Everything is constant time unless the inputs are marked [`Public`].
+//!
+//! For example, computing `2^k * X` by repeated doubling:
+//!
+//! ```
+//! use secp256kfun::{g, marker::*, op, Point, Scalar};
+//! let X = Point::random(&mut rand::thread_rng());
+//! let mut acc = op::point_double(&X);
+//! for _ in 1..10 {
+//!     acc = op::point_double(&acc);
+//! }
+//! let k = Scalar::from(1024u32).mark::<Public>();
+//! assert_eq!(op::point_normalize(acc), g!(k * X).normalize());
+//! ```
+//!
 //! [`Points`]: crate::Point
 //! [`Scalars`]: crate::Scalar
 //! [`specialized`]: https://github.com/rust-lang/rust/issues/31844
 //! [`G`]: crate::G
+//! [`Public`]: crate::marker::Public
+//! [`Point::normalize`]: crate::Point::normalize
 #[allow(unused_imports)]
 use crate::{
-    backend::{self, ConstantTime, TimeSensitive, VariableTime},
+    backend::{self, BackendScalar, ConstantTime, TimeSensitive, VariableTime},
     marker::*,
     Point, Scalar,
 };
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::vec::Vec;
 
 /// Computes `x * A + y * B` more efficiently than calling [`scalar_mul_point`] twice.
 pub fn double_mul<ZA, SA, TA, ZX, SX, ZB, SB, TB, ZY, SY>(
@@ -43,7 +74,7 @@ pub fn double_mul<ZA, SA, TA, ZX, SX, ZB, SB, TB, ZY, SY>(
     )
 }
 
-/// Computes multiplies the point `P` by the scalar `x`.
+/// Multiplies the point `P` by the scalar `x`.
 pub fn scalar_mul_point<Z1, S1, T2, S2, Z2>(
     x: &Scalar<S1, Z1>,
     P: &Point<T2, S2, Z2>,
@@ -120,6 +151,13 @@ pub fn point_add<Z1, Z2, S1, S2, T1, T2>(
     Point::from_inner(ConstantTime::point_add_point(&A.0, &B.0), Jacobian)
 }
 
+/// Doubles a point.
+///
+/// This is faster than `point_add(A, A)`.
+pub fn point_double<T, S, Z>(A: &Point<T, S, Z>) -> Point<Jacobian, Public, Z> {
+    Point::from_inner(ConstantTime::point_double(&A.0), Jacobian)
+}
+
 /// Checks if two points are equal
 pub fn point_eq<Z1, Z2, S1, S2, T1, T2>(A: &Point<T1, S1, Z1>, B: &Point<T2, S2, Z2>) -> bool {
     ConstantTime::point_eq_point(&A.0, &B.0)
@@ -148,7 +186,23 @@ pub fn point_normalize<T, S, Z>(mut A: Point<T, S, Z>) -> Point<Normal, S, Z> {
     Point::from_inner(A.0, Normal)
 }
 
+/// Normalize many points at once.
+///
+/// This does a single field inversion rather than one for each point so it's much faster than
+/// calling [`point_normalize`] on each of them.
+#[cfg(feature = "alloc")]
+pub fn point_normalize_batch<T, S, Z>(points: Vec<Point<T, S, Z>>) -> Vec<Point<Normal, S, Z>> {
+    let mut inner = points.into_iter().map(|point| point.0).collect::<Vec<_>>();
+    ConstantTime::point_normalize_batch(&mut inner);
+    inner
+        .into_iter()
+        .map(|point| Point::from_inner(point, Normal))
+        .collect()
+}
+
 /// Does a linear combination of points
+///
+/// If one of the iterators is longer than the other the extra items are ignored.
 pub fn lincomb<'a, T1: 'a, S1: 'a, Z1: 'a, S2: 'a, Z2: 'a>(
     scalars: impl IntoIterator<Item = &'a Scalar<S2, Z2>>,
     points: impl IntoIterator<Item = &'a Point<T1, S1, Z1>>,
@@ -162,6 +216,22 @@ pub fn lincomb<'a, T1: 'a, S1: 'a, Z1: 'a, S2: 'a, Z2: 'a>(
     )
 }
 
+/// Computes the sum of the products of corresponding scalars from `a` and `b`.
+///
+/// If one of the iterators is longer than the other the extra scalars are ignored.
+pub fn scalar_dot<'a, S1: 'a, Z1: 'a, S2: 'a, Z2: 'a>(
+    a: impl IntoIterator<Item = &'a Scalar<S1, Z1>>,
+    b: impl IntoIterator<Item = &'a Scalar<S2, Z2>>,
+) -> Scalar<Secret, Zero> {
+    Scalar::from_inner(
+        a.into_iter()
+            .zip(b)
+            .fold(backend::Scalar::zero(), |acc, (a, b)| {
+                ConstantTime::scalar_add(&acc, &ConstantTime::scalar_mul(&a.0, &b.0))
+            }),
+    )
+}
+
 /// Check if a point has an even y-coordinate
 pub fn point_is_y_even<T: Normalized, S>(A: &Point<T, S, NonZero>) -> bool {
     ConstantTime::norm_point_is_y_even(&A.0)
@@ -212,5 +282,13 @@ mod test {
             assert_eq!(lincomb([&a,&b,&c], [&A,&B,&C]),
                        point_add(&scalar_mul_point(&a, &A), &point_add(&scalar_mul_point(&b, &B), &scalar_mul_point(&c, &C))))
         }
+
+        #[test]
+        fn double_against_add(A in any::<Point>()) {
+            use crate::op::*;
+            let doubled = point_double(&A);
+            assert_eq!(doubled, point_add(&A, &A));
+            assert_eq!(point_double(&doubled), point_add(&doubled, &doubled));
+        }
     }
 }
@@ -0,0 +1,188 @@
+//! Pay-to-contract: committing to a contract in a public key.
+//!
+//! Tweaking a public key `P` to `P + H(P || contract) * G` commits it to `contract` so that the
+//! owner of `P` can still sign for the tweaked key (by adding the same tweak to their secret key)
+//! while anyone who learns `P` and the contract can check the commitment. Timestamping schemes use
+//! this to commit to a document hash in an ordinary looking key and federated sidechains use it to
+//! tie a peg-in address to the sidechain address the funds go to.
+//!
+//! [`Point::p2c_tweak`] returns the tweaked key and a [`P2CProof`] which is the original key. The
+//! proof works in both directions: from the tweaked key and the contract it shows what the
+//! original key was and from the original key it shows which tweaked key commits to the contract.
+//!
+//! The tweak is the tagged hash (see [`Tagged`]) with the tag `secp256kfun/p2c` of the compressed
+//! original key followed by the contract. You choose the hash function `H` and have to use the
+//! same one to check the commitment.
+//!
+//! # Examples
+//!
+//! ```
+//! use secp256kfun::{KeyPair, Scalar};
+//! use sha2::Sha256;
+//! let keypair = KeyPair::new(Scalar::random(&mut rand::thread_rng()));
+//! let contract = b"sha256 of a document to timestamp";
+//! let (tweaked_key, proof) = keypair.public_key().p2c_tweak::<Sha256>(contract);
+//! // later anyone can check the key commits to the contract
+//! assert!(proof.verify::<Sha256>(&tweaked_key, contract));
+//! assert!(!proof.verify::<Sha256>(&tweaked_key, b"some other document"));
+//! // and the owner of the original key can sign for the tweaked key
+//! assert_eq!(
+//!     keypair.p2c_tweak::<Sha256>(contract).public_key(),
+//!     tweaked_key
+//! );
+//! ```
+//!
+//! [`Tagged`]: crate::hash::Tagged
+use crate::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    hash::{HashAdd, Tagged},
+    marker::*,
+    s, KeyPair, Point, Scalar, G,
+};
+
+/// The tweak that commits `original` to `contract`.
+///
+/// This is what [`Point::p2c_tweak`] adds (times `G`) to the key.
+pub fn tweak<H: Tagged + Digest<OutputSize = U32>>(
+    original: &Point,
+    contract: &[u8],
+) -> Scalar<Public, Zero> {
+    let hash = H::default()
+        .tagged(b"secp256kfun/p2c")
+        .add(original)
+        .add(contract)
+        .finalize();
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(hash.as_slice());
+    Scalar::from_bytes_mod_order(bytes).mark::<Public>()
+}
+
+/// Proof that a key made with [`Point::p2c_tweak`] commits to a contract.
+///
+/// The proof is just the original key.
+///
+/// See the [module documentation](crate::p2c).
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct P2CProof {
+    /// The key before it was tweaked.
+    pub original: Point,
+}
+
+impl P2CProof {
+    /// Recomputes the tweaked key from the original key and `contract`.
+    pub fn tweaked_key<H: Tagged + Digest<OutputSize = U32>>(&self, contract: &[u8]) -> Point {
+        tweak_point::<H>(&self.original, contract)
+    }
+
+    /// Checks that `tweaked_key` is the original key tweaked to commit to `contract`.
+    #[must_use]
+    pub fn verify<H: Tagged + Digest<OutputSize = U32>>(
+        &self,
+        tweaked_key: &Point,
+        contract: &[u8],
+    ) -> bool {
+        self.tweaked_key::<H>(contract) == *tweaked_key
+    }
+
+    /// Serializes the proof as the compressed original key.
+    pub fn to_bytes(&self) -> [u8; 33] {
+        self.original.to_bytes()
+    }
+
+    /// Deserializes a proof from a compressed key.
+    pub fn from_bytes(bytes: [u8; 33]) -> Option<Self> {
+        Point::from_bytes(bytes).map(|original| P2CProof { original })
+    }
+}
+
+crate::impl_fromstr_deserialize! {
+    name => "pay-to-contract proof",
+    fn from_bytes(bytes: [u8;33]) -> Option<P2CProof> {
+        P2CProof::from_bytes(bytes)
+    }
+}
+
+crate::impl_display_debug_serialize! {
+    fn to_bytes(proof: &P2CProof) -> [u8;33] {
+        proof.to_bytes()
+    }
+}
+
+fn tweak_point<H: Tagged + Digest<OutputSize = U32>>(original: &Point, contract: &[u8]) -> Point {
+    let t = tweak::<H>(original, contract);
+    let P = original;
+    g!(P + t * G).normalize().non_zero().expect(
+        "computationally unreachable: the tweak would have to be the negation of the secret key",
+    )
+}
+
+/// # Pay-to-contract
+///
+/// See [`p2c`](crate::p2c).
+impl Point {
+    /// Tweaks the key to commit to `contract`.
+    ///
+    /// Returns the tweaked key and the proof that it commits to `contract`.
+    pub fn p2c_tweak<H: Tagged + Digest<OutputSize = U32>>(
+        &self,
+        contract: &[u8],
+    ) -> (Point, P2CProof) {
+        (tweak_point::<H>(self, contract), P2CProof {
+            original: *self,
+        })
+    }
+}
+
+/// # Pay-to-contract
+///
+/// See [`p2c`](crate::p2c).
+impl KeyPair {
+    /// Tweaks the keypair so its public key is the one from [`Point::p2c_tweak`].
+    pub fn p2c_tweak<H: Tagged + Digest<OutputSize = U32>>(&self, contract: &[u8]) -> KeyPair {
+        let t = tweak::<H>(&self.public_key(), contract);
+        let x = self.secret_key();
+        KeyPair::new(s!(x + t).non_zero().expect(
+            "computationally unreachable: the tweak would have to be the negation of the secret key",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+    #[cfg(feature = "std")]
+    use std::string::ToString;
+
+    #[test]
+    fn p2c_commits_to_key_and_contract() {
+        let keypair = KeyPair::new(Scalar::random(&mut rand::thread_rng()));
+        let (tweaked_key, proof) = keypair.public_key().p2c_tweak::<Sha256>(b"contract");
+        assert_eq!(proof.original, keypair.public_key());
+        assert!(proof.verify::<Sha256>(&tweaked_key, b"contract"));
+        assert!(!proof.verify::<Sha256>(&tweaked_key, b"contracT"));
+        assert!(!proof.verify::<Sha256>(&keypair.public_key(), b"contract"));
+
+        let other = Point::random(&mut rand::thread_rng());
+        assert_ne!(other.p2c_tweak::<Sha256>(b"contract").0, tweaked_key);
+        assert!(!other
+            .p2c_tweak::<Sha256>(b"contract")
+            .1
+            .verify::<Sha256>(&tweaked_key, b"contract"));
+
+        let tweaked_keypair = keypair.p2c_tweak::<Sha256>(b"contract");
+        assert_eq!(tweaked_keypair.public_key(), tweaked_key);
+        let t = tweak::<Sha256>(&keypair.public_key(), b"contract");
+        let x = keypair.secret_key();
+        assert_eq!(tweaked_keypair.secret_key(), &s!(x + t));
+    }
+
+    #[test]
+    fn p2c_proof_roundtrip() {
+        let (_, proof) = Point::random(&mut rand::thread_rng()).p2c_tweak::<Sha256>(b"contract");
+        assert_eq!(P2CProof::from_bytes(proof.to_bytes()), Some(proof));
+        #[cfg(feature = "std")]
+        assert_eq!(proof.to_string().parse::<P2CProof>().unwrap(), proof);
+    }
+}
@@ -0,0 +1,365 @@
+//! Vector Pedersen commitments.
+//!
+//! A [`VectorPedersen`] commits to a vector of scalars `v` with a blinding scalar `r` as
+//!
+//! `C = v[0] * G_0 + v[1] * G_1 + ... + r * H`
+//!
+//! where `G_i` and `H` are independent [`generators`] derived from a label. The commitment hides
+//! `v` and is binding as long as nobody knows the discrete logarithm between any of the
+//! generators.
+//!
+//! Commitments are homomorphic. Adding two commitments gives a commitment to the sum of the
+//! vectors under the sum of the blindings and multiplying one by a scalar multiplies both.
+//!
+//! Only available with the `alloc` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use secp256kfun::{pedersen::VectorPedersen, s, Scalar};
+//! let pedersen = VectorPedersen::<sha2::Sha256>::new(b"my-protocol/wires", 3);
+//! let a = [Scalar::from(1u32), Scalar::from(2u32), Scalar::from(3u32)];
+//! let b = [Scalar::from(4u32), Scalar::from(5u32), Scalar::from(6u32)];
+//! let r_a = Scalar::random(&mut rand::thread_rng());
+//! let r_b = Scalar::random(&mut rand::thread_rng());
+//! let C_a = pedersen.commit(&a, &r_a);
+//! let C_b = pedersen.commit(&b, &r_b);
+//!
+//! let sum = a.iter().zip(&b).map(|(a, b)| s!(a + b)).collect::<Vec<_>>();
+//! assert!(pedersen.open(&(C_a + C_b), &sum, &s!(r_a + r_b)));
+//!
+//! let k = Scalar::from(7u32);
+//! let scaled = a.iter().map(|a| s!(k * a)).collect::<Vec<_>>();
+//! assert!(pedersen.open(&(C_a * &k), &scaled, &s!(k * r_a)));
+//! ```
+//!
+//! # Amounts
+//!
+//! [`commit_amount`] commits to a `u64` amount `v` the way confidential transactions do:
+//!
+//! `C = r * G + v * H`
+//!
+//! where `H` is [`amount_generator`]. A transaction balances when the input commitments minus the
+//! output commitments is a commitment to the fee with zero blinding which
+//! [`verify_balance`] checks. [`balancing_blinding`] gives the blinding for the last output that
+//! makes this so.
+//!
+//! ```
+//! use secp256kfun::{pedersen, Scalar};
+//! let r_in = [Scalar::random(&mut rand::thread_rng())];
+//! let r_out = [Scalar::random(&mut rand::thread_rng())];
+//! let r_change = pedersen::balancing_blinding(&r_in, &r_out);
+//! let input = pedersen::commit_amount(100_000, &r_in[0]);
+//! let outputs = [
+//!     pedersen::commit_amount(60_000, &r_out[0]),
+//!     pedersen::commit_amount(39_000, &r_change),
+//! ];
+//! assert!(pedersen::verify_balance(&[input], &outputs, 1_000));
+//! assert!(!pedersen::verify_balance(&[input], &outputs, 2_000));
+//! ```
+//!
+//! The amounts are scalars so the balance equation only holds modulo the curve order. Without a
+//! range proof for each output someone can commit to a "negative" amount (a huge scalar) and
+//! create coins out of thin air so don't use these for anything that matters without one.
+//!
+//! [`generators`]: crate::generators
+use crate::{
+    digest::{generic_array::typenum::U32, Digest},
+    g,
+    generators::Generators,
+    hash::Tagged,
+    marker::*,
+    s, Point, Scalar, G,
+};
+
+/// A commitment produced by [`VectorPedersen::commit`].
+///
+/// Use `+`, `-` and `*` (by a scalar) to combine commitments homomorphically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Commitment(pub Point<Normal, Public, Zero>);
+
+impl Commitment {
+    /// The commitment to the zero vector with zero blinding.
+    pub fn zero() -> Self {
+        Commitment(Point::zero())
+    }
+}
+
+impl core::ops::Add for Commitment {
+    type Output = Commitment;
+
+    fn add(self, rhs: Commitment) -> Commitment {
+        let (A, B) = (self.0, rhs.0);
+        Commitment(g!(A + B).normalize())
+    }
+}
+
+impl core::ops::Sub for Commitment {
+    type Output = Commitment;
+
+    fn sub(self, rhs: Commitment) -> Commitment {
+        let (A, B) = (self.0, rhs.0);
+        Commitment(g!(A - B).normalize())
+    }
+}
+
+impl core::ops::Neg for Commitment {
+    type Output = Commitment;
+
+    fn neg(self) -> Commitment {
+        Commitment(-self.0)
+    }
+}
+
+impl<S, Z: ZeroChoice> core::ops::Mul<&Scalar<S, Z>> for Commitment {
+    type Output = Commitment;
+
+    fn mul(self, k: &Scalar<S, Z>) -> Commitment {
+        let A = self.0;
+        Commitment(g!(k * A).normalize().mark::<Zero>())
+    }
+}
+
+/// Commits to vectors of scalars with generators derived from a label.
+///
+/// See the [module documentation](crate::pedersen).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VectorPedersen<H> {
+    // the first is the blinding generator and the rest are for the values
+    generators: Generators<H>,
+}
+
+impl<H: Tagged + Digest<OutputSize = U32>> VectorPedersen<H> {
+    /// Creates a commitment scheme for vectors of up to `n` scalars.
+    pub fn new(label: &[u8], n: usize) -> Self {
+        VectorPedersen {
+            generators: Generators::new(label, n + 1),
+        }
+    }
+
+    /// Makes room for committing to vectors of up to `n` scalars.
+    ///
+    /// The generators already in use stay the same so existing commitments are still valid.
+    pub fn extend_to(&mut self, n: usize) {
+        self.generators.extend_to(n + 1)
+    }
+}
+
+impl<H> VectorPedersen<H> {
+    /// The maximum length of a vector that can be committed to.
+    pub fn len(&self) -> usize {
+        self.generators.len() - 1
+    }
+
+    /// Whether only the empty vector can be committed to.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The generator for the blinding scalar (`H` above).
+    pub fn blinding_generator(&self) -> &Point {
+        &self.generators[0]
+    }
+
+    /// The generators for the values (`G_i` above).
+    pub fn value_generators(&self) -> &[Point] {
+        &self.generators.as_slice()[1..]
+    }
+
+    /// Commits to `values` with `blinding`.
+    ///
+    /// If `values` is shorter than [`len`] the missing values are zero.
+    ///
+    /// # Panics
+    ///
+    /// If `values` is longer than [`len`].
+    ///
+    /// [`len`]: Self::len
+    pub fn commit<S1, Z1, S2, Z2: ZeroChoice>(
+        &self,
+        values: &[Scalar<S1, Z1>],
+        blinding: &Scalar<S2, Z2>,
+    ) -> Commitment {
+        assert!(
+            values.len() <= self.len(),
+            "can only commit to {} values but got {}",
+            self.len(),
+            values.len()
+        );
+        let (gens, H) = (self.value_generators(), self.blinding_generator());
+        Commitment(g!(dot(values, gens) + blinding * H).normalize())
+    }
+
+    /// Checks that `commitment` is a commitment to `values` with `blinding`.
+    ///
+    /// Returns `false` rather than panicking if `values` is too long.
+    pub fn open<S1, Z1, S2, Z2: ZeroChoice>(
+        &self,
+        commitment: &Commitment,
+        values: &[Scalar<S1, Z1>],
+        blinding: &Scalar<S2, Z2>,
+    ) -> bool {
+        values.len() <= self.len() && self.commit(values, blinding) == *commitment
+    }
+}
+
+const AMOUNT_GENERATOR_X: [u8; 32] = [
+    0x50, 0x92, 0x9b, 0x74, 0xc1, 0xa0, 0x49, 0x54, 0xb7, 0x8b, 0x4b, 0x60, 0x35, 0xe9, 0x7a, 0x5e,
+    0x07, 0x8a, 0x5a, 0x0f, 0x28, 0xec, 0x96, 0xd5, 0x47, 0xbf, 0xee, 0x9a, 0xce, 0x80, 0x3a, 0xc0,
+];
+
+/// The generator `H` that [`commit_amount`] commits to amounts with.
+///
+/// It is the point with an even y-coordinate whose x-coordinate is the SHA256 of the uncompressed
+/// encoding of [`G`] (the "nothing up my sleeve" point suggested by [BIP341]). It is the same `H`
+/// as Elements uses for confidential transactions.
+///
+/// [BIP341]: https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki#constructing-and-spending-taproot-outputs
+pub fn amount_generator() -> Point<EvenY> {
+    Point::from_xonly_bytes(AMOUNT_GENERATOR_X).expect("it's on the curve")
+}
+
+/// Commits to `amount` with `blinding` as `blinding * G + amount * H`.
+///
+/// See the [module documentation](crate::pedersen#amounts).
+pub fn commit_amount<S, Z: ZeroChoice>(amount: u64, blinding: &Scalar<S, Z>) -> Commitment {
+    let (v, r, H) = (Scalar::from_u64(amount), blinding, amount_generator());
+    Commitment(g!(r * G + v * H).normalize())
+}
+
+/// Checks that `commitment` is a commitment to `amount` with `blinding`.
+pub fn open_amount<S, Z: ZeroChoice>(
+    commitment: &Commitment,
+    amount: u64,
+    blinding: &Scalar<S, Z>,
+) -> bool {
+    commit_amount(amount, blinding) == *commitment
+}
+
+/// Checks that the sum of `inputs` is the sum of `outputs` plus `fee * H`.
+///
+/// This only holds if the blindings of the inputs add up to the blindings of the outputs (see
+/// [`balancing_blinding`]) and the amounts do too modulo the curve order (see the [module
+/// documentation](crate::pedersen#amounts) for why that isn't enough on its own).
+#[must_use]
+pub fn verify_balance(inputs: &[Commitment], outputs: &[Commitment], fee: u64) -> bool {
+    let sum = |commitments: &[Commitment]| {
+        commitments
+            .iter()
+            .fold(Commitment::zero(), |acc, commitment| acc + *commitment)
+    };
+    sum(inputs) - sum(outputs) == commit_amount(fee, &Scalar::zero().mark::<Public>())
+}
+
+/// The blinding for one more output that makes the blindings of `outputs` and it add up to the
+/// blindings of `inputs`.
+pub fn balancing_blinding<S1, Z1, S2, Z2>(
+    inputs: &[Scalar<S1, Z1>],
+    outputs: &[Scalar<S2, Z2>],
+) -> Scalar<Secret, Zero> {
+    let inputs = inputs.iter().fold(Scalar::zero(), |acc, r| s!(acc + r));
+    outputs.iter().fold(inputs, |acc, r| s!(acc - r))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::s;
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::vec::Vec;
+    use sha2::Sha256;
+    #[cfg(feature = "std")]
+    use std::vec::Vec;
+
+    #[test]
+    fn commitments_are_homomorphic_and_binding() {
+        let mut pedersen = VectorPedersen::<Sha256>::new(b"test", 4);
+        assert_eq!(pedersen.len(), 4);
+        let a = (0..4)
+            .map(|_| Scalar::random(&mut rand::thread_rng()).mark::<Zero>())
+            .collect::<Vec<_>>();
+        let b = (0..4)
+            .map(|_| Scalar::random(&mut rand::thread_rng()).mark::<Zero>())
+            .collect::<Vec<_>>();
+        let (r_a, r_b) = (
+            Scalar::random(&mut rand::thread_rng()),
+            Scalar::random(&mut rand::thread_rng()),
+        );
+        let C_a = pedersen.commit(&a, &r_a);
+        let C_b = pedersen.commit(&b, &r_b);
+        assert!(pedersen.open(&C_a, &a, &r_a));
+        assert!(!pedersen.open(&C_a, &b, &r_a));
+        assert!(!pedersen.open(&C_a, &a, &r_b));
+
+        let diff = a.iter().zip(&b).map(|(a, b)| s!(a - b)).collect::<Vec<_>>();
+        assert!(pedersen.open(&(C_a - C_b), &diff, &s!(r_a - r_b)));
+        assert_eq!(C_a + -C_a, Commitment::zero());
+        assert_eq!(
+            pedersen.commit(&a, &s!(r_a - r_a)),
+            pedersen.commit(&a, &Scalar::zero())
+        );
+
+        // shorter vectors are padded with zeros
+        let padded = [a[0].clone(), a[1].clone(), Scalar::zero(), Scalar::zero()];
+        assert_eq!(
+            pedersen.commit(&a[..2], &r_a),
+            pedersen.commit(&padded, &r_a)
+        );
+
+        // too long
+        let mut longer = a.clone();
+        longer.push(Scalar::zero());
+        assert!(!pedersen.open(&C_a, &longer, &r_a));
+        pedersen.extend_to(5);
+        assert!(pedersen.open(&C_a, &longer, &r_a));
+    }
+
+    #[test]
+    fn amount_commitments_balance() {
+        let H = amount_generator();
+        let mut uncompressed_G = [0u8; 65];
+        uncompressed_G.copy_from_slice(&G.to_bytes_uncompressed());
+        assert_eq!(
+            H.to_xonly_bytes(),
+            <[u8; 32]>::from(Sha256::digest(&uncompressed_G[..]))
+        );
+
+        let r = Scalar::random(&mut rand::thread_rng());
+        let C = commit_amount(u64::MAX, &r);
+        assert!(open_amount(&C, u64::MAX, &r));
+        assert!(!open_amount(&C, u64::MAX - 1, &r));
+        let v = Scalar::from_u64(u64::MAX);
+        assert_eq!(C.0, g!(r * G + v * H));
+        assert_eq!(
+            commit_amount(0, &Scalar::zero().mark::<Public>()),
+            Commitment::zero()
+        );
+
+        let r_in = [
+            Scalar::random(&mut rand::thread_rng()),
+            Scalar::random(&mut rand::thread_rng()),
+        ];
+        let r_out = [Scalar::random(&mut rand::thread_rng())];
+        let r_change = balancing_blinding(&r_in, &r_out);
+        let inputs = [commit_amount(5, &r_in[0]), commit_amount(7, &r_in[1])];
+        let outputs = [commit_amount(8, &r_out[0]), commit_amount(3, &r_change)];
+        assert!(verify_balance(&inputs, &outputs, 1));
+        assert!(!verify_balance(&inputs, &outputs, 0));
+        assert!(!verify_balance(&inputs, &outputs[..1], 4));
+        assert!(verify_balance(&[], &[], 0));
+
+        // the wrong blinding doesn't balance even if the amounts do
+        let outputs = [commit_amount(8, &r_out[0]), commit_amount(3, &r_out[0])];
+        assert!(!verify_balance(&inputs, &outputs, 1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn commit_to_too_many_values() {
+        let pedersen = VectorPedersen::<Sha256>::new(b"test", 1);
+        let _ = pedersen.commit(
+            &[Scalar::one(), Scalar::one()],
+            &Scalar::random(&mut rand::thread_rng()),
+        );
+    }
+}
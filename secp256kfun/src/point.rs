@@ -2,10 +2,14 @@ use crate::{
     backend::{self, BackendPoint, TimeSensitive},
     hash::HashInto,
     marker::*,
-    op, Scalar,
+    op, FieldElement, Scalar,
 };
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
 use core::marker::PhantomData;
 use rand_core::RngCore;
+#[cfg(feature = "std")]
+use std::vec::Vec;
 
 /// A point on the secp256k1 elliptic curve.
 ///
@@ -28,7 +32,7 @@ use rand_core::RngCore;
 ///
 /// # Serialization
 ///
-/// Only points that are normalized (i.e. `T` ≠ `Jacobian`) can be serialized. A Point that is
+/// Only points that are normalized (i.e. `T` ≠ `Jacobian`) can be serialized.
 /// `EvenY` points serialize to and from their 32-byte x-only representation.
 /// `Normal` points serialize to and from the standard 33-byte representation specified in
 /// [_Standards for Efficient Cryptography_] (the same as [`Point::to_bytes`]). Points that are
@@ -61,6 +65,12 @@ impl<T: Copy, Z: Copy> Copy for Point<T, Public, Z> {}
 impl Point<Normal, Public, NonZero> {
     /// Samples a point uniformly from the group.
     ///
+    /// The point is sampled by choosing a random x-coordinate rather than multiplying [`G`] by a
+    /// random scalar so it is faster and nobody knows its discrete logarithm. If you need the
+    /// discrete logarithm use `g!(x * G)` with `x` from [`Scalar::random_nonzero`].
+    ///
+    /// [`G`]: crate::G
+    ///
     /// # Examples
     ///
     /// Generate a random point from `thread_rng`.
@@ -91,6 +101,62 @@ impl Point<Normal, Public, NonZero> {
         y.copy_from_slice(&bytes[33..65]);
         backend::Point::norm_from_coordinates(x, y).map(|p| Point::from_inner(p, Normal))
     }
+
+    /// Convenience method for calling [`from_bytes_uncompressed`] with a slice.
+    /// Returns `None` if [`from_bytes_uncompressed`] would or if `slice` is not 65 bytes long.
+    ///
+    /// Unlike some other parsers this does not accept the [hybrid] encoding.
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::Point;
+    /// let point = Point::random(&mut rand::thread_rng());
+    /// let bytes = point.to_bytes_uncompressed();
+    /// assert_eq!(Point::from_slice_uncompressed(&bytes[..]).unwrap(), point);
+    /// assert!(Point::from_slice_uncompressed(&point.to_bytes_hybrid()[..]).is_none());
+    /// assert!(Point::from_slice_uncompressed(&point.to_bytes()[..]).is_none());
+    /// ```
+    ///
+    /// [`from_bytes_uncompressed`]: Self::from_bytes_uncompressed
+    /// [hybrid]: Self::from_bytes_hybrid
+    pub fn from_slice_uncompressed(slice: &[u8]) -> Option<Self> {
+        if slice.len() != 65 {
+            return None;
+        }
+        let mut bytes = [0u8; 65];
+        bytes.copy_from_slice(slice);
+        Self::from_bytes_uncompressed(bytes)
+    }
+
+    /// Creates a Point from a 65-byte _hybrid_ encoding specified in [_ANSI X9.62_]. This is the
+    /// uncompressed encoding except that the first byte is `0x06` or `0x07` to indicate that the
+    /// y-coordinate is even or odd respectively. If the first byte doesn't match the
+    /// y-coordinate or the coordinates are not on the curve it will return `None`.
+    ///
+    /// The hybrid encoding is obsolete and you should only need this to interoperate with legacy
+    /// systems.
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::Point;
+    /// let point = Point::random(&mut rand::thread_rng());
+    /// let bytes = point.to_bytes_hybrid();
+    /// assert_eq!(Point::from_bytes_hybrid(bytes).unwrap(), point);
+    /// ```
+    ///
+    /// [_ANSI X9.62_]: https://www.x9.org/
+    pub fn from_bytes_hybrid(mut bytes: [u8; 65]) -> Option<Self> {
+        let y_odd = match bytes[0] {
+            0x06 => false,
+            0x07 => true,
+            _ => return None,
+        };
+        if (bytes[64] & 0x01 == 0x01) != y_odd {
+            return None;
+        }
+        bytes[0] = 0x04;
+        Self::from_bytes_uncompressed(bytes)
+    }
 }
 
 impl<Z: ZeroChoice> Point<Normal, Public, Z> {
@@ -189,6 +255,88 @@ impl Point<EvenY, Public, NonZero> {
     }
 }
 
+/// # Blinding
+///
+/// Blinding a public key multiplies it by a `tweak` so that the result can't be linked to the
+/// original by anyone who doesn't know the tweak. The owner of the secret key can blind it in the
+/// same way (see [`KeyPair::blind`]) so whoever knows the tweak can hand over a fresh looking key
+/// while still being able to prove where it came from by revealing the tweak. This is how
+/// coinswap style protocols derive many unlinkable keys from a single long term key.
+///
+/// [`KeyPair::blind`]: crate::KeyPair::blind
+impl Point<Normal, Public, NonZero> {
+    /// Blinds the point by multiplying it by `tweak`.
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::{KeyPair, Scalar};
+    /// let keypair = KeyPair::new(Scalar::random(&mut rand::thread_rng()));
+    /// let tweak = Scalar::random(&mut rand::thread_rng());
+    /// let blinded = keypair.public_key().blind(&tweak);
+    /// assert_eq!(blinded, keypair.blind(&tweak).public_key());
+    /// assert_eq!(blinded.unblind(&tweak), keypair.public_key());
+    /// assert!(blinded.is_blinding_of(&keypair.public_key(), &tweak));
+    /// ```
+    pub fn blind(&self, tweak: &Scalar<impl Secrecy>) -> Self {
+        crate::op::scalar_mul_point(tweak, self).normalize()
+    }
+
+    /// Removes the blinding `tweak` from a point made with [`blind`].
+    ///
+    /// [`blind`]: Point::blind
+    pub fn unblind(&self, tweak: &Scalar<impl Secrecy>) -> Self {
+        crate::op::scalar_mul_point(&tweak.invert(), self).normalize()
+    }
+
+    /// Checks that this point is `original` blinded with `tweak`.
+    pub fn is_blinding_of(&self, original: &Self, tweak: &Scalar<impl Secrecy>) -> bool {
+        original.blind(tweak) == *self
+    }
+}
+
+/// # Blinding
+///
+/// Blinding an x-only key works the same way as blinding a [`Normal`] point except that the result
+/// is negated if necessary so it has an even y-coordinate. [`XOnlyKeyPair::blind`] negates the
+/// secret key to match and [`unblind`] undoes the negation so none of this needs to be tracked by
+/// the caller.
+///
+/// [`Normal`]: crate::marker::Normal
+/// [`XOnlyKeyPair::blind`]: crate::XOnlyKeyPair::blind
+/// [`unblind`]: Point::unblind
+impl Point<EvenY, Public, NonZero> {
+    /// Blinds the x-only point by multiplying it by `tweak` (and negating it if needed).
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::{Scalar, XOnlyKeyPair};
+    /// let keypair = XOnlyKeyPair::new(Scalar::random(&mut rand::thread_rng()));
+    /// let tweak = Scalar::random(&mut rand::thread_rng());
+    /// let blinded = keypair.public_key().blind(&tweak);
+    /// assert_eq!(blinded, keypair.blind(&tweak).public_key());
+    /// assert_eq!(blinded.unblind(&tweak), keypair.public_key());
+    /// ```
+    pub fn blind(&self, tweak: &Scalar<impl Secrecy>) -> Self {
+        crate::op::scalar_mul_point(tweak, self)
+            .into_point_with_even_y()
+            .0
+    }
+
+    /// Removes the blinding `tweak` from a point made with [`blind`].
+    ///
+    /// [`blind`]: Point::blind
+    pub fn unblind(&self, tweak: &Scalar<impl Secrecy>) -> Self {
+        crate::op::scalar_mul_point(&tweak.invert(), self)
+            .into_point_with_even_y()
+            .0
+    }
+
+    /// Checks that this point is `original` blinded with `tweak`.
+    pub fn is_blinding_of(&self, original: &Self, tweak: &Scalar<impl Secrecy>) -> bool {
+        original.blind(tweak) == *self
+    }
+}
+
 impl<T, S, Z> Point<T, S, Z> {
     /// Returns true if this point the [`identity element`] of the group A.K.A. the point at infinity.
     ///
@@ -227,6 +375,18 @@ impl<T, S, Z> Point<T, S, Z> {
     pub fn set_secrecy<SNew>(self) -> Point<T, SNew, Z> {
         Point::from_inner(self.0, self.1)
     }
+
+    /// Changes the secrecy of the point to one that is at least as secret as the current one.
+    ///
+    /// See [`Scalar::coerce`].
+    ///
+    /// [`Scalar::coerce`]: crate::Scalar::coerce
+    pub fn coerce<SNew: Secrecy>(self) -> Point<T, SNew, Z>
+    where
+        S: CoerceTo<SNew>,
+    {
+        Point::from_inner(self.0, self.1)
+    }
 }
 
 impl Point<Normal, Public, Zero> {
@@ -265,6 +425,33 @@ impl<T, S> Point<T, S, Zero> {
     pub fn expect_nonzero(self, reason: &str) -> Point<T, S, NonZero> {
         self.mark::<NonZero>().expect(reason)
     }
+
+    /// Converts a point marked with `Zero` to one that is marked `NonZero` if it isn't the
+    /// identity element.
+    ///
+    /// This is shorthand for `point.mark::<NonZero>()`. Use it when a `g!` expression can
+    /// legitimately be zero (e.g. when the inputs came from someone else) to handle that case with
+    /// `?` rather than with a panic.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use secp256kfun::{g, marker::*, Point, Scalar, G};
+    /// // The x-coordinate of the nonce implied by `s` and `c` or `None` if it's zero.
+    /// fn implied_nonce(s: &Scalar<Public, Zero>, c: &Scalar<Public>, X: &Point) -> Option<[u8; 32]> {
+    ///     let R = g!(s * G - c * X).normalize().non_zero()?;
+    ///     Some(R.to_xonly_bytes())
+    /// }
+    /// let X = Point::random(&mut rand::thread_rng());
+    /// let c = Scalar::random(&mut rand::thread_rng()).mark::<Public>();
+    /// assert!(implied_nonce(&Scalar::zero().mark::<Public>(), &c, &X).is_some());
+    /// // s * G - c * G is zero when s = c
+    /// let H = G.clone().mark::<Normal>();
+    /// assert_eq!(implied_nonce(&c.mark::<Zero>(), &c, &H), None);
+    /// ```
+    pub fn non_zero(self) -> Option<Point<T, S, NonZero>> {
+        self.mark::<NonZero>()
+    }
 }
 
 impl<Z, T> Point<T, Public, Z> {
@@ -282,6 +469,32 @@ impl<S, Z> Point<Jacobian, S, Z> {
     pub fn normalize(self) -> Point<Normal, S, Z> {
         self.mark::<Normal>()
     }
+
+    /// Normalizes many points with a single field inversion.
+    ///
+    /// Normalizing is dominated by the cost of a field inversion so if you have many points that
+    /// need to be normalized (e.g. to hash or serialize them) this is much faster than calling
+    /// [`normalize`] on each one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use secp256kfun::{g, Point, Scalar, G};
+    /// let scalars = (0..10)
+    ///     .map(|_| Scalar::random(&mut rand::thread_rng()))
+    ///     .collect::<Vec<_>>();
+    /// let points = scalars.iter().map(|x| g!(x * G)).collect::<Vec<_>>();
+    /// let normalized = Point::normalize_batch(points.clone());
+    /// for (point, normal) in points.into_iter().zip(normalized) {
+    ///     assert_eq!(point.normalize(), normal);
+    /// }
+    /// ```
+    ///
+    /// [`normalize`]: Self::normalize
+    #[cfg(feature = "alloc")]
+    pub fn normalize_batch(points: Vec<Self>) -> Vec<Point<Normal, S, Z>> {
+        op::point_normalize_batch(points)
+    }
 }
 
 impl<T: PointType, S, Z> core::ops::Neg for Point<T, S, Z> {
@@ -345,6 +558,30 @@ impl<S, Z, T: Normalized> Point<T, S, Z> {
 
 impl<S> Point<EvenY, S, NonZero> {
     /// Creates a point with `EvenY` from 32 byte x-coordinate
+    ///
+    /// This is how [BIP-340] public keys and nonces are encoded so it is all a verifier needs to
+    /// decode them. There's no need to decode a `Normal` point and convert it. Deserializing a
+    /// `Point<EvenY>` with serde does the same thing.
+    ///
+    /// Returns `None` if there is no point with that x-coordinate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use secp256kfun::{hex, marker::*, Point, G};
+    /// let bytes =
+    ///     hex::decode_array("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+    ///         .unwrap();
+    /// let point = Point::<EvenY>::from_xonly_bytes(bytes).unwrap();
+    /// assert_eq!(point, *G);
+    /// assert_eq!(point.to_xonly_bytes(), bytes);
+    /// // x = 5 isn't on the curve
+    /// let mut bytes = [0u8; 32];
+    /// bytes[31] = 5;
+    /// assert!(Point::<EvenY>::from_xonly_bytes(bytes).is_none());
+    /// ```
+    ///
+    /// [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
     pub fn from_xonly_bytes(bytes: [u8; 32]) -> Option<Self> {
         backend::Point::norm_from_bytes_y_oddness(bytes, false)
             .map(|point| Point::from_inner(point, EvenY))
@@ -366,11 +603,50 @@ impl<S, T: Normalized> Point<T, S, NonZero> {
         backend::BackendPoint::norm_to_coordinates(&self.0)
     }
 
+    /// Returns the x-coordinate of the point as a [`FieldElement`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use secp256kfun::{FieldElement, Point};
+    /// let point = Point::random(&mut rand::thread_rng());
+    /// let (x, y) = (point.x(), point.y());
+    /// assert_eq!(y.square(), x.square() * x + FieldElement::from(7));
+    /// assert_eq!(x.to_bytes(), point.x_bytes());
+    /// ```
+    ///
+    /// [`FieldElement`]: crate::FieldElement
+    pub fn x(&self) -> FieldElement {
+        FieldElement::from_bytes(self.x_bytes()).expect("coordinates are always less than p")
+    }
+
+    /// Returns the y-coordinate of the point as a [`FieldElement`].
+    ///
+    /// [`FieldElement`]: crate::FieldElement
+    pub fn y(&self) -> FieldElement {
+        FieldElement::from_bytes(self.y_bytes()).expect("coordinates are always less than p")
+    }
+
+    /// Returns the big-endian encoding of the point's x-coordinate.
+    pub fn x_bytes(&self) -> [u8; 32] {
+        self.coordinates().0
+    }
+
+    /// Returns the big-endian encoding of the point's y-coordinate.
+    pub fn y_bytes(&self) -> [u8; 32] {
+        self.coordinates().1
+    }
+
     /// Returns whether the point has an even y-coordinate
     pub fn is_y_even(&self) -> bool {
         op::point_is_y_even(self)
     }
 
+    /// Returns whether the point has an odd y-coordinate
+    pub fn is_y_odd(&self) -> bool {
+        !self.is_y_even()
+    }
+
     /// Serializes a point with `EvenY` to its 32-byte x-coordinate
     pub fn to_xonly_bytes(&self) -> [u8; 32] {
         self.coordinates().0
@@ -395,6 +671,28 @@ impl<S, T: Normalized> Point<T, S, NonZero> {
         bytes[33..65].copy_from_slice(y.as_ref());
         bytes
     }
+
+    /// Returns a short identifier for the point for showing in user interfaces and logs.
+    ///
+    /// Points with the same coordinates have the same fingerprint whatever their markers. See
+    /// [`Fingerprint`] for what it is (and isn't) good for.
+    ///
+    /// [`Fingerprint`]: crate::fmt::Fingerprint
+    #[cfg(feature = "sha2")]
+    pub fn fingerprint(&self) -> crate::fmt::Fingerprint {
+        crate::fmt::Fingerprint::from_point_bytes(&self.to_bytes())
+    }
+
+    /// Encodes a point with the obsolete 65-byte _hybrid_ encoding.
+    ///
+    /// See [`from_bytes_hybrid`].
+    ///
+    /// [`from_bytes_hybrid`]: Point::from_bytes_hybrid
+    pub fn to_bytes_hybrid(&self) -> [u8; 65] {
+        let mut bytes = self.to_bytes_uncompressed();
+        bytes[0] = 0x06 | (bytes[64] & 0x01);
+        bytes
+    }
 }
 
 impl<S> HashInto for Point<Normal, S, NonZero> {
@@ -429,12 +727,45 @@ fn coords_to_bytes(x: [u8; 32], y: [u8; 32]) -> [u8; 33] {
     bytes
 }
 
-crate::impl_debug! {
-    fn to_bytes<T, S,Z>(point: &Point<T, S, Z>) -> Result<[u8;33], &str> {
-        let mut p = point.0.clone();
+/// Formats the point as its compressed encoding along with its markers.
+///
+/// Points that aren't marked [`Public`] are hidden. See [`fmt`](crate::fmt).
+impl<T, S: Secrecy, Z> core::fmt::Debug for Point<T, S, Z> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.fmt_debug(f, false)
+    }
+}
+
+/// Formats the point as its compressed encoding along with its markers whatever its [`Secrecy`].
+impl<T, S: Secrecy, Z> core::fmt::Debug for crate::fmt::Reveal<'_, Point<T, S, Z>> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.0.fmt_debug(f, true)
+    }
+}
+
+impl<T, S: Secrecy, Z> Point<T, S, Z> {
+    /// Wraps the point so that [`Debug`] shows it even if it is secret.
+    ///
+    /// Only use this while debugging. See [`fmt`](crate::fmt).
+    ///
+    /// [`Debug`]: core::fmt::Debug
+    pub fn reveal(&self) -> crate::fmt::Reveal<'_, Self> {
+        crate::fmt::Reveal(self)
+    }
+
+    fn fmt_debug(&self, f: &mut core::fmt::Formatter, reveal: bool) -> core::fmt::Result {
+        let mut p = self.0;
         backend::VariableTime::point_normalize(&mut p);
         let p: Point<Normal, S, Z> = Point::from_inner(p, Normal);
-        Ok(p.to_bytes())
+        write!(
+            f,
+            "Point<{},{},{}>(",
+            crate::fmt::short_type_name::<T>(),
+            crate::fmt::short_type_name::<S>(),
+            crate::fmt::short_type_name::<Z>()
+        )?;
+        crate::fmt::write_bytes::<S>(f, &p.to_bytes(), reveal)?;
+        write!(f, ")")
     }
 }
 
@@ -453,7 +784,7 @@ crate::impl_display_serialize! {
 crate::impl_fromstr_deserialize! {
     name => "secp256k1 32-byte x-coordinate",
     fn from_bytes<S>(bytes: [u8;32]) -> Option<Point<EvenY,S, NonZero>> {
-        Point::from_xonly_bytes(bytes).map(|p| p.set_secrecy::<S>())
+        Point::from_xonly_bytes(bytes).map(|p: Point<EvenY, Public, NonZero>| p.set_secrecy::<S>())
     }
 }
 
@@ -467,7 +798,7 @@ crate::impl_fromstr_deserialize! {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{g, G};
+    use crate::{g, s, G};
     use proptest::prelude::*;
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::wasm_bindgen_test as test;
@@ -543,6 +874,23 @@ mod test {
     }
 
     proptest! {
+        #[test]
+        fn blinding(secret_key in any::<Scalar>(), tweak in any::<Scalar>()) {
+            let keypair = crate::KeyPair::new(secret_key.clone());
+            let blinded = keypair.blind(&tweak);
+            assert_eq!(g!({ blinded.secret_key() } * G), blinded.public_key());
+            assert!(blinded.public_key().is_blinding_of(&keypair.public_key(), &tweak));
+            assert_eq!(blinded.unblind(&tweak), keypair);
+
+            let keypair = crate::XOnlyKeyPair::new(secret_key);
+            let blinded = keypair.blind(&tweak);
+            assert_eq!(g!({ blinded.secret_key() } * G), blinded.public_key());
+            assert_eq!(blinded.public_key(), keypair.public_key().blind(&tweak));
+            assert!(blinded.public_key().is_blinding_of(&keypair.public_key(), &tweak));
+            assert_eq!(blinded.public_key().unblind(&tweak), keypair.public_key());
+            assert_eq!(blinded.unblind(&tweak), keypair);
+        }
+
         #[test]
         fn operations_even_y(P in any::<Point<EvenY>>()) {
             operations_test!(&P);
@@ -597,6 +945,23 @@ mod test {
         }
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn even_y_serde_is_xonly() {
+        let point = Point::random(&mut rand::thread_rng())
+            .into_point_with_even_y()
+            .0;
+        let serialized = bincode::serialize(&point).unwrap();
+        assert_eq!(serialized, point.to_xonly_bytes());
+        let deserialized = bincode::deserialize::<Point<EvenY>>(&serialized[..]).unwrap();
+        assert_eq!(deserialized, point);
+        let json = serde_json::to_string(&point).unwrap();
+        assert_eq!(serde_json::from_str::<Point<EvenY>>(&json).unwrap(), point);
+        let mut not_on_curve = [0u8; 32];
+        not_on_curve[31] = 5;
+        assert!(bincode::deserialize::<Point<EvenY>>(&not_on_curve[..]).is_err());
+    }
+
     #[test]
     fn g_to_and_from_bytes() {
         use core::str::FromStr;
@@ -665,6 +1030,83 @@ mod test {
         );
     }
 
+    #[test]
+    fn expressions_resulting_in_zero_are_data() {
+        let x = Scalar::random(&mut rand::thread_rng());
+        let c = Scalar::random(&mut rand::thread_rng()).mark::<Public>();
+        let X = g!(x * G).normalize();
+        let s = s!(c * x).mark::<Public>();
+        // the double_mul subtraction path
+        let implied = g!(s * G - c * X);
+        assert!(implied.is_zero());
+        assert_eq!(implied.normalize().non_zero(), None);
+        assert_eq!(g!(-implied).normalize().to_bytes(), [0u8; 33]);
+        // the point_sub path
+        assert_eq!(g!(X - { g!(x * G) }).normalize().non_zero(), None);
+        assert_eq!(g!(implied - X).normalize().non_zero(), Some(-X));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn normalize_batch_matches_normalize() {
+        let mut points = (0..5)
+            .map(|_| {
+                let x = Scalar::random(&mut rand::thread_rng());
+                g!(x * G).mark::<Zero>()
+            })
+            .collect::<Vec<_>>();
+        // points at infinity in the middle and at the ends must not break the others
+        points.insert(0, Point::zero().mark::<Jacobian>());
+        let P = points[2];
+        points.insert(3, g!(P - P));
+        points.push(Point::zero().mark::<Jacobian>());
+        let normalized = Point::normalize_batch(points.clone());
+        assert_eq!(normalized.len(), points.len());
+        for (point, normal) in points.into_iter().zip(normalized) {
+            assert_eq!(point.normalize().to_bytes(), normal.to_bytes());
+            assert_eq!(point.is_zero(), normal.is_zero());
+        }
+        assert!(Point::<Jacobian, Public, Zero>::normalize_batch(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn hybrid_encoding() {
+        let g_hybrid = (*G).mark::<Normal>().to_bytes_hybrid();
+        assert_eq!(g_hybrid[0], 0x06);
+        assert_eq!(
+            &g_hybrid[1..],
+            &(*G).mark::<Normal>().to_bytes_uncompressed()[1..]
+        );
+        assert_eq!(Point::from_bytes_hybrid(g_hybrid).unwrap(), *G);
+        assert_eq!((-G).to_bytes_hybrid()[0], 0x07);
+
+        let mut wrong_parity = g_hybrid;
+        wrong_parity[0] = 0x07;
+        assert!(Point::from_bytes_hybrid(wrong_parity).is_none());
+        assert!(Point::from_bytes_uncompressed(g_hybrid).is_none());
+        // hybrid encodings are only accepted by from_bytes_hybrid
+        assert!(Point::from_slice_uncompressed(&g_hybrid[..]).is_none());
+        assert!(Point::<_, Public, NonZero>::from_slice(&g_hybrid[..]).is_none());
+        assert!(Point::<_, Public, NonZero>::from_slice(
+            &(*G).mark::<Normal>().to_bytes_uncompressed()[..]
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn coordinate_accessors() {
+        let point = Point::random(&mut rand::thread_rng());
+        let (x, y) = point.coordinates();
+        assert_eq!(point.x_bytes(), x);
+        assert_eq!(point.y_bytes(), y);
+        assert_eq!(point.x().to_bytes(), x);
+        assert_eq!(point.y().to_bytes(), y);
+        assert_eq!(point.is_y_odd(), point.y().is_odd());
+        assert_eq!((-point).x(), point.x());
+        assert_eq!((-point).y(), -point.y());
+        assert_ne!((-point).is_y_odd(), point.is_y_odd());
+    }
+
     #[test]
     fn zero_to_and_from_bytes() {
         let zero = Point::zero();
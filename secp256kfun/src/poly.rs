@@ -0,0 +1,282 @@
+//! Polynomial arithmetic over the scalar field.
+//!
+//! Polynomials are slices of coefficients starting with the constant term so `[a, b, c]` is
+//! `a + b*x + c*x^2`. The functions take coefficients with any markers and return
+//! `Scalar<Secret, Zero>` coefficients since they're usually used for secret sharing. Mark them
+//! [`Public`] if they're not secret.
+//!
+//! The order of the secp256k1 scalar field doesn't have large power of two roots of unity so the
+//! usual FFT algorithms aren't available. Instead [`mul`] uses Karatsuba multiplication
+//! (`O(n^1.58)`) and [`interpolate`] uses the barycentric form of Lagrange interpolation (`O(n^2)`)
+//! with a single field inversion. For the sizes threshold protocols use these are faster than the
+//! asymptotically better alternatives.
+//!
+//! Only available with the `alloc` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use secp256kfun::{poly, s, Scalar};
+//! // (1 + x)(2 + x) = 2 + 3x + x^2
+//! let product = poly::mul(&[s!(1), s!(1)], &[s!(2), s!(1)]);
+//! assert_eq!(product, vec![s!(2), s!(3), s!(1)]);
+//! let xs = [s!(1), s!(2), s!(3)];
+//! let ys = poly::eval_many(&product, &xs);
+//! assert_eq!(ys, vec![s!(6), s!(12), s!(20)]);
+//! // and back again
+//! assert_eq!(poly::interpolate(&xs, &ys), product);
+//! ```
+//!
+//! [`Public`]: crate::marker::Public
+use crate::{marker::*, s, Scalar};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::{vec, vec::Vec};
+
+/// Below this many coefficients [`mul`] uses schoolbook multiplication.
+const KARATSUBA_THRESHOLD: usize = 16;
+
+/// Evaluates `poly` at `x` with Horner's method.
+pub fn eval(
+    poly: &[Scalar<impl Secrecy, impl ZeroChoice>],
+    x: &Scalar<impl Secrecy, impl ZeroChoice>,
+) -> Scalar<Secret, Zero> {
+    poly.iter()
+        .rev()
+        .fold(Scalar::zero(), |acc, coef| s!(acc * x + coef))
+}
+
+/// Evaluates `poly` at each of `xs`.
+///
+/// This is `O(n * m)` for `n` coefficients and `m` points. Without fast division a subproduct tree
+/// doesn't do any better.
+pub fn eval_many(
+    poly: &[Scalar<impl Secrecy, impl ZeroChoice>],
+    xs: &[Scalar<impl Secrecy, impl ZeroChoice>],
+) -> Vec<Scalar<Secret, Zero>> {
+    xs.iter().map(|x| eval(poly, x)).collect()
+}
+
+/// Adds two polynomials.
+pub fn add(
+    a: &[Scalar<impl Secrecy, impl ZeroChoice>],
+    b: &[Scalar<impl Secrecy, impl ZeroChoice>],
+) -> Vec<Scalar<Secret, Zero>> {
+    let zero = Scalar::<Secret, Zero>::zero();
+    (0..a.len().max(b.len()))
+        .map(|i| match (a.get(i), b.get(i)) {
+            (Some(a), Some(b)) => s!(a + b),
+            (Some(a), None) => s!(a + zero),
+            (None, Some(b)) => s!(zero + b),
+            (None, None) => unreachable!("i is less than the longest length"),
+        })
+        .collect()
+}
+
+/// Multiplies two polynomials.
+///
+/// The product has `a.len() + b.len() - 1` coefficients (or none if either is empty).
+pub fn mul(
+    a: &[Scalar<impl Secrecy, impl ZeroChoice>],
+    b: &[Scalar<impl Secrecy, impl ZeroChoice>],
+) -> Vec<Scalar<Secret, Zero>> {
+    karatsuba(&normalize(a), &normalize(b))
+}
+
+/// The monic polynomial whose roots are `roots` i.e. `(x - roots[0]) * (x - roots[1]) * ...`.
+pub fn from_roots(roots: &[Scalar<impl Secrecy, impl ZeroChoice>]) -> Vec<Scalar<Secret, Zero>> {
+    let mut poly = vec![Scalar::one().mark::<Zero>()];
+    for root in roots {
+        // multiply by (x - root)
+        poly.push(Scalar::zero());
+        for i in (0..poly.len()).rev() {
+            let prev = if i == 0 {
+                Scalar::zero()
+            } else {
+                poly[i - 1].clone()
+            };
+            let coef = &poly[i];
+            poly[i] = s!(prev - root * coef);
+        }
+    }
+    poly
+}
+
+/// Finds the polynomial with fewer than `xs.len()` coefficients that goes through each
+/// `(xs[i], ys[i])`.
+///
+/// The result always has `xs.len()` coefficients (the highest ones may be zero).
+///
+/// # Panics
+///
+/// If `xs` and `ys` are different lengths or the `xs` aren't distinct.
+pub fn interpolate(
+    xs: &[Scalar<impl Secrecy, impl ZeroChoice>],
+    ys: &[Scalar<impl Secrecy, impl ZeroChoice>],
+) -> Vec<Scalar<Secret, Zero>> {
+    assert_eq!(xs.len(), ys.len(), "need one y for each x");
+    let (xs, ys) = (normalize(xs), normalize(ys));
+    let n = xs.len();
+    // M(x) = (x - xs[0]) * ... * (x - xs[n-1])
+    let vanishing = from_roots(&xs);
+    // w_i = 1 / prod_{j != i} (xs[i] - xs[j])
+    let mut weights = xs
+        .iter()
+        .enumerate()
+        .map(|(i, x_i)| {
+            xs.iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold(Scalar::one(), |acc, (_, x_j)| {
+                    s!(acc * (x_i - x_j))
+                        .non_zero()
+                        .expect("xs must be distinct")
+                })
+        })
+        .collect::<Vec<_>>();
+    batch_invert(&mut weights);
+
+    let mut poly = vec![Scalar::zero(); n];
+    for ((x_i, y_i), w_i) in xs.iter().zip(&ys).zip(&weights) {
+        let scale = s!(y_i * w_i);
+        // M(x) / (x - x_i) by synthetic division (the remainder is zero)
+        let mut carry = Scalar::<Secret, Zero>::zero();
+        for k in (0..n).rev() {
+            let coef = &vanishing[k + 1];
+            carry = s!(coef + carry * x_i);
+            poly[k] = s!({ &poly[k] } + scale * carry);
+        }
+    }
+    poly
+}
+
+/// Inverts every scalar in `scalars` with a single inversion (Montgomery's trick).
+fn batch_invert(scalars: &mut [Scalar]) {
+    let mut prefix_products = Vec::with_capacity(scalars.len());
+    let mut product = Scalar::one();
+    for scalar in scalars.iter() {
+        prefix_products.push(product.clone());
+        product = s!(product * scalar);
+    }
+    let mut product_inv = product.invert();
+    for (scalar, prefix_product) in scalars.iter_mut().zip(prefix_products).rev() {
+        let inverse = s!(product_inv * prefix_product);
+        product_inv = s!(product_inv * { &*scalar });
+        *scalar = inverse;
+    }
+}
+
+fn normalize(poly: &[Scalar<impl Secrecy, impl ZeroChoice>]) -> Vec<Scalar<Secret, Zero>> {
+    poly.iter()
+        .map(|coef| coef.clone().mark::<(Secret, Zero)>())
+        .collect()
+}
+
+fn schoolbook(a: &[Scalar<Secret, Zero>], b: &[Scalar<Secret, Zero>]) -> Vec<Scalar<Secret, Zero>> {
+    let mut product = vec![Scalar::zero(); a.len() + b.len() - 1];
+    for (i, a_i) in a.iter().enumerate() {
+        for (j, b_j) in b.iter().enumerate() {
+            product[i + j] = s!({ &product[i + j] } + a_i * b_j);
+        }
+    }
+    product
+}
+
+fn karatsuba(a: &[Scalar<Secret, Zero>], b: &[Scalar<Secret, Zero>]) -> Vec<Scalar<Secret, Zero>> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    if a.len().min(b.len()) < KARATSUBA_THRESHOLD {
+        return schoolbook(a, b);
+    }
+    // a = a0 + x^m a1, b = b0 + x^m b1
+    let m = a.len().min(b.len()) / 2;
+    let (a0, a1) = a.split_at(m);
+    let (b0, b1) = b.split_at(m);
+    let z0 = karatsuba(a0, b0);
+    let z2 = karatsuba(a1, b1);
+    let z1 = karatsuba(&add(a0, a1), &add(b0, b1));
+
+    let mut product = vec![Scalar::zero(); a.len() + b.len() - 1];
+    for (i, coef) in z0.iter().enumerate() {
+        product[i] = s!({ &product[i] } + coef);
+        product[i + m] = s!({ &product[i + m] } - coef);
+    }
+    for (i, coef) in z2.iter().enumerate() {
+        product[i + 2 * m] = s!({ &product[i + 2 * m] } + coef);
+        product[i + m] = s!({ &product[i + m] } - coef);
+    }
+    for (i, coef) in z1.iter().enumerate() {
+        product[i + m] = s!({ &product[i + m] } + coef);
+    }
+    product
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn random_poly(len: usize) -> Vec<Scalar<Secret, Zero>> {
+        (0..len)
+            .map(|_| Scalar::random(&mut rand::thread_rng()).mark::<Zero>())
+            .collect()
+    }
+
+    #[test]
+    fn mul_matches_schoolbook() {
+        for (a_len, b_len) in [
+            (0, 3),
+            (1, 1),
+            (5, 7),
+            (16, 16),
+            (40, 17),
+            (33, 70),
+            (64, 64),
+        ] {
+            let a = random_poly(a_len);
+            let b = random_poly(b_len);
+            let product = mul(&a, &b);
+            if a_len == 0 {
+                assert!(product.is_empty());
+                continue;
+            }
+            assert_eq!(product, schoolbook(&a, &b));
+            let x = Scalar::random(&mut rand::thread_rng());
+            assert_eq!(eval(&product, &x), s!({ eval(&a, &x) } * { eval(&b, &x) }));
+        }
+    }
+
+    #[test]
+    fn interpolate_round_trip() {
+        for n in [1, 2, 5, 20] {
+            let poly = random_poly(n);
+            let xs = random_poly(n);
+            let ys = eval_many(&poly, &xs);
+            assert_eq!(interpolate(&xs, &ys), poly);
+        }
+        // fewer coefficients than points pads with zeros
+        let xs = [s!(1), s!(2), s!(3)];
+        assert_eq!(interpolate(&xs, &[s!(7), s!(7), s!(7)]), vec![
+            s!(7).mark::<Zero>(),
+            Scalar::zero(),
+            Scalar::zero()
+        ]);
+    }
+
+    #[test]
+    fn from_roots_vanishes() {
+        let roots = random_poly(6);
+        let poly = from_roots(&roots);
+        assert_eq!(poly.len(), 7);
+        assert_eq!(poly[6], Scalar::one());
+        assert!(eval_many(&poly, &roots).iter().all(|y| y.is_zero()));
+        assert!(!eval(&poly, &Scalar::random(&mut rand::thread_rng())).is_zero());
+    }
+
+    #[test]
+    #[should_panic(expected = "xs must be distinct")]
+    fn interpolate_duplicate_xs() {
+        interpolate(&[s!(1), s!(1)], &[s!(2), s!(3)]);
+    }
+}
@@ -1,8 +1,8 @@
 //! Scalar arithmetic (integers mod the secp256k1 group order)
-use crate::{backend, hash::HashInto, marker::*, op};
+use crate::{backend, hash::HashInto, limbs, marker::*, op};
 use core::marker::PhantomData;
 use digest::{generic_array::typenum::U32, Digest};
-use rand_core::RngCore;
+use rand_core::{CryptoRng, RngCore};
 
 /// A secp256k1 scalar (an integer mod the curve order)
 ///
@@ -63,11 +63,52 @@ impl<Z, S> Scalar<S, Z> {
         backend::BackendScalar::to_bytes(&self.0)
     }
 
+    /// Returns the scalar as eight 32-bit limbs with the least significant limb first.
+    ///
+    /// Each limb is a native `u32` so the array can be handed to hardware that works on
+    /// platform-endian words without going through big-endian bytes. Use [`u32::to_be`] or
+    /// [`u32::to_le`] on each limb if the hardware wants a fixed byte order instead.
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::Scalar;
+    /// let limbs = Scalar::from(0x0102_0304u32).to_u32_limbs();
+    /// assert_eq!(limbs, [0x0102_0304, 0, 0, 0, 0, 0, 0, 0]);
+    /// ```
+    pub fn to_u32_limbs(&self) -> [u32; 8] {
+        limbs::to_u32_limbs(&self.to_bytes())
+    }
+
+    /// Returns the scalar as sixteen 16-bit limbs with the least significant limb first.
+    ///
+    /// See [`to_u32_limbs`](Self::to_u32_limbs).
+    pub fn to_u16_limbs(&self) -> [u16; 16] {
+        limbs::to_u16_limbs(&self.to_bytes())
+    }
+
     /// Negates the scalar in-place if `cond` is true.
     pub fn conditional_negate(&mut self, cond: bool) {
         op::scalar_conditional_negate(self, cond)
     }
 
+    /// Returns `b` if `choice` is true and `a` otherwise in constant time.
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::{s, Scalar};
+    /// let (a, b) = (s!(1), s!(2));
+    /// assert_eq!(Scalar::conditional_select(&a, &b, true), b);
+    /// assert_eq!(Scalar::conditional_select(&a, &b, false), a);
+    /// ```
+    pub fn conditional_select(a: &Self, b: &Self, choice: bool) -> Self {
+        use subtle::ConditionallySelectable;
+        Self::from_inner(backend::Scalar::conditional_select(
+            &a.0,
+            &b.0,
+            subtle::Choice::from(choice as u8),
+        ))
+    }
+
     /// Returns whether the scalar is greater than the `curve_order`/2.
     pub fn is_high(&self) -> bool {
         op::scalar_is_high(self)
@@ -90,6 +131,91 @@ impl<Z, S> Scalar<S, Z> {
     pub fn set_secrecy<SNew>(self) -> Scalar<SNew, Z> {
         Scalar::from_inner(self.0)
     }
+
+    /// Changes the secrecy of the scalar to one that is at least as secret as the current one.
+    ///
+    /// Unlike [`mark`] this can't accidentally make a secret scalar public. See [`Secrecy`] for the
+    /// rules.
+    ///
+    /// # Examples
+    ///
+    /// ```compile_fail
+    /// use secp256kfun::{marker::*, Scalar};
+    /// let secret_scalar = Scalar::random(&mut rand::thread_rng());
+    /// let public_scalar: Scalar<Public> = secret_scalar.coerce(); // doesn't compile
+    /// ```
+    ///
+    /// [`mark`]: crate::marker::Mark::mark
+    /// [`Secrecy`]: crate::marker::Secrecy
+    pub fn coerce<SNew: Secrecy>(self) -> Scalar<SNew, Z>
+    where
+        S: CoerceTo<SNew>,
+    {
+        Scalar::from_inner(self.0)
+    }
+}
+
+impl Scalar<Secret, Zero> {
+    /// Generates a uniformly random scalar that may be zero.
+    ///
+    /// The probability of getting zero is negligible but the type says it's possible, which is
+    /// what you want when the scalar is going to be used in a context that allows zero anyway
+    /// (e.g. the coefficients of a polynomial).
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::{marker::*, Scalar};
+    /// let x: Scalar<Secret, Zero> = Scalar::random_maybe_zero(&mut rand::thread_rng());
+    /// ```
+    pub fn random_maybe_zero<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Scalar::random(rng).mark::<Zero>()
+    }
+
+    /// Generates a uniformly random scalar in the range `[0, bound)`.
+    ///
+    /// This uses rejection sampling so it takes a variable number of draws from `rng` (less than
+    /// two on average). Only the number of draws depends on the result.
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::{marker::*, Scalar};
+    /// let bound = Scalar::from(1_000_000u32)
+    ///     .mark::<(Public, NonZero)>()
+    ///     .unwrap();
+    /// let x = Scalar::random_below(&bound, &mut rand::thread_rng());
+    /// assert!(u32::from_be_bytes(x.to_bytes()[28..].try_into().unwrap()) < 1_000_000);
+    /// ```
+    pub fn random_below<R: RngCore + CryptoRng>(
+        bound: &Scalar<Public, NonZero>,
+        rng: &mut R,
+    ) -> Self {
+        let bound = bound.to_bytes();
+        // sample only as many bits as the bound has so we accept at least half the time
+        let leading_zeros = {
+            let first = bound.iter().position(|byte| *byte != 0).unwrap_or(31);
+            first * 8 + bound[first].leading_zeros() as usize
+        };
+        loop {
+            let mut bytes = [0u8; 32];
+            rng.fill_bytes(&mut bytes);
+            for byte in &mut bytes[..leading_zeros / 8] {
+                *byte = 0;
+            }
+            bytes[leading_zeros / 8] &= 0xff >> (leading_zeros % 8);
+            if ct_less_than(&bytes, &bound) {
+                return Scalar::from_bytes(bytes).expect("less than the bound which is a scalar");
+            }
+        }
+    }
+}
+
+/// Whether `a < b` as big-endian integers without branching on their values.
+fn ct_less_than(a: &[u8; 32], b: &[u8; 32]) -> bool {
+    let mut borrow = 0u16;
+    for (a, b) in a.iter().zip(b.iter()).rev() {
+        borrow = ((*a as u16).wrapping_sub(*b as u16).wrapping_sub(borrow) >> 8) & 1;
+    }
+    borrow == 1
 }
 
 impl<S> Scalar<S, NonZero> {
@@ -123,6 +249,21 @@ impl Scalar<Secret, NonZero> {
             .mark::<NonZero>()
             .expect("computationally unreachable")
     }
+
+    /// Generates a uniformly random non-zero scalar.
+    ///
+    /// This is the same as [`Scalar::random`] except that it insists on a [`CryptoRng`] and says
+    /// what it returns in its name. See [`Scalar::random_maybe_zero`] if zero is acceptable.
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::{marker::*, Scalar};
+    /// let x: Scalar<Secret, NonZero> = Scalar::random_nonzero(&mut rand::thread_rng());
+    /// ```
+    pub fn random_nonzero<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self::random(rng)
+    }
+
     /// Converts the output of a 32-byte hash into a scalar by reducing it modulo the curve order.
     /// # Example
     /// ```
@@ -144,6 +285,30 @@ impl Scalar<Secret, NonZero> {
             .expect("computationally unreachable")
     }
 
+    /// Creates a non-zero scalar from 32 big-endian bytes, rejecting zero and integers greater than
+    /// or equal to the curve order.
+    ///
+    /// This is what a secret key has to be so use it to import secret keys from other systems.
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::{Scalar, ScalarFromBytesError};
+    /// assert!(Scalar::from_non_zero_bytes([42u8; 32]).is_ok());
+    /// assert_eq!(
+    ///     Scalar::from_non_zero_bytes([0u8; 32]),
+    ///     Err(ScalarFromBytesError::Zero)
+    /// );
+    /// assert_eq!(
+    ///     Scalar::from_non_zero_bytes([0xff; 32]),
+    ///     Err(ScalarFromBytesError::OutOfRange)
+    /// );
+    /// ```
+    pub fn from_non_zero_bytes(bytes: [u8; 32]) -> Result<Self, ScalarFromBytesError> {
+        Scalar::from_bytes_strict(bytes)?
+            .mark::<NonZero>()
+            .ok_or(ScalarFromBytesError::Zero)
+    }
+
     /// Converts a [`NonZeroU32`] into a `Scalar<Secret,NonZero>`.
     ///
     /// [`NonZeroU32`]: core::num::NonZeroU32
@@ -164,6 +329,14 @@ impl Scalar<Secret, NonZero> {
 
 impl Scalar<Secret, Zero> {
     /// Converts 32 bytes into a scalar by reducing it modulo the curve order `q`.
+    ///
+    /// Every input is accepted. Integers from `q` to `2^256 - 1` have `q` subtracted from them so
+    /// two different inputs can give the same scalar and zero is possible. Use this for hash
+    /// outputs and other uniformly random bytes (where an input that large turns up with
+    /// probability around `2^-128`) and importing keys from systems that reduce them the same way.
+    /// Use [`from_bytes_strict`] or [`Scalar::from_non_zero_bytes`] for keys that should already be
+    /// in range.
+    ///
     /// # Example
     /// ```
     /// # use core::convert::TryInto;
@@ -176,10 +349,35 @@ impl Scalar<Secret, Zero> {
     /// );
     /// assert_eq!(scalar_overflowed, Scalar::one())
     /// ```
+    ///
+    /// [`from_bytes_strict`]: Scalar::from_bytes_strict
     pub fn from_bytes_mod_order(bytes: [u8; 32]) -> Self {
         Self::from_inner(backend::BackendScalar::from_bytes_mod_order(bytes))
     }
 
+    /// Creates a scalar from 32 big-endian bytes, rejecting integers greater than or equal to the
+    /// curve order instead of reducing them.
+    ///
+    /// This is [`from_bytes`] with an error that says why the bytes were rejected. Zero is
+    /// accepted (see [`Scalar::from_non_zero_bytes`] if it shouldn't be).
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::{hex, Scalar, ScalarFromBytesError};
+    /// let q = hex::decode_array("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141")
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     Scalar::from_bytes_strict(q),
+    ///     Err(ScalarFromBytesError::OutOfRange)
+    /// );
+    /// assert_eq!(Scalar::from_bytes_strict([0u8; 32]), Ok(Scalar::zero()));
+    /// ```
+    ///
+    /// [`from_bytes`]: Scalar::from_bytes
+    pub fn from_bytes_strict(bytes: [u8; 32]) -> Result<Self, ScalarFromBytesError> {
+        Self::from_bytes(bytes).ok_or(ScalarFromBytesError::OutOfRange)
+    }
+
     /// Exactly like [`from_bytes_mod_order`] except
     /// it operates on a 32-byte slice rather than an array.  If the slice is
     /// not 32 bytes long then the function returns `None`.
@@ -218,6 +416,33 @@ impl Scalar<Secret, Zero> {
         backend::BackendScalar::from_bytes(bytes).map(Self::from_inner)
     }
 
+    /// Creates a scalar from eight 32-bit limbs with the least significant limb first. Returns
+    /// `None` if they represent an integer greater than or equal to the curve order.
+    ///
+    /// This is the inverse of [`to_u32_limbs`](Scalar::to_u32_limbs).
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::Scalar;
+    /// let scalar = Scalar::random(&mut rand::thread_rng());
+    /// assert_eq!(
+    ///     Scalar::from_u32_limbs(scalar.to_u32_limbs()).unwrap(),
+    ///     scalar
+    /// );
+    /// assert!(Scalar::from_u32_limbs([u32::MAX; 8]).is_none());
+    /// ```
+    pub fn from_u32_limbs(limbs: [u32; 8]) -> Option<Self> {
+        Self::from_bytes(limbs::from_u32_limbs(&limbs))
+    }
+
+    /// Creates a scalar from sixteen 16-bit limbs with the least significant limb first. Returns
+    /// `None` if they represent an integer greater than or equal to the curve order.
+    ///
+    /// This is the inverse of [`to_u16_limbs`](Scalar::to_u16_limbs).
+    pub fn from_u16_limbs(limbs: [u16; 16]) -> Option<Self> {
+        Self::from_bytes(limbs::from_u16_limbs(&limbs))
+    }
+
     /// Creates a scalar from 32 big-endian encoded bytes in a slice. If the
     /// length of the slice is not 32 or the bytes represent an integer greater
     /// than or equal to the curve order then it returns `None`.
@@ -241,8 +466,66 @@ impl Scalar<Secret, Zero> {
     pub fn zero() -> Self {
         Self::from_inner(backend::BackendScalar::zero())
     }
+
+    /// Converts a signed integer into a scalar. Negative integers are negated modulo the curve
+    /// order.
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::{s, Scalar};
+    /// assert_eq!(Scalar::from_i32(-3), s!(-3));
+    /// assert_eq!(Scalar::from_i32(3), s!(3));
+    /// ```
+    pub fn from_i32(int: i32) -> Self {
+        let mut scalar = Scalar::from(int.unsigned_abs());
+        scalar.conditional_negate(int < 0);
+        scalar
+    }
+
+    /// Converts a `u64` into a scalar.
+    ///
+    /// This isn't a `From` implementation so that `Scalar::from(42)` keeps inferring `u32`.
+    ///
+    /// # Example
+    /// ```
+    /// use secp256kfun::{s, Scalar};
+    /// assert_eq!(
+    ///     Scalar::from_u64(1 << 32),
+    ///     s!({ Scalar::from(1u32 << 16) } * { Scalar::from(1u32 << 16) })
+    /// );
+    /// ```
+    pub fn from_u64(int: u64) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[24..].copy_from_slice(&int.to_be_bytes());
+        Scalar::from_bytes_mod_order(bytes)
+    }
 }
 
+/// Why 32 bytes couldn't be converted into a scalar.
+///
+/// Returned by [`Scalar::from_bytes_strict`] and [`Scalar::from_non_zero_bytes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarFromBytesError {
+    /// The bytes encode an integer greater than or equal to the curve order.
+    OutOfRange,
+    /// The bytes encode zero.
+    Zero,
+}
+
+impl core::fmt::Display for ScalarFromBytesError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            ScalarFromBytesError::OutOfRange => {
+                write!(f, "scalar is not less than the curve order")
+            }
+            ScalarFromBytesError::Zero => write!(f, "scalar is zero"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ScalarFromBytesError {}
+
 impl<S> Scalar<S, Zero> {
     /// Converts a scalar marked with `Zero` to one that is marked `NonZero`.
     /// You must provide a justification for this as the `reason`.
@@ -264,6 +547,22 @@ impl<S> Scalar<S, Zero> {
     pub fn expect_nonzero(self, reason: &str) -> Scalar<S, NonZero> {
         self.mark::<NonZero>().expect(reason)
     }
+
+    /// Converts a scalar marked with `Zero` to one that is marked `NonZero` if it isn't zero.
+    ///
+    /// This is shorthand for `scalar.mark::<NonZero>()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use secp256kfun::{s, Scalar};
+    /// let x = Scalar::random(&mut rand::thread_rng());
+    /// assert!(s!(x - x).non_zero().is_none());
+    /// assert!(s!(x + x).non_zero().is_some());
+    /// ```
+    pub fn non_zero(self) -> Option<Scalar<S, NonZero>> {
+        self.mark::<NonZero>()
+    }
 }
 
 impl<Z1, Z2, S1, S2> PartialEq<Scalar<S2, Z2>> for Scalar<S1, Z1> {
@@ -285,12 +584,50 @@ crate::impl_fromstr_deserialize! {
     }
 }
 
-crate::impl_display_debug_serialize! {
+crate::impl_display_serialize! {
     fn to_bytes<Z,S>(scalar: &Scalar<S,Z>) -> [u8;32] {
         scalar.to_bytes()
     }
 }
 
+/// Formats the scalar as hex along with its markers.
+///
+/// Scalars that aren't marked [`Public`] are hidden. See [`fmt`](crate::fmt).
+impl<S: Secrecy, Z> core::fmt::Debug for Scalar<S, Z> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.fmt_debug(f, false)
+    }
+}
+
+/// Formats the scalar as hex along with its markers whatever its [`Secrecy`].
+impl<S: Secrecy, Z> core::fmt::Debug for crate::fmt::Reveal<'_, Scalar<S, Z>> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.0.fmt_debug(f, true)
+    }
+}
+
+impl<S: Secrecy, Z> Scalar<S, Z> {
+    /// Wraps the scalar so that [`Debug`] shows it even if it is secret.
+    ///
+    /// Only use this while debugging. See [`fmt`](crate::fmt).
+    ///
+    /// [`Debug`]: core::fmt::Debug
+    pub fn reveal(&self) -> crate::fmt::Reveal<'_, Self> {
+        crate::fmt::Reveal(self)
+    }
+
+    fn fmt_debug(&self, f: &mut core::fmt::Formatter, reveal: bool) -> core::fmt::Result {
+        write!(
+            f,
+            "Scalar<{},{}>(",
+            crate::fmt::short_type_name::<S>(),
+            crate::fmt::short_type_name::<Z>()
+        )?;
+        crate::fmt::write_bytes::<S>(f, &self.to_bytes(), reveal)?;
+        write!(f, ")")
+    }
+}
+
 crate::impl_fromstr_deserialize! {
     name => "secp256k1 scalar",
     fn from_bytes<S>(bytes: [u8;32]) -> Option<Scalar<S,Zero>> {
@@ -346,6 +683,39 @@ mod test {
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::wasm_bindgen_test as test;
 
+    #[test]
+    fn random_below() {
+        let mut rng = rand::thread_rng();
+        let ten = Scalar::from(10u32).mark::<(Public, NonZero)>().unwrap();
+        let mut seen = [false; 10];
+        for _ in 0..1000 {
+            let x = Scalar::random_below(&ten, &mut rng);
+            seen[x.to_bytes()[31] as usize] = true;
+            assert_eq!(x.to_bytes()[..31], [0u8; 31]);
+        }
+        assert!(seen.iter().all(|seen| *seen));
+
+        let one = Scalar::one().mark::<Public>();
+        assert!(Scalar::random_below(&one, &mut rng).is_zero());
+
+        let big = Scalar::minus_one().mark::<Public>();
+        let x = Scalar::random_below(&big, &mut rng);
+        assert!(ct_less_than(&x.to_bytes(), &big.to_bytes()));
+    }
+
+    #[test]
+    fn ct_less_than_matches_ord() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let a = Scalar::random(&mut rng).to_bytes();
+            let mut b = a;
+            b[rng.next_u32() as usize % 32] ^= 1 << (rng.next_u32() % 8);
+            assert_eq!(ct_less_than(&a, &b), a < b);
+            assert_eq!(ct_less_than(&b, &a), b < a);
+            assert!(!ct_less_than(&a, &a));
+        }
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn scalar_serde_rountrip() {
@@ -363,6 +733,17 @@ mod test {
     }
 
     proptest! {
+        #[test]
+        fn limbs_round_trip(scalar in any::<Scalar<Public, Zero>>()) {
+            let bytes = scalar.to_bytes();
+            let limbs32 = scalar.to_u32_limbs();
+            let limbs16 = scalar.to_u16_limbs();
+            assert_eq!(limbs32[7] >> 24, bytes[0] as u32);
+            assert_eq!(limbs16[0] as u32, limbs32[0] & 0xffff);
+            assert_eq!(Scalar::from_u32_limbs(limbs32), Some(scalar.mark::<Secret>()));
+            assert_eq!(Scalar::from_u16_limbs(limbs16), Some(scalar.mark::<Secret>()));
+        }
+
         #[test]
         fn invert(x in any::<Scalar>(), y in any::<Scalar<Public>>()) {
             assert_eq!(s!(x * { x.invert() }), s!(1));
@@ -419,6 +800,41 @@ mod test {
         assert_eq!(Scalar::zero(), Scalar::from(0));
     }
 
+    #[test]
+    fn from_bytes_overflow_policies() {
+        let q = crate::hex::decode_array::<32>(
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+        )
+        .unwrap();
+        let mut q_minus_one = q;
+        q_minus_one[31] -= 1;
+        let mut q_plus_one = q;
+        q_plus_one[31] += 1;
+
+        assert_eq!(Scalar::from_bytes_mod_order(q), Scalar::zero());
+        assert_eq!(Scalar::from_bytes_mod_order(q_plus_one), Scalar::one());
+        assert_eq!(
+            Scalar::from_bytes_strict(q),
+            Err(ScalarFromBytesError::OutOfRange)
+        );
+        assert_eq!(
+            Scalar::from_bytes_strict(q_minus_one),
+            Ok(Scalar::minus_one().mark::<Zero>())
+        );
+        assert_eq!(
+            Scalar::from_non_zero_bytes(q),
+            Err(ScalarFromBytesError::OutOfRange)
+        );
+        assert_eq!(
+            Scalar::from_non_zero_bytes(q_minus_one),
+            Ok(Scalar::minus_one())
+        );
+        assert_eq!(
+            Scalar::from_non_zero_bytes([0u8; 32]),
+            Err(ScalarFromBytesError::Zero)
+        );
+    }
+
     #[test]
     fn from_slice() {
         assert!(Scalar::from_slice(b"xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx".as_ref()).is_some());
@@ -469,6 +885,29 @@ mod test {
         );
     }
 
+    #[test]
+    fn from_integers() {
+        assert_eq!(
+            Scalar::from_u64(u64::MAX),
+            s!({ Scalar::from(u32::MAX) } * { Scalar::from(u32::MAX) }
+                + { Scalar::from(u32::MAX) }
+                + { Scalar::from(u32::MAX) })
+        );
+        assert_eq!(Scalar::from_u64(7), Scalar::from(7u32));
+        assert_eq!(Scalar::from_i32(i32::MIN), -Scalar::from(1u32 << 31));
+        assert_eq!(Scalar::from_i32(-1), Scalar::minus_one());
+        assert_eq!(Scalar::from_i32(-5), s!(-5));
+        assert_eq!(Scalar::from_i32(0), Scalar::zero());
+    }
+
+    proptest! {
+        #[test]
+        fn conditional_select(a in any::<Scalar>(), b in any::<Scalar>()) {
+            assert_eq!(Scalar::conditional_select(&a, &b, false), a);
+            assert_eq!(Scalar::conditional_select(&a, &b, true), b);
+        }
+    }
+
     #[test]
     fn nz_scalar_to_scalar_subtraction_is_not_commutative() {
         let two = s!(2);
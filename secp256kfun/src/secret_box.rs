@@ -0,0 +1,268 @@
+//! Heap storage for long-lived secrets that is kept out of swap and wiped when dropped.
+//!
+//! A [`SecretBox`] puts its value on pages of its own. With the `mlock` feature those pages are
+//! locked into RAM with `mlock` (unix) or `VirtualLock` (windows) so the secret isn't written to
+//! swap. On unix the pages are also surrounded by inaccessible guard pages so that an overflow
+//! from a neighbouring allocation crashes rather than reading or writing the secret and on linux
+//! they are left out of core dumps. Whatever the platform, the memory is zeroed when the box is
+//! dropped.
+//!
+//! Locking can fail (e.g. when the process has hit `RLIMIT_MEMLOCK`) so check
+//! [`SecretBox::is_locked`] if it matters to you. Without the `mlock` feature nothing is locked
+//! but the value is still kept on its own pages and wiped on drop.
+//!
+//! Note that the value is moved into the box so copies of it may still be left on the stack.
+//! This is for keys that live for the lifetime of a server process, not for ephemeral secrets.
+//!
+//! # Examples
+//!
+//! ```
+//! use secp256kfun::{secret_box::SecretBox, KeyPair, Scalar};
+//! let secret_key = SecretBox::new(Scalar::random(&mut rand::thread_rng()));
+//! println!("secret key is locked into RAM: {}", secret_key.is_locked());
+//! // keypairs hold a copy of the secret key so they can go in a box of their own
+//! let keypair = KeyPair::new_boxed(Scalar::random(&mut rand::thread_rng()));
+//! let public_key = keypair.public_key();
+//! ```
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::alloc::{alloc_zeroed, dealloc, Layout};
+use core::{
+    fmt,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+#[cfg(feature = "std")]
+use std::alloc::{alloc_zeroed, dealloc, Layout};
+
+/// A value kept on locked pages of its own and wiped on drop.
+///
+/// See the [module documentation](crate::secret_box).
+pub struct SecretBox<T> {
+    value: NonNull<T>,
+    // the pages holding `value` (after any guard page)
+    len: usize,
+    locked: bool,
+    mapped: bool,
+}
+
+// SAFETY: the box owns its value exclusively like `Box<T>` does
+unsafe impl<T: Send> Send for SecretBox<T> {}
+unsafe impl<T: Sync> Sync for SecretBox<T> {}
+
+impl<T> SecretBox<T> {
+    /// Moves `value` into locked memory.
+    pub fn new(value: T) -> Self {
+        let page_size = page_size();
+        assert!(
+            core::mem::align_of::<T>() <= page_size,
+            "alignment is bigger than a page"
+        );
+        let len = round_up(core::mem::size_of::<T>().max(1), page_size);
+        let (ptr, mapped) = match map_guarded(len, page_size) {
+            Some(ptr) => (ptr, true),
+            None => {
+                let layout = Layout::from_size_align(len, page_size).expect("page size is valid");
+                // SAFETY: the layout has a non-zero size
+                let ptr = unsafe { alloc_zeroed(layout) };
+                if ptr.is_null() {
+                    panic!("out of memory allocating a SecretBox");
+                }
+                (ptr, false)
+            }
+        };
+        let locked = lock(ptr, len);
+        let value_ptr = ptr as *mut T;
+        // SAFETY: `ptr` points to at least `size_of::<T>()` writable bytes aligned to a page
+        unsafe { value_ptr.write(value) };
+        SecretBox {
+            value: NonNull::new(value_ptr).expect("checked above"),
+            len,
+            locked,
+            mapped,
+        }
+    }
+
+    /// Whether the memory holding the value is locked into RAM.
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl<T> Deref for SecretBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: `value` was initialized in `new` and is only dropped in `drop`
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<T> DerefMut for SecretBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: as above and we have `&mut self`
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<T: Clone> Clone for SecretBox<T> {
+    fn clone(&self) -> Self {
+        SecretBox::new(self.deref().clone())
+    }
+}
+
+impl<T> fmt::Debug for SecretBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SecretBox")
+            .field("locked", &self.locked)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for SecretBox<T> {
+    fn drop(&mut self) {
+        let ptr = self.value.as_ptr();
+        // SAFETY: the value is initialized and never used again. The `len` bytes after `ptr` are
+        // ours to overwrite.
+        unsafe {
+            core::ptr::drop_in_place(ptr);
+            let bytes = ptr as *mut u8;
+            for i in 0..self.len {
+                core::ptr::write_volatile(bytes.add(i), 0);
+            }
+        }
+        core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+        let ptr = ptr as *mut u8;
+        if self.locked {
+            unlock(ptr, self.len);
+        }
+        let page_size = page_size();
+        if self.mapped {
+            unmap_guarded(ptr, self.len, page_size);
+        } else {
+            let layout = Layout::from_size_align(self.len, page_size).expect("page size is valid");
+            // SAFETY: allocated in `new` with the same layout
+            unsafe { dealloc(ptr, layout) };
+        }
+    }
+}
+
+fn round_up(n: usize, multiple: usize) -> usize {
+    (n + multiple - 1) / multiple * multiple
+}
+
+fn page_size() -> usize {
+    #[cfg(all(feature = "mlock", unix))]
+    {
+        // SAFETY: sysconf has no preconditions
+        let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        if size > 0 {
+            return size as usize;
+        }
+    }
+    4096
+}
+
+/// Maps `len` bytes with an inaccessible page on either side and returns the start of the
+/// accessible part.
+#[allow(unused_variables)]
+fn map_guarded(len: usize, page_size: usize) -> Option<*mut u8> {
+    #[cfg(all(feature = "mlock", unix))]
+    // SAFETY: we only touch memory we just mapped
+    unsafe {
+        let total = len + 2 * page_size;
+        let base = libc::mmap(
+            core::ptr::null_mut(),
+            total,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANON,
+            -1,
+            0,
+        );
+        if base == libc::MAP_FAILED {
+            return None;
+        }
+        let base = base as *mut u8;
+        let guards_ok = libc::mprotect(base as *mut _, page_size, libc::PROT_NONE) == 0
+            && libc::mprotect(
+                base.add(page_size + len) as *mut _,
+                page_size,
+                libc::PROT_NONE,
+            ) == 0;
+        if !guards_ok {
+            libc::munmap(base as *mut _, total);
+            return None;
+        }
+        #[cfg(target_os = "linux")]
+        libc::madvise(base.add(page_size) as *mut _, len, libc::MADV_DONTDUMP);
+        return Some(base.add(page_size));
+    }
+    #[allow(unreachable_code)]
+    None
+}
+
+#[allow(unused_variables)]
+fn unmap_guarded(ptr: *mut u8, len: usize, page_size: usize) {
+    #[cfg(all(feature = "mlock", unix))]
+    // SAFETY: `ptr` came from `map_guarded` with the same `len`
+    unsafe {
+        libc::munmap(ptr.sub(page_size) as *mut _, len + 2 * page_size);
+    }
+}
+
+#[allow(unused_variables)]
+fn lock(ptr: *mut u8, len: usize) -> bool {
+    #[cfg(all(feature = "mlock", unix))]
+    // SAFETY: the range is memory we own
+    return unsafe { libc::mlock(ptr as *const _, len) } == 0;
+    #[cfg(all(feature = "mlock", windows))]
+    // SAFETY: the range is memory we own
+    return unsafe { winapi::um::memoryapi::VirtualLock(ptr as *mut _, len) } != 0;
+    #[allow(unreachable_code)]
+    false
+}
+
+#[allow(unused_variables)]
+fn unlock(ptr: *mut u8, len: usize) {
+    #[cfg(all(feature = "mlock", unix))]
+    // SAFETY: the range is memory we own
+    unsafe {
+        libc::munlock(ptr as *const _, len);
+    }
+    #[cfg(all(feature = "mlock", windows))]
+    // SAFETY: the range is memory we own
+    unsafe {
+        winapi::um::memoryapi::VirtualUnlock(ptr as *mut _, len);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{KeyPair, Scalar, XOnlyKeyPair};
+
+    #[test]
+    fn holds_and_wipes_values() {
+        let secret = Scalar::random(&mut rand::thread_rng());
+        let mut boxed = SecretBox::new(secret.clone());
+        assert_eq!(*boxed, secret);
+        *boxed = Scalar::one();
+        assert_eq!(*boxed.clone(), Scalar::one());
+        assert_eq!(
+            (boxed.value.as_ptr() as usize) % page_size(),
+            0,
+            "value is on its own page"
+        );
+
+        let keypair = KeyPair::new_boxed(secret.clone());
+        assert_eq!(
+            keypair.public_key(),
+            KeyPair::new(secret.clone()).public_key()
+        );
+        let xonly = XOnlyKeyPair::new_boxed(secret.clone());
+        assert_eq!(xonly.public_key(), XOnlyKeyPair::new(secret).public_key());
+
+        // zero sized types still get a page
+        let unit = SecretBox::new(());
+        assert_eq!(unit.len, page_size());
+    }
+}
@@ -60,6 +60,12 @@ impl<'a, S> Slice<'a, S> {
     }
 }
 
+impl<'a, S> AsRef<[u8]> for Slice<'a, S> {
+    fn as_ref(&self) -> &[u8] {
+        self.inner
+    }
+}
+
 impl<'a, S> From<Slice<'a, S>> for &'a [u8] {
     fn from(msg: Slice<'a, S>) -> Self {
         msg.inner
@@ -0,0 +1,102 @@
+use crate::{marker::*, Point, Scalar};
+
+/// Serialization to a fixed length byte array.
+///
+/// Types with more than one encoding implement this once for each length so the encoding is
+/// chosen by the length you ask for. This is mostly useful for writing straight into a larger
+/// buffer (e.g. a network message) with [`write_into`].
+///
+/// # Examples
+///
+/// ```
+/// use secp256kfun::{marker::*, Point, Scalar, ToBytes};
+/// let point = Point::random(&mut rand::thread_rng());
+/// let scalar = Scalar::random(&mut rand::thread_rng());
+/// let compressed: [u8; 33] = point.to_byte_array();
+/// assert_eq!(compressed, point.to_bytes());
+/// let uncompressed = ToBytes::<65>::to_byte_array(&point);
+/// assert_eq!(uncompressed, point.to_bytes_uncompressed());
+///
+/// let mut message = [0u8; 65];
+/// let rest = ToBytes::<33>::write_into(&point, &mut message[..]);
+/// let rest = scalar.write_into(rest);
+/// assert!(rest.is_empty());
+/// assert_eq!(&message[..33], &compressed[..]);
+/// assert_eq!(&message[33..], &scalar.to_bytes()[..]);
+/// ```
+///
+/// [`write_into`]: ToBytes::write_into
+pub trait ToBytes<const N: usize> {
+    /// Writes the encoding into `buf`.
+    fn write_bytes(&self, buf: &mut [u8; N]);
+
+    /// Returns the encoding as an array.
+    fn to_byte_array(&self) -> [u8; N] {
+        let mut bytes = [0u8; N];
+        self.write_bytes(&mut bytes);
+        bytes
+    }
+
+    /// Writes the encoding to the start of `buf` and returns the rest of it.
+    ///
+    /// # Panics
+    ///
+    /// If `buf` is shorter than `N`.
+    fn write_into<'a>(&self, buf: &'a mut [u8]) -> &'a mut [u8] {
+        let (head, tail) = buf.split_at_mut(N);
+        self.write_bytes(head.try_into().expect("split at N"));
+        tail
+    }
+}
+
+impl<T: Normalized, S, Z> ToBytes<33> for Point<T, S, Z> {
+    fn write_bytes(&self, buf: &mut [u8; 33]) {
+        *buf = self.to_bytes()
+    }
+}
+
+impl<T: Normalized, S> ToBytes<65> for Point<T, S, NonZero> {
+    fn write_bytes(&self, buf: &mut [u8; 65]) {
+        *buf = self.to_bytes_uncompressed()
+    }
+}
+
+impl<S> ToBytes<32> for Point<EvenY, S, NonZero> {
+    fn write_bytes(&self, buf: &mut [u8; 32]) {
+        *buf = self.to_xonly_bytes()
+    }
+}
+
+impl<S, Z> ToBytes<32> for Scalar<S, Z> {
+    fn write_bytes(&self, buf: &mut [u8; 32]) {
+        *buf = self.to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn even_y_point_encodings() {
+        let (point, _) = Point::random(&mut rand::thread_rng()).into_point_with_even_y();
+        let xonly: [u8; 32] = point.to_byte_array();
+        let compressed: [u8; 33] = point.to_byte_array();
+        assert_eq!(xonly, point.to_xonly_bytes());
+        assert_eq!(compressed[0], 0x02);
+        assert_eq!(&compressed[1..], &xonly[..]);
+    }
+
+    #[test]
+    fn zero_point_compressed() {
+        let zero: [u8; 33] = Point::zero().to_byte_array();
+        assert_eq!(zero, [0u8; 33]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn write_into_short_buffer_panics() {
+        let mut buf = [0u8; 31];
+        Scalar::random(&mut rand::thread_rng()).write_into(&mut buf[..]);
+    }
+}
@@ -0,0 +1,156 @@
+//! Fiat-Shamir transcripts.
+//!
+//! A [`Transcript`] absorbs the public inputs of a protocol and then produces a challenge from
+//! them. Every protocol in this family of crates that needs a Fiat-Shamir challenge gets it through
+//! this trait so that the choice of how challenges are produced is made once when the protocol is
+//! instantiated.
+//!
+//! There are two implementations:
+//!
+//! - Any [`Digest`] with 32-byte output is a transcript. Labels are ignored and the inputs are
+//!   simply concatenated into the hash. When the hash has been [`Tagged`] this is exactly the
+//!   [BIP-340] tagged hash construction so this is what you want if you need to be compatible with
+//!   BIP-340.
+//! - [`MerlinTranscript`] (with the `merlin` feature) which wraps a [merlin] STROBE based
+//!   transcript. Every input is framed with its label and length so composing protocols is
+//!   domain separated for free.
+//!
+//! [`Digest`]: digest::Digest
+//! [`Tagged`]: crate::hash::Tagged
+//! [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+//! [merlin]: https://merlin.cool
+use crate::{
+    digest::{generic_array::typenum::U32, Digest},
+    marker::*,
+    Scalar,
+};
+
+/// A Fiat-Shamir transcript.
+///
+/// # Example
+///
+/// ```
+/// use secp256kfun::{hash::Tagged, marker::*, transcript::Transcript, G};
+/// let mut transcript = sha2::Sha256::default().tagged(b"my-protocol/challenge");
+/// transcript.append_message(b"X", &G.to_bytes());
+/// let challenge = transcript.challenge_scalar::<Public>(b"c");
+/// ```
+pub trait Transcript: Clone {
+    /// Absorbs `message` into the transcript under `label`.
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]);
+
+    /// Consumes the transcript and produces 32 challenge bytes.
+    fn challenge_bytes(self, label: &'static [u8]) -> [u8; 32];
+
+    /// Consumes the transcript and produces a challenge scalar by reducing the
+    /// [`challenge_bytes`] modulo the curve order.
+    ///
+    /// Since the transcript input is usually adversarially controlled the result is conservatively
+    /// marked as `Zero`.
+    ///
+    /// [`challenge_bytes`]: Self::challenge_bytes
+    fn challenge_scalar<S: Secrecy>(self, label: &'static [u8]) -> Scalar<S, Zero> {
+        Scalar::from_bytes_mod_order(self.challenge_bytes(label)).mark::<S>()
+    }
+}
+
+impl<H: Digest<OutputSize = U32> + Clone> Transcript for H {
+    fn append_message(&mut self, _label: &'static [u8], message: &[u8]) {
+        self.update(message)
+    }
+
+    fn challenge_bytes(self, _label: &'static [u8]) -> [u8; 32] {
+        self.finalize().into()
+    }
+}
+
+#[cfg(feature = "merlin")]
+mod merlin_transcript {
+    use super::Transcript;
+    use crate::hash::Tagged;
+
+    /// A [`Transcript`] backed by a [merlin] transcript.
+    ///
+    /// The [`Default`] instance uses `secp256kfun` as its application label. [`Tagged::tagged`]
+    /// appends the tag as a domain separator so it can be used anywhere a tagged hash is expected.
+    ///
+    /// [merlin]: https://merlin.cool
+    #[derive(Clone)]
+    pub struct MerlinTranscript(pub merlin::Transcript);
+
+    impl MerlinTranscript {
+        /// Creates a new transcript with an application label.
+        pub fn new(label: &'static [u8]) -> Self {
+            MerlinTranscript(merlin::Transcript::new(label))
+        }
+    }
+
+    impl Default for MerlinTranscript {
+        fn default() -> Self {
+            Self::new(b"secp256kfun")
+        }
+    }
+
+    impl core::fmt::Debug for MerlinTranscript {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            write!(f, "MerlinTranscript")
+        }
+    }
+
+    impl Tagged for MerlinTranscript {
+        fn tagged(&self, tag: &[u8]) -> Self {
+            let mut tagged = self.clone();
+            tagged.0.append_message(b"dom-sep", tag);
+            tagged
+        }
+    }
+
+    impl Transcript for MerlinTranscript {
+        fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+            self.0.append_message(label, message)
+        }
+
+        fn challenge_bytes(mut self, label: &'static [u8]) -> [u8; 32] {
+            let mut bytes = [0u8; 32];
+            self.0.challenge_bytes(label, &mut bytes);
+            bytes
+        }
+    }
+}
+
+#[cfg(feature = "merlin")]
+pub use merlin_transcript::MerlinTranscript;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hash::Tagged;
+    use sha2::Sha256;
+
+    #[test]
+    fn digest_transcript_is_tagged_hash() {
+        let mut transcript = Sha256::default().tagged(b"test");
+        transcript.append_message(b"a", b"hello");
+        transcript.append_message(b"b", b" world");
+        let expected: [u8; 32] = Sha256::default()
+            .tagged(b"test")
+            .chain_update(b"hello world")
+            .finalize()
+            .into();
+        assert_eq!(transcript.challenge_bytes(b"c"), expected);
+    }
+
+    #[cfg(feature = "merlin")]
+    #[test]
+    fn merlin_transcript_binds_labels() {
+        let mut t1 = MerlinTranscript::default().tagged(b"test");
+        let mut t2 = t1.clone();
+        let t3 = MerlinTranscript::default().tagged(b"other-test");
+        t1.append_message(b"a", b"hello");
+        t2.append_message(b"b", b"hello");
+        let c1 = t1.clone().challenge_bytes(b"c");
+        assert_eq!(c1, t1.challenge_bytes(b"c"));
+        assert_ne!(c1, t2.challenge_bytes(b"c"));
+        assert_ne!(c1, t3.challenge_bytes(b"c"));
+    }
+}
@@ -0,0 +1,7 @@
+// Only `NonZero` scalars can be inverted.
+use secp256kfun::{marker::*, Scalar};
+
+fn main() {
+    let zero = Scalar::<Secret, Zero>::zero();
+    let _ = zero.invert();
+}
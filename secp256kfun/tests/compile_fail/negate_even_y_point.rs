@@ -0,0 +1,12 @@
+// Negating a point with an even y-coordinate gives one with an odd y-coordinate.
+use secp256kfun::{marker::*, Point};
+
+fn main() {
+    let X = Point::<EvenY>::from_xonly_bytes([
+        0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62, 0x95, 0xce, 0x87, 0x0b,
+        0x07, 0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81, 0x5b, 0x16, 0xf8,
+        0x17, 0x98,
+    ])
+    .unwrap();
+    let _minus_X: Point<EvenY> = -X;
+}
@@ -0,0 +1,7 @@
+// `coerce` can only make a scalar more secret.
+use secp256kfun::{marker::*, s, Scalar};
+
+fn main() {
+    let x = s!(42);
+    let _x: Scalar<Public> = x.coerce();
+}
@@ -0,0 +1,8 @@
+// `x_eq_scalar` runs in variable time so it only takes `Public` scalars.
+use secp256kfun::{g, s, G};
+
+fn main() {
+    let x = s!(42);
+    let X = g!(x * G).normalize();
+    let _ = X.x_eq_scalar(&x);
+}
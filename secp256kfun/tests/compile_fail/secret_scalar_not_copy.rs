@@ -0,0 +1,10 @@
+// `Secret` scalars aren't `Copy` so they aren't duplicated in memory by accident.
+use secp256kfun::{s, Scalar};
+
+fn consume(_x: Scalar) {}
+
+fn main() {
+    let x = s!(42);
+    consume(x);
+    consume(x);
+}
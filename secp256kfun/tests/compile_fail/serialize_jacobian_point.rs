@@ -0,0 +1,7 @@
+// Points must be normalized before they can be serialized.
+use secp256kfun::{g, s, G};
+
+fn main() {
+    let x = s!(42);
+    let _ = g!(x * G).to_bytes();
+}
@@ -0,0 +1,7 @@
+// The difference of two points may be zero so it can't be used where a `NonZero` point is needed.
+use secp256kfun::{g, s, Point, G};
+
+fn main() {
+    let x = s!(42);
+    let _X: Point = g!(x * G - x * G).normalize();
+}
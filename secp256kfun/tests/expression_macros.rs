@@ -180,3 +180,28 @@ fn g_expressions_give_correct_answers() {
         op::double_mul(&x, &has_point.has, &y, &has_has_point.has_has.has)
     );
 }
+
+#[test]
+fn dot_products() {
+    let x = s!(3);
+    let y = s!(5);
+    let z = s!(7);
+    let A = Point::random(&mut rand::thread_rng());
+    let B = Point::random(&mut rand::thread_rng());
+    let scalars = [x.clone(), y.clone()];
+    let points = [A, B];
+
+    assert_eq!(s!(dot(scalars, scalars)), s!(x * x + y * y));
+    assert_eq!(
+        s!(z + dot(scalars, [z.clone(), z.clone()])),
+        s!(z + x * z + y * z)
+    );
+    assert_eq!(s!(dot(scalars, [z.clone()])), s!(x * z));
+
+    assert_eq!(g!(dot(scalars, points)), g!(x * A + y * B));
+    assert_eq!(g!(dot(scalars, points) - B), g!(x * A + y * B - B));
+    assert_eq!(g!(A + dot(scalars, points)), g!(A + x * A + y * B));
+    assert_eq!(g!(z * dot(scalars, points)), g!((z * x) * A + (z * y) * B));
+    assert_eq!(g!((dot(scalars, scalars)) * A), g!((x * x + y * y) * A));
+    assert!(g!(dot(scalars, [] as [Point; 0])).is_zero());
+}
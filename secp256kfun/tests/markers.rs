@@ -0,0 +1,11 @@
+//! Checks that misusing the markers is caught at compile time.
+//!
+//! Each file in `tests/compile_fail` must fail to compile with the error in the `.stderr` file next
+//! to it. If a change to the compiler alters the error messages regenerate them with
+//! `TRYBUILD=overwrite cargo test --test markers` and check the diff.
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn marker_misuse_fails_to_compile() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/compile_fail/*.rs");
+}
@@ -210,6 +210,27 @@ mod test {
                 );
             }
         }
+
+        #[test]
+        #[cfg(feature = "merlin")]
+        fn dleq_with_merlin_transcript() {
+            use crate::{
+                secp256k1::fun::{g, marker::*, transcript::MerlinTranscript, G},
+                FunTranscript,
+            };
+            type DLEQ = Eq<secp256k1::DLG<U32>, secp256k1::DL<U32>>;
+            let x = Scalar::random(&mut rand::thread_rng());
+            let H = Point::random(&mut rand::thread_rng());
+            let statement = (g!(x * G).normalize(), (H, g!(x * H).normalize()));
+            let proof_system =
+                FiatShamir::<DLEQ, FunTranscript<MerlinTranscript, ChaCha20Rng>>::default();
+            let proof = proof_system.prove(&x, &statement, Some(&mut rand::thread_rng()));
+            assert!(proof_system.verify(&statement, &proof));
+
+            let hash_proof_system =
+                FiatShamir::<DLEQ, HashTranscript<Sha256, ChaCha20Rng>>::default();
+            assert!(!hash_proof_system.verify(&statement, &proof));
+        }
     }
 
     #[cfg(feature = "ed25519")]
@@ -15,6 +15,31 @@
 //!
 //! # Example
 //!
+//! ```
+//! use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar as ScalarQ};
+//! use rand_chacha::ChaCha20Rng;
+//! use secp256kfun::Point as PointP;
+//! use sha2::Sha256;
+//! use sigma_fun::{ext::dl_secp256k1_ed25519_eq::CrossCurveDLEQ, HashTranscript};
+//!
+//! // The second Pedersen commitment base on each curve. Nobody may know their discrete logarithm
+//! // with respect to the standard base points so in practice you would hash to the curve to get
+//! // them.
+//! let HP = PointP::random(&mut rand::thread_rng());
+//! let HQ = &ScalarQ::random(&mut rand::thread_rng()) * &ED25519_BASEPOINT_TABLE;
+//! let proof_system = CrossCurveDLEQ::<HashTranscript<Sha256, ChaCha20Rng>>::new(HP, HQ);
+//!
+//! // The secret must fit in 252 bits so both curves interpret it as the same integer
+//! let mut secret_bytes = [0u8; 32];
+//! rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut secret_bytes);
+//! secret_bytes[31] &= 0x0f;
+//! let secret = ScalarQ::from_bits(secret_bytes);
+//!
+//! let (proof, (public_key_secp256k1, public_key_ed25519)) =
+//!     proof_system.prove(&secret, &mut rand::thread_rng());
+//! assert!(proof_system.verify(&proof, (public_key_secp256k1, public_key_ed25519)));
+//! ```
+//!
 //! [MRL-0010]: https://web.getmonero.org/resources/research-lab/pubs/MRL-0010.pdf
 use crate::{
     ed25519,
@@ -119,7 +144,7 @@ impl<T: Transcript<CoreProof> + Default> CrossCurveDLEQ<T> {
     ///
     /// # Panics
     ///
-    /// - If the secret is larger than 2^253 -1
+    /// - If the secret is larger than 2^252 - 1
     /// - If the secret is 0
     pub fn prove(
         &self,
@@ -306,6 +331,18 @@ mod test {
         let _ = proof_system.prove(&high_scalar, &mut rand::thread_rng());
     }
 
+    #[test]
+    fn largest_252_bit_scalar() {
+        let mut bytes = [0xff; 32];
+        bytes[31] = 0x0f;
+        let secret = ScalarQ::from_bits(bytes);
+        let HP = PointP::random(&mut rand::thread_rng());
+        let HQ = &ScalarQ::random(&mut rand::thread_rng()) * &ED25519_BASEPOINT_TABLE;
+        let proof_system = CrossCurveDLEQ::<Transcript>::new(HP, HQ);
+        let (proof, claim) = proof_system.prove(&secret, &mut rand::thread_rng());
+        assert!(proof_system.verify(&proof, claim));
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(3))]
         #[test]
@@ -5,13 +5,19 @@ use crate::{
     rand_core::{CryptoRng, RngCore},
     Sigma,
 };
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use core::marker::PhantomData;
+#[cfg(feature = "alloc")]
+use digest::Digest;
 use digest::Update;
 use generic_array::{
     typenum::{self, type_operators::IsLessOrEqual, U32},
     ArrayLength, GenericArray,
 };
 pub use secp256kfun as fun;
+#[cfg(feature = "alloc")]
+use secp256kfun::op;
 use secp256kfun::{g, marker::*, s, Point, Scalar};
 
 /// Proves knowledge of `x` such that `A = x * B` for some `A` and `B` included in the statement.
@@ -177,6 +183,165 @@ where
     }
 }
 
+/// Proves that `A_i = x * B_i` for every `(B_i, A_i)` in the statement with one combined
+/// statement.
+///
+/// This proves the same thing as [`EqAll`] of [`DL`] but the statements are first combined into
+/// `(Σ ρ_i * B_i, Σ ρ_i * A_i)` with weights `ρ_i` derived by hashing all of them with `H`. The
+/// proof is then a single [`DL`] proof on the combined statement so there is one announcement no
+/// matter how many statements there are, verifying is one multi-scalar multiplication instead of
+/// one multiplication per statement and the number of statements doesn't have to be known at
+/// compile time. If any of the statements has a different witness the combined statement is false
+/// except with negligible probability.
+///
+/// This is how an oracle that attests with `s_i = k_i + e_i * x` can show that all the `e_i * X`
+/// it committed to use the same `x` with one proof instead of one [`Eq`] proof per message.
+///
+/// # Example
+///
+/// ```
+/// use rand_chacha::ChaCha20Rng;
+/// use sha2::Sha256;
+/// use sigma_fun::{
+///     secp256k1::{
+///         fun::{g, marker::*, Point, Scalar, G},
+///         DLEQBatch,
+///     },
+///     typenum::U32,
+///     FiatShamir, HashTranscript,
+/// };
+/// let x = Scalar::random(&mut rand::thread_rng());
+/// let mut statement = vec![((*G).mark::<Normal>(), g!(x * G).normalize())];
+/// for _ in 0..10 {
+///     let H = Point::random(&mut rand::thread_rng());
+///     statement.push((H, g!(x * H).normalize()));
+/// }
+/// let proof_system =
+///     FiatShamir::<DLEQBatch<U32, Sha256>, HashTranscript<Sha256, ChaCha20Rng>>::default();
+/// let proof = proof_system.prove(&x, &statement, Some(&mut rand::thread_rng()));
+/// assert!(proof_system.verify(&statement, &proof));
+/// ```
+///
+/// Proving panics if the statement is empty.
+///
+/// [`EqAll`]: crate::EqAll
+/// [`Eq`]: crate::Eq
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default)]
+pub struct DLEQBatch<L, H> {
+    dl: DL<L>,
+    hash: PhantomData<H>,
+}
+
+#[cfg(feature = "alloc")]
+impl<L, H: Digest<OutputSize = U32> + Clone + Default> DLEQBatch<L, H> {
+    /// Combines the statements into one with the hashed weights.
+    ///
+    /// Returns `None` if there are no statements or the combined base is zero.
+    pub fn combine(&self, statement: &[(Point, Point)]) -> Option<(Point, Point)> {
+        if statement.is_empty() {
+            return None;
+        }
+        let mut seed = H::default();
+        Digest::update(&mut seed, b"sigma_fun/DLEQ-batch");
+        for (base, image) in statement {
+            Digest::update(&mut seed, base.to_bytes());
+            Digest::update(&mut seed, image.to_bytes());
+        }
+        let weights = (0..statement.len() as u32)
+            .map(|i| {
+                let weight = seed.clone().chain_update(i.to_be_bytes()).finalize();
+                let mut bytes = [0u8; 32];
+                bytes.copy_from_slice(weight.as_slice());
+                Scalar::from_bytes_mod_order(bytes).mark::<Public>()
+            })
+            .collect::<Vec<_>>();
+        let base = op::lincomb(&weights, statement.iter().map(|(base, _)| base));
+        let image = op::lincomb(&weights, statement.iter().map(|(_, image)| image));
+        Some((base.normalize().non_zero()?, image.normalize().non_zero()?))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<L: ArrayLength<u8>, H: Digest<OutputSize = U32> + Clone + Default> Sigma for DLEQBatch<L, H>
+where
+    L: IsLessOrEqual<U32>,
+    <L as IsLessOrEqual<U32>>::Output: typenum::marker_traits::NonZero,
+{
+    type Witness = Scalar;
+    type Statement = Vec<(Point, Point)>;
+    type AnnounceSecret = Scalar;
+    type Announcement = Point;
+    type Response = Scalar<Public, Zero>;
+    type ChallengeLength = L;
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        _statement: &Self::Statement,
+        announce_secret: Self::AnnounceSecret,
+        _announce: &Self::Announcement,
+        challenge: &GenericArray<u8, Self::ChallengeLength>,
+    ) -> Self::Response {
+        let challenge = normalize_challenge(challenge);
+        s!(announce_secret + challenge * witness).mark::<Public>()
+    }
+
+    fn announce(
+        &self,
+        statement: &Self::Statement,
+        announce_secret: &Self::AnnounceSecret,
+    ) -> Self::Announcement {
+        let combined = self
+            .combine(statement)
+            .expect("the statement must have at least one pair");
+        self.dl.announce(&combined, announce_secret)
+    }
+
+    fn sample_response<Rng: CryptoRng + RngCore>(&self, rng: &mut Rng) -> Self::Response {
+        self.dl.sample_response(rng)
+    }
+
+    fn implied_announcement(
+        &self,
+        statement: &Self::Statement,
+        challenge: &GenericArray<u8, Self::ChallengeLength>,
+        response: &Self::Response,
+    ) -> Option<Self::Announcement> {
+        let combined = self.combine(statement)?;
+        self.dl.implied_announcement(&combined, challenge, response)
+    }
+
+    fn hash_statement<U: Update>(&self, hash: &mut U, statement: &Self::Statement) {
+        for pair in statement {
+            self.dl.hash_statement(hash, pair)
+        }
+    }
+
+    fn hash_announcement<U: Update>(&self, hash: &mut U, announcement: &Self::Announcement) {
+        self.dl.hash_announcement(hash, announcement)
+    }
+
+    fn hash_witness<U: Update>(&self, hash: &mut U, witness: &Self::Witness) {
+        self.dl.hash_witness(hash, witness)
+    }
+
+    fn gen_announce_secret<Rng: CryptoRng + RngCore>(
+        &self,
+        witness: &Self::Witness,
+        rng: &mut Rng,
+    ) -> Self::AnnounceSecret {
+        self.dl.gen_announce_secret(witness, rng)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<L, H> crate::Writable for DLEQBatch<L, H> {
+    fn write_to<W: core::fmt::Write>(&self, w: &mut W) -> core::fmt::Result {
+        write!(w, "DLEQ-batch(secp256k1)")
+    }
+}
+
 fn normalize_challenge<L: ArrayLength<u8>>(
     challenge: &GenericArray<u8, L>,
 ) -> Scalar<Public, Zero> {
@@ -195,3 +360,72 @@ impl<L> crate::Writable for DLG<L> {
 
 crate::impl_display!(DL<L>);
 crate::impl_display!(DLG<L>);
+#[cfg(feature = "alloc")]
+crate::impl_display!(DLEQBatch<L, H>);
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::*;
+    use crate::{EqAll, FiatShamir, HashTranscript};
+    use rand_chacha::ChaCha20Rng;
+    use secp256kfun::G;
+    use sha2::Sha256;
+
+    type Batch = FiatShamir<DLEQBatch<U32, Sha256>, HashTranscript<Sha256, ChaCha20Rng>>;
+
+    fn statement(x: &Scalar, n: usize) -> Vec<(Point, Point)> {
+        let mut statement = vec![((*G).mark::<Normal>(), g!(x * G).normalize())];
+        for _ in 1..n {
+            let H = Point::random(&mut rand::thread_rng());
+            statement.push((H, g!(x * H).normalize()));
+        }
+        statement
+    }
+
+    #[test]
+    fn dleq_batch() {
+        let proof_system = Batch::default();
+        let x = Scalar::random(&mut rand::thread_rng());
+        for n in [1, 2, 20] {
+            let statement = statement(&x, n);
+            let proof = proof_system.prove(&x, &statement, Some(&mut rand::thread_rng()));
+            assert!(proof_system.verify(&statement, &proof));
+
+            // one statement with a different witness
+            let mut bogus_statement = statement.clone();
+            let last = bogus_statement.len() - 1;
+            bogus_statement[last].1 = Point::random(&mut rand::thread_rng());
+            assert!(!proof_system.verify(&bogus_statement, &proof));
+            let bogus_proof =
+                proof_system.prove(&x, &bogus_statement, Some(&mut rand::thread_rng()));
+            assert!(!proof_system.verify(&bogus_statement, &bogus_proof));
+
+            // the proof doesn't verify for a subset of the statements
+            if n > 1 {
+                assert!(!proof_system.verify(&statement[..n - 1].to_vec(), &proof));
+            }
+        }
+        let proof = proof_system.prove(&x, &statement(&x, 2), Some(&mut rand::thread_rng()));
+        assert!(!proof_system.verify(&vec![], &proof));
+    }
+
+    #[test]
+    fn dleq_batch_name_and_size() {
+        let batch = DLEQBatch::<U32, Sha256>::default();
+        assert_eq!(format!("{}", batch), "DLEQ-batch(secp256k1)");
+
+        // the proof is the same size as the EqAll proof for a single statement
+        let x = Scalar::random(&mut rand::thread_rng());
+        let statement = statement(&x, 5);
+        let proof = Batch::default().prove(&x, &statement, Some(&mut rand::thread_rng()));
+        let eq_all =
+            FiatShamir::<EqAll<DL<U32>, typenum::U5>, HashTranscript<Sha256, ChaCha20Rng>>::default(
+            );
+        let eq_all_proof = eq_all.prove(&x, &statement, Some(&mut rand::thread_rng()));
+        assert!(eq_all.verify(&statement, &eq_all_proof));
+        assert_eq!(
+            proof.challenge.len() + proof.response.to_bytes().len(),
+            eq_all_proof.challenge.len() + eq_all_proof.response.to_bytes().len()
+        );
+    }
+}
@@ -152,3 +152,125 @@ where
         R::from_seed(secret_seed.into())
     }
 }
+
+/// A transcript that gets its challenges from a [`secp256kfun::transcript::Transcript`].
+///
+/// This lets sigma protocols share the same challenge machinery as the rest of the library e.g.
+/// with the `merlin` feature enabled `FunTranscript<MerlinTranscript, ChaCha20Rng>` gives you a
+/// [merlin] backed proof system. Each input is appended to the inner transcript under its own
+/// label. `R` may be set to `()` but then it won't implement [`ProverTranscript`].
+///
+/// Note that with a plain hash as the inner transcript the labels are ignored. In that case you
+/// should prefer [`HashTranscript`] which tags the hash with the name of the sigma protocol.
+///
+/// [merlin]: https://merlin.cool
+#[cfg(feature = "secp256k1")]
+#[derive(Clone, Debug)]
+pub struct FunTranscript<T, R = ()> {
+    transcript: T,
+    rng: PhantomData<R>,
+}
+
+#[cfg(feature = "secp256k1")]
+impl<T, R> FunTranscript<T, R> {
+    /// Creates a sigma protocol transcript from an inner transcript.
+    pub fn new(transcript: T) -> Self {
+        FunTranscript {
+            transcript,
+            rng: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl<T: Default, R> Default for FunTranscript<T, R> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+/// Appends everything written to it to a transcript under a fixed label.
+#[cfg(feature = "secp256k1")]
+struct Append<'a, T> {
+    transcript: &'a mut T,
+    label: &'static [u8],
+}
+
+#[cfg(feature = "secp256k1")]
+impl<'a, T: secp256kfun::transcript::Transcript> Update for Append<'a, T> {
+    fn update(&mut self, data: &[u8]) {
+        self.transcript.append_message(self.label, data)
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl<S, T, R> Transcript<S> for FunTranscript<T, R>
+where
+    S: Sigma,
+    S::ChallengeLength: IsLessOrEqual<U32>,
+    <S::ChallengeLength as IsLessOrEqual<U32>>::Output: NonZero,
+    T: secp256kfun::transcript::Transcript,
+    R: Clone,
+{
+    fn add_name<N: Writable + ?Sized>(&mut self, name: &N) {
+        let mut append = WriteHash(Append {
+            transcript: &mut self.transcript,
+            label: b"sigma-name",
+        });
+        name.write_to(&mut append)
+            .expect("writing to transcript won't fail");
+    }
+
+    fn add_statement(&mut self, sigma: &S, statement: &S::Statement) {
+        let mut append = Append {
+            transcript: &mut self.transcript,
+            label: b"statement",
+        };
+        sigma.hash_statement(&mut append, statement);
+    }
+
+    fn get_challenge(
+        mut self,
+        sigma: &S,
+        announcement: &S::Announcement,
+    ) -> GenericArray<u8, S::ChallengeLength> {
+        let mut append = Append {
+            transcript: &mut self.transcript,
+            label: b"announcement",
+        };
+        sigma.hash_announcement(&mut append, announcement);
+        let challenge_bytes = self.transcript.challenge_bytes(b"challenge");
+        GenericArray::clone_from_slice(&challenge_bytes[..S::ChallengeLength::to_usize()])
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl<S, T, R> ProverTranscript<S> for FunTranscript<T, R>
+where
+    S: Sigma,
+    T: secp256kfun::transcript::Transcript,
+    R: SeedableRng + CryptoRng + RngCore + Clone,
+    R::Seed: From<[u8; 32]>,
+{
+    type Rng = R;
+
+    fn gen_rng<SysRng: CryptoRng + RngCore>(
+        &self,
+        sigma: &S,
+        witness: &S::Witness,
+        in_rng: Option<&mut SysRng>,
+    ) -> Self::Rng {
+        let mut transcript = self.transcript.clone();
+        let mut append = Append {
+            transcript: &mut transcript,
+            label: b"witness",
+        };
+        sigma.hash_witness(&mut append, witness);
+        if let Some(rng) = in_rng {
+            let mut randomness = [0u8; 32];
+            rng.fill_bytes(&mut randomness);
+            transcript.append_message(b"randomness", &randomness);
+        }
+        R::from_seed(transcript.challenge_bytes(b"rng-seed").into())
+    }
+}